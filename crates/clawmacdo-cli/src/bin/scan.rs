@@ -1,73 +1,133 @@
 use clap::Parser;
-use std::process::Command;
+use clawmacdo_core::security_scan::{self, ScanReport, Severity};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitCode, Stdio};
 
 #[derive(Parser, Debug)]
-#[command(name = "scan", about = "Run security scans", version)]
+#[command(
+    name = "scan",
+    about = "Security scan of an OpenClaw host, over SSH or locally",
+    version
+)]
 struct Args {
-    /// Target to scan: ubuntu|macos|windows|config|all
-    #[arg(long, default_value_t = String::from("ubuntu"))]
-    target: String,
-    /// Output file path (for single-target scans)
+    /// Host to scan over SSH (omit with --local)
+    #[arg(long, required_unless_present = "local", conflicts_with = "local")]
+    host: Option<String>,
+    /// SSH private key for --host
+    #[arg(long, requires = "host", required_unless_present = "local")]
+    key: Option<PathBuf>,
+    /// SSH user for --host; non-root users need passwordless sudo
+    #[arg(long, default_value = "root")]
+    user: String,
+    /// Scan this machine (run as root for complete results)
     #[arg(long)]
-    out: Option<String>,
+    local: bool,
+    /// Report format
+    #[arg(long, default_value = "json", value_parser = ["json", "sarif", "text"])]
+    format: String,
+    /// Output file path (stdout when omitted)
+    #[arg(long)]
+    out: Option<PathBuf>,
+    /// Exit with status 2 when the risk level reaches this severity
+    #[arg(long, value_parser = ["low", "medium", "high", "critical"])]
+    fail_on: Option<String>,
 }
 
-fn main() {
-    let args = Args::parse();
-    let out = args
-        .out
-        .unwrap_or_else(|| format!("/tmp/openclaw_scan_{}.json", chrono::Utc::now().timestamp()));
-    let format = std::env::var("FORMAT").unwrap_or_else(|_| String::from("json"));
-    match args.target.as_str() {
-        "ubuntu" => {
-            let status = Command::new("/bin/bash")
-                .arg("scripts/ubuntu_scan.sh")
-                .arg(&out)
-                .status()
-                .expect("failed");
-            println!("ubuntu scan exit: {status}");
-        }
-        "macos" => {
-            let status = Command::new("/bin/bash")
-                .arg("scripts/macos_scan.sh")
-                .arg(&out)
-                .status()
-                .expect("failed");
-            println!("macos scan exit: {status}");
+fn collect_local() -> Result<String, String> {
+    let mut child = Command::new("/bin/bash")
+        .arg("-s")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("failed to start bash: {e}"))?;
+    child
+        .stdin
+        .take()
+        .expect("piped stdin")
+        .write_all(security_scan::COLLECT_SCRIPT.as_bytes())
+        .map_err(|e| format!("failed to write the collector: {e}"))?;
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("collector failed: {e}"))?;
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn collect_remote(host: &str, key: &Path, user: &str) -> Result<String, String> {
+    let shell = if user == "root" {
+        "/bin/bash -s"
+    } else {
+        "sudo /bin/bash -s"
+    };
+    clawmacdo_ssh::exec_with_input_as(
+        host,
+        key,
+        shell,
+        security_scan::COLLECT_SCRIPT.as_bytes(),
+        user,
+    )
+    .map_err(|e| e.to_string())
+}
+
+fn render(report: &ScanReport, format: &str) -> String {
+    match format {
+        "sarif" => {
+            serde_json::to_string_pretty(&security_scan::to_sarif(report))
+                .expect("SARIF serializes")
+                + "\n"
         }
-        "windows" => {
-            let status = Command::new("/bin/bash")
-                .arg("scripts/windows_scan_wrapper.sh")
-                .arg(&out)
-                .status()
-                .expect("failed");
-            println!("windows scan exit: {status}");
+        "text" => security_scan::to_text(report),
+        _ => serde_json::to_string_pretty(report).expect("report serializes") + "\n",
+    }
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let (fallback_host, collected) = match &args.host {
+        Some(host) => {
+            let key = args
+                .key
+                .as_deref()
+                .expect("clap requires --key with --host");
+            (host.clone(), collect_remote(host, key, &args.user))
         }
-        "config" => {
-            let status = Command::new("/bin/bash")
-                .arg("scripts/openclaw_config_scan.sh")
-                .arg(&out)
-                .status()
-                .expect("failed");
-            println!("config scan exit: {status}");
+        None => ("localhost".to_string(), collect_local()),
+    };
+    let output = match collected {
+        Ok(output) => output,
+        Err(err) => {
+            eprintln!("scan failed: {err}");
+            return ExitCode::FAILURE;
         }
-        "all" => {
-            let status = Command::new("/bin/bash")
-                .arg("scripts/run_all_scans.sh")
-                .status()
-                .expect("failed");
-            println!("run_all_scans exit: {status}");
+    };
+    let report = security_scan::evaluate(&fallback_host, &output);
+    let rendered = render(&report, &args.format);
+
+    match &args.out {
+        Some(path) => {
+            if let Err(err) = std::fs::write(path, rendered) {
+                eprintln!("failed to write {}: {err}", path.display());
+                return ExitCode::FAILURE;
+            }
+            eprintln!(
+                "{}: risk {}/100 ({}), report written to {}",
+                report.host,
+                report.risk.score,
+                report.risk.level.as_str(),
+                path.display()
+            );
         }
-        _ => eprintln!("unknown target: {}", args.target),
+        None => print!("{rendered}"),
     }
-    if format != "json" && args.target != "all" {
-        let fmt_status = Command::new("/bin/bash")
-            .arg("scripts/format_outputs.sh")
-            .arg(&out)
-            .arg(&format)
-            .arg(&out)
-            .status()
-            .expect("format failed");
-        println!("format exit: {fmt_status}");
+
+    let threshold = args.fail_on.as_deref().map(|s| {
+        s.parse::<Severity>()
+            .expect("clap restricts --fail-on to known severities")
+    });
+    match threshold {
+        Some(threshold) if report.risk.failed > 0 && report.risk.level >= threshold => {
+            ExitCode::from(2)
+        }
+        _ => ExitCode::SUCCESS,
     }
 }
@@ -1,6 +1,12 @@
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
 use chrono::{TimeZone, Utc};
+use console::style;
 use reqwest::Client;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::io::{BufRead, Read, Write};
+use std::path::{Path, PathBuf};
 
 /// List ARK inference endpoints for the given BytePlus account.
 pub async fn list_endpoints(access_key: &str, secret_key: &str) -> Result<()> {
@@ -37,12 +43,7 @@ pub async fn list_endpoints(access_key: &str, secret_key: &str) -> Result<()> {
             .and_then(|v| v.as_str())
             .unwrap_or("-");
         let status = ep.get("Status").and_then(|v| v.as_str()).unwrap_or("-");
-        let model = ep
-            .get("ModelReference")
-            .and_then(|v| v.get("ModelId").or_else(|| v.get("FoundationModel")))
-            .and_then(|v| v.as_str())
-            .or_else(|| ep.get("Model").and_then(|v| v.as_str()))
-            .unwrap_or("-");
+        let (model, _) = endpoint_model(ep);
 
         println!("{id:<30} {name:<20} {status:<10} {model}");
     }
@@ -107,76 +108,529 @@ pub async fn get_api_key(
     Ok(())
 }
 
+/// The model an endpoint serves, as `(name, version)`. Endpoints report either
+/// a foundation model object or a bare model ID.
+pub(crate) fn endpoint_model(ep: &Value) -> (String, String) {
+    let reference = &ep["ModelReference"];
+    let foundation = &reference["FoundationModel"];
+    let name = foundation["Name"]
+        .as_str()
+        .or_else(|| foundation.as_str())
+        .or_else(|| reference["ModelId"].as_str())
+        .or_else(|| reference["CustomModelId"].as_str())
+        .or_else(|| ep["Model"].as_str())
+        .unwrap_or("-");
+    let version = foundation["ModelVersion"].as_str().unwrap_or("-");
+    (name.to_string(), version.to_string())
+}
+
+fn endpoint_field<'a>(ep: &'a Value, names: &[&str]) -> &'a str {
+    names
+        .iter()
+        .find_map(|name| ep[*name].as_str())
+        .unwrap_or("-")
+}
+
+/// One endpoint serving a model.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct ModelEndpoint {
+    pub id: String,
+    pub name: String,
+    pub status: String,
+}
+
+/// A model version and the endpoints serving it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct ArkModel {
+    pub model: String,
+    pub version: String,
+    pub endpoints: Vec<ModelEndpoint>,
+}
+
+/// Group raw `ListEndpoints` items by the model they serve.
+pub(crate) fn join_models(endpoints: &[Value]) -> Vec<ArkModel> {
+    let mut grouped: BTreeMap<(String, String), Vec<ModelEndpoint>> = BTreeMap::new();
+    for ep in endpoints {
+        grouped
+            .entry(endpoint_model(ep))
+            .or_default()
+            .push(ModelEndpoint {
+                id: endpoint_field(ep, &["Id", "EndpointId"]).to_string(),
+                name: endpoint_field(ep, &["Name", "EndpointName"]).to_string(),
+                status: endpoint_field(ep, &["Status"]).to_string(),
+            });
+    }
+    grouped
+        .into_iter()
+        .map(|((model, version), endpoints)| ArkModel {
+            model,
+            version,
+            endpoints,
+        })
+        .collect()
+}
+
+/// List the models behind the account's ARK endpoints.
+pub async fn models(access_key: &str, secret_key: &str, json: bool) -> Result<()> {
+    if access_key.is_empty() || secret_key.is_empty() {
+        bail!("BytePlus access key and secret key are required.\nSet BYTEPLUS_ACCESS_KEY and BYTEPLUS_SECRET_KEY environment variables or pass --access-key and --secret-key.");
+    }
+    let client =
+        clawmacdo_cloud::byteplus::BytePlusClient::new(access_key, secret_key, "ap-southeast-1")?;
+    let models = join_models(&client.list_endpoints().await?);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&models)?);
+        return Ok(());
+    }
+    if models.is_empty() {
+        println!("No endpoints found. Create one in the BytePlus ARK console first.");
+        return Ok(());
+    }
+    println!("{:<36} {:<12} ENDPOINTS", "MODEL", "VERSION");
+    println!("{}", "-".repeat(90));
+    for model in &models {
+        let endpoints: Vec<String> = model
+            .endpoints
+            .iter()
+            .map(|ep| format!("{} ({}, {})", ep.id, ep.name, ep.status))
+            .collect();
+        println!(
+            "{:<36} {:<12} {}",
+            model.model,
+            model.version,
+            endpoints.join(", ")
+        );
+    }
+    Ok(())
+}
+
 const ARK_BASE_URL: &str = "https://ark.ap-southeast.bytepluses.com/api/v3";
 
-/// Send a chat completion prompt to a BytePlus ARK endpoint.
-pub async fn chat(api_key: &str, endpoint_id: &str, prompt: &str) -> Result<()> {
-    if api_key.is_empty() {
-        bail!("ARK API key is required. Generate one with: clawmacdo ark-api-key --resource-ids <endpoint-id>");
+/// Token counts ARK reports for a completion.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub(crate) struct Usage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+}
+
+impl Usage {
+    fn from_value(usage: &Value) -> Option<Self> {
+        let prompt_tokens = usage["prompt_tokens"].as_u64()?;
+        let completion_tokens = usage["completion_tokens"].as_u64().unwrap_or(0);
+        Some(Self {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: usage["total_tokens"]
+                .as_u64()
+                .unwrap_or(prompt_tokens + completion_tokens),
+        })
     }
-    if endpoint_id.is_empty() {
-        bail!("Endpoint ID is required. Use: clawmacdo ark-api-key --list");
+
+    fn add(&mut self, other: Usage) {
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+        self.total_tokens += other.total_tokens;
     }
-    if prompt.is_empty() {
-        bail!("Prompt cannot be empty.");
+}
+
+impl std::fmt::Display for Usage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "tokens: {} prompt + {} completion = {} total",
+            self.prompt_tokens, self.completion_tokens, self.total_tokens
+        )
     }
+}
 
-    let url = format!("{ARK_BASE_URL}/chat/completions");
+/// One parsed line of a streamed completion.
+#[derive(Debug, PartialEq)]
+enum StreamLine {
+    Chunk {
+        delta: Option<String>,
+        usage: Option<Usage>,
+    },
+    Done,
+}
 
-    let body = serde_json::json!({
-        "model": endpoint_id,
-        "messages": [
-            {"role": "user", "content": prompt}
-        ]
-    });
+fn parse_stream_line(line: &str) -> Result<Option<StreamLine>> {
+    let Some(data) = line.strip_prefix("data:").map(str::trim) else {
+        return Ok(None);
+    };
+    if data == "[DONE]" {
+        return Ok(Some(StreamLine::Done));
+    }
+    let chunk: Value = serde_json::from_str(data).context("Malformed ARK stream chunk")?;
+    if let Some(message) = chunk["error"]["message"].as_str() {
+        bail!("ARK API error: {message}");
+    }
+    Ok(Some(StreamLine::Chunk {
+        delta: chunk["choices"][0]["delta"]["content"]
+            .as_str()
+            .filter(|s| !s.is_empty())
+            .map(str::to_string),
+        usage: Usage::from_value(&chunk["usage"]),
+    }))
+}
 
-    let client = Client::new();
-    let resp = client
-        .post(&url)
-        .header("Authorization", format!("Bearer {api_key}"))
-        .header("Content-Type", "application/json")
-        .json(&body)
-        .send()
-        .await?;
+/// A finished reply.
+#[derive(Debug, Default)]
+pub(crate) struct Reply {
+    pub content: String,
+    pub usage: Option<Usage>,
+}
+
+/// Chat completions against one ARK endpoint.
+pub(crate) struct ArkClient {
+    base_url: String,
+    api_key: String,
+    endpoint_id: String,
+    client: Client,
+}
 
-    let status = resp.status();
-    let resp_body: serde_json::Value = resp.json().await?;
-
-    if !status.is_success() {
-        let err_msg = resp_body
-            .get("error")
-            .and_then(|e| e.get("message"))
-            .and_then(|m| m.as_str())
-            .unwrap_or("Unknown error");
-        bail!("ARK API error ({status}): {err_msg}");
-    }
-
-    let content = resp_body
-        .get("choices")
-        .and_then(|c| c.as_array())
-        .and_then(|arr| arr.first())
-        .and_then(|c| c.get("message"))
-        .and_then(|m| m.get("content"))
-        .and_then(|v| v.as_str())
-        .unwrap_or("<no response>");
-
-    println!("{content}");
-
-    // Print usage stats if available
-    if let Some(usage) = resp_body.get("usage") {
-        let prompt_tokens = usage
-            .get("prompt_tokens")
-            .and_then(|v| v.as_u64())
-            .unwrap_or(0);
-        let completion_tokens = usage
-            .get("completion_tokens")
-            .and_then(|v| v.as_u64())
-            .unwrap_or(0);
-        eprintln!(
-            "\n[tokens: {prompt_tokens} prompt + {completion_tokens} completion = {} total]",
-            prompt_tokens + completion_tokens
+impl ArkClient {
+    pub(crate) fn new(api_key: &str, endpoint_id: &str) -> Result<Self> {
+        if api_key.trim().is_empty() {
+            bail!("ARK API key is required. Generate one with: clawmacdo ark-api-key --resource-ids <endpoint-id>");
+        }
+        if endpoint_id.trim().is_empty() {
+            bail!("Endpoint ID is required. Use: clawmacdo ark-models");
+        }
+        Ok(Self {
+            base_url: ARK_BASE_URL.to_string(),
+            api_key: api_key.trim().to_string(),
+            endpoint_id: endpoint_id.trim().to_string(),
+            client: Client::new(),
+        })
+    }
+
+    /// Send `messages` and return the reply. With `stream`, each piece of
+    /// the reply is passed to `on_delta` as it arrives; otherwise the whole
+    /// reply is passed once.
+    pub(crate) async fn complete(
+        &self,
+        messages: &[Value],
+        stream: bool,
+        on_delta: &mut (dyn FnMut(&str) + Send),
+    ) -> Result<Reply> {
+        let mut body = json!({
+            "model": self.endpoint_id,
+            "messages": messages,
+            "stream": stream,
+        });
+        if stream {
+            body["stream_options"] = json!({ "include_usage": true });
+        }
+        let resp = self
+            .client
+            .post(format!("{}/chat/completions", self.base_url))
+            .bearer_auth(&self.api_key)
+            .json(&body)
+            .send()
+            .await
+            .context("ARK request failed")?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            let message = serde_json::from_str::<Value>(&body)
+                .ok()
+                .and_then(|v| v["error"]["message"].as_str().map(str::to_string))
+                .unwrap_or_else(|| body.chars().take(300).collect());
+            bail!("ARK API error ({status}): {message}");
+        }
+
+        if !stream {
+            let body: Value = resp.json().await?;
+            let content = body["choices"][0]["message"]["content"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string();
+            on_delta(&content);
+            return Ok(Reply {
+                content,
+                usage: Usage::from_value(&body["usage"]),
+            });
+        }
+
+        let mut resp = resp;
+        let mut reply = Reply::default();
+        let mut pending: Vec<u8> = Vec::new();
+        'read: while let Some(chunk) = resp.chunk().await.context("ARK reply stream failed")? {
+            pending.extend_from_slice(&chunk);
+            while let Some(end) = pending.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = pending.drain(..=end).collect();
+                match parse_stream_line(String::from_utf8_lossy(&line).trim())? {
+                    Some(StreamLine::Chunk { delta, usage }) => {
+                        if let Some(delta) = delta {
+                            on_delta(&delta);
+                            reply.content.push_str(&delta);
+                        }
+                        if usage.is_some() {
+                            reply.usage = usage;
+                        }
+                    }
+                    Some(StreamLine::Done) => break 'read,
+                    None => {}
+                }
+            }
+        }
+        Ok(reply)
+    }
+}
+
+/// Set the conversation's system prompt, replacing any existing one.
+pub(crate) fn set_system(messages: &mut Vec<Value>, system: &str) {
+    messages.retain(|m| m["role"] != "system");
+    messages.insert(0, json!({ "role": "system", "content": system }));
+}
+
+/// Read a history file: a JSON array of `{role, content}` messages. A missing
+/// file is an empty conversation.
+fn load_history(path: &Path) -> Result<Vec<Value>> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    if contents.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let messages: Vec<Value> = serde_json::from_str(&contents)
+        .with_context(|| format!("{} is not a JSON array of messages", path.display()))?;
+    if let Some(bad) = messages
+        .iter()
+        .find(|m| !m["role"].is_string() || m.get("content").is_none())
+    {
+        bail!(
+            "{} has a message without a role and content: {bad}",
+            path.display()
         );
     }
+    Ok(messages)
+}
+
+fn save_history(path: &Path, messages: &[Value]) -> Result<()> {
+    std::fs::write(path, serde_json::to_string_pretty(messages)? + "\n")
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Parameters for `ark-chat`.
+pub struct ArkChatParams {
+    pub api_key: String,
+    pub endpoint_id: String,
+    /// Single prompt to send; `-` reads stdin. Interactive when `None`.
+    pub prompt: Option<String>,
+    pub system: Option<String>,
+    /// Conversation file read before and written after each turn.
+    pub history: Option<PathBuf>,
+    pub no_stream: bool,
+}
+
+/// Send one turn and record it in `messages`, printing the reply.
+async fn turn(
+    client: &ArkClient,
+    messages: &mut Vec<Value>,
+    prompt: &str,
+    stream: bool,
+) -> Result<Reply> {
+    messages.push(json!({ "role": "user", "content": prompt }));
+    let mut stdout = std::io::stdout();
+    let result = client
+        .complete(messages, stream, &mut |delta| {
+            print!("{delta}");
+            let _ = stdout.flush();
+        })
+        .await;
+    println!();
+    match result {
+        Ok(reply) => {
+            messages.push(json!({ "role": "assistant", "content": reply.content }));
+            Ok(reply)
+        }
+        Err(e) => {
+            messages.pop();
+            Err(e)
+        }
+    }
+}
+
+/// `ark-chat`: chat with a BytePlus ARK endpoint, streaming the reply.
+pub async fn chat(params: ArkChatParams) -> Result<()> {
+    let client = ArkClient::new(&params.api_key, &params.endpoint_id)?;
+    let stream = !params.no_stream;
+    let mut messages = match &params.history {
+        Some(path) => load_history(path)?,
+        None => Vec::new(),
+    };
+    if let Some(system) = params.system.as_deref().filter(|s| !s.trim().is_empty()) {
+        set_system(&mut messages, system);
+    }
+    let save = |messages: &[Value]| match &params.history {
+        Some(path) => save_history(path, messages),
+        None => Ok(()),
+    };
+
+    if let Some(prompt) = params.prompt {
+        let prompt = if prompt == "-" {
+            let mut input = String::new();
+            std::io::stdin().read_to_string(&mut input)?;
+            input
+        } else {
+            prompt
+        };
+        if prompt.trim().is_empty() {
+            bail!("Prompt cannot be empty.");
+        }
+        let reply = turn(&client, &mut messages, prompt.trim(), stream).await?;
+        save(&messages)?;
+        if let Some(usage) = reply.usage {
+            eprintln!("\n[{usage}]");
+        }
+        return Ok(());
+    }
 
+    let turns = messages.iter().filter(|m| m["role"] == "user").count();
+    eprintln!(
+        "Chatting with ARK endpoint {}{}.\nCommands: /reset, /exit",
+        client.endpoint_id,
+        if turns > 0 {
+            format!(" ({turns} earlier turn(s) loaded)")
+        } else {
+            String::new()
+        }
+    );
+    let mut total = Usage::default();
+    let stdin = std::io::stdin();
+    loop {
+        print!("{} ", style(">").cyan().bold());
+        std::io::stdout().flush()?;
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line)? == 0 {
+            println!();
+            break;
+        }
+        match line.trim() {
+            "" => continue,
+            "/exit" | "/quit" => break,
+            "/reset" => {
+                messages.retain(|m| m["role"] == "system");
+                save(&messages)?;
+                eprintln!("History cleared.");
+            }
+            prompt => match turn(&client, &mut messages, prompt, stream).await {
+                Ok(reply) => {
+                    save(&messages)?;
+                    if let Some(usage) = reply.usage {
+                        total.add(usage);
+                        eprintln!("{}", style(format!("[{usage}]")).dim());
+                    }
+                }
+                Err(e) => eprintln!("{} {e:#}", style("error:").red()),
+            },
+        }
+    }
+    if total.total_tokens > 0 {
+        eprintln!("[session {total}]");
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clawmacdo_core::test_http::{self, Response};
+
+    #[test]
+    fn endpoints_are_grouped_by_model() {
+        let endpoints = vec![
+            json!({"Id": "ep-1", "Name": "prod", "Status": "Running",
+                   "ModelReference": {"FoundationModel": {"Name": "seed-1-6", "ModelVersion": "250615"}}}),
+            json!({"EndpointId": "ep-2", "EndpointName": "dev", "Status": "Running",
+                   "ModelReference": {"FoundationModel": {"Name": "seed-1-6", "ModelVersion": "250615"}}}),
+            json!({"Id": "ep-3", "Name": "custom", "ModelReference": {"CustomModelId": "cm-9"}}),
+        ];
+        let models = join_models(&endpoints);
+        assert_eq!(models.len(), 2);
+        assert_eq!(
+            (models[0].model.as_str(), models[0].version.as_str()),
+            ("cm-9", "-")
+        );
+        assert_eq!(models[0].endpoints[0].status, "-");
+        assert_eq!(models[1].model, "seed-1-6");
+        let ids: Vec<&str> = models[1].endpoints.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, vec!["ep-1", "ep-2"]);
+    }
+
+    #[test]
+    fn history_keeps_one_system_prompt() {
+        let path = std::env::temp_dir().join(format!(
+            "clawmacdo-ark-history-{}.json",
+            uuid::Uuid::new_v4()
+        ));
+        assert!(load_history(&path).unwrap().is_empty());
+
+        let mut messages = vec![
+            json!({"role": "system", "content": "old"}),
+            json!({"role": "user", "content": "hi"}),
+        ];
+        set_system(&mut messages, "new");
+        save_history(&path, &messages).unwrap();
+        let loaded = load_history(&path).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[0]["content"], "new");
+
+        std::fs::write(&path, r#"[{"content": "no role"}]"#).unwrap();
+        assert!(load_history(&path).is_err());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn streamed_reply_and_usage_are_collected() {
+        let body = concat!(
+            "data: {\"choices\":[{\"delta\":{\"role\":\"assistant\"}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"content\":\"Hel\"}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"content\":\"lo\"}}]}\n\n",
+            "data: {\"choices\":[],\"usage\":{\"prompt_tokens\":9,\"completion_tokens\":2,\"total_tokens\":11}}\n\n",
+            "data: [DONE]\n\n",
+        );
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let tx = std::sync::Mutex::new(Some(tx));
+        let addr = test_http::serve(move |req| {
+            if let Some(tx) = tx.lock().unwrap().take() {
+                let _ = tx.send(req);
+            }
+            Response::new("200 OK", body).header("Content-Type", "text/event-stream")
+        })
+        .await;
+
+        let mut client = ArkClient::new("ark-key", "ep-1").unwrap();
+        client.base_url = format!("http://{addr}");
+        let mut deltas = Vec::new();
+        let reply = client
+            .complete(
+                &[json!({"role": "user", "content": "hi"})],
+                true,
+                &mut |d| deltas.push(d.to_string()),
+            )
+            .await
+            .unwrap();
+        assert_eq!(deltas, vec!["Hel", "lo"]);
+        assert_eq!(reply.content, "Hello");
+        assert_eq!(
+            reply.usage,
+            Some(Usage {
+                prompt_tokens: 9,
+                completion_tokens: 2,
+                total_tokens: 11
+            })
+        );
+        let request = rx.await.unwrap();
+        assert_eq!(request.header("authorization"), Some("Bearer ark-key"));
+        let body = request.json();
+        assert_eq!(body["model"], "ep-1");
+        assert_eq!(body["stream_options"]["include_usage"], true);
+    }
+}
@@ -1,15 +1,16 @@
 use anyhow::{Context, Result};
 use chrono::Utc;
-use clawmacdo_core::config;
+use clawmacdo_core::backup_crypto::{self, ArchiveWriter, BackupEncryption};
+use clawmacdo_core::{backup_store, config};
 use clawmacdo_ui::ui;
 use flate2::write::GzEncoder;
 use flate2::Compression;
-use std::fs::File;
 use std::path::Path;
 
 /// Create a backup archive of ~/.openclaw/ and the macOS LaunchAgent plist.
-/// RRun.
-pub fn run() -> Result<()> {
+/// The archive is age-encrypted when backup encryption is configured and then
+/// copied to the configured backup store.
+pub async fn run() -> Result<()> {
     config::ensure_dirs()?;
 
     let openclaw_dir = config::openclaw_dir()?;
@@ -17,12 +18,16 @@ pub fn run() -> Result<()> {
     let backups_dir = config::backups_dir()?;
 
     let timestamp = Utc::now().format("%Y%m%d_%H%M%S");
-    let archive_name = format!("openclaw_backup_{timestamp}.tar.gz");
+    let encryption = BackupEncryption::from_env()?;
+    let mut archive_name = format!("openclaw_backup_{timestamp}.tar.gz");
+    if encryption.is_some() {
+        archive_name = backup_crypto::encrypted_name(&archive_name);
+    }
     let archive_path = backups_dir.join(&archive_name);
 
     let sp = ui::spinner("Creating backup archive...");
 
-    let file = File::create(&archive_path)
+    let file = ArchiveWriter::create(&archive_path, encryption.as_ref())
         .with_context(|| format!("Failed to create {}", archive_path.display()))?;
     let enc = GzEncoder::new(file, Compression::default());
     let mut tar = tar::Builder::new(enc);
@@ -45,28 +50,43 @@ pub fn run() -> Result<()> {
     }
 
     let enc = tar.into_inner()?;
-    enc.finish()?;
+    enc.finish()?.finish()?;
 
     sp.finish_and_clear();
 
     let size = std::fs::metadata(&archive_path)?.len();
     println!("Backup created: {}", archive_path.display());
     println!("Size: {}", format_size(size));
+    if encryption.is_some() {
+        println!("Encrypted: yes (age)");
+    }
+
+    let store = backup_store::configured_store()?;
+    store
+        .put(&archive_path, &archive_name)
+        .await
+        .with_context(|| format!("Failed to store backup in {}", store.describe()))?;
+    if store.describe() != backups_dir.display().to_string() {
+        println!("Stored in: {}", store.describe());
+    }
 
     // List contents
     println!("\nArchive contents:");
-    let count = list_tar_contents(&archive_path)?;
-    if count == 0 {
-        println!("  (empty archive — no files found to back up)");
+    match list_tar_contents(&archive_path) {
+        Ok(0) => println!("  (empty archive — no files found to back up)"),
+        Ok(_) => {}
+        // Recipient-only setups cannot decrypt locally; the archive itself is fine.
+        Err(e) if encryption.is_some() => println!("  (not listed: {e})"),
+        Err(e) => return Err(e),
     }
 
     Ok(())
 }
 
-/// LList tar contents.
+/// List tar contents, decrypting encrypted archives on the fly.
 fn list_tar_contents(path: &Path) -> Result<usize> {
-    let file = File::open(path)?;
-    let dec = flate2::read::GzDecoder::new(file);
+    let reader = backup_crypto::open_archive_reader(path)?;
+    let dec = flate2::read::GzDecoder::new(reader);
     let mut archive = tar::Archive::new(dec);
     let mut count = 0;
     for entry in archive.entries()? {
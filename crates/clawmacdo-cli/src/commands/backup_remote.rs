@@ -0,0 +1,749 @@
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Datelike, Utc};
+use clawmacdo_core::archive;
+use clawmacdo_core::backup_crypto::{self, ArchiveWriter, BackupEncryption};
+use clawmacdo_core::backup_store::{self, BackupStore};
+use clawmacdo_core::config;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Remote directory holding all OpenClaw state (sessions, credentials, agents, workspace).
+const REMOTE_OPENCLAW_PARENT: &str = "/home/openclaw";
+const MANIFEST_SUFFIX: &str = ".manifest.json";
+const MANIFEST_VERSION: u32 = 1;
+
+/// Look up a deploy record by hostname, IP, or deploy ID.
+fn find_deploy_record(query: &str) -> Result<config::DeployRecord> {
+    let deploys_dir = config::deploys_dir()?;
+    if !deploys_dir.exists() {
+        bail!("No deploy records found. Deploy an instance first.");
+    }
+
+    for entry in std::fs::read_dir(&deploys_dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let contents = std::fs::read_to_string(&path)?;
+        let record: config::DeployRecord = match serde_json::from_str(&contents) {
+            Ok(r) => r,
+            Err(_) => continue,
+        };
+        if record.id == query || record.hostname == query || record.ip_address == query {
+            return Ok(record);
+        }
+    }
+
+    bail!("No deploy record found for '{query}'. Use a deploy ID, hostname, or IP address.");
+}
+
+fn ssh_user_for_provider(provider: &Option<config::CloudProviderType>) -> &'static str {
+    match provider {
+        Some(config::CloudProviderType::Lightsail) => "ubuntu",
+        Some(config::CloudProviderType::Azure) => "azureuser",
+        _ => "root",
+    }
+}
+
+/// How many remote backups to keep per instance. All zero means keep everything.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    pub keep_last: usize,
+    pub keep_daily: usize,
+    pub keep_weekly: usize,
+}
+
+impl RetentionPolicy {
+    pub fn is_unlimited(&self) -> bool {
+        self.keep_last == 0 && self.keep_daily == 0 && self.keep_weekly == 0
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestFile {
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+/// Sidecar written next to each remote backup archive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub version: u32,
+    pub deploy_id: String,
+    pub hostname: String,
+    pub ip_address: String,
+    pub source_dir: String,
+    pub archive: String,
+    pub created_at: DateTime<Utc>,
+    /// Size and sha256 of the tar.gz stream, before any encryption.
+    pub size_bytes: u64,
+    pub sha256: String,
+    #[serde(default)]
+    pub encrypted: bool,
+    pub files: Vec<ManifestFile>,
+}
+
+pub struct BackupRemoteParams {
+    pub query: String,
+    pub retention: RetentionPolicy,
+    pub json: bool,
+}
+
+/// Writer that hashes the tar.gz stream, writes it (optionally encrypted) to disk,
+/// and tees a copy to the per-file checksum thread.
+struct TeeWriter {
+    archive: ArchiveWriter,
+    hasher: Sha256,
+    size: u64,
+    tee: Option<std::sync::mpsc::SyncSender<Vec<u8>>>,
+}
+
+impl Write for TeeWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.archive.write_all(buf)?;
+        self.hasher.update(buf);
+        self.size += buf.len() as u64;
+        if let Some(tee) = &self.tee {
+            // The reader only goes away on a parse error, which is reported on join.
+            if tee.send(buf.to_vec()).is_err() {
+                self.tee = None;
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.archive.flush()
+    }
+}
+
+/// `Read` over chunks received from a channel; EOF once the sender is dropped.
+struct ChannelReader {
+    rx: std::sync::mpsc::Receiver<Vec<u8>>,
+    buf: Vec<u8>,
+    pos: usize,
+}
+
+impl Read for ChannelReader {
+    fn read(&mut self, out: &mut [u8]) -> std::io::Result<usize> {
+        while self.pos >= self.buf.len() {
+            match self.rx.recv() {
+                Ok(chunk) => {
+                    self.buf = chunk;
+                    self.pos = 0;
+                }
+                Err(_) => return Ok(0),
+            }
+        }
+        let n = out.len().min(self.buf.len() - self.pos);
+        out[..n].copy_from_slice(&self.buf[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+/// Filesystem-safe label for archive names, derived from the instance hostname.
+fn archive_label(record: &config::DeployRecord) -> String {
+    let source = if record.hostname.trim().is_empty() {
+        &record.id
+    } else {
+        &record.hostname
+    };
+    source
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// Build the remote `tar` command. Entries are stored under `openclaw/` so the
+/// archive has the same layout as local backups and works with `deploy --backup`.
+/// GNU tar exits 1 when files change while being read (live sessions); that is
+/// tolerated, anything higher is a real failure.
+fn remote_tar_cmd(ssh_user: &str) -> String {
+    let sudo = if ssh_user == "root" { "" } else { "sudo " };
+    format!(
+        "{sudo}tar --create --gzip --file - --directory {REMOTE_OPENCLAW_PARENT} \
+         --transform 's,^\\.openclaw,openclaw,' --warning=no-file-changed .openclaw; \
+         rc=$?; if [ $rc -gt 1 ]; then exit $rc; fi"
+    )
+}
+
+/// Build the remote `tar` command for a full state transfer (used by `migrate`).
+///
+/// On top of `~/.openclaw` (stored under `openclaw/`) this captures state that
+/// lives elsewhere in the `openclaw` account, stored under `extras/`:
+/// - `extras/crontab`: the user's crontab,
+/// - `extras/systemd-user`: `~/.config/systemd/user` units,
+/// - `extras/home/<path>`: agent workspaces (skills, wiki, hooks) configured
+///   outside `~/.openclaw` but inside the home directory.
+fn remote_full_state_tar_cmd(ssh_user: &str) -> String {
+    let sudo = if ssh_user == "root" { "" } else { "sudo " };
+    r#"stage=$(mktemp -d)
+__SUDO__crontab -l -u __USER__ > "$stage/crontab" 2>/dev/null || rm -f "$stage/crontab"
+__SUDO__node - > "$stage/workspaces" 2>/dev/null <<'NODE' || : > "$stage/workspaces"
+const fs = require('fs');
+const path = require('path');
+const home = '__HOME__';
+let cfg = {};
+try {
+  cfg = JSON.parse(fs.readFileSync(path.join(home, '.openclaw', 'openclaw.json'), 'utf8'));
+} catch (_) {}
+const agents = cfg.agents || {};
+const values = [];
+if (agents.defaults && agents.defaults.workspace) values.push(agents.defaults.workspace);
+for (const agent of Array.isArray(agents.list) ? agents.list : []) {
+  if (agent && agent.workspace) values.push(agent.workspace);
+}
+const seen = new Set();
+for (let value of values) {
+  if (typeof value !== 'string') continue;
+  if (value === '~') value = home;
+  else if (value.startsWith('~/')) value = path.join(home, value.slice(2));
+  else if (value.startsWith('$HOME/')) value = path.join(home, value.slice(6));
+  if (!path.isAbsolute(value)) value = path.join(home, value);
+  const rel = path.relative(home, path.resolve(value));
+  if (!rel || rel.startsWith('..') || path.isAbsolute(rel)) continue;
+  if (rel === '.openclaw' || rel.startsWith('.openclaw' + path.sep)) continue;
+  if (seen.has(rel) || !fs.existsSync(value)) continue;
+  seen.add(rel);
+  console.log(rel);
+}
+NODE
+set -- --directory __HOME__ .openclaw
+if __SUDO__test -d __HOME__/.config/systemd/user; then set -- "$@" .config/systemd/user; fi
+while IFS= read -r ws; do
+  if [ -n "$ws" ]; then set -- "$@" "./$ws"; fi
+done < "$stage/workspaces"
+if [ -s "$stage/crontab" ]; then set -- "$@" --directory "$stage" crontab; fi
+__SUDO__tar --create --gzip --file -   --transform 's,^\.openclaw,openclaw,'   --transform 's,^\.config/systemd/user,extras/systemd-user,'   --transform 's,^\./,extras/home/,'   --transform 's,^crontab$,extras/crontab,'   --warning=no-file-changed "$@"
+rc=$?
+rm -rf "$stage"
+if [ $rc -gt 1 ]; then exit $rc; fi
+"#
+    .replace("__SUDO__", sudo)
+    .replace("__USER__", config::OPENCLAW_USER)
+    .replace("__HOME__", REMOTE_OPENCLAW_PARENT)
+}
+
+/// Compute size and sha256 for every regular file in a tar.gz stream.
+fn entry_checksums<R: Read>(reader: R) -> Result<Vec<ManifestFile>> {
+    let files = archive::tar_gz_checksums(reader).context("Failed to read archive entries")?;
+    Ok(files
+        .into_iter()
+        .map(|file| ManifestFile {
+            path: file.path,
+            size: file.size,
+            sha256: file.sha256,
+        })
+        .collect())
+}
+
+fn manifest_path_for(archive: &Path) -> PathBuf {
+    let mut name = archive.as_os_str().to_os_string();
+    name.push(MANIFEST_SUFFIX);
+    PathBuf::from(name)
+}
+
+/// Read every remote-backup manifest in `dir` belonging to `deploy_id`.
+pub fn load_manifests(dir: &Path, deploy_id: &str) -> Result<Vec<(PathBuf, BackupManifest)>> {
+    let mut manifests = Vec::new();
+    if !dir.exists() {
+        return Ok(manifests);
+    }
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_manifest = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.ends_with(MANIFEST_SUFFIX));
+        if !is_manifest {
+            continue;
+        }
+        let Ok(contents) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let Ok(manifest) = serde_json::from_str::<BackupManifest>(&contents) else {
+            continue;
+        };
+        if manifest.deploy_id == deploy_id {
+            manifests.push((path, manifest));
+        }
+    }
+    Ok(manifests)
+}
+
+/// Return the indices of backups to keep under `policy`.
+///
+/// `timestamps` may be in any order. Keep-last retains the N newest; keep-daily and
+/// keep-weekly retain the newest backup of each of the N most recent days / ISO weeks.
+pub fn select_retained(timestamps: &[DateTime<Utc>], policy: RetentionPolicy) -> HashSet<usize> {
+    let mut order: Vec<usize> = (0..timestamps.len()).collect();
+    order.sort_by(|a, b| timestamps[*b].cmp(&timestamps[*a]));
+
+    if policy.is_unlimited() {
+        return order.into_iter().collect();
+    }
+
+    let mut keep: HashSet<usize> = order.iter().take(policy.keep_last).copied().collect();
+
+    let mut days = HashSet::new();
+    let mut weeks = HashSet::new();
+    for idx in &order {
+        let ts = timestamps[*idx];
+        if days.len() < policy.keep_daily && days.insert(ts.date_naive()) {
+            keep.insert(*idx);
+        }
+        let week = ts.iso_week();
+        if weeks.len() < policy.keep_weekly && weeks.insert((week.year(), week.week())) {
+            keep.insert(*idx);
+        }
+    }
+    keep
+}
+
+/// Delete archives (and their manifests) for `deploy_id` that fall outside `policy`,
+/// locally and in the configured backup store. Returns the archive names removed.
+pub async fn prune(
+    dir: &Path,
+    store: &dyn BackupStore,
+    deploy_id: &str,
+    policy: RetentionPolicy,
+) -> Result<Vec<String>> {
+    if policy.is_unlimited() {
+        return Ok(Vec::new());
+    }
+    let manifests = load_manifests(dir, deploy_id)?;
+    let timestamps: Vec<DateTime<Utc>> = manifests.iter().map(|(_, m)| m.created_at).collect();
+    let keep = select_retained(&timestamps, policy);
+
+    let mut removed = Vec::new();
+    for (idx, (manifest_path, manifest)) in manifests.iter().enumerate() {
+        if keep.contains(&idx) {
+            continue;
+        }
+        let archive = dir.join(&manifest.archive);
+        if archive.exists() {
+            std::fs::remove_file(&archive)
+                .with_context(|| format!("Failed to remove {}", archive.display()))?;
+        }
+        store.delete(&manifest.archive).await.with_context(|| {
+            format!(
+                "Failed to remove {} from {}",
+                manifest.archive,
+                store.describe()
+            )
+        })?;
+        std::fs::remove_file(manifest_path)
+            .with_context(|| format!("Failed to remove {}", manifest_path.display()))?;
+        removed.push(manifest.archive.clone());
+    }
+    Ok(removed)
+}
+
+/// Stream a tar.gz of the instance's `~/.openclaw` into the backups directory
+/// (age-encrypted when configured), write its manifest and copy it to the backup
+/// store. Returns the archive path and manifest.
+pub async fn backup_instance(
+    record: &config::DeployRecord,
+    store: &dyn BackupStore,
+) -> Result<(PathBuf, BackupManifest)> {
+    stream_backup(record, store, false).await
+}
+
+/// Like [`backup_instance`], but also captures the crontab, systemd user units
+/// and out-of-tree agent workspaces under `extras/` for a full state transfer.
+pub async fn backup_instance_full(
+    record: &config::DeployRecord,
+    store: &dyn BackupStore,
+) -> Result<(PathBuf, BackupManifest)> {
+    stream_backup(record, store, true).await
+}
+
+async fn stream_backup(
+    record: &config::DeployRecord,
+    store: &dyn BackupStore,
+    full_state: bool,
+) -> Result<(PathBuf, BackupManifest)> {
+    config::ensure_dirs()?;
+    let backups_dir = config::backups_dir()?;
+    let encryption = BackupEncryption::from_env()?;
+    let created_at = Utc::now();
+    let mut archive_name = format!(
+        "openclaw_remote_{}_{}.tar.gz",
+        archive_label(record),
+        created_at.format("%Y%m%d_%H%M%S")
+    );
+    if encryption.is_some() {
+        archive_name = backup_crypto::encrypted_name(&archive_name);
+    }
+    let archive_path = backups_dir.join(&archive_name);
+    let partial_path = backups_dir.join(format!("{archive_name}.partial"));
+
+    let ip = record.ip_address.clone();
+    let key = PathBuf::from(&record.ssh_key_path);
+    let ssh_user = ssh_user_for_provider(&record.provider);
+    let cmd = if full_state {
+        remote_full_state_tar_cmd(ssh_user)
+    } else {
+        remote_tar_cmd(ssh_user)
+    };
+    let partial = partial_path.clone();
+    let encrypted = encryption.is_some();
+    let stream_result =
+        tokio::task::spawn_blocking(move || -> Result<(u64, String, Vec<ManifestFile>)> {
+            let archive = ArchiveWriter::create(&partial, encryption.as_ref())
+                .with_context(|| format!("Failed to create {}", partial.display()))?;
+            let (tx, rx) = std::sync::mpsc::sync_channel(64);
+            let checksums = std::thread::spawn(move || {
+                entry_checksums(ChannelReader {
+                    rx,
+                    buf: Vec::new(),
+                    pos: 0,
+                })
+            });
+            let mut writer = TeeWriter {
+                archive,
+                hasher: Sha256::new(),
+                size: 0,
+                tee: Some(tx),
+            };
+            let streamed = clawmacdo_ssh::exec_stream_as(&ip, &key, &cmd, ssh_user, &mut writer);
+            writer.tee = None;
+            let files = checksums
+                .join()
+                .map_err(|_| anyhow::anyhow!("Checksum thread panicked"))?;
+            streamed?;
+            let TeeWriter {
+                archive,
+                hasher,
+                size,
+                ..
+            } = writer;
+            archive.finish()?;
+            Ok((size, hex::encode(hasher.finalize()), files?))
+        })
+        .await?;
+
+    let (size_bytes, sha256, files) = match stream_result {
+        Ok(v) => v,
+        Err(e) => {
+            let _ = std::fs::remove_file(&partial_path);
+            return Err(e.context(format!(
+                "Failed to stream backup from {}",
+                record.ip_address
+            )));
+        }
+    };
+    if size_bytes == 0 {
+        let _ = std::fs::remove_file(&partial_path);
+        bail!("Remote backup from {} was empty", record.ip_address);
+    }
+    std::fs::rename(&partial_path, &archive_path)?;
+
+    let manifest = BackupManifest {
+        version: MANIFEST_VERSION,
+        deploy_id: record.id.clone(),
+        hostname: record.hostname.clone(),
+        ip_address: record.ip_address.clone(),
+        source_dir: format!("{REMOTE_OPENCLAW_PARENT}/.openclaw"),
+        archive: archive_name.clone(),
+        created_at,
+        size_bytes,
+        sha256,
+        encrypted,
+        files,
+    };
+    std::fs::write(
+        manifest_path_for(&archive_path),
+        serde_json::to_string_pretty(&manifest)?,
+    )?;
+    store
+        .put(&archive_path, &archive_name)
+        .await
+        .with_context(|| format!("Failed to store backup in {}", store.describe()))?;
+
+    Ok((archive_path, manifest))
+}
+
+/// Back up one instance, then apply the retention policy.
+pub async fn run(params: &BackupRemoteParams) -> Result<()> {
+    let record = find_deploy_record(&params.query)?;
+    if !params.json {
+        println!(
+            "Backing up {REMOTE_OPENCLAW_PARENT}/.openclaw from {} ({})...",
+            record.hostname, record.ip_address
+        );
+    }
+
+    let store = backup_store::configured_store()?;
+    let (archive_path, manifest) = backup_instance(&record, store.as_ref()).await?;
+    let pruned = prune(
+        &config::backups_dir()?,
+        store.as_ref(),
+        &record.id,
+        params.retention,
+    )
+    .await?;
+
+    if params.json {
+        println!(
+            "{}",
+            serde_json::json!({
+                "ok": true,
+                "archive": archive_path.display().to_string(),
+                "manifest": manifest_path_for(&archive_path).display().to_string(),
+                "size_bytes": manifest.size_bytes,
+                "sha256": manifest.sha256,
+                "files": manifest.files.len(),
+                "encrypted": manifest.encrypted,
+                "store": store.describe(),
+                "pruned": pruned,
+            })
+        );
+    } else {
+        println!("Backup created: {}", archive_path.display());
+        println!(
+            "  {} bytes, {} files, sha256 {}",
+            manifest.size_bytes,
+            manifest.files.len(),
+            manifest.sha256
+        );
+        if manifest.encrypted {
+            println!("  Encrypted with age");
+        }
+        for name in &pruned {
+            println!("  Pruned {name}");
+        }
+    }
+    Ok(())
+}
+
+/// Parse an interval such as `30m`, `6h` or `1d`.
+pub fn parse_interval(input: &str) -> Result<std::time::Duration> {
+    let trimmed = input.trim();
+    let (number, unit) = trimmed.split_at(
+        trimmed
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(trimmed.len()),
+    );
+    let value: u64 = number
+        .parse()
+        .with_context(|| format!("Invalid interval '{input}'"))?;
+    let secs = match unit {
+        "s" => value,
+        "m" | "" => value * 60,
+        "h" => value * 3600,
+        "d" => value * 86_400,
+        _ => bail!("Invalid interval '{input}'. Use a number followed by s, m, h or d."),
+    };
+    if secs == 0 {
+        bail!("Interval must be greater than zero");
+    }
+    Ok(std::time::Duration::from_secs(secs))
+}
+
+/// Repeat `run` every `every`, logging failures instead of exiting.
+pub async fn run_scheduled(params: BackupRemoteParams, every: std::time::Duration) -> Result<()> {
+    println!(
+        "Scheduled remote backups of '{}' every {}s (Ctrl+C to stop).",
+        params.query,
+        every.as_secs()
+    );
+    loop {
+        if let Err(e) = run(&params).await {
+            eprintln!("Remote backup of '{}' failed: {e:#}", params.query);
+        }
+        tokio::time::sleep(every).await;
+    }
+}
+
+fn env_usize(name: &str) -> usize {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+/// Start scheduled backups inside `serve` when `CLAWMACDO_BACKUP_INSTANCES` is set.
+///
+/// `CLAWMACDO_BACKUP_INSTANCES` is a comma-separated list of deploy IDs, hostnames
+/// or IPs; `CLAWMACDO_BACKUP_EVERY` sets the interval (default `24h`), and
+/// `CLAWMACDO_BACKUP_KEEP_LAST` / `_KEEP_DAILY` / `_KEEP_WEEKLY` set retention.
+pub fn spawn_schedule_from_env() -> Result<()> {
+    let instances = std::env::var("CLAWMACDO_BACKUP_INSTANCES").unwrap_or_default();
+    let instances: Vec<String> = instances
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    if instances.is_empty() {
+        return Ok(());
+    }
+
+    let every =
+        parse_interval(&std::env::var("CLAWMACDO_BACKUP_EVERY").unwrap_or_else(|_| "24h".into()))?;
+    let retention = RetentionPolicy {
+        keep_last: env_usize("CLAWMACDO_BACKUP_KEEP_LAST"),
+        keep_daily: env_usize("CLAWMACDO_BACKUP_KEEP_DAILY"),
+        keep_weekly: env_usize("CLAWMACDO_BACKUP_KEEP_WEEKLY"),
+    };
+
+    println!(
+        "  Scheduled remote backups: {} every {}s",
+        instances.join(", "),
+        every.as_secs()
+    );
+    for query in instances {
+        let params = BackupRemoteParams {
+            query,
+            retention,
+            json: true,
+        };
+        tokio::spawn(async move {
+            let _ = run_scheduled(params, every).await;
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn ts(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, day, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn keep_last_retains_newest_backups() {
+        let stamps = vec![ts(1, 0), ts(3, 0), ts(2, 0)];
+        let keep = select_retained(
+            &stamps,
+            RetentionPolicy {
+                keep_last: 2,
+                ..Default::default()
+            },
+        );
+        assert_eq!(keep, HashSet::from([1, 2]));
+    }
+
+    #[test]
+    fn keep_daily_retains_newest_per_day() {
+        let stamps = vec![ts(2, 1), ts(2, 9), ts(1, 5), ts(1, 23)];
+        let keep = select_retained(
+            &stamps,
+            RetentionPolicy {
+                keep_daily: 2,
+                ..Default::default()
+            },
+        );
+        assert_eq!(keep, HashSet::from([1, 3]));
+    }
+
+    #[test]
+    fn keep_weekly_spans_iso_weeks() {
+        // 2026-03-02 is a Monday; the 9th starts the next ISO week.
+        let stamps = vec![ts(2, 0), ts(4, 0), ts(9, 0), ts(16, 0)];
+        let keep = select_retained(
+            &stamps,
+            RetentionPolicy {
+                keep_weekly: 2,
+                ..Default::default()
+            },
+        );
+        assert_eq!(keep, HashSet::from([2, 3]));
+    }
+
+    #[test]
+    fn unlimited_policy_keeps_everything() {
+        let stamps = vec![ts(1, 0), ts(2, 0)];
+        assert_eq!(
+            select_retained(&stamps, RetentionPolicy::default()).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn tee_writer_checksums_entries_while_writing() {
+        let mut tar = tar::Builder::new(flate2::write::GzEncoder::new(
+            Vec::new(),
+            flate2::Compression::default(),
+        ));
+        let mut header = tar::Header::new_gnu();
+        header.set_size(5);
+        header.set_mode(0o600);
+        header.set_cksum();
+        tar.append_data(&mut header, "openclaw/.env", &b"A=1\n\n"[..])
+            .unwrap();
+        let gz = tar.into_inner().unwrap().finish().unwrap();
+
+        let path =
+            std::env::temp_dir().join(format!("clawmacdo-tee-{}.tar.gz", uuid::Uuid::new_v4()));
+        let (tx, rx) = std::sync::mpsc::sync_channel(4);
+        let checksums = std::thread::spawn(move || {
+            entry_checksums(ChannelReader {
+                rx,
+                buf: Vec::new(),
+                pos: 0,
+            })
+        });
+        let mut writer = TeeWriter {
+            archive: ArchiveWriter::create(&path, None).unwrap(),
+            hasher: Sha256::new(),
+            size: 0,
+            tee: Some(tx),
+        };
+        for chunk in gz.chunks(7) {
+            writer.write_all(chunk).unwrap();
+        }
+        writer.tee = None;
+        writer.archive.finish().unwrap();
+
+        let files = checksums.join().unwrap().unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, "openclaw/.env");
+        assert_eq!(files[0].size, 5);
+        assert_eq!(writer.size, gz.len() as u64);
+        assert_eq!(std::fs::read(&path).unwrap(), gz);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn parse_interval_accepts_units() {
+        assert_eq!(parse_interval("90s").unwrap().as_secs(), 90);
+        assert_eq!(parse_interval("30m").unwrap().as_secs(), 1800);
+        assert_eq!(parse_interval("6h").unwrap().as_secs(), 21_600);
+        assert_eq!(parse_interval("1d").unwrap().as_secs(), 86_400);
+        assert!(parse_interval("0h").is_err());
+        assert!(parse_interval("2w").is_err());
+    }
+
+    #[test]
+    fn remote_tar_cmd_uses_sudo_for_non_root_users() {
+        assert!(remote_tar_cmd("root").starts_with("tar "));
+        assert!(remote_tar_cmd("ubuntu").starts_with("sudo tar "));
+        assert!(remote_tar_cmd("root").contains("--transform 's,^\\.openclaw,openclaw,'"));
+    }
+
+    #[test]
+    fn full_state_tar_cmd_maps_extras() {
+        let cmd = remote_full_state_tar_cmd("ubuntu");
+        assert!(cmd.contains("sudo crontab -l -u openclaw"));
+        assert!(cmd.contains("sudo tar --create --gzip --file -"));
+        assert!(cmd.contains("--directory /home/openclaw .openclaw"));
+        assert!(cmd.contains("'s,^\\./,extras/home/,'"));
+        assert!(!remote_full_state_tar_cmd("root").contains("sudo "));
+    }
+}
@@ -0,0 +1,710 @@
+//! `clawmacdo bake`: build a reusable OpenClaw image on a throwaway builder
+//! instance so that `deploy --image` can skip the slow package installs.
+//!
+//! The builder runs cloud-init and every provisioning step that carries no
+//! secrets, then has its keys and host identity scrubbed before it is
+//! snapshotted. Images are recorded in the `baked_images` table.
+
+use super::deploy::resolve_provider;
+use super::openclaw_version::parse_version_output;
+use anyhow::{bail, Context, Result};
+use clawmacdo_core::config::{self, CloudProviderType};
+use clawmacdo_db as db;
+use clawmacdo_provision as provision;
+use clawmacdo_ssh as ssh;
+use clawmacdo_ui::progress;
+use tokio::sync::mpsc;
+
+const TOTAL_STEPS: i32 = 8;
+
+/// Written into the image so a booted instance can tell it was baked.
+const BAKED_MARKER: &str = "/etc/clawmacdo-baked";
+
+pub struct BakeParams {
+    pub provider: String,
+    pub openclaw_version: String,
+    pub name: Option<String>,
+    pub region: Option<String>,
+    pub size: Option<String>,
+    pub do_token: String,
+    pub aws_access_key_id: String,
+    pub aws_secret_access_key: String,
+    pub aws_region: String,
+    pub byteplus_access_key: String,
+    pub byteplus_secret_key: String,
+    /// Leave the builder instance running after the snapshot, for debugging.
+    pub keep_builder: bool,
+    pub json: bool,
+    pub progress_tx: Option<mpsc::UnboundedSender<String>>,
+}
+
+/// Image names double as Lightsail snapshot names: letters, digits, `.`,
+/// `_` and `-`, starting with a letter.
+fn validate_name(name: &str) -> Result<()> {
+    let valid = name.len() <= 64
+        && name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "._-".contains(c));
+    if !valid {
+        bail!(
+            "Invalid image name '{name}': use up to 64 letters, digits, '.', '_' or '-', starting with a letter"
+        );
+    }
+    Ok(())
+}
+
+fn default_name(openclaw_version: &str, bake_id: &str) -> String {
+    let version: String = openclaw_version
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    format!("openclaw-{version}-{}", &bake_id[..8])
+}
+
+/// Runs on the first boot of every instance created from the image:
+/// copies the provider's login key to root, as the deploy cloud-init does,
+/// and touches the sentinel that `deploy` waits for.
+fn first_boot_script() -> String {
+    let sentinel = config::CLOUD_INIT_SENTINEL;
+    format!(
+        r#"#!/bin/sh
+# Installed by `clawmacdo bake`.
+for u in ubuntu azureuser; do
+  if [ -s /home/$u/.ssh/authorized_keys ]; then
+    mkdir -p /root/.ssh && chmod 700 /root/.ssh
+    cp /home/$u/.ssh/authorized_keys /root/.ssh/authorized_keys
+    chmod 600 /root/.ssh/authorized_keys
+  fi
+done
+touch {sentinel}
+"#
+    )
+}
+
+/// Remove everything tied to the builder: login keys, SSH host keys, the
+/// machine ID, env files, logs and cloud-init state. Ends by installing the
+/// first-boot script, since `cloud-init clean` wipes `/var/lib/cloud`.
+fn scrub_script(openclaw_version: &str) -> String {
+    let home = config::OPENCLAW_HOME;
+    let sentinel = config::CLOUD_INIT_SENTINEL;
+    let first_boot = first_boot_script();
+    format!(
+        r#"set -e
+rm -f {home}/.ssh/authorized_keys /root/.ssh/authorized_keys /home/*/.ssh/authorized_keys
+rm -f {home}/.openclaw/.env {home}/.openclaw/gateway.env /tmp/.env_upload /tmp/.gateway_env_upload /tmp/.authorized_keys_upload
+rm -rf /root/.openclaw
+rm -f /root/.bash_history /home/*/.bash_history {home}/.bash_history
+rm -f /etc/ssh/ssh_host_*
+truncate -s 0 /etc/machine-id
+rm -f /var/lib/dbus/machine-id
+rm -f {sentinel}
+apt-get clean
+journalctl --rotate >/dev/null 2>&1 || true
+journalctl --vacuum-time=1s >/dev/null 2>&1 || true
+find /var/log -type f -name '*.gz' -delete
+find /var/log -type f -exec truncate -s 0 {{}} +
+cloud-init clean --logs
+mkdir -p /var/lib/cloud/scripts/per-instance
+cat > /var/lib/cloud/scripts/per-instance/clawmacdo-ready.sh <<'BOOTEOF'
+{first_boot}BOOTEOF
+chmod 755 /var/lib/cloud/scripts/per-instance/clawmacdo-ready.sh
+printf 'openclaw_version=%s\n' '{openclaw_version}' > {BAKED_MARKER}
+sync
+echo scrubbed"#
+    )
+}
+
+/// The builder instance and what is needed to remove it again.
+enum Builder {
+    #[cfg(feature = "digitalocean")]
+    DigitalOcean {
+        client: clawmacdo_cloud::digitalocean::DoClient,
+        droplet_id: u64,
+        key_id: u64,
+    },
+    #[cfg(feature = "lightsail")]
+    Lightsail {
+        provider: clawmacdo_cloud::lightsail_cli::LightsailCliProvider,
+        name: String,
+        key_name: String,
+    },
+    #[cfg(feature = "byteplus")]
+    BytePlus {
+        client: clawmacdo_cloud::byteplus::BytePlusClient,
+        instance_id: String,
+        key_name: String,
+    },
+}
+
+/// Create the builder from the stock image with the deploy cloud-init.
+async fn create_builder(
+    provider: &CloudProviderType,
+    params: &BakeParams,
+    name: &str,
+    region: &str,
+    size: &str,
+    public_key: &str,
+) -> Result<Builder> {
+    match provider {
+        #[cfg(feature = "digitalocean")]
+        CloudProviderType::DigitalOcean => {
+            let client = clawmacdo_cloud::digitalocean::DoClient::new(&params.do_token)?;
+            let key = client
+                .upload_ssh_key(name, public_key)
+                .await
+                .context("Failed to upload SSH key to DigitalOcean")?;
+            let droplet = client
+                .create_droplet(
+                    name,
+                    region,
+                    size,
+                    key.id,
+                    &clawmacdo_cloud::cloud_init::generate(),
+                    false,
+                    "bake",
+                )
+                .await
+                .context("Failed to create builder droplet")?;
+            Ok(Builder::DigitalOcean {
+                client,
+                droplet_id: droplet.id,
+                key_id: key.id,
+            })
+        }
+        #[cfg(feature = "lightsail")]
+        CloudProviderType::Lightsail => {
+            use clawmacdo_cloud::cloud_provider::CreateInstanceParams;
+            use clawmacdo_cloud::CloudProvider;
+
+            clawmacdo_cloud::lightsail_cli::ensure_aws_cli()?;
+            let lightsail = clawmacdo_cloud::lightsail_cli::LightsailCliProvider::with_credentials(
+                region.to_string(),
+                params.aws_access_key_id.clone(),
+                params.aws_secret_access_key.clone(),
+            );
+            lightsail
+                .upload_ssh_key(name, public_key)
+                .await
+                .context("Failed to upload SSH key to AWS Lightsail")?;
+            let instance = lightsail
+                .create_instance(CreateInstanceParams {
+                    name: name.to_string(),
+                    region: region.to_string(),
+                    size: size.to_string(),
+                    image: "ubuntu_24_04".to_string(),
+                    ssh_key_id: name.to_string(),
+                    user_data: clawmacdo_cloud::cloud_init::generate_shell(),
+                    tags: vec!["app=clawmacdo-bake".to_string()],
+                    customer_email: "bake".to_string(),
+                })
+                .await
+                .context("Failed to create builder instance")?;
+            Ok(Builder::Lightsail {
+                provider: lightsail,
+                name: instance.id,
+                key_name: name.to_string(),
+            })
+        }
+        #[cfg(feature = "byteplus")]
+        CloudProviderType::BytePlus => {
+            use base64::Engine;
+
+            let client = clawmacdo_cloud::byteplus::BytePlusClient::new(
+                &params.byteplus_access_key,
+                &params.byteplus_secret_key,
+                region,
+            )?;
+            client
+                .import_key_pair(name, public_key)
+                .await
+                .context("Failed to upload SSH key to BytePlus")?;
+            let user_data = base64::engine::general_purpose::STANDARD
+                .encode(clawmacdo_cloud::cloud_init::generate());
+            let instance_id = client
+                .create_instance(name, size, name, &user_data, "bake", false)
+                .await
+                .context("Failed to create builder ECS instance")?;
+            Ok(Builder::BytePlus {
+                client,
+                instance_id,
+                key_name: name.to_string(),
+            })
+        }
+        #[allow(unreachable_patterns)]
+        other => {
+            let _ = (params, name, region, size, public_key);
+            bail!("bake supports digitalocean, lightsail and byteplus; {other} is not supported or not compiled in")
+        }
+    }
+}
+
+impl Builder {
+    fn ssh_user(&self) -> &'static str {
+        match self {
+            #[cfg(feature = "lightsail")]
+            Builder::Lightsail { .. } => "ubuntu",
+            #[allow(unreachable_patterns)]
+            _ => "root",
+        }
+    }
+
+    /// Wait for the builder to become active and return its public IP.
+    async fn wait_ready(&self) -> Result<String> {
+        match self {
+            #[cfg(feature = "digitalocean")]
+            Builder::DigitalOcean {
+                client, droplet_id, ..
+            } => {
+                let droplet = client
+                    .wait_for_active(*droplet_id, std::time::Duration::from_secs(300))
+                    .await
+                    .context("Builder droplet did not become active within 5 minutes")?;
+                droplet
+                    .public_ip()
+                    .context("Builder droplet has no public IP")
+            }
+            #[cfg(feature = "lightsail")]
+            Builder::Lightsail { provider, name, .. } => {
+                use clawmacdo_cloud::CloudProvider;
+                let info = provider
+                    .wait_for_active(name, 600)
+                    .await
+                    .context("Builder instance did not become active within 10 minutes")?;
+                info.public_ip.context("Builder instance has no public IP")
+            }
+            #[cfg(feature = "byteplus")]
+            Builder::BytePlus {
+                client,
+                instance_id,
+                ..
+            } => {
+                let instance = client
+                    .wait_for_running(instance_id, std::time::Duration::from_secs(300))
+                    .await
+                    .context("Builder instance did not become RUNNING within 5 minutes")?;
+                instance
+                    .public_ip
+                    .context("Builder instance has no public IP")
+            }
+        }
+    }
+
+    /// Snapshot the scrubbed builder and return the provider image reference.
+    async fn snapshot(&self, image_name: &str) -> Result<String> {
+        match self {
+            #[cfg(feature = "digitalocean")]
+            Builder::DigitalOcean {
+                client, droplet_id, ..
+            } => {
+                let action = client
+                    .shutdown_droplet(*droplet_id)
+                    .await
+                    .context("Failed to shut down builder droplet")?;
+                client
+                    .wait_for_action(action, std::time::Duration::from_secs(120))
+                    .await
+                    .context("Builder droplet did not shut down in time")?;
+                let action = client
+                    .create_snapshot(*droplet_id, image_name)
+                    .await
+                    .context("Failed to start snapshot")?;
+                client
+                    .wait_for_action(action, std::time::Duration::from_secs(1800))
+                    .await
+                    .context("Snapshot did not complete within 30 minutes")?;
+                let snapshots = client.get_droplet_snapshots(*droplet_id).await?;
+                let snapshot = snapshots
+                    .into_iter()
+                    .find(|s| s.name == image_name)
+                    .context("Snapshot finished but was not listed on the droplet")?;
+                Ok(snapshot.id)
+            }
+            #[cfg(feature = "lightsail")]
+            Builder::Lightsail { provider, name, .. } => {
+                provider.create_instance_snapshot(name, image_name)?;
+                provider
+                    .wait_for_snapshot(image_name, std::time::Duration::from_secs(1800))
+                    .await
+                    .context("Lightsail snapshot did not become available within 30 minutes")?;
+                Ok(image_name.to_string())
+            }
+            #[cfg(feature = "byteplus")]
+            Builder::BytePlus {
+                client,
+                instance_id,
+                ..
+            } => {
+                let volume_id = client.describe_system_volume(instance_id).await?;
+                let snapshot_id = client.create_ebs_snapshot(&volume_id, image_name).await?;
+                client
+                    .wait_for_snapshot(&snapshot_id, std::time::Duration::from_secs(1800))
+                    .await
+                    .context("BytePlus snapshot did not become available within 30 minutes")?;
+                let image_id = client.create_image(&snapshot_id, image_name).await?;
+                client
+                    .wait_for_image(&image_id, std::time::Duration::from_secs(1800))
+                    .await
+                    .context("BytePlus image did not become available within 30 minutes")?;
+                Ok(image_id)
+            }
+        }
+    }
+
+    /// Delete the builder instance and its SSH key.
+    async fn destroy(&self) -> Result<()> {
+        match self {
+            #[cfg(feature = "digitalocean")]
+            Builder::DigitalOcean {
+                client,
+                droplet_id,
+                key_id,
+            } => {
+                client.delete_droplet(*droplet_id).await?;
+                client.delete_ssh_key(*key_id).await?;
+            }
+            #[cfg(feature = "lightsail")]
+            Builder::Lightsail {
+                provider,
+                name,
+                key_name,
+            } => {
+                use clawmacdo_cloud::CloudProvider;
+                provider.delete_instance(name).await?;
+                provider.delete_ssh_key(key_name).await?;
+            }
+            #[cfg(feature = "byteplus")]
+            Builder::BytePlus {
+                client,
+                instance_id,
+                key_name,
+            } => {
+                client.terminate_instance(instance_id).await?;
+                client.delete_key_pair(key_name).await?;
+            }
+        }
+        Ok(())
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            #[cfg(feature = "digitalocean")]
+            Builder::DigitalOcean { droplet_id, .. } => format!("droplet {droplet_id}"),
+            #[cfg(feature = "lightsail")]
+            Builder::Lightsail { name, .. } => format!("Lightsail instance {name}"),
+            #[cfg(feature = "byteplus")]
+            Builder::BytePlus { instance_id, .. } => format!("ECS instance {instance_id}"),
+        }
+    }
+}
+
+fn default_region(provider: &CloudProviderType, params: &BakeParams) -> String {
+    if let Some(region) = params.region.as_deref().filter(|r| !r.trim().is_empty()) {
+        return region.trim().to_string();
+    }
+    match provider {
+        CloudProviderType::Lightsail => params.aws_region.clone(),
+        CloudProviderType::BytePlus => config::DEFAULT_BYTEPLUS_REGION.to_string(),
+        _ => config::DEFAULT_REGION.to_string(),
+    }
+}
+
+fn default_size(provider: &CloudProviderType) -> &'static str {
+    match provider {
+        CloudProviderType::BytePlus => config::DEFAULT_BYTEPLUS_SIZE,
+        _ => config::DEFAULT_SIZE,
+    }
+}
+
+/// Steps 4–7 on a created builder: wait for it, provision, scrub, snapshot.
+async fn bake_builder(
+    provider: &CloudProviderType,
+    builder: &Builder,
+    keypair: &ssh::KeyPair,
+    openclaw_version: &str,
+    image_name: &str,
+    tx: &Option<mpsc::UnboundedSender<String>>,
+) -> Result<(String, String)> {
+    let ip = &builder.wait_ready().await?;
+    progress::emit(tx, &format!("  {} at {ip}", builder.describe()));
+    let user = builder.ssh_user();
+    let key = &keypair.private_key_path;
+
+    progress::emit(
+        tx,
+        &format!("\n[Step 4/{TOTAL_STEPS}] Waiting for SSH and cloud-init..."),
+    );
+    ssh::wait_for_ssh(ip, key, std::time::Duration::from_secs(300), Some(user))
+        .await
+        .context("SSH did not become available within 5 minutes")?;
+    ssh::wait_for_cloud_init(ip, key, std::time::Duration::from_secs(1800), Some(user))
+        .await
+        .context("Cloud-init did not complete within 30 minutes")?;
+    let preamble = super::hermes::provider_preamble(provider);
+    if !preamble.is_empty() {
+        progress::emit(tx, "  Applying provider network and apt fixes...");
+        provision::provision::commands::ssh_root_as_async(
+            ip,
+            key,
+            &format!("{preamble}apt-get update -y >/dev/null 2>&1 || true"),
+            user,
+        )
+        .await
+        .context("Provider fixes failed")?;
+    }
+
+    progress::emit(
+        tx,
+        &format!("\n[Step 5/{TOTAL_STEPS}] Running provisioning steps (no secrets)..."),
+    );
+    let version = provision::run_generic(
+        ip,
+        key,
+        &keypair.public_key_openssh,
+        openclaw_version,
+        user,
+        tx,
+    )
+    .await
+    .context("Provision failed")?;
+    // Record what was installed, not the requested spec: a bake of `latest`
+    // must not match a later `deploy --openclaw-version latest`.
+    let version = parse_version_output(&version).with_context(|| {
+        format!("Could not read the installed OpenClaw version from '{version}'")
+    })?;
+    progress::emit(tx, &format!("  OpenClaw version: {version}"));
+
+    progress::emit(
+        tx,
+        &format!("\n[Step 6/{TOTAL_STEPS}] Scrubbing keys, host identity and logs..."),
+    );
+    let out =
+        provision::provision::commands::ssh_root_as_async(ip, key, &scrub_script(&version), user)
+            .await
+            .context("Scrub failed")?;
+    if !out.contains("scrubbed") {
+        bail!("Scrub did not complete: {}", out.trim());
+    }
+
+    progress::emit(
+        tx,
+        &format!("\n[Step 7/{TOTAL_STEPS}] Creating image '{image_name}'..."),
+    );
+    let image_id = builder.snapshot(image_name).await?;
+    progress::emit(tx, &format!("  Image: {image_id}"));
+    Ok((image_id, version))
+}
+
+pub async fn run(params: BakeParams) -> Result<()> {
+    config::ensure_dirs()?;
+    let bake_id = uuid::Uuid::new_v4().to_string();
+    let tx = &params.progress_tx;
+
+    progress::emit(
+        tx,
+        &format!("\n[Step 1/{TOTAL_STEPS}] Resolving parameters..."),
+    );
+    let provider = resolve_provider(&params.provider)?;
+    if !matches!(
+        provider,
+        CloudProviderType::DigitalOcean
+            | CloudProviderType::Lightsail
+            | CloudProviderType::BytePlus
+    ) {
+        bail!("bake supports digitalocean, lightsail and byteplus, not {provider}");
+    }
+    let openclaw_version = params.openclaw_version.trim().to_string();
+    if openclaw_version.is_empty() {
+        bail!("--openclaw-version is required");
+    }
+    let image_name = params
+        .name
+        .clone()
+        .unwrap_or_else(|| default_name(&openclaw_version, &bake_id));
+    validate_name(&image_name)?;
+    let conn = db::init_db()?;
+    if db::find_baked_image(&conn, &image_name)?.is_some() {
+        bail!("A baked image named '{image_name}' already exists; pick another --name");
+    }
+    let region = default_region(&provider, &params);
+    let size = params
+        .size
+        .clone()
+        .unwrap_or_else(|| default_size(&provider).to_string());
+    let builder_name = format!("clawmacdo-bake-{}", &bake_id[..8]);
+    progress::emit(tx, &format!("  Provider: {provider}"));
+    progress::emit(tx, &format!("  Region:   {region}"));
+    progress::emit(tx, &format!("  Size:     {size}"));
+    progress::emit(tx, &format!("  OpenClaw: {openclaw_version}"));
+    progress::emit(tx, &format!("  Image:    {image_name}"));
+
+    progress::emit(
+        tx,
+        &format!("\n[Step 2/{TOTAL_STEPS}] Generating builder SSH key..."),
+    );
+    let keypair = ssh::generate_keypair(&bake_id)?;
+
+    progress::emit(
+        tx,
+        &format!("\n[Step 3/{TOTAL_STEPS}] Creating builder instance {builder_name}..."),
+    );
+    let builder = create_builder(
+        &provider,
+        &params,
+        &builder_name,
+        &region,
+        &size,
+        &keypair.public_key_openssh,
+    )
+    .await?;
+
+    let result = bake_builder(
+        &provider,
+        &builder,
+        &keypair,
+        &openclaw_version,
+        &image_name,
+        tx,
+    )
+    .await;
+
+    progress::emit(tx, &format!("\n[Step 8/{TOTAL_STEPS}] Cleaning up..."));
+    if params.keep_builder {
+        progress::emit(
+            tx,
+            &format!(
+                "  Keeping {} (--keep-builder); key: {}",
+                builder.describe(),
+                keypair.private_key_path.display()
+            ),
+        );
+    } else {
+        if let Err(e) = builder.destroy().await {
+            progress::emit(
+                tx,
+                &format!(
+                    "  Warning: could not remove {}: {e:#}. Delete it by hand.",
+                    builder.describe()
+                ),
+            );
+        } else {
+            progress::emit(tx, &format!("  Removed {}", builder.describe()));
+        }
+        let _ = std::fs::remove_file(&keypair.private_key_path);
+        let _ = std::fs::remove_file(keypair.private_key_path.with_extension("pub"));
+    }
+
+    let (image_id, version) = result?;
+    db::insert_baked_image(
+        &conn,
+        &image_name,
+        &provider.to_string(),
+        &region,
+        &image_id,
+        &version,
+    )?;
+    let row = db::find_baked_image(&conn, &image_name)?.context("Baked image was not recorded")?;
+
+    if params.json {
+        println!("{}", serde_json::to_string_pretty(&row)?);
+    } else {
+        println!("\nBaked image '{image_name}' ({provider}, {region})");
+        println!("  Image ID: {image_id}");
+        println!("  OpenClaw: {version}");
+        println!(
+            "  Deploy:   clawmacdo deploy --provider {provider} --image {image_name} --openclaw-version {version} ..."
+        );
+    }
+    Ok(())
+}
+
+/// `list-bakes`: images recorded by `bake`.
+pub fn list(json: bool) -> Result<()> {
+    let conn = db::init_db()?;
+    let images = db::list_baked_images(&conn)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&images)?);
+        return Ok(());
+    }
+    if images.is_empty() {
+        println!("No baked images. Create one with `clawmacdo bake`.");
+        return Ok(());
+    }
+    println!(
+        "{:<36} {:<13} {:<15} {:<12} {:<20} IMAGE ID",
+        "NAME", "PROVIDER", "REGION", "OPENCLAW", "CREATED"
+    );
+    for image in images {
+        println!(
+            "{:<36} {:<13} {:<15} {:<12} {:<20} {}",
+            image.name,
+            image.provider,
+            image.region,
+            image.openclaw_version,
+            image.created_at,
+            image.image_id
+        );
+    }
+    Ok(())
+}
+
+/// Look up the baked image for `deploy --image` and check it can be used
+/// for `provider` in `region`.
+pub(crate) fn resolve(
+    query: &str,
+    provider: &CloudProviderType,
+    region: Option<&str>,
+) -> Result<db::BakedImageRow> {
+    let conn = db::init_db()?;
+    let image = db::find_baked_image(&conn, query)?.with_context(|| {
+        format!("No baked image '{query}'. See `clawmacdo list-bakes` or run `clawmacdo bake`.")
+    })?;
+    if image.provider != provider.to_string() {
+        bail!(
+            "Baked image '{}' is for {}, not {provider}",
+            image.name,
+            image.provider
+        );
+    }
+    if let Some(region) = region.filter(|r| !r.trim().is_empty()) {
+        if region != image.region {
+            bail!(
+                "Baked image '{}' lives in {}; deploy there or bake again in {region}",
+                image.name,
+                image.region
+            );
+        }
+    }
+    Ok(image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scrub_removes_identity_and_reinstalls_first_boot_hook_after_clean() {
+        let script = scrub_script("2026.3.22");
+        assert!(script.contains("/root/.ssh/authorized_keys"));
+        assert!(script.contains("/etc/ssh/ssh_host_*"));
+        assert!(script.contains("truncate -s 0 /etc/machine-id"));
+        assert!(script.contains("/.openclaw/.env"));
+        assert!(script.contains(config::CLOUD_INIT_SENTINEL));
+        let clean = script.find("cloud-init clean").unwrap();
+        let hook = script
+            .find("scripts/per-instance/clawmacdo-ready.sh")
+            .unwrap();
+        assert!(clean < hook);
+        assert!(script.trim_end().ends_with("echo scrubbed"));
+    }
+
+    #[test]
+    fn image_names_are_checked_and_defaults_are_valid() {
+        assert!(validate_name("openclaw-2026.3.22").is_ok());
+        assert!(validate_name("1-starts-with-digit").is_err());
+        assert!(validate_name("has space").is_err());
+        let name = default_name("2026.3.22", "0123456789abcdef");
+        assert_eq!(name, "openclaw-2026-3-22-01234567");
+        assert!(validate_name(&name).is_ok());
+    }
+}
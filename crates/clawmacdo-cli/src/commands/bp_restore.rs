@@ -1,3 +1,4 @@
+use super::dns;
 use anyhow::{Context, Result};
 use chrono::Utc;
 use clawmacdo_cloud::byteplus::BytePlusClient;
@@ -183,7 +184,7 @@ pub async fn run(params: BpRestoreParams) -> Result<RestoreResult> {
         .context("Failed to update deployment status")?;
 
     // Save JSON deploy record
-    let record = DeployRecord {
+    let mut record = DeployRecord {
         id: deploy_id.clone(),
         provider: Some(CloudProviderType::BytePlus),
         droplet_id: 0,
@@ -198,7 +199,11 @@ pub async fn run(params: BpRestoreParams) -> Result<RestoreResult> {
         resource_group: None,
         backup_restored: None,
         created_at: Utc::now(),
+        fqdn: None,
     };
+    if let Some(line) = dns::publish(&mut record).await {
+        progress::emit(tx, &line);
+    }
     let record_path = record.save()?;
 
     progress::emit(tx, "\n--- Restore Complete ---");
@@ -0,0 +1,470 @@
+use super::fleet::{self, FleetTarget};
+use super::openclaw_gateway_url;
+use anyhow::{bail, Context, Result};
+use base64::Engine;
+use console::style;
+use serde_json::{json, Value};
+use std::io::{BufRead, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const GATEWAY_PORT: u16 = 18789;
+const MAX_ATTACHMENT_BYTES: u64 = 5 * 1024 * 1024;
+
+pub struct ChatParams {
+    pub instance: String,
+    pub agent: String,
+    /// One prompt to send, `-` for stdin; `None` starts an interactive session.
+    pub once: Option<String>,
+    pub attach: Vec<PathBuf>,
+    pub system: Option<String>,
+    /// `auto`, `public` or `tunnel`.
+    pub via: String,
+    pub no_stream: bool,
+    /// Seconds the gateway may go silent before the request fails.
+    pub timeout: u64,
+}
+
+/// A file sent along with a message.
+pub(crate) struct Attachment {
+    name: String,
+    kind: AttachmentKind,
+}
+
+enum AttachmentKind {
+    Text(String),
+    /// MIME type and raw bytes.
+    Image(&'static str, Vec<u8>),
+}
+
+fn image_mime(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    match ext.as_str() {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "gif" => Some("image/gif"),
+        "webp" => Some("image/webp"),
+        _ => None,
+    }
+}
+
+fn load_attachment(path: &Path) -> Result<Attachment> {
+    let size = std::fs::metadata(path)
+        .with_context(|| format!("Cannot read attachment {}", path.display()))?
+        .len();
+    if size > MAX_ATTACHMENT_BYTES {
+        bail!(
+            "Attachment {} is {size} bytes; the limit is {MAX_ATTACHMENT_BYTES}",
+            path.display()
+        );
+    }
+    let data = std::fs::read(path)?;
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string());
+    let kind = match image_mime(path) {
+        Some(mime) => AttachmentKind::Image(mime, data),
+        None => AttachmentKind::Text(String::from_utf8(data).map_err(|_| {
+            anyhow::anyhow!(
+                "Attachment {} is neither text nor a PNG/JPEG/GIF/WebP image",
+                path.display()
+            )
+        })?),
+    };
+    Ok(Attachment { name, kind })
+}
+
+/// A user message. Text attachments are inlined after the prompt; images
+/// turn the content into OpenAI-style content parts.
+pub(crate) fn user_message(text: &str, attachments: &[Attachment]) -> Value {
+    let mut body = text.to_string();
+    let mut images = Vec::new();
+    for attachment in attachments {
+        match &attachment.kind {
+            AttachmentKind::Text(content) => {
+                body.push_str(&format!(
+                    "\n\n`{}`:\n```\n{}\n```",
+                    attachment.name,
+                    content.trim_end()
+                ));
+            }
+            AttachmentKind::Image(mime, data) => images.push(json!({
+                "type": "image_url",
+                "image_url": {
+                    "url": format!(
+                        "data:{mime};base64,{}",
+                        base64::engine::general_purpose::STANDARD.encode(data)
+                    )
+                }
+            })),
+        }
+    }
+    if images.is_empty() {
+        return json!({ "role": "user", "content": body });
+    }
+    let mut parts = vec![json!({ "type": "text", "text": body })];
+    parts.extend(images);
+    json!({ "role": "user", "content": parts })
+}
+
+#[derive(Debug, PartialEq)]
+enum SseLine {
+    Delta(String),
+    Done,
+}
+
+/// Parse one line of a streamed chat completion.
+fn parse_sse_line(line: &str) -> Result<Option<SseLine>> {
+    let Some(data) = line.strip_prefix("data:").map(str::trim) else {
+        return Ok(None);
+    };
+    if data == "[DONE]" {
+        return Ok(Some(SseLine::Done));
+    }
+    let chunk: Value = serde_json::from_str(data).context("Malformed stream chunk")?;
+    if let Some(message) = chunk["error"]["message"].as_str() {
+        bail!("Gateway error: {message}");
+    }
+    Ok(chunk["choices"][0]["delta"]["content"]
+        .as_str()
+        .filter(|s| !s.is_empty())
+        .map(|s| SseLine::Delta(s.to_string())))
+}
+
+fn clean_agent(agent: &str) -> Result<&str> {
+    let agent = agent.trim();
+    if agent.is_empty()
+        || agent.len() > 80
+        || !agent
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+    {
+        bail!("--agent may only contain letters, numbers, dots, underscores, and hyphens.");
+    }
+    Ok(agent)
+}
+
+struct Gateway {
+    base_url: String,
+    token: String,
+    client: reqwest::Client,
+}
+
+/// Forward an ephemeral local port to the gateway over SSH for the rest of
+/// the process.
+fn open_tunnel(target: &FleetTarget) -> Result<u16> {
+    let listener = std::net::TcpListener::bind(("127.0.0.1", 0))?;
+    let port = listener.local_addr()?.port();
+    let (ip, key, user) = (target.ip.clone(), target.key.clone(), target.ssh_user());
+    std::thread::spawn(move || {
+        if let Err(e) = clawmacdo_ssh::forward_local_as(
+            &ip,
+            &key,
+            user,
+            listener,
+            "127.0.0.1",
+            GATEWAY_PORT,
+            &|_| {},
+        ) {
+            eprintln!("SSH tunnel to {ip} closed: {e}");
+        }
+    });
+    Ok(port)
+}
+
+async fn healthy(client: &reqwest::Client, url: &str) -> bool {
+    client
+        .get(format!("{url}/health"))
+        .timeout(Duration::from_secs(5))
+        .send()
+        .await
+        .is_ok_and(|resp| resp.status().is_success())
+}
+
+async fn connect(target: &FleetTarget, via: &str, timeout: u64) -> Result<Gateway> {
+    let user = target.ssh_user();
+    let token = openclaw_gateway_url::gateway_token(&target.ip, &target.key, user)
+        .await
+        .context("Failed to read the gateway token")?;
+    if token.is_empty() {
+        bail!(
+            "No gateway.auth.token in openclaw.json on {}. Set one with `clawmacdo openclaw-gateway-token --instance {}`.",
+            target.hostname,
+            target.hostname
+        );
+    }
+    // An idle timeout rather than a total one, so a long streamed reply is
+    // not cut off while tokens are still arriving.
+    let client = reqwest::Client::builder()
+        .connect_timeout(Duration::from_secs(10))
+        .read_timeout(Duration::from_secs(timeout))
+        .build()?;
+
+    let mut base_url = None;
+    if via != "tunnel" {
+        match openclaw_gateway_url::public_url(&target.ip, &target.key, user).await {
+            Some(url) if via == "public" || healthy(&client, &url).await => base_url = Some(url),
+            Some(url) => eprintln!("{url} is not reachable; using an SSH tunnel."),
+            None if via == "public" => bail!(
+                "No public gateway URL for {}. Use --via tunnel, or publish it with `clawmacdo expose`.",
+                target.hostname
+            ),
+            None => {}
+        }
+    }
+    let base_url = match base_url {
+        Some(url) => url,
+        None => format!("http://127.0.0.1:{}", open_tunnel(target)?),
+    };
+    Ok(Gateway {
+        base_url,
+        token,
+        client,
+    })
+}
+
+/// Send the conversation and return the reply, printing it as it streams.
+async fn complete(
+    gateway: &Gateway,
+    agent: &str,
+    messages: &[Value],
+    stream: bool,
+) -> Result<String> {
+    let resp = gateway
+        .client
+        .post(format!("{}/v1/chat/completions", gateway.base_url))
+        .bearer_auth(&gateway.token)
+        .header("x-openclaw-agent-id", agent)
+        .json(&json!({
+            "model": format!("openclaw:{agent}"),
+            "messages": messages,
+            "stream": stream,
+        }))
+        .send()
+        .await
+        .context("Gateway request failed")?;
+    let status = resp.status();
+    if !status.is_success() {
+        let body = resp.text().await.unwrap_or_default();
+        let hint = if status == reqwest::StatusCode::NOT_FOUND {
+            " (is gateway.http.endpoints.chatCompletions.enabled set?)"
+        } else {
+            ""
+        };
+        bail!(
+            "Gateway returned {status}{hint}: {}",
+            body.chars().take(300).collect::<String>()
+        );
+    }
+
+    if !stream {
+        let body: Value = resp.json().await?;
+        let reply = body["choices"][0]["message"]["content"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        println!("{reply}");
+        return Ok(reply);
+    }
+
+    let mut resp = resp;
+    let mut reply = String::new();
+    let mut pending: Vec<u8> = Vec::new();
+    let mut stdout = std::io::stdout();
+    'read: while let Some(chunk) = resp.chunk().await.context("Reply stream failed")? {
+        pending.extend_from_slice(&chunk);
+        while let Some(end) = pending.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = pending.drain(..=end).collect();
+            match parse_sse_line(String::from_utf8_lossy(&line).trim())? {
+                Some(SseLine::Delta(text)) => {
+                    print!("{text}");
+                    let _ = stdout.flush();
+                    reply.push_str(&text);
+                }
+                Some(SseLine::Done) => break 'read,
+                None => {}
+            }
+        }
+    }
+    println!();
+    Ok(reply)
+}
+
+/// `chat <instance>`: talk to an OpenClaw agent through the gateway's
+/// OpenAI-compatible endpoint.
+pub async fn run(params: ChatParams) -> Result<()> {
+    let agent = clean_agent(&params.agent)?.to_string();
+    let mut attachments = params
+        .attach
+        .iter()
+        .map(|path| load_attachment(path))
+        .collect::<Result<Vec<_>>>()?;
+    let target = fleet::find(&params.instance)?;
+    let gateway = connect(&target, &params.via, params.timeout).await?;
+    let stream = !params.no_stream;
+
+    let mut messages: Vec<Value> = Vec::new();
+    if let Some(system) = &params.system {
+        messages.push(json!({ "role": "system", "content": system }));
+    }
+
+    if let Some(prompt) = params.once {
+        let prompt = if prompt == "-" {
+            let mut input = String::new();
+            std::io::stdin().read_to_string(&mut input)?;
+            input
+        } else {
+            prompt
+        };
+        if prompt.trim().is_empty() {
+            bail!("Prompt cannot be empty.");
+        }
+        messages.push(user_message(&prompt, &attachments));
+        let reply = complete(&gateway, &agent, &messages, stream).await?;
+        if reply.trim().is_empty() {
+            bail!("Agent '{agent}' on {} did not respond", target.hostname);
+        }
+        return Ok(());
+    }
+
+    eprintln!(
+        "Chatting with agent '{agent}' on {} via {}.\nCommands: /attach <file>, /reset, /exit",
+        target.hostname, gateway.base_url
+    );
+    let stdin = std::io::stdin();
+    loop {
+        print!("{} ", style(">").cyan().bold());
+        std::io::stdout().flush()?;
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line)? == 0 {
+            println!();
+            break;
+        }
+        let line = line.trim();
+        match line
+            .split_once(' ')
+            .map_or((line, ""), |(c, rest)| (c, rest.trim()))
+        {
+            ("", _) => continue,
+            ("/exit" | "/quit", _) => break,
+            ("/reset", _) => {
+                messages.retain(|m| m["role"] == "system");
+                attachments.clear();
+                eprintln!("History cleared.");
+                continue;
+            }
+            ("/attach", path) if !path.is_empty() => {
+                match load_attachment(Path::new(path)) {
+                    Ok(attachment) => {
+                        eprintln!("Attached {} to the next message.", attachment.name);
+                        attachments.push(attachment);
+                    }
+                    Err(e) => eprintln!("{e:#}"),
+                }
+                continue;
+            }
+            _ => {}
+        }
+
+        messages.push(user_message(line, &attachments));
+        match complete(&gateway, &agent, &messages, stream).await {
+            Ok(reply) => {
+                attachments.clear();
+                messages.push(json!({ "role": "assistant", "content": reply }));
+            }
+            Err(e) => {
+                messages.pop();
+                eprintln!("{} {e:#}", style("error:").red());
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clawmacdo_core::test_http::{self, Request, Response};
+
+    #[test]
+    fn stream_lines_are_parsed() {
+        assert_eq!(
+            parse_sse_line(r#"data: {"choices":[{"delta":{"content":"Hel"}}]}"#).unwrap(),
+            Some(SseLine::Delta("Hel".into()))
+        );
+        assert_eq!(
+            parse_sse_line(r#"data: {"choices":[{"delta":{"role":"assistant"}}]}"#).unwrap(),
+            None
+        );
+        assert_eq!(parse_sse_line("data: [DONE]").unwrap(), Some(SseLine::Done));
+        assert_eq!(parse_sse_line(": keep-alive").unwrap(), None);
+        assert!(parse_sse_line(r#"data: {"error":{"message":"agent busy"}}"#).is_err());
+    }
+
+    #[test]
+    fn attachments_become_message_content() {
+        let text = Attachment {
+            name: "notes.md".into(),
+            kind: AttachmentKind::Text("- item\n".into()),
+        };
+        assert_eq!(
+            user_message("Summarise", &[text]),
+            json!({ "role": "user", "content": "Summarise\n\n`notes.md`:\n```\n- item\n```" })
+        );
+
+        let image = Attachment {
+            name: "a.png".into(),
+            kind: AttachmentKind::Image("image/png", vec![1, 2, 3]),
+        };
+        let message = user_message("What is this?", &[image]);
+        assert_eq!(message["content"][0]["text"], "What is this?");
+        assert_eq!(
+            message["content"][1]["image_url"]["url"],
+            "data:image/png;base64,AQID"
+        );
+        assert!(clean_agent("main").is_ok());
+        assert!(clean_agent("../x").is_err());
+    }
+
+    /// Serve one canned streamed completion and hand back the request.
+    async fn stand_in(body: &'static str) -> (String, tokio::sync::oneshot::Receiver<Request>) {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let tx = std::sync::Mutex::new(Some(tx));
+        let addr = test_http::serve(move |req| {
+            if let Some(tx) = tx.lock().unwrap().take() {
+                let _ = tx.send(req);
+            }
+            Response::new("200 OK", body).header("Content-Type", "text/event-stream")
+        })
+        .await;
+        (format!("http://{addr}"), rx)
+    }
+
+    #[tokio::test]
+    async fn streamed_reply_is_collected() {
+        let (base_url, request) = stand_in(
+            "data: {\"choices\":[{\"delta\":{\"content\":\"Hello\"}}]}\n\n\
+             data: {\"choices\":[{\"delta\":{\"content\":\" there\"}}]}\n\n\
+             data: [DONE]\n\n",
+        )
+        .await;
+        let gateway = Gateway {
+            base_url,
+            token: "gw-token".into(),
+            client: reqwest::Client::new(),
+        };
+        let messages = [user_message("hi", &[])];
+        let reply = complete(&gateway, "ops", &messages, true).await.unwrap();
+        assert_eq!(reply, "Hello there");
+
+        let request = request.await.unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/v1/chat/completions");
+        assert_eq!(request.header("authorization"), Some("Bearer gw-token"));
+        assert_eq!(request.header("x-openclaw-agent-id"), Some("ops"));
+        let body = request.json();
+        assert_eq!(body["model"], "openclaw:ops");
+        assert_eq!(body["stream"], true);
+    }
+}
@@ -1,3 +1,4 @@
+use super::{dns, tailnet};
 use anyhow::{bail, Context, Result};
 use chrono::Utc;
 #[cfg(feature = "byteplus")]
@@ -7,12 +8,14 @@ use clawmacdo_cloud::digitalocean::DoClient;
 #[cfg(feature = "lightsail")]
 use clawmacdo_cloud::lightsail_cli::LightsailCliProvider;
 use clawmacdo_cloud::tencent::TencentClient;
+use clawmacdo_core::archive;
+use clawmacdo_core::backup_crypto;
 use clawmacdo_core::config::{self, CloudProviderType, DeployRecord};
 use clawmacdo_db as db;
+use clawmacdo_provision::extensions::{ExtensionStep, Phase};
 use clawmacdo_provision::{self as provision, ProvisionOpts};
 use clawmacdo_ssh as ssh;
 use clawmacdo_ui::{progress, ui};
-use std::path::Component;
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use tokio::sync::mpsc;
@@ -62,6 +65,10 @@ pub struct DeployParams {
     pub profile: String,
     pub spot: bool,
     pub openclaw_version: String,
+    /// Name or image ID of a `clawmacdo bake` image to boot from.
+    pub image: Option<String>,
+    /// User-supplied provisioning steps, see `provision::extensions`.
+    pub extensions: Vec<ExtensionStep>,
     pub non_interactive: bool,
     pub progress_tx: Option<mpsc::UnboundedSender<String>>,
     pub db: Option<Db>,
@@ -103,6 +110,31 @@ fn record_step_skipped(db: &Option<Db>, deploy_id: &str, step: i32) {
     }
 }
 
+/// Run the post-gateway extension steps once the gateway is configured.
+async fn run_post_gateway_extensions(
+    ip: &str,
+    key: &std::path::Path,
+    ssh_user: &str,
+    extensions: &[ExtensionStep],
+    step_db: &Option<Db>,
+    deploy_id: &str,
+    tx: &Option<mpsc::UnboundedSender<String>>,
+) -> Result<()> {
+    let (on_step, on_step_done) = make_step_callbacks(step_db, deploy_id);
+    provision::extensions::run_phase(
+        ip,
+        key,
+        ssh_user,
+        extensions,
+        Phase::PostGateway,
+        tx,
+        &on_step,
+        &on_step_done,
+    )
+    .await?;
+    Ok(())
+}
+
 fn make_step_callbacks(
     step_db: &Option<Db>,
     deploy_id: &str,
@@ -318,46 +350,10 @@ fn require_public_ip(public_ip: Option<String>, resource: &str) -> Result<String
     public_ip.ok_or_else(|| anyhow::anyhow!("{resource} did not expose a public IP"))
 }
 
-fn validate_backup_archive(path: &std::path::Path) -> Result<()> {
+pub(crate) fn validate_backup_archive(path: &std::path::Path) -> Result<()> {
     let file = std::fs::File::open(path)
         .with_context(|| format!("Failed to open backup archive {}", path.display()))?;
-    let decoder = flate2::read::GzDecoder::new(file);
-    let mut archive = tar::Archive::new(decoder);
-    let mut saw_entries = false;
-
-    for entry in archive
-        .entries()
-        .context("Failed to read backup archive entries")?
-    {
-        let entry = entry.context("Failed to parse backup archive entry")?;
-        let entry_path = entry.path().context("Failed to read backup archive path")?;
-
-        if entry_path.is_absolute() {
-            bail!(
-                "Backup archive contains an absolute path: {}",
-                entry_path.display()
-            );
-        }
-
-        for component in entry_path.components() {
-            if matches!(
-                component,
-                Component::ParentDir | Component::RootDir | Component::Prefix(_)
-            ) {
-                bail!(
-                    "Backup archive contains an unsafe path: {}",
-                    entry_path.display()
-                );
-            }
-        }
-
-        saw_entries = true;
-    }
-
-    if !saw_entries {
-        bail!("Backup archive is empty")
-    }
-
+    archive::validate_tar_gz(file).context("Backup archive failed validation")?;
     Ok(())
 }
 
@@ -424,7 +420,7 @@ fn bundled_extensions_copy_cmd(home: &str) -> String {
 }
 
 /// Resolve the cloud provider type from the --provider flag.
-fn resolve_provider(provider: &str) -> Result<CloudProviderType> {
+pub(crate) fn resolve_provider(provider: &str) -> Result<CloudProviderType> {
     match provider {
         "digitalocean" | "do" => Ok(CloudProviderType::DigitalOcean),
         "lightsail" | "aws" => Ok(CloudProviderType::Lightsail),
@@ -442,12 +438,28 @@ pub async fn run(params: DeployParams) -> Result<DeployRecord> {
         Some(hostname) => config::normalize_hostname(hostname).map_err(anyhow::Error::from)?,
         None => None,
     };
+    if params.tailscale
+        && params
+            .tailscale_auth_key
+            .as_deref()
+            .is_none_or(|k| k.trim().is_empty())
+    {
+        let name = params.hostname.as_deref().unwrap_or("new deployment");
+        if let Some(auth_key) = tailnet::mint_auth_key(name).await? {
+            progress::emit(
+                &params.progress_tx,
+                "  Minted a tagged, single-use Tailscale auth key.",
+            );
+            params.tailscale_auth_key = Some(auth_key);
+        }
+    }
 
     let provider = resolve_provider(&params.provider)?;
+    let baked = resolve_baked_image(&mut params, &provider)?;
     match provider {
-        CloudProviderType::DigitalOcean => run_do(params).await,
+        CloudProviderType::DigitalOcean => run_do(params, baked).await,
         #[cfg(feature = "lightsail")]
-        CloudProviderType::Lightsail => run_lightsail(params).await,
+        CloudProviderType::Lightsail => run_lightsail(params, baked).await,
         #[cfg(not(feature = "lightsail"))]
         CloudProviderType::Lightsail => {
             bail!("Lightsail support not compiled in. Build with --features lightsail")
@@ -460,7 +472,7 @@ pub async fn run(params: DeployParams) -> Result<DeployRecord> {
             bail!("Azure support not compiled in. Build with --features azure")
         }
         #[cfg(feature = "byteplus")]
-        CloudProviderType::BytePlus => run_byteplus(params).await,
+        CloudProviderType::BytePlus => run_byteplus(params, baked).await,
         #[cfg(not(feature = "byteplus"))]
         CloudProviderType::BytePlus => {
             bail!("BytePlus support not compiled in. Build with --features byteplus")
@@ -468,11 +480,57 @@ pub async fn run(params: DeployParams) -> Result<DeployRecord> {
     }
 }
 
+/// Look up `--image` and default the deploy region to the image's region.
+fn resolve_baked_image(
+    params: &mut DeployParams,
+    provider: &CloudProviderType,
+) -> Result<Option<db::BakedImageRow>> {
+    let Some(query) = params
+        .image
+        .as_deref()
+        .map(str::trim)
+        .filter(|q| !q.is_empty())
+    else {
+        return Ok(None);
+    };
+    if matches!(
+        provider,
+        CloudProviderType::Tencent | CloudProviderType::Azure
+    ) {
+        bail!("--image is not supported on {provider}; use digitalocean, lightsail or byteplus");
+    }
+    let region = match provider {
+        CloudProviderType::Lightsail => Some(params.aws_region.as_str()),
+        _ => params.region.as_deref(),
+    };
+    let image = super::bake::resolve(query, provider, region)?;
+    if params.region.is_none() && !matches!(provider, CloudProviderType::Lightsail) {
+        params.region = Some(image.region.clone());
+    }
+    progress::emit(
+        &params.progress_tx,
+        &format!(
+            "  Using baked image '{}' ({}, OpenClaw {})",
+            image.name, image.image_id, image.openclaw_version
+        ),
+    );
+    if image.openclaw_version != params.openclaw_version {
+        progress::emit(
+            &params.progress_tx,
+            &format!(
+                "  Image was baked with OpenClaw {}; {} will be installed over it.",
+                image.openclaw_version, params.openclaw_version
+            ),
+        );
+    }
+    Ok(Some(image))
+}
+
 // ══════════════════════════════════════════════════════════════════════════
 // DigitalOcean deploy (unchanged from original)
 // ══════════════════════════════════════════════════════════════════════════
 
-async fn run_do(params: DeployParams) -> Result<DeployRecord> {
+async fn run_do(params: DeployParams, baked: Option<db::BakedImageRow>) -> Result<DeployRecord> {
     config::ensure_dirs()?;
     let deploy_id = params
         .deploy_id
@@ -509,7 +567,10 @@ async fn run_do(params: DeployParams) -> Result<DeployRecord> {
     let backup_path = if params.non_interactive {
         params.backup
     } else {
-        params.backup.or_else(|| ui::prompt_backup().ok().flatten())
+        match params.backup {
+            Some(path) => Some(path),
+            None => ui::prompt_backup().await.ok().flatten(),
+        }
     };
 
     progress::emit(tx, "  Provider: DigitalOcean");
@@ -568,19 +629,38 @@ async fn run_do(params: DeployParams) -> Result<DeployRecord> {
     if has_value(&anthropic_setup_token) {
         progress::emit(tx, "  Detected Anthropic setup token (sk-ant-oat...).");
     }
-    let user_data = cloud_init::generate();
-    let droplet = do_client
-        .create_droplet(
-            &hostname,
-            &region,
-            &size,
-            key_info.id,
-            &user_data,
-            params.enable_backups,
-            &params.customer_email,
-        )
-        .await
-        .context("Failed to create droplet")?;
+    let droplet = match &baked {
+        Some(image) => {
+            let snapshot_id: u64 = image.image_id.parse().with_context(|| {
+                format!("Invalid DigitalOcean snapshot ID '{}'", image.image_id)
+            })?;
+            do_client
+                .create_droplet_from_snapshot(
+                    &hostname,
+                    &region,
+                    &size,
+                    snapshot_id,
+                    key_info.id,
+                    params.enable_backups,
+                    &params.customer_email,
+                )
+                .await
+        }
+        None => {
+            do_client
+                .create_droplet(
+                    &hostname,
+                    &region,
+                    &size,
+                    key_info.id,
+                    &cloud_init::generate(),
+                    params.enable_backups,
+                    &params.customer_email,
+                )
+                .await
+        }
+    }
+    .context("Failed to create droplet")?;
     let droplet_id = droplet.id;
     progress::emit(tx, &format!("  Droplet created: ID {droplet_id}"));
     record_step_complete(step_db, &deploy_id, 4);
@@ -614,6 +694,8 @@ async fn run_do(params: DeployParams) -> Result<DeployRecord> {
         &params.profile,
         &params.progress_tx,
         &params.db,
+        baked.as_ref().map(|b| b.openclaw_version.as_str()),
+        &params.extensions,
     )
     .await;
 
@@ -666,7 +748,10 @@ async fn run_tencent(params: DeployParams) -> Result<DeployRecord> {
     let backup_path = if params.non_interactive {
         params.backup
     } else {
-        params.backup.or_else(|| ui::prompt_backup().ok().flatten())
+        match params.backup {
+            Some(path) => Some(path),
+            None => ui::prompt_backup().await.ok().flatten(),
+        }
     };
 
     progress::emit(tx, "  Provider: Tencent Cloud");
@@ -797,11 +882,12 @@ async fn run_tencent(params: DeployParams) -> Result<DeployRecord> {
         record_step_start(step_db, &deploy_id, 8, "Upload & restore backup");
         progress::emit(tx, "\n[Step 8/16] Uploading and restoring backup...");
         let sp = ui::spinner("[Step 8/16] Uploading and restoring backup...");
-        validate_backup_archive(bp)?;
+        let plain = backup_crypto::open_for_restore(bp)?;
+        validate_backup_archive(plain.path())?;
         let remote_archive = "/tmp/openclaw_backup.tar.gz";
         let ip_c = ip.clone();
         let key_c = keypair.private_key_path.clone();
-        let bp_c = bp.to_path_buf();
+        let bp_c = plain.path().to_path_buf();
         tokio::task::spawn_blocking(move || ssh::scp_upload(&ip_c, &key_c, &bp_c, remote_archive))
             .await??;
         let extract_cmd = backup_restore_cmd(false);
@@ -833,6 +919,8 @@ async fn run_tencent(params: DeployParams) -> Result<DeployRecord> {
         tailscale: params.tailscale,
         tailscale_auth_key: params.tailscale_auth_key.as_deref(),
         openclaw_version: &params.openclaw_version,
+        baked_openclaw_version: None,
+        extensions: &params.extensions,
         hostname: &hostname,
         ssh_user: None,
         progress_tx: tx.clone(),
@@ -987,10 +1075,21 @@ SVCEOF\n\
     provision::commands::ssh_as_openclaw_with_user_multi_async(&ip_c, &key_c, cmds, "root").await?;
     record_step_complete(step_db, &deploy_id, 15);
 
+    run_post_gateway_extensions(
+        &ip_c,
+        &key_c,
+        "root",
+        &params.extensions,
+        step_db,
+        &deploy_id,
+        tx,
+    )
+    .await?;
+
     // Step 16: Save DeployRecord
     record_step_start(step_db, &deploy_id, 16, "Saving deploy record");
     progress::emit(tx, "\n[Step 16/16] Saving deploy record...");
-    let record = DeployRecord {
+    let mut record = DeployRecord {
         id: deploy_id.to_string(),
         provider: Some(CloudProviderType::Tencent),
         droplet_id: 0, // Not applicable for Tencent
@@ -1005,7 +1104,11 @@ SVCEOF\n\
         resource_group: None,
         backup_restored,
         created_at: Utc::now(),
+        fqdn: None,
     };
+    if let Some(line) = dns::publish(&mut record).await {
+        progress::emit(tx, &line);
+    }
     let record_path = record.save()?;
     progress::emit(tx, &format!("  Saved: {}", record_path.display()));
     progress::emit(tx, "\n[Step 16/16] Done!");
@@ -1019,7 +1122,10 @@ SVCEOF\n\
 // ══════════════════════════════════════════════════════════════════════════
 
 #[cfg(feature = "byteplus")]
-async fn run_byteplus(params: DeployParams) -> Result<DeployRecord> {
+async fn run_byteplus(
+    params: DeployParams,
+    baked: Option<db::BakedImageRow>,
+) -> Result<DeployRecord> {
     config::ensure_dirs()?;
     let deploy_id = params
         .deploy_id
@@ -1043,7 +1149,10 @@ async fn run_byteplus(params: DeployParams) -> Result<DeployRecord> {
     let backup_path = if params.non_interactive {
         params.backup
     } else {
-        params.backup.or_else(|| ui::prompt_backup().ok().flatten())
+        match params.backup {
+            Some(path) => Some(path),
+            None => ui::prompt_backup().await.ok().flatten(),
+        }
     };
 
     progress::emit(tx, "  Provider: BytePlus Cloud");
@@ -1102,23 +1211,39 @@ async fn run_byteplus(params: DeployParams) -> Result<DeployRecord> {
     if has_value(&anthropic_setup_token) {
         progress::emit(tx, "  Detected Anthropic setup token (sk-ant-oat...).");
     }
-    let user_data = cloud_init::generate();
-    let user_data_b64 =
-        base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &user_data);
     if params.spot {
         progress::emit(tx, "  Spot instance: enabled (SpotAsPriceGo)");
     }
-    let instance_id = bp_client
-        .create_instance(
-            &hostname,
-            &size,
-            &key_name,
-            &user_data_b64,
-            &params.customer_email,
-            params.spot,
-        )
-        .await
-        .context("Failed to create BytePlus ECS instance")?;
+    let instance_id = match &baked {
+        Some(image) => {
+            bp_client
+                .create_instance_from_image(
+                    &hostname,
+                    &image.image_id,
+                    &size,
+                    &key_name,
+                    &params.customer_email,
+                    params.spot,
+                )
+                .await
+        }
+        None => {
+            let user_data = cloud_init::generate();
+            let user_data_b64 =
+                base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &user_data);
+            bp_client
+                .create_instance(
+                    &hostname,
+                    &size,
+                    &key_name,
+                    &user_data_b64,
+                    &params.customer_email,
+                    params.spot,
+                )
+                .await
+        }
+    }
+    .context("Failed to create BytePlus ECS instance")?;
     progress::emit(tx, &format!("  Instance created: {instance_id}"));
     record_step_complete(step_db, &deploy_id, 4);
 
@@ -1205,11 +1330,12 @@ fi"#;
         record_step_start(step_db, &deploy_id, 8, "Upload & restore backup");
         progress::emit(tx, "\n[Step 8/16] Uploading and restoring backup...");
         let sp = ui::spinner("[Step 8/16] Uploading and restoring backup...");
-        validate_backup_archive(bp)?;
+        let plain = backup_crypto::open_for_restore(bp)?;
+        validate_backup_archive(plain.path())?;
         let remote_archive = "/tmp/openclaw_backup.tar.gz";
         let ip_c = ip.clone();
         let key_c = keypair.private_key_path.clone();
-        let bp_c = bp.to_path_buf();
+        let bp_c = plain.path().to_path_buf();
         tokio::task::spawn_blocking(move || ssh::scp_upload(&ip_c, &key_c, &bp_c, remote_archive))
             .await??;
         let extract_cmd = backup_restore_cmd(false);
@@ -1241,6 +1367,8 @@ fi"#;
         tailscale: params.tailscale,
         tailscale_auth_key: params.tailscale_auth_key.as_deref(),
         openclaw_version: &params.openclaw_version,
+        baked_openclaw_version: baked.as_ref().map(|b| b.openclaw_version.as_str()),
+        extensions: &params.extensions,
         hostname: &hostname,
         ssh_user: None,
         progress_tx: tx.clone(),
@@ -1393,10 +1521,21 @@ SVCEOF\n\
     provision::commands::ssh_as_openclaw_with_user_multi_async(&ip_c, &key_c, cmds, "root").await?;
     record_step_complete(step_db, &deploy_id, 15);
 
+    run_post_gateway_extensions(
+        &ip_c,
+        &key_c,
+        "root",
+        &params.extensions,
+        step_db,
+        &deploy_id,
+        tx,
+    )
+    .await?;
+
     // Step 16: Save DeployRecord
     record_step_start(step_db, &deploy_id, 16, "Saving deploy record");
     progress::emit(tx, "\n[Step 16/16] Saving deploy record...");
-    let record = DeployRecord {
+    let mut record = DeployRecord {
         id: deploy_id.to_string(),
         provider: Some(CloudProviderType::BytePlus),
         droplet_id: 0,
@@ -1411,7 +1550,11 @@ SVCEOF\n\
         resource_group: None,
         backup_restored,
         created_at: Utc::now(),
+        fqdn: None,
     };
+    if let Some(line) = dns::publish(&mut record).await {
+        progress::emit(tx, &line);
+    }
     let record_path = record.save()?;
     progress::emit(tx, &format!("  Saved: {}", record_path.display()));
     progress::emit(tx, "\n[Step 16/16] Done!");
@@ -1454,6 +1597,8 @@ async fn deploy_steps_5_through_16(
     profile: &str,
     progress_tx: &Option<mpsc::UnboundedSender<String>>,
     step_db: &Option<Db>,
+    baked_openclaw_version: Option<&str>,
+    extensions: &[ExtensionStep],
 ) -> Result<DeployRecord> {
     let tx = progress_tx;
 
@@ -1514,11 +1659,12 @@ async fn deploy_steps_5_through_16(
     if let Some(bp) = backup_path {
         progress::emit(tx, "\n[Step 8/16] Uploading and restoring backup...");
         let sp = ui::spinner("[Step 8/16] Uploading and restoring backup...");
-        validate_backup_archive(bp)?;
+        let plain = backup_crypto::open_for_restore(bp)?;
+        validate_backup_archive(plain.path())?;
         let remote_archive = "/tmp/openclaw_backup.tar.gz";
         let ip_c = ip.clone();
         let key_c = private_key_path.to_path_buf();
-        let bp_c = bp.to_path_buf();
+        let bp_c = plain.path().to_path_buf();
         tokio::task::spawn_blocking(move || ssh::scp_upload(&ip_c, &key_c, &bp_c, remote_archive))
             .await??;
         let extract_cmd = backup_restore_cmd(false);
@@ -1550,6 +1696,8 @@ async fn deploy_steps_5_through_16(
         tailscale,
         tailscale_auth_key,
         openclaw_version,
+        baked_openclaw_version,
+        extensions,
         hostname,
         ssh_user: None,
         progress_tx: tx.clone(),
@@ -1669,10 +1817,12 @@ fs.writeFileSync(p,JSON.stringify(cfg,null,2)+\"\\n\");' && echo ok"
     provision::commands::ssh_as_openclaw_with_user_multi_async(&ip_c, &key_c, cmds, "root").await?;
     record_step_complete(step_db, deploy_id, 15);
 
+    run_post_gateway_extensions(&ip_c, &key_c, "root", extensions, step_db, deploy_id, tx).await?;
+
     // Step 16: Save DeployRecord
     record_step_start(step_db, deploy_id, 16, "Saving deploy record");
     progress::emit(tx, "\n[Step 16/16] Saving deploy record...");
-    let record = DeployRecord {
+    let mut record = DeployRecord {
         id: deploy_id.to_string(),
         provider: Some(CloudProviderType::DigitalOcean),
         droplet_id,
@@ -1687,7 +1837,11 @@ fs.writeFileSync(p,JSON.stringify(cfg,null,2)+\"\\n\");' && echo ok"
         resource_group: None,
         backup_restored,
         created_at: Utc::now(),
+        fqdn: None,
     };
+    if let Some(line) = dns::publish(&mut record).await {
+        progress::emit(tx, &line);
+    }
     let record_path = record.save()?;
     progress::emit(tx, &format!("  Saved: {}", record_path.display()));
     progress::emit(tx, "\n[Step 16/16] Done!");
@@ -1701,7 +1855,10 @@ fs.writeFileSync(p,JSON.stringify(cfg,null,2)+\"\\n\");' && echo ok"
 // ══════════════════════════════════════════════════════════════════════════
 
 #[cfg(feature = "lightsail")]
-async fn run_lightsail(params: DeployParams) -> Result<DeployRecord> {
+async fn run_lightsail(
+    params: DeployParams,
+    baked: Option<db::BakedImageRow>,
+) -> Result<DeployRecord> {
     use clawmacdo_cloud::CloudProvider;
 
     config::ensure_dirs()?;
@@ -1778,19 +1935,31 @@ async fn run_lightsail(params: DeployParams) -> Result<DeployRecord> {
     record_step_start(step_db, &deploy_id, 6, "Creating Lightsail instance");
     progress::emit(tx, "\n[Step 6/16] Creating Lightsail instance...");
 
-    let create_params = clawmacdo_cloud::cloud_provider::CreateInstanceParams {
-        name: hostname.to_string(),
-        region: region.clone(),
-        size: size.to_string(),
-        image: "ubuntu_24_04".to_string(), // Ubuntu 24.04 LTS
-        ssh_key_id: key_name.clone(),
-        user_data,
-        tags: vec![],
-        customer_email: params.customer_email.clone(),
+    let instance_id = match &baked {
+        Some(image) => {
+            lightsail.create_instance_from_snapshot(
+                &hostname,
+                &image.image_id,
+                &lightsail.get_bundle_id(&size),
+                &key_name,
+            )?;
+            hostname.clone()
+        }
+        None => {
+            let create_params = clawmacdo_cloud::cloud_provider::CreateInstanceParams {
+                name: hostname.to_string(),
+                region: region.clone(),
+                size: size.to_string(),
+                image: "ubuntu_24_04".to_string(), // Ubuntu 24.04 LTS
+                ssh_key_id: key_name.clone(),
+                user_data,
+                tags: vec![],
+                customer_email: params.customer_email.clone(),
+            };
+            lightsail.create_instance(create_params).await?.id
+        }
     };
-
-    let instance_info = lightsail.create_instance(create_params).await?;
-    progress::emit(tx, &format!("  → Instance ID: {}", instance_info.id));
+    progress::emit(tx, &format!("  → Instance ID: {instance_id}"));
     record_step_complete(step_db, &deploy_id, 6);
 
     // Step 7: Wait for instance to become active
@@ -1802,7 +1971,7 @@ async fn run_lightsail(params: DeployParams) -> Result<DeployRecord> {
     );
     progress::emit(tx, "\n[Step 7/16] Waiting for instance to become active...");
     let instance_info = lightsail
-        .wait_for_active(&instance_info.id, 600) // 10 minute timeout
+        .wait_for_active(&instance_id, 600) // 10 minute timeout
         .await?;
 
     let ip = instance_info
@@ -1859,11 +2028,12 @@ async fn run_lightsail(params: DeployParams) -> Result<DeployRecord> {
     let backup_restored: Option<String>;
     if let Some(bp) = params.backup.as_deref() {
         progress::emit(tx, "\n[Step 8/16] Uploading and restoring backup...");
-        validate_backup_archive(bp)?;
+        let plain = backup_crypto::open_for_restore(bp)?;
+        validate_backup_archive(plain.path())?;
         let remote_archive = "/tmp/openclaw_backup.tar.gz";
         let ip_c = ip.clone();
         let key_c = keypair.private_key_path.clone();
-        let bp_c = bp.to_path_buf();
+        let bp_c = plain.path().to_path_buf();
         tokio::task::spawn_blocking(move || {
             ssh::scp_upload_as(&ip_c, &key_c, &bp_c, remote_archive, "ubuntu")
         })
@@ -1901,6 +2071,8 @@ async fn run_lightsail(params: DeployParams) -> Result<DeployRecord> {
         tailscale: params.tailscale,
         tailscale_auth_key: params.tailscale_auth_key.as_deref(),
         openclaw_version: &params.openclaw_version,
+        baked_openclaw_version: baked.as_ref().map(|b| b.openclaw_version.as_str()),
+        extensions: &params.extensions,
         ssh_user: Some("ubuntu"),
         progress_tx: tx.clone(),
         on_step,
@@ -2049,10 +2221,21 @@ SVCEOF\n\
     provision::commands::ssh_as_openclaw_with_user_multi_async(&ip_c, &key_c, cmds, "ubuntu")
         .await?;
 
+    run_post_gateway_extensions(
+        &ip_c,
+        &key_c,
+        "ubuntu",
+        &params.extensions,
+        step_db,
+        &deploy_id,
+        tx,
+    )
+    .await?;
+
     // Step 16: Save DeployRecord
     record_step_start(step_db, &deploy_id, 16, "Saving deploy record");
     progress::emit(tx, "\n[Step 16/16] Saving deploy record...");
-    let record = DeployRecord {
+    let mut record = DeployRecord {
         id: deploy_id,
         provider: Some(CloudProviderType::Lightsail),
         droplet_id: 0, // Not applicable for Lightsail
@@ -2067,7 +2250,11 @@ SVCEOF\n\
         resource_group: None,
         backup_restored,
         created_at: Utc::now(),
+        fqdn: None,
     };
+    if let Some(line) = dns::publish(&mut record).await {
+        progress::emit(tx, &line);
+    }
     let record_path = record.save()?;
     progress::emit(tx, &format!("  Saved: {}", record_path.display()));
     progress::emit(tx, "\n[Step 16/16] Done!");
@@ -2161,7 +2348,10 @@ async fn run_azure(params: DeployParams) -> Result<DeployRecord> {
     let backup_path = if params.non_interactive {
         params.backup
     } else {
-        params.backup.or_else(|| ui::prompt_backup().ok().flatten())
+        match params.backup {
+            Some(path) => Some(path),
+            None => ui::prompt_backup().await.ok().flatten(),
+        }
     };
     let resource_group = format!("clawmacdo-{}", &deploy_id[..8]);
 
@@ -2287,11 +2477,12 @@ async fn run_azure(params: DeployParams) -> Result<DeployRecord> {
     if let Some(bp) = backup_path.as_deref() {
         record_step_start(step_db, &deploy_id, 8, "Upload & restore backup");
         progress::emit(tx, "\n[Step 8/16] Uploading and restoring backup...");
-        validate_backup_archive(bp)?;
+        let plain = backup_crypto::open_for_restore(bp)?;
+        validate_backup_archive(plain.path())?;
         let remote_archive = "/tmp/openclaw_backup.tar.gz";
         let ip_c = ip.clone();
         let key_c = keypair.private_key_path.clone();
-        let bp_c = bp.to_path_buf();
+        let bp_c = plain.path().to_path_buf();
         tokio::task::spawn_blocking(move || {
             ssh::scp_upload_as(&ip_c, &key_c, &bp_c, remote_archive, "azureuser")
         })
@@ -2326,6 +2517,8 @@ async fn run_azure(params: DeployParams) -> Result<DeployRecord> {
         tailscale: params.tailscale,
         tailscale_auth_key: params.tailscale_auth_key.as_deref(),
         openclaw_version: &params.openclaw_version,
+        baked_openclaw_version: None,
+        extensions: &params.extensions,
         ssh_user: Some("azureuser"),
         progress_tx: tx.clone(),
         on_step,
@@ -2476,10 +2669,21 @@ SVCEOF\n\
         .await?;
     record_step_complete(step_db, &deploy_id, 15);
 
+    run_post_gateway_extensions(
+        &ip_c,
+        &key_c,
+        "azureuser",
+        &params.extensions,
+        step_db,
+        &deploy_id,
+        tx,
+    )
+    .await?;
+
     // Step 16: Save DeployRecord
     record_step_start(step_db, &deploy_id, 16, "Saving deploy record");
     progress::emit(tx, "\n[Step 16/16] Saving deploy record...");
-    let record = DeployRecord {
+    let mut record = DeployRecord {
         id: deploy_id,
         provider: Some(CloudProviderType::Azure),
         droplet_id: 0, // Not applicable for Azure
@@ -2494,7 +2698,11 @@ SVCEOF\n\
         resource_group: Some(resource_group),
         backup_restored,
         created_at: Utc::now(),
+        fqdn: None,
     };
+    if let Some(line) = dns::publish(&mut record).await {
+        progress::emit(tx, &line);
+    }
     let record_path = record.save()?;
     progress::emit(tx, &format!("  Saved: {}", record_path.display()));
     progress::emit(tx, "\n[Step 16/16] Done!");
@@ -44,6 +44,8 @@ pub struct DeployCmdArgs {
     pub profile: String,
     pub spot: bool,
     pub openclaw_version: String,
+    pub image: Option<String>,
+    pub extensions_dir: Option<PathBuf>,
     pub detach: bool,
     pub json: bool,
     /// Pre-assigned deploy ID (from detach re-exec)
@@ -109,6 +111,12 @@ fn spawn_with_configured_retries(cmd: &mut Command) -> Result<()> {
 }
 
 pub async fn run(args: DeployCmdArgs) -> Result<()> {
+    // Validate extension steps before anything is created or detached.
+    let extensions = match &args.extensions_dir {
+        Some(dir) => clawmacdo_provision::extensions::load_dir(dir)?,
+        None => Vec::new(),
+    };
+
     let conn = db::init_db()?;
     let db_handle: deploy::Db = Arc::new(Mutex::new(conn));
 
@@ -237,6 +245,8 @@ pub async fn run(args: DeployCmdArgs) -> Result<()> {
         profile: args.profile,
         spot: args.spot,
         openclaw_version: args.openclaw_version,
+        image: args.image,
+        extensions,
         non_interactive: true,
         progress_tx: None,
         db: Some(db_handle.clone()),
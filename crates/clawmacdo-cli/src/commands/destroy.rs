@@ -1,3 +1,4 @@
+use super::{dns, tailnet};
 use anyhow::{bail, Result};
 use clawmacdo_cloud::digitalocean::DoClient;
 #[cfg(feature = "lightsail")]
@@ -135,6 +136,8 @@ async fn run_do(params: DestroyParams) -> Result<()> {
     );
     client.delete_droplet(droplet.id).await?;
     println!("Droplet deleted.");
+    dns::retract(&droplet.name).await;
+    tailnet::forget(&droplet.name).await;
 
     let hostname_suffix = droplet
         .name
@@ -216,6 +219,8 @@ Deleting instance '{}'...",
     );
     provider.delete_instance(&instance.name).await?;
     println!("Instance deleted.");
+    dns::retract(&instance.name).await;
+    tailnet::forget(&instance.name).await;
 
     let hostname_suffix = instance
         .name
@@ -283,6 +288,8 @@ async fn run_tencent(params: DestroyParams) -> Result<()> {
     );
     client.terminate_instance(&instance.id).await?;
     println!("Instance terminated.");
+    dns::retract(&instance.name).await;
+    tailnet::forget(&instance.name).await;
 
     // Clean up SSH key pair
     let hostname_suffix = instance
@@ -378,6 +385,8 @@ async fn run_azure(params: DestroyParams) -> Result<()> {
     );
     provider.delete_resource_group()?;
     println!("Resource group deletion initiated (--no-wait).");
+    dns::retract(&params.name).await;
+    tailnet::forget(&params.name).await;
 
     // Clean up local key
     let hostname_suffix = params
@@ -450,6 +459,8 @@ async fn run_byteplus(params: DestroyParams) -> Result<()> {
             }
             client.terminate_instance(&inst.id).await?;
             println!("  Terminated.");
+            dns::retract(&inst.name).await;
+            tailnet::forget(&inst.name).await;
         }
 
         // Release any orphaned unbound EIPs
@@ -504,6 +515,8 @@ async fn run_byteplus(params: DestroyParams) -> Result<()> {
 
         client.terminate_instance(&instance.id).await?;
         println!("Instance terminated.");
+        dns::retract(&instance.name).await;
+        tailnet::forget(&instance.name).await;
 
         // Release any orphaned unbound EIPs
         println!("Checking for orphaned EIPs...");
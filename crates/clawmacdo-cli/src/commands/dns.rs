@@ -0,0 +1,101 @@
+use clawmacdo_cloud::dns;
+use clawmacdo_core::config::{self, DeployRecord};
+
+/// Point the record's DNS name (`<hostname>.<zone>` the first time) at its IP
+/// and remember the name on the record; the caller saves it. Returns a line
+/// to show, or `None` when no DNS provider is configured. DNS failures are
+/// reported in the line and never fail the deploy.
+pub(crate) async fn publish(record: &mut DeployRecord) -> Option<String> {
+    let provider = match dns::configured_provider() {
+        Ok(Some(provider)) => provider,
+        Ok(None) => return None,
+        Err(e) => return Some(format!("  warning: DNS not updated: {e}")),
+    };
+    let name = match record.fqdn.clone() {
+        Some(name) => name,
+        None => match dns::fqdn(&record.hostname, provider.zone()) {
+            Ok(name) => name,
+            Err(e) => return Some(format!("  warning: DNS not updated: {e}")),
+        },
+    };
+    let ip = &record.ip_address;
+    match dns::upsert(provider.as_ref(), &name, ip).await {
+        Ok(()) => {
+            record.fqdn = Some(name.clone());
+            Some(format!("  DNS: {name} -> {ip} ({})", provider.name()))
+        }
+        Err(e) => Some(format!("  warning: could not point {name} at {ip}: {e}")),
+    }
+}
+
+/// Remove the DNS records of the destroyed instance `hostname`. Only records
+/// still pointing at its IP are removed, so a name moved to another instance
+/// (e.g. by `migrate`) survives.
+pub(crate) async fn retract(hostname: &str) {
+    let Some(mut record) = find_record(hostname) else {
+        return;
+    };
+    let Some(name) = record.fqdn.clone() else {
+        return;
+    };
+    let provider = match dns::configured_provider() {
+        Ok(Some(provider)) => provider,
+        Ok(None) => {
+            println!("DNS provider not configured; {name} was left in place.");
+            return;
+        }
+        Err(e) => {
+            println!("warning: DNS records for {name} not removed: {e}");
+            return;
+        }
+    };
+    match dns::remove(provider.as_ref(), &name, Some(&record.ip_address)).await {
+        Ok(removed) => {
+            println!("Removed {removed} DNS record(s) for {name}.");
+            record.fqdn = None;
+            let _ = record.save();
+        }
+        Err(e) => println!("warning: DNS records for {name} not removed: {e}"),
+    }
+}
+
+/// Move the source's DNS name onto the migration target, dropping the
+/// target's own name. Both records are saved.
+pub(crate) async fn transfer(
+    source: &mut DeployRecord,
+    target: &mut DeployRecord,
+) -> Option<String> {
+    let name = source.fqdn.clone()?;
+    let provider = match dns::configured_provider() {
+        Ok(Some(provider)) => provider,
+        Ok(None) => {
+            return Some(format!(
+                "  warning: DNS provider not configured; {name} still points at {}",
+                source.ip_address
+            ))
+        }
+        Err(e) => return Some(format!("  warning: DNS not updated: {e}")),
+    };
+    let ip = &target.ip_address;
+    if let Err(e) = dns::upsert(provider.as_ref(), &name, ip).await {
+        return Some(format!("  warning: could not point {name} at {ip}: {e}"));
+    }
+    if let Some(own) = target.fqdn.take().filter(|own| *own != name) {
+        let _ = dns::remove(provider.as_ref(), &own, Some(ip)).await;
+    }
+    target.fqdn = source.fqdn.take();
+    let _ = source.save();
+    let _ = target.save();
+    Some(format!("  DNS: {name} -> {ip} ({})", provider.name()))
+}
+
+/// The deploy record for `hostname`, newest first when several match.
+fn find_record(hostname: &str) -> Option<DeployRecord> {
+    let dir = config::deploys_dir().ok()?;
+    std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| std::fs::read_to_string(entry.ok()?.path()).ok())
+        .filter_map(|contents| serde_json::from_str::<DeployRecord>(&contents).ok())
+        .filter(|record| record.hostname == hostname)
+        .max_by_key(|record| record.created_at)
+}
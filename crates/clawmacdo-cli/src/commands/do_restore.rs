@@ -1,3 +1,4 @@
+use super::dns;
 use anyhow::{bail, Context, Result};
 use chrono::Utc;
 use clawmacdo_cloud::digitalocean::DoClient;
@@ -191,7 +192,7 @@ pub async fn run(params: DoRestoreParams) -> Result<RestoreResult> {
         .context("Failed to update deployment status")?;
 
     // Save JSON deploy record
-    let record = DeployRecord {
+    let mut record = DeployRecord {
         id: deploy_id.clone(),
         provider: Some(CloudProviderType::DigitalOcean),
         droplet_id,
@@ -206,7 +207,11 @@ pub async fn run(params: DoRestoreParams) -> Result<RestoreResult> {
         resource_group: None,
         backup_restored: None,
         created_at: Utc::now(),
+        fqdn: None,
     };
+    if let Some(line) = dns::publish(&mut record).await {
+        progress::emit(tx, &line);
+    }
     let record_path = record.save()?;
 
     progress::emit(tx, "\n--- Restore Complete ---");
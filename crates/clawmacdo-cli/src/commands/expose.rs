@@ -0,0 +1,193 @@
+use super::fleet::{self, FleetTarget};
+use super::tailscale_funnel;
+use anyhow::{bail, Context, Result};
+use clawmacdo_core::config::OPENCLAW_GATEWAY_PORT;
+use clawmacdo_provision::provision::commands::ssh_root_as_async;
+use clawmacdo_provision::provision::reverse_proxy::{self, ProxyServer};
+
+pub struct ExposeParams {
+    /// Deploy ID, hostname, or IP address.
+    pub instance: String,
+    /// `funnel`, `proxy` or `none`; `None` shows the current mode.
+    pub mode: Option<String>,
+    /// Public domain for proxy mode (defaults to the instance's DNS name, or
+    /// the hostname when it is a FQDN).
+    pub domain: Option<String>,
+    pub server: String,
+    /// ACME account email.
+    pub email: Option<String>,
+    /// Tailscale auth key for funnel mode on instances not yet on the tailnet.
+    pub auth_key: Option<String>,
+}
+
+const FUNNEL_OFF_CMD: &str =
+    "if command -v tailscale >/dev/null 2>&1; then tailscale funnel off >/dev/null 2>&1 || true; fi";
+
+/// Validate a domain for the proxy config and the ACME request.
+fn clean_domain(domain: &str) -> Result<String> {
+    let domain = domain.trim().trim_end_matches('.').to_ascii_lowercase();
+    let valid_label = |label: &str| {
+        !label.is_empty()
+            && label.len() <= 63
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    };
+    if domain.len() > 253 || !domain.contains('.') || !domain.split('.').all(valid_label) {
+        bail!(
+            "Invalid domain '{domain}': expected a fully qualified name like openclaw.example.com"
+        );
+    }
+    Ok(domain)
+}
+
+fn clean_email(email: &str) -> Result<String> {
+    let email = email.trim();
+    let valid = email.len() <= 254
+        && email
+            .split_once('@')
+            .is_some_and(|(user, host)| !user.is_empty() && host.contains('.'))
+        && email
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "@.+-_".contains(c));
+    if !valid {
+        bail!("Invalid ACME email '{email}'");
+    }
+    Ok(email.to_string())
+}
+
+/// Warn when the domain does not resolve to the instance: ACME validation
+/// would fail (unless a CDN proxies the name).
+async fn check_dns(domain: &str, ip: &str) {
+    match tokio::net::lookup_host((domain, 443)).await {
+        Ok(addrs) => {
+            let addrs: Vec<String> = addrs.map(|a| a.ip().to_string()).collect();
+            if !addrs.iter().any(|a| a == ip) {
+                println!(
+                    "  warning: {domain} resolves to {} rather than {ip}; certificate issuance will fail unless traffic reaches this instance",
+                    if addrs.is_empty() {
+                        "nothing".to_string()
+                    } else {
+                        addrs.join(", ")
+                    }
+                );
+            }
+        }
+        Err(err) => println!("  warning: could not resolve {domain}: {err}"),
+    }
+}
+
+async fn show(target: &FleetTarget) -> Result<()> {
+    let exposure = reverse_proxy::exposure(&target.ip, &target.key, target.ssh_user()).await?;
+    let name = &target.hostname;
+    match exposure {
+        Some(exposure) if exposure.mode == "proxy" => println!(
+            "{name}: proxy ({}) at {}",
+            exposure.server.as_deref().unwrap_or("unknown server"),
+            exposure.proxy_url().unwrap_or_default()
+        ),
+        Some(exposure) if exposure.mode == "funnel" => {
+            println!("{name}: Tailscale Funnel (see `clawmacdo openclaw-gateway-url --instance {name}`)")
+        }
+        Some(exposure) => println!(
+            "{name}: {} (gateway on loopback; reach it with `clawmacdo tunnel {name} {OPENCLAW_GATEWAY_PORT}`)",
+            exposure.mode
+        ),
+        None => println!(
+            "{name}: not recorded. Instances deployed before `expose` may still allow port {OPENCLAW_GATEWAY_PORT} in UFW; run `clawmacdo expose {name} --mode none|proxy|funnel` to set a mode."
+        ),
+    }
+    Ok(())
+}
+
+/// `expose <instance> --mode funnel|proxy|none`: choose how the gateway is
+/// reached. Every mode keeps the gateway on loopback and closes its raw port
+/// in UFW. `proxy` serves it over HTTPS through Caddy or nginx with an ACME
+/// certificate, `funnel` through Tailscale Funnel, and `none` not at all.
+pub async fn run(params: ExposeParams) -> Result<()> {
+    let target = fleet::find(&params.instance)?;
+    let (ip, key, ssh_user) = (&target.ip, &target.key, target.ssh_user());
+    let Some(mode) = params.mode.as_deref() else {
+        return show(&target).await;
+    };
+
+    match mode {
+        "proxy" => {
+            let domain = match params.domain.as_deref() {
+                Some(domain) => clean_domain(domain)?,
+                None => {
+                    let name = target.fqdn.as_deref().unwrap_or(&target.hostname);
+                    clean_domain(name).with_context(|| {
+                        format!("{name} is not a public domain name; pass --domain")
+                    })?
+                }
+            };
+            let email = params.email.as_deref().map(clean_email).transpose()?;
+            let server: ProxyServer = params.server.parse()?;
+
+            println!(
+                "Exposing {} through {server} at https://{domain}...",
+                target.hostname
+            );
+            check_dns(&domain, ip).await;
+            println!("[1/2] Installing {server}, requesting a certificate and closing port {OPENCLAW_GATEWAY_PORT}...");
+            reverse_proxy::provision(ip, key, server, &domain, email.as_deref(), ssh_user).await?;
+            println!("[2/2] Turning Tailscale Funnel off...");
+            ssh_root_as_async(ip, key, FUNNEL_OFF_CMD, ssh_user).await?;
+            println!("\nGateway available at https://{domain}");
+        }
+        "funnel" => {
+            println!("Exposing {} through Tailscale Funnel...", target.hostname);
+            match params.auth_key.as_deref() {
+                Some(auth_key) => {
+                    tailscale_funnel::setup(&target.id, Some(auth_key), OPENCLAW_GATEWAY_PORT)
+                        .await?
+                }
+                None => {
+                    let (ok, message, url, _) =
+                        tailscale_funnel::funnel_toggle(&target.id, "on", OPENCLAW_GATEWAY_PORT)
+                            .await?;
+                    if !ok {
+                        bail!("{message}");
+                    }
+                    println!("  {message}");
+                    if let Some(url) = url {
+                        println!("  Gateway URL: {url}");
+                    }
+                }
+            }
+            println!("Removing the reverse proxy and closing port {OPENCLAW_GATEWAY_PORT}...");
+            reverse_proxy::disable(ip, key, "funnel", ssh_user).await?;
+            println!("\nGateway exposed through Tailscale Funnel only.");
+        }
+        "none" => {
+            println!("Unexposing the gateway on {}...", target.hostname);
+            ssh_root_as_async(ip, key, FUNNEL_OFF_CMD, ssh_user).await?;
+            reverse_proxy::disable(ip, key, "none", ssh_user).await?;
+            println!(
+                "\nGateway listens on loopback only. Reach it with `clawmacdo tunnel {} {OPENCLAW_GATEWAY_PORT}`.",
+                target.hostname
+            );
+        }
+        other => bail!("Unknown mode '{other}' (expected funnel, proxy or none)"),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_domains_and_emails() {
+        assert_eq!(
+            clean_domain("OpenClaw.Example.com.").unwrap(),
+            "openclaw.example.com"
+        );
+        assert!(clean_domain("openclaw-7f3a").is_err());
+        assert!(clean_domain("bad_label.example.com").is_err());
+        assert!(clean_domain("x.example.com; rm -rf /").is_err());
+        assert!(clean_email("ops+acme@example.com").is_ok());
+        assert!(clean_email("ops@example.com' -d evil").is_err());
+    }
+}
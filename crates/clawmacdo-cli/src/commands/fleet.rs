@@ -0,0 +1,583 @@
+use anyhow::{bail, Context, Result};
+use clawmacdo_core::config;
+use clawmacdo_db as db;
+use console::style;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// Which deployed instances a fleet command targets. Criteria combine with
+/// AND; `--tag` may be repeated and every tag must be present.
+#[derive(clap::Args, Debug, Clone, Default)]
+pub struct Selector {
+    /// Only instances carrying this tag (repeatable; see `clawmacdo tag`)
+    #[arg(long = "tag")]
+    pub tags: Vec<String>,
+    /// Only instances on this cloud provider (digitalocean, tencent, lightsail, azure, byteplus)
+    #[arg(long)]
+    pub provider: Option<String>,
+    /// Only instances deployed for this customer email
+    #[arg(long)]
+    pub customer_email: Option<String>,
+    /// Comma-separated deploy IDs, hostnames, or IP addresses
+    #[arg(long, value_delimiter = ',')]
+    pub instances: Vec<String>,
+    /// Target every deployed instance
+    #[arg(long)]
+    pub all: bool,
+}
+
+impl Selector {
+    fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+            && self.provider.is_none()
+            && self.customer_email.is_none()
+            && self.instances.is_empty()
+    }
+}
+
+/// A deployed instance a fleet command can reach over SSH.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct FleetTarget {
+    pub(crate) id: String,
+    pub(crate) hostname: String,
+    pub(crate) ip: String,
+    /// Stable DNS name, when a DNS provider manages one.
+    pub(crate) fqdn: Option<String>,
+    #[serde(skip)]
+    pub(crate) key: PathBuf,
+    pub(crate) provider: Option<String>,
+    pub(crate) customer_email: Option<String>,
+    pub(crate) tags: Vec<String>,
+}
+
+impl FleetTarget {
+    pub(crate) fn ssh_user(&self) -> &'static str {
+        match self.provider.as_deref() {
+            Some("lightsail") => "ubuntu",
+            Some("azure") => "azureuser",
+            _ => "root",
+        }
+    }
+}
+
+/// Validate a tag: 1–64 characters of `[A-Za-z0-9._:-]`.
+pub(crate) fn clean_tag(tag: &str) -> Result<String> {
+    let tag = tag.trim();
+    if tag.is_empty()
+        || tag.len() > 64
+        || !tag
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | ':' | '-'))
+    {
+        bail!("Invalid tag '{tag}': use 1-64 letters, digits, '.', '_', ':' or '-'");
+    }
+    Ok(tag.to_string())
+}
+
+/// All deploy records joined with the customer email and tags from the
+/// deployments database, sorted by hostname.
+pub(crate) fn load_targets() -> Result<Vec<FleetTarget>> {
+    let deploys_dir = config::deploys_dir()?;
+    if !deploys_dir.exists() {
+        bail!("No deploy records found. Deploy an instance first.");
+    }
+    let (emails, mut tags) = match db::init_db() {
+        Ok(conn) => {
+            let (rows, _) = db::list_deployments_paginated(&conn, 1, u32::MAX)?;
+            let emails: BTreeMap<String, String> = rows
+                .into_iter()
+                .map(|row| (row.id, row.customer_email))
+                .collect();
+            (emails, db::all_deployment_tags(&conn)?)
+        }
+        Err(_) => (BTreeMap::new(), BTreeMap::new()),
+    };
+
+    let mut targets = Vec::new();
+    for entry in std::fs::read_dir(&deploys_dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let contents = std::fs::read_to_string(&path)?;
+        let Ok(record) = serde_json::from_str::<config::DeployRecord>(&contents) else {
+            continue;
+        };
+        targets.push(FleetTarget {
+            customer_email: emails.get(&record.id).cloned(),
+            tags: tags.remove(&record.id).unwrap_or_default(),
+            provider: record.provider.map(|p| p.to_string()),
+            key: PathBuf::from(record.ssh_key_path),
+            id: record.id,
+            hostname: record.hostname,
+            ip: record.ip_address,
+            fqdn: record.fqdn,
+        });
+    }
+    targets.sort_by(|a, b| a.hostname.cmp(&b.hostname).then(a.id.cmp(&b.id)));
+    Ok(targets)
+}
+
+/// Filter `targets` by `selector`. An explicit `--instances` list keeps its
+/// order and fails on names that match no deploy record.
+pub(crate) fn select(targets: Vec<FleetTarget>, selector: &Selector) -> Result<Vec<FleetTarget>> {
+    if selector.is_empty() && !selector.all {
+        bail!("Select instances with --tag, --provider, --customer-email, --instances or --all");
+    }
+    let matches = |target: &FleetTarget| {
+        selector.tags.iter().all(|tag| target.tags.contains(tag))
+            && selector
+                .provider
+                .as_deref()
+                .is_none_or(|p| target.provider.as_deref() == Some(p))
+            && selector.customer_email.as_deref().is_none_or(|email| {
+                target
+                    .customer_email
+                    .as_deref()
+                    .is_some_and(|e| e.eq_ignore_ascii_case(email))
+            })
+    };
+    if selector.instances.is_empty() {
+        return Ok(targets.into_iter().filter(|t| matches(t)).collect());
+    }
+    let mut selected: Vec<FleetTarget> = Vec::new();
+    for query in &selector.instances {
+        let query = query.trim();
+        let Some(target) = targets.iter().find(|t| {
+            t.id == query
+                || t.hostname == query
+                || t.ip == query
+                || t.fqdn.as_deref() == Some(query)
+        }) else {
+            bail!(
+                "No deploy record found for '{query}'. Use a deploy ID, hostname, or IP address."
+            );
+        };
+        if matches(target) && !selected.iter().any(|t| t.id == target.id) {
+            selected.push(target.clone());
+        }
+    }
+    Ok(selected)
+}
+
+/// Resolve a selector against the local deploy records.
+pub(crate) fn resolve(selector: &Selector) -> Result<Vec<FleetTarget>> {
+    let mut selector = selector.clone();
+    selector.tags = selector
+        .tags
+        .iter()
+        .map(|t| clean_tag(t))
+        .collect::<Result<_>>()?;
+    let targets = select(load_targets()?, &selector)?;
+    if targets.is_empty() {
+        bail!("No deployed instances match the selector");
+    }
+    Ok(targets)
+}
+
+/// Look up one deployed instance by deploy ID, hostname, or IP address.
+pub(crate) fn find(query: &str) -> Result<FleetTarget> {
+    let selector = Selector {
+        instances: vec![query.to_string()],
+        ..Selector::default()
+    };
+    Ok(select(load_targets()?, &selector)?.remove(0))
+}
+
+/// `tag --instance <q> [--add a,b] [--remove c]`: edit and print the tags
+/// used by `--tag` selectors.
+pub fn tag(query: &str, add: &[String], remove: &[String]) -> Result<()> {
+    let target = find(query)?;
+    let add: Vec<String> = add.iter().map(|t| clean_tag(t)).collect::<Result<_>>()?;
+    let remove: Vec<String> = remove.iter().map(|t| clean_tag(t)).collect::<Result<_>>()?;
+    let conn = db::init_db().context("Failed to open deployments database")?;
+    let tags = db::update_deployment_tags(&conn, &target.id, &add, &remove)?;
+    if tags.is_empty() {
+        println!("{} ({}) has no tags.", target.hostname, target.ip);
+    } else {
+        println!("{} ({}): {}", target.hostname, target.ip, tags.join(", "));
+    }
+    Ok(())
+}
+
+/// Restart the gateway (when `restart`) and wait until `/health` answers;
+/// exits non-zero so the SSH call fails when the gateway stays down.
+pub(crate) fn gateway_health_cmd(restart: bool) -> String {
+    let restart = if restart {
+        "(systemctl --user daemon-reload 2>/dev/null || true) && \
+         (systemctl --user restart openclaw-gateway.service 2>/dev/null || \
+          systemctl --user start openclaw-gateway.service 2>/dev/null || true) && "
+    } else {
+        ""
+    };
+    format!(
+        "export XDG_RUNTIME_DIR=/run/user/$(id -u) DBUS_SESSION_BUS_ADDRESS=unix:path=/run/user/$(id -u)/bus && \
+         {restart}\
+         for i in $(seq 1 30); do \
+           if curl -fsS --max-time 2 http://127.0.0.1:18789/health >/dev/null 2>&1; then echo 'gateway: healthy'; exit 0; fi; \
+           sleep 2; \
+         done; \
+         echo 'gateway: FAILED - not healthy after restart'; exit 1"
+    )
+}
+
+// ── Batched execution ───────────────────────────────────────────────────────
+
+/// How a fleet operation is spread over its targets.
+#[derive(Debug, Clone)]
+pub(crate) struct BatchOptions {
+    /// Instances in the first batch; any failure there halts the run.
+    pub(crate) canary: usize,
+    pub(crate) batch_size: usize,
+    /// Instances worked on at the same time within a batch.
+    pub(crate) concurrency: usize,
+    /// Halt after a batch once more than this many instances have failed.
+    pub(crate) max_failures: usize,
+    /// Suppress progress output (for `--json`).
+    pub(crate) quiet: bool,
+}
+
+/// Batch sizes for `total` targets: the canary batch first, then
+/// `batch_size` at a time.
+pub(crate) fn plan_batches(total: usize, canary: usize, batch_size: usize) -> Vec<usize> {
+    let mut sizes = Vec::new();
+    let canary = canary.min(total);
+    if canary > 0 {
+        sizes.push(canary);
+    }
+    let mut left = total - canary;
+    while left > 0 {
+        let size = batch_size.max(1).min(left);
+        sizes.push(size);
+        left -= size;
+    }
+    sizes
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum TargetStatus {
+    Ok,
+    Failed,
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct TargetResult {
+    pub(crate) id: String,
+    pub(crate) hostname: String,
+    pub(crate) ip: String,
+    /// 1-based batch number; the canary batch is 1 when there is one.
+    pub(crate) batch: usize,
+    pub(crate) status: TargetStatus,
+    pub(crate) detail: String,
+    pub(crate) seconds: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct FleetSummary {
+    pub(crate) ok: bool,
+    pub(crate) total: usize,
+    pub(crate) succeeded: usize,
+    pub(crate) failed: usize,
+    pub(crate) skipped: usize,
+    /// Why the run stopped early, if it did.
+    pub(crate) halted: Option<String>,
+    pub(crate) results: Vec<TargetResult>,
+}
+
+impl FleetSummary {
+    fn from_results(results: Vec<TargetResult>, halted: Option<String>) -> Self {
+        let count = |status| results.iter().filter(|r| r.status == status).count();
+        let (succeeded, failed, skipped) = (
+            count(TargetStatus::Ok),
+            count(TargetStatus::Failed),
+            count(TargetStatus::Skipped),
+        );
+        Self {
+            ok: failed == 0 && halted.is_none(),
+            total: results.len(),
+            succeeded,
+            failed,
+            skipped,
+            halted,
+            results,
+        }
+    }
+
+    /// Hostnames of the instances that failed, for follow-up commands.
+    pub(crate) fn failed_instances(&self) -> Vec<&str> {
+        self.results
+            .iter()
+            .filter(|r| r.status == TargetStatus::Failed)
+            .map(|r| r.hostname.as_str())
+            .collect()
+    }
+
+    pub(crate) fn print_table(&self) {
+        println!(
+            "\n  {:<28}  {:<16}  {:<5}  {:<7}  {:>6}  Detail",
+            "Instance", "IP", "Batch", "Status", "Time"
+        );
+        println!("  {}", "-".repeat(96));
+        for result in &self.results {
+            // Pad before styling; escape codes would count towards the width.
+            let status = match result.status {
+                TargetStatus::Ok => style(format!("{:<7}", "ok")).green(),
+                TargetStatus::Failed => style(format!("{:<7}", "failed")).red(),
+                TargetStatus::Skipped => style(format!("{:<7}", "skipped")).yellow(),
+            };
+            let detail = result.detail.lines().next().unwrap_or("");
+            println!(
+                "  {:<28}  {:<16}  {:<5}  {}  {:>5.0}s  {}",
+                result.hostname, result.ip, result.batch, status, result.seconds, detail
+            );
+        }
+        println!(
+            "\n  Total: {}  ok: {}  failed: {}  skipped: {}",
+            self.total, self.succeeded, self.failed, self.skipped
+        );
+        if let Some(reason) = &self.halted {
+            println!("  Halted: {reason}");
+        }
+    }
+}
+
+/// Run `op` on every target batch by batch, at most `concurrency` at a time.
+/// A failing canary, or more than `max_failures` failures, halts the run and
+/// marks the remaining targets as skipped. `op` returns a one-line detail.
+pub(crate) async fn run_batches<F, Fut>(
+    targets: Vec<FleetTarget>,
+    options: &BatchOptions,
+    op: F,
+) -> FleetSummary
+where
+    F: Fn(FleetTarget) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<String>> + Send + 'static,
+{
+    let op = Arc::new(op);
+    let semaphore = Arc::new(Semaphore::new(options.concurrency.max(1)));
+    let sizes = plan_batches(targets.len(), options.canary, options.batch_size);
+    let batch_count = sizes.len();
+    let mut remaining = targets.into_iter();
+    let mut results: Vec<TargetResult> = Vec::new();
+    let mut halted: Option<String> = None;
+
+    for (index, size) in sizes.into_iter().enumerate() {
+        let batch_no = index + 1;
+        let batch: Vec<FleetTarget> = remaining.by_ref().take(size).collect();
+        if halted.is_some() {
+            results.extend(batch.into_iter().map(|target| TargetResult {
+                id: target.id,
+                hostname: target.hostname,
+                ip: target.ip,
+                batch: batch_no,
+                status: TargetStatus::Skipped,
+                detail: "not started".into(),
+                seconds: 0.0,
+            }));
+            continue;
+        }
+        let is_canary = index == 0 && options.canary > 0;
+        if !options.quiet {
+            let label = if is_canary { " (canary)" } else { "" };
+            println!(
+                "[batch {batch_no}/{batch_count}]{label} {} instance(s)...",
+                batch.len()
+            );
+        }
+
+        let mut set = JoinSet::new();
+        for (position, target) in batch.iter().cloned().enumerate() {
+            let op = Arc::clone(&op);
+            let semaphore = Arc::clone(&semaphore);
+            set.spawn(async move {
+                let _permit = semaphore.acquire_owned().await;
+                let started = Instant::now();
+                let outcome = op(target).await;
+                (position, outcome, started.elapsed().as_secs_f64())
+            });
+        }
+        let mut outcomes: Vec<Option<(Result<String>, f64)>> = batch.iter().map(|_| None).collect();
+        while let Some(joined) = set.join_next().await {
+            if let Ok((position, outcome, seconds)) = joined {
+                outcomes[position] = Some((outcome, seconds));
+            }
+        }
+
+        let mut batch_failures = 0;
+        for (target, outcome) in batch.into_iter().zip(outcomes) {
+            let (status, detail, seconds) = match outcome {
+                Some((Ok(detail), seconds)) => (TargetStatus::Ok, detail, seconds),
+                Some((Err(err), seconds)) => (TargetStatus::Failed, format!("{err:#}"), seconds),
+                None => (TargetStatus::Failed, "task panicked".into(), 0.0),
+            };
+            if status == TargetStatus::Failed {
+                batch_failures += 1;
+            }
+            if !options.quiet {
+                let mark = match status {
+                    TargetStatus::Ok => style("ok").green(),
+                    _ => style("failed").red(),
+                };
+                println!(
+                    "  {mark}  {} ({})  {}",
+                    target.hostname,
+                    target.ip,
+                    detail.lines().next().unwrap_or("")
+                );
+            }
+            results.push(TargetResult {
+                id: target.id,
+                hostname: target.hostname,
+                ip: target.ip,
+                batch: batch_no,
+                status,
+                detail,
+                seconds,
+            });
+        }
+
+        let failed = results
+            .iter()
+            .filter(|r| r.status == TargetStatus::Failed)
+            .count();
+        if is_canary && batch_failures > 0 && batch_count > 1 {
+            halted = Some(format!("{batch_failures} canary instance(s) failed"));
+        } else if failed > options.max_failures && remaining.len() > 0 {
+            halted = Some(format!(
+                "{failed} failure(s) exceeded --max-failures {}",
+                options.max_failures
+            ));
+        }
+    }
+
+    FleetSummary::from_results(results, halted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(id: &str, provider: &str, email: &str, tags: &[&str]) -> FleetTarget {
+        FleetTarget {
+            id: id.into(),
+            hostname: format!("host-{id}"),
+            ip: format!("10.0.0.{}", id.len()),
+            fqdn: None,
+            key: PathBuf::new(),
+            provider: Some(provider.into()),
+            customer_email: Some(email.into()),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+        }
+    }
+
+    fn ids(targets: &[FleetTarget]) -> Vec<&str> {
+        targets.iter().map(|t| t.id.as_str()).collect()
+    }
+
+    fn fleet() -> Vec<FleetTarget> {
+        vec![
+            target("a", "digitalocean", "ann@example.com", &["prod", "sg"]),
+            target("bb", "lightsail", "bob@example.com", &["prod"]),
+            target("ccc", "digitalocean", "bob@example.com", &[]),
+        ]
+    }
+
+    #[test]
+    fn selector_combines_criteria() {
+        let by_tag = Selector {
+            tags: vec!["prod".into()],
+            ..Selector::default()
+        };
+        assert_eq!(ids(&select(fleet(), &by_tag).unwrap()), ["a", "bb"]);
+
+        let by_provider_and_email = Selector {
+            provider: Some("digitalocean".into()),
+            customer_email: Some("BOB@example.com".into()),
+            ..Selector::default()
+        };
+        assert_eq!(
+            ids(&select(fleet(), &by_provider_and_email).unwrap()),
+            ["ccc"]
+        );
+
+        let listed = Selector {
+            instances: vec!["host-ccc".into(), "10.0.0.1".into(), "a".into()],
+            ..Selector::default()
+        };
+        assert_eq!(ids(&select(fleet(), &listed).unwrap()), ["ccc", "a"]);
+
+        let unknown = Selector {
+            instances: vec!["nope".into()],
+            ..Selector::default()
+        };
+        assert!(select(fleet(), &unknown).is_err());
+        assert!(select(fleet(), &Selector::default()).is_err());
+        let all = Selector {
+            all: true,
+            ..Selector::default()
+        };
+        assert_eq!(select(fleet(), &all).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn batches_start_with_the_canary() {
+        assert_eq!(plan_batches(10, 1, 4), [1, 4, 4, 1]);
+        assert_eq!(plan_batches(3, 0, 5), [3]);
+        assert_eq!(plan_batches(2, 5, 5), [2]);
+        assert_eq!(plan_batches(0, 1, 5), Vec::<usize>::new());
+        assert!(clean_tag("env:prod").is_ok());
+        assert!(clean_tag("bad tag").is_err());
+    }
+
+    #[tokio::test]
+    async fn failures_halt_the_run() {
+        let options = BatchOptions {
+            canary: 1,
+            batch_size: 1,
+            concurrency: 2,
+            max_failures: 0,
+            quiet: true,
+        };
+        let summary = run_batches(fleet(), &options, |t| async move {
+            if t.id == "a" {
+                bail!("boom")
+            }
+            Ok("done".to_string())
+        })
+        .await;
+        assert_eq!(
+            summary.halted.as_deref(),
+            Some("1 canary instance(s) failed")
+        );
+        assert_eq!((summary.failed, summary.skipped), (1, 2));
+        assert_eq!(summary.failed_instances(), ["host-a"]);
+
+        let options = BatchOptions {
+            canary: 0,
+            batch_size: 2,
+            max_failures: 1,
+            ..options
+        };
+        let summary = run_batches(fleet(), &options, |t| async move {
+            if t.id == "ccc" {
+                bail!("boom")
+            }
+            Ok("done".to_string())
+        })
+        .await;
+        assert!(summary.halted.is_none());
+        assert_eq!(
+            (summary.succeeded, summary.failed, summary.skipped),
+            (2, 1, 0)
+        );
+        assert!(!summary.ok);
+    }
+}
@@ -0,0 +1,1611 @@
+use super::deploy::resolve_provider;
+use super::fleet::{self, FleetTarget};
+use super::{dns, logs};
+use anyhow::{bail, Context, Result};
+use base64::Engine;
+use chrono::Utc;
+use clawmacdo_core::config::{self, CloudProviderType, DeployRecord};
+use clawmacdo_db as db;
+use clawmacdo_provision::provision::commands::ssh_root_as_async;
+use clawmacdo_ssh as ssh;
+use clawmacdo_ui::progress;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+
+pub type Db = Arc<Mutex<rusqlite::Connection>>;
+
+pub const TOTAL_STEPS: i32 = 8;
+pub const DEFAULT_HERMES_IMAGE: &str = "nousresearch/hermes-agent:latest";
+pub const DEFAULT_BEDROCK_REGION: &str = "ap-southeast-1";
+pub const DEFAULT_BEDROCK_MODEL: &str = "amazon.nova-pro-v1:0";
+
+const PROVISION_DIR: &str = "/opt/hermes-provision";
+const ENV_FILE: &str = "/opt/hermes-provision/hermes.env";
+const IMAGE_FILE: &str = "/opt/hermes-provision/image";
+const RUN_SCRIPT: &str = "/opt/hermes-provision/run.sh";
+const CONTAINERS: &[&str] = &["hermes", "hermes-dashboard"];
+
+pub struct HermesParams {
+    pub deploy_id: Option<String>,
+    pub customer_email: String,
+    /// Cloud provider (lightsail, digitalocean, tencent, azure, byteplus).
+    pub provider: String,
+    pub name: Option<String>,
+    /// Region for providers other than Lightsail (default: the provider's default).
+    pub region: Option<String>,
+    pub size: Option<String>,
+    pub aws_access_key_id: String,
+    pub aws_secret_access_key: String,
+    pub aws_region: String,
+    pub do_token: String,
+    pub tencent_secret_id: String,
+    pub tencent_secret_key: String,
+    pub azure_tenant_id: String,
+    pub azure_subscription_id: String,
+    pub azure_client_id: String,
+    pub azure_client_secret: String,
+    pub byteplus_access_key: String,
+    pub byteplus_secret_key: String,
+    pub image: String,
+    pub env_file: Option<PathBuf>,
+    pub env_content: Option<String>,
+    pub env_vars: Vec<String>,
+    pub bedrock_api_key: Option<String>,
+    pub bedrock_region: String,
+    pub bedrock_model: String,
+    pub telegram_bot_token: Option<String>,
+    pub telegram_allowed_users: Option<String>,
+    pub telegram_home_channel: Option<String>,
+    pub dashboard: bool,
+    pub mount_docker_socket: bool,
+    pub dry_run: bool,
+    pub json: bool,
+    pub progress_tx: Option<mpsc::UnboundedSender<String>>,
+    pub db: Option<Db>,
+}
+
+fn record_step_start(db: &Option<Db>, deploy_id: &str, step: i32, label: &str) {
+    db::record_step_start(db, deploy_id, step, TOTAL_STEPS, label);
+}
+
+fn record_step_complete(db: &Option<Db>, deploy_id: &str, step: i32) {
+    db::record_step_complete(db, deploy_id, step);
+}
+
+fn record_step_failed(db: &Option<Db>, deploy_id: &str, step: i32, err: &str) {
+    db::record_step_failed(db, deploy_id, step, err);
+}
+
+fn record_step_skipped(db: &Option<Db>, deploy_id: &str, step: i32) {
+    db::record_step_skipped(db, deploy_id, step);
+}
+
+fn sh_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\"'\"'"))
+}
+
+fn validate_env_assignment(value: &str) -> Result<()> {
+    let Some((key, _)) = value.split_once('=') else {
+        bail!("--env values must use KEY=VALUE syntax");
+    };
+    if key.is_empty() {
+        bail!("--env key cannot be empty");
+    }
+    let mut chars = key.chars();
+    let first = chars.next().unwrap_or_default();
+    if !(first == '_' || first.is_ascii_alphabetic())
+        || !chars.all(|ch| ch == '_' || ch.is_ascii_alphanumeric())
+    {
+        bail!("Invalid env key '{key}'. Use shell-style names like OPENAI_API_KEY.");
+    }
+    Ok(())
+}
+
+fn append_env_assignment(out: &mut String, key: &str, value: &str) {
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        return;
+    }
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+    out.push_str(key);
+    out.push('=');
+    out.push_str(trimmed);
+    out.push('\n');
+}
+
+fn normalize_csv(value: &str) -> Option<String> {
+    let normalized = value
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(",");
+    if normalized.is_empty() {
+        None
+    } else {
+        Some(normalized)
+    }
+}
+
+fn first_csv_value(value: &str) -> Option<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .find(|part| !part.is_empty())
+        .map(str::to_string)
+}
+
+fn normalized_bedrock_region(params: &HermesParams) -> String {
+    let region = params.bedrock_region.trim();
+    if region.is_empty() {
+        DEFAULT_BEDROCK_REGION.to_string()
+    } else {
+        region.to_string()
+    }
+}
+
+fn normalized_bedrock_model(params: &HermesParams) -> String {
+    let model = params.bedrock_model.trim();
+    if model.is_empty() {
+        DEFAULT_BEDROCK_MODEL.to_string()
+    } else {
+        model.to_string()
+    }
+}
+
+fn bedrock_mantle_base_url(region: &str) -> String {
+    format!("https://bedrock-mantle.{region}.api.aws/v1")
+}
+
+fn yaml_single_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+fn build_bedrock_config_content(region: &str, model: &str) -> String {
+    let base_url = bedrock_mantle_base_url(region);
+    format!(
+        "model:\n  default: {}\n  provider: custom\n  base_url: {}\nbedrock:\n  region: {}\n",
+        yaml_single_quote(model),
+        yaml_single_quote(&base_url),
+        yaml_single_quote(region),
+    )
+}
+
+fn build_aws_cli_config_content(region: &str) -> String {
+    format!("[default]\nregion = {region}\noutput = json\n")
+}
+
+fn build_env_content(params: &HermesParams) -> Result<String> {
+    let mut chunks = Vec::new();
+
+    if let Some(path) = params.env_file.as_deref() {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read env file {}", path.display()))?;
+        chunks.push(contents.trim_end().to_string());
+    }
+
+    if let Some(contents) = params.env_content.as_deref() {
+        let trimmed = contents.trim_end();
+        if !trimmed.is_empty() {
+            chunks.push(trimmed.to_string());
+        }
+    }
+
+    for item in &params.env_vars {
+        let trimmed = item.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        validate_env_assignment(trimmed)?;
+        chunks.push(trimmed.to_string());
+    }
+
+    let mut env = chunks
+        .into_iter()
+        .filter(|chunk| !chunk.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+    if !env.is_empty() && !env.ends_with('\n') {
+        env.push('\n');
+    }
+    if let Some(api_key) = params.bedrock_api_key.as_deref() {
+        let region = normalized_bedrock_region(params);
+        let base_url = bedrock_mantle_base_url(&region);
+        append_env_assignment(&mut env, "AWS_BEARER_TOKEN_BEDROCK", api_key);
+        append_env_assignment(&mut env, "OPENAI_API_KEY", api_key);
+        append_env_assignment(&mut env, "OPENAI_BASE_URL", &base_url);
+        append_env_assignment(&mut env, "AWS_REGION", &region);
+        append_env_assignment(&mut env, "AWS_DEFAULT_REGION", &region);
+    }
+    if let Some(token) = params.telegram_bot_token.as_deref() {
+        append_env_assignment(&mut env, "TELEGRAM_BOT_TOKEN", token);
+    }
+    let allowed_users = params
+        .telegram_allowed_users
+        .as_deref()
+        .and_then(normalize_csv);
+    if let Some(users) = allowed_users.as_deref() {
+        append_env_assignment(&mut env, "TELEGRAM_ALLOWED_USERS", users);
+    }
+    let home_channel = params
+        .telegram_home_channel
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+        .or_else(|| allowed_users.as_deref().and_then(first_csv_value));
+    if let Some(channel) = home_channel.as_deref() {
+        append_env_assignment(&mut env, "TELEGRAM_HOME_CHANNEL", channel);
+    }
+    Ok(env)
+}
+
+/// The script that (re)creates the Hermes containers from the env file and
+/// the image named in `image`. Written at bootstrap and run again by
+/// `hermes-update-image` and `hermes-env-set`.
+fn generate_run_script(dashboard: bool, mount_docker_socket: bool) -> String {
+    let docker_socket_mount = if mount_docker_socket {
+        "  -v /var/run/docker.sock:/var/run/docker.sock \\\n"
+    } else {
+        ""
+    };
+    let dashboard_block = if dashboard {
+        r#"docker rm -f hermes-dashboard >/dev/null 2>&1 || true
+docker run -d \
+  --name hermes-dashboard \
+  --restart unless-stopped \
+  --network host \
+  -v /opt/hermes-data:/opt/data \
+  --env-file "$ENV_FILE" \
+  "$HERMES_IMAGE" dashboard --host 127.0.0.1 --no-open
+"#
+    } else {
+        "docker rm -f hermes-dashboard >/dev/null 2>&1 || true\n"
+    };
+
+    format!(
+        r#"#!/bin/bash
+# Managed by clawmacdo: recreates the Hermes containers from {ENV_FILE}
+# and the image named in {IMAGE_FILE}.
+set -euo pipefail
+
+HERMES_IMAGE="$(cat {IMAGE_FILE})"
+ENV_FILE={ENV_FILE}
+
+docker rm -f hermes >/dev/null 2>&1 || true
+docker run -d \
+  --name hermes \
+  --restart unless-stopped \
+  --network host \
+  -v /opt/hermes-data:/opt/data \
+{docker_socket_mount}  --env-file "$ENV_FILE" \
+  "$HERMES_IMAGE" gateway run
+
+{dashboard_block}"#
+    )
+}
+
+/// Provider-specific fixes that must run before any package install.
+pub(crate) fn provider_preamble(provider: &CloudProviderType) -> &'static str {
+    match provider {
+        // BytePlus internal DNS (100.96.0.x) can fail to resolve external
+        // names, and its default apt mirror is often unreachable from outside.
+        CloudProviderType::BytePlus => {
+            r#"mkdir -p /etc/systemd/resolved.conf.d
+cat > /etc/systemd/resolved.conf.d/public-dns.conf <<'DNSEOF'
+[Resolve]
+DNS=8.8.8.8 1.1.1.1
+FallbackDNS=8.8.4.4 1.0.0.1
+DNSEOF
+systemctl restart systemd-resolved || true
+if grep -q 'mirrors\.ivolces\.com' /etc/apt/sources.list 2>/dev/null; then
+  sed -i 's|http://mirrors\.ivolces\.com/ubuntu/|http://archive.ubuntu.com/ubuntu/|g' /etc/apt/sources.list
+fi
+"#
+        }
+        _ => "",
+    }
+}
+
+fn generate_user_data(
+    provider: &CloudProviderType,
+    image: &str,
+    env_content: &str,
+    config_content: &str,
+    aws_cli_config_content: &str,
+    dashboard: bool,
+    mount_docker_socket: bool,
+) -> String {
+    let sentinel = config::CLOUD_INIT_SENTINEL;
+    let image_q = sh_quote(image);
+    let env_b64 = base64::engine::general_purpose::STANDARD.encode(env_content.as_bytes());
+    let config_b64 = base64::engine::general_purpose::STANDARD.encode(config_content.as_bytes());
+    let aws_cli_config_b64 =
+        base64::engine::general_purpose::STANDARD.encode(aws_cli_config_content.as_bytes());
+    let preamble = provider_preamble(provider);
+    let run_script = generate_run_script(dashboard, mount_docker_socket);
+
+    format!(
+        r#"#!/bin/bash
+set -euo pipefail
+
+export DEBIAN_FRONTEND=noninteractive
+LOG=/var/log/clawmacdo-hermes-provision.log
+exec > >(tee -a "$LOG") 2>&1
+
+HERMES_IMAGE={image_q}
+ENV_FILE={ENV_FILE}
+ENV_B64='{env_b64}'
+CONFIG_FILE=/opt/hermes-data/config.yaml
+CONFIG_B64='{config_b64}'
+AWS_CLI_CONFIG_B64='{aws_cli_config_b64}'
+
+echo "[clawmacdo] Starting Hermes Agent bootstrap"
+{preamble}
+apt-get update -y
+apt-get upgrade -y
+apt-get install -y ca-certificates curl gnupg unzip ufw docker.io fail2ban unattended-upgrades
+
+command -v docker >/dev/null 2>&1 || curl -fsSL https://get.docker.com | sh
+systemctl enable --now docker
+
+if ! command -v aws >/dev/null 2>&1; then
+  case "$(uname -m)" in
+    aarch64|arm64) AWSCLI_ARCH=aarch64 ;;
+    *) AWSCLI_ARCH=x86_64 ;;
+  esac
+  if curl -fsSL "https://awscli.amazonaws.com/awscli-exe-linux-${{AWSCLI_ARCH}}.zip" -o /tmp/awscliv2.zip \
+      && rm -rf /tmp/aws \
+      && unzip -q /tmp/awscliv2.zip -d /tmp \
+      && /tmp/aws/install --update >/dev/null 2>&1; then
+    echo "[clawmacdo] AWS CLI installed"
+  else
+    echo "[clawmacdo] Warning: AWS CLI install failed; Hermes Bedrock API-key mode can still run"
+  fi
+fi
+
+ufw default deny incoming
+ufw default allow outgoing
+ufw allow 22/tcp
+ufw --force enable
+
+mkdir -p /opt/hermes-data {PROVISION_DIR}
+cat > "$ENV_FILE" <<'ENVEOF'
+# Managed by clawmacdo hermes-provision.
+ENVEOF
+if [ -n "$ENV_B64" ]; then
+  printf '%s' "$ENV_B64" | base64 -d >> "$ENV_FILE"
+fi
+grep -q '^HERMES_UID=' "$ENV_FILE" || printf 'HERMES_UID=10000\n' >> "$ENV_FILE"
+grep -q '^HERMES_GID=' "$ENV_FILE" || printf 'HERMES_GID=10000\n' >> "$ENV_FILE"
+chmod 600 "$ENV_FILE"
+
+if [ -n "$CONFIG_B64" ]; then
+  printf '%s' "$CONFIG_B64" | base64 -d > "$CONFIG_FILE"
+  chmod 640 "$CONFIG_FILE"
+fi
+
+if [ -n "$AWS_CLI_CONFIG_B64" ]; then
+  mkdir -p /root/.aws /opt/hermes-data/.aws
+  printf '%s' "$AWS_CLI_CONFIG_B64" | base64 -d > /root/.aws/config
+  printf '%s' "$AWS_CLI_CONFIG_B64" | base64 -d > /opt/hermes-data/.aws/config
+  chmod 700 /root/.aws /opt/hermes-data/.aws
+  chmod 600 /root/.aws/config /opt/hermes-data/.aws/config
+fi
+
+printf '%s\n' "$HERMES_IMAGE" > {IMAGE_FILE}
+cat > {RUN_SCRIPT} <<'RUNEOF'
+{run_script}RUNEOF
+chmod 700 {RUN_SCRIPT}
+
+docker pull "$HERMES_IMAGE"
+{RUN_SCRIPT}
+docker ps --filter name=hermes
+
+touch {sentinel}
+echo "[clawmacdo] Hermes Agent bootstrap complete"
+"#
+    )
+}
+
+fn provider_label(provider: &CloudProviderType) -> &'static str {
+    match provider {
+        CloudProviderType::DigitalOcean => "DigitalOcean",
+        CloudProviderType::Tencent => "Tencent Cloud",
+        CloudProviderType::Lightsail => "AWS Lightsail",
+        CloudProviderType::Azure => "Microsoft Azure",
+        CloudProviderType::BytePlus => "BytePlus Cloud",
+    }
+}
+
+/// The login user of a fresh instance on each provider.
+fn provider_ssh_user(provider: &CloudProviderType) -> &'static str {
+    match provider {
+        CloudProviderType::Lightsail => "ubuntu",
+        CloudProviderType::Azure => "azureuser",
+        _ => "root",
+    }
+}
+
+fn default_region(provider: &CloudProviderType, params: &HermesParams) -> String {
+    let region = match provider {
+        CloudProviderType::Lightsail => params.aws_region.trim(),
+        _ => params.region.as_deref().unwrap_or_default().trim(),
+    };
+    if !region.is_empty() {
+        return region.to_string();
+    }
+    match provider {
+        CloudProviderType::DigitalOcean => config::DEFAULT_REGION,
+        CloudProviderType::Tencent => config::DEFAULT_TENCENT_REGION,
+        CloudProviderType::Lightsail => "ap-southeast-1",
+        CloudProviderType::Azure => config::DEFAULT_AZURE_REGION,
+        CloudProviderType::BytePlus => config::DEFAULT_BYTEPLUS_REGION,
+    }
+    .to_string()
+}
+
+fn default_size(provider: &CloudProviderType) -> &'static str {
+    match provider {
+        CloudProviderType::DigitalOcean | CloudProviderType::Lightsail => config::DEFAULT_SIZE,
+        CloudProviderType::Tencent => config::DEFAULT_TENCENT_INSTANCE_TYPE,
+        CloudProviderType::Azure => config::DEFAULT_AZURE_SIZE,
+        CloudProviderType::BytePlus => config::DEFAULT_BYTEPLUS_SIZE,
+    }
+}
+
+/// The provider credentials a real (non dry-run) provision needs.
+fn missing_credentials(
+    provider: &CloudProviderType,
+    params: &HermesParams,
+) -> Option<&'static str> {
+    let blank = |value: &str| value.trim().is_empty();
+    match provider {
+        CloudProviderType::Lightsail
+            if blank(&params.aws_access_key_id) || blank(&params.aws_secret_access_key) =>
+        {
+            Some("AWS credentials required. Set AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY.")
+        }
+        CloudProviderType::DigitalOcean if blank(&params.do_token) => {
+            Some("DigitalOcean API token required. Set DO_TOKEN or pass --do-token.")
+        }
+        CloudProviderType::Tencent
+            if blank(&params.tencent_secret_id) || blank(&params.tencent_secret_key) =>
+        {
+            Some("Tencent credentials required. Set TENCENT_SECRET_ID and TENCENT_SECRET_KEY.")
+        }
+        CloudProviderType::Azure
+            if blank(&params.azure_tenant_id)
+                || blank(&params.azure_subscription_id)
+                || blank(&params.azure_client_id)
+                || blank(&params.azure_client_secret) =>
+        {
+            Some("Azure credentials required: tenant ID, subscription ID, client ID, and client secret.")
+        }
+        CloudProviderType::BytePlus
+            if blank(&params.byteplus_access_key) || blank(&params.byteplus_secret_key) =>
+        {
+            Some("BytePlus credentials required. Set BYTEPLUS_ACCESS_KEY and BYTEPLUS_SECRET_KEY.")
+        }
+        _ => None,
+    }
+}
+
+/// What to create, shared by every provider's launch path.
+struct LaunchSpec<'a> {
+    deploy_id: &'a str,
+    hostname: &'a str,
+    region: &'a str,
+    size: &'a str,
+    public_key: &'a str,
+    user_data: String,
+}
+
+/// A created instance that has a public IP (steps 3–5).
+struct Launched {
+    droplet_id: u64,
+    instance_id: Option<String>,
+    ip: String,
+    ssh_key_id: Option<String>,
+    ssh_key_fingerprint: String,
+    resource_group: Option<String>,
+}
+
+#[cfg(feature = "lightsail")]
+async fn launch_lightsail(params: &HermesParams, spec: LaunchSpec<'_>) -> Result<Launched> {
+    use clawmacdo_cloud::cloud_provider::CreateInstanceParams;
+    use clawmacdo_cloud::lightsail_cli::LightsailCliProvider;
+    use clawmacdo_cloud::CloudProvider;
+
+    let (tx, step_db, deploy_id) = (&params.progress_tx, &params.db, spec.deploy_id);
+    clawmacdo_cloud::lightsail_cli::ensure_aws_cli()?;
+    let lightsail = LightsailCliProvider::with_credentials(
+        spec.region.to_string(),
+        params.aws_access_key_id.clone(),
+        params.aws_secret_access_key.clone(),
+    );
+
+    record_step_start(step_db, deploy_id, 3, "Uploading SSH key to AWS Lightsail");
+    progress::emit(tx, "\n[Step 3/8] Uploading SSH key to AWS Lightsail...");
+    let key_name = format!("clawmacdo-{deploy_id}");
+    let key_info = lightsail
+        .upload_ssh_key(&key_name, spec.public_key)
+        .await
+        .context("Failed to upload SSH key to AWS Lightsail")?;
+    progress::emit(tx, &format!("  Key ID: {}", key_info.id));
+    record_step_complete(step_db, deploy_id, 3);
+
+    record_step_start(step_db, deploy_id, 4, "Creating Lightsail instance");
+    progress::emit(
+        tx,
+        "\n[Step 4/8] Creating Lightsail instance with Hermes Agent cloud-init...",
+    );
+    let instance_info = lightsail
+        .create_instance(CreateInstanceParams {
+            name: spec.hostname.to_string(),
+            region: spec.region.to_string(),
+            size: spec.size.to_string(),
+            image: "ubuntu_24_04".to_string(),
+            ssh_key_id: key_name.clone(),
+            user_data: spec.user_data,
+            tags: vec!["app=hermes-agent".to_string()],
+            customer_email: params.customer_email.clone(),
+        })
+        .await
+        .context("Failed to create Lightsail instance")?;
+    progress::emit(tx, &format!("  Instance ID: {}", instance_info.id));
+    record_step_complete(step_db, deploy_id, 4);
+
+    record_step_start(step_db, deploy_id, 5, "Waiting for Lightsail instance");
+    progress::emit(
+        tx,
+        "\n[Step 5/8] Waiting for Lightsail instance to become active...",
+    );
+    let instance_info = lightsail
+        .wait_for_active(&instance_info.id, 600)
+        .await
+        .context("Lightsail instance did not become active within 10 minutes")?;
+    let ip = instance_info
+        .public_ip
+        .context("Lightsail instance has no public IP")?;
+    progress::emit(tx, &format!("  IP: {ip}"));
+    record_step_complete(step_db, deploy_id, 5);
+
+    Ok(Launched {
+        droplet_id: 0,
+        instance_id: Some(instance_info.id),
+        ip,
+        ssh_key_id: Some(key_info.id),
+        ssh_key_fingerprint: String::new(),
+        resource_group: None,
+    })
+}
+
+#[cfg(feature = "digitalocean")]
+async fn launch_do(params: &HermesParams, spec: LaunchSpec<'_>) -> Result<Launched> {
+    use clawmacdo_cloud::digitalocean::DoClient;
+
+    let (tx, step_db, deploy_id) = (&params.progress_tx, &params.db, spec.deploy_id);
+    let do_client = DoClient::new(&params.do_token)?;
+
+    record_step_start(step_db, deploy_id, 3, "Uploading SSH key to DigitalOcean");
+    progress::emit(tx, "\n[Step 3/8] Uploading SSH key to DigitalOcean...");
+    let key_name = format!("clawmacdo-{}", &deploy_id[..8]);
+    let key_info = do_client
+        .upload_ssh_key(&key_name, spec.public_key)
+        .await
+        .context("Failed to upload SSH key to DigitalOcean")?;
+    progress::emit(tx, &format!("  Key ID: {}", key_info.id));
+    record_step_complete(step_db, deploy_id, 3);
+
+    record_step_start(step_db, deploy_id, 4, "Creating droplet");
+    progress::emit(
+        tx,
+        "\n[Step 4/8] Creating droplet with Hermes Agent cloud-init...",
+    );
+    let droplet = do_client
+        .create_droplet(
+            spec.hostname,
+            spec.region,
+            spec.size,
+            key_info.id,
+            &spec.user_data,
+            false,
+            &params.customer_email,
+        )
+        .await
+        .context("Failed to create droplet")?;
+    progress::emit(tx, &format!("  Droplet ID: {}", droplet.id));
+    record_step_complete(step_db, deploy_id, 4);
+
+    record_step_start(step_db, deploy_id, 5, "Waiting for droplet");
+    progress::emit(tx, "\n[Step 5/8] Waiting for droplet to become active...");
+    let droplet = do_client
+        .wait_for_active(droplet.id, std::time::Duration::from_secs(300))
+        .await
+        .context("Droplet did not become active within 5 minutes")?;
+    let ip = droplet.public_ip().context("Droplet has no public IP")?;
+    progress::emit(tx, &format!("  IP: {ip}"));
+    record_step_complete(step_db, deploy_id, 5);
+
+    Ok(Launched {
+        droplet_id: droplet.id,
+        instance_id: None,
+        ip,
+        ssh_key_id: None,
+        ssh_key_fingerprint: key_info.fingerprint,
+        resource_group: None,
+    })
+}
+
+#[cfg(feature = "tencent-cloud")]
+async fn launch_tencent(params: &HermesParams, spec: LaunchSpec<'_>) -> Result<Launched> {
+    use clawmacdo_cloud::tencent::TencentClient;
+
+    let (tx, step_db, deploy_id) = (&params.progress_tx, &params.db, spec.deploy_id);
+    let tc_client = TencentClient::new(
+        &params.tencent_secret_id,
+        &params.tencent_secret_key,
+        spec.region,
+    )?;
+
+    record_step_start(step_db, deploy_id, 3, "Uploading SSH key to Tencent Cloud");
+    progress::emit(tx, "\n[Step 3/8] Uploading SSH key to Tencent Cloud...");
+    let key_name = format!("clawmacdo_{}", &deploy_id[..8]);
+    let key_info = tc_client
+        .import_key_pair(&key_name, spec.public_key)
+        .await
+        .context("Failed to upload SSH key to Tencent Cloud")?;
+    progress::emit(tx, &format!("  Key ID: {}", key_info.id));
+    record_step_complete(step_db, deploy_id, 3);
+
+    record_step_start(step_db, deploy_id, 4, "Creating CVM instance");
+    progress::emit(
+        tx,
+        "\n[Step 4/8] Creating CVM instance with Hermes Agent cloud-init...",
+    );
+    let user_data_b64 = base64::engine::general_purpose::STANDARD.encode(&spec.user_data);
+    let instance_id = tc_client
+        .create_instance(
+            spec.hostname,
+            spec.size,
+            config::DEFAULT_TENCENT_IMAGE_ID,
+            &key_info.id,
+            &user_data_b64,
+            &params.customer_email,
+        )
+        .await
+        .context("Failed to create CVM instance")?;
+    progress::emit(tx, &format!("  Instance ID: {instance_id}"));
+    record_step_complete(step_db, deploy_id, 4);
+
+    record_step_start(step_db, deploy_id, 5, "Waiting for CVM instance");
+    progress::emit(tx, "\n[Step 5/8] Waiting for instance to become active...");
+    let instance = tc_client
+        .wait_for_running(&instance_id, std::time::Duration::from_secs(300))
+        .await
+        .context("Instance did not become RUNNING within 5 minutes")?;
+    let ip = instance
+        .public_ip
+        .context("Tencent instance has no public IP")?;
+    progress::emit(tx, &format!("  IP: {ip}"));
+    record_step_complete(step_db, deploy_id, 5);
+
+    Ok(Launched {
+        droplet_id: 0,
+        instance_id: Some(instance_id),
+        ip,
+        ssh_key_id: Some(key_info.id),
+        ssh_key_fingerprint: String::new(),
+        resource_group: None,
+    })
+}
+
+#[cfg(feature = "byteplus")]
+async fn launch_byteplus(params: &HermesParams, spec: LaunchSpec<'_>) -> Result<Launched> {
+    use clawmacdo_cloud::byteplus::BytePlusClient;
+
+    let (tx, step_db, deploy_id) = (&params.progress_tx, &params.db, spec.deploy_id);
+    let bp_client = BytePlusClient::new(
+        &params.byteplus_access_key,
+        &params.byteplus_secret_key,
+        spec.region,
+    )?;
+
+    record_step_start(step_db, deploy_id, 3, "Uploading SSH key to BytePlus");
+    progress::emit(tx, "\n[Step 3/8] Uploading SSH key to BytePlus...");
+    let key_name = format!("clawmacdo-{}", &deploy_id[..8]);
+    let key_info = bp_client
+        .import_key_pair(&key_name, spec.public_key)
+        .await
+        .context("Failed to upload SSH key to BytePlus")?;
+    progress::emit(tx, &format!("  Key ID: {}", key_info.id));
+    record_step_complete(step_db, deploy_id, 3);
+
+    record_step_start(step_db, deploy_id, 4, "Creating ECS instance");
+    progress::emit(
+        tx,
+        "\n[Step 4/8] Creating ECS instance with Hermes Agent cloud-init...",
+    );
+    let user_data_b64 = base64::engine::general_purpose::STANDARD.encode(&spec.user_data);
+    let instance_id = bp_client
+        .create_instance(
+            spec.hostname,
+            spec.size,
+            &key_name,
+            &user_data_b64,
+            &params.customer_email,
+            false,
+        )
+        .await
+        .context("Failed to create BytePlus ECS instance")?;
+    progress::emit(tx, &format!("  Instance ID: {instance_id}"));
+    record_step_complete(step_db, deploy_id, 4);
+
+    record_step_start(step_db, deploy_id, 5, "Waiting for ECS instance");
+    progress::emit(tx, "\n[Step 5/8] Waiting for instance to become active...");
+    let instance = bp_client
+        .wait_for_running(&instance_id, std::time::Duration::from_secs(300))
+        .await
+        .context("Instance did not become RUNNING within 5 minutes")?;
+    let ip = instance
+        .public_ip
+        .context("BytePlus instance has no public IP")?;
+    progress::emit(tx, &format!("  IP: {ip}"));
+    record_step_complete(step_db, deploy_id, 5);
+
+    Ok(Launched {
+        droplet_id: 0,
+        instance_id: Some(instance_id),
+        ip,
+        ssh_key_id: Some(key_info.id),
+        ssh_key_fingerprint: String::new(),
+        resource_group: None,
+    })
+}
+
+#[cfg(feature = "azure")]
+async fn launch_azure(params: &HermesParams, spec: LaunchSpec<'_>) -> Result<Launched> {
+    use clawmacdo_cloud::azure_cli::{self, AzureCliProvider};
+    use clawmacdo_cloud::cloud_provider::CreateInstanceParams;
+    use clawmacdo_cloud::CloudProvider;
+
+    let (tx, step_db, deploy_id) = (&params.progress_tx, &params.db, spec.deploy_id);
+
+    record_step_start(step_db, deploy_id, 3, "Signing in to Azure");
+    progress::emit(
+        tx,
+        "\n[Step 3/8] Signing in to Azure (the SSH key is passed inline)...",
+    );
+    azure_cli::ensure_az_cli()?;
+    azure_cli::az_login(
+        &params.azure_tenant_id,
+        &params.azure_client_id,
+        &params.azure_client_secret,
+    )?;
+    azure_cli::az_set_subscription(&params.azure_subscription_id)?;
+    let resource_group = format!("clawmacdo-{}", &deploy_id[..8]);
+    let azure = AzureCliProvider::new(
+        spec.region.to_string(),
+        resource_group.clone(),
+        params.azure_subscription_id.clone(),
+    );
+    azure.ensure_resource_group()?;
+    progress::emit(tx, &format!("  Resource group '{resource_group}' ready."));
+    record_step_complete(step_db, deploy_id, 3);
+
+    record_step_start(step_db, deploy_id, 4, "Creating Azure VM");
+    progress::emit(
+        tx,
+        "\n[Step 4/8] Creating Azure VM with Hermes Agent cloud-init...",
+    );
+    let instance_info = azure
+        .create_instance(CreateInstanceParams {
+            name: spec.hostname.to_string(),
+            region: spec.region.to_string(),
+            size: spec.size.to_string(),
+            image: config::DEFAULT_AZURE_IMAGE.to_string(),
+            ssh_key_id: spec.public_key.to_string(),
+            user_data: spec.user_data,
+            tags: vec![],
+            customer_email: params.customer_email.clone(),
+        })
+        .await
+        .context("Failed to create Azure VM")?;
+    progress::emit(tx, &format!("  VM created: {}", instance_info.name));
+    record_step_complete(step_db, deploy_id, 4);
+
+    record_step_start(step_db, deploy_id, 5, "Waiting for Azure VM");
+    progress::emit(tx, "\n[Step 5/8] Waiting for VM to become active...");
+    let instance_info = azure
+        .wait_for_active(&instance_info.name, 600)
+        .await
+        .context("Azure VM did not become active within 10 minutes")?;
+    let ip = instance_info.public_ip.context("VM has no public IP")?;
+    progress::emit(tx, &format!("  IP: {ip}"));
+    record_step_complete(step_db, deploy_id, 5);
+
+    Ok(Launched {
+        droplet_id: 0,
+        instance_id: Some(instance_info.id),
+        ip,
+        ssh_key_id: None,
+        ssh_key_fingerprint: String::new(),
+        resource_group: Some(resource_group),
+    })
+}
+
+async fn launch(
+    provider: &CloudProviderType,
+    params: &HermesParams,
+    spec: LaunchSpec<'_>,
+) -> Result<Launched> {
+    match provider {
+        #[cfg(feature = "lightsail")]
+        CloudProviderType::Lightsail => launch_lightsail(params, spec).await,
+        #[cfg(feature = "digitalocean")]
+        CloudProviderType::DigitalOcean => launch_do(params, spec).await,
+        #[cfg(feature = "tencent-cloud")]
+        CloudProviderType::Tencent => launch_tencent(params, spec).await,
+        #[cfg(feature = "byteplus")]
+        CloudProviderType::BytePlus => launch_byteplus(params, spec).await,
+        #[cfg(feature = "azure")]
+        CloudProviderType::Azure => launch_azure(params, spec).await,
+        #[allow(unreachable_patterns)]
+        other => {
+            let _ = (params, spec);
+            bail!("{} support not compiled in.", provider_label(other))
+        }
+    }
+}
+
+fn print_summary(
+    record: &DeployRecord,
+    provider: &CloudProviderType,
+    dashboard: bool,
+    bedrock_region: &str,
+    bedrock_model: &str,
+) {
+    let divider = "=".repeat(60);
+    let ip = &record.ip_address;
+    let key = &record.ssh_key_path;
+    let user = provider_ssh_user(provider);
+
+    println!("\n{divider}");
+    println!("  Hermes Agent Provision Complete");
+    println!("{divider}");
+    println!("  Provider:          {}", provider_label(provider));
+    println!("  Hostname:          {}", record.hostname);
+    println!("  IP Address:        {ip}");
+    if let Some(fqdn) = &record.fqdn {
+        println!("  DNS Name:          {fqdn}");
+    }
+    println!("  Region:            {}", record.region);
+    println!("  Size:              {}", record.size);
+    println!();
+    println!("  SSH Access:");
+    println!("    clawmacdo ssh {}", record.hostname);
+    println!("    ssh -i {key} {user}@{ip}");
+    println!();
+    println!("  Hermes data:       /opt/hermes-data on server");
+    println!("  Hermes env:        {ENV_FILE} on server");
+    println!("  AWS CLI config:    /root/.aws/config and /opt/hermes-data/.aws/config");
+    println!("  AI model:          AWS Bedrock {bedrock_model} ({bedrock_region})");
+    println!(
+        "  Health:            clawmacdo hermes-status {}",
+        record.hostname
+    );
+    println!(
+        "  Gateway logs:      clawmacdo hermes-logs {} --follow",
+        record.hostname
+    );
+    println!(
+        "  Provisioning log:  clawmacdo hermes-logs {} --provision",
+        record.hostname
+    );
+    if dashboard {
+        println!(
+            "  Dashboard tunnel:  clawmacdo tunnel {} 9119",
+            record.hostname
+        );
+        println!("                     then open http://127.0.0.1:9119");
+    }
+    println!(
+        "  Deploy Record:     ~/.clawmacdo/deploys/{}.json",
+        record.id
+    );
+    println!("{divider}\n");
+}
+
+pub async fn run(params: HermesParams) -> Result<DeployRecord> {
+    config::ensure_dirs()?;
+    let deploy_id = params
+        .deploy_id
+        .clone()
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let tx = &params.progress_tx;
+    let step_db = &params.db;
+
+    record_step_start(step_db, &deploy_id, 1, "Resolving Hermes parameters");
+    progress::emit(tx, "\n[Step 1/8] Resolving Hermes Agent parameters...");
+    let provider = match resolve_provider(&params.provider) {
+        Ok(provider) => provider,
+        Err(e) => {
+            record_step_failed(step_db, &deploy_id, 1, &e.to_string());
+            return Err(e);
+        }
+    };
+    let region = default_region(&provider, &params);
+    let size = params
+        .size
+        .clone()
+        .filter(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| default_size(&provider).to_string());
+    let hostname = params
+        .name
+        .clone()
+        .filter(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| format!("hermes-{}", &deploy_id[..8]));
+    let image = if params.image.trim().is_empty() {
+        DEFAULT_HERMES_IMAGE.to_string()
+    } else {
+        params.image.trim().to_string()
+    };
+    if params
+        .bedrock_api_key
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .is_none()
+    {
+        let err =
+            "AWS Bedrock API key required. Set AWS_BEARER_TOKEN_BEDROCK or pass --bedrock-api-key.";
+        record_step_failed(step_db, &deploy_id, 1, err);
+        bail!("{err}");
+    }
+    let bedrock_region = normalized_bedrock_region(&params);
+    let bedrock_model = normalized_bedrock_model(&params);
+    let config_content = build_bedrock_config_content(&bedrock_region, &bedrock_model);
+    let aws_cli_config_content = build_aws_cli_config_content(&bedrock_region);
+    let env_content = build_env_content(&params)?;
+    let user_data = generate_user_data(
+        &provider,
+        &image,
+        &env_content,
+        &config_content,
+        &aws_cli_config_content,
+        params.dashboard,
+        params.mount_docker_socket,
+    );
+    progress::emit(tx, &format!("  Provider: {}", provider_label(&provider)));
+    progress::emit(tx, &format!("  Region:   {region}"));
+    progress::emit(tx, &format!("  Size:     {size}"));
+    progress::emit(tx, &format!("  Hostname: {hostname}"));
+    progress::emit(tx, &format!("  Image:    {image}"));
+    progress::emit(tx, &format!("  AI Model: AWS Bedrock {bedrock_model}"));
+    progress::emit(tx, &format!("  AI Region: {bedrock_region}"));
+    record_step_complete(step_db, &deploy_id, 1);
+
+    if params.dry_run {
+        progress::emit(tx, "\n[Dry-run] No cloud resources will be created.");
+        if params.json {
+            let payload = serde_json::json!({
+                "provider": format!("hermes-{provider}"),
+                "region": region,
+                "size": size,
+                "hostname": hostname,
+                "image": image,
+                "bedrock_region": bedrock_region,
+                "bedrock_model": bedrock_model,
+                "dashboard": params.dashboard,
+                "mount_docker_socket": params.mount_docker_socket,
+                "user_data": user_data,
+            });
+            println!("{}", serde_json::to_string_pretty(&payload)?);
+        } else {
+            println!(
+                "\n--- {} user-data preview ---\n{user_data}",
+                provider_label(&provider)
+            );
+        }
+        let dry_steps = [
+            "Generating SSH key pair",
+            "Uploading SSH key",
+            "Creating instance",
+            "Waiting for instance",
+            "Waiting for SSH",
+            "Waiting for Hermes cloud-init",
+            "Saving deploy record",
+        ];
+        for (idx, label) in dry_steps.iter().enumerate() {
+            let step = idx as i32 + 2;
+            record_step_start(step_db, &deploy_id, step, label);
+            record_step_skipped(step_db, &deploy_id, step);
+        }
+        return Ok(DeployRecord {
+            id: deploy_id,
+            provider: Some(provider),
+            droplet_id: 0,
+            instance_id: Some("(dry-run)".to_string()),
+            hostname,
+            ip_address: "0.0.0.0".to_string(),
+            region,
+            size,
+            ssh_key_path: "(dry-run)".to_string(),
+            ssh_key_fingerprint: String::new(),
+            ssh_key_id: Some("(dry-run)".to_string()),
+            resource_group: None,
+            backup_restored: None,
+            created_at: Utc::now(),
+            fqdn: None,
+        });
+    }
+
+    if let Some(err) = missing_credentials(&provider, &params) {
+        record_step_failed(step_db, &deploy_id, 1, err);
+        bail!("{err}");
+    }
+
+    record_step_start(step_db, &deploy_id, 2, "Generating SSH key pair");
+    progress::emit(tx, "\n[Step 2/8] Generating SSH key pair...");
+    let keypair = ssh::generate_keypair(&deploy_id)?;
+    progress::emit(
+        tx,
+        &format!("  Key saved: {}", keypair.private_key_path.display()),
+    );
+    record_step_complete(step_db, &deploy_id, 2);
+
+    let launched = launch(
+        &provider,
+        &params,
+        LaunchSpec {
+            deploy_id: &deploy_id,
+            hostname: &hostname,
+            region: &region,
+            size: &size,
+            public_key: &keypair.public_key_openssh,
+            user_data,
+        },
+    )
+    .await?;
+    let ip = launched.ip.clone();
+    let ssh_user = provider_ssh_user(&provider);
+
+    record_step_start(step_db, &deploy_id, 6, "Waiting for SSH");
+    progress::emit(tx, "\n[Step 6/8] Waiting for SSH...");
+    ssh::wait_for_ssh(
+        &ip,
+        &keypair.private_key_path,
+        std::time::Duration::from_secs(300),
+        Some(ssh_user),
+    )
+    .await
+    .context("SSH did not become available within 5 minutes")?;
+    progress::emit(tx, "[Step 6/8] SSH ready");
+    record_step_complete(step_db, &deploy_id, 6);
+
+    record_step_start(step_db, &deploy_id, 7, "Waiting for Hermes cloud-init");
+    progress::emit(
+        tx,
+        "\n[Step 7/8] Waiting for Hermes Agent cloud-init to finish...",
+    );
+    ssh::wait_for_cloud_init(
+        &ip,
+        &keypair.private_key_path,
+        std::time::Duration::from_secs(1800),
+        Some(ssh_user),
+    )
+    .await
+    .context("Hermes Agent cloud-init did not complete within 30 minutes")?;
+    progress::emit(tx, "[Step 7/8] Hermes Agent cloud-init complete");
+    record_step_complete(step_db, &deploy_id, 7);
+
+    record_step_start(step_db, &deploy_id, 8, "Saving deploy record");
+    progress::emit(tx, "\n[Step 8/8] Saving deploy record...");
+    let mut record = DeployRecord {
+        id: deploy_id,
+        provider: Some(provider.clone()),
+        droplet_id: launched.droplet_id,
+        instance_id: launched.instance_id,
+        hostname,
+        ip_address: ip,
+        region,
+        size,
+        ssh_key_path: keypair.private_key_path.display().to_string(),
+        ssh_key_fingerprint: launched.ssh_key_fingerprint,
+        ssh_key_id: launched.ssh_key_id,
+        resource_group: launched.resource_group,
+        backup_restored: None,
+        created_at: Utc::now(),
+        fqdn: None,
+    };
+    if let Some(line) = dns::publish(&mut record).await {
+        progress::emit(tx, &line);
+    }
+    let record_path = record.save()?;
+    progress::emit(tx, &format!("  Saved: {}", record_path.display()));
+    progress::emit(tx, "\n[Step 8/8] Done!");
+    record_step_complete(step_db, &record.id, 8);
+    progress::emit(tx, "\n[Done] Hermes Agent provision complete!");
+    if params.json {
+        println!("{}", serde_json::to_string_pretty(&record)?);
+    } else {
+        print_summary(
+            &record,
+            &provider,
+            params.dashboard,
+            &bedrock_region,
+            &bedrock_model,
+        );
+    }
+    Ok(record)
+}
+
+// ══════════════════════════════════════════════════════════════════════════
+// Lifecycle commands for a provisioned Hermes instance
+// ══════════════════════════════════════════════════════════════════════════
+
+async fn run_root(target: &FleetTarget, command: &str) -> Result<String> {
+    ssh_root_as_async(&target.ip, &target.key, command, target.ssh_user())
+        .await
+        .with_context(|| format!("Command failed on {} ({})", target.hostname, target.ip))
+}
+
+fn write_file_cmd(path: &str, contents: &str, mode: &str) -> String {
+    let b64 = base64::engine::general_purpose::STANDARD.encode(contents.as_bytes());
+    format!("printf '%s' '{b64}' | base64 -d > {path} && chmod {mode} {path}")
+}
+
+/// Make sure the instance has `run.sh`. Instances bootstrapped before it
+/// existed get one matching their running containers.
+async fn ensure_run_script(target: &FleetTarget) -> Result<()> {
+    let probe = format!(
+        "if [ -x {RUN_SCRIPT} ]; then echo present; else \
+         docker inspect --format '{{{{.Config.Image}}}}|{{{{json .HostConfig.Binds}}}}' hermes 2>/dev/null; \
+         docker ps -a --format '{{{{.Names}}}}' 2>/dev/null; fi"
+    );
+    let output = run_root(target, &probe).await?;
+    if output.lines().any(|line| line.trim() == "present") {
+        return Ok(());
+    }
+    let Some((image, binds)) = output.lines().find_map(|line| line.split_once('|')) else {
+        bail!(
+            "No Hermes container on {} ({}). Is it a Hermes instance?",
+            target.hostname,
+            target.ip
+        );
+    };
+    let dashboard = output.lines().any(|line| line.trim() == "hermes-dashboard");
+    let mount_docker_socket = binds.contains("/var/run/docker.sock");
+    let install = format!(
+        "set -e; mkdir -p {PROVISION_DIR}; {}; {}",
+        write_file_cmd(IMAGE_FILE, &format!("{}\n", image.trim()), "644"),
+        write_file_cmd(
+            RUN_SCRIPT,
+            &generate_run_script(dashboard, mount_docker_socket),
+            "700"
+        ),
+    );
+    run_root(target, &install).await?;
+    Ok(())
+}
+
+fn validate_image(image: &str) -> Result<&str> {
+    let image = image.trim();
+    if image.is_empty()
+        || !image
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "._/:@-".contains(c))
+    {
+        bail!("Invalid image reference '{image}'");
+    }
+    Ok(image)
+}
+
+/// `hermes-update-image <instance> [--image ...]`: pull the image (the
+/// current one by default) and recreate the containers when it changed.
+pub async fn update_image(query: &str, image: Option<&str>, force: bool) -> Result<()> {
+    let image = image.map(validate_image).transpose()?;
+    let target = fleet::find(query)?;
+    ensure_run_script(&target).await?;
+
+    let set_image = image
+        .map(|image| format!("printf '%s\\n' {} > {IMAGE_FILE}\n", sh_quote(image)))
+        .unwrap_or_default();
+    let command = format!(
+        r#"set -euo pipefail
+{set_image}IMAGE="$(cat {IMAGE_FILE})"
+docker pull -q "$IMAGE" >/dev/null
+OLD="$(docker inspect --format '{{{{.Image}}}}' hermes 2>/dev/null || true)"
+NEW="$(docker image inspect --format '{{{{.Id}}}}' "$IMAGE")"
+if [ "$OLD" = "$NEW" ] && [ "{force}" != "true" ]; then
+  echo "unchanged $IMAGE"
+  exit 0
+fi
+{RUN_SCRIPT} >/dev/null
+docker image prune -f >/dev/null 2>&1 || true
+echo "updated $IMAGE"
+"#
+    );
+    println!(
+        "Pulling the Hermes image on {} ({})...",
+        target.hostname, target.ip
+    );
+    let output = run_root(&target, &command).await?;
+    match output.lines().rev().find_map(|l| l.split_once(' ')) {
+        Some(("unchanged", image)) => {
+            println!("{image} is already running; nothing to do (--force recreates anyway).");
+            return Ok(());
+        }
+        Some(("updated", image)) => println!("Recreated the Hermes containers on {image}."),
+        _ => println!("{}", output.trim_end()),
+    }
+    report_status(&target, false).await
+}
+
+/// Remote script that replaces or removes keys in the Hermes env file.
+fn env_set_command(assignments: &[String], unset: &[String]) -> Result<String> {
+    let mut keys = Vec::new();
+    let mut lines = String::new();
+    for assignment in assignments {
+        let assignment = assignment.trim();
+        validate_env_assignment(assignment)?;
+        let (key, _) = assignment.split_once('=').unwrap_or_default();
+        keys.push(key.to_string());
+        lines.push_str(assignment);
+        lines.push('\n');
+    }
+    for key in unset {
+        let key = key.trim();
+        validate_env_assignment(&format!("{key}="))?;
+        keys.push(key.to_string());
+    }
+    if keys.is_empty() {
+        bail!("Give at least one KEY=VALUE or --unset KEY");
+    }
+    let b64 = base64::engine::general_purpose::STANDARD.encode(lines.as_bytes());
+    Ok(format!(
+        r#"set -euo pipefail
+test -f {ENV_FILE} || {{ echo "{ENV_FILE} not found" >&2; exit 3; }}
+TMP="$(mktemp {PROVISION_DIR}/hermes.env.XXXXXX)"
+grep -v -E '^(export[[:space:]]+)?({})=' {ENV_FILE} > "$TMP" || true
+printf '%s' '{b64}' | base64 -d >> "$TMP"
+chmod 600 "$TMP"
+mv "$TMP" {ENV_FILE}
+"#,
+        keys.join("|")
+    ))
+}
+
+/// `hermes-env-set <instance> KEY=VALUE... [--unset KEY]`: edit the Hermes
+/// env file and recreate the containers so they pick it up.
+pub async fn env_set(
+    query: &str,
+    assignments: &[String],
+    unset: &[String],
+    restart: bool,
+) -> Result<()> {
+    let command = env_set_command(assignments, unset)?;
+    let target = fleet::find(query)?;
+    if restart {
+        ensure_run_script(&target).await?;
+    }
+    run_root(&target, &command).await?;
+    for assignment in assignments {
+        let (key, _) = assignment.trim().split_once('=').unwrap_or_default();
+        println!("Set {key}");
+    }
+    for key in unset {
+        println!("Removed {}", key.trim());
+    }
+    if !restart {
+        println!("Containers not restarted; the change applies on the next restart.");
+        return Ok(());
+    }
+    run_root(&target, RUN_SCRIPT).await?;
+    println!("Recreated the Hermes containers.");
+    report_status(&target, false).await
+}
+
+/// `hermes-logs <instance>`: the gateway container's logs, the dashboard's,
+/// or the provisioning log.
+pub async fn logs(
+    query: &str,
+    dashboard: bool,
+    provision: bool,
+    mut log_query: logs::LogQuery,
+    redact: bool,
+) -> Result<()> {
+    if provision {
+        log_query.source = "hermes".to_string();
+    } else {
+        log_query.source = "docker".to_string();
+        log_query.container = if dashboard {
+            "hermes-dashboard"
+        } else {
+            "hermes"
+        }
+        .to_string();
+    }
+    logs::run(query, log_query, redact).await
+}
+
+/// One Hermes container as `docker inspect` reports it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct ContainerStatus {
+    name: String,
+    state: String,
+    /// Health check result, when the image defines one.
+    health: Option<String>,
+    restarts: u64,
+    started_at: String,
+    image: String,
+}
+
+impl ContainerStatus {
+    fn is_healthy(&self) -> bool {
+        self.state == "running" && self.health.as_deref() != Some("unhealthy")
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct HermesStatus {
+    hostname: String,
+    ip: String,
+    bootstrapped: bool,
+    image: Option<String>,
+    containers: Vec<ContainerStatus>,
+    healthy: bool,
+}
+
+/// Parse `docker inspect --format '{{json .}}'` lines, ignoring other output.
+fn parse_containers(output: &str) -> Vec<ContainerStatus> {
+    output
+        .lines()
+        .filter(|line| line.trim_start().starts_with('{'))
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .map(|c| ContainerStatus {
+            name: c["Name"]
+                .as_str()
+                .unwrap_or_default()
+                .trim_start_matches('/')
+                .to_string(),
+            state: c["State"]["Status"]
+                .as_str()
+                .unwrap_or("unknown")
+                .to_string(),
+            health: c["State"]["Health"]["Status"].as_str().map(str::to_string),
+            restarts: c["RestartCount"].as_u64().unwrap_or(0),
+            started_at: c["State"]["StartedAt"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            image: c["Config"]["Image"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+        })
+        .collect()
+}
+
+async fn report_status(target: &FleetTarget, json: bool) -> Result<()> {
+    let command = format!(
+        "docker inspect --format '{{{{json .}}}}' {} 2>/dev/null; \
+         test -f {} && echo bootstrap=done; \
+         test -f {IMAGE_FILE} && echo \"image=$(cat {IMAGE_FILE})\"; true",
+        CONTAINERS.join(" "),
+        config::CLOUD_INIT_SENTINEL
+    );
+    let output = run_root(target, &command).await?;
+    let containers = parse_containers(&output);
+    let gateway_healthy = containers
+        .iter()
+        .any(|c| c.name == "hermes" && c.is_healthy());
+    let status = HermesStatus {
+        hostname: target.hostname.clone(),
+        ip: target.ip.clone(),
+        bootstrapped: output.lines().any(|l| l.trim() == "bootstrap=done"),
+        image: output
+            .lines()
+            .find_map(|l| l.trim().strip_prefix("image="))
+            .map(str::to_string),
+        healthy: gateway_healthy && containers.iter().all(ContainerStatus::is_healthy),
+        containers,
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&status)?);
+    } else {
+        println!("{} ({})", status.hostname, status.ip);
+        println!(
+            "  Bootstrap: {}",
+            if status.bootstrapped {
+                "complete"
+            } else {
+                "not finished"
+            }
+        );
+        if let Some(image) = &status.image {
+            println!("  Image:     {image}");
+        }
+        for name in CONTAINERS {
+            match status.containers.iter().find(|c| c.name == *name) {
+                Some(c) => println!(
+                    "  {:<17} {}{}  restarts {}  since {}",
+                    c.name,
+                    c.state,
+                    c.health
+                        .as_deref()
+                        .map(|h| format!(" ({h})"))
+                        .unwrap_or_default(),
+                    c.restarts,
+                    c.started_at
+                ),
+                None => println!("  {name:<17} not present"),
+            }
+        }
+    }
+    if !status.healthy {
+        bail!("Hermes on {} is not healthy", status.hostname);
+    }
+    Ok(())
+}
+
+/// `hermes-status <instance>`: bootstrap state and container health. Exits
+/// non-zero when the gateway container is not running or is unhealthy.
+pub async fn status(query: &str, json: bool) -> Result<()> {
+    let target = fleet::find(query)?;
+    report_status(&target, json).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_repeated_env_assignments() {
+        assert!(validate_env_assignment("OPENAI_API_KEY=sk-test").is_ok());
+        assert!(validate_env_assignment("_CUSTOM=value").is_ok());
+        assert!(validate_env_assignment("1BAD=value").is_err());
+        assert!(validate_env_assignment("NO_EQUALS").is_err());
+    }
+
+    #[test]
+    fn user_data_runs_gateway_and_optional_dashboard() {
+        let user_data = generate_user_data(
+            &CloudProviderType::Lightsail,
+            DEFAULT_HERMES_IMAGE,
+            "TELEGRAM_BOT_TOKEN=test\n",
+            &build_bedrock_config_content(DEFAULT_BEDROCK_REGION, DEFAULT_BEDROCK_MODEL),
+            &build_aws_cli_config_content(DEFAULT_BEDROCK_REGION),
+            true,
+            true,
+        );
+
+        assert!(user_data.contains("docker run -d"));
+        assert!(user_data.contains("AWS_CLI_CONFIG_B64="));
+        assert!(user_data.contains("awscli-exe-linux-${AWSCLI_ARCH}.zip"));
+        assert!(user_data.contains("/opt/hermes-data/.aws/config"));
+        assert!(user_data.contains("\"$HERMES_IMAGE\" gateway run"));
+        assert!(user_data.contains("hermes-dashboard"));
+        assert!(user_data.contains("/var/run/docker.sock:/var/run/docker.sock"));
+        assert!(user_data.contains(config::CLOUD_INIT_SENTINEL));
+        assert!(user_data.contains(RUN_SCRIPT));
+        assert!(!user_data.contains("mirrors.ivolces.com"));
+    }
+
+    #[test]
+    fn byteplus_user_data_fixes_dns_and_mirror_before_apt() {
+        let user_data = generate_user_data(
+            &CloudProviderType::BytePlus,
+            DEFAULT_HERMES_IMAGE,
+            "",
+            "",
+            "",
+            false,
+            false,
+        );
+        let dns = user_data.find("DNS=8.8.8.8").unwrap();
+        let apt = user_data.find("apt-get update").unwrap();
+        assert!(dns < apt);
+        assert!(user_data.contains("archive.ubuntu.com"));
+        assert!(!user_data.contains("/var/run/docker.sock"));
+    }
+
+    #[test]
+    fn build_env_content_adds_telegram_onboarding_fields() {
+        let params = HermesParams {
+            deploy_id: None,
+            provider: "lightsail".to_string(),
+            region: None,
+            do_token: String::new(),
+            tencent_secret_id: String::new(),
+            tencent_secret_key: String::new(),
+            azure_tenant_id: String::new(),
+            azure_subscription_id: String::new(),
+            azure_client_id: String::new(),
+            azure_client_secret: String::new(),
+            byteplus_access_key: String::new(),
+            byteplus_secret_key: String::new(),
+            customer_email: "test@example.com".to_string(),
+            name: None,
+            aws_access_key_id: String::new(),
+            aws_secret_access_key: String::new(),
+            aws_region: "ap-southeast-1".to_string(),
+            size: None,
+            image: DEFAULT_HERMES_IMAGE.to_string(),
+            env_file: None,
+            env_content: Some("NOUS_API_KEY=nous-test".to_string()),
+            env_vars: vec!["OPENROUTER_API_KEY=or-test".to_string()],
+            bedrock_api_key: Some("bedrock-test".to_string()),
+            bedrock_region: DEFAULT_BEDROCK_REGION.to_string(),
+            bedrock_model: DEFAULT_BEDROCK_MODEL.to_string(),
+            telegram_bot_token: Some("123456:abc".to_string()),
+            telegram_allowed_users: Some("111, 222".to_string()),
+            telegram_home_channel: None,
+            dashboard: false,
+            mount_docker_socket: false,
+            dry_run: true,
+            json: false,
+            progress_tx: None,
+            db: None,
+        };
+
+        let env = build_env_content(&params).unwrap();
+
+        assert!(env.contains("NOUS_API_KEY=nous-test\n"));
+        assert!(env.contains("OPENROUTER_API_KEY=or-test\n"));
+        assert!(env.contains("AWS_BEARER_TOKEN_BEDROCK=bedrock-test\n"));
+        assert!(env.contains("OPENAI_API_KEY=bedrock-test\n"));
+        assert!(env.contains("OPENAI_BASE_URL=https://bedrock-mantle.ap-southeast-1.api.aws/v1\n"));
+        assert!(env.contains("AWS_REGION=ap-southeast-1\n"));
+        assert!(env.contains("TELEGRAM_BOT_TOKEN=123456:abc\n"));
+        assert!(env.contains("TELEGRAM_ALLOWED_USERS=111,222\n"));
+        assert!(env.contains("TELEGRAM_HOME_CHANNEL=111\n"));
+    }
+
+    #[test]
+    fn bedrock_config_sets_nova_pro_in_singapore() {
+        let config = build_bedrock_config_content(DEFAULT_BEDROCK_REGION, DEFAULT_BEDROCK_MODEL);
+        assert!(config.contains("default: 'amazon.nova-pro-v1:0'"));
+        assert!(config.contains("provider: custom"));
+        assert!(config.contains("base_url: 'https://bedrock-mantle.ap-southeast-1.api.aws/v1'"));
+        assert!(config.contains("region: 'ap-southeast-1'"));
+    }
+
+    #[test]
+    fn env_set_replaces_keys_without_leaking_values() {
+        let cmd = env_set_command(
+            &["TELEGRAM_BOT_TOKEN=123:secret".to_string()],
+            &["OLD_KEY".to_string()],
+        )
+        .unwrap();
+        assert!(cmd.contains("^(export[[:space:]]+)?(TELEGRAM_BOT_TOKEN|OLD_KEY)="));
+        assert!(!cmd.contains("123:secret"));
+        assert!(env_set_command(&[], &[]).is_err());
+        assert!(env_set_command(&["1BAD=x".to_string()], &[]).is_err());
+        assert!(env_set_command(&[], &["BAD KEY".to_string()]).is_err());
+    }
+
+    #[test]
+    fn parses_container_health_from_inspect() {
+        let output = concat!(
+            r#"{"Name":"/hermes","RestartCount":2,"State":{"Status":"running","StartedAt":"2026-10-01T00:00:00Z","Health":{"Status":"healthy"}},"Config":{"Image":"nousresearch/hermes-agent:latest"}}"#,
+            "\n",
+            r#"{"Name":"/hermes-dashboard","RestartCount":0,"State":{"Status":"exited","StartedAt":""},"Config":{"Image":"nousresearch/hermes-agent:latest"}}"#,
+            "\nbootstrap=done\n"
+        );
+        let containers = parse_containers(output);
+        assert_eq!(containers.len(), 2);
+        assert_eq!(containers[0].name, "hermes");
+        assert_eq!(containers[0].health.as_deref(), Some("healthy"));
+        assert_eq!(containers[0].restarts, 2);
+        assert!(containers[0].is_healthy());
+        assert_eq!(containers[1].health, None);
+        assert!(!containers[1].is_healthy());
+    }
+}
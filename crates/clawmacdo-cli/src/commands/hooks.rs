@@ -3,7 +3,8 @@ use clawmacdo_core::config;
 use clawmacdo_provision::provision::commands::{
     ssh_as_openclaw_with_user_async, ssh_as_openclaw_with_user_multi_async, ssh_root_async,
 };
-use std::path::PathBuf;
+use clawmacdo_provision::provision::reverse_proxy;
+use std::path::{Path, PathBuf};
 
 const OPENCLAW_CONFIG: &str = "/home/openclaw/.openclaw/openclaw.json";
 const SESSIONS_FILE: &str = "/home/openclaw/.openclaw/agents/main/sessions/sessions.json";
@@ -110,6 +111,22 @@ fn parse_funnel_url(status_output: &str) -> Option<String> {
     })
 }
 
+/// Base URL the gateway is reachable at: the Funnel URL, else the reverse
+/// proxy URL, else the loopback address (only reachable on the instance).
+async fn gateway_base_url(ip: &str, key: &Path, ssh_user: &str) -> String {
+    if let Ok(out) = ssh_root_async(ip, key, "tailscale funnel status 2>&1").await {
+        if let Some(url) = parse_funnel_url(&out) {
+            return url;
+        }
+    }
+    if let Ok(Some(exposure)) = reverse_proxy::exposure(ip, key, ssh_user).await {
+        if let Some(url) = exposure.proxy_url() {
+            return url;
+        }
+    }
+    format!("http://127.0.0.1:{}", config::OPENCLAW_GATEWAY_PORT)
+}
+
 /// Enable webhook hooks on an OpenClaw instance.
 ///
 /// If hooks are already configured and enabled, prints the existing token.
@@ -153,13 +170,7 @@ pub async fn enable(query: &str) -> Result<()> {
                 .map(|a| a.len())
                 .unwrap_or(0);
 
-            // Get funnel URL for display.
-            let funnel_url = match ssh_root_async(&ip, &key, "tailscale funnel status 2>&1").await {
-                Ok(out) => parse_funnel_url(&out),
-                Err(_) => None,
-            };
-            let fallback_url = format!("http://{ip}:18789");
-            let base_url = funnel_url.as_deref().unwrap_or(&fallback_url);
+            let base_url = gateway_base_url(&ip, &key, ssh_user).await;
 
             println!("Hooks already enabled.");
             println!("  Token:    {token}");
@@ -254,13 +265,7 @@ pub async fn enable(query: &str) -> Result<()> {
         .find_map(|l| l.strip_prefix("TOKEN="))
         .unwrap_or("(unknown)");
 
-    // Get funnel URL for display.
-    let funnel_url = match ssh_root_async(&ip, &key, "tailscale funnel status 2>&1").await {
-        Ok(out) => parse_funnel_url(&out),
-        Err(_) => None,
-    };
-    let fallback_url2 = format!("http://{ip}:18789");
-    let base_url = funnel_url.as_deref().unwrap_or(&fallback_url2);
+    let base_url = gateway_base_url(&ip, &key, ssh_user).await;
 
     println!("\nWebhook hooks enabled!");
     println!("  Token:    {token}");
@@ -1,51 +1,34 @@
 use anyhow::Result;
-use chrono::{DateTime, Utc};
-use clawmacdo_core::config;
+use clawmacdo_core::backup_store;
 
-/// List all backup archives in ~/.clawmacdo/backups/ with sizes and dates.
-/// RRun.
-pub fn run() -> Result<()> {
-    let backups_dir = config::backups_dir()?;
+/// List all backup archives in the configured backup store with sizes and dates.
+pub async fn run(json: bool) -> Result<()> {
+    let store = backup_store::configured_store()?;
+    let entries = store.list().await?;
 
-    if !backups_dir.exists() {
-        println!("No backups directory found at {}", backups_dir.display());
+    if json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
         return Ok(());
     }
 
-    let mut entries: Vec<(String, u64, DateTime<Utc>)> = Vec::new();
-
-    for entry in std::fs::read_dir(&backups_dir)? {
-        let entry = entry?;
-        let path = entry.path();
-        if path.extension().and_then(|e| e.to_str()) == Some("gz") {
-            let name = path
-                .file_name()
-                .unwrap_or_default()
-                .to_string_lossy()
-                .to_string();
-            let meta = std::fs::metadata(&path)?;
-            let size = meta.len();
-            let modified: DateTime<Utc> = meta.modified()?.into();
-            entries.push((name, size, modified));
-        }
-    }
-
     if entries.is_empty() {
-        println!("No backup archives found in {}", backups_dir.display());
+        println!("No backup archives found in {}", store.describe());
         return Ok(());
     }
 
-    entries.sort_by(|a, b| b.2.cmp(&a.2)); // newest first
-
-    println!("Backups in {}:\n", backups_dir.display());
-    println!("  {:<50}  {:>10}  Date", "Name", "Size");
-    println!("  {}", "-".repeat(80));
-    for (name, size, date) in &entries {
+    println!("Backups in {}:\n", store.describe());
+    println!(
+        "  {:<54}  {:>10}  {:<23}  Encrypted",
+        "Name", "Size", "Date"
+    );
+    println!("  {}", "-".repeat(100));
+    for b in &entries {
         println!(
-            "  {:<50}  {:>10}  {}",
-            name,
-            format_size(*size),
-            date.format("%Y-%m-%d %H:%M:%S UTC")
+            "  {:<54}  {:>10}  {:<23}  {}",
+            b.name,
+            format_size(b.size),
+            b.modified.format("%Y-%m-%d %H:%M:%S UTC"),
+            if b.encrypted { "yes" } else { "no" }
         );
     }
     println!("\n  Total: {} backup(s)", entries.len());
@@ -1,3 +1,5 @@
+use super::dns;
+use super::restore_backup::post_restore_repair_cmd;
 use anyhow::{Context, Result};
 use chrono::Utc;
 use clawmacdo_cloud::lightsail_cli::LightsailCliProvider;
@@ -28,61 +30,6 @@ pub struct RestoreResult {
     pub ssh_key_path: String,
 }
 
-fn build_post_restore_repair_cmd() -> String {
-    let home = config::OPENCLAW_HOME;
-    let mut cmd = r#"export HOME="__HOME__"
-export PATH="__HOME__/.local/bin:__HOME__/.local/share/pnpm:/usr/local/bin:/usr/bin:/bin:$PATH"
-export XDG_RUNTIME_DIR=/run/user/$(id -u)
-export DBUS_SESSION_BUS_ADDRESS=unix:path=/run/user/$(id -u)/bus
-if [ ! -S "$XDG_RUNTIME_DIR/bus" ]; then
-  dbus-daemon --session --address="$DBUS_SESSION_BUS_ADDRESS" --fork >/dev/null 2>&1 || true
-fi
-
-node <<'NODE'
-const fs = require('fs');
-const path = require('path');
-const home = process.env.HOME || '__HOME__';
-const configPath = path.join(home, '.openclaw', 'openclaw.json');
-
-let changed = false;
-try {
-  const cfg = JSON.parse(fs.readFileSync(configPath, 'utf8'));
-  const telegram = cfg.channels && cfg.channels.telegram;
-  if (telegram && telegram.streaming !== undefined) {
-    const streaming = telegram.streaming;
-    if (!streaming || typeof streaming !== 'object' || Array.isArray(streaming)) {
-      delete telegram.streaming;
-      changed = true;
-    }
-  }
-  if (changed) {
-    fs.copyFileSync(configPath, configPath + '.clawmacdo-restore.bak');
-    fs.writeFileSync(configPath, JSON.stringify(cfg, null, 2) + '\n', { mode: 0o600 });
-    fs.chmodSync(configPath, 0o600);
-  }
-} catch (_) {}
-console.log(changed ? 'telegram config: normalized legacy streaming value' : 'telegram config: ok');
-NODE
-
-(openclaw doctor --fix >/dev/null 2>&1 || true)
-(systemctl --user daemon-reload 2>/dev/null || true)
-(systemctl --user restart openclaw-gateway.service >/dev/null 2>&1 || \
- systemctl --user start openclaw-gateway.service >/dev/null 2>&1 || true)
-for i in $(seq 1 45); do
-  if curl -fsS --max-time 2 http://127.0.0.1:18789/health >/dev/null 2>&1; then
-    echo 'gateway: healthy'
-    exit 0
-  fi
-  sleep $(( i < 10 ? 2 : 4 ))
-done
-echo 'gateway: FAILED - not healthy after restore repair'
-exit 1
-"#
-    .to_string();
-    cmd = cmd.replace("__HOME__", home);
-    cmd
-}
-
 pub async fn run(params: LsRestoreParams) -> Result<RestoreResult> {
     config::ensure_dirs()?;
 
@@ -211,7 +158,7 @@ pub async fn run(params: LsRestoreParams) -> Result<RestoreResult> {
         ssh_as_openclaw_with_user_async(
             &ip,
             &keypair.private_key_path,
-            &build_post_restore_repair_cmd(),
+            &post_restore_repair_cmd(),
             "ubuntu",
         )
         .await
@@ -252,7 +199,7 @@ pub async fn run(params: LsRestoreParams) -> Result<RestoreResult> {
         .context("Failed to update deployment status")?;
 
     // Save JSON deploy record
-    let record = DeployRecord {
+    let mut record = DeployRecord {
         id: deploy_id.clone(),
         provider: Some(CloudProviderType::Lightsail),
         droplet_id: 0,
@@ -267,7 +214,11 @@ pub async fn run(params: LsRestoreParams) -> Result<RestoreResult> {
         resource_group: None,
         backup_restored: None,
         created_at: Utc::now(),
+        fqdn: None,
     };
+    if let Some(line) = dns::publish(&mut record).await {
+        progress::emit(tx, &line);
+    }
     let record_path = record.save()?;
 
     progress::emit(tx, "\n--- Restore Complete ---");
@@ -288,16 +239,3 @@ pub async fn run(params: LsRestoreParams) -> Result<RestoreResult> {
         ssh_key_path: keypair.private_key_path.display().to_string(),
     })
 }
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn post_restore_repair_normalizes_legacy_telegram_streaming_and_waits_for_health() {
-        let cmd = build_post_restore_repair_cmd();
-        assert!(cmd.contains("delete telegram.streaming"));
-        assert!(cmd.contains("openclaw doctor --fix"));
-        assert!(cmd.contains("http://127.0.0.1:18789/health"));
-    }
-}
@@ -1,3 +1,4 @@
+use super::dns;
 use anyhow::{bail, Context, Result};
 use chrono::Utc;
 use clawmacdo_cloud::lightsail_cli::LightsailCliProvider;
@@ -497,7 +498,9 @@ fn line_bool(output: &str, key: &str) -> bool {
     line_value(output, key).as_deref() == Some("true")
 }
 
-fn save_deploy_record(
+/// Save the deploy record and point its DNS name at the instance; returns
+/// the DNS line to show, if any.
+async fn save_deploy_record(
     deploy_id: &str,
     hostname: &str,
     ip: &str,
@@ -505,7 +508,7 @@ fn save_deploy_record(
     size: &str,
     key_path: &str,
     key_fingerprint: String,
-) -> Result<()> {
+) -> Result<Option<String>> {
     let conn = db::init_db().context("Failed to open deployments database")?;
     db::insert_deployment(
         &conn,
@@ -521,7 +524,7 @@ fn save_deploy_record(
     db::update_deployment_status(&conn, deploy_id, "completed", Some(ip), Some(hostname))
         .context("Failed to update deployment status")?;
 
-    let record = DeployRecord {
+    let mut record = DeployRecord {
         id: deploy_id.to_string(),
         provider: Some(CloudProviderType::Lightsail),
         droplet_id: 0,
@@ -536,9 +539,11 @@ fn save_deploy_record(
         resource_group: None,
         backup_restored: None,
         created_at: Utc::now(),
+        fqdn: None,
     };
+    let dns_line = dns::publish(&mut record).await;
     record.save()?;
-    Ok(())
+    Ok(dns_line)
 }
 
 pub async fn run(params: LsRestoreFastParams) -> Result<()> {
@@ -650,7 +655,7 @@ pub async fn run(params: LsRestoreFastParams) -> Result<()> {
     .context("Lightsail instance became active but SSH did not become ready")?;
 
     let key_path = keypair.private_key_path.display().to_string();
-    save_deploy_record(
+    if let Some(line) = save_deploy_record(
         &deploy_id,
         &hostname,
         &ip,
@@ -658,7 +663,11 @@ pub async fn run(params: LsRestoreFastParams) -> Result<()> {
         &size,
         &key_path,
         key_info.fingerprint.unwrap_or_default(),
-    )?;
+    )
+    .await?
+    {
+        emit(params.json, line);
+    }
 
     emit(
         params.json,
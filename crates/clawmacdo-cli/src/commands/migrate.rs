@@ -1,126 +1,592 @@
+use crate::commands::backup_remote;
 use crate::commands::deploy::{self, DeployParams};
-use anyhow::{Context, Result};
-use chrono::Utc;
+use crate::commands::destroy::{self, DestroyParams};
+use crate::commands::dns;
+use crate::commands::restore_backup::{self, RestoreOptions};
+use anyhow::{bail, Context, Result};
+use clawmacdo_core::backup_store;
 use clawmacdo_core::config;
-use clawmacdo_ssh as ssh;
+use clawmacdo_db as db;
+use clawmacdo_provision::provision::commands::ssh_root_as_async;
 use clawmacdo_ui::ui;
+use serde::Deserialize;
+use std::collections::BTreeSet;
 use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-/// Parameters for a cloud-to-cloud migration.
+/// Parameters for an instance-to-instance migration.
+///
+/// `target` describes the new instance exactly like `deploy` does.
+/// `source_credentials` are used to destroy the source, which may live on
+/// a different provider or account.
 pub struct MigrateParams {
-    pub provider: String,
+    /// Deploy ID, hostname, or IP address of the source instance.
+    pub source: String,
+    pub target: DeployParams,
+    pub source_credentials: SourceCredentials,
+    pub destroy_source: bool,
+    /// Grace period before the source is destroyed.
+    pub destroy_after: Duration,
+    pub yes: bool,
+}
+
+/// Cloud credentials for the source instance's provider. When none are set
+/// for that provider, the target's credentials for it are used instead.
+#[derive(Debug, Default, Clone)]
+pub struct SourceCredentials {
     pub do_token: String,
     pub tencent_secret_id: String,
     pub tencent_secret_key: String,
+    pub aws_access_key_id: String,
+    pub aws_secret_access_key: String,
     pub azure_tenant_id: String,
     pub azure_subscription_id: String,
     pub azure_client_id: String,
     pub azure_client_secret: String,
-    pub anthropic_key: String,
-    pub openai_key: String,
-    pub gemini_key: String,
-    pub whatsapp_phone_number: String,
-    pub telegram_bot_token: String,
-    pub source_ip: String,
-    pub source_key: PathBuf,
-    pub region: Option<String>,
-    pub size: Option<String>,
-    pub hostname: Option<String>,
-    pub enable_sandbox: bool,
-    pub tailscale: bool,
-    pub tailscale_auth_key: Option<String>,
-    pub primary_model: String,
-    pub failover_1: String,
-    pub failover_2: String,
-    pub profile: String,
+    pub byteplus_access_key: String,
+    pub byteplus_secret_key: String,
+}
+
+impl SourceCredentials {
+    /// The target deploy's credentials, used when the source shares them.
+    fn from_target(target: &DeployParams) -> Self {
+        Self {
+            do_token: target.do_token.clone(),
+            tencent_secret_id: target.tencent_secret_id.clone(),
+            tencent_secret_key: target.tencent_secret_key.clone(),
+            aws_access_key_id: target.aws_access_key_id.clone(),
+            aws_secret_access_key: target.aws_secret_access_key.clone(),
+            azure_tenant_id: target.azure_tenant_id.clone(),
+            azure_subscription_id: target.azure_subscription_id.clone(),
+            azure_client_id: target.azure_client_id.clone(),
+            azure_client_secret: target.azure_client_secret.clone(),
+            byteplus_access_key: target.byteplus_access_key.clone(),
+            byteplus_secret_key: target.byteplus_secret_key.clone(),
+        }
+    }
+}
+
+/// Pick one provider's credential set: the source flags when they are all
+/// set, the target's when none are. A partial set is an error rather than
+/// being completed from the target, which could pair keys from two accounts.
+fn credential_set<const N: usize>(
+    provider: &config::CloudProviderType,
+    hostname: &str,
+    source: [(&str, &str); N],
+    fallback: [&str; N],
+) -> Result<[String; N]> {
+    let set = source.iter().filter(|(value, _)| !value.is_empty()).count();
+    let values = if set == N {
+        source.map(|(value, _)| value)
+    } else if set == 0 {
+        fallback
+    } else {
+        let missing: Vec<&str> = source
+            .iter()
+            .filter(|(value, _)| value.is_empty())
+            .map(|(_, flag)| *flag)
+            .collect();
+        bail!(
+            "Incomplete {provider} source credentials: also pass {}",
+            missing.join(", ")
+        );
+    };
+    if values.iter().any(|v| v.is_empty()) {
+        bail!(
+            "No {provider} credentials to destroy the source {hostname}; pass the --source-* credential flags"
+        );
+    }
+    Ok(values.map(str::to_string))
+}
+
+/// Destroy parameters for the source, carrying only the credentials of the
+/// source record's provider.
+fn source_destroy_params(
+    source: &config::DeployRecord,
+    creds: &SourceCredentials,
+    fallback: &SourceCredentials,
+) -> Result<DestroyParams> {
+    let provider = source
+        .provider
+        .clone()
+        .unwrap_or(config::CloudProviderType::DigitalOcean);
+    let host = source.hostname.as_str();
+    let mut params = DestroyParams {
+        provider: provider.to_string(),
+        do_token: String::new(),
+        tencent_secret_id: String::new(),
+        tencent_secret_key: String::new(),
+        aws_access_key_id: String::new(),
+        aws_secret_access_key: String::new(),
+        aws_region: source.region.clone(),
+        azure_tenant_id: String::new(),
+        azure_subscription_id: String::new(),
+        azure_client_id: String::new(),
+        azure_client_secret: String::new(),
+        azure_resource_group: source.resource_group.clone().unwrap_or_default(),
+        byteplus_access_key: String::new(),
+        byteplus_secret_key: String::new(),
+        name: source.hostname.clone(),
+        ip_address: source.ip_address.clone(),
+        yes: true,
+    };
+    match provider {
+        config::CloudProviderType::DigitalOcean => {
+            [params.do_token] = credential_set(
+                &provider,
+                host,
+                [(&creds.do_token, "--source-do-token")],
+                [&fallback.do_token],
+            )?;
+        }
+        config::CloudProviderType::Tencent => {
+            [params.tencent_secret_id, params.tencent_secret_key] = credential_set(
+                &provider,
+                host,
+                [
+                    (&creds.tencent_secret_id, "--source-tencent-secret-id"),
+                    (&creds.tencent_secret_key, "--source-tencent-secret-key"),
+                ],
+                [&fallback.tencent_secret_id, &fallback.tencent_secret_key],
+            )?;
+        }
+        config::CloudProviderType::Lightsail => {
+            [params.aws_access_key_id, params.aws_secret_access_key] = credential_set(
+                &provider,
+                host,
+                [
+                    (&creds.aws_access_key_id, "--source-aws-access-key-id"),
+                    (
+                        &creds.aws_secret_access_key,
+                        "--source-aws-secret-access-key",
+                    ),
+                ],
+                [&fallback.aws_access_key_id, &fallback.aws_secret_access_key],
+            )?;
+        }
+        config::CloudProviderType::Azure => {
+            [
+                params.azure_tenant_id,
+                params.azure_subscription_id,
+                params.azure_client_id,
+                params.azure_client_secret,
+            ] = credential_set(
+                &provider,
+                host,
+                [
+                    (&creds.azure_tenant_id, "--source-azure-tenant-id"),
+                    (
+                        &creds.azure_subscription_id,
+                        "--source-azure-subscription-id",
+                    ),
+                    (&creds.azure_client_id, "--source-azure-client-id"),
+                    (&creds.azure_client_secret, "--source-azure-client-secret"),
+                ],
+                [
+                    &fallback.azure_tenant_id,
+                    &fallback.azure_subscription_id,
+                    &fallback.azure_client_id,
+                    &fallback.azure_client_secret,
+                ],
+            )?;
+        }
+        config::CloudProviderType::BytePlus => {
+            [params.byteplus_access_key, params.byteplus_secret_key] = credential_set(
+                &provider,
+                host,
+                [
+                    (&creds.byteplus_access_key, "--source-byteplus-access-key"),
+                    (&creds.byteplus_secret_key, "--source-byteplus-secret-key"),
+                ],
+                [&fallback.byteplus_access_key, &fallback.byteplus_secret_key],
+            )?;
+        }
+    }
+    Ok(params)
+}
+
+/// Channels configured on an instance, used to verify a migration.
+#[derive(Debug, Default, Deserialize, PartialEq, Eq)]
+pub struct ChannelSnapshot {
+    #[serde(default)]
+    pub enabled: BTreeSet<String>,
+    #[serde(default)]
+    pub whatsapp_linked: bool,
+}
+
+/// Look up a deploy record by hostname, IP, or deploy ID.
+fn find_deploy_record(query: &str) -> Result<config::DeployRecord> {
+    let deploys_dir = config::deploys_dir()?;
+    if !deploys_dir.exists() {
+        bail!("No deploy records found. Deploy an instance first.");
+    }
+
+    for entry in std::fs::read_dir(&deploys_dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let contents = std::fs::read_to_string(&path)?;
+        let record: config::DeployRecord = match serde_json::from_str(&contents) {
+            Ok(r) => r,
+            Err(_) => continue,
+        };
+        if record.id == query || record.hostname == query || record.ip_address == query {
+            return Ok(record);
+        }
+    }
+
+    bail!("No deploy record found for '{query}'. Use a deploy ID, hostname, or IP address.");
+}
+
+fn ssh_user_for_provider(provider: &Option<config::CloudProviderType>) -> &'static str {
+    match provider {
+        Some(config::CloudProviderType::Lightsail) => "ubuntu",
+        Some(config::CloudProviderType::Azure) => "azureuser",
+        _ => "root",
+    }
+}
+
+/// Node script printing the enabled channels and whether WhatsApp has a linked session.
+fn channel_snapshot_cmd() -> String {
+    r#"node <<'NODE'
+const fs = require('fs');
+const path = require('path');
+const root = path.join('__HOME__', '.openclaw');
+let cfg = {};
+try {
+  cfg = JSON.parse(fs.readFileSync(path.join(root, 'openclaw.json'), 'utf8'));
+} catch (_) {}
+const channels = cfg.channels || {};
+const enabled = Object.keys(channels).filter((name) => {
+  const channel = channels[name];
+  return channel && typeof channel === 'object' && channel.enabled !== false;
+});
+let whatsappLinked = false;
+const waDir = path.join(root, 'credentials', 'whatsapp');
+try {
+  whatsappLinked = fs
+    .readdirSync(waDir)
+    .some((account) => fs.existsSync(path.join(waDir, account, 'creds.json')));
+} catch (_) {}
+console.log(JSON.stringify({ enabled, whatsapp_linked: whatsappLinked }));
+NODE
+"#
+    .replace("__HOME__", config::OPENCLAW_HOME)
+}
+
+fn parse_channel_snapshot(output: &str) -> Result<ChannelSnapshot> {
+    let line = output
+        .lines()
+        .rev()
+        .find(|l| l.trim_start().starts_with('{'))
+        .context("Channel check returned no JSON")?;
+    serde_json::from_str(line.trim()).context("Failed to parse channel check output")
+}
+
+async fn channel_snapshot(record: &config::DeployRecord) -> Result<ChannelSnapshot> {
+    let key = PathBuf::from(&record.ssh_key_path);
+    let output = ssh_root_as_async(
+        &record.ip_address,
+        &key,
+        &channel_snapshot_cmd(),
+        ssh_user_for_provider(&record.provider),
+    )
+    .await
+    .with_context(|| format!("Failed to read channels on {}", record.ip_address))?;
+    parse_channel_snapshot(&output)
 }
 
-/// Run the full migrate flow: remote backup from source, then deploy to new droplet.
-/// RRun.
+/// Channels working on the source that are not working on the target.
+pub fn missing_channels(source: &ChannelSnapshot, target: &ChannelSnapshot) -> Vec<String> {
+    let mut missing: Vec<String> = source
+        .enabled
+        .difference(&target.enabled)
+        .cloned()
+        .collect();
+    if source.whatsapp_linked && !target.whatsapp_linked {
+        missing.push("whatsapp (session not linked)".into());
+    }
+    missing
+}
+
+/// Run the full migrate flow: back up the source, deploy the target on any
+/// provider, restore the full state, verify channels and optionally destroy
+/// the source.
 pub async fn run(params: MigrateParams) -> Result<()> {
     config::ensure_dirs()?;
+    let MigrateParams {
+        source,
+        mut target,
+        source_credentials,
+        destroy_source,
+        destroy_after,
+        yes,
+    } = params;
 
-    // ── Step 1: SSH into source droplet ─────────────────────────────────
-    let sp = ui::spinner("[Migrate 1/5] Connecting to source droplet...");
-    let source_ip = params.source_ip.clone();
-    let source_key = params.source_key.clone();
-    let verify_ip = source_ip.clone();
-    let verify_key = source_key.clone();
-    tokio::task::spawn_blocking(move || ssh::exec(&verify_ip, &verify_key, "echo ok"))
-        .await?
-        .context("Failed to connect to source droplet")?;
-    sp.finish_with_message("[Migrate 1/5] Connected to source");
-
-    // ── Step 2: Remote backup on source ─────────────────────────────────
-    let sp = ui::spinner("[Migrate 2/5] Creating backup on source droplet...");
-    let timestamp = Utc::now().format("%Y%m%d_%H%M%S").to_string();
-    let remote_archive = format!("/tmp/openclaw_migrate_{timestamp}.tar.gz");
-    let tar_cmd = format!("cd /root && tar czf {remote_archive} .openclaw/ 2>/dev/null && echo ok");
-    let tar_ip = source_ip.clone();
-    let tar_key = source_key.clone();
-    tokio::task::spawn_blocking(move || ssh::exec(&tar_ip, &tar_key, &tar_cmd))
-        .await?
-        .context("Failed to create remote backup")?;
-    sp.finish_with_message("[Migrate 2/5] Remote backup created");
-
-    // ── Step 3: SCP download backup to local ────────────────────────────
-    let sp = ui::spinner("[Migrate 3/5] Downloading backup from source...");
-    let local_archive_name = format!("openclaw_migrate_{timestamp}.tar.gz");
-    let local_archive = config::backups_dir()?.join(&local_archive_name);
-    let dl_ip = source_ip.clone();
-    let dl_key = source_key.clone();
-    let dl_remote = remote_archive.clone();
-    let dl_local = local_archive.clone();
-    tokio::task::spawn_blocking(move || ssh::scp_download(&dl_ip, &dl_key, &dl_remote, &dl_local))
-        .await?
-        .context("Failed to download backup from source")?;
+    // ── Step 1: Look up and inspect the source ──────────────────────────
+    let mut source = find_deploy_record(&source)?;
+    let sp = ui::spinner("[Migrate 1/6] Checking channels on source...");
+    let source_channels = channel_snapshot(&source).await?;
     sp.finish_with_message(format!(
-        "[Migrate 3/5] Backup downloaded: {}",
-        local_archive.display()
+        "[Migrate 1/6] Source {} ({}): {} channel(s) enabled",
+        source.hostname,
+        source.ip_address,
+        source_channels.enabled.len()
     ));
 
-    // ── Step 4: Run full deploy with the downloaded backup ──────────────
-    println!("[Migrate 4/5] Starting deploy to new droplet...");
-    let deploy_params = DeployParams {
-        customer_name: String::new(),
-        customer_email: String::new(),
-        provider: params.provider,
-        do_token: params.do_token,
-        tencent_secret_id: params.tencent_secret_id,
-        tencent_secret_key: params.tencent_secret_key,
-        aws_access_key_id: String::new(),
-        aws_secret_access_key: String::new(),
-        aws_region: String::new(),
-        azure_tenant_id: params.azure_tenant_id,
-        azure_subscription_id: params.azure_subscription_id,
-        azure_client_id: params.azure_client_id,
-        azure_client_secret: params.azure_client_secret,
-        anthropic_key: params.anthropic_key,
-        openai_key: params.openai_key,
-        gemini_key: params.gemini_key,
-        opencode_api_key: String::new(),
-        whatsapp_phone_number: params.whatsapp_phone_number,
-        telegram_bot_token: params.telegram_bot_token,
-        region: params.region,
-        size: params.size,
-        hostname: params.hostname,
-        backup: Some(local_archive),
-        enable_backups: false,
-        enable_sandbox: params.enable_sandbox,
-        tailscale: params.tailscale,
-        tailscale_auth_key: params.tailscale_auth_key,
-        primary_model: params.primary_model,
-        failover_1: params.failover_1,
-        failover_2: params.failover_2,
-        profile: params.profile,
-        spot: false,
-        non_interactive: false,
-        progress_tx: None,
+    // ── Step 2: Stream a full-state backup from the source ──────────────
+    let sp = ui::spinner("[Migrate 2/6] Backing up source state...");
+    let store = backup_store::configured_store()?;
+    let (archive, manifest) = backup_remote::backup_instance_full(&source, store.as_ref())
+        .await
+        .context("Failed to back up the source instance")?;
+    sp.finish_with_message(format!(
+        "[Migrate 2/6] Backup saved: {} ({} files)",
+        archive.display(),
+        manifest.files.len()
+    ));
+
+    // ── Step 3: Deploy the target ──────────────────────────────────────
+    println!(
+        "[Migrate 3/6] Deploying target on {}...",
+        target.provider.as_str()
+    );
+    // Resolve the source's credentials up front so a destroy can't fail
+    // after the target is already running.
+    let destroy_params = if destroy_source {
+        Some(source_destroy_params(
+            &source,
+            &source_credentials,
+            &SourceCredentials::from_target(&target),
+        )?)
+    } else {
+        None
     };
-    let record = deploy::run(deploy_params).await?;
 
-    // ── Step 5: Print migration summary ─────────────────────────────────
-    ui::print_migrate_summary(&source_ip, &record);
+    let conn = db::init_db()?;
+    let db_handle: deploy::Db = Arc::new(Mutex::new(conn));
+    let deploy_id = uuid::Uuid::new_v4().to_string();
+    if let Ok(conn) = db_handle.lock() {
+        db::insert_deployment(
+            &conn,
+            &deploy_id,
+            &target.customer_name,
+            &target.customer_email,
+            &target.provider,
+            target.region.as_deref().unwrap_or(""),
+            target.size.as_deref().unwrap_or(""),
+            target.hostname.as_deref().unwrap_or(""),
+        )?;
+    }
+    target.deploy_id = Some(deploy_id.clone());
+    target.non_interactive = true;
+    target.db = Some(db_handle.clone());
+
+    let mut record = match deploy::run(target).await {
+        Ok(record) => {
+            if let Ok(conn) = db_handle.lock() {
+                let _ = db::update_deployment_status(
+                    &conn,
+                    &deploy_id,
+                    "completed",
+                    Some(&record.ip_address),
+                    Some(&record.hostname),
+                );
+            }
+            record
+        }
+        Err(e) => {
+            if let Ok(conn) = db_handle.lock() {
+                let _ = db::update_deployment_status(&conn, &deploy_id, "failed", None, None);
+            }
+            return Err(e.context("Target deploy failed; the source was left untouched"));
+        }
+    };
+
+    // ── Step 4: Restore the full state onto the target ──────────────────
+    println!("[Migrate 4/6] Restoring state onto {}...", record.hostname);
+    restore_backup::restore_into(
+        &record,
+        &archive,
+        RestoreOptions {
+            yes: true,
+            dry_run: false,
+            skip_safety_backup: true,
+        },
+    )
+    .await
+    .context("Failed to restore source state onto the target")?;
+
+    // ── Step 5: Verify channels ─────────────────────────────────────────
+    let sp = ui::spinner("[Migrate 5/6] Verifying channels on target...");
+    let target_channels = channel_snapshot(&record).await?;
+    let missing = missing_channels(&source_channels, &target_channels);
+    if !missing.is_empty() {
+        sp.finish_with_message("[Migrate 5/6] Channel verification failed");
+        ui::print_migrate_summary(&source.ip_address, &record);
+        bail!(
+            "Channels missing on target: {}. The source {} was left running.",
+            missing.join(", "),
+            source.hostname
+        );
+    }
+    sp.finish_with_message(format!(
+        "[Migrate 5/6] Channels verified: {}",
+        if target_channels.enabled.is_empty() {
+            "none configured".to_string()
+        } else {
+            target_channels
+                .enabled
+                .iter()
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+    ));
+    if let Some(line) = dns::transfer(&mut source, &mut record).await {
+        println!("{line}");
+    }
+
+    ui::print_migrate_summary(&source.ip_address, &record);
+
+    // ── Step 6: Optionally destroy the source ───────────────────────────
+    let Some(destroy_params) = destroy_params else {
+        println!(
+            "\n[Migrate 6/6] Source {} left running. Destroy it with `clawmacdo destroy --provider {} --name {}` once you are satisfied.",
+            source.hostname,
+            source
+                .provider
+                .clone()
+                .unwrap_or(config::CloudProviderType::DigitalOcean),
+            source.hostname
+        );
+        return Ok(());
+    };
+    if !yes {
+        let confirmed = dialoguer::Confirm::new()
+            .with_prompt(format!(
+                "Destroy source {} ({})?",
+                source.hostname, source.ip_address
+            ))
+            .default(false)
+            .interact()?;
+        if !confirmed {
+            println!("Source left running.");
+            return Ok(());
+        }
+    }
+    let secs = destroy_after.as_secs();
+    if secs > 0 {
+        println!(
+            "\n[Migrate 6/6] Destroying source {} in {secs}s. Press Ctrl-C to keep it.",
+            source.hostname
+        );
+        tokio::time::sleep(destroy_after).await;
+    }
+    println!("[Migrate 6/6] Destroying source {}...", source.hostname);
+    destroy::run(destroy_params)
+        .await
+        .context("Migration succeeded but destroying the source failed")?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(enabled: &[&str], whatsapp_linked: bool) -> ChannelSnapshot {
+        ChannelSnapshot {
+            enabled: enabled.iter().map(|s| s.to_string()).collect(),
+            whatsapp_linked,
+        }
+    }
+
+    #[test]
+    fn destroy_params_use_only_the_source_providers_credentials() {
+        let source = config::DeployRecord {
+            id: "src".into(),
+            provider: Some(config::CloudProviderType::Lightsail),
+            droplet_id: 0,
+            instance_id: Some("openclaw-src".into()),
+            hostname: "openclaw-src".into(),
+            ip_address: "203.0.113.7".into(),
+            region: "us-west-2".into(),
+            size: "s-2vcpu-4gb".into(),
+            ssh_key_path: String::new(),
+            ssh_key_fingerprint: String::new(),
+            ssh_key_id: None,
+            resource_group: None,
+            backup_restored: None,
+            created_at: chrono::Utc::now(),
+            fqdn: None,
+        };
+        let fallback = SourceCredentials {
+            do_token: "do-target".into(),
+            aws_access_key_id: "AKIATARGET".into(),
+            aws_secret_access_key: "target-secret".into(),
+            ..Default::default()
+        };
+        // A complete source set is used as is.
+        let creds = SourceCredentials {
+            aws_access_key_id: "AKIASOURCE".into(),
+            aws_secret_access_key: "source-secret".into(),
+            ..Default::default()
+        };
+        let params = source_destroy_params(&source, &creds, &fallback).unwrap();
+        assert_eq!(params.provider, "lightsail");
+        assert_eq!(params.aws_access_key_id, "AKIASOURCE");
+        assert_eq!(params.aws_secret_access_key, "source-secret");
+        assert_eq!(params.aws_region, "us-west-2");
+        assert!(params.do_token.is_empty());
+
+        // A partial set is never completed from the target's account.
+        let partial = SourceCredentials {
+            aws_access_key_id: "AKIASOURCE".into(),
+            ..Default::default()
+        };
+        let Err(err) = source_destroy_params(&source, &partial, &fallback) else {
+            panic!("a partial source credential set must be rejected");
+        };
+        let err = err.to_string();
+        assert!(err.contains("--source-aws-secret-access-key"), "{err}");
+
+        // With no source credentials at all, the target's set applies.
+        let params =
+            source_destroy_params(&source, &SourceCredentials::default(), &fallback).unwrap();
+        assert_eq!(params.aws_access_key_id, "AKIATARGET");
+        assert_eq!(params.aws_secret_access_key, "target-secret");
+
+        let err = source_destroy_params(
+            &source,
+            &SourceCredentials::default(),
+            &SourceCredentials::default(),
+        );
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn missing_channels_reports_disabled_and_unlinked() {
+        let source = snapshot(&["telegram", "whatsapp"], true);
+        assert!(missing_channels(&source, &snapshot(&["telegram", "whatsapp"], true)).is_empty());
+        assert_eq!(
+            missing_channels(&source, &snapshot(&["whatsapp"], false)),
+            vec!["telegram", "whatsapp (session not linked)"]
+        );
+        // Extra channels on the target are fine.
+        assert!(missing_channels(&snapshot(&[], false), &source).is_empty());
+    }
+
+    #[test]
+    fn parse_channel_snapshot_uses_last_json_line() {
+        let parsed = parse_channel_snapshot(
+            "warning: something\n{\"enabled\":[\"telegram\"],\"whatsapp_linked\":false}\n",
+        )
+        .unwrap();
+        assert_eq!(parsed, snapshot(&["telegram"], false));
+        assert!(parse_channel_snapshot("no json").is_err());
+    }
+}
@@ -1,25 +1,33 @@
 #[cfg(feature = "byteplus")]
 pub mod ark;
+pub mod backup;
+pub mod backup_remote;
+pub mod bake;
 pub mod bedrock_token;
 #[cfg(feature = "byteplus")]
 pub mod bp_restore;
 #[cfg(feature = "byteplus")]
 pub mod bp_snapshot;
+pub mod chat;
 pub mod claude_auth;
 pub mod cron_schedule;
 pub mod deploy;
 pub mod deploy_cmd;
 pub mod destroy;
+pub mod dns;
 #[cfg(feature = "digitalocean")]
 pub mod do_restore;
 #[cfg(feature = "digitalocean")]
 pub mod do_snapshot;
 pub mod docker_fix;
+pub mod expose;
+pub mod fleet;
 pub mod gws;
 pub mod gyne_consumer_profile;
-#[cfg(feature = "lightsail")]
-pub mod hermes_lightsail;
+pub mod hermes;
 pub mod hooks;
+pub mod list_backups;
+pub mod logs;
 #[cfg(feature = "lightsail")]
 pub mod ls_restore;
 #[cfg(feature = "lightsail")]
@@ -27,6 +35,7 @@ pub mod ls_restore_fast;
 #[cfg(feature = "lightsail")]
 pub mod ls_snapshot;
 pub mod memory_download;
+pub mod migrate;
 pub mod openclaw_gateway_token;
 pub mod openclaw_gateway_url;
 pub mod openclaw_identity;
@@ -35,19 +44,31 @@ pub mod openclaw_md_download;
 pub mod openclaw_version;
 pub mod plugin_install;
 pub mod remotion_avatar;
+pub mod restore_backup;
 pub mod scan_security;
 #[cfg(feature = "web-ui")]
 pub mod serve;
 pub mod skill;
 pub mod skill_deploy;
 pub mod skill_diff;
+pub mod skill_lint;
+pub mod skill_package;
 pub mod skill_remove;
+pub mod skill_rollout;
+pub mod ssh;
+pub mod stats;
+pub mod tailnet;
 pub mod tailscale_funnel;
 pub mod telegram;
 pub mod track;
 pub mod ttyproxy_instance;
 pub mod update_ip;
 pub mod update_model;
+pub mod upgrade;
+pub mod version_status;
 pub mod whatsapp;
 pub mod whatsapp_setup;
 pub mod wiki;
+pub mod wiki_convert;
+pub mod wiki_search;
+pub mod wiki_sync;
@@ -3,8 +3,9 @@ use clawmacdo_core::config;
 use clawmacdo_provision::provision::commands::{
     ssh_as_openclaw_with_user_async, ssh_root_as_async,
 };
+use clawmacdo_provision::provision::reverse_proxy;
 use serde_json::Value;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 pub struct OpenclawGatewayUrlParams {
     pub instance: String,
@@ -104,19 +105,24 @@ NODE
     .replace("__HOME__", home)
 }
 
-pub async fn run(params: OpenclawGatewayUrlParams) -> Result<()> {
-    let instance = clean_instance(&params.instance)?;
-    let (ip, key, provider) = find_deploy_record(&instance)?;
-    let ssh_user = ssh_user_for_provider(&provider);
-
-    let status = ssh_root_as_async(&ip, &key, "tailscale funnel status 2>&1 || true", ssh_user)
+/// The gateway's public URL: Tailscale Funnel, then the reverse proxy, then
+/// the instance's tailnet name.
+pub(crate) async fn public_url(ip: &str, key: &Path, ssh_user: &str) -> Option<String> {
+    let status = ssh_root_as_async(ip, key, "tailscale funnel status 2>&1 || true", ssh_user)
         .await
         .unwrap_or_default();
     let mut public_url = parse_funnel_url(&status);
+    if public_url.is_none() {
+        public_url = reverse_proxy::exposure(ip, key, ssh_user)
+            .await
+            .ok()
+            .flatten()
+            .and_then(|exposure| exposure.proxy_url());
+    }
     if public_url.is_none() {
         let status_json = ssh_root_as_async(
-            &ip,
-            &key,
+            ip,
+            key,
             "tailscale status --json 2>/dev/null || true",
             ssh_user,
         )
@@ -124,12 +130,27 @@ pub async fn run(params: OpenclawGatewayUrlParams) -> Result<()> {
         .unwrap_or_default();
         public_url = parse_tailscale_dns_url(&status_json);
     }
+    public_url
+}
 
-    let token = ssh_as_openclaw_with_user_async(&ip, &key, &build_token_cmd(), ssh_user)
-        .await
-        .unwrap_or_default()
-        .trim()
-        .to_string();
+/// The gateway auth token (`gateway.auth.token`), empty when unset.
+pub(crate) async fn gateway_token(ip: &str, key: &Path, ssh_user: &str) -> Result<String> {
+    Ok(
+        ssh_as_openclaw_with_user_async(ip, key, &build_token_cmd(), ssh_user)
+            .await?
+            .trim()
+            .to_string(),
+    )
+}
+
+pub async fn run(params: OpenclawGatewayUrlParams) -> Result<()> {
+    let instance = clean_instance(&params.instance)?;
+    let (ip, key, provider) = find_deploy_record(&instance)?;
+    let ssh_user = ssh_user_for_provider(&provider);
+
+    let public_url = public_url(&ip, &key, ssh_user).await;
+
+    let token = gateway_token(&ip, &key, ssh_user).await.unwrap_or_default();
 
     let gateway_url = public_url.as_ref().map(|url| {
         if token.is_empty() {
@@ -160,7 +181,7 @@ pub async fn run(params: OpenclawGatewayUrlParams) -> Result<()> {
             Ok(())
         }
         None => {
-            bail!("No Tailscale Funnel Gateway URL found for {instance}. Enable Funnel first.");
+            bail!("No public Gateway URL found for {instance}. Publish the gateway with `clawmacdo expose {instance} --mode funnel|proxy` first.");
         }
     }
 }
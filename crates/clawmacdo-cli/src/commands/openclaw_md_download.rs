@@ -2,10 +2,10 @@ use anyhow::{bail, Context, Result};
 use base64::engine::general_purpose::STANDARD as BASE64;
 use base64::Engine;
 use chrono::Utc;
+use clawmacdo_core::archive;
 use clawmacdo_core::config;
 use clawmacdo_provision::provision::commands::ssh_as_openclaw_with_user_async;
 use serde::Deserialize;
-use std::io::Write;
 use std::path::{Path, PathBuf};
 
 const BOOTSTRAP_FILES: &[&str] = &[
@@ -40,19 +40,6 @@ struct RemoteMdFile {
     size: u64,
 }
 
-struct ZipEntry {
-    name: String,
-    data: Vec<u8>,
-    crc32: u32,
-}
-
-struct CentralDirectoryEntry {
-    name: String,
-    crc32: u32,
-    size: u32,
-    offset: u32,
-}
-
 /// Look up a deploy record by hostname, IP, or deploy ID.
 /// Returns (ip, ssh_key_path, provider).
 fn find_deploy_record(query: &str) -> Result<(String, PathBuf, Option<String>)> {
@@ -227,108 +214,6 @@ fn resolve_output_path(output: &Path) -> Result<PathBuf> {
     Ok(path)
 }
 
-fn write_u16(out: &mut Vec<u8>, value: u16) {
-    out.extend_from_slice(&value.to_le_bytes());
-}
-
-fn write_u32(out: &mut Vec<u8>, value: u32) {
-    out.extend_from_slice(&value.to_le_bytes());
-}
-
-fn crc32(data: &[u8]) -> u32 {
-    let mut crc = 0xffff_ffffu32;
-    for &byte in data {
-        crc ^= byte as u32;
-        for _ in 0..8 {
-            let mask = (crc & 1).wrapping_neg();
-            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
-        }
-    }
-    !crc
-}
-
-fn to_u16(value: usize, label: &str) -> Result<u16> {
-    u16::try_from(value).with_context(|| format!("{label} is too large for ZIP32"))
-}
-
-fn to_u32(value: usize, label: &str) -> Result<u32> {
-    u32::try_from(value).with_context(|| format!("{label} is too large for ZIP32"))
-}
-
-fn write_zip(path: &Path, entries: &[ZipEntry]) -> Result<()> {
-    let mut out = Vec::new();
-    let mut central_entries = Vec::new();
-    let mod_time = 0u16;
-    let mod_date = 33u16; // 1980-01-01
-    let flags = 0x0800u16; // UTF-8 names
-
-    for entry in entries {
-        let name = entry.name.as_bytes();
-        let size = to_u32(entry.data.len(), &entry.name)?;
-        let offset = to_u32(out.len(), "ZIP local header offset")?;
-
-        write_u32(&mut out, 0x0403_4b50);
-        write_u16(&mut out, 20);
-        write_u16(&mut out, flags);
-        write_u16(&mut out, 0);
-        write_u16(&mut out, mod_time);
-        write_u16(&mut out, mod_date);
-        write_u32(&mut out, entry.crc32);
-        write_u32(&mut out, size);
-        write_u32(&mut out, size);
-        write_u16(&mut out, to_u16(name.len(), "ZIP file name")?);
-        write_u16(&mut out, 0);
-        out.extend_from_slice(name);
-        out.extend_from_slice(&entry.data);
-
-        central_entries.push(CentralDirectoryEntry {
-            name: entry.name.clone(),
-            crc32: entry.crc32,
-            size,
-            offset,
-        });
-    }
-
-    let central_offset = to_u32(out.len(), "ZIP central directory offset")?;
-    for entry in &central_entries {
-        let name = entry.name.as_bytes();
-
-        write_u32(&mut out, 0x0201_4b50);
-        write_u16(&mut out, 20);
-        write_u16(&mut out, 20);
-        write_u16(&mut out, flags);
-        write_u16(&mut out, 0);
-        write_u16(&mut out, mod_time);
-        write_u16(&mut out, mod_date);
-        write_u32(&mut out, entry.crc32);
-        write_u32(&mut out, entry.size);
-        write_u32(&mut out, entry.size);
-        write_u16(&mut out, to_u16(name.len(), "ZIP file name")?);
-        write_u16(&mut out, 0);
-        write_u16(&mut out, 0);
-        write_u16(&mut out, 0);
-        write_u16(&mut out, 0);
-        write_u32(&mut out, 0);
-        write_u32(&mut out, entry.offset);
-        out.extend_from_slice(name);
-    }
-    let central_size = to_u32(out.len(), "ZIP output length")? - central_offset;
-    let entry_count = to_u16(central_entries.len(), "ZIP entry count")?;
-
-    write_u32(&mut out, 0x0605_4b50);
-    write_u16(&mut out, 0);
-    write_u16(&mut out, 0);
-    write_u16(&mut out, entry_count);
-    write_u16(&mut out, entry_count);
-    write_u32(&mut out, central_size);
-    write_u32(&mut out, central_offset);
-    write_u16(&mut out, 0);
-
-    let mut file = std::fs::File::create(path)?;
-    file.write_all(&out)?;
-    Ok(())
-}
-
 pub async fn run(params: OpenclawMdDownloadParams) -> Result<()> {
     let instance = params.instance.trim();
     if instance.is_empty() {
@@ -367,15 +252,11 @@ pub async fn run(params: OpenclawMdDownloadParams) -> Result<()> {
             );
         }
         total_size += file.size;
-        entries.push(ZipEntry {
-            name: file.name.clone(),
-            crc32: crc32(&data),
-            data,
-        });
+        entries.push((file.name.clone(), data));
     }
 
     let zip_path = resolve_output_path(&params.output)?;
-    write_zip(&zip_path, &entries)?;
+    archive::write_zip_file(&zip_path, &entries)?;
 
     println!("  Workspace: {}", manifest.workspace);
     println!("  Files:     {}", entries.len());
@@ -59,13 +59,77 @@ async fn list_versions_http() -> Result<Vec<String>> {
 }
 
 /// Parse a version string into a sortable tuple of numeric parts.
-fn version_sort_key(v: &str) -> Vec<u64> {
+pub(crate) fn version_sort_key(v: &str) -> Vec<u64> {
     v.split(|c: char| !c.is_ascii_digit())
         .filter(|s| !s.is_empty())
         .map(|s| s.parse::<u64>().unwrap_or(0))
         .collect()
 }
 
+/// Resolve `latest` or an explicit version against the published versions.
+pub(crate) fn resolve_version(requested: &str, versions: &[String]) -> Result<String> {
+    let requested = requested.trim();
+    if requested == "latest" {
+        return versions
+            .last()
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("The npm registry lists no openclaw versions"));
+    }
+    let version = clean_version(requested)?;
+    if !versions.contains(&version) {
+        bail!("openclaw@{version} is not published; run `clawmacdo openclaw-versions` to list versions");
+    }
+    Ok(version)
+}
+
+/// Validate a version before it is embedded in a remote command.
+pub(crate) fn clean_version(version: &str) -> Result<String> {
+    let version = version.trim();
+    if version.is_empty()
+        || version.len() > 64
+        || !version
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+'))
+    {
+        bail!("Invalid OpenClaw version '{version}'");
+    }
+    Ok(version.to_string())
+}
+
+/// Remote command installing `openclaw@<version>` globally with pnpm.
+pub(crate) fn install_cmd(version: &str) -> String {
+    let home = config::OPENCLAW_HOME;
+    format!(
+        "export PNPM_HOME={home}/.local/share/pnpm \
+         PATH={home}/.local/bin:{home}/.local/share/pnpm:/usr/local/bin:/usr/bin:/bin \
+         HOME={home} && \
+         pnpm install -g openclaw@{version} 2>&1"
+    )
+}
+
+/// Remote command printing `openclaw --version`.
+pub(crate) fn version_cmd() -> String {
+    let home = config::OPENCLAW_HOME;
+    format!(
+        "PATH={home}/.local/bin:{home}/.local/share/pnpm:/usr/local/bin:/usr/bin:/bin \
+         HOME={home} \
+         openclaw --version"
+    )
+}
+
+/// Pull the version number out of `openclaw --version` output
+/// (`2026.3.20` or `OpenClaw 2026.3.20 (abc123)`).
+pub(crate) fn parse_version_output(output: &str) -> Option<String> {
+    output
+        .split_whitespace()
+        .map(|word| {
+            word.trim_start_matches('v')
+                .trim_end_matches([',', ')', ';'])
+        })
+        .find(|word| word.starts_with(|c: char| c.is_ascii_digit()) && word.contains('.'))
+        .and_then(|word| clean_version(word).ok())
+}
+
 /// CLI handler: print available openclaw versions.
 pub async fn run_list(json: bool) -> Result<()> {
     let versions = list_versions().await?;
@@ -132,22 +196,15 @@ fn ssh_user_for_provider(provider: &Option<String>) -> &'static str {
 
 /// CLI handler: install a specific openclaw version on a running instance.
 pub async fn run_install(query: &str, version: &str) -> Result<()> {
+    let version = clean_version(version)?;
     let (ip, key, provider) = find_deploy_record(query)?;
     let ssh_user = ssh_user_for_provider(&provider);
-    let home = config::OPENCLAW_HOME;
 
     println!("Installing openclaw@{version} on {ip}...");
 
-    // Install the specified version
-    let install_cmd = format!(
-        "export PNPM_HOME={home}/.local/share/pnpm \
-         PATH={home}/.local/bin:{home}/.local/share/pnpm:/usr/local/bin:/usr/bin:/bin \
-         HOME={home} && \
-         pnpm install -g openclaw@{version} 2>&1"
-    );
-
     println!("[1/3] Installing openclaw@{version}...");
-    let install_out = ssh_as_openclaw_with_user_async(&ip, &key, &install_cmd, ssh_user).await?;
+    let install_out =
+        ssh_as_openclaw_with_user_async(&ip, &key, &install_cmd(&version), ssh_user).await?;
     if !install_out.trim().is_empty() {
         for line in install_out.trim().lines().take(5) {
             println!("  {line}");
@@ -156,12 +213,7 @@ pub async fn run_install(query: &str, version: &str) -> Result<()> {
 
     // Verify
     println!("[2/3] Verifying installation...");
-    let verify_cmd = format!(
-        "PATH={home}/.local/bin:{home}/.local/share/pnpm:/usr/local/bin:/usr/bin:/bin \
-         HOME={home} \
-         openclaw --version"
-    );
-    let ver_out = ssh_as_openclaw_with_user_async(&ip, &key, &verify_cmd, ssh_user).await?;
+    let ver_out = ssh_as_openclaw_with_user_async(&ip, &key, &version_cmd(), ssh_user).await?;
     println!("  OpenClaw version: {}", ver_out.trim());
 
     // Restart gateway
@@ -184,3 +236,30 @@ pub async fn run_install(query: &str, version: &str) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_latest_and_rejects_unknown_versions() {
+        let versions = vec!["2026.3.2".to_string(), "2026.3.20".to_string()];
+        assert_eq!(resolve_version("latest", &versions).unwrap(), "2026.3.20");
+        assert_eq!(resolve_version("2026.3.2", &versions).unwrap(), "2026.3.2");
+        assert!(resolve_version("2026.4.1", &versions).is_err());
+        assert!(resolve_version("1; rm -rf /", &versions).is_err());
+    }
+
+    #[test]
+    fn parses_version_output() {
+        assert_eq!(
+            parse_version_output("2026.3.20\n").as_deref(),
+            Some("2026.3.20")
+        );
+        assert_eq!(
+            parse_version_output("OpenClaw v2026.3.20 (abc123)").as_deref(),
+            Some("2026.3.20")
+        );
+        assert_eq!(parse_version_output("command not found"), None);
+    }
+}
@@ -0,0 +1,468 @@
+use super::backup_remote;
+use super::deploy::validate_backup_archive;
+use anyhow::{bail, Context, Result};
+use clawmacdo_core::archive;
+use clawmacdo_core::backup_crypto;
+use clawmacdo_core::backup_store;
+use clawmacdo_core::config;
+use clawmacdo_provision::provision::commands::{
+    ssh_as_openclaw_with_user_async, ssh_root_as_async,
+};
+use clawmacdo_ssh as ssh;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+/// Archive entries restored into `~/.openclaw` live under this prefix.
+const ARCHIVE_PREFIX: &str = "openclaw/";
+
+/// Full-state archives (from `migrate`) keep crontab, systemd units and
+/// out-of-tree workspaces under this prefix.
+const EXTRAS_PREFIX: &str = "extras/";
+
+pub struct RestoreBackupParams {
+    pub query: String,
+    pub archive: String,
+    pub yes: bool,
+    pub dry_run: bool,
+    pub skip_safety_backup: bool,
+}
+
+/// How [`restore_into`] should behave once the diff has been shown.
+pub struct RestoreOptions {
+    pub yes: bool,
+    pub dry_run: bool,
+    pub skip_safety_backup: bool,
+}
+
+/// Look up a deploy record by hostname, IP, or deploy ID.
+fn find_deploy_record(query: &str) -> Result<config::DeployRecord> {
+    let deploys_dir = config::deploys_dir()?;
+    if !deploys_dir.exists() {
+        bail!("No deploy records found. Deploy an instance first.");
+    }
+
+    for entry in std::fs::read_dir(&deploys_dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let contents = std::fs::read_to_string(&path)?;
+        let record: config::DeployRecord = match serde_json::from_str(&contents) {
+            Ok(r) => r,
+            Err(_) => continue,
+        };
+        if record.id == query || record.hostname == query || record.ip_address == query {
+            return Ok(record);
+        }
+    }
+
+    bail!("No deploy record found for '{query}'. Use a deploy ID, hostname, or IP address.");
+}
+
+fn ssh_user_for_provider(provider: &Option<config::CloudProviderType>) -> &'static str {
+    match provider {
+        Some(config::CloudProviderType::Lightsail) => "ubuntu",
+        Some(config::CloudProviderType::Azure) => "azureuser",
+        _ => "root",
+    }
+}
+
+/// Post-restore repair run as `openclaw`: normalize legacy config, run doctor,
+/// restart the gateway and wait for `/health`. Exits non-zero if it never gets healthy.
+pub(crate) fn post_restore_repair_cmd() -> String {
+    let home = config::OPENCLAW_HOME;
+    let mut cmd = r#"export HOME="__HOME__"
+export PATH="__HOME__/.local/bin:__HOME__/.local/share/pnpm:/usr/local/bin:/usr/bin:/bin:$PATH"
+export XDG_RUNTIME_DIR=/run/user/$(id -u)
+export DBUS_SESSION_BUS_ADDRESS=unix:path=/run/user/$(id -u)/bus
+if [ ! -S "$XDG_RUNTIME_DIR/bus" ]; then
+  dbus-daemon --session --address="$DBUS_SESSION_BUS_ADDRESS" --fork >/dev/null 2>&1 || true
+fi
+
+node <<'NODE'
+const fs = require('fs');
+const path = require('path');
+const home = process.env.HOME || '__HOME__';
+const configPath = path.join(home, '.openclaw', 'openclaw.json');
+
+let changed = false;
+try {
+  const cfg = JSON.parse(fs.readFileSync(configPath, 'utf8'));
+  const telegram = cfg.channels && cfg.channels.telegram;
+  if (telegram && telegram.streaming !== undefined) {
+    const streaming = telegram.streaming;
+    if (!streaming || typeof streaming !== 'object' || Array.isArray(streaming)) {
+      delete telegram.streaming;
+      changed = true;
+    }
+  }
+  if (changed) {
+    fs.copyFileSync(configPath, configPath + '.clawmacdo-restore.bak');
+    fs.writeFileSync(configPath, JSON.stringify(cfg, null, 2) + '\n', { mode: 0o600 });
+    fs.chmodSync(configPath, 0o600);
+  }
+} catch (_) {}
+console.log(changed ? 'telegram config: normalized legacy streaming value' : 'telegram config: ok');
+NODE
+
+(openclaw doctor --fix >/dev/null 2>&1 || true)
+(systemctl --user daemon-reload 2>/dev/null || true)
+(systemctl --user restart openclaw-gateway.service >/dev/null 2>&1 || \
+ systemctl --user start openclaw-gateway.service >/dev/null 2>&1 || true)
+for i in $(seq 1 45); do
+  if curl -fsS --max-time 2 http://127.0.0.1:18789/health >/dev/null 2>&1; then
+    echo 'gateway: healthy'
+    exit 0
+  fi
+  sleep $(( i < 10 ? 2 : 4 ))
+done
+echo 'gateway: FAILED - not healthy after restore repair'
+exit 1
+"#
+    .to_string();
+    cmd = cmd.replace("__HOME__", home);
+    cmd
+}
+
+/// What restoring an archive would do to the instance's `~/.openclaw`.
+#[derive(Debug, Default, Serialize, PartialEq, Eq)]
+pub struct RestoreDiff {
+    pub added: Vec<String>,
+    pub changed: Vec<String>,
+    pub unchanged: usize,
+    /// Files only on the instance. Restore overlays the archive, so these are kept.
+    pub kept: Vec<String>,
+}
+
+/// Compare archive and instance file checksums, both keyed by path relative to `~/.openclaw`.
+pub fn diff_files(
+    archive: &BTreeMap<String, String>,
+    remote: &BTreeMap<String, String>,
+) -> RestoreDiff {
+    let mut diff = RestoreDiff::default();
+    for (path, sha) in archive {
+        match remote.get(path) {
+            None => diff.added.push(path.clone()),
+            Some(remote_sha) if remote_sha != sha => diff.changed.push(path.clone()),
+            Some(_) => diff.unchanged += 1,
+        }
+    }
+    diff.kept = remote
+        .keys()
+        .filter(|p| !archive.contains_key(*p))
+        .cloned()
+        .collect();
+    diff
+}
+
+/// sha256 of every regular file under `openclaw/` in a plain tar.gz, keyed by relative path.
+fn archive_checksums(path: &Path) -> Result<BTreeMap<String, String>> {
+    let file = std::fs::File::open(path)
+        .with_context(|| format!("Failed to open backup archive {}", path.display()))?;
+    Ok(archive::tar_gz_checksums(file)?
+        .into_iter()
+        .filter_map(|file| {
+            let rel = file.path.strip_prefix(ARCHIVE_PREFIX)?;
+            Some((rel.trim_start_matches("./").to_string(), file.sha256))
+        })
+        .collect())
+}
+
+/// Names of the `extras/` sections present in an archive (e.g. `crontab`, `home`).
+fn archive_extras(path: &Path) -> Result<BTreeSet<String>> {
+    let file = std::fs::File::open(path)
+        .with_context(|| format!("Failed to open backup archive {}", path.display()))?;
+    Ok(archive::tar_gz_list(file)?
+        .into_iter()
+        .filter_map(|(entry_path, _)| {
+            let rest = entry_path.strip_prefix(EXTRAS_PREFIX)?;
+            rest.split('/')
+                .next()
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+        })
+        .collect())
+}
+
+/// Parse `sha256sum` output (`<hash>  ./<path>`) into relative path -> hash.
+fn parse_sha256sum(output: &str) -> BTreeMap<String, String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (hash, path) = line.split_once("  ")?;
+            if hash.len() != 64 {
+                return None;
+            }
+            Some((path.trim_start_matches("./").to_string(), hash.to_string()))
+        })
+        .collect()
+}
+
+fn remote_checksums_cmd() -> String {
+    let home = config::OPENCLAW_HOME;
+    format!(
+        "if [ -d {home}/.openclaw ]; then cd {home}/.openclaw && \
+         find . -type f -print0 | xargs -0 -r sha256sum; fi"
+    )
+}
+
+/// Extract the uploaded archive over `~/.openclaw`, install any `extras/`
+/// (crontab, systemd user units, workspaces) and hand ownership back to `openclaw`.
+fn extract_cmd(remote_archive: &str, staging: &str) -> String {
+    let home = config::OPENCLAW_HOME;
+    let user = config::OPENCLAW_USER;
+    format!(
+        "set -e
+rm -rf {staging}
+mkdir -p {staging} {home}/.openclaw
+tar --extract --gzip --file {remote_archive} --directory {staging} --no-same-owner
+[ -d {staging}/openclaw ]
+cp -a {staging}/openclaw/. {home}/.openclaw/
+chown -R {user}:{user} {home}/.openclaw
+chmod 700 {home}/.openclaw
+find {home}/.openclaw -maxdepth 3 \\( -name '.env' -o -name 'openclaw.json' -o -path '*/credentials/*' \\) -type f -exec chmod 600 {{}} +
+if [ -d {staging}/extras/home ]; then
+  cp -a {staging}/extras/home/. {home}/
+  (cd {staging}/extras/home && find . -mindepth 1 -maxdepth 1 -exec chown -R {user}:{user} {home}/{{}} \\;)
+fi
+if [ -d {staging}/extras/systemd-user ]; then
+  mkdir -p {home}/.config/systemd/user
+  cp -a {staging}/extras/systemd-user/. {home}/.config/systemd/user/
+  chown -R {user}:{user} {home}/.config
+fi
+if [ -f {staging}/extras/crontab ]; then
+  crontab -u {user} {staging}/extras/crontab
+fi
+rm -rf {staging} {remote_archive}
+echo ok"
+    )
+}
+
+fn gateway_cmd(action: &str) -> String {
+    format!(
+        "export XDG_RUNTIME_DIR=/run/user/$(id -u)
+systemctl --user {action} openclaw-gateway.service"
+    )
+}
+
+/// Resolve `archive` to a local file: an existing path, or a name in the configured store.
+async fn resolve_archive(archive: &str) -> Result<PathBuf> {
+    let direct = PathBuf::from(archive);
+    if direct.is_file() {
+        return Ok(direct);
+    }
+    if let Ok(path) = config::resolve_backup_path(archive) {
+        return Ok(path);
+    }
+    let store = backup_store::configured_store()?;
+    store.fetch(archive).await.with_context(|| {
+        format!(
+            "Backup '{archive}' not found locally or in {}",
+            store.describe()
+        )
+    })
+}
+
+fn print_diff(diff: &RestoreDiff) {
+    println!("\nChanges to {}/.openclaw:", config::OPENCLAW_HOME);
+    for path in &diff.added {
+        println!("  + {path}");
+    }
+    for path in &diff.changed {
+        println!("  ~ {path}");
+    }
+    println!(
+        "\n  {} added, {} changed, {} unchanged, {} only on instance (kept)",
+        diff.added.len(),
+        diff.changed.len(),
+        diff.unchanged,
+        diff.kept.len()
+    );
+}
+
+/// Restore a backup archive into a running instance's `~/.openclaw`.
+pub async fn run(params: RestoreBackupParams) -> Result<()> {
+    let record = find_deploy_record(&params.query)?;
+    let archive_path = resolve_archive(&params.archive).await?;
+    restore_into(
+        &record,
+        &archive_path,
+        RestoreOptions {
+            yes: params.yes,
+            dry_run: params.dry_run,
+            skip_safety_backup: params.skip_safety_backup,
+        },
+    )
+    .await
+}
+
+/// Diff, then overlay a local archive onto `record`'s instance and restart the gateway.
+pub async fn restore_into(
+    record: &config::DeployRecord,
+    archive_path: &Path,
+    options: RestoreOptions,
+) -> Result<()> {
+    let ip = record.ip_address.clone();
+    let key = PathBuf::from(&record.ssh_key_path);
+    let ssh_user = ssh_user_for_provider(&record.provider);
+
+    let plain = backup_crypto::open_for_restore(archive_path)?;
+    validate_backup_archive(plain.path())?;
+    let archive_files = archive_checksums(plain.path())?;
+    if archive_files.is_empty() {
+        bail!(
+            "Backup archive {} has no files under {ARCHIVE_PREFIX}",
+            archive_path.display()
+        );
+    }
+    let extras = archive_extras(plain.path())?;
+
+    println!(
+        "Restoring {} into {} ({ip})...",
+        archive_path.display(),
+        record.hostname
+    );
+    let remote_output = ssh_root_as_async(&ip, &key, &remote_checksums_cmd(), ssh_user)
+        .await
+        .context("Failed to read current files on the instance")?;
+    let diff = diff_files(&archive_files, &parse_sha256sum(&remote_output));
+    print_diff(&diff);
+    if !extras.is_empty() {
+        let names: Vec<&str> = extras.iter().map(String::as_str).collect();
+        println!("  extras: {}", names.join(", "));
+    }
+
+    if options.dry_run {
+        println!("\nDry run: nothing was changed.");
+        return Ok(());
+    }
+    if diff.added.is_empty() && diff.changed.is_empty() && extras.is_empty() {
+        println!("\nInstance already matches the backup; nothing to restore.");
+        return Ok(());
+    }
+    if !options.yes {
+        let confirmed = dialoguer::Confirm::new()
+            .with_prompt("Stop the gateway and restore these files?")
+            .default(false)
+            .interact()?;
+        if !confirmed {
+            println!("Aborted.");
+            return Ok(());
+        }
+    }
+
+    println!("\nStopping gateway...");
+    ssh_as_openclaw_with_user_async(&ip, &key, &gateway_cmd("stop"), ssh_user)
+        .await
+        .context("Failed to stop the gateway")?;
+
+    // The gateway is down from here on, so every failure restarts it.
+    let restored = async {
+        if !options.skip_safety_backup {
+            println!("Taking safety backup...");
+            let store = backup_store::configured_store()?;
+            let (path, _) = backup_remote::backup_instance(record, store.as_ref())
+                .await
+                .context("Safety backup failed; restore aborted")?;
+            println!("  Safety backup: {}", path.display());
+        }
+
+        println!("Uploading and extracting archive...");
+        let token = uuid::Uuid::new_v4().simple().to_string();
+        let remote_archive = format!("/tmp/clawmacdo_restore_{token}.tar.gz");
+        let staging = format!("/tmp/clawmacdo-restore-{token}");
+        let ip_c = ip.clone();
+        let key_c = key.clone();
+        let local = plain.path().to_path_buf();
+        let remote_c = remote_archive.clone();
+        tokio::task::spawn_blocking(move || {
+            ssh::scp_upload_as(&ip_c, &key_c, &local, &remote_c, ssh_user)
+        })
+        .await?
+        .context("Failed to upload backup to the instance")?;
+        ssh_root_as_async(&ip, &key, &extract_cmd(&remote_archive, &staging), ssh_user)
+            .await
+            .context("Failed to extract backup on the instance")?;
+
+        println!("Repairing config and restarting gateway...");
+        ssh_as_openclaw_with_user_async(&ip, &key, &post_restore_repair_cmd(), ssh_user)
+            .await
+            .context("Gateway did not become healthy after restore; the safety backup can be restored with restore-backup")
+    }
+    .await;
+
+    let output = match restored {
+        Ok(output) => output,
+        Err(e) => {
+            println!("Restore failed; restarting gateway...");
+            return match ssh_as_openclaw_with_user_async(&ip, &key, &gateway_cmd("start"), ssh_user)
+                .await
+            {
+                Ok(_) => Err(e.context("Restore failed; gateway restarted")),
+                Err(start) => Err(e.context(format!(
+                    "Restore failed and the gateway could not be restarted: {start}"
+                ))),
+            };
+        }
+    };
+    for line in output.trim().lines() {
+        println!("  {line}");
+    }
+
+    println!(
+        "\nRestored {} file(s) into {}.",
+        diff.added.len() + diff.changed.len(),
+        record.hostname
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_classifies_added_changed_and_kept_files() {
+        let archive = BTreeMap::from([
+            ("openclaw.json".to_string(), "a".repeat(64)),
+            (".env".to_string(), "b".repeat(64)),
+            ("agents/main.md".to_string(), "c".repeat(64)),
+        ]);
+        let remote = BTreeMap::from([
+            ("openclaw.json".to_string(), "a".repeat(64)),
+            (".env".to_string(), "d".repeat(64)),
+            ("sessions/today.json".to_string(), "e".repeat(64)),
+        ]);
+        let diff = diff_files(&archive, &remote);
+        assert_eq!(diff.added, vec!["agents/main.md"]);
+        assert_eq!(diff.changed, vec![".env"]);
+        assert_eq!(diff.unchanged, 1);
+        assert_eq!(diff.kept, vec!["sessions/today.json"]);
+    }
+
+    #[test]
+    fn parse_sha256sum_strips_dot_prefix() {
+        let hash = "f".repeat(64);
+        let parsed = parse_sha256sum(&format!("{hash}  ./.env\n{hash}  ./a b.txt\ngarbage\n"));
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed.get("a b.txt"), Some(&hash));
+    }
+
+    #[test]
+    fn extract_cmd_restores_ownership_and_cleans_up() {
+        let cmd = extract_cmd("/tmp/x.tar.gz", "/tmp/stage");
+        assert!(cmd.contains("chown -R openclaw:openclaw /home/openclaw/.openclaw"));
+        assert!(cmd.contains("cp -a /tmp/stage/openclaw/. /home/openclaw/.openclaw/"));
+        assert!(cmd.contains("rm -rf /tmp/stage /tmp/x.tar.gz"));
+    }
+
+    #[test]
+    fn post_restore_repair_normalizes_legacy_telegram_streaming_and_waits_for_health() {
+        let cmd = post_restore_repair_cmd();
+        assert!(cmd.contains("delete telegram.streaming"));
+        assert!(cmd.contains("openclaw doctor --fix"));
+        assert!(cmd.contains("http://127.0.0.1:18789/health"));
+    }
+}
@@ -1,22 +1,151 @@
-// CLI scaffold: security scan command
-use clap::Parser;
-use std::path::PathBuf;
-
-#[derive(Parser)]
-pub struct ScanSecurity {
-    #[clap(long, default_value = "all")]
-    pub target: String,
-    #[clap(long)]
-    pub host: Option<String>,
-    #[clap(long)]
+use super::skill_deploy::{find_deploy_record, ssh_user_for_provider};
+use anyhow::{bail, Context, Result};
+use clawmacdo_core::security_scan::{self, CheckStatus, ScanReport, Severity};
+use console::style;
+use std::path::{Path, PathBuf};
+
+pub struct ScanSecurityParams {
+    /// Deploy ID, hostname or IP address.
+    pub instance: String,
+    /// `text`, `json` or `sarif`.
+    pub format: String,
     pub out: Option<PathBuf>,
+    /// Exit non-zero when the risk level reaches this severity.
+    pub fail_on: Option<String>,
+}
+
+/// Run the collector as root over SSH and return its raw output.
+pub(crate) async fn collect(ip: &str, key: &Path, ssh_user: &str) -> Result<String> {
+    let shell = if ssh_user == "root" {
+        "/bin/bash -s"
+    } else {
+        "sudo /bin/bash -s"
+    };
+    let (ip, key, user) = (ip.to_string(), key.to_path_buf(), ssh_user.to_string());
+    let output = tokio::task::spawn_blocking(move || {
+        clawmacdo_ssh::exec_with_input_as(
+            &ip,
+            &key,
+            shell,
+            security_scan::COLLECT_SCRIPT.as_bytes(),
+            &user,
+        )
+    })
+    .await??;
+    Ok(output)
 }
 
-#[allow(dead_code)]
-pub fn run(args: ScanSecurity) -> anyhow::Result<()> {
+/// Render a report as `text`, `json` or `sarif`.
+pub(crate) fn render(report: &ScanReport, format: &str) -> Result<String> {
+    Ok(match format {
+        "json" => serde_json::to_string_pretty(report)? + "\n",
+        "sarif" => serde_json::to_string_pretty(&security_scan::to_sarif(report))? + "\n",
+        "text" => security_scan::to_text(report),
+        other => bail!("Unknown format '{other}' (expected text, json or sarif)"),
+    })
+}
+
+fn print_summary(report: &ScanReport) {
+    println!();
+    for check in &report.checks {
+        let status = match check.status {
+            CheckStatus::Pass => style(format!("{:<8}", "pass")).green(),
+            CheckStatus::Unknown => style(format!("{:<8}", "unknown")).dim(),
+            CheckStatus::Fail => match check.severity {
+                Severity::Critical | Severity::High => {
+                    style(format!("{:<8}", check.severity.as_str())).red()
+                }
+                _ => style(format!("{:<8}", check.severity.as_str())).yellow(),
+            },
+        };
+        println!("  {status}  {:<24}  {}", check.id, check.value);
+        if let Some(fix) = &check.remediation {
+            println!("  {:<8}  {:<24}  {}", "", "", style(fix).dim());
+        }
+    }
+    let level = report.risk.level.as_str();
+    let level = match report.risk.level {
+        Severity::Critical | Severity::High => style(level).red().bold(),
+        Severity::Medium => style(level).yellow().bold(),
+        _ => style(level).green().bold(),
+    };
     println!(
-        "TODO: implement security scan. target={} host={:?} out={:?}",
-        args.target, args.host, args.out
+        "\n  Risk: {}/100 ({level}), {} of {} check(s) failed",
+        report.risk.score,
+        report.risk.failed,
+        report.checks.len()
     );
+}
+
+/// `scan-security`: audit sshd, UFW, fail2ban, open ports, secret file modes,
+/// gateway auth and Docker socket exposure on a deployed instance, and report
+/// the findings with a risk score as text, JSON or SARIF.
+pub async fn run(params: ScanSecurityParams) -> Result<()> {
+    let fail_on = params
+        .fail_on
+        .as_deref()
+        .map(str::parse::<Severity>)
+        .transpose()
+        .map_err(anyhow::Error::msg)?;
+    // Reject a bad format before connecting.
+    let format = params.format.as_str();
+    if !matches!(format, "text" | "json" | "sarif") {
+        bail!("Unknown format '{format}' (expected text, json or sarif)");
+    }
+
+    let (ip, key, provider) = find_deploy_record(&params.instance)?;
+    let ssh_user = ssh_user_for_provider(&provider);
+    let interactive = format == "text" && params.out.is_none();
+    if interactive {
+        println!("Scanning {} ({ip})...", params.instance);
+    }
+    let output = collect(&ip, &key, ssh_user)
+        .await
+        .with_context(|| format!("security scan of {ip} failed"))?;
+    let report = security_scan::evaluate(&ip, &output);
+
+    match &params.out {
+        Some(path) => {
+            std::fs::write(path, render(&report, format)?)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            println!(
+                "Wrote {format} report for {} to {} (risk {}/100, {})",
+                report.host,
+                path.display(),
+                report.risk.score,
+                report.risk.level.as_str()
+            );
+        }
+        None if interactive => print_summary(&report),
+        None => print!("{}", render(&report, format)?),
+    }
+
+    if let Some(threshold) = fail_on {
+        if report.risk.failed > 0 && report.risk.level >= threshold {
+            bail!(
+                "risk level {} reaches --fail-on {}",
+                report.risk.level.as_str(),
+                threshold.as_str()
+            );
+        }
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_each_format() {
+        let report = security_scan::evaluate("10.0.0.5", "@@ sshd\npermitrootlogin yes\n");
+        let json: serde_json::Value =
+            serde_json::from_str(&render(&report, "json").unwrap()).unwrap();
+        assert_eq!(json["checks"][0]["id"], "sshd_permit_root");
+        assert!(render(&report, "sarif").unwrap().contains("\"2.1.0\""));
+        assert!(render(&report, "text")
+            .unwrap()
+            .contains("- [FAIL high] sshd_permit_root: PermitRootLogin yes"));
+        assert!(render(&report, "xml").is_err());
+    }
+}
@@ -11,7 +11,9 @@ use axum::routing::{delete, get, post};
 use axum::Router;
 use chrono::TimeZone;
 use clawmacdo_cloud::CloudProvider;
+use clawmacdo_core::backup_store;
 use clawmacdo_core::config;
+use clawmacdo_core::metrics;
 use clawmacdo_db as db;
 use clawmacdo_provision::provision::commands::{
     ssh_as_openclaw_async, ssh_as_openclaw_with_user_async, ssh_root_async,
@@ -494,6 +496,42 @@ struct ArkListEndpointsResponse {
     error: Option<String>,
 }
 
+#[derive(Serialize)]
+struct ArkModelsResponse {
+    ok: bool,
+    models: Vec<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Body of `POST /api/ark/chat`: earlier `messages` plus the new `prompt`.
+#[derive(Deserialize)]
+struct ArkChatRequest {
+    #[serde(default)]
+    api_key: String,
+    #[serde(default)]
+    endpoint_id: String,
+    #[serde(default)]
+    prompt: String,
+    #[serde(default)]
+    messages: Vec<serde_json::Value>,
+    #[serde(default)]
+    system: Option<String>,
+    /// Reply as server-sent events instead of one JSON object.
+    #[serde(default)]
+    stream: bool,
+}
+
+#[derive(Serialize)]
+struct ArkChatResponse {
+    ok: bool,
+    reply: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    usage: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
 #[derive(Serialize)]
 struct ConfigResponse {
     dry_run: bool,
@@ -526,6 +564,7 @@ async fn rate_limit_middleware(
         } else {
             entry.0 += 1;
             if entry.0 > RATE_LIMIT_MAX {
+                metrics::inc_counter(metrics::RATE_LIMIT_REJECTIONS_TOTAL, &[]);
                 return (
                     StatusCode::TOO_MANY_REQUESTS,
                     "Rate limit exceeded. Try again later.",
@@ -778,6 +817,7 @@ pub async fn run(port: u16) -> anyhow::Result<()> {
             post(snapshot_deployment_handler),
         )
         .route("/api/deployments/{id}/refresh-ip", post(refresh_ip_handler))
+        .route("/api/deployments/{id}/logs", get(deployment_logs_handler))
         .route("/api/deployments/{id}/funnel", post(toggle_funnel_handler))
         .route(
             "/api/deployments/{id}/funnel/status",
@@ -808,6 +848,9 @@ pub async fn run(port: u16) -> anyhow::Result<()> {
         .route("/api/config", get(config_handler))
         .route("/api/ark/endpoints", post(ark_list_endpoints_handler))
         .route("/api/ark/api-key", post(ark_api_key_handler))
+        .route("/api/ark/models", post(ark_models_handler))
+        .route("/api/ark/chat", post(ark_chat_handler))
+        .route("/metrics", get(metrics_handler))
         .layer(middleware::from_fn(api_key_middleware));
 
     // Web routes — protected by PIN auth middleware
@@ -833,6 +876,8 @@ pub async fn run(port: u16) -> anyhow::Result<()> {
         .layer(cors)
         .with_state(state);
 
+    crate::commands::backup_remote::spawn_schedule_from_env()?;
+
     let bind_addr = std::env::var("CLAWMACDO_BIND").unwrap_or_else(|_| "127.0.0.1".into());
     let addr = format!("{bind_addr}:{port}");
     println!("ClawMacToDO web UI running at http://{addr}");
@@ -884,7 +929,7 @@ async fn mascot_handler() -> impl IntoResponse {
 
 /// LList backups handler.
 async fn list_backups_handler() -> impl IntoResponse {
-    let entries = list_backup_files().unwrap_or_default();
+    let entries = list_backup_files().await.unwrap_or_default();
     Json(entries)
 }
 
@@ -941,11 +986,15 @@ async fn start_deploy_handler(
         }
     }
 
-    if !is_hermes && req.tailscale && req.tailscale_auth_key.trim().is_empty() {
+    if !is_hermes
+        && req.tailscale
+        && req.tailscale_auth_key.trim().is_empty()
+        && !crate::commands::tailnet::api_configured()
+    {
         return (
             StatusCode::BAD_REQUEST,
             Json(ErrorResponse {
-                message: "Tailscale auth key is required when Tailscale is enabled.".into(),
+                message: "Tailscale auth key is required when Tailscale is enabled (or set TAILSCALE_API_KEY on the server).".into(),
             }),
         )
             .into_response();
@@ -1006,7 +1055,12 @@ async fn start_deploy_handler(
     let backup: Option<PathBuf> = if is_hermes || req.backup.is_empty() || req.backup == "none" {
         None
     } else {
-        match config::resolve_backup_path(&req.backup) {
+        let resolved = match config::resolve_backup_path(&req.backup) {
+            Ok(path) => Ok(path),
+            // Not a local file: the name of an archive in a remote store.
+            Err(err) => fetch_stored_backup(&req.backup).await.map_err(|_| err),
+        };
+        match resolved {
             Ok(path) => Some(path),
             Err(err) => {
                 return (
@@ -1075,37 +1129,46 @@ async fn start_deploy_handler(
                 } else {
                     Some(req.size.clone())
                 };
-                let result = crate::commands::hermes_lightsail::run(
-                    crate::commands::hermes_lightsail::HermesLightsailParams {
-                        deploy_id: Some(id.clone()),
-                        customer_email: customer_email.clone(),
-                        name: hostname,
-                        aws_access_key_id: req.aws_access_key_id,
-                        aws_secret_access_key: req.aws_secret_access_key,
-                        aws_region: hermes_region,
-                        size: hermes_size,
-                        image: req.hermes_image,
-                        env_file: None,
-                        env_content: Some(req.hermes_env),
-                        env_vars: Vec::new(),
-                        bedrock_api_key: Some(req.hermes_bedrock_api_key)
-                            .filter(|value| !value.trim().is_empty()),
-                        bedrock_region: req.hermes_bedrock_region,
-                        bedrock_model: req.hermes_bedrock_model,
-                        telegram_bot_token: Some(req.hermes_telegram_bot_token)
-                            .filter(|value| !value.trim().is_empty()),
-                        telegram_allowed_users: Some(req.hermes_telegram_allowed_users)
-                            .filter(|value| !value.trim().is_empty()),
-                        telegram_home_channel: Some(req.hermes_telegram_home_channel)
-                            .filter(|value| !value.trim().is_empty()),
-                        dashboard: req.hermes_dashboard,
-                        mount_docker_socket: req.hermes_mount_docker_socket,
-                        dry_run: is_dry_run(),
-                        json: false,
-                        progress_tx: Some(tx.clone()),
-                        db: Some(db_clone.clone()),
-                    },
-                )
+                let result = crate::commands::hermes::run(crate::commands::hermes::HermesParams {
+                    deploy_id: Some(id.clone()),
+                    customer_email: customer_email.clone(),
+                    provider: "lightsail".to_string(),
+                    region: None,
+                    name: hostname,
+                    do_token: String::new(),
+                    tencent_secret_id: String::new(),
+                    tencent_secret_key: String::new(),
+                    azure_tenant_id: String::new(),
+                    azure_subscription_id: String::new(),
+                    azure_client_id: String::new(),
+                    azure_client_secret: String::new(),
+                    byteplus_access_key: String::new(),
+                    byteplus_secret_key: String::new(),
+                    aws_access_key_id: req.aws_access_key_id,
+                    aws_secret_access_key: req.aws_secret_access_key,
+                    aws_region: hermes_region,
+                    size: hermes_size,
+                    image: req.hermes_image,
+                    env_file: None,
+                    env_content: Some(req.hermes_env),
+                    env_vars: Vec::new(),
+                    bedrock_api_key: Some(req.hermes_bedrock_api_key)
+                        .filter(|value| !value.trim().is_empty()),
+                    bedrock_region: req.hermes_bedrock_region,
+                    bedrock_model: req.hermes_bedrock_model,
+                    telegram_bot_token: Some(req.hermes_telegram_bot_token)
+                        .filter(|value| !value.trim().is_empty()),
+                    telegram_allowed_users: Some(req.hermes_telegram_allowed_users)
+                        .filter(|value| !value.trim().is_empty()),
+                    telegram_home_channel: Some(req.hermes_telegram_home_channel)
+                        .filter(|value| !value.trim().is_empty()),
+                    dashboard: req.hermes_dashboard,
+                    mount_docker_socket: req.hermes_mount_docker_socket,
+                    dry_run: is_dry_run(),
+                    json: false,
+                    progress_tx: Some(tx.clone()),
+                    db: Some(db_clone.clone()),
+                })
                 .await;
 
                 let final_status = match &result {
@@ -1206,6 +1269,8 @@ async fn start_deploy_handler(
             profile: req.profile,
             spot: req.spot,
             openclaw_version: req.openclaw_version,
+            image: None,
+            extensions: Vec::new(),
             non_interactive: true,
             progress_tx: Some(tx.clone()),
             db: Some(db_clone.clone()),
@@ -1323,7 +1388,184 @@ async fn deploy_events_handler(
         }
     };
 
-    Sse::new(stream.map(|msg| Ok(Event::default().data(msg))))
+    // The guard lives as long as the stream, so the gauge drops when the client disconnects.
+    let guard = metrics::SseStreamGuard::new();
+    Sse::new(stream.map(move |msg| {
+        let _ = &guard;
+        Ok(Event::default().data(msg))
+    }))
+}
+
+#[derive(Deserialize)]
+struct LogsQuery {
+    #[serde(default = "default_log_source")]
+    source: String,
+    #[serde(default)]
+    follow: bool,
+    since: Option<String>,
+    grep: Option<String>,
+    #[serde(default = "default_log_lines")]
+    lines: usize,
+    #[serde(default = "default_log_container")]
+    container: String,
+}
+
+fn default_log_source() -> String {
+    "gateway".into()
+}
+
+fn default_log_lines() -> usize {
+    200
+}
+
+fn default_log_container() -> String {
+    "hermes".into()
+}
+
+/// Relay an instance log as SSE, one redacted line per `data` event. The
+/// stream ends with an `end` event, or an `error` event when it cannot run.
+async fn deployment_logs_handler(
+    Path(id): Path<String>,
+    Query(query): Query<LogsQuery>,
+) -> Sse<impl tokio_stream::Stream<Item = Result<Event, Infallible>>> {
+    use crate::commands::{fleet, logs};
+
+    let (tx, rx) = mpsc::unbounded_channel::<Event>();
+    tokio::spawn(async move {
+        let query = logs::LogQuery {
+            source: query.source,
+            follow: query.follow,
+            since: query.since,
+            grep: query.grep,
+            lines: query.lines.min(5000),
+            container: query.container,
+        };
+        let started = async {
+            let command = logs::log_command(&query)?;
+            let target = fleet::find(&id)?;
+            anyhow::Ok((command, target))
+        };
+        let (command, target) = match started.await {
+            Ok(started) => started,
+            Err(e) => {
+                let _ = tx.send(Event::default().event("error").data(format!("{e:#}")));
+                return;
+            }
+        };
+        let redactor = logs::load_redactor(&target).await;
+        let (line_tx, mut line_rx) = mpsc::unbounded_channel::<String>();
+        let relay = {
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                while let Some(line) = line_rx.recv().await {
+                    if tx.send(Event::default().data(line)).is_err() {
+                        break;
+                    }
+                }
+            })
+        };
+        let outcome = logs::stream(&target, &command, redactor, line_tx).await;
+        let _ = relay.await;
+        let _ = match outcome {
+            Ok(status) => tx.send(
+                Event::default()
+                    .event("end")
+                    .data(status.map(|s| s.to_string()).unwrap_or_default()),
+            ),
+            Err(e) => tx.send(Event::default().event("error").data(format!("{e:#}"))),
+        };
+    });
+
+    let guard = metrics::SseStreamGuard::new();
+    Sse::new(UnboundedReceiverStream::new(rx).map(move |event| {
+        let _ = &guard;
+        Ok(event)
+    }))
+    .keep_alive(axum::response::sse::KeepAlive::default())
+}
+
+const DEPLOYMENTS_METRIC: &str = "clawmacdo_deployments";
+const DEPLOY_STEP_DURATION_METRIC: &str = "clawmacdo_deploy_step_duration_seconds";
+const DEPLOY_JOBS_RUNNING_METRIC: &str = "clawmacdo_deploy_jobs_running";
+
+/// Prometheus scrape endpoint.
+///
+/// Deploy counts and step durations are computed from the SQLite history on each
+/// scrape; SSH, cloud API, rate-limit and SSE metrics come from the in-process registry.
+async fn metrics_handler(State(state): State<AppState>) -> Response {
+    let running = state
+        .jobs
+        .read()
+        .await
+        .values()
+        .filter(|job| job.status == JobStatus::Running)
+        .count();
+
+    let (outcomes, steps) = {
+        let conn = match lock_db(&state.db) {
+            Ok(conn) => conn,
+            Err(resp) => return resp,
+        };
+        (
+            db::deployment_outcome_counts(&conn).unwrap_or_default(),
+            db::completed_step_durations(&conn).unwrap_or_default(),
+        )
+    };
+
+    let mut out = String::new();
+    metrics::write_header(
+        &mut out,
+        DEPLOYMENTS_METRIC,
+        metrics::MetricKind::Gauge,
+        "Recorded deployments by provider and outcome",
+    );
+    for row in &outcomes {
+        out.push_str(&format!(
+            "{DEPLOYMENTS_METRIC}{} {}\n",
+            metrics::format_labels(&[("provider", &row.provider), ("outcome", &row.status)]),
+            row.count
+        ));
+    }
+
+    metrics::write_header(
+        &mut out,
+        DEPLOY_STEP_DURATION_METRIC,
+        metrics::MetricKind::Histogram,
+        "Deploy step durations derived from deploy_steps timestamps",
+    );
+    let mut histograms: std::collections::BTreeMap<(String, i32), metrics::Histogram> =
+        std::collections::BTreeMap::new();
+    for row in &steps {
+        histograms
+            .entry((row.provider.clone(), row.step_number))
+            .or_insert_with(|| metrics::Histogram::new(metrics::DEFAULT_BUCKETS))
+            .observe(row.seconds);
+    }
+    for ((provider, step), hist) in &histograms {
+        let step = step.to_string();
+        hist.render_into(
+            &mut out,
+            DEPLOY_STEP_DURATION_METRIC,
+            &[("provider", provider), ("step", &step)],
+        );
+    }
+
+    metrics::write_header(
+        &mut out,
+        DEPLOY_JOBS_RUNNING_METRIC,
+        metrics::MetricKind::Gauge,
+        "Deploy jobs currently running in this server",
+    );
+    out.push_str(&format!("{DEPLOY_JOBS_RUNNING_METRIC} {running}\n"));
+
+    out.push_str(&metrics::render());
+
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        out,
+    )
+        .into_response()
 }
 
 /// Return deploy/operation steps from SQLite for progress polling.
@@ -3262,6 +3504,168 @@ async fn ark_api_key_handler(Json(_req): Json<ArkApiKeyRequest>) -> impl IntoRes
     )
 }
 
+#[cfg(feature = "byteplus")]
+async fn ark_models_handler(Json(req): Json<ArkListEndpointsRequest>) -> impl IntoResponse {
+    use crate::commands::ark;
+    use clawmacdo_cloud::byteplus::BytePlusClient;
+
+    let failure = |status: StatusCode, error: String| {
+        (
+            status,
+            Json(ArkModelsResponse {
+                ok: false,
+                models: vec![],
+                error: Some(error),
+            }),
+        )
+    };
+    if req.access_key.is_empty() || req.secret_key.is_empty() {
+        return failure(
+            StatusCode::BAD_REQUEST,
+            "access_key and secret_key are required.".into(),
+        );
+    }
+    let client = match BytePlusClient::new(&req.access_key, &req.secret_key, "ap-southeast-1") {
+        Ok(c) => c,
+        Err(e) => {
+            return failure(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Client init failed: {e}"),
+            )
+        }
+    };
+    match client.list_endpoints().await {
+        Ok(endpoints) => (
+            StatusCode::OK,
+            Json(ArkModelsResponse {
+                ok: true,
+                models: ark::join_models(&endpoints)
+                    .into_iter()
+                    .filter_map(|m| serde_json::to_value(m).ok())
+                    .collect(),
+                error: None,
+            }),
+        ),
+        Err(e) => failure(
+            StatusCode::BAD_GATEWAY,
+            format!("Failed to list endpoints: {e}"),
+        ),
+    }
+}
+
+#[cfg(not(feature = "byteplus"))]
+async fn ark_models_handler(Json(_req): Json<ArkListEndpointsRequest>) -> impl IntoResponse {
+    (
+        StatusCode::NOT_IMPLEMENTED,
+        Json(ArkModelsResponse {
+            ok: false,
+            models: vec![],
+            error: Some("BytePlus support not compiled in.".into()),
+        }),
+    )
+}
+
+/// Send one chat turn to an ARK endpoint, so a key can be tried before it is
+/// used in a deploy. With `stream`, replies with `data` events carrying
+/// pieces of the reply, then a `usage` event (JSON) and `end`, or `error`.
+#[cfg(feature = "byteplus")]
+async fn ark_chat_handler(Json(req): Json<ArkChatRequest>) -> Response {
+    use crate::commands::ark;
+
+    let prepared = (|| {
+        let client = ark::ArkClient::new(&req.api_key, &req.endpoint_id)?;
+        if req.prompt.trim().is_empty() {
+            anyhow::bail!("prompt is required.");
+        }
+        let mut messages = req.messages;
+        if let Some(system) = req.system.as_deref().filter(|s| !s.trim().is_empty()) {
+            ark::set_system(&mut messages, system);
+        }
+        messages.push(serde_json::json!({ "role": "user", "content": req.prompt.trim() }));
+        anyhow::Ok((client, messages))
+    })();
+    let (client, messages) = match prepared {
+        Ok(prepared) => prepared,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ArkChatResponse {
+                    ok: false,
+                    reply: String::new(),
+                    usage: None,
+                    error: Some(format!("{e:#}")),
+                }),
+            )
+                .into_response()
+        }
+    };
+
+    if !req.stream {
+        return match client.complete(&messages, false, &mut |_| {}).await {
+            Ok(reply) => Json(ArkChatResponse {
+                ok: true,
+                reply: reply.content,
+                usage: reply.usage.and_then(|u| serde_json::to_value(u).ok()),
+                error: None,
+            })
+            .into_response(),
+            Err(e) => (
+                StatusCode::BAD_GATEWAY,
+                Json(ArkChatResponse {
+                    ok: false,
+                    reply: String::new(),
+                    usage: None,
+                    error: Some(format!("{e:#}")),
+                }),
+            )
+                .into_response(),
+        };
+    }
+
+    let (tx, rx) = mpsc::unbounded_channel::<Event>();
+    tokio::spawn(async move {
+        let delta_tx = tx.clone();
+        let outcome = client
+            .complete(&messages, true, &mut |delta| {
+                let _ = delta_tx.send(Event::default().data(delta));
+            })
+            .await;
+        match outcome {
+            Ok(reply) => {
+                if let Some(usage) = reply.usage.and_then(|u| serde_json::to_string(&u).ok()) {
+                    let _ = tx.send(Event::default().event("usage").data(usage));
+                }
+                let _ = tx.send(Event::default().event("end").data(""));
+            }
+            Err(e) => {
+                let _ = tx.send(Event::default().event("error").data(format!("{e:#}")));
+            }
+        }
+    });
+
+    let guard = metrics::SseStreamGuard::new();
+    Sse::new(UnboundedReceiverStream::new(rx).map(move |event| {
+        let _ = &guard;
+        Ok::<_, Infallible>(event)
+    }))
+    .keep_alive(axum::response::sse::KeepAlive::default())
+    .into_response()
+}
+
+#[cfg(not(feature = "byteplus"))]
+async fn ark_chat_handler(Json(_req): Json<ArkChatRequest>) -> Response {
+    (
+        StatusCode::NOT_IMPLEMENTED,
+        Json(ArkChatResponse {
+            ok: false,
+            reply: String::new(),
+            usage: None,
+            error: Some("BytePlus support not compiled in.".into()),
+        }),
+    )
+        .into_response()
+}
+
 // ── Helpers ─────────────────────────────────────────────────────────────────
 
 /// Resolve the SSH username from the provider string.
@@ -3289,37 +3693,39 @@ fn normalize_pairing_code(raw: &str) -> Option<String> {
     }
 }
 
-/// LList backup files.
-fn list_backup_files() -> anyhow::Result<Vec<BackupEntry>> {
+/// List backup files in the configured backup store.
+///
+/// Local archives carry their path; archives in a remote store carry their
+/// name, which the deploy handler fetches through [`fetch_stored_backup`].
+async fn list_backup_files() -> anyhow::Result<Vec<BackupEntry>> {
     let backups_dir = config::backups_dir()?;
-    let mut entries = Vec::new();
-
-    if !backups_dir.exists() {
-        return Ok(entries);
-    }
-
-    for entry in std::fs::read_dir(&backups_dir)? {
-        let entry = entry?;
-        let path = entry.path();
-        if path.extension().and_then(|e| e.to_str()) == Some("gz") {
-            let name = path
-                .file_name()
-                .unwrap_or_default()
-                .to_string_lossy()
-                .to_string();
-            let size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
-            entries.push(BackupEntry {
-                name,
-                path: path.display().to_string(),
-                size,
-            });
-        }
-    }
-
-    entries.sort_by(|a, b| b.name.cmp(&a.name));
+    let store = backup_store::configured_store()?;
+    let entries = store
+        .list()
+        .await?
+        .into_iter()
+        .map(|backup| {
+            let local = backups_dir.join(&backup.name);
+            BackupEntry {
+                path: if local.is_file() {
+                    local.display().to_string()
+                } else {
+                    backup.name.clone()
+                },
+                name: backup.name,
+                size: backup.size,
+            }
+        })
+        .collect();
     Ok(entries)
 }
 
+/// Fetch a named archive from the configured backup store into the local cache.
+async fn fetch_stored_backup(name: &str) -> anyhow::Result<PathBuf> {
+    let store = backup_store::configured_store()?;
+    Ok(store.fetch(name.trim()).await?)
+}
+
 // ── Embedded HTML ───────────────────────────────────────────────────────────
 
 const LOGIN_HTML: &str = r##"<!DOCTYPE html>
@@ -3888,6 +4294,7 @@ function syncModelSelectors(n) {
     html += '<div class="flex gap-2"><div class="relative flex-1"><input type="password" name="' + pDef.keyField + '" data-model-key="primary" data-required class="w-full bg-slate-800 border border-slate-700 rounded-lg px-3 sm:px-4 py-2 sm:py-2.5 pr-10 sm:pr-12 text-sm sm:text-base text-slate-200 placeholder-slate-500 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent" placeholder="' + pDef.placeholder + '" value="' + esc(saved[pDef.keyField] || '') + '">' + eyeBtn() + '</div>';
     if (primary === 'byteplus') {
       html += '<button type="button" onclick="generateArkKey(' + n + ')" class="shrink-0 px-3 py-2 bg-blue-600 hover:bg-blue-700 text-white text-xs font-medium rounded-lg transition-colors" title="Generate ARK API Key from BytePlus credentials">Generate</button>';
+      html += '<button type="button" onclick="testArkKey(' + n + ')" class="shrink-0 px-3 py-2 bg-slate-700 hover:bg-slate-600 text-white text-xs font-medium rounded-lg transition-colors" title="Send a test prompt with this ARK API Key">Test</button>';
     }
     html += '</div></div>';
   }
@@ -4461,6 +4868,7 @@ async function generateArkKey(n) {
 
     arkInput.value = keyData.api_key;
     arkInput.type = 'text';
+    arkInput.dataset.endpointId = endpointId;
     alert('ARK API Key generated!\\nExpires: ' + keyData.expires);
   } catch (err) {
     alert('Error: ' + err.message);
@@ -4469,6 +4877,44 @@ async function generateArkKey(n) {
   }
 }
 
+async function testArkKey(n) {
+  const card = document.getElementById('deploy-card-' + n);
+  if (!card) return;
+  const arkInput = card.querySelector('[name="byteplus_ark_api_key"]');
+  if (!arkInput) { alert('BytePlus ARK API Key field not found.'); return; }
+  const apiKey = arkInput.value.trim();
+  if (!apiKey) { alert('Enter or generate an ARK API Key first.'); return; }
+
+  let endpointId = arkInput.dataset.endpointId || '';
+  if (!endpointId) {
+    endpointId = (prompt('ARK endpoint ID to test against (ep-...):') || '').trim();
+    if (!endpointId) return;
+    arkInput.dataset.endpointId = endpointId;
+  }
+
+  const testBtn = card.querySelector('button[onclick*="testArkKey"]');
+  const origText = testBtn ? testBtn.textContent : '';
+  if (testBtn) { testBtn.textContent = 'Testing...'; testBtn.disabled = true; }
+  try {
+    const resp = await fetch('/api/ark/chat', {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ api_key: apiKey, endpoint_id: endpointId, prompt: 'Reply with the single word OK.' })
+    });
+    const data = await resp.json();
+    if (!data.ok) {
+      alert('ARK key test failed: ' + (data.error || 'Unknown error'));
+      return;
+    }
+    const usage = data.usage ? '\nTokens: ' + data.usage.total_tokens : '';
+    alert('ARK key works with ' + endpointId + '.\nReply: ' + data.reply.slice(0, 200) + usage);
+  } catch (err) {
+    alert('Error: ' + err.message);
+  } finally {
+    if (testBtn) { testBtn.textContent = origText; testBtn.disabled = false; }
+  }
+}
+
 function syncTailscaleKeyRequirement(form) {
   const tailscaleToggle = form.querySelector('[name="tailscale"]');
   const tailscaleKeyInput = form.querySelector('[name="tailscale_auth_key"]');
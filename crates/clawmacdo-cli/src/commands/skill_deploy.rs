@@ -1,13 +1,200 @@
-use anyhow::{bail, Result};
+use super::skill_lint;
+use super::skill_package::{self, SkillFiles};
+use super::wiki::{handle_remote_status, remote_json_value};
+use anyhow::{bail, Context, Result};
+use clawmacdo_core::archive::{self, ZipArchive};
 use clawmacdo_core::config;
+use clawmacdo_provision::provision::commands::ssh_as_openclaw_with_user_async;
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 // Gateway auto-discovers workspace skills from the skills/ subdirectory of the workspace.
 const OPENCLAW_WORKSPACE: &str = "/home/openclaw/.openclaw/workspace/skills";
 
+// Restart gateway; poll up to 3s instead of a fixed sleep 2.
+const RESTART_CMD: &str =
+    "export XDG_RUNTIME_DIR=/run/user/$(id -u) DBUS_SESSION_BUS_ADDRESS=unix:path=/run/user/$(id -u)/bus && \
+         (systemctl --user daemon-reload 2>/dev/null || true) && \
+         (systemctl --user restart openclaw-gateway.service 2>/dev/null || \
+          systemctl --user start openclaw-gateway.service 2>/dev/null || true) && \
+         for i in 1 2 3; do \
+           s=$(systemctl --user is-active openclaw-gateway.service 2>/dev/null) && \
+           [ \"$s\" = 'active' ] && break; \
+           sleep 1; \
+         done && \
+         echo \"gateway: $(systemctl --user is-active openclaw-gateway.service 2>/dev/null || echo unknown)\"";
+
+const MAX_SKILL_ENTRIES: usize = 2000;
+const MAX_SKILL_FILE_BYTES: u64 = 20 * 1024 * 1024;
+const MAX_SKILL_TOTAL_BYTES: u64 = 100 * 1024 * 1024;
+
+/// The checked entries of a skill ZIP that passed local validation.
+#[derive(Debug)]
+pub(crate) struct ValidatedSkillZip {
+    /// Skill directory names, sorted.
+    pub(crate) skills: Vec<String>,
+    pub(crate) total_size: u64,
+    /// Archive path → (contents, Unix mode), macOS metadata dropped.
+    pub(crate) files: BTreeMap<String, (Vec<u8>, u32)>,
+}
+
+impl ValidatedSkillZip {
+    /// Files grouped by skill directory, paths relative to the skill.
+    pub(crate) fn by_skill(&self) -> Vec<(String, SkillFiles)> {
+        self.skills
+            .iter()
+            .map(|dir| {
+                let prefix = format!("{dir}/");
+                let files = self
+                    .files
+                    .iter()
+                    .filter_map(|(path, (data, mode))| {
+                        let rel = path.strip_prefix(&prefix)?;
+                        Some((rel.to_string(), data.clone(), *mode))
+                    })
+                    .collect();
+                (dir.clone(), files)
+            })
+            .collect()
+    }
+}
+
+fn clean_skill_dir_name(name: &str) -> Result<()> {
+    if name.starts_with('.')
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+    {
+        bail!("Invalid skill directory name '{name}': use letters, digits, '.', '_' or '-'");
+    }
+    Ok(())
+}
+
+/// Check a skill ZIP before anything is uploaded.
+///
+/// Every file must sit under a top-level skill directory that contains a
+/// `SKILL.md`. Unsafe paths (zip-slip), symlinks, encrypted entries and
+/// archives over the size limits are rejected; macOS metadata is dropped.
+pub(crate) fn validate_skill_zip(data: &[u8]) -> Result<ValidatedSkillZip> {
+    let mut zip = ZipArchive::new(std::io::Cursor::new(data), MAX_SKILL_ENTRIES)
+        .context("Not a valid ZIP file")?;
+    let mut files: BTreeMap<String, (Vec<u8>, u32)> = BTreeMap::new();
+    let mut skills: BTreeMap<String, bool> = BTreeMap::new();
+    let mut total_size = 0u64;
+
+    for index in 0..zip.entries().len() {
+        let entry = zip.entries()[index].clone();
+        let name = archive::safe_relative_path(&entry.name)
+            .with_context(|| format!("Unsafe ZIP entry {:?}", entry.name))?;
+        if name == "__MACOSX"
+            || name.starts_with("__MACOSX/")
+            || name.rsplit('/').next() == Some(".DS_Store")
+        {
+            continue;
+        }
+        if entry.is_symlink() {
+            bail!("ZIP entry {name} is a symlink; skills must not contain links");
+        }
+        if entry.is_encrypted() {
+            bail!("ZIP entry {name} is encrypted");
+        }
+        let Some((skill, rest)) = name.split_once('/') else {
+            if entry.is_dir() {
+                clean_skill_dir_name(&name)?;
+                skills.entry(name).or_insert(false);
+                continue;
+            }
+            bail!("ZIP entry {name} is at the archive root; put each skill in its own directory");
+        };
+        clean_skill_dir_name(skill)?;
+        let has_skill_md = skills.entry(skill.to_string()).or_insert(false);
+        if entry.is_dir() {
+            continue;
+        }
+        if rest == "SKILL.md" {
+            *has_skill_md = true;
+        }
+        if entry.size > MAX_SKILL_FILE_BYTES {
+            bail!("ZIP entry {name} is larger than {MAX_SKILL_FILE_BYTES} bytes");
+        }
+        total_size += entry.size;
+        if total_size > MAX_SKILL_TOTAL_BYTES {
+            bail!("Skill ZIP expands to more than {MAX_SKILL_TOTAL_BYTES} bytes");
+        }
+        let contents = zip.read(index, MAX_SKILL_FILE_BYTES)?;
+        if files.insert(name.clone(), (contents, entry.mode)).is_some() {
+            bail!("ZIP entry {name} appears more than once");
+        }
+    }
+
+    if skills.is_empty() {
+        bail!("Skill ZIP contains no skill directories");
+    }
+    if let Some((skill, _)) = skills.iter().find(|(_, has_skill_md)| !**has_skill_md) {
+        bail!("Skill directory '{skill}' has no SKILL.md");
+    }
+
+    Ok(ValidatedSkillZip {
+        skills: skills.into_keys().collect(),
+        total_size,
+        files,
+    })
+}
+
+/// Read a local skill ZIP and validate it (see `validate_skill_zip`).
+pub(crate) fn read_skill_zip(zip_path: &Path) -> Result<ValidatedSkillZip> {
+    if !zip_path.exists() {
+        bail!("File not found: {}", zip_path.display());
+    }
+    let ext = zip_path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    if ext != "zip" {
+        bail!("File must be a .zip archive: {}", zip_path.display());
+    }
+    validate_skill_zip(&std::fs::read(zip_path)?)
+        .with_context(|| format!("Skill ZIP {} failed validation", zip_path.display()))
+}
+
+/// Run `skill-lint` over a validated ZIP and fail on errors. Findings are
+/// printed unless `quiet`; tool availability is left to the install step,
+/// which reports missing tools per instance.
+pub(crate) fn lint_before_deploy(
+    validated: &ValidatedSkillZip,
+    zip_path: &Path,
+    quiet: bool,
+) -> Result<()> {
+    let report = skill_lint::lint_skills(&validated.by_skill());
+    if !quiet && !report.findings.is_empty() {
+        println!("Linting {}...", zip_path.display());
+        report.print();
+        println!();
+    }
+    if report.errors > 0 {
+        bail!(
+            "skill-lint found {} error(s); fix them or pass --no-lint",
+            report.errors
+        );
+    }
+    Ok(())
+}
+
+/// Hand the workspace skills and the version store to openclaw.
+///
+/// Single chmod -R u=rwX,go=rX sets dirs to 755, files to 644 in one pass.
+/// chown/chmod use `|| true` because the openclaw user may not be able to chown
+/// pre-existing files owned by root from a previous deploy.
+pub(crate) fn perms_cmd() -> String {
+    let ws = OPENCLAW_WORKSPACE;
+    let store = skill_package::SKILL_VERSIONS_DIR;
+    format!(
+        "(chown -R openclaw:openclaw {ws} {store} 2>/dev/null || true) && \
+         (chmod -R u=rwX,go=rX {ws} {store} 2>/dev/null || true) && \
+         echo 'perms OK'"
+    )
+}
+
 /// Look up a deploy record by hostname, IP, or deploy ID.
 /// Returns (ip, ssh_key_path, provider).
-fn find_deploy_record(query: &str) -> Result<(String, PathBuf, Option<String>)> {
+pub(crate) fn find_deploy_record(query: &str) -> Result<(String, PathBuf, Option<String>)> {
     let deploys_dir = config::deploys_dir()?;
     if !deploys_dir.exists() {
         bail!("No deploy records found. Deploy an instance first.");
@@ -37,115 +224,189 @@ fn find_deploy_record(query: &str) -> Result<(String, PathBuf, Option<String>)>
     bail!("No deploy record found for '{query}'. Use a deploy ID, hostname, or IP address.");
 }
 
-fn ssh_user_for_provider(provider: &Option<String>) -> &'static str {
+pub(crate) fn ssh_user_for_provider(provider: &Option<String>) -> &'static str {
     match provider.as_deref() {
         Some("lightsail") => "ubuntu",
         _ => "root",
     }
 }
 
-/// Deploy a ZIP of OpenClaw skills to an instance.
+/// Deploy a ZIP of OpenClaw skills to an instance as versioned packages.
 ///
-/// All network I/O (SCP upload + extract + restart) shares a single SSH session,
+/// All network I/O (SCP upload + install + restart) shares a single SSH session,
 /// eliminating the extra TCP connect + handshake that two separate sessions would incur.
 ///
 /// Steps:
-/// 1. Validate and read the local ZIP file
-/// 2. SCP the ZIP to /tmp on the instance  ┐
-/// 3. Extract with `unzip` + fix perms      ├── single SSH session
-/// 4. Restart the OpenClaw gateway          ┘
-pub async fn deploy(query: &str, zip_path: &Path) -> Result<()> {
-    // Validate file exists and is a zip
-    if !zip_path.exists() {
-        bail!("File not found: {}", zip_path.display());
-    }
-    let ext = zip_path.extension().and_then(|e| e.to_str()).unwrap_or("");
-    if ext != "zip" {
-        bail!("File must be a .zip archive: {}", zip_path.display());
-    }
-
-    let zip_bytes = std::fs::read(zip_path)?;
-    if zip_bytes.len() < 4 || &zip_bytes[..4] != b"PK\x03\x04" {
-        bail!("Not a valid ZIP file: {}", zip_path.display());
+/// 1. Read the local ZIP, validate it (see `validate_skill_zip`), lint it
+///    unless `lint` is false (see `skill_lint`) and build one package per
+///    skill directory (see `skill_package::build_package`)
+/// 2. SCP the packages to /tmp on the instance          ┐
+/// 3. Store each as a new version, activate it, prune   ├── single SSH session
+/// 4. Restart the OpenClaw gateway                      ┘
+pub async fn deploy(
+    query: &str,
+    zip_path: &Path,
+    keep: usize,
+    force: bool,
+    lint: bool,
+) -> Result<()> {
+    let validated = read_skill_zip(zip_path)?;
+    if lint {
+        lint_before_deploy(&validated, zip_path, false)?;
     }
+    let packages = skill_package::packages_from_zip(&validated)?;
 
     let (ip, key, provider) = find_deploy_record(query)?;
     let ssh_user = ssh_user_for_provider(&provider);
 
     println!(
-        "Deploying skills from {} ({} bytes) to {ip}...",
+        "Deploying {} skill(s) from {} ({} file(s), {} bytes) to {ip}...",
+        packages.len(),
         zip_path.display(),
-        zip_bytes.len()
+        validated.files.len(),
+        validated.total_size
     );
+    for package in &packages {
+        println!(
+            "  • {}  {} ({})",
+            package.dir, package.manifest.version, package.manifest.name
+        );
+    }
 
-    let tmp_zip = "/tmp/openclaw-skills-upload.zip";
-    let ws = OPENCLAW_WORKSPACE;
-
-    // Extract with `unzip -o` (overwrite, no prompts). Falls back to Python if unzip is missing.
-    // Single chmod -R u=rwX,go=rX sets dirs to 755, files to 644 in one pass.
-    // chown/chmod use `|| true` because the openclaw user may not be able to chown
-    // pre-existing files owned by root from a previous deploy.
-    let extract_cmd = format!(
-        "mkdir -p {ws} && \
-         if command -v unzip >/dev/null 2>&1; then \
-           unzip -o {tmp_zip} -d {ws} 2>&1; \
-         else \
-           python3 -c \"\
-import zipfile,os;z=zipfile.ZipFile('{tmp_zip}');\
-[os.makedirs(os.path.dirname(os.path.join('{ws}',m.replace(chr(92),'/'))),exist_ok=True) or \
-open(os.path.join('{ws}',m.replace(chr(92),'/')),'wb').write(z.read(m)) for m in z.namelist() if not m.endswith('/')];\
-z.close();print('extracted OK')\" 2>&1; \
-         fi && \
-         (chown -R openclaw:openclaw {ws} 2>/dev/null || true) && \
-         (chmod -R u=rwX,go=rX {ws} 2>/dev/null || true) && \
-         (rm -f {tmp_zip} 2>/dev/null || true) && \
-         echo 'extract+perms OK'"
-    );
-
-    // Restart gateway; poll up to 3s instead of a fixed sleep 2.
-    let restart_cmd =
-        "export XDG_RUNTIME_DIR=/run/user/$(id -u) DBUS_SESSION_BUS_ADDRESS=unix:path=/run/user/$(id -u)/bus && \
-         (systemctl --user daemon-reload 2>/dev/null || true) && \
-         (systemctl --user restart openclaw-gateway.service 2>/dev/null || \
-          systemctl --user start openclaw-gateway.service 2>/dev/null || true) && \
-         for i in 1 2 3; do \
-           s=$(systemctl --user is-active openclaw-gateway.service 2>/dev/null) && \
-           [ \"$s\" = 'active' ] && break; \
-           sleep 1; \
-         done && \
-         echo \"gateway: $(systemctl --user is-active openclaw-gateway.service 2>/dev/null || echo unknown)\"";
-
-    println!("[1/3] Uploading ZIP to instance...");
-    println!("[2/3] Extracting skills + fixing permissions...");
+    println!("[1/3] Uploading skills to instance...");
+    println!("[2/3] Installing versions + fixing permissions...");
     println!("[3/3] Restarting gateway...");
 
-    // Single SSH session: SCP upload, then run extract + restart commands.
-    let scp_ip = ip.clone();
-    let scp_key = key.clone();
-    let scp_user = ssh_user.to_string();
-    let extract_cmd_owned = extract_cmd;
-    let restart_cmd_owned = restart_cmd.to_string();
-    let outputs = tokio::task::spawn_blocking(move || {
-        let cmds: Vec<&str> = vec![&extract_cmd_owned, &restart_cmd_owned];
-        clawmacdo_ssh::scp_upload_bytes_and_exec_as(
-            &scp_ip, &scp_key, &zip_bytes, tmp_zip, 0o644, &cmds, &scp_user,
-        )
-    })
-    .await??;
+    let (value, outputs) = skill_package::install_packages(
+        &ip,
+        &key,
+        ssh_user,
+        &packages,
+        keep,
+        force,
+        &[perms_cmd(), RESTART_CMD.to_string()],
+    )
+    .await?;
 
-    // outputs[0] = extract + perms
-    let extract_out = outputs[0].trim();
-    if extract_out.contains("extract+perms OK") {
-        println!("  Skills extracted to workspace.");
-    } else {
-        println!("  {extract_out}");
+    for skill in value
+        .get("skills")
+        .and_then(serde_json::Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let field = |name: &str| skill.get(name).and_then(serde_json::Value::as_str);
+        let dir = field("dir").unwrap_or("");
+        let version = field("version").unwrap_or("");
+        match field("previous") {
+            _ if skill.get("unchanged").and_then(serde_json::Value::as_bool) == Some(true) => {
+                println!("  {dir}: {version} (unchanged)")
+            }
+            Some(previous) => println!("  {dir}: {previous} → {version}"),
+            None => println!("  {dir}: installed {version}"),
+        }
+        let missing: Vec<&str> = skill
+            .get("missing_tools")
+            .and_then(serde_json::Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(serde_json::Value::as_str)
+            .collect();
+        if !missing.is_empty() {
+            println!(
+                "    warning: required tool(s) not found on the instance: {}",
+                missing.join(", ")
+            );
+        }
     }
 
     // outputs[1] = restart
-    println!("  {}", outputs[1].trim());
+    if let Some(restart) = outputs.get(1) {
+        println!("  {}", restart.trim());
+    }
 
     println!("\nSkills deployed to {ip}.");
     println!("The gateway has been restarted and will pick up the new skills.");
+    println!("Roll back with `clawmacdo skill-rollback --instance {query} --skill <name>`.");
+
+    Ok(())
+}
+
+/// Re-activate a stored version of a skill (the previous one by default)
+/// and restart the gateway.
+pub async fn rollback(query: &str, skill: &str, to: Option<&str>) -> Result<()> {
+    let skill = skill_package::clean_skill_name(skill)?;
+    let (ip, key, provider) = find_deploy_record(query)?;
+    let ssh_user = ssh_user_for_provider(&provider);
+    let cmd = skill_package::build_rollback_cmd(&skill, to)?;
+
+    println!("Rolling back skill '{skill}' on {ip}...");
+    let output = ssh_as_openclaw_with_user_async(&ip, &key, &cmd, ssh_user).await?;
+    let value = remote_json_value(&output, "skill rollback")?;
+    handle_remote_status(&value, false)?;
+
+    let field = |name: &str| {
+        value
+            .get(name)
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or("")
+    };
+    if let Some(snapshot) = value.get("snapshot").and_then(serde_json::Value::as_str) {
+        println!("  Local edits saved as version {snapshot}.");
+    }
+    println!("  {}: {} → {}", field("dir"), field("from"), field("to"));
 
+    let restart = ssh_as_openclaw_with_user_async(&ip, &key, RESTART_CMD, ssh_user).await?;
+    println!("  {}", restart.trim());
+    println!("\nSkill '{skill}' rolled back to {} on {ip}.", field("to"));
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clawmacdo_core::archive::{ZipMethod, ZipWriter};
+
+    fn zip_of(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Vec::new());
+        for (name, data) in entries {
+            zip.add_file(name, data, ZipMethod::Stored).unwrap();
+        }
+        zip.finish().unwrap()
+    }
+
+    #[test]
+    fn validate_skill_zip_accepts_skill_directories() {
+        let data = zip_of(&[
+            ("news/SKILL.md", b"---\nname: news\n---\n"),
+            ("news/scripts/run.sh", b"echo hi\n"),
+            ("__MACOSX/news/._SKILL.md", b"junk"),
+            ("weather/SKILL.md", b"# weather\n"),
+        ]);
+        let validated = validate_skill_zip(&data).unwrap();
+        assert_eq!(validated.skills, ["news", "weather"]);
+        assert_eq!(validated.files.len(), 3);
+        assert!(validated.files.contains_key("news/scripts/run.sh"));
+    }
+
+    #[test]
+    fn validate_skill_zip_rejects_bad_layouts() {
+        let root_file = zip_of(&[("SKILL.md", b"x")]);
+        assert!(validate_skill_zip(&root_file).is_err());
+        let missing = zip_of(&[("news/SKILL.md", b"x"), ("other/readme.md", b"x")]);
+        let err = validate_skill_zip(&missing).unwrap_err().to_string();
+        assert!(err.contains("other"), "{err}");
+        assert!(validate_skill_zip(b"PK\x03\x04 not really").is_err());
+    }
+
+    #[test]
+    fn validate_skill_zip_rejects_zip_slip() {
+        // The writer refuses unsafe names, so patch a same-length placeholder.
+        let mut data = zip_of(&[("news/SKILL.md", b"x"), ("xx/xxxx.sh", b"x")]);
+        for _ in 0..2 {
+            let at = data.windows(10).position(|w| w == b"xx/xxxx.sh").unwrap();
+            data[at..at + 10].copy_from_slice(b"../evil.sh");
+        }
+        let err = validate_skill_zip(&data).unwrap_err().to_string();
+        assert!(err.contains("Unsafe"), "{err}");
+    }
+}
@@ -41,8 +41,28 @@ fn ssh_user_for_provider(provider: &Option<String>) -> &'static str {
     }
 }
 
-/// Collect local files from a skill directory: path relative to skill root → (abs_path, md5).
-fn local_checksums(skill_dir: &Path) -> Result<Vec<(String, String)>> {
+/// sha256 of a file's contents, as reported by `sha256sum` on the instance.
+pub(crate) fn file_sha256(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+/// Digest of a whole skill: sha256 over `path\0sha256\n` lines in path order.
+/// Matches `skillDigest()` in the remote skill package scripts.
+pub(crate) fn skill_digest(checksums: &[(String, String)]) -> String {
+    let mut sorted: Vec<&(String, String)> = checksums.iter().collect();
+    sorted.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut hasher = Sha256::new();
+    for (path, sha256) in sorted {
+        hasher.update(path.as_bytes());
+        hasher.update([0]);
+        hasher.update(sha256.as_bytes());
+        hasher.update(b"\n");
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Collect local files from a skill directory: path relative to skill root → sha256.
+pub(crate) fn local_checksums(skill_dir: &Path) -> Result<Vec<(String, String)>> {
     let mut out = Vec::new();
     collect_files(skill_dir, skill_dir, &mut out)?;
     out.sort_by(|a, b| a.0.cmp(&b.0));
@@ -62,15 +82,14 @@ fn collect_files(root: &Path, dir: &Path, out: &mut Vec<(String, String)>) -> Re
                 .to_string_lossy()
                 .replace('\\', "/");
             let bytes = std::fs::read(&path)?;
-            let digest = hex::encode(Sha256::digest(&bytes));
-            out.push((rel, digest));
+            out.push((rel, file_sha256(&bytes)));
         }
     }
     Ok(())
 }
 
 /// Fetch remote checksums for a named skill on an OpenClaw instance.
-/// Returns Vec<(relative_path, md5_hex)> sorted by path.
+/// Returns Vec<(relative_path, sha256_hex)> sorted by path.
 async fn remote_checksums(
     ip: &str,
     key: &Path,
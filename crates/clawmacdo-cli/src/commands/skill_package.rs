@@ -0,0 +1,719 @@
+use super::skill_deploy::ValidatedSkillZip;
+use super::skill_diff::{file_sha256, skill_digest};
+use super::wiki::{handle_remote_status, js_string, remote_json_value};
+use anyhow::{bail, Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use clawmacdo_core::config;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+
+/// Optional manifest a skill ships at the root of its directory.
+pub(crate) const MANIFEST_FILE: &str = "skill.json";
+/// Previous versions live outside `workspace/skills` so the gateway ignores them.
+pub(crate) const SKILL_VERSIONS_DIR: &str = "/home/openclaw/.openclaw/skill-versions";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct SkillRequirements {
+    #[serde(default)]
+    pub(crate) tools: Vec<String>,
+    #[serde(default)]
+    pub(crate) permissions: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct SkillFile {
+    pub(crate) path: String,
+    pub(crate) size: u64,
+    pub(crate) sha256: String,
+}
+
+/// Resolved manifest recorded with every installed version.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct SkillManifest {
+    pub(crate) name: String,
+    pub(crate) version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) description: Option<String>,
+    #[serde(default)]
+    pub(crate) requires: SkillRequirements,
+    pub(crate) files: Vec<SkillFile>,
+    /// `skill_digest` of `files`; identical digests mean identical content.
+    pub(crate) digest: String,
+}
+
+/// The author-written `skill.json`. Every field is optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SkillManifestSource {
+    name: Option<String>,
+    version: Option<String>,
+    description: Option<String>,
+    /// When present, must list exactly the files shipped with the skill.
+    files: Option<Vec<String>>,
+    #[serde(default)]
+    requires: SkillRequirements,
+}
+
+/// Files of one skill directory: (path relative to the skill, contents, mode).
+pub(crate) type SkillFiles = Vec<(String, Vec<u8>, u32)>;
+
+/// One skill directory ready to install: its manifest plus file contents.
+#[derive(Debug)]
+pub(crate) struct SkillPackage {
+    pub(crate) dir: String,
+    pub(crate) manifest: SkillManifest,
+    pub(crate) files: SkillFiles,
+}
+
+fn clean_version(value: &str) -> Result<String> {
+    let version = value.trim();
+    if version.is_empty()
+        || version.len() > 64
+        || version.starts_with('.')
+        || !version
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '+' | '-'))
+    {
+        bail!("Invalid skill version '{value}': use letters, digits, '.', '_', '+' or '-'");
+    }
+    Ok(version.to_string())
+}
+
+fn clean_requirement(kind: &str, value: &str) -> Result<String> {
+    let value = value.trim();
+    if value.is_empty()
+        || value.len() > 64
+        || !value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '+' | '-' | ':'))
+    {
+        bail!("Invalid required {kind} '{value}' in {MANIFEST_FILE}");
+    }
+    Ok(value.to_string())
+}
+
+/// Validate a `--skill` argument (a skill directory name).
+pub(crate) fn clean_skill_name(value: &str) -> Result<String> {
+    let name = value.trim();
+    if name.is_empty()
+        || name.starts_with('.')
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+    {
+        bail!("Invalid skill name '{value}': use letters, digits, '.', '_' or '-'");
+    }
+    Ok(name.to_string())
+}
+
+/// Top-level `key: value` fields of a SKILL.md YAML front-matter block.
+#[derive(Debug, Default)]
+pub(crate) struct FrontMatter {
+    /// (key, unquoted value, 1-based line number)
+    pub(crate) fields: Vec<(String, String, usize)>,
+    /// False when the opening `---` has no closing `---`.
+    pub(crate) closed: bool,
+    /// 0-based index of the first body line.
+    pub(crate) body_start: usize,
+}
+
+impl FrontMatter {
+    pub(crate) fn get(&self, key: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(k, _, _)| k == key)
+            .map(|(_, v, _)| v.as_str())
+            .filter(|v| !v.is_empty())
+    }
+}
+
+/// Parse the front-matter of a SKILL.md; `None` when it does not start with `---`.
+/// Indented (nested) lines are skipped; only top-level keys are recorded.
+pub(crate) fn parse_front_matter(text: &str) -> Option<FrontMatter> {
+    let mut lines = text.lines().enumerate();
+    if lines.next().map(|(_, l)| l.trim()) != Some("---") {
+        return None;
+    }
+    let mut front = FrontMatter::default();
+    for (index, line) in lines {
+        if line.trim() == "---" {
+            front.closed = true;
+            front.body_start = index + 1;
+            break;
+        }
+        if line.starts_with([' ', '\t', '-', '#']) {
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+            .unwrap_or(value);
+        front
+            .fields
+            .push((key.trim().to_string(), value.trim().to_string(), index + 1));
+    }
+    Some(front)
+}
+
+/// Build the package for one skill directory.
+///
+/// Name and version come from `skill.json`, then the SKILL.md front-matter,
+/// then the directory name and a content-derived `0.0.0+<digest>` version.
+pub(crate) fn build_package(dir: &str, files: SkillFiles) -> Result<SkillPackage> {
+    let find = |name: &str| files.iter().find(|(path, _, _)| path == name);
+    let source: SkillManifestSource = match find(MANIFEST_FILE) {
+        Some((_, data, _)) => serde_json::from_slice(data)
+            .with_context(|| format!("Failed to parse {dir}/{MANIFEST_FILE}"))?,
+        None => SkillManifestSource::default(),
+    };
+    let front = find("SKILL.md")
+        .and_then(|(_, data, _)| parse_front_matter(&String::from_utf8_lossy(data)))
+        .unwrap_or_default();
+    let md_name = front.get("name").map(str::to_string);
+    let md_version = front.get("version").map(str::to_string);
+
+    let checksums: Vec<(String, String)> = files
+        .iter()
+        .map(|(path, data, _)| (path.clone(), file_sha256(data)))
+        .collect();
+    let digest = skill_digest(&checksums);
+
+    if let Some(listed) = &source.files {
+        let shipped: std::collections::BTreeSet<&str> = files
+            .iter()
+            .map(|(path, _, _)| path.as_str())
+            .filter(|path| *path != MANIFEST_FILE)
+            .collect();
+        let listed: std::collections::BTreeSet<&str> = listed.iter().map(String::as_str).collect();
+        if let Some(missing) = listed.difference(&shipped).next() {
+            bail!("{dir}/{MANIFEST_FILE} lists {missing}, which is not in the package");
+        }
+        if let Some(extra) = shipped.difference(&listed).next() {
+            bail!("{dir}/{extra} is not listed in {dir}/{MANIFEST_FILE}");
+        }
+    }
+
+    let version = match source.version.or(md_version) {
+        Some(version) => clean_version(&version).with_context(|| format!("Skill {dir}"))?,
+        None => format!("0.0.0+{}", &digest[..12]),
+    };
+    let requires = SkillRequirements {
+        tools: source
+            .requires
+            .tools
+            .iter()
+            .map(|t| clean_requirement("tool", t))
+            .collect::<Result<_>>()?,
+        permissions: source
+            .requires
+            .permissions
+            .iter()
+            .map(|p| clean_requirement("permission", p))
+            .collect::<Result<_>>()?,
+    };
+    let mut manifest_files: Vec<SkillFile> = files
+        .iter()
+        .zip(&checksums)
+        .map(|((path, data, _), (_, sha256))| SkillFile {
+            path: path.clone(),
+            size: data.len() as u64,
+            sha256: sha256.clone(),
+        })
+        .collect();
+    manifest_files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(SkillPackage {
+        dir: dir.to_string(),
+        manifest: SkillManifest {
+            name: source.name.or(md_name).unwrap_or_else(|| dir.to_string()),
+            version,
+            description: source.description,
+            requires,
+            files: manifest_files,
+            digest,
+        },
+        files,
+    })
+}
+
+/// Split a validated skill ZIP into one package per skill directory.
+pub(crate) fn packages_from_zip(validated: &ValidatedSkillZip) -> Result<Vec<SkillPackage>> {
+    validated
+        .by_skill()
+        .into_iter()
+        .map(|(dir, files)| build_package(&dir, files))
+        .collect()
+}
+
+/// Payload uploaded for `build_install_cmd`.
+fn install_payload(packages: &[SkillPackage]) -> Result<Vec<u8>> {
+    let skills: Vec<Value> = packages
+        .iter()
+        .map(|package| {
+            let files: Vec<Value> = package
+                .files
+                .iter()
+                .map(|(path, data, mode)| {
+                    serde_json::json!({
+                        "path": path,
+                        "mode": if mode & 0o111 != 0 { 0o755 } else { 0o644 },
+                        "data": BASE64.encode(data),
+                    })
+                })
+                .collect();
+            serde_json::json!({
+                "dir": package.dir,
+                "manifest": package.manifest,
+                "files": files,
+            })
+        })
+        .collect();
+    Ok(serde_json::to_vec(
+        &serde_json::json!({ "skills": skills }),
+    )?)
+}
+
+/// Shared Node helpers for the install, rollback and versions scripts.
+const SKILL_STORE_JS: &str = r#"const fs = require('fs');
+const path = require('path');
+const crypto = require('crypto');
+const { execFileSync } = require('child_process');
+
+const home = process.env.HOME || '__HOME__';
+const skillsDir = path.join(home, '.openclaw', 'workspace', 'skills');
+const store = path.join(home, '.openclaw', 'skill-versions');
+
+function fail(code, message, extra = {}) {
+  console.log(JSON.stringify({ ok: false, error: { code, message }, ...extra }));
+  process.exit(0);
+}
+
+function readJson(file, fallback) {
+  try {
+    return JSON.parse(fs.readFileSync(file, 'utf8'));
+  } catch (_) {
+    return fallback;
+  }
+}
+
+function writeJson(file, value) {
+  const tmp = `${file}.tmp-${process.pid}`;
+  fs.writeFileSync(tmp, JSON.stringify(value, null, 2) + '\n', { mode: 0o644 });
+  fs.renameSync(tmp, file);
+}
+
+function stamp() {
+  return new Date().toISOString().replace(/[-:]/g, '').replace(/\..*$/, '').replace('T', '-');
+}
+
+function listFiles(dir, base = dir) {
+  const out = [];
+  for (const entry of fs.readdirSync(dir, { withFileTypes: true })) {
+    const full = path.join(dir, entry.name);
+    if (entry.isSymbolicLink()) continue;
+    if (entry.isDirectory()) out.push(...listFiles(full, base));
+    else if (entry.isFile()) out.push(path.relative(base, full).split(path.sep).join('/'));
+  }
+  return out.sort();
+}
+
+function fileEntries(dir) {
+  return listFiles(dir).map((rel) => {
+    const data = fs.readFileSync(path.join(dir, rel));
+    return { path: rel, size: data.length, sha256: crypto.createHash('sha256').update(data).digest('hex') };
+  });
+}
+
+// Same formula as skill_digest() in skill_diff.rs.
+function skillDigest(files) {
+  const hash = crypto.createHash('sha256');
+  for (const file of [...files].sort((a, b) => (a.path < b.path ? -1 : a.path > b.path ? 1 : 0))) {
+    hash.update(file.path);
+    hash.update(Buffer.from([0]));
+    hash.update(file.sha256);
+    hash.update('\n');
+  }
+  return hash.digest('hex');
+}
+
+function statePath(dir) {
+  return path.join(store, dir, 'installed.json');
+}
+
+function loadState(dir) {
+  return readJson(statePath(dir), null);
+}
+
+function versionDir(dir, version) {
+  return path.join(store, dir, 'versions', version);
+}
+
+function versionManifest(dir, version) {
+  return readJson(path.join(versionDir(dir, version), 'manifest.json'), null);
+}
+
+function recordHistory(state, version, action) {
+  state.current = version;
+  state.history = Array.isArray(state.history) ? state.history : [];
+  state.history.push({ version, action, at: new Date().toISOString() });
+}
+
+// Keep a copy of the active directory when it is not a recorded version
+// (first versioned deploy, or files edited in place) so rollback can reach it.
+function snapshotActive(dir, state, incomingDigest = null) {
+  const active = path.join(skillsDir, dir);
+  if (!fs.existsSync(active)) return null;
+  const files = fileEntries(active);
+  const digest = skillDigest(files);
+  if (digest === incomingDigest) return null;
+  const current = state.current ? versionManifest(dir, state.current) : null;
+  if (current && current.digest === digest) return null;
+  const version = `local-${stamp()}`;
+  const target = versionDir(dir, version);
+  fs.mkdirSync(path.dirname(target), { recursive: true });
+  fs.cpSync(active, path.join(target, 'files'), { recursive: true });
+  writeJson(path.join(target, 'manifest.json'), {
+    name: state.name || dir,
+    version,
+    requires: { tools: [], permissions: [] },
+    files,
+    digest,
+    snapshot: true
+  });
+  recordHistory(state, version, 'snapshot');
+  return version;
+}
+
+// Copy a stored version into the skills directory and swap it in with renames.
+function activate(dir, version) {
+  const active = path.join(skillsDir, dir);
+  const next = path.join(skillsDir, `.${dir}.clawmacdo-next`);
+  const prev = path.join(skillsDir, `.${dir}.clawmacdo-prev`);
+  fs.mkdirSync(skillsDir, { recursive: true });
+  fs.rmSync(next, { recursive: true, force: true });
+  fs.rmSync(prev, { recursive: true, force: true });
+  fs.cpSync(path.join(versionDir(dir, version), 'files'), next, { recursive: true });
+  const hadActive = fs.existsSync(active);
+  if (hadActive) fs.renameSync(active, prev);
+  try {
+    fs.renameSync(next, active);
+  } catch (error) {
+    if (hadActive) fs.renameSync(prev, active);
+    throw error;
+  }
+  fs.rmSync(prev, { recursive: true, force: true });
+}
+
+function prune(dir, state, keep) {
+  const root = path.join(store, dir, 'versions');
+  if (!fs.existsSync(root)) return [];
+  const order = [];
+  for (const entry of [...(state.history || [])].reverse()) {
+    if (entry.version !== state.current && !order.includes(entry.version)) order.push(entry.version);
+  }
+  const retained = new Set([state.current, ...order.slice(0, keep)]);
+  const removed = [];
+  for (const version of fs.readdirSync(root)) {
+    if (retained.has(version)) continue;
+    fs.rmSync(path.join(root, version), { recursive: true, force: true });
+    removed.push(version);
+  }
+  state.history = (state.history || []).filter((entry) => retained.has(entry.version));
+  return removed;
+}
+
+function missingTools(tools) {
+  return (tools || []).filter((tool) => {
+    try {
+      execFileSync('sh', ['-c', 'command -v "$1"', 'sh', tool], { stdio: 'ignore' });
+      return false;
+    } catch (_) {
+      return true;
+    }
+  });
+}
+"#;
+
+fn node_script(body: &str) -> String {
+    let home = config::OPENCLAW_HOME;
+    let script = format!(
+        "set -e\nexport HOME=\"__HOME__\"\nexport PATH=\"__HOME__/.local/bin:__HOME__/.local/share/pnpm:/usr/local/bin:/usr/bin:/bin:$PATH\"\n\nnode <<'NODE'\n{SKILL_STORE_JS}\n{body}NODE\n"
+    );
+    script.replace("__HOME__", home)
+}
+
+/// Install every skill in the uploaded payload as a new stored version and
+/// make it active. Re-deploying identical content re-activates the stored
+/// version; different content under an existing version needs `force`.
+pub(crate) fn build_install_cmd(upload_tmp: &str, keep: usize, force: bool) -> Result<String> {
+    let body = r#"const uploadTmp = __UPLOAD_TMP_JSON__;
+const keep = __KEEP__;
+const force = __FORCE__;
+
+let payload;
+try {
+  payload = JSON.parse(fs.readFileSync(uploadTmp, 'utf8'));
+} catch (error) {
+  fail('bad_payload', `Failed to read uploaded skills: ${error && error.message || error}`);
+} finally {
+  fs.rmSync(uploadTmp, { force: true });
+}
+
+const results = [];
+for (const skill of payload.skills || []) {
+  const { dir, manifest } = skill;
+  const state = loadState(dir) || { name: manifest.name, current: null, history: [] };
+  const previous = state.current;
+  const snapshot = snapshotActive(dir, state, manifest.digest);
+  const target = versionDir(dir, manifest.version);
+  const existing = versionManifest(dir, manifest.version);
+  if (existing && existing.digest !== manifest.digest && !force) {
+    fail('version_conflict', `${dir} ${manifest.version} is already installed with different content; bump the version or pass --force`, { skills: results });
+  }
+  if (!existing || existing.digest !== manifest.digest) {
+    const staging = `${target}.clawmacdo-staging-${process.pid}`;
+    fs.rmSync(staging, { recursive: true, force: true });
+    const filesDir = path.join(staging, 'files');
+    for (const file of skill.files) {
+      const dest = path.resolve(filesDir, file.path);
+      if (!dest.startsWith(filesDir + path.sep)) fail('unsafe_path', `Unsafe path ${dir}/${file.path}`);
+      fs.mkdirSync(path.dirname(dest), { recursive: true });
+      fs.writeFileSync(dest, Buffer.from(file.data, 'base64'), { mode: file.mode });
+      fs.chmodSync(dest, file.mode);
+    }
+    writeJson(path.join(staging, 'manifest.json'), manifest);
+    fs.rmSync(target, { recursive: true, force: true });
+    fs.mkdirSync(path.dirname(target), { recursive: true });
+    fs.renameSync(staging, target);
+  }
+  try {
+    activate(dir, manifest.version);
+  } catch (error) {
+    fail('activate_failed', `Failed to activate ${dir} ${manifest.version}: ${error && error.message || error}`, { skills: results });
+  }
+  state.name = manifest.name;
+  recordHistory(state, manifest.version, 'install');
+  const pruned = prune(dir, state, keep);
+  writeJson(statePath(dir), state);
+  results.push({
+    dir,
+    name: manifest.name,
+    version: manifest.version,
+    previous: snapshot || previous,
+    unchanged: previous === manifest.version && !snapshot,
+    missing_tools: missingTools(manifest.requires && manifest.requires.tools),
+    pruned
+  });
+}
+
+console.log(JSON.stringify({ ok: true, skills: results }));
+"#
+    .replace("__UPLOAD_TMP_JSON__", &js_string(upload_tmp)?)
+    .replace("__KEEP__", &keep.to_string())
+    .replace("__FORCE__", if force { "true" } else { "false" });
+    Ok(node_script(&body))
+}
+
+/// Re-activate a stored version of `skill` (the previous one by default).
+pub(crate) fn build_rollback_cmd(skill: &str, to: Option<&str>) -> Result<String> {
+    let body = r#"const dir = __SKILL_JSON__;
+const requested = __TO_JSON__;
+
+const state = loadState(dir);
+if (!state || !state.current) {
+  fail('not_versioned', `${dir} has no recorded versions; deploy it with skill-deploy first`);
+}
+const from = state.current;
+const snapshot = snapshotActive(dir, state);
+let target = requested;
+if (!target) {
+  const seen = new Set([state.current, from]);
+  for (const entry of [...state.history].reverse()) {
+    if (!seen.has(entry.version) && versionManifest(dir, entry.version)) {
+      target = entry.version;
+      break;
+    }
+  }
+}
+if (!target) fail('no_previous_version', `${dir} has no earlier version to roll back to`);
+if (!versionManifest(dir, target)) fail('unknown_version', `${dir} ${target} is not stored on this instance`);
+try {
+  activate(dir, target);
+} catch (error) {
+  fail('activate_failed', `Failed to activate ${dir} ${target}: ${error && error.message || error}`);
+}
+recordHistory(state, target, 'rollback');
+writeJson(statePath(dir), state);
+console.log(JSON.stringify({ ok: true, dir, name: state.name || dir, from, to: target, snapshot }));
+"#
+    .replace("__SKILL_JSON__", &js_string(skill)?)
+    .replace(
+        "__TO_JSON__",
+        &match to {
+            Some(version) => js_string(&clean_version(version)?)?,
+            None => "null".to_string(),
+        },
+    );
+    Ok(node_script(&body))
+}
+
+/// Report stored versions for every skill, and whether the active copy has
+/// drifted from the version it was installed as.
+pub(crate) fn build_versions_cmd() -> String {
+    let body = r#"const dirs = new Set();
+for (const root of [skillsDir, store]) {
+  if (!fs.existsSync(root)) continue;
+  for (const entry of fs.readdirSync(root, { withFileTypes: true })) {
+    if (entry.isDirectory() && !entry.name.startsWith('.')) dirs.add(entry.name);
+  }
+}
+const skills = [...dirs].sort().map((dir) => {
+  const state = loadState(dir) || { current: null, history: [] };
+  const active = path.join(skillsDir, dir);
+  const isActive = fs.existsSync(active);
+  const current = state.current ? versionManifest(dir, state.current) : null;
+  const modified = isActive && current ? skillDigest(fileEntries(active)) !== current.digest : null;
+  const root = path.join(store, dir, 'versions');
+  const installedAt = {};
+  for (const entry of state.history || []) installedAt[entry.version] = entry.at;
+  const versions = fs.existsSync(root)
+    ? fs.readdirSync(root)
+        .filter((version) => !version.includes('.clawmacdo-staging-'))
+        .map((version) => {
+          const manifest = versionManifest(dir, version) || {};
+          return {
+            version,
+            installed_at: installedAt[version] || null,
+            file_count: (manifest.files || []).length,
+            requires: manifest.requires || { tools: [], permissions: [] },
+            snapshot: !!manifest.snapshot
+          };
+        })
+        .sort((a, b) => String(a.installed_at).localeCompare(String(b.installed_at)))
+    : [];
+  return { dir, name: state.name || dir, active: isActive, current: state.current, modified, versions };
+});
+console.log(JSON.stringify({ ok: true, skills }));
+"#;
+    node_script(body)
+}
+
+/// Upload `packages` and install them in one SSH session, then run `after`
+/// (e.g. a gateway restart) on the same session. Returns the install result
+/// and the outputs of `after`.
+pub(crate) async fn install_packages(
+    ip: &str,
+    key: &Path,
+    ssh_user: &str,
+    packages: &[SkillPackage],
+    keep: usize,
+    force: bool,
+    after: &[String],
+) -> Result<(Value, Vec<String>)> {
+    let payload = install_payload(packages)?;
+    let remote_tmp = format!("/tmp/clawmacdo-skills-{}.json", uuid::Uuid::new_v4());
+    let cmd = build_install_cmd(&remote_tmp, keep, force)?;
+    let ip = ip.to_string();
+    let key = key.to_path_buf();
+    let ssh_user = ssh_user.to_string();
+    let after = after.to_vec();
+    let mut outputs = tokio::task::spawn_blocking(move || {
+        let mut cmds: Vec<&str> = vec![&cmd];
+        cmds.extend(after.iter().map(String::as_str));
+        clawmacdo_ssh::scp_upload_bytes_and_exec_as(
+            &ip,
+            &key,
+            &payload,
+            &remote_tmp,
+            0o644,
+            &cmds,
+            &ssh_user,
+        )
+    })
+    .await
+    .context("skill install task failed")??;
+    let value = remote_json_value(
+        outputs.first().map(String::as_str).unwrap_or(""),
+        "skill install",
+    )?;
+    outputs.remove(0);
+    handle_remote_status(&value, false)?;
+    Ok((value, outputs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str, data: &str) -> (String, Vec<u8>, u32) {
+        (path.to_string(), data.as_bytes().to_vec(), 0o644)
+    }
+
+    #[test]
+    fn build_package_reads_manifest_and_front_matter() {
+        let package = build_package(
+            "news",
+            vec![
+                file(
+                    "SKILL.md",
+                    "---\nname: News Digest\nversion: 1.2.0\n---\n# News\n",
+                ),
+                file("scripts/run.sh", "echo hi\n"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(package.manifest.name, "News Digest");
+        assert_eq!(package.manifest.version, "1.2.0");
+        assert_eq!(package.manifest.files.len(), 2);
+        assert_eq!(package.manifest.files[1].path, "scripts/run.sh");
+
+        let manifest = r#"{"version":"2.0.0","files":["SKILL.md"],"requires":{"tools":["curl"],"permissions":["network"]}}"#;
+        let package = build_package(
+            "news",
+            vec![file("SKILL.md", "# News\n"), file(MANIFEST_FILE, manifest)],
+        )
+        .unwrap();
+        assert_eq!(package.manifest.name, "news");
+        assert_eq!(package.manifest.version, "2.0.0");
+        assert_eq!(package.manifest.requires.tools, ["curl"]);
+    }
+
+    #[test]
+    fn build_package_derives_version_and_checks_file_list() {
+        let a = build_package("x", vec![file("SKILL.md", "a")]).unwrap();
+        let b = build_package("x", vec![file("SKILL.md", "b")]).unwrap();
+        assert!(a.manifest.version.starts_with("0.0.0+"));
+        assert_ne!(a.manifest.version, b.manifest.version);
+        assert_ne!(a.manifest.digest, b.manifest.digest);
+
+        let listed = r#"{"files":["SKILL.md","missing.sh"]}"#;
+        assert!(build_package(
+            "x",
+            vec![file("SKILL.md", "a"), file(MANIFEST_FILE, listed)]
+        )
+        .is_err());
+        let bad = r#"{"version":"../1"}"#;
+        assert!(build_package("x", vec![file("SKILL.md", "a"), file(MANIFEST_FILE, bad)]).is_err());
+        let unknown = r#"{"verison":"1"}"#;
+        assert!(build_package("x", vec![file(MANIFEST_FILE, unknown)]).is_err());
+    }
+
+    #[test]
+    fn remote_scripts_share_the_version_store() {
+        let install = build_install_cmd("/tmp/skills.json", 3, false).unwrap();
+        assert!(install.contains("const keep = 3;"));
+        assert!(install.contains("version_conflict"));
+        assert!(install.contains("skill-versions"));
+        let rollback = build_rollback_cmd("news", Some("1.0.0")).unwrap();
+        assert!(rollback.contains("const requested = \"1.0.0\";"));
+        assert!(build_rollback_cmd("news", Some("../x")).is_err());
+        assert!(build_versions_cmd().contains("modified"));
+    }
+}
@@ -1,7 +1,10 @@
+use super::skill_package;
+use super::wiki::{handle_remote_status, remote_json_value};
 use anyhow::{bail, Result};
 use clawmacdo_core::config;
 use clawmacdo_provision::provision::commands::ssh_as_openclaw_with_user_async;
-use std::path::PathBuf;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
 
 const REMOTE_SKILLS_DIR: &str = "/home/openclaw/.openclaw/workspace/skills";
 
@@ -89,9 +92,15 @@ pub async fn remove(query: &str, skill_name: &str) -> Result<()> {
 }
 
 /// List all skill directories currently deployed on an instance.
-pub async fn list(query: &str) -> Result<()> {
+///
+/// With `versions`, list the stored versions of each skill instead (see
+/// `skill_package`), marking the active one and any local edits.
+pub async fn list(query: &str, versions: bool) -> Result<()> {
     let (ip, key, provider) = find_deploy_record(query)?;
     let ssh_user = ssh_user_for_provider(&provider);
+    if versions {
+        return list_versions(&ip, &key, ssh_user).await;
+    }
     let home = config::OPENCLAW_HOME;
 
     let cmd = format!(
@@ -153,6 +162,66 @@ pub async fn list(query: &str) -> Result<()> {
     Ok(())
 }
 
+async fn list_versions(ip: &str, key: &Path, ssh_user: &str) -> Result<()> {
+    let cmd = skill_package::build_versions_cmd();
+    let output = ssh_as_openclaw_with_user_async(ip, key, &cmd, ssh_user).await?;
+    let value = remote_json_value(&output, "skill versions")?;
+    handle_remote_status(&value, false)?;
+
+    println!("Skill versions on {ip}:");
+    println!("  Store: {}", skill_package::SKILL_VERSIONS_DIR);
+    println!();
+    let skills = value
+        .get("skills")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+    if skills.is_empty() {
+        println!("  (no skills deployed)");
+        return Ok(());
+    }
+    for skill in &skills {
+        let dir = skill.get("dir").and_then(Value::as_str).unwrap_or("");
+        let current = skill.get("current").and_then(Value::as_str);
+        let state = match (
+            skill.get("active").and_then(Value::as_bool),
+            skill.get("modified").and_then(Value::as_bool),
+        ) {
+            (Some(false), _) => " [not active]",
+            (_, Some(true)) => " [modified since install]",
+            _ => "",
+        };
+        match current {
+            Some(current) => println!("  • {dir}  current: {current}{state}"),
+            None => println!("  • {dir}  (unversioned; redeploy with skill-deploy){state}"),
+        }
+        for version in skill
+            .get("versions")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            let name = version.get("version").and_then(Value::as_str).unwrap_or("");
+            let marker = if Some(name) == current { "*" } else { " " };
+            let installed = version
+                .get("installed_at")
+                .and_then(Value::as_str)
+                .unwrap_or("-");
+            let files = version
+                .get("file_count")
+                .and_then(Value::as_u64)
+                .unwrap_or(0);
+            let snapshot = if version.get("snapshot").and_then(Value::as_bool) == Some(true) {
+                "  (snapshot of local files)"
+            } else {
+                ""
+            };
+            println!("      {marker} {name:<24} {installed:<26} {files} file(s){snapshot}");
+        }
+    }
+    Ok(())
+}
+
 /// Check file ownership and permissions for a deployed skill.
 ///
 /// Reports any files that are not owned by openclaw or have wrong permissions,
@@ -0,0 +1,236 @@
+use super::fleet::{self, BatchOptions, FleetTarget, Selector};
+use super::skill_deploy::{lint_before_deploy, perms_cmd, read_skill_zip};
+use super::skill_package::{self, SkillPackage};
+use super::wiki::{handle_remote_status, print_json, remote_json_value};
+use anyhow::{bail, Result};
+use clawmacdo_provision::provision::commands::ssh_as_openclaw_with_user_async;
+use serde_json::Value;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// What `skill-rollout` does on each instance.
+pub enum RolloutAction {
+    /// Install the skills from a ZIP as new versions.
+    Deploy {
+        file: PathBuf,
+        keep: usize,
+        force: bool,
+        lint: bool,
+    },
+    /// Re-activate a stored version of one skill (the previous one by default).
+    Rollback { skill: String, to: Option<String> },
+}
+
+pub struct SkillRolloutParams {
+    pub selector: Selector,
+    pub action: RolloutAction,
+    pub canary: usize,
+    pub batch_size: usize,
+    pub concurrency: usize,
+    pub max_failures: usize,
+    pub dry_run: bool,
+    pub json: bool,
+}
+
+/// One-line summary of an install result: `news 1.0.0 → 1.1.0, notes 2.0.0 (unchanged)`.
+fn install_detail(value: &Value) -> String {
+    let mut parts = Vec::new();
+    for skill in value
+        .get("skills")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let field = |name: &str| skill.get(name).and_then(Value::as_str);
+        let dir = field("dir").unwrap_or("");
+        let version = field("version").unwrap_or("");
+        let mut part = match field("previous") {
+            _ if skill.get("unchanged").and_then(Value::as_bool) == Some(true) => {
+                format!("{dir} {version} (unchanged)")
+            }
+            Some(previous) => format!("{dir} {previous} → {version}"),
+            None => format!("{dir} {version}"),
+        };
+        let missing: Vec<&str> = skill
+            .get("missing_tools")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .collect();
+        if !missing.is_empty() {
+            part.push_str(&format!(" [missing: {}]", missing.join(" ")));
+        }
+        parts.push(part);
+    }
+    parts.join(", ")
+}
+
+async fn deploy_one(
+    target: FleetTarget,
+    packages: Arc<Vec<SkillPackage>>,
+    keep: usize,
+    force: bool,
+) -> Result<String> {
+    let (value, _) = skill_package::install_packages(
+        &target.ip,
+        &target.key,
+        target.ssh_user(),
+        &packages,
+        keep,
+        force,
+        &[perms_cmd(), fleet::gateway_health_cmd(true)],
+    )
+    .await?;
+    Ok(format!("{}; gateway healthy", install_detail(&value)))
+}
+
+async fn rollback_one(
+    target: FleetTarget,
+    skill: Arc<String>,
+    to: Option<String>,
+) -> Result<String> {
+    let cmd = skill_package::build_rollback_cmd(&skill, to.as_deref())?;
+    let output =
+        ssh_as_openclaw_with_user_async(&target.ip, &target.key, &cmd, target.ssh_user()).await?;
+    let value = remote_json_value(&output, "skill rollback")?;
+    handle_remote_status(&value, false)?;
+    let field = |name: &str| value.get(name).and_then(Value::as_str).unwrap_or("");
+    let detail = format!("{} {} → {}", field("dir"), field("from"), field("to"));
+    ssh_as_openclaw_with_user_async(
+        &target.ip,
+        &target.key,
+        &fleet::gateway_health_cmd(true),
+        target.ssh_user(),
+    )
+    .await?;
+    Ok(format!("{detail}; gateway healthy"))
+}
+
+/// `skill-rollout`: deploy a skill ZIP (or roll a skill back) across the
+/// instances matched by a selector — canary first, then batches with bounded
+/// concurrency — verifying gateway health on each instance and halting once
+/// failures exceed the threshold.
+pub async fn run(params: SkillRolloutParams) -> Result<()> {
+    let targets = fleet::resolve(&params.selector)?;
+    let options = BatchOptions {
+        canary: params.canary,
+        batch_size: params.batch_size,
+        concurrency: params.concurrency,
+        max_failures: params.max_failures,
+        quiet: params.json,
+    };
+    let batches = fleet::plan_batches(targets.len(), options.canary, options.batch_size);
+
+    let (what, packages) = match &params.action {
+        RolloutAction::Deploy { file, lint, .. } => {
+            let validated = read_skill_zip(file)?;
+            if *lint {
+                lint_before_deploy(&validated, file, params.json)?;
+            }
+            let packages = skill_package::packages_from_zip(&validated)?;
+            let names: Vec<String> = packages
+                .iter()
+                .map(|p| format!("{} {}", p.dir, p.manifest.version))
+                .collect();
+            (format!("Deploying {}", names.join(", ")), packages)
+        }
+        RolloutAction::Rollback { skill, to } => {
+            skill_package::clean_skill_name(skill)?;
+            let target = to.as_deref().unwrap_or("the previous version");
+            (format!("Rolling back {skill} to {target}"), Vec::new())
+        }
+    };
+
+    if !params.json {
+        println!(
+            "{what} on {} instance(s) in {} batch(es) (canary {}, batch size {}, concurrency {}, max failures {})",
+            targets.len(),
+            batches.len(),
+            options.canary.min(targets.len()),
+            options.batch_size,
+            options.concurrency,
+            options.max_failures
+        );
+    }
+    if params.dry_run {
+        if params.json {
+            print_json(&serde_json::json!({
+                "ok": true,
+                "dry_run": true,
+                "batches": batches,
+                "targets": targets,
+            }))?;
+        } else {
+            let mut remaining = targets.iter();
+            for (index, size) in batches.iter().enumerate() {
+                let hosts: Vec<&str> = remaining
+                    .by_ref()
+                    .take(*size)
+                    .map(|t| t.hostname.as_str())
+                    .collect();
+                println!("  batch {}: {}", index + 1, hosts.join(", "));
+            }
+            println!("\nDry run: nothing was changed.");
+        }
+        return Ok(());
+    }
+
+    let summary = match params.action {
+        RolloutAction::Deploy { keep, force, .. } => {
+            let packages = Arc::new(packages);
+            fleet::run_batches(targets, &options, move |target| {
+                deploy_one(target, Arc::clone(&packages), keep, force)
+            })
+            .await
+        }
+        RolloutAction::Rollback { skill, to } => {
+            let skill = Arc::new(skill_package::clean_skill_name(&skill)?);
+            fleet::run_batches(targets, &options, move |target| {
+                rollback_one(target, Arc::clone(&skill), to.clone())
+            })
+            .await
+        }
+    };
+
+    if params.json {
+        print_json(&serde_json::to_value(&summary)?)?;
+    } else {
+        summary.print_table();
+        let failed = summary.failed_instances();
+        if !failed.is_empty() {
+            println!(
+                "\n  Retry or roll back the failed instances with --instances {}",
+                failed.join(",")
+            );
+        }
+    }
+    if !summary.ok {
+        match &summary.halted {
+            Some(reason) => bail!("skill-rollout halted: {reason}"),
+            None => bail!("skill-rollout failed on {} instance(s)", summary.failed),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn install_detail_summarises_each_skill() {
+        let value = serde_json::json!({
+            "ok": true,
+            "skills": [
+                {"dir": "news", "version": "1.1.0", "previous": "1.0.0", "missing_tools": ["jq"]},
+                {"dir": "notes", "version": "2.0.0", "previous": "2.0.0", "unchanged": true},
+                {"dir": "new", "version": "0.1.0", "missing_tools": []}
+            ]
+        });
+        assert_eq!(
+            install_detail(&value),
+            "news 1.0.0 → 1.1.0 [missing: jq], notes 2.0.0 (unchanged), new 0.1.0"
+        );
+    }
+}
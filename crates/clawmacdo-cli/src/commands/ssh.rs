@@ -0,0 +1,239 @@
+use super::fleet::{self, BatchOptions, FleetTarget, Selector};
+use anyhow::{anyhow, bail, Context, Result};
+use console::{style, Term};
+
+/// Well-known services worth naming when a tunnel comes up.
+const KNOWN_PORTS: &[(u16, &str)] = &[(18789, "OpenClaw gateway"), (9119, "Hermes dashboard")];
+
+/// Parse a forward spec: `<port>`, `<local>:<remote>` or
+/// `<local>:<host>:<remote>`. The host is resolved on the instance and
+/// defaults to `127.0.0.1`.
+fn parse_forward(spec: &str) -> Result<(u16, String, u16)> {
+    let port = |s: &str| -> Result<u16> {
+        s.trim()
+            .parse()
+            .with_context(|| format!("Invalid port '{s}' in '{spec}'"))
+    };
+    let parts: Vec<&str> = spec.split(':').collect();
+    let (local, host, remote) = match parts.as_slice() {
+        [remote] => (port(remote)?, "127.0.0.1", port(remote)?),
+        [local, remote] => (port(local)?, "127.0.0.1", port(remote)?),
+        [local, host, remote] if !host.trim().is_empty() => {
+            (port(local)?, host.trim(), port(remote)?)
+        }
+        _ => {
+            bail!("Invalid forward '{spec}' (expected <local>:<remote> or <local>:<host>:<remote>)")
+        }
+    };
+    if remote == 0 {
+        bail!("Remote port in '{spec}' must not be 0");
+    }
+    Ok((local, host.to_string(), remote))
+}
+
+/// Puts the local terminal in raw mode for an interactive session and
+/// restores it on drop. A no-op when stdin is not a terminal.
+struct RawMode {
+    #[cfg(unix)]
+    saved: Option<libc::termios>,
+}
+
+impl RawMode {
+    #[cfg(unix)]
+    fn enable() -> Self {
+        // SAFETY: plain termios calls on fd 0 with a zero-initialised struct.
+        unsafe {
+            let mut termios: libc::termios = std::mem::zeroed();
+            if libc::isatty(libc::STDIN_FILENO) != 1
+                || libc::tcgetattr(libc::STDIN_FILENO, &mut termios) != 0
+            {
+                return Self { saved: None };
+            }
+            let saved = termios;
+            libc::cfmakeraw(&mut termios);
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios);
+            Self { saved: Some(saved) }
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn enable() -> Self {
+        Self {}
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Some(saved) = self.saved {
+            // SAFETY: restores the attributes read in `enable`.
+            unsafe {
+                libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &saved);
+            }
+        }
+    }
+}
+
+fn window_size() -> (u32, u32) {
+    let (rows, cols) = Term::stdout().size();
+    (u32::from(cols), u32::from(rows))
+}
+
+/// `ssh <instance> [-t] [-- command...]`: an interactive shell, or one
+/// command with its output streamed and its exit status passed through.
+pub async fn shell(query: &str, tty: bool, print: bool, command: Vec<String>) -> Result<()> {
+    let target = fleet::find(query)?;
+    let user = target.ssh_user();
+    let command = (!command.is_empty()).then(|| command.join(" "));
+    if print {
+        let tty = if tty { " -t" } else { "" };
+        let command = command
+            .map(|c| format!(" '{}'", c.replace('\'', "'\\''")))
+            .unwrap_or_default();
+        println!(
+            "ssh -i {}{tty} {user}@{}{command}",
+            target.key.display(),
+            target.ip
+        );
+        return Ok(());
+    }
+
+    let FleetTarget { ip, key, .. } = target;
+    let status = match command {
+        Some(command) if !tty => {
+            tokio::task::spawn_blocking(move || {
+                clawmacdo_ssh::exec_forward_as(&ip, &key, &command, user)
+            })
+            .await??
+        }
+        command => {
+            let term = std::env::var("TERM").unwrap_or_else(|_| "xterm-256color".into());
+            tokio::task::spawn_blocking(move || {
+                let _raw = RawMode::enable();
+                clawmacdo_ssh::shell_as(&ip, &key, user, command.as_deref(), &term, &window_size)
+            })
+            .await??
+        }
+    };
+    if status != 0 {
+        std::process::exit(status);
+    }
+    Ok(())
+}
+
+/// `exec --selector ... -- command...`: run one command on every selected
+/// instance in parallel and print each instance's output in turn.
+pub async fn exec(
+    selector: &Selector,
+    concurrency: usize,
+    json: bool,
+    command: Vec<String>,
+) -> Result<()> {
+    let command = command.join(" ");
+    if command.trim().is_empty() {
+        bail!("Give the command to run after `--`");
+    }
+    let targets = fleet::resolve(selector)?;
+    let options = BatchOptions {
+        canary: 0,
+        batch_size: targets.len(),
+        concurrency,
+        max_failures: usize::MAX,
+        quiet: true,
+    };
+    if !json {
+        println!("Running `{command}` on {} instance(s)...", targets.len());
+    }
+    let summary = fleet::run_batches(targets, &options, move |target| {
+        let command = command.clone();
+        async move {
+            let user = target.ssh_user();
+            let (status, output) = tokio::task::spawn_blocking(move || {
+                clawmacdo_ssh::exec_status_as(&target.ip, &target.key, &command, user)
+            })
+            .await??;
+            let output = output.trim_end().to_string();
+            if status == 0 {
+                Ok(output)
+            } else {
+                Err(anyhow!("exit status {status}\n{output}"))
+            }
+        }
+    })
+    .await;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+    } else {
+        for result in &summary.results {
+            let mark = match result.status {
+                fleet::TargetStatus::Ok => style("ok").green(),
+                _ => style("failed").red(),
+            };
+            println!(
+                "\n==> {} ({}) {mark} {:.1}s",
+                result.hostname, result.ip, result.seconds
+            );
+            if !result.detail.is_empty() {
+                println!("{}", result.detail);
+            }
+        }
+        println!("\n{} ok, {} failed", summary.succeeded, summary.failed);
+    }
+    if !summary.ok {
+        bail!("command failed on {} instance(s)", summary.failed);
+    }
+    Ok(())
+}
+
+/// `tunnel <instance> <local>:<remote>`: forward a local port to a port on
+/// the instance until interrupted.
+pub async fn tunnel(query: &str, spec: &str, bind: &str) -> Result<()> {
+    let (local, host, remote) = parse_forward(spec)?;
+    let target = fleet::find(query)?;
+    let listener = std::net::TcpListener::bind((bind, local))
+        .with_context(|| format!("Failed to listen on {bind}:{local}"))?;
+    let addr = listener.local_addr()?;
+    println!(
+        "Forwarding {addr} -> {host}:{remote} on {} ({}). Press Ctrl-C to stop.",
+        target.hostname, target.ip
+    );
+    if let Some((_, name)) = KNOWN_PORTS.iter().find(|(port, _)| *port == remote) {
+        println!("  {name}: http://{addr}/");
+    }
+
+    let user = target.ssh_user();
+    let FleetTarget { ip, key, .. } = target;
+    tokio::task::spawn_blocking(move || {
+        clawmacdo_ssh::forward_local_as(&ip, &key, user, listener, &host, remote, &|peer| {
+            println!("  connection from {peer}")
+        })
+    })
+    .await??;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forward_specs_are_parsed() {
+        assert_eq!(
+            parse_forward("9119").unwrap(),
+            (9119, "127.0.0.1".into(), 9119)
+        );
+        assert_eq!(
+            parse_forward("8080:18789").unwrap(),
+            (8080, "127.0.0.1".into(), 18789)
+        );
+        assert_eq!(
+            parse_forward("0:db.internal:5432").unwrap(),
+            (0, "db.internal".into(), 5432)
+        );
+        assert!(parse_forward("8080:").is_err());
+        assert!(parse_forward("8080::5432").is_err());
+        assert!(parse_forward("8080:0").is_err());
+        assert!(parse_forward("a:b:c:d").is_err());
+    }
+}
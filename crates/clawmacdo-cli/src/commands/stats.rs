@@ -0,0 +1,155 @@
+use anyhow::Result;
+use clawmacdo_db as db;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Serialize)]
+struct ProviderSummary {
+    provider: String,
+    total: u64,
+    completed: u64,
+    failed: u64,
+    other: u64,
+    success_rate: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+struct StepSummary {
+    step_number: i32,
+    label: String,
+    samples: usize,
+    mean_secs: f64,
+    p50_secs: f64,
+    p95_secs: f64,
+    max_secs: f64,
+}
+
+#[derive(Debug, Serialize)]
+struct StatsReport {
+    providers: Vec<ProviderSummary>,
+    steps: Vec<StepSummary>,
+}
+
+/// Nearest-rank percentile over an ascending-sorted slice.
+fn percentile(sorted: &[f64], pct: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = ((pct / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+fn summarize_providers(rows: &[db::OutcomeCountRow]) -> Vec<ProviderSummary> {
+    let mut by_provider: BTreeMap<&str, ProviderSummary> = BTreeMap::new();
+    for row in rows {
+        let entry = by_provider
+            .entry(row.provider.as_str())
+            .or_insert_with(|| ProviderSummary {
+                provider: row.provider.clone(),
+                total: 0,
+                completed: 0,
+                failed: 0,
+                other: 0,
+                success_rate: None,
+            });
+        entry.total += row.count;
+        match row.status.as_str() {
+            "completed" => entry.completed += row.count,
+            "failed" => entry.failed += row.count,
+            _ => entry.other += row.count,
+        }
+    }
+    by_provider
+        .into_values()
+        .map(|mut summary| {
+            let finished = summary.completed + summary.failed;
+            if finished > 0 {
+                summary.success_rate = Some(summary.completed as f64 / finished as f64);
+            }
+            summary
+        })
+        .collect()
+}
+
+fn summarize_steps(rows: &[db::StepDurationRow]) -> Vec<StepSummary> {
+    let mut by_step: BTreeMap<i32, (String, Vec<f64>)> = BTreeMap::new();
+    for row in rows {
+        let entry = by_step
+            .entry(row.step_number)
+            .or_insert_with(|| (row.label.clone(), Vec::new()));
+        entry.1.push(row.seconds);
+    }
+    by_step
+        .into_iter()
+        .map(|(step_number, (label, mut samples))| {
+            samples.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+            let sum: f64 = samples.iter().sum();
+            StepSummary {
+                step_number,
+                label,
+                samples: samples.len(),
+                mean_secs: sum / samples.len() as f64,
+                p50_secs: percentile(&samples, 50.0),
+                p95_secs: percentile(&samples, 95.0),
+                max_secs: samples.last().copied().unwrap_or(0.0),
+            }
+        })
+        .collect()
+}
+
+/// Print deploy outcome counts and per-step duration statistics from the SQLite history.
+pub fn run(json: bool) -> Result<()> {
+    let conn = db::init_db()?;
+    let report = StatsReport {
+        providers: summarize_providers(&db::deployment_outcome_counts(&conn)?),
+        steps: summarize_steps(&db::completed_step_durations(&conn)?),
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    if report.providers.is_empty() {
+        println!("No deployments recorded yet.");
+        return Ok(());
+    }
+
+    println!("Deployments by provider:\n");
+    println!(
+        "  {:<18}  {:>6}  {:>9}  {:>6}  {:>6}  {:>8}",
+        "Provider", "Total", "Completed", "Failed", "Other", "Success"
+    );
+    println!("  {}", "-".repeat(66));
+    for p in &report.providers {
+        let rate = p
+            .success_rate
+            .map(|r| format!("{:.0}%", r * 100.0))
+            .unwrap_or_else(|| "-".into());
+        println!(
+            "  {:<18}  {:>6}  {:>9}  {:>6}  {:>6}  {:>8}",
+            p.provider, p.total, p.completed, p.failed, p.other, rate
+        );
+    }
+
+    if report.steps.is_empty() {
+        println!("\nNo completed deploy steps recorded yet.");
+        return Ok(());
+    }
+
+    println!("\nStep durations (completed steps):\n");
+    println!(
+        "  {:>4}  {:<40}  {:>7}  {:>8}  {:>8}  {:>8}",
+        "Step", "Label", "Samples", "p50", "p95", "Max"
+    );
+    println!("  {}", "-".repeat(84));
+    for s in &report.steps {
+        let label: String = s.label.chars().take(40).collect();
+        println!(
+            "  {:>4}  {:<40}  {:>7}  {:>7.1}s  {:>7.1}s  {:>7.1}s",
+            s.step_number, label, s.samples, s.p50_secs, s.p95_secs, s.max_secs
+        );
+    }
+
+    Ok(())
+}
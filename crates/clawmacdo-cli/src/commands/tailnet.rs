@@ -0,0 +1,328 @@
+use super::fleet::{self, FleetTarget};
+use anyhow::{Context, Result};
+use clawmacdo_cloud::tailscale_api::{self, Device, TailscaleClient};
+use clawmacdo_provision::provision::tailscale::{self as ts_provision, ServeMode};
+use serde::Serialize;
+
+/// Whether the Tailscale API is configured (`TAILSCALE_API_KEY`).
+pub(crate) fn api_configured() -> bool {
+    std::env::var(tailscale_api::API_KEY_ENV).is_ok_and(|v| !v.trim().is_empty())
+}
+
+fn client() -> Result<TailscaleClient> {
+    TailscaleClient::from_env()?.with_context(|| {
+        format!(
+            "Set {} to an API access token to manage the tailnet",
+            tailscale_api::API_KEY_ENV
+        )
+    })
+}
+
+/// Mint a single-use, ephemeral, pre-authorized auth key carrying the
+/// configured tags for a new node. `None` when the API is not configured.
+pub(crate) async fn mint_auth_key(hostname: &str) -> Result<Option<String>> {
+    let Some(client) = TailscaleClient::from_env()? else {
+        return Ok(None);
+    };
+    let key = client
+        .create_auth_key(&format!("clawmacdo {hostname}"), true)
+        .await
+        .context("Failed to mint a Tailscale auth key")?;
+    Ok(Some(key.key))
+}
+
+/// Remove the tailnet node of the destroyed instance `hostname`. Only devices
+/// carrying one of the configured tags are removed, so a personal machine
+/// that happens to share the name is never touched.
+pub(crate) async fn forget(hostname: &str) {
+    let client = match TailscaleClient::from_env() {
+        Ok(Some(client)) => client,
+        Ok(None) => return,
+        Err(e) => {
+            println!("warning: tailnet device for {hostname} not removed: {e}");
+            return;
+        }
+    };
+    let devices = match client.list_devices().await {
+        Ok(devices) => devices,
+        Err(e) => {
+            println!("warning: tailnet device for {hostname} not removed: {e}");
+            return;
+        }
+    };
+    for device in devices.iter().filter(|d| d.is_host(hostname)) {
+        if !is_managed(&client, device) {
+            println!(
+                "Tailnet device {} has none of the tags {}; left in place.",
+                device.name,
+                client.tags().join(", ")
+            );
+            continue;
+        }
+        match client.delete_device(&device.id).await {
+            Ok(()) => println!("Removed tailnet device {}.", device.name),
+            Err(e) => println!(
+                "warning: could not remove tailnet device {}: {e}",
+                device.name
+            ),
+        }
+    }
+}
+
+fn is_managed(client: &TailscaleClient, device: &Device) -> bool {
+    device.tags.iter().any(|t| client.tags().contains(t))
+}
+
+#[derive(Serialize)]
+struct DeviceRow<'a> {
+    #[serde(flatten)]
+    device: &'a Device,
+    deploy_id: Option<&'a str>,
+    deploy_hostname: Option<&'a str>,
+    managed: bool,
+}
+
+/// Pair each device with the deployment it belongs to, if any.
+fn match_devices<'a>(
+    devices: &'a [Device],
+    targets: &'a [FleetTarget],
+) -> Vec<(&'a Device, Option<&'a FleetTarget>)> {
+    devices
+        .iter()
+        .map(|d| (d, targets.iter().find(|t| d.is_host(&t.hostname))))
+        .collect()
+}
+
+/// `tailscale-devices [--prune] [--json]`: list tailnet devices matched
+/// against the deploy records. `--prune` removes devices carrying the
+/// configured tags that belong to no deployment.
+pub async fn devices(prune: bool, json: bool) -> Result<()> {
+    let client = client()?;
+    let devices = client.list_devices().await?;
+    let targets = fleet::load_targets().unwrap_or_default();
+    let matched = match_devices(&devices, &targets);
+    let missing: Vec<&FleetTarget> = targets
+        .iter()
+        .filter(|t| !devices.iter().any(|d| d.is_host(&t.hostname)))
+        .collect();
+
+    if json {
+        let rows: Vec<DeviceRow> = matched
+            .iter()
+            .map(|(device, target)| DeviceRow {
+                device,
+                deploy_id: target.map(|t| t.id.as_str()),
+                deploy_hostname: target.map(|t| t.hostname.as_str()),
+                managed: is_managed(&client, device),
+            })
+            .collect();
+        let missing: Vec<&str> = missing.iter().map(|t| t.hostname.as_str()).collect();
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "devices": rows,
+                "deployments_without_device": missing,
+            }))?
+        );
+    } else {
+        println!(
+            "{:<40}  {:<16}  {:<22}  DEPLOYMENT",
+            "DEVICE", "TAILNET IP", "LAST SEEN"
+        );
+        for (device, target) in &matched {
+            let deployment = match target {
+                Some(t) => format!("{} ({})", t.hostname, t.ip),
+                None if is_managed(&client, device) => "- (stale)".to_string(),
+                None => "-".to_string(),
+            };
+            println!(
+                "{:<40}  {:<16}  {:<22}  {deployment}",
+                device.name,
+                device.addresses.first().map(String::as_str).unwrap_or("-"),
+                device.last_seen.as_deref().unwrap_or("-"),
+            );
+        }
+        if !missing.is_empty() {
+            println!("\nDeployments with no tailnet device:");
+            for target in &missing {
+                println!("  {} ({})", target.hostname, target.ip);
+            }
+        }
+    }
+
+    if prune {
+        let stale: Vec<&Device> = matched
+            .iter()
+            .filter(|(d, t)| t.is_none() && is_managed(&client, d))
+            .map(|(d, _)| *d)
+            .collect();
+        if stale.is_empty() && !json {
+            println!("\nNo stale tagged devices to prune.");
+        }
+        for device in stale {
+            client
+                .delete_device(&device.id)
+                .await
+                .with_context(|| format!("Failed to remove {}", device.name))?;
+            if !json {
+                println!("Pruned {}", device.name);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `tailscale-acl [--funnel] [--apply]`: check that the policy lets the
+/// configured tags be used (and, with `--funnel`, use Funnel); `--apply`
+/// writes the missing entries.
+pub async fn acl(funnel: bool, apply: bool) -> Result<()> {
+    let client = client()?;
+    let mut policy = client.policy().await?;
+    let changes = tailscale_api::ensure_policy(&mut policy.document, client.tags(), funnel);
+    if changes.is_empty() {
+        println!(
+            "Tailnet policy already allows {}{}.",
+            client.tags().join(", "),
+            if funnel { " with Funnel" } else { "" }
+        );
+        return Ok(());
+    }
+    println!("Tailnet policy changes needed:");
+    for change in &changes {
+        println!("  - {change}");
+    }
+    if !apply {
+        println!("\nRe-run with --apply to update the policy.");
+        return Ok(());
+    }
+    client
+        .set_policy(&policy)
+        .await
+        .context("Failed to update the tailnet policy")?;
+    println!("\nPolicy updated. Comments in a HuJSON policy file are not preserved.");
+    Ok(())
+}
+
+/// `tailscale-serve --instance <q> --mode funnel|tailnet|off`: converge the
+/// instance's `tailscale serve` config on the wanted state.
+pub async fn serve(query: &str, mode: &str, port: u16, dry_run: bool) -> Result<()> {
+    let mode: ServeMode = mode.parse()?;
+    let target = fleet::find(query)?;
+    let (current, commands) = ts_provision::apply_serve(
+        &target.ip,
+        &target.key,
+        port,
+        mode,
+        dry_run,
+        target.ssh_user(),
+    )
+    .await?;
+    let now = match (&current.proxy, current.funnel) {
+        (None, _) if !current.extra => "off".to_string(),
+        (Some(proxy), true) => format!("funnel -> {proxy}"),
+        (Some(proxy), false) => format!("tailnet -> {proxy}"),
+        (None, _) => "custom".to_string(),
+    };
+    let extra = if current.extra {
+        " (plus other serve entries)"
+    } else {
+        ""
+    };
+    println!("{}: serve config is {now}{extra}", target.hostname);
+    if commands.is_empty() {
+        println!("Already {mode}; nothing to change.");
+        return Ok(());
+    }
+    if dry_run {
+        println!("Would run:");
+    } else {
+        println!("Applied:");
+    }
+    for command in &commands {
+        println!("  {command}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clawmacdo_provision::provision::tailscale::{parse_serve_status, serve_plan, ServeState};
+
+    #[test]
+    fn serve_plan_converges_from_any_state() {
+        let empty = parse_serve_status("No serve config\n").unwrap();
+        assert_eq!(empty, ServeState::default());
+        assert_eq!(
+            serve_plan(&empty, 18789, ServeMode::Funnel),
+            vec!["tailscale funnel --bg --https=443 http://127.0.0.1:18789"]
+        );
+        assert!(serve_plan(&empty, 18789, ServeMode::Off).is_empty());
+
+        let funnel = parse_serve_status(
+            r#"{"TCP":{"443":{"HTTPS":true}},
+                "Web":{"openclaw-1a2b.tail1234.ts.net:443":{"Handlers":{"/":{"Proxy":"http://127.0.0.1:18789"}}}},
+                "AllowFunnel":{"openclaw-1a2b.tail1234.ts.net:443":true}}"#,
+        )
+        .unwrap();
+        assert_eq!(funnel, ServeState::wanted(18789, ServeMode::Funnel));
+        assert!(serve_plan(&funnel, 18789, ServeMode::Funnel).is_empty());
+        assert_eq!(
+            serve_plan(&funnel, 18789, ServeMode::Tailnet),
+            vec![
+                "tailscale serve reset",
+                "tailscale serve --bg --https=443 http://127.0.0.1:18789"
+            ]
+        );
+
+        let extra = parse_serve_status(
+            r#"{"TCP":{"443":{"HTTPS":true},"8443":{"HTTPS":true}},
+                "Web":{"h:443":{"Handlers":{"/":{"Proxy":"http://127.0.0.1:18789"}}},
+                       "h:8443":{"Handlers":{"/":{"Path":"/srv"}}}}}"#,
+        )
+        .unwrap();
+        assert!(extra.extra);
+        assert_eq!(
+            serve_plan(&extra, 18789, ServeMode::Tailnet),
+            vec![
+                "tailscale serve reset",
+                "tailscale serve --bg --https=443 http://127.0.0.1:18789"
+            ]
+        );
+        assert!(parse_serve_status("permission denied").is_err());
+    }
+
+    #[test]
+    fn devices_match_deployments_by_hostname() {
+        let device = |name: &str, hostname: &str| Device {
+            id: name.into(),
+            name: format!("{name}.tail1234.ts.net"),
+            hostname: hostname.into(),
+            addresses: vec![],
+            tags: vec![],
+            last_seen: None,
+            os: None,
+        };
+        let devices = vec![
+            device("openclaw-1a2b", "openclaw-1a2b"),
+            device("openclaw-1a2b-2", "localhost"),
+            device("openclaw-1a2bc", "openclaw-1a2bc"),
+        ];
+        let target = FleetTarget {
+            id: "d1".into(),
+            hostname: "openclaw-1a2b".into(),
+            ip: "203.0.113.5".into(),
+            fqdn: None,
+            key: Default::default(),
+            provider: None,
+            customer_email: None,
+            tags: vec![],
+        };
+        let targets = [target];
+        let matched: Vec<bool> = match_devices(&devices, &targets)
+            .iter()
+            .map(|(_, t)| t.is_some())
+            .collect();
+        assert_eq!(matched, vec![true, true, false]);
+    }
+}
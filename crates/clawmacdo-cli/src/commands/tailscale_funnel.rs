@@ -1,4 +1,5 @@
-use anyhow::{bail, Result};
+use super::tailnet;
+use anyhow::{bail, Context, Result};
 use clawmacdo_core::config;
 use clawmacdo_provision::provision::commands::{ssh_as_openclaw_async, ssh_root_async};
 use std::path::{Path, PathBuf};
@@ -39,13 +40,23 @@ fn find_deploy_record(query: &str) -> Result<(String, PathBuf, Option<String>)>
 ///
 /// Steps:
 /// 1. Install Tailscale (if not already installed)
-/// 2. Connect Tailscale with the provided auth key
+/// 2. Connect Tailscale with the provided auth key (minted through the
+///    Tailscale API when none is given and `TAILSCALE_API_KEY` is set)
 /// 3. Enable Funnel on port 18789
 /// 4. Retrieve and display the public Funnel URL
 /// 5. Update openclaw.json controlUi.allowedOrigins + trustedProxies
 /// 6. Auto-approve all pending devices
-pub async fn setup(query: &str, auth_key: &str, port: u16) -> Result<()> {
+pub async fn setup(query: &str, auth_key: Option<&str>, port: u16) -> Result<()> {
     let (ip, key, _provider) = find_deploy_record(query)?;
+    let auth_key = match auth_key {
+        Some(auth_key) => auth_key.to_string(),
+        None => tailnet::mint_auth_key(query).await?.with_context(|| {
+            format!(
+                "Pass --auth-key or set {} so an auth key can be minted",
+                clawmacdo_cloud::tailscale_api::API_KEY_ENV
+            )
+        })?,
+    };
     let home = config::OPENCLAW_HOME;
 
     println!("Setting up Tailscale Funnel on {ip}...\n");
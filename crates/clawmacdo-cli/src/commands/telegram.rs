@@ -221,18 +221,6 @@ pub async fn configure_bot(query: &str, bot_token: &str, reset: bool) -> Result<
     Ok(())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn configure_cmd_removes_legacy_telegram_streaming_string() {
-        let cmd = build_configure_cmd("123456:abcdef", false).unwrap();
-        assert!(cmd.contains("delete cfg.channels.telegram.streaming"));
-        assert!(!cmd.contains("streaming || 'partial'"));
-    }
-}
-
 /// Retrieve the Telegram chat ID from a deployed instance.
 /// Searches the openclaw credentials directory for the paired Telegram chat ID.
 pub async fn get_chat_id(query: &str) -> Result<()> {
@@ -349,3 +337,15 @@ pub async fn approve_pairing(query: &str, code: &str) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn configure_cmd_removes_legacy_telegram_streaming_string() {
+        let cmd = build_configure_cmd("123456:abcdef", false).unwrap();
+        assert!(cmd.contains("delete cfg.channels.telegram.streaming"));
+        assert!(!cmd.contains("streaming || 'partial'"));
+    }
+}
@@ -135,10 +135,13 @@ fn render_human(deployment: &db::DeploymentRow, steps: &[db::DeployStepRow]) {
             "failed" => red.apply_to("✗").to_string(),
             _ => " ".to_string(),
         };
-        println!(
-            "  {} [{:>2}/{}] {}",
-            indicator, step.step_number, step.total_steps, step.label
-        );
+        // Extension steps are numbered after the built-in ones.
+        let position = if step.step_number > step.total_steps {
+            "ext".to_string()
+        } else {
+            format!("{:>2}/{}", step.step_number, step.total_steps)
+        };
+        println!("  {} [{position:>5}] {}", indicator, step.label);
         if let Some(err) = &step.error_msg {
             println!("      {}", red.apply_to(err));
         }
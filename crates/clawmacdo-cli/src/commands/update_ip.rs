@@ -1,3 +1,4 @@
+use super::dns;
 use anyhow::{bail, Context, Result};
 #[cfg(feature = "lightsail")]
 use clawmacdo_cloud::CloudProvider;
@@ -95,18 +96,36 @@ pub async fn run(query: &str) -> Result<()> {
         _ => bail!("update-ip not supported for provider '{provider}'"),
     };
 
-    if new_ip == *old_ip {
+    let changed = new_ip != *old_ip;
+    if changed {
+        println!("IP changed: {old_ip} -> {new_ip}");
+    } else {
         println!("IP unchanged: {old_ip}");
-        return Ok(());
     }
 
-    println!("IP changed: {old_ip} -> {new_ip}");
+    // Point the DNS name at the current IP even when it is unchanged, so
+    // records created before DNS was configured get a name too.
+    let mut updated = record.clone();
+    updated.ip_address = new_ip.clone();
+    let dns_line = dns::publish(&mut updated).await;
 
-    // Update JSON deploy record — read, replace IP, write back
-    let mut raw: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&record_path)?)?;
-    raw["ip_address"] = serde_json::Value::String(new_ip.clone());
-    std::fs::write(&record_path, serde_json::to_string_pretty(&raw)?)?;
-    println!("  Updated: {}", record_path.display());
+    if changed || updated.fqdn != record.fqdn {
+        // Update JSON deploy record — read, replace IP and DNS name, write back
+        let mut raw: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&record_path)?)?;
+        raw["ip_address"] = serde_json::Value::String(new_ip.clone());
+        if let Some(fqdn) = &updated.fqdn {
+            raw["fqdn"] = serde_json::Value::String(fqdn.clone());
+        }
+        std::fs::write(&record_path, serde_json::to_string_pretty(&raw)?)?;
+        println!("  Updated: {}", record_path.display());
+    }
+    if let Some(line) = dns_line {
+        println!("{line}");
+    }
+    if !changed {
+        return Ok(());
+    }
 
     // Update SQLite
     if let Ok(conn) = db::init_db() {
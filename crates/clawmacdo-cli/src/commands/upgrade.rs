@@ -0,0 +1,255 @@
+use super::fleet::{self, BatchOptions, FleetTarget, Selector};
+use super::openclaw_version::{
+    install_cmd, list_versions, parse_version_output, resolve_version, version_cmd,
+};
+use super::wiki::print_json;
+use anyhow::{anyhow, bail, Result};
+use clawmacdo_core::config;
+use clawmacdo_db as db;
+use clawmacdo_provision::provision::commands::ssh_as_openclaw_with_user_async;
+use std::sync::Arc;
+
+pub struct UpgradeParams {
+    pub selector: Selector,
+    /// A published version or `latest`.
+    pub to: String,
+    pub canary: usize,
+    pub batch_size: usize,
+    pub concurrency: usize,
+    pub max_failures: usize,
+    pub dry_run: bool,
+    pub json: bool,
+}
+
+/// Installed version and whether `openclaw channels status` succeeded.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Probe {
+    pub(crate) version: Option<String>,
+    pub(crate) channels_ok: bool,
+}
+
+/// `openclaw channels status`, reduced to a `channels: ok` / `channels: FAILED`
+/// line plus the tail of its output. Always exits 0.
+fn channels_cmd() -> String {
+    let home = config::OPENCLAW_HOME;
+    format!(
+        "export PATH={home}/.local/bin:{home}/.local/share/pnpm:/usr/local/bin:/usr/bin:/bin HOME={home} && \
+         LOG=$(mktemp) && \
+         if timeout 45s openclaw channels status >\"$LOG\" 2>&1; then echo 'channels: ok'; \
+         else echo 'channels: FAILED'; tail -n 3 \"$LOG\"; fi; \
+         rm -f \"$LOG\""
+    )
+}
+
+fn parse_probe(output: &str) -> Probe {
+    let mut lines = output.lines();
+    let version = lines.next().and_then(parse_version_output);
+    Probe {
+        version,
+        channels_ok: output.lines().any(|l| l.trim() == "channels: ok"),
+    }
+}
+
+async fn ssh(target: &FleetTarget, cmd: &str) -> Result<String> {
+    Ok(ssh_as_openclaw_with_user_async(&target.ip, &target.key, cmd, target.ssh_user()).await?)
+}
+
+/// Read the installed OpenClaw version and channel health from an instance.
+pub(crate) async fn probe(target: &FleetTarget) -> Result<Probe> {
+    let cmd = format!(
+        "({} 2>/dev/null | head -n 1 | grep . || echo 'version: unknown'); {}",
+        version_cmd(),
+        channels_cmd()
+    );
+    Ok(parse_probe(&ssh(target, &cmd).await?))
+}
+
+/// Store a version result; a failing database only costs the record.
+pub(crate) fn record(
+    target: &FleetTarget,
+    version: Option<&str>,
+    previous: Option<&str>,
+    result: &str,
+) {
+    let recorded = db::init_db()
+        .and_then(|conn| db::record_openclaw_version(&conn, &target.id, version, previous, result));
+    if let Err(err) = recorded {
+        eprintln!(
+            "warning: could not record the OpenClaw version of {}: {err:#}",
+            target.hostname
+        );
+    }
+}
+
+/// Install `version`, then require the version, gateway health and (when
+/// they worked before) channels to check out.
+async fn install_and_verify(target: &FleetTarget, version: &str, channels: bool) -> Result<()> {
+    ssh(target, &install_cmd(version)).await?;
+    let installed = parse_version_output(&ssh(target, &version_cmd()).await?);
+    if installed.as_deref() != Some(version) {
+        bail!(
+            "openclaw --version reports {} after installing {version}",
+            installed.as_deref().unwrap_or("nothing")
+        );
+    }
+    ssh(target, &fleet::gateway_health_cmd(true)).await?;
+    if channels {
+        let output = ssh(target, &channels_cmd()).await?;
+        if !output.lines().any(|l| l.trim() == "channels: ok") {
+            bail!(
+                "channel status failed after upgrade: {}",
+                output.lines().skip(1).collect::<Vec<_>>().join(" ")
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Upgrade one instance, reinstalling the previous version when the new one
+/// does not come up healthy.
+async fn upgrade_one(target: FleetTarget, version: Arc<String>) -> Result<String> {
+    let before = probe(&target).await?;
+    let Some(previous) = before.version else {
+        bail!("could not read the installed OpenClaw version; not upgrading");
+    };
+    record(&target, Some(&previous), None, "probed");
+    if previous == *version {
+        return Ok(format!("already on {previous}"));
+    }
+
+    match install_and_verify(&target, &version, before.channels_ok).await {
+        Ok(()) => {
+            record(&target, Some(&version), Some(&previous), "upgraded");
+            let channels = if before.channels_ok {
+                "channels ok"
+            } else {
+                "channels not checked"
+            };
+            Ok(format!(
+                "{previous} → {version}; gateway healthy, {channels}"
+            ))
+        }
+        Err(err) => {
+            let reverted = async {
+                ssh(&target, &install_cmd(&previous)).await?;
+                ssh(&target, &fleet::gateway_health_cmd(true)).await
+            }
+            .await;
+            match reverted {
+                Ok(_) => {
+                    record(&target, Some(&previous), None, "reverted");
+                    Err(anyhow!("{err:#}; reverted to {previous}"))
+                }
+                Err(revert_err) => {
+                    record(&target, None, Some(&previous), "revert_failed");
+                    Err(anyhow!(
+                        "{err:#}; reverting to {previous} also failed: {revert_err:#}"
+                    ))
+                }
+            }
+        }
+    }
+}
+
+/// `upgrade --to <version|latest>`: upgrade OpenClaw on the selected
+/// instances in canary-first batches, verifying each host and reverting it
+/// to its previous version on failure. Versions are recorded in the
+/// deployments database for `status`.
+pub async fn run(params: UpgradeParams) -> Result<()> {
+    let versions = list_versions().await?;
+    let version = resolve_version(&params.to, &versions)?;
+    let targets = fleet::resolve(&params.selector)?;
+    let options = BatchOptions {
+        canary: params.canary,
+        batch_size: params.batch_size,
+        concurrency: params.concurrency,
+        max_failures: params.max_failures,
+        quiet: params.json,
+    };
+    let batches = fleet::plan_batches(targets.len(), options.canary, options.batch_size);
+
+    if params.dry_run {
+        if params.json {
+            print_json(&serde_json::json!({
+                "ok": true,
+                "dry_run": true,
+                "version": version,
+                "batches": batches,
+                "targets": targets,
+            }))?;
+        } else {
+            println!(
+                "Would upgrade {} instance(s) to openclaw@{version} in {} batch(es):",
+                targets.len(),
+                batches.len()
+            );
+            let mut remaining = targets.iter();
+            for (index, size) in batches.iter().enumerate() {
+                let hosts: Vec<&str> = remaining
+                    .by_ref()
+                    .take(*size)
+                    .map(|t| t.hostname.as_str())
+                    .collect();
+                println!("  batch {}: {}", index + 1, hosts.join(", "));
+            }
+            println!("\nDry run: nothing was changed.");
+        }
+        return Ok(());
+    }
+
+    if !params.json {
+        println!(
+            "Upgrading {} instance(s) to openclaw@{version} in {} batch(es) (canary {}, batch size {}, concurrency {}, max failures {})",
+            targets.len(),
+            batches.len(),
+            options.canary.min(targets.len()),
+            options.batch_size,
+            options.concurrency,
+            options.max_failures
+        );
+    }
+    let target_version = Arc::new(version.clone());
+    let summary = fleet::run_batches(targets, &options, move |target| {
+        upgrade_one(target, Arc::clone(&target_version))
+    })
+    .await;
+
+    if params.json {
+        let mut value = serde_json::to_value(&summary)?;
+        value["version"] = serde_json::Value::String(version);
+        print_json(&value)?;
+    } else {
+        summary.print_table();
+        println!("\n  Run `clawmacdo status` to see OpenClaw versions across the fleet.");
+    }
+    if !summary.ok {
+        match &summary.halted {
+            Some(reason) => bail!("upgrade halted: {reason}"),
+            None => bail!("upgrade failed on {} instance(s)", summary.failed),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn probe_output_is_parsed() {
+        assert_eq!(
+            parse_probe("2026.3.20\nchannels: ok\n"),
+            Probe {
+                version: Some("2026.3.20".into()),
+                channels_ok: true
+            }
+        );
+        assert_eq!(
+            parse_probe("version: unknown\nchannels: FAILED\nerror: unknown command\n"),
+            Probe {
+                version: None,
+                channels_ok: false
+            }
+        );
+    }
+}
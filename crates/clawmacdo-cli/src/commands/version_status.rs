@@ -0,0 +1,181 @@
+use super::fleet::{self, BatchOptions, Selector};
+use super::openclaw_version::version_sort_key;
+use super::upgrade;
+use super::wiki::print_json;
+use anyhow::Result;
+use clawmacdo_db as db;
+use console::style;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+pub struct StatusParams {
+    pub selector: Selector,
+    /// Probe every selected instance over SSH before reporting.
+    pub refresh: bool,
+    pub json: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct InstanceStatus {
+    id: String,
+    hostname: String,
+    ip: String,
+    provider: Option<String>,
+    tags: Vec<String>,
+    version: Option<String>,
+    previous_version: Option<String>,
+    last_result: Option<String>,
+    checked_at: Option<String>,
+    /// `current`, `behind` or `unknown`, relative to the newest version seen.
+    drift: &'static str,
+}
+
+/// Newest of the recorded versions.
+fn newest<'a>(versions: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    versions.max_by_key(|v| version_sort_key(v))
+}
+
+fn drift(version: Option<&str>, newest: Option<&str>) -> &'static str {
+    match (version, newest) {
+        (Some(version), Some(newest)) if version_sort_key(version) >= version_sort_key(newest) => {
+            "current"
+        }
+        (Some(_), Some(_)) => "behind",
+        _ => "unknown",
+    }
+}
+
+/// `status`: OpenClaw versions across the deployed fleet, as recorded by
+/// `upgrade` and `status --refresh`, with drift from the newest version.
+pub async fn run(params: StatusParams) -> Result<()> {
+    let mut selector = params.selector;
+    selector.all = true;
+    let targets = fleet::resolve(&selector)?;
+
+    if params.refresh {
+        if !params.json {
+            println!("Probing {} instance(s)...", targets.len());
+        }
+        let options = BatchOptions {
+            canary: 0,
+            batch_size: targets.len(),
+            concurrency: 8,
+            max_failures: usize::MAX,
+            quiet: true,
+        };
+        let summary = fleet::run_batches(targets.clone(), &options, |target| async move {
+            let probe = upgrade::probe(&target).await?;
+            upgrade::record(&target, probe.version.as_deref(), None, "probed");
+            Ok(probe.version.unwrap_or_else(|| "unknown".into()))
+        })
+        .await;
+        if !params.json {
+            for failed in summary.failed_instances() {
+                println!("  {} could not be probed", style(failed).red());
+            }
+        }
+    }
+
+    let conn = db::init_db()?;
+    let recorded = db::openclaw_versions(&conn)?;
+    let newest_version = newest(
+        targets
+            .iter()
+            .filter_map(|t| recorded.get(&t.id)?.version.as_deref()),
+    )
+    .map(str::to_string);
+
+    let instances: Vec<InstanceStatus> = targets
+        .into_iter()
+        .map(|target| {
+            let row = recorded.get(&target.id);
+            let version = row.and_then(|r| r.version.clone());
+            InstanceStatus {
+                drift: drift(version.as_deref(), newest_version.as_deref()),
+                previous_version: row.and_then(|r| r.previous_version.clone()),
+                last_result: row.map(|r| r.last_result.clone()),
+                checked_at: row.map(|r| r.checked_at.clone()),
+                version,
+                id: target.id,
+                hostname: target.hostname,
+                ip: target.ip,
+                provider: target.provider,
+                tags: target.tags,
+            }
+        })
+        .collect();
+
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for instance in &instances {
+        *counts
+            .entry(instance.version.as_deref().unwrap_or("unknown"))
+            .or_default() += 1;
+    }
+
+    if params.json {
+        print_json(&serde_json::json!({
+            "ok": true,
+            "newest_version": newest_version,
+            "versions": counts,
+            "drifted": counts.len() > 1,
+            "instances": instances,
+        }))?;
+        return Ok(());
+    }
+
+    println!(
+        "  {:<28}  {:<16}  {:<12}  {:<12}  {:<12}  {:<13}  {:<19}  Drift",
+        "Instance", "IP", "Provider", "OpenClaw", "Previous", "Last result", "Checked"
+    );
+    println!("  {}", "-".repeat(130));
+    for instance in &instances {
+        let drift = match instance.drift {
+            "current" => style(instance.drift).green(),
+            "behind" => style(instance.drift).yellow(),
+            _ => style(instance.drift).dim(),
+        };
+        println!(
+            "  {:<28}  {:<16}  {:<12}  {:<12}  {:<12}  {:<13}  {:<19}  {drift}",
+            instance.hostname,
+            instance.ip,
+            instance.provider.as_deref().unwrap_or("-"),
+            instance.version.as_deref().unwrap_or("-"),
+            instance.previous_version.as_deref().unwrap_or("-"),
+            instance.last_result.as_deref().unwrap_or("-"),
+            instance.checked_at.as_deref().unwrap_or("never"),
+        );
+    }
+    let breakdown: Vec<String> = counts
+        .iter()
+        .map(|(version, count)| format!("{version}: {count}"))
+        .collect();
+    println!(
+        "\n  Total: {} instance(s)  ({})",
+        instances.len(),
+        breakdown.join(", ")
+    );
+    if counts.len() > 1 {
+        println!(
+            "  Version drift: upgrade with `clawmacdo upgrade --to {} ...`",
+            newest_version.as_deref().unwrap_or("latest")
+        );
+    }
+    if counts.contains_key("unknown") {
+        println!("  Some versions are unknown; run `clawmacdo status --refresh` to probe them.");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drift_is_relative_to_the_newest_version() {
+        let newest_version = newest(["2026.3.2", "2026.3.20", "2026.10.1"].into_iter());
+        assert_eq!(newest_version, Some("2026.10.1"));
+        assert_eq!(drift(Some("2026.10.1"), newest_version), "current");
+        assert_eq!(drift(Some("2026.3.20"), newest_version), "behind");
+        assert_eq!(drift(None, newest_version), "unknown");
+    }
+}
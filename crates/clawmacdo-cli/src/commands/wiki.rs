@@ -1,15 +1,16 @@
+use super::wiki_convert::{prepare_ingest, SourceKind, MAX_SOURCE_BYTES};
 use anyhow::{bail, Context, Result};
 use base64::engine::general_purpose::STANDARD as BASE64;
 use base64::Engine;
 use chrono::Utc;
+use clawmacdo_core::archive::{self, read_zip, ZipFile, ZipLimits};
 use clawmacdo_core::config;
 use clawmacdo_provision::provision::commands::ssh_as_openclaw_with_user_async;
 use serde::Deserialize;
 use serde_json::Value;
-use std::io::Write;
 use std::path::{Path, PathBuf};
 
-const MAX_MARKDOWN_BYTES: u64 = 5 * 1024 * 1024;
+pub(crate) const MAX_MARKDOWN_BYTES: u64 = 5 * 1024 * 1024;
 
 pub struct WikiTreeParams {
     pub instance: String,
@@ -56,6 +57,15 @@ pub struct WikiDeleteParams {
     pub json: bool,
 }
 
+pub struct WikiImportParams {
+    pub instance: String,
+    pub agent: String,
+    pub project: String,
+    pub archive: PathBuf,
+    pub replace: bool,
+    pub json: bool,
+}
+
 pub struct WikiIngestParams {
     pub instance: String,
     pub agent: String,
@@ -63,41 +73,40 @@ pub struct WikiIngestParams {
     pub source: PathBuf,
     pub prompt: String,
     pub timeout: u64,
+    pub chunk_bytes: Option<u64>,
     pub json: bool,
 }
 
 #[derive(Debug, Deserialize)]
-struct RemoteManifest {
-    workspace: String,
-    project: String,
-    files: Vec<RemoteWikiFile>,
+pub(crate) struct RemoteManifest {
+    pub(crate) workspace: String,
+    pub(crate) project: String,
+    pub(crate) files: Vec<RemoteWikiFile>,
 }
 
 #[derive(Debug, Deserialize)]
-struct RemoteWikiFile {
-    path: String,
-    size: u64,
-    sha256: String,
+pub(crate) struct RemoteWikiFile {
+    pub(crate) path: String,
+    pub(crate) size: u64,
+    pub(crate) sha256: String,
     #[serde(default)]
-    mtime: String,
+    pub(crate) mtime: String,
     #[serde(default)]
-    data: Option<String>,
-}
-
-struct ZipEntry {
-    name: String,
-    data: Vec<u8>,
-    crc32: u32,
+    pub(crate) data: Option<String>,
 }
 
-struct CentralDirectoryEntry {
-    name: String,
-    crc32: u32,
-    size: u32,
-    offset: u32,
+pub(crate) fn find_deploy_record(query: &str) -> Result<(String, PathBuf, Option<String>)> {
+    let record = find_record(query)?;
+    let provider = record.provider.map(|p| p.to_string());
+    Ok((
+        record.ip_address,
+        PathBuf::from(record.ssh_key_path),
+        provider,
+    ))
 }
 
-fn find_deploy_record(query: &str) -> Result<(String, PathBuf, Option<String>)> {
+/// The deploy record matching a deploy ID, hostname or IP address.
+pub(crate) fn find_record(query: &str) -> Result<config::DeployRecord> {
     let deploys_dir = config::deploys_dir()?;
     if !deploys_dir.exists() {
         bail!("No deploy records found. Deploy an instance first.");
@@ -115,19 +124,14 @@ fn find_deploy_record(query: &str) -> Result<(String, PathBuf, Option<String>)>
             Err(_) => continue,
         };
         if record.id == query || record.hostname == query || record.ip_address == query {
-            let provider = record.provider.map(|p| p.to_string());
-            return Ok((
-                record.ip_address,
-                PathBuf::from(record.ssh_key_path),
-                provider,
-            ));
+            return Ok(record);
         }
     }
 
     bail!("No deploy record found for '{query}'. Use a deploy ID, hostname, or IP address.");
 }
 
-fn ssh_user_for_provider(provider: &Option<String>) -> &'static str {
+pub(crate) fn ssh_user_for_provider(provider: &Option<String>) -> &'static str {
     match provider.as_deref() {
         Some("lightsail") => "ubuntu",
         Some("azure") => "azureuser",
@@ -135,7 +139,7 @@ fn ssh_user_for_provider(provider: &Option<String>) -> &'static str {
     }
 }
 
-fn clean_instance(value: &str) -> Result<String> {
+pub(crate) fn clean_instance(value: &str) -> Result<String> {
     let value = value.trim();
     if value.is_empty() {
         bail!("--instance cannot be empty.");
@@ -149,7 +153,7 @@ fn clean_instance(value: &str) -> Result<String> {
     Ok(value.to_string())
 }
 
-fn clean_agent_id(value: &str) -> Result<String> {
+pub(crate) fn clean_agent_id(value: &str) -> Result<String> {
     let agent = value.trim();
     if agent.is_empty() {
         bail!("--agent cannot be empty.");
@@ -166,7 +170,7 @@ fn clean_agent_id(value: &str) -> Result<String> {
     Ok(agent.to_string())
 }
 
-fn clean_project_slug(value: &str) -> Result<String> {
+pub(crate) fn clean_project_slug(value: &str) -> Result<String> {
     let project = value.trim();
     if project.is_empty() {
         bail!("--project cannot be empty.");
@@ -200,7 +204,7 @@ fn clean_deletable_wiki_project_slug(value: &str) -> Result<String> {
     Ok(project)
 }
 
-fn clean_relative_markdown_path(value: &str) -> Result<String> {
+pub(crate) fn clean_relative_markdown_path(value: &str) -> Result<String> {
     let path = value.trim();
     if path.is_empty() {
         bail!("--path cannot be empty.");
@@ -263,12 +267,10 @@ fn clean_source_file(path: PathBuf) -> Result<PathBuf> {
     if !path.is_file() {
         bail!("--source must point to a file: {}", path.display());
     }
+    SourceKind::from_path(&path)?;
     let metadata = std::fs::metadata(&path)?;
-    if metadata.len() > MAX_MARKDOWN_BYTES {
-        bail!("--source must be 5 MiB or smaller.");
-    }
-    if path.extension().and_then(|ext| ext.to_str()) != Some("md") {
-        bail!("--source must point to a Markdown .md file.");
+    if metadata.len() > MAX_SOURCE_BYTES {
+        bail!("--source must be 50 MiB or smaller.");
     }
     path.canonicalize()
         .with_context(|| format!("Failed to resolve --source path {}", path.display()))
@@ -295,20 +297,20 @@ fn clean_timeout_secs(value: u64) -> Result<u64> {
     Ok(value)
 }
 
-fn js_string(value: &str) -> Result<String> {
+pub(crate) fn js_string(value: &str) -> Result<String> {
     Ok(serde_json::to_string(value)?)
 }
 
-fn remote_json_value(output: &str, context: &str) -> Result<Value> {
+pub(crate) fn remote_json_value(output: &str, context: &str) -> Result<Value> {
     serde_json::from_str(output.trim()).with_context(|| format!("Failed to parse {context} JSON"))
 }
 
-fn print_json(value: &Value) -> Result<()> {
+pub(crate) fn print_json(value: &Value) -> Result<()> {
     println!("{}", serde_json::to_string_pretty(value)?);
     Ok(())
 }
 
-fn handle_remote_status(value: &Value, json: bool) -> Result<()> {
+pub(crate) fn handle_remote_status(value: &Value, json: bool) -> Result<()> {
     if value.get("ok").and_then(Value::as_bool) == Some(false) {
         if json {
             print_json(value)?;
@@ -1216,6 +1218,281 @@ NODE
     Ok(template)
 }
 
+fn build_import_cmd(agent: &str, project: &str, upload_tmp: &str, replace: bool) -> Result<String> {
+    let home = config::OPENCLAW_HOME;
+    let mut template = r#"set -e
+export HOME="__HOME__"
+export PATH="__HOME__/.local/bin:__HOME__/.local/share/pnpm:/usr/local/bin:/usr/bin:/bin:$PATH"
+
+node <<'NODE'
+const fs = require('fs');
+const path = require('path');
+const crypto = require('crypto');
+const { execFileSync } = require('child_process');
+
+const home = process.env.HOME || '__HOME__';
+const configPath = path.join(home, '.openclaw', 'openclaw.json');
+const agentId = __AGENT_JSON__;
+const project = __PROJECT_JSON__;
+const uploadTmp = __UPLOAD_TMP_JSON__;
+const replace = __REPLACE__;
+const maxBytes = __MAX_BYTES__;
+const indexStart = '<!-- clawmacdo:wiki-index:start -->';
+const indexEnd = '<!-- clawmacdo:wiki-index:end -->';
+
+function fail(code, message, extra = {}) {
+  console.log(JSON.stringify({ ok: false, error: { code, message }, ...extra }));
+  process.exit(0);
+}
+
+function readJson(file) {
+  try {
+    return JSON.parse(fs.readFileSync(file, 'utf8'));
+  } catch (_) {
+    return {};
+  }
+}
+
+function expandWorkspace(raw) {
+  let value = typeof raw === 'string' && raw.trim()
+    ? raw.trim()
+    : path.join(home, '.openclaw', 'workspace');
+  if (value === '~') value = home;
+  if (value.startsWith('~/')) value = path.join(home, value.slice(2));
+  if (value.startsWith('$HOME/')) value = path.join(home, value.slice(6));
+  if (value.startsWith('${HOME}/')) value = path.join(home, value.slice(8));
+  if (!path.isAbsolute(value)) value = path.join(home, value);
+  return path.normalize(value);
+}
+
+function isWithin(root, target) {
+  const rel = path.relative(root, target);
+  return rel === '' || (!!rel && !rel.startsWith('..') && !path.isAbsolute(rel));
+}
+
+function sha256Bytes(data) {
+  return crypto.createHash('sha256').update(data).digest('hex');
+}
+
+function normalizeRel(value) {
+  return value.split(path.sep).join('/');
+}
+
+function lstatOrNull(file) {
+  try {
+    return fs.lstatSync(file);
+  } catch (_) {
+    return null;
+  }
+}
+
+function copyTree(src, dst) {
+  fs.mkdirSync(dst, { recursive: true });
+  for (const entry of fs.readdirSync(src, { withFileTypes: true })) {
+    const from = path.join(src, entry.name);
+    const to = path.join(dst, entry.name);
+    if (entry.isSymbolicLink()) continue;
+    if (entry.isDirectory()) copyTree(from, to);
+    else if (entry.isFile()) fs.copyFileSync(from, to);
+  }
+}
+
+function listMarkdown(dir, base = dir) {
+  const out = [];
+  for (const entry of fs.readdirSync(dir, { withFileTypes: true })) {
+    const full = path.join(dir, entry.name);
+    if (entry.isSymbolicLink()) continue;
+    if (entry.isDirectory()) out.push(...listMarkdown(full, base));
+    else if (entry.isFile() && entry.name.toLowerCase().endsWith('.md')) {
+      out.push(normalizeRel(path.relative(base, full)));
+    }
+  }
+  return out.sort((a, b) => a.localeCompare(b));
+}
+
+function pageTitle(file, rel) {
+  try {
+    for (const line of fs.readFileSync(file, 'utf8').split(/\r?\n/)) {
+      const heading = line.match(/^#\s+(.+?)\s*#*\s*$/);
+      if (heading) return heading[1].replace(/[\[\]]/g, '');
+    }
+  } catch (_) {}
+  return rel.replace(/\.md$/i, '');
+}
+
+function regenerateIndex(projectDir) {
+  const indexFile = path.join(projectDir, 'INDEX.md');
+  const pages = listMarkdown(projectDir).filter((rel) => rel !== 'INDEX.md');
+  const lines = ['## Pages', ''];
+  for (const rel of pages) {
+    lines.push(`- [${pageTitle(path.join(projectDir, rel), rel)}](${encodeURI(rel)})`);
+  }
+  const block = indexStart + '\n' + lines.join('\n') + '\n' + indexEnd + '\n';
+  let existing = '';
+  try {
+    existing = fs.readFileSync(indexFile, 'utf8');
+  } catch (_) {
+    existing = `# ${project} Index\n`;
+  }
+  const startAt = existing.indexOf(indexStart);
+  const endAt = existing.indexOf(indexEnd);
+  let next;
+  if (startAt !== -1 && endAt > startAt) {
+    let after = endAt + indexEnd.length;
+    if (existing[after] === '\n') after += 1;
+    next = existing.slice(0, startAt) + block + existing.slice(after);
+  } else {
+    next = existing.trimEnd() + '\n\n' + block;
+  }
+  fs.writeFileSync(indexFile, next, { mode: 0o644 });
+  return pages.length;
+}
+
+let payload;
+try {
+  payload = JSON.parse(fs.readFileSync(uploadTmp, 'utf8'));
+} catch (_) {
+  fail('upload_not_found', 'Uploaded wiki archive was not found on the instance.');
+} finally {
+  try { fs.unlinkSync(uploadTmp); } catch (_) {}
+}
+
+const cfg = readJson(configPath);
+const agents = cfg.agents || {};
+const list = Array.isArray(agents.list) ? agents.list : [];
+const agent = list.find((item) => item && item.id === agentId);
+const workspace = expandWorkspace(
+  agent && agent.workspace
+    ? agent.workspace
+    : agents.defaults && agents.defaults.workspace
+);
+
+if (!fs.existsSync(workspace) || !fs.statSync(workspace).isDirectory()) {
+  fail('workspace_not_found', `OpenClaw workspace not found: ${workspace}`);
+}
+
+const workspaceReal = fs.realpathSync(workspace);
+const projectDir = path.join(workspaceReal, project);
+const rootMd = path.join(workspaceReal, `${project}.md`);
+if (path.dirname(projectDir) !== workspaceReal) {
+  fail('path_escape', 'Project directory is not a direct child of the OpenClaw workspace.', { project });
+}
+const projectStat = lstatOrNull(projectDir);
+const rootStat = lstatOrNull(rootMd);
+if (projectStat && (projectStat.isSymbolicLink() || !projectStat.isDirectory())) {
+  fail('invalid_path', `Project path is not a regular directory: ${project}`, { project });
+}
+if (rootStat && (rootStat.isSymbolicLink() || !rootStat.isFile())) {
+  fail('invalid_path', `Project root page is not a regular file: ${project}.md`, { project });
+}
+
+const files = Array.isArray(payload.files) ? payload.files : [];
+for (const file of files) {
+  const rel = file && file.path;
+  if (typeof rel !== 'string' || !rel.toLowerCase().endsWith('.md')
+    || (rel !== `${project}.md` && !rel.startsWith(`${project}/`))
+    || rel.split('/').some((s) => !s || s === '.' || s === '..')) {
+    fail('invalid_path', `Archive entry is not a Markdown page of ${project}: ${rel}`, { path: rel });
+  }
+}
+
+// Pre-import backup of the current project.
+let backup = null;
+const existing = [];
+if (projectStat) existing.push(project);
+if (rootStat) existing.push(`${project}.md`);
+if (existing.length) {
+  const backupDir = path.join(home, '.openclaw', 'wiki-backups');
+  fs.mkdirSync(backupDir, { recursive: true, mode: 0o700 });
+  const stamp = new Date().toISOString().replace(/[:.]/g, '-');
+  backup = path.join(backupDir, `${project}-${stamp}.tar.gz`);
+  execFileSync('tar', ['-czf', backup, '-C', workspaceReal, ...existing]);
+}
+
+// Build the new project next to the old one, then swap it in with renames.
+const staging = path.join(workspaceReal, `.clawmacdo-import-${project}-${process.pid}`);
+fs.rmSync(staging, { recursive: true, force: true });
+fs.mkdirSync(staging, { recursive: true });
+const stagedDir = path.join(staging, project);
+const stagedRoot = path.join(staging, `${project}.md`);
+const imported = [];
+let pageCount = 0;
+try {
+  if (!replace && projectStat) copyTree(projectDir, stagedDir);
+  fs.mkdirSync(stagedDir, { recursive: true });
+  if (!replace && rootStat) fs.copyFileSync(rootMd, stagedRoot);
+
+  for (const file of files) {
+    const data = Buffer.from(file.data || '', 'base64');
+    if (data.length > maxBytes) {
+      throw new Error(`${file.path} exceeds ${maxBytes} bytes.`);
+    }
+    const target = path.resolve(staging, file.path);
+    if (!isWithin(staging, target)) throw new Error(`Path escapes the import staging directory: ${file.path}`);
+    fs.mkdirSync(path.dirname(target), { recursive: true });
+    fs.writeFileSync(target, data, { mode: 0o644 });
+    imported.push({ path: file.path, size: data.length, sha256: sha256Bytes(data) });
+  }
+  pageCount = regenerateIndex(stagedDir);
+} catch (error) {
+  fs.rmSync(staging, { recursive: true, force: true });
+  fail('import_failed', String(error && error.message || error), { backup });
+}
+
+const previousDir = path.join(staging, '.previous-dir');
+const previousRoot = path.join(staging, '.previous-root.md');
+const moved = [];
+try {
+  if (projectStat) {
+    fs.renameSync(projectDir, previousDir);
+    moved.push([previousDir, projectDir]);
+  }
+  fs.renameSync(stagedDir, projectDir);
+  moved.push([projectDir, null]);
+  const hasStagedRoot = fs.existsSync(stagedRoot);
+  if (rootStat && (hasStagedRoot || replace)) {
+    fs.renameSync(rootMd, previousRoot);
+    moved.push([previousRoot, rootMd]);
+  }
+  if (hasStagedRoot) {
+    fs.renameSync(stagedRoot, rootMd);
+    moved.push([rootMd, null]);
+  }
+} catch (error) {
+  for (const [from, to] of moved.reverse()) {
+    try {
+      if (to) fs.renameSync(from, to);
+      else fs.rmSync(from, { recursive: true, force: true });
+    } catch (_) {}
+  }
+  fs.rmSync(staging, { recursive: true, force: true });
+  fail('import_failed', `Failed to swap in the imported project: ${error && error.message || error}`, { backup });
+}
+fs.rmSync(staging, { recursive: true, force: true });
+
+console.log(JSON.stringify({
+  ok: true,
+  workspace: workspaceReal,
+  project,
+  replace,
+  backup,
+  index: `${project}/INDEX.md`,
+  page_count: pageCount,
+  imported
+}));
+NODE
+"#
+    .to_string();
+
+    template = template.replace("__HOME__", home);
+    template = template.replace("__AGENT_JSON__", &js_string(agent)?);
+    template = template.replace("__PROJECT_JSON__", &js_string(project)?);
+    template = template.replace("__UPLOAD_TMP_JSON__", &js_string(upload_tmp)?);
+    template = template.replace("__REPLACE__", if replace { "true" } else { "false" });
+    template = template.replace("__MAX_BYTES__", &MAX_MARKDOWN_BYTES.to_string());
+    Ok(template)
+}
+
 fn manifest_from_value(value: Value) -> Result<RemoteManifest> {
     if value.get("ok").and_then(Value::as_bool) != Some(true) {
         bail!("remote wiki command did not return ok=true");
@@ -1223,105 +1500,63 @@ fn manifest_from_value(value: Value) -> Result<RemoteManifest> {
     serde_json::from_value(value).context("Failed to decode remote wiki manifest")
 }
 
-fn write_u16(out: &mut Vec<u8>, value: u16) {
-    out.extend_from_slice(&value.to_le_bytes());
-}
-
-fn write_u32(out: &mut Vec<u8>, value: u32) {
-    out.extend_from_slice(&value.to_le_bytes());
+const MAX_IMPORT_ENTRIES: usize = 5000;
+const MAX_IMPORT_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Read and validate the Markdown entries of a wiki ZIP.
+///
+/// Every entry must be a Markdown file whose name passes
+/// `clean_relative_markdown_path`, so absolute paths, `..` segments and
+/// backslashes (zip-slip) are rejected before anything leaves this machine.
+fn read_zip_entries(data: &[u8]) -> Result<Vec<ZipFile>> {
+    let mut seen = std::collections::BTreeSet::new();
+    let limits = ZipLimits {
+        max_entries: MAX_IMPORT_ENTRIES,
+        max_entry_bytes: MAX_MARKDOWN_BYTES,
+        max_total_bytes: MAX_IMPORT_BYTES,
+    };
+    let entries = read_zip(data, limits, |name| {
+        if name.starts_with("__MACOSX/") || name.rsplit('/').next() == Some(".DS_Store") {
+            return Ok(None);
+        }
+        let clean = clean_relative_markdown_path(name)
+            .with_context(|| format!("Invalid ZIP entry {name}"))?;
+        if !seen.insert(clean.clone()) {
+            bail!("ZIP entry {clean} appears more than once");
+        }
+        Ok(Some(clean))
+    })?;
+    if entries.is_empty() {
+        bail!("ZIP archive contains no Markdown files");
+    }
+    Ok(entries)
 }
 
-fn crc32(data: &[u8]) -> u32 {
-    let mut crc = 0xffff_ffffu32;
-    for &byte in data {
-        crc ^= byte as u32;
-        for _ in 0..8 {
-            let mask = (crc & 1).wrapping_neg();
-            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+/// Move every entry under `project`. The archive must hold a single project,
+/// i.e. every entry is `<root>.md` or `<root>/...` for one `<root>`.
+fn remap_import_entries(entries: &mut [ZipFile], project: &str) -> Result<()> {
+    let root_of = |name: &str| -> String {
+        match name.split_once('/') {
+            Some((root, _)) => root.to_string(),
+            None => name.trim_end_matches(".md").to_string(),
         }
+    };
+    let root = root_of(&entries[0].name);
+    if let Some(other) = entries.iter().find(|e| root_of(&e.name) != root) {
+        bail!(
+            "ZIP archive mixes wiki projects '{root}' and '{}'; import one project at a time",
+            root_of(&other.name)
+        );
+    }
+    if root == project {
+        return Ok(());
+    }
+    for entry in entries.iter_mut() {
+        entry.name = match entry.name.split_once('/') {
+            Some((_, rest)) => format!("{project}/{rest}"),
+            None => format!("{project}.md"),
+        };
     }
-    !crc
-}
-
-fn to_u16(value: usize, label: &str) -> Result<u16> {
-    u16::try_from(value).with_context(|| format!("{label} is too large for ZIP32"))
-}
-
-fn to_u32(value: usize, label: &str) -> Result<u32> {
-    u32::try_from(value).with_context(|| format!("{label} is too large for ZIP32"))
-}
-
-fn write_zip(path: &Path, entries: &[ZipEntry]) -> Result<()> {
-    let mut out = Vec::new();
-    let mut central_entries = Vec::new();
-    let mod_time = 0u16;
-    let mod_date = 33u16;
-    let flags = 0x0800u16;
-
-    for entry in entries {
-        let name = entry.name.as_bytes();
-        let size = to_u32(entry.data.len(), &entry.name)?;
-        let offset = to_u32(out.len(), "ZIP local header offset")?;
-
-        write_u32(&mut out, 0x0403_4b50);
-        write_u16(&mut out, 20);
-        write_u16(&mut out, flags);
-        write_u16(&mut out, 0);
-        write_u16(&mut out, mod_time);
-        write_u16(&mut out, mod_date);
-        write_u32(&mut out, entry.crc32);
-        write_u32(&mut out, size);
-        write_u32(&mut out, size);
-        write_u16(&mut out, to_u16(name.len(), "ZIP file name")?);
-        write_u16(&mut out, 0);
-        out.extend_from_slice(name);
-        out.extend_from_slice(&entry.data);
-
-        central_entries.push(CentralDirectoryEntry {
-            name: entry.name.clone(),
-            crc32: entry.crc32,
-            size,
-            offset,
-        });
-    }
-
-    let central_offset = to_u32(out.len(), "ZIP central directory offset")?;
-    for entry in &central_entries {
-        let name = entry.name.as_bytes();
-
-        write_u32(&mut out, 0x0201_4b50);
-        write_u16(&mut out, 20);
-        write_u16(&mut out, 20);
-        write_u16(&mut out, flags);
-        write_u16(&mut out, 0);
-        write_u16(&mut out, mod_time);
-        write_u16(&mut out, mod_date);
-        write_u32(&mut out, entry.crc32);
-        write_u32(&mut out, entry.size);
-        write_u32(&mut out, entry.size);
-        write_u16(&mut out, to_u16(name.len(), "ZIP file name")?);
-        write_u16(&mut out, 0);
-        write_u16(&mut out, 0);
-        write_u16(&mut out, 0);
-        write_u16(&mut out, 0);
-        write_u32(&mut out, 0);
-        write_u32(&mut out, entry.offset);
-        out.extend_from_slice(name);
-    }
-    let central_size = to_u32(out.len(), "ZIP output length")? - central_offset;
-    let entry_count = to_u16(central_entries.len(), "ZIP entry count")?;
-
-    write_u32(&mut out, 0x0605_4b50);
-    write_u16(&mut out, 0);
-    write_u16(&mut out, 0);
-    write_u16(&mut out, entry_count);
-    write_u16(&mut out, entry_count);
-    write_u32(&mut out, central_size);
-    write_u32(&mut out, central_offset);
-    write_u16(&mut out, 0);
-
-    let mut file = std::fs::File::create(path)?;
-    file.write_all(&out)?;
     Ok(())
 }
 
@@ -1349,7 +1584,7 @@ fn resolve_export_output(output: &Path, project: &str) -> Result<PathBuf> {
     Ok(path)
 }
 
-fn format_size(bytes: u64) -> String {
+pub(crate) fn format_size(bytes: u64) -> String {
     if bytes < 1024 {
         format!("{bytes} B")
     } else if bytes < 1024 * 1024 {
@@ -1359,7 +1594,7 @@ fn format_size(bytes: u64) -> String {
     }
 }
 
-async fn collect_manifest(
+pub(crate) async fn collect_manifest(
     instance: &str,
     agent: &str,
     project: &str,
@@ -1517,58 +1752,68 @@ pub async fn ingest(params: WikiIngestParams) -> Result<()> {
     let prompt = clean_ingest_prompt(&params.prompt)?;
     let timeout = clean_timeout_secs(params.timeout)?;
 
-    let content = std::fs::read(&source)?;
-    let source_name = source
-        .file_name()
-        .and_then(|name| name.to_str())
-        .unwrap_or("uploaded-doc.md")
-        .to_string();
-    let remote_tmp = format!("/tmp/clawmacdo-wiki-ingest-{}.md", uuid::Uuid::new_v4());
+    let data = std::fs::read(&source)?;
+    let chunks = prepare_ingest(&source, &data, params.chunk_bytes)?;
     let (ip, key, provider) = find_deploy_record(&instance)?;
     let ssh_user = ssh_user_for_provider(&provider);
-    let cmd = build_ingest_cmd(
-        &agent,
-        &project,
-        &remote_tmp,
-        &source_name,
-        &prompt,
-        timeout,
-    )?;
-
-    let scp_ip = ip.clone();
-    let scp_key = key.clone();
-    let scp_user = ssh_user.to_string();
-    let cmd_owned = cmd;
-    let remote_tmp_owned = remote_tmp.clone();
-    let outputs = tokio::task::spawn_blocking(move || {
-        let cmds: Vec<&str> = vec![&cmd_owned];
-        clawmacdo_ssh::scp_upload_bytes_and_exec_as(
-            &scp_ip,
-            &scp_key,
-            &content,
-            &remote_tmp_owned,
-            0o644,
-            &cmds,
-            &scp_user,
-        )
-    })
-    .await
-    .context("wiki source upload and ingest task failed")??;
 
-    let output = outputs.first().cloned().unwrap_or_default();
-    let value = remote_json_value(&output, "wiki ingest")?;
-    handle_remote_status(&value, params.json)?;
+    let total = chunks.len();
+    let mut values = Vec::with_capacity(total);
+    for (i, chunk) in chunks.into_iter().enumerate() {
+        if total > 1 && !params.json {
+            println!("[{}/{total}] Ingesting {}...", i + 1, chunk.name);
+        }
+        let remote_tmp = format!("/tmp/clawmacdo-wiki-ingest-{}.md", uuid::Uuid::new_v4());
+        let cmd = build_ingest_cmd(&agent, &project, &remote_tmp, &chunk.name, &prompt, timeout)?;
+
+        let scp_ip = ip.clone();
+        let scp_key = key.clone();
+        let scp_user = ssh_user.to_string();
+        let content = chunk.data;
+        let outputs = tokio::task::spawn_blocking(move || {
+            let cmds: Vec<&str> = vec![&cmd];
+            clawmacdo_ssh::scp_upload_bytes_and_exec_as(
+                &scp_ip,
+                &scp_key,
+                &content,
+                &remote_tmp,
+                0o644,
+                &cmds,
+                &scp_user,
+            )
+        })
+        .await
+        .context("wiki source upload and ingest task failed")??;
+
+        let output = outputs.first().cloned().unwrap_or_default();
+        let value = remote_json_value(&output, "wiki ingest")?;
+        handle_remote_status(&value, params.json)?;
+        if !params.json {
+            print_ingest_result(&value, &project);
+        }
+        values.push(value);
+    }
 
     if params.json {
-        return print_json(&value);
+        if total == 1 {
+            return print_json(&values[0]);
+        }
+        return print_json(&serde_json::json!({
+            "ok": true,
+            "project": project,
+            "chunks": values,
+        }));
     }
+    Ok(())
+}
 
+fn print_ingest_result(value: &Value, project: &str) {
     println!(
         "Ingested into project {}",
         value
             .get("project")
             .and_then(Value::as_str)
-            .unwrap_or(&project)
+            .unwrap_or(project)
     );
     if let Some(summary) = value.get("summary").and_then(Value::as_str) {
         println!("Summary: {summary}");
@@ -1589,7 +1834,6 @@ pub async fn ingest(params: WikiIngestParams) -> Result<()> {
             }
         }
     }
-    Ok(())
 }
 
 pub async fn export(params: WikiExportParams) -> Result<()> {
@@ -1624,15 +1868,11 @@ pub async fn export(params: WikiExportParams) -> Result<()> {
             );
         }
         total_size += file.size;
-        entries.push(ZipEntry {
-            name: file.path.clone(),
-            crc32: crc32(&data),
-            data,
-        });
+        entries.push((file.path.clone(), data));
     }
 
     let zip_path = resolve_export_output(&params.output, &project)?;
-    write_zip(&zip_path, &entries)?;
+    archive::write_zip_file(&zip_path, &entries)?;
 
     if params.json {
         println!(
@@ -1658,6 +1898,76 @@ pub async fn export(params: WikiExportParams) -> Result<()> {
     Ok(())
 }
 
+pub async fn import(params: WikiImportParams) -> Result<()> {
+    let instance = clean_instance(&params.instance)?;
+    let agent = clean_agent_id(&params.agent)?;
+    let project = clean_project_slug(&params.project)?;
+    let archive = std::fs::read(&params.archive)
+        .with_context(|| format!("Failed to read {}", params.archive.display()))?;
+    let mut entries = read_zip_entries(&archive)?;
+    remap_import_entries(&mut entries, &project)?;
+
+    let files: Vec<Value> = entries
+        .iter()
+        .map(|entry| serde_json::json!({ "path": entry.name, "data": BASE64.encode(&entry.data) }))
+        .collect();
+    let payload = serde_json::to_vec(&serde_json::json!({ "files": files }))?;
+    let remote_tmp = format!("/tmp/clawmacdo-wiki-import-{}.json", uuid::Uuid::new_v4());
+    let (ip, key, provider) = find_deploy_record(&instance)?;
+    let ssh_user = ssh_user_for_provider(&provider);
+    let cmd = build_import_cmd(&agent, &project, &remote_tmp, params.replace)?;
+
+    let scp_user = ssh_user.to_string();
+    let outputs = tokio::task::spawn_blocking(move || {
+        let cmds: Vec<&str> = vec![&cmd];
+        clawmacdo_ssh::scp_upload_bytes_and_exec_as(
+            &ip,
+            &key,
+            &payload,
+            &remote_tmp,
+            0o644,
+            &cmds,
+            &scp_user,
+        )
+    })
+    .await
+    .context("wiki import upload task failed")??;
+
+    let output = outputs.first().cloned().unwrap_or_default();
+    let value = remote_json_value(&output, "wiki import")?;
+    handle_remote_status(&value, params.json)?;
+
+    if params.json {
+        return print_json(&value);
+    }
+
+    let imported = value
+        .get("imported")
+        .and_then(Value::as_array)
+        .map(Vec::len)
+        .unwrap_or(0);
+    println!(
+        "Workspace: {}",
+        value.get("workspace").and_then(Value::as_str).unwrap_or("")
+    );
+    println!("Project:   {project}");
+    println!("Imported:  {imported} file(s)");
+    println!(
+        "Mode:      {}",
+        if params.replace { "replace" } else { "merge" }
+    );
+    println!(
+        "Index:     {} ({} page(s))",
+        value.get("index").and_then(Value::as_str).unwrap_or(""),
+        value.get("page_count").and_then(Value::as_u64).unwrap_or(0)
+    );
+    match value.get("backup").and_then(Value::as_str) {
+        Some(backup) => println!("Backup:    {backup}"),
+        None => println!("Backup:    none (project did not exist)"),
+    }
+    Ok(())
+}
+
 pub async fn delete(params: WikiDeleteParams) -> Result<()> {
     let instance = clean_instance(&params.instance)?;
     let agent = clean_agent_id(&params.agent)?;
@@ -1689,6 +1999,7 @@ pub async fn delete(params: WikiDeleteParams) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use clawmacdo_core::archive::{ZipMethod, ZipWriter};
 
     #[test]
     fn clean_relative_markdown_path_rejects_unsafe_paths() {
@@ -1778,4 +2089,80 @@ mod tests {
         assert!(cmd.contains("source_files"));
         assert!(cmd.contains(".clawmacdo-ingest-summary.md"));
     }
+
+    fn zip_entry(name: &str, data: &[u8]) -> ZipFile {
+        ZipFile {
+            name: name.to_string(),
+            data: data.to_vec(),
+            mode: 0o644,
+        }
+    }
+
+    fn zip_bytes(entries: &[ZipFile]) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Vec::new());
+        for entry in entries {
+            zip.add_file(&entry.name, &entry.data, ZipMethod::Deflated)
+                .unwrap();
+        }
+        zip.finish().unwrap()
+    }
+
+    /// The writer refuses unsafe names, so hostile archives are built with a
+    /// same-length placeholder that is patched afterwards.
+    fn hostile_zip_bytes(name: &str) -> Vec<u8> {
+        let placeholder = "x".repeat(name.len());
+        let mut data = zip_bytes(&[zip_entry(&placeholder, b"x")]);
+        let mut at = 0;
+        while let Some(pos) = data[at..]
+            .windows(name.len())
+            .position(|w| w == placeholder.as_bytes())
+        {
+            let start = at + pos;
+            data[start..start + name.len()].copy_from_slice(name.as_bytes());
+            at = start + name.len();
+        }
+        data
+    }
+
+    #[test]
+    fn read_zip_entries_round_trips_export() {
+        let data = zip_bytes(&[
+            zip_entry("llm_wiki.md", b"# Root\n"),
+            zip_entry("llm_wiki/topics/a.md", b"# A\n"),
+        ]);
+        let entries = read_zip_entries(&data).unwrap();
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["llm_wiki.md", "llm_wiki/topics/a.md"]);
+        assert_eq!(entries[1].data, b"# A\n");
+    }
+
+    #[test]
+    fn read_zip_entries_rejects_unsafe_entries() {
+        for name in ["../evil.md", "/etc/evil.md", "wiki/notes.txt"] {
+            let data = hostile_zip_bytes(name);
+            assert!(read_zip_entries(&data).is_err(), "{name}");
+        }
+        let data = zip_bytes(&[zip_entry("a.md", b"x"), zip_entry("a.md", b"y")]);
+        assert!(read_zip_entries(&data).is_err());
+    }
+
+    #[test]
+    fn remap_import_entries_renames_single_project() {
+        let mut entries = vec![zip_entry("old.md", b""), zip_entry("old/a/b.md", b"")];
+        remap_import_entries(&mut entries, "new").unwrap();
+        assert_eq!(entries[0].name, "new.md");
+        assert_eq!(entries[1].name, "new/a/b.md");
+
+        let mut mixed = vec![zip_entry("one/a.md", b""), zip_entry("two/b.md", b"")];
+        assert!(remap_import_entries(&mut mixed, "new").is_err());
+    }
+
+    #[test]
+    fn build_import_cmd_backs_up_and_swaps_atomically() {
+        let cmd = build_import_cmd("main", "llm_wiki", "/tmp/import.json", false).unwrap();
+        assert!(cmd.contains("wiki-backups"));
+        assert!(cmd.contains("fs.renameSync(stagedDir, projectDir)"));
+        assert!(cmd.contains("clawmacdo:wiki-index:start"));
+        assert!(cmd.contains("const replace = false;"));
+    }
 }
@@ -0,0 +1,1462 @@
+//! Local conversion of wiki ingest sources (HTML, PDF, DOCX, plain text) to
+//! Markdown, with optional chunking under `MAX_MARKDOWN_BYTES`.
+
+use super::wiki::MAX_MARKDOWN_BYTES;
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use clawmacdo_core::archive::{read_zip, ZipLimits};
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::path::Path;
+
+/// Largest source file accepted before conversion. Only the converted
+/// Markdown has to fit `MAX_MARKDOWN_BYTES` (per chunk).
+pub(crate) const MAX_SOURCE_BYTES: u64 = 50 * 1024 * 1024;
+/// Smallest `--chunk-bytes` value, leaving room for the provenance header.
+const MIN_CHUNK_BYTES: u64 = 4 * 1024;
+/// Space kept free in every chunk for the provenance header.
+const HEADER_RESERVE: usize = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SourceKind {
+    Markdown,
+    Text,
+    Html,
+    Pdf,
+    Docx,
+}
+
+impl SourceKind {
+    pub(crate) fn from_path(path: &Path) -> Result<Self> {
+        let ext = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        Ok(match ext.as_str() {
+            "md" | "markdown" => Self::Markdown,
+            "txt" | "text" => Self::Text,
+            "html" | "htm" | "xhtml" => Self::Html,
+            "pdf" => Self::Pdf,
+            "docx" => Self::Docx,
+            _ => bail!("--source must be a .md, .txt, .html, .pdf or .docx file."),
+        })
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Markdown => "markdown",
+            Self::Text => "text",
+            Self::Html => "html",
+            Self::Pdf => "pdf",
+            Self::Docx => "docx",
+        }
+    }
+}
+
+/// One Markdown document to hand to a single `wiki-ingest` run.
+#[derive(Debug)]
+pub(crate) struct IngestChunk {
+    pub(crate) name: String,
+    pub(crate) data: Vec<u8>,
+}
+
+struct Converted {
+    title: Option<String>,
+    /// Front-matter lines carried over from the source, kept verbatim.
+    front_matter: Vec<String>,
+    body: String,
+}
+
+/// Convert `data` to Markdown and split it into ingestable chunks.
+///
+/// Markdown sources that fit in one document are passed through untouched.
+/// Everything else gets a provenance front-matter header; with `chunk_bytes`
+/// the document is split at headings, then paragraphs, into chunks of at
+/// most that size.
+pub(crate) fn prepare_ingest(
+    path: &Path,
+    data: &[u8],
+    chunk_bytes: Option<u64>,
+) -> Result<Vec<IngestChunk>> {
+    let kind = SourceKind::from_path(path)?;
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("uploaded-doc");
+    let stem = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("uploaded-doc");
+
+    if let Some(bytes) = chunk_bytes {
+        if !(MIN_CHUNK_BYTES..=MAX_MARKDOWN_BYTES).contains(&bytes) {
+            bail!("--chunk-bytes must be between {MIN_CHUNK_BYTES} and {MAX_MARKDOWN_BYTES}.");
+        }
+    }
+    if kind == SourceKind::Markdown && chunk_bytes.is_none() {
+        if data.len() as u64 > MAX_MARKDOWN_BYTES {
+            bail!("--source must be 5 MiB or smaller; pass --chunk-bytes to split it.");
+        }
+        return Ok(vec![IngestChunk {
+            name: format!("{stem}.md"),
+            data: data.to_vec(),
+        }]);
+    }
+
+    let converted = match kind {
+        SourceKind::Markdown | SourceKind::Text => {
+            let text = String::from_utf8_lossy(data);
+            let (front_matter, body) = split_front_matter(&text);
+            let title = front_matter.iter().find_map(|line| {
+                line.strip_prefix("title:")
+                    .map(|t| t.trim().trim_matches('"').trim_matches('\'').to_string())
+            });
+            Converted {
+                title,
+                front_matter,
+                body: body.to_string(),
+            }
+        }
+        SourceKind::Html => html_to_markdown(&String::from_utf8_lossy(data)),
+        SourceKind::Pdf => pdf_to_markdown(data)?,
+        SourceKind::Docx => docx_to_markdown(data)?,
+    };
+    if converted.body.trim().is_empty() {
+        bail!("No text could be extracted from {file_name}.");
+    }
+
+    let limit = chunk_bytes.unwrap_or(MAX_MARKDOWN_BYTES) as usize;
+    let pieces = if chunk_bytes.is_some() {
+        chunk_markdown(&converted.body, limit - HEADER_RESERVE)
+    } else {
+        vec![converted.body.clone()]
+    };
+    let sha = hex::encode(Sha256::digest(data));
+    let converted_at = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    let total = pieces.len();
+
+    let mut chunks = Vec::with_capacity(total);
+    for (i, piece) in pieces.into_iter().enumerate() {
+        let header = provenance_header(
+            &converted,
+            file_name,
+            kind,
+            &sha,
+            &converted_at,
+            (total > 1).then_some((i + 1, total)),
+        );
+        let doc = format!("{header}\n{}\n", piece.trim_end());
+        if doc.len() > limit {
+            bail!(
+                "Converted {file_name} is {} bytes; pass --chunk-bytes to split it into several ingest runs.",
+                doc.len()
+            );
+        }
+        let name = if total > 1 {
+            format!("{stem}-part-{:02}-of-{total:02}.md", i + 1)
+        } else {
+            format!("{stem}.md")
+        };
+        chunks.push(IngestChunk {
+            name,
+            data: doc.into_bytes(),
+        });
+    }
+    Ok(chunks)
+}
+
+fn yaml_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| "\"\"".into())
+}
+
+fn provenance_header(
+    converted: &Converted,
+    file_name: &str,
+    kind: SourceKind,
+    sha: &str,
+    converted_at: &str,
+    chunk: Option<(usize, usize)>,
+) -> String {
+    let mut out = String::from("---\n");
+    if let Some(title) = converted.title.as_deref().filter(|t| !t.is_empty()) {
+        out.push_str(&format!("title: {}\n", yaml_string(title)));
+    }
+    out.push_str(&format!("source: {}\n", yaml_string(file_name)));
+    out.push_str(&format!("source_type: {}\n", kind.label()));
+    out.push_str(&format!("source_sha256: {sha}\n"));
+    out.push_str(&format!("converted_at: {converted_at}\n"));
+    out.push_str(&format!(
+        "converter: clawmacdo {}\n",
+        env!("CARGO_PKG_VERSION")
+    ));
+    if let Some((index, total)) = chunk {
+        out.push_str(&format!("chunk: {index} of {total}\n"));
+    }
+    for line in &converted.front_matter {
+        let key = line.split(':').next().unwrap_or("").trim();
+        if !matches!(
+            key,
+            "title"
+                | "source"
+                | "source_type"
+                | "source_sha256"
+                | "converted_at"
+                | "converter"
+                | "chunk"
+        ) {
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    out.push_str("---\n");
+    out
+}
+
+/// Split a leading `---` front-matter block from the body.
+fn split_front_matter(text: &str) -> (Vec<String>, &str) {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let Some(rest) = text
+        .strip_prefix("---\n")
+        .or_else(|| text.strip_prefix("---\r\n"))
+    else {
+        return (Vec::new(), text);
+    };
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            let lines = rest[..offset]
+                .lines()
+                .filter(|l| !l.trim().is_empty())
+                .map(str::to_string)
+                .collect();
+            return (lines, &rest[offset + line.len()..]);
+        }
+        offset += line.len();
+    }
+    (Vec::new(), text)
+}
+
+// ── Chunking ────────────────────────────────────────────────────────────────
+
+/// Split Markdown into pieces of at most `budget` bytes, preferring
+/// boundaries at headings, then blank lines, then line ends.
+fn chunk_markdown(body: &str, budget: usize) -> Vec<String> {
+    let mut sections: Vec<String> = Vec::new();
+    let mut in_fence = false;
+    for line in body.split_inclusive('\n') {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        }
+        if !in_fence && line.starts_with('#') || sections.is_empty() {
+            sections.push(String::new());
+        }
+        if let Some(last) = sections.last_mut() {
+            last.push_str(line);
+        }
+    }
+
+    let mut pieces: Vec<String> = Vec::new();
+    for section in sections {
+        for part in split_to_budget(&section, budget) {
+            match pieces.last_mut() {
+                Some(last) if last.len() + part.len() <= budget => last.push_str(&part),
+                _ => pieces.push(part),
+            }
+        }
+    }
+    pieces.retain(|p| !p.trim().is_empty());
+    pieces
+}
+
+fn split_to_budget(text: &str, budget: usize) -> Vec<String> {
+    if text.len() <= budget {
+        return vec![text.to_string()];
+    }
+    let mut out: Vec<String> = Vec::new();
+    let units: Vec<&str> = if text.contains("\n\n") {
+        text.split_inclusive("\n\n").collect()
+    } else if text.contains('\n') {
+        text.split_inclusive('\n').collect()
+    } else {
+        let mut parts = Vec::new();
+        let mut rest = text;
+        while rest.len() > budget {
+            let mut cut = budget;
+            while !rest.is_char_boundary(cut) {
+                cut -= 1;
+            }
+            parts.push(&rest[..cut]);
+            rest = &rest[cut..];
+        }
+        parts.push(rest);
+        return parts.into_iter().map(str::to_string).collect();
+    };
+    for unit in units {
+        for part in split_to_budget(unit, budget) {
+            match out.last_mut() {
+                Some(last) if last.len() + part.len() <= budget => last.push_str(&part),
+                _ => out.push(part),
+            }
+        }
+    }
+    out
+}
+
+// ── Shared markup helpers ───────────────────────────────────────────────────
+
+/// Decode HTML/XML character references.
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(at) = rest.find('&') {
+        out.push_str(&rest[..at]);
+        rest = &rest[at..];
+        let Some(end) = rest.as_bytes().iter().take(12).position(|&b| b == b';') else {
+            out.push('&');
+            rest = &rest[1..];
+            continue;
+        };
+        let entity = &rest[1..end];
+        let decoded = if let Some(num) = entity.strip_prefix('#') {
+            let code = match num.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                None => num.parse().ok(),
+            };
+            code.and_then(char::from_u32).map(String::from)
+        } else {
+            match entity {
+                "amp" => Some("&"),
+                "lt" => Some("<"),
+                "gt" => Some(">"),
+                "quot" => Some("\""),
+                "apos" => Some("'"),
+                "nbsp" => Some(" "),
+                "ndash" => Some("–"),
+                "mdash" => Some("—"),
+                "hellip" => Some("…"),
+                "lsquo" => Some("‘"),
+                "rsquo" => Some("’"),
+                "ldquo" => Some("“"),
+                "rdquo" => Some("”"),
+                "bull" | "middot" => Some("·"),
+                "copy" => Some("©"),
+                "reg" => Some("®"),
+                "trade" => Some("™"),
+                _ => None,
+            }
+            .map(String::from)
+        };
+        match decoded {
+            Some(value) => {
+                out.push_str(&value);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Collapse runs of blank lines and trailing spaces left by the converters.
+fn tidy_markdown(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut blank = 0;
+    for line in text.lines() {
+        let line = line.trim_end();
+        if line.trim().is_empty() {
+            blank += 1;
+            if blank > 1 {
+                continue;
+            }
+            out.push('\n');
+        } else {
+            blank = 0;
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    out.trim().to_string()
+}
+
+#[derive(Debug)]
+enum Token {
+    Open {
+        name: String,
+        attrs: Vec<(String, String)>,
+        self_closing: bool,
+    },
+    Close(String),
+    Text(String),
+}
+
+/// Split HTML or XML into tags and text. Comments, doctypes and processing
+/// instructions are dropped; `<script>` and `<style>` bodies are skipped.
+fn tokenize_markup(input: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut rest = input;
+    while !rest.is_empty() {
+        let Some(lt) = rest.find('<') else {
+            tokens.push(Token::Text(rest.to_string()));
+            break;
+        };
+        if lt > 0 {
+            tokens.push(Token::Text(rest[..lt].to_string()));
+        }
+        rest = &rest[lt..];
+        if let Some(after) = rest.strip_prefix("<!--") {
+            rest = after.find("-->").map(|i| &after[i + 3..]).unwrap_or("");
+            continue;
+        }
+        if let Some(after) = rest.strip_prefix("<![CDATA[") {
+            let end = after.find("]]>").unwrap_or(after.len());
+            tokens.push(Token::Text(after[..end].replace('&', "&amp;")));
+            rest = after.get(end + 3..).unwrap_or("");
+            continue;
+        }
+        if rest.starts_with("<!") || rest.starts_with("<?") {
+            rest = rest.find('>').map(|i| &rest[i + 1..]).unwrap_or("");
+            continue;
+        }
+
+        // Find the end of the tag, honouring quoted attribute values.
+        let mut quote = None;
+        let mut end = None;
+        for (i, ch) in rest.char_indices().skip(1) {
+            match (quote, ch) {
+                (Some(q), c) if c == q => quote = None,
+                (Some(_), _) => {}
+                (None, '"' | '\'') => quote = Some(ch),
+                (None, '>') => {
+                    end = Some(i);
+                    break;
+                }
+                _ => {}
+            }
+        }
+        let Some(end) = end else {
+            tokens.push(Token::Text(rest.to_string()));
+            break;
+        };
+        let inner = &rest[1..end];
+        rest = &rest[end + 1..];
+
+        if let Some(name) = inner.strip_prefix('/') {
+            tokens.push(Token::Close(name.trim().to_ascii_lowercase()));
+            continue;
+        }
+        let self_closing = inner.ends_with('/');
+        let inner = inner.trim_end_matches('/');
+        let name_end = inner
+            .find(|c: char| c.is_whitespace())
+            .unwrap_or(inner.len());
+        let name = inner[..name_end].to_ascii_lowercase();
+        if name.is_empty() || !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            tokens.push(Token::Text(format!("<{inner}>")));
+            continue;
+        }
+        let attrs = parse_attrs(&inner[name_end..]);
+
+        if name == "script" || name == "style" {
+            let close = format!("</{name}");
+            let lower = rest.to_ascii_lowercase();
+            rest = match lower.find(&close) {
+                Some(i) => rest[i..]
+                    .find('>')
+                    .map(|j| &rest[i + j + 1..])
+                    .unwrap_or(""),
+                None => "",
+            };
+            continue;
+        }
+        tokens.push(Token::Open {
+            name,
+            attrs,
+            self_closing,
+        });
+    }
+    tokens
+}
+
+fn parse_attrs(input: &str) -> Vec<(String, String)> {
+    let mut attrs = Vec::new();
+    let mut rest = input.trim_start();
+    while !rest.is_empty() {
+        let key_end = rest
+            .find(|c: char| c == '=' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        let key = rest[..key_end].to_ascii_lowercase();
+        rest = rest[key_end..].trim_start();
+        let mut value = String::new();
+        if let Some(after) = rest.strip_prefix('=') {
+            let after = after.trim_start();
+            if let Some(q) = after.chars().next().filter(|c| *c == '"' || *c == '\'') {
+                let body = &after[1..];
+                let close = body.find(q).unwrap_or(body.len());
+                value = decode_entities(&body[..close]);
+                rest = body.get(close + 1..).unwrap_or("");
+            } else {
+                let close = after
+                    .find(|c: char| c.is_whitespace())
+                    .unwrap_or(after.len());
+                value = decode_entities(&after[..close]);
+                rest = &after[close..];
+            }
+        }
+        if !key.is_empty() {
+            attrs.push((key, value));
+        }
+        rest = rest.trim_start();
+    }
+    attrs
+}
+
+fn attr<'a>(attrs: &'a [(String, String)], key: &str) -> Option<&'a str> {
+    attrs
+        .iter()
+        .find(|(k, _)| k == key)
+        .map(|(_, v)| v.as_str())
+}
+
+// ── HTML ────────────────────────────────────────────────────────────────────
+
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+/// Elements that never hold article content.
+const BOILERPLATE_ELEMENTS: &[&str] = &[
+    "nav", "header", "footer", "aside", "form", "noscript", "svg", "iframe", "button", "template",
+    "select", "dialog", "canvas",
+];
+
+/// `class`/`id` words that mark navigation, ads and other page chrome.
+const BOILERPLATE_HINTS: &[&str] = &[
+    "sidebar",
+    "cookie",
+    "cookies",
+    "banner",
+    "advert",
+    "ad",
+    "ads",
+    "promo",
+    "share",
+    "social",
+    "comments",
+    "related",
+    "newsletter",
+    "breadcrumb",
+    "breadcrumbs",
+    "menu",
+    "navbar",
+    "footer",
+    "popup",
+    "modal",
+    "subscribe",
+];
+
+fn is_boilerplate(name: &str, attrs: &[(String, String)]) -> bool {
+    if BOILERPLATE_ELEMENTS.contains(&name) {
+        return true;
+    }
+    if attr(attrs, "hidden").is_some() || attr(attrs, "aria-hidden") == Some("true") {
+        return true;
+    }
+    let hints = format!(
+        "{} {}",
+        attr(attrs, "class").unwrap_or(""),
+        attr(attrs, "id").unwrap_or("")
+    )
+    .to_ascii_lowercase();
+    hints
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .any(|word| BOILERPLATE_HINTS.contains(&word))
+}
+
+/// Index range of the tokens inside the first `<name>` element.
+fn element_range(tokens: &[Token], name: &str) -> Option<(usize, usize)> {
+    let start = tokens
+        .iter()
+        .position(|t| matches!(t, Token::Open { name: n, self_closing: false, .. } if n == name))?;
+    let mut depth = 0;
+    for (i, token) in tokens.iter().enumerate().skip(start) {
+        match token {
+            Token::Open {
+                name: n,
+                self_closing: false,
+                ..
+            } if n == name => depth += 1,
+            Token::Close(n) if n == name => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some((start + 1, i));
+                }
+            }
+            _ => {}
+        }
+    }
+    Some((start + 1, tokens.len()))
+}
+
+fn text_of(tokens: &[Token]) -> String {
+    let text: String = tokens
+        .iter()
+        .filter_map(|t| match t {
+            Token::Text(text) => Some(text.as_str()),
+            _ => None,
+        })
+        .collect();
+    decode_entities(&text)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+struct HtmlWriter {
+    out: String,
+    /// Start offsets of open `<a>` and `<blockquote>` elements in `out`.
+    marks: Vec<(String, usize, String)>,
+    lists: Vec<(bool, usize)>,
+    pre: usize,
+}
+
+impl HtmlWriter {
+    fn block(&mut self) {
+        if !self.out.is_empty() && !self.out.ends_with("\n\n") {
+            if self.out.ends_with('\n') {
+                self.out.push('\n');
+            } else {
+                self.out.push_str("\n\n");
+            }
+        }
+    }
+
+    fn line(&mut self) {
+        if !self.out.is_empty() && !self.out.ends_with('\n') {
+            self.out.push('\n');
+        }
+    }
+
+    fn text(&mut self, raw: &str) {
+        let text = decode_entities(raw);
+        if self.pre > 0 {
+            self.out.push_str(&text);
+            return;
+        }
+        let collapsed: String = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        if collapsed.is_empty() {
+            if text.chars().any(char::is_whitespace)
+                && !self.out.is_empty()
+                && !self.out.ends_with([' ', '\n'])
+            {
+                self.out.push(' ');
+            }
+            return;
+        }
+        if text.starts_with(char::is_whitespace) && !self.out.ends_with([' ', '\n']) {
+            self.out.push(' ');
+        }
+        self.out.push_str(&collapsed);
+        if text.ends_with(char::is_whitespace) {
+            self.out.push(' ');
+        }
+    }
+
+    fn open(&mut self, name: &str, attrs: &[(String, String)]) {
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.block();
+                let level = name[1..].parse::<usize>().unwrap_or(1);
+                self.out.push_str(&"#".repeat(level));
+                self.out.push(' ');
+            }
+            "p" | "div" | "section" | "article" | "main" | "figure" | "table" | "dl" => {
+                self.block()
+            }
+            "ul" | "ol" => {
+                if self.lists.is_empty() {
+                    self.block();
+                } else {
+                    self.line();
+                }
+                self.lists.push((name == "ol", 0));
+            }
+            "li" => {
+                self.line();
+                let depth = self.lists.len().saturating_sub(1);
+                self.out.push_str(&"  ".repeat(depth));
+                match self.lists.last_mut() {
+                    Some((true, n)) => {
+                        *n += 1;
+                        self.out.push_str(&format!("{n}. "));
+                    }
+                    _ => self.out.push_str("- "),
+                }
+            }
+            "tr" | "dt" | "dd" => self.line(),
+            "td" | "th" if !self.out.ends_with('\n') => self.out.push_str(" | "),
+            "br" => self.out.push('\n'),
+            "hr" => {
+                self.block();
+                self.out.push_str("---\n\n");
+            }
+            "pre" => {
+                self.block();
+                self.out.push_str("```\n");
+                self.pre += 1;
+            }
+            "code" if self.pre == 0 => self.out.push('`'),
+            "strong" | "b" => self.out.push_str("**"),
+            "em" | "i" => self.out.push('_'),
+            "img" => {
+                let alt = attr(attrs, "alt").unwrap_or("").trim();
+                let src = attr(attrs, "src").unwrap_or("");
+                if !alt.is_empty() && src.starts_with("http") {
+                    self.out.push_str(&format!("![{alt}]({src})"));
+                }
+            }
+            "a" => {
+                let href = attr(attrs, "href").unwrap_or("").to_string();
+                self.marks.push(("a".into(), self.out.len(), href));
+            }
+            "blockquote" => {
+                self.block();
+                self.marks
+                    .push(("blockquote".into(), self.out.len(), String::new()));
+            }
+            _ => {}
+        }
+    }
+
+    fn close(&mut self, name: &str) {
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "p" | "div" | "section" | "article"
+            | "main" | "figure" | "table" | "dl" => self.block(),
+            "ul" | "ol" => {
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.block();
+                }
+            }
+            "pre" => {
+                self.pre = self.pre.saturating_sub(1);
+                self.line();
+                self.out.push_str("```\n\n");
+            }
+            "code" if self.pre == 0 => self.out.push('`'),
+            "strong" | "b" => self.out.push_str("**"),
+            "em" | "i" => self.out.push('_'),
+            "a" | "blockquote" => {
+                let Some(pos) = self.marks.iter().rposition(|(n, _, _)| n == name) else {
+                    return;
+                };
+                let (_, start, href) = self.marks.remove(pos);
+                let inner = self.out.split_off(start.min(self.out.len()));
+                if name == "a" {
+                    let text = inner.trim();
+                    let keep_link = !href.is_empty()
+                        && !href.starts_with('#')
+                        && !href.starts_with("javascript:");
+                    if keep_link && !text.is_empty() {
+                        self.out.push_str(&format!("[{text}]({href})"));
+                    } else {
+                        self.out.push_str(&inner);
+                    }
+                } else {
+                    for line in inner.trim().lines() {
+                        self.out.push_str("> ");
+                        self.out.push_str(line);
+                        self.out.push('\n');
+                    }
+                    self.block();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Readability-style extraction: keep `<article>` (or `<main>`, or `<body>`),
+/// drop navigation and page chrome, and render the rest as Markdown.
+fn html_to_markdown(html: &str) -> Converted {
+    let tokens = tokenize_markup(html);
+    let title = element_range(&tokens, "title")
+        .map(|(s, e)| text_of(&tokens[s..e]))
+        .filter(|t| !t.is_empty())
+        .or_else(|| {
+            element_range(&tokens, "h1")
+                .map(|(s, e)| text_of(&tokens[s..e]))
+                .filter(|t| !t.is_empty())
+        });
+    let (start, end) = element_range(&tokens, "article")
+        .or_else(|| element_range(&tokens, "main"))
+        .or_else(|| element_range(&tokens, "body"))
+        .unwrap_or((0, tokens.len()));
+
+    let mut writer = HtmlWriter {
+        out: String::new(),
+        marks: Vec::new(),
+        lists: Vec::new(),
+        pre: 0,
+    };
+    let mut skip: Option<(String, usize)> = None;
+    for token in &tokens[start..end] {
+        if let Some((name, depth)) = skip.as_mut() {
+            match token {
+                Token::Open {
+                    name: n,
+                    self_closing: false,
+                    ..
+                } if n == name => *depth += 1,
+                Token::Close(n) if n == name => {
+                    *depth -= 1;
+                    if *depth == 0 {
+                        skip = None;
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+        match token {
+            Token::Open {
+                name,
+                attrs,
+                self_closing,
+            } => {
+                if name == "head" || name == "title" || is_boilerplate(name, attrs) {
+                    if !self_closing && !VOID_ELEMENTS.contains(&name.as_str()) {
+                        skip = Some((name.clone(), 1));
+                    }
+                    continue;
+                }
+                writer.open(name, attrs);
+                if *self_closing && !VOID_ELEMENTS.contains(&name.as_str()) {
+                    writer.close(name);
+                }
+            }
+            Token::Close(name) => writer.close(name),
+            Token::Text(text) => writer.text(text),
+        }
+    }
+
+    Converted {
+        title,
+        front_matter: Vec::new(),
+        body: tidy_markdown(&writer.out),
+    }
+}
+
+// ── DOCX ────────────────────────────────────────────────────────────────────
+
+/// Convert a DOCX file via `word/document.xml`: headings from paragraph
+/// styles, bullets from numbering, table rows as `|`-separated lines.
+fn docx_to_markdown(data: &[u8]) -> Result<Converted> {
+    let limits = ZipLimits {
+        max_entries: 10_000,
+        max_entry_bytes: MAX_SOURCE_BYTES,
+        max_total_bytes: MAX_SOURCE_BYTES,
+    };
+    let entries = read_zip(data, limits, |name| {
+        Ok::<_, anyhow::Error>(
+            matches!(name, "word/document.xml" | "docProps/core.xml").then(|| name.to_string()),
+        )
+    })
+    .context("Not a valid DOCX file")?;
+    let document = entries
+        .iter()
+        .find(|e| e.name == "word/document.xml")
+        .context("DOCX file has no word/document.xml")?;
+    let title = entries
+        .iter()
+        .find(|e| e.name == "docProps/core.xml")
+        .and_then(|core| {
+            let tokens = tokenize_markup(&String::from_utf8_lossy(&core.data));
+            element_range(&tokens, "dc:title").map(|(s, e)| text_of(&tokens[s..e]))
+        })
+        .filter(|t| !t.is_empty());
+
+    let tokens = tokenize_markup(&String::from_utf8_lossy(&document.data));
+    let mut out = String::new();
+    let mut paragraph = String::new();
+    let mut style: Option<String> = None;
+    let mut list = false;
+    let mut in_text = false;
+    let mut table_depth = 0usize;
+    let mut cell = Vec::<String>::new();
+    let mut row = Vec::<String>::new();
+
+    for token in &tokens {
+        match token {
+            Token::Open {
+                name,
+                attrs,
+                self_closing,
+            } => match name.as_str() {
+                "w:p" => {
+                    paragraph.clear();
+                    style = None;
+                    list = false;
+                }
+                "w:pstyle" => style = attr(attrs, "w:val").map(str::to_string),
+                "w:numpr" => list = true,
+                "w:t" if !self_closing => in_text = true,
+                "w:tab" => paragraph.push('\t'),
+                "w:br" | "w:cr" => paragraph.push('\n'),
+                "w:tbl" => table_depth += 1,
+                "w:tr" => row.clear(),
+                "w:tc" => cell.clear(),
+                _ => {}
+            },
+            Token::Close(name) => match name.as_str() {
+                "w:t" => in_text = false,
+                "w:p" => {
+                    let text = paragraph.trim();
+                    if table_depth > 0 {
+                        if !text.is_empty() {
+                            cell.push(text.to_string());
+                        }
+                        continue;
+                    }
+                    if text.is_empty() {
+                        continue;
+                    }
+                    let style = style.as_deref().unwrap_or("").to_ascii_lowercase();
+                    let prefix = if style == "title" {
+                        "# ".to_string()
+                    } else if let Some(level) = style
+                        .strip_prefix("heading")
+                        .and_then(|n| n.parse::<usize>().ok())
+                    {
+                        format!("{} ", "#".repeat(level.clamp(1, 6)))
+                    } else if list || style.contains("list") {
+                        "- ".to_string()
+                    } else {
+                        String::new()
+                    };
+                    out.push_str(&prefix);
+                    out.push_str(text);
+                    out.push_str(if prefix == "- " { "\n" } else { "\n\n" });
+                }
+                "w:tc" => row.push(cell.join(" ")),
+                "w:tr" => {
+                    out.push_str(&format!("| {} |\n", row.join(" | ")));
+                }
+                "w:tbl" => {
+                    table_depth = table_depth.saturating_sub(1);
+                    out.push('\n');
+                }
+                _ => {}
+            },
+            Token::Text(text) if in_text => paragraph.push_str(&decode_entities(text)),
+            Token::Text(_) => {}
+        }
+    }
+
+    Ok(Converted {
+        title,
+        front_matter: Vec::new(),
+        body: tidy_markdown(&out),
+    })
+}
+
+// ── PDF ─────────────────────────────────────────────────────────────────────
+
+fn find_bytes(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    if from >= haystack.len() {
+        return None;
+    }
+    haystack[from..]
+        .windows(needle.len())
+        .position(|w| w == needle)
+        .map(|i| i + from)
+}
+
+fn rfind_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).rposition(|w| w == needle)
+}
+
+/// Decode a PDF string: UTF-16BE with a BOM, otherwise Latin-1 (close enough
+/// to PDFDocEncoding for text extraction).
+fn pdf_string_text(bytes: &[u8]) -> String {
+    if bytes.starts_with(&[0xfe, 0xff]) {
+        let units: Vec<u16> = bytes[2..]
+            .chunks_exact(2)
+            .map(|c| u16::from_be_bytes([c[0], c[1]]))
+            .collect();
+        return String::from_utf16_lossy(&units);
+    }
+    bytes.iter().map(|&b| b as char).collect()
+}
+
+/// Parse a literal string starting after `(`; returns the bytes and the index
+/// after the closing `)`.
+fn pdf_literal(data: &[u8], mut i: usize) -> (Vec<u8>, usize) {
+    let mut out = Vec::new();
+    let mut depth = 1;
+    while i < data.len() {
+        let b = data[i];
+        i += 1;
+        match b {
+            b'\\' if i < data.len() => {
+                let e = data[i];
+                i += 1;
+                match e {
+                    b'n' => out.push(b'\n'),
+                    b'r' => out.push(b'\r'),
+                    b't' => out.push(b'\t'),
+                    b'b' => out.push(8),
+                    b'f' => out.push(12),
+                    b'\r' | b'\n' => {
+                        if e == b'\r' && data.get(i) == Some(&b'\n') {
+                            i += 1;
+                        }
+                    }
+                    b'0'..=b'7' => {
+                        let mut value = (e - b'0') as u32;
+                        for _ in 0..2 {
+                            match data.get(i) {
+                                Some(d @ b'0'..=b'7') => {
+                                    value = value * 8 + (d - b'0') as u32;
+                                    i += 1;
+                                }
+                                _ => break,
+                            }
+                        }
+                        out.push(value as u8);
+                    }
+                    other => out.push(other),
+                }
+            }
+            b'(' => {
+                depth += 1;
+                out.push(b);
+            }
+            b')' => {
+                depth -= 1;
+                if depth == 0 {
+                    break;
+                }
+                out.push(b);
+            }
+            _ => out.push(b),
+        }
+    }
+    (out, i)
+}
+
+fn pdf_hex(data: &[u8], mut i: usize) -> (Vec<u8>, usize) {
+    let mut digits = Vec::new();
+    while i < data.len() && data[i] != b'>' {
+        if data[i].is_ascii_hexdigit() {
+            digits.push(data[i]);
+        }
+        i += 1;
+    }
+    if digits.len() % 2 == 1 {
+        digits.push(b'0');
+    }
+    let bytes = digits
+        .chunks(2)
+        .filter_map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+        .collect();
+    (bytes, i + 1)
+}
+
+#[derive(Debug)]
+enum PdfOperand {
+    Number(f64),
+    Str(Vec<u8>),
+    Array(Vec<PdfOperand>),
+    Other,
+}
+
+/// Extract text from one content stream by interpreting the text-showing
+/// operators (`Tj`, `TJ`, `'`, `"`) and line moves (`Td`, `TD`, `T*`, `Tm`).
+fn pdf_content_text(data: &[u8]) -> String {
+    let mut out = String::new();
+    let mut stack: Vec<PdfOperand> = Vec::new();
+    let mut array: Option<Vec<PdfOperand>> = None;
+    let mut last_y: Option<f64> = None;
+    let mut i = 0;
+
+    let push = |stack: &mut Vec<PdfOperand>, array: &mut Option<Vec<PdfOperand>>, op| match array {
+        Some(items) => items.push(op),
+        None => stack.push(op),
+    };
+    let newline = |out: &mut String| {
+        if !out.is_empty() && !out.ends_with('\n') {
+            out.push('\n');
+        }
+    };
+
+    while i < data.len() {
+        let b = data[i];
+        match b {
+            b'%' => {
+                while i < data.len() && data[i] != b'\n' && data[i] != b'\r' {
+                    i += 1;
+                }
+            }
+            b'(' => {
+                let (bytes, next) = pdf_literal(data, i + 1);
+                push(&mut stack, &mut array, PdfOperand::Str(bytes));
+                i = next;
+            }
+            b'<' if data.get(i + 1) == Some(&b'<') => {
+                push(&mut stack, &mut array, PdfOperand::Other);
+                i += 2;
+            }
+            b'>' if data.get(i + 1) == Some(&b'>') => i += 2,
+            b'<' => {
+                let (bytes, next) = pdf_hex(data, i + 1);
+                push(&mut stack, &mut array, PdfOperand::Str(bytes));
+                i = next;
+            }
+            b'[' => {
+                array = Some(Vec::new());
+                i += 1;
+            }
+            b']' => {
+                if let Some(items) = array.take() {
+                    stack.push(PdfOperand::Array(items));
+                }
+                i += 1;
+            }
+            b'/' => {
+                i += 1;
+                while i < data.len()
+                    && !data[i].is_ascii_whitespace()
+                    && !b"/[]()<>{}%".contains(&data[i])
+                {
+                    i += 1;
+                }
+                push(&mut stack, &mut array, PdfOperand::Other);
+            }
+            b'0'..=b'9' | b'-' | b'+' | b'.' => {
+                let start = i;
+                i += 1;
+                while i < data.len() && (data[i].is_ascii_digit() || data[i] == b'.') {
+                    i += 1;
+                }
+                let value = std::str::from_utf8(&data[start..i])
+                    .ok()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(0.0);
+                push(&mut stack, &mut array, PdfOperand::Number(value));
+            }
+            _ if b.is_ascii_whitespace() => i += 1,
+            _ => {
+                let start = i;
+                while i < data.len()
+                    && !data[i].is_ascii_whitespace()
+                    && !b"/[]()<>{}%".contains(&data[i])
+                {
+                    i += 1;
+                }
+                if i == start {
+                    i += 1;
+                    continue;
+                }
+                let number = |op: Option<&PdfOperand>| match op {
+                    Some(PdfOperand::Number(n)) => *n,
+                    _ => 0.0,
+                };
+                match &data[start..i] {
+                    b"Tj" => {
+                        if let Some(PdfOperand::Str(s)) = stack.last() {
+                            out.push_str(&pdf_string_text(s));
+                        }
+                    }
+                    b"'" | b"\"" => {
+                        newline(&mut out);
+                        if let Some(PdfOperand::Str(s)) = stack.last() {
+                            out.push_str(&pdf_string_text(s));
+                        }
+                    }
+                    b"TJ" => {
+                        if let Some(PdfOperand::Array(items)) = stack.last() {
+                            for item in items {
+                                match item {
+                                    PdfOperand::Str(s) => out.push_str(&pdf_string_text(s)),
+                                    // Large negative kerning is a word gap.
+                                    PdfOperand::Number(n) if *n < -200.0 && !out.ends_with(' ') => {
+                                        out.push(' ')
+                                    }
+                                    _ => {}
+                                }
+                            }
+                        }
+                    }
+                    b"Td" | b"TD" => {
+                        let ty = number(stack.last());
+                        let tx = number(stack.len().checked_sub(2).and_then(|i| stack.get(i)));
+                        if ty.abs() > 0.01 {
+                            newline(&mut out);
+                        } else if tx > 0.0 && !out.ends_with([' ', '\n']) {
+                            out.push(' ');
+                        }
+                    }
+                    b"T*" => newline(&mut out),
+                    b"Tm" => {
+                        let y = number(stack.last());
+                        if last_y.is_some_and(|last| (last - y).abs() > 0.5) {
+                            newline(&mut out);
+                        } else if !out.ends_with([' ', '\n']) && !out.is_empty() {
+                            out.push(' ');
+                        }
+                        last_y = Some(y);
+                    }
+                    b"ET" if !out.ends_with([' ', '\n']) && !out.is_empty() => out.push(' '),
+                    _ => {}
+                }
+                stack.clear();
+            }
+        }
+    }
+    out
+}
+
+/// Extract the text of a PDF from its Flate-compressed or uncompressed
+/// content streams. Scanned PDFs (images only) have no text to extract.
+fn pdf_to_markdown(data: &[u8]) -> Result<Converted> {
+    if !data.starts_with(b"%PDF-") {
+        bail!("Not a PDF file");
+    }
+    if find_bytes(data, b"/Encrypt", 0).is_some() {
+        bail!("Encrypted PDFs are not supported; save an unencrypted copy first.");
+    }
+
+    let mut pages = Vec::new();
+    let mut at = 0;
+    while let Some(pos) = find_bytes(data, b"stream", at) {
+        at = pos + 6;
+        if pos >= 3 && &data[pos - 3..pos] == b"end" {
+            continue;
+        }
+        let mut start = pos + 6;
+        if data.get(start) == Some(&b'\r') {
+            start += 1;
+        }
+        if data.get(start) == Some(&b'\n') {
+            start += 1;
+        }
+        let Some(end) = find_bytes(data, b"endstream", start) else {
+            break;
+        };
+        at = end + 9;
+        let dict_start = rfind_bytes(&data[..pos], b"obj").unwrap_or(0);
+        let dict = &data[dict_start..pos];
+        let skip = [
+            b"/Image".as_slice(),
+            b"/FontFile",
+            b"/Length1",
+            b"/Metadata",
+            b"/ObjStm",
+            b"/XRef",
+            b"/ICCBased",
+            b"/EmbeddedFile",
+        ];
+        if skip.iter().any(|s| find_bytes(dict, s, 0).is_some()) {
+            continue;
+        }
+        let raw = &data[start..end];
+        let content = if find_bytes(dict, b"/FlateDecode", 0).is_some() {
+            let mut out = Vec::new();
+            if flate2::read::ZlibDecoder::new(raw)
+                .take(MAX_SOURCE_BYTES)
+                .read_to_end(&mut out)
+                .is_err()
+                && out.is_empty()
+            {
+                continue;
+            }
+            out
+        } else if find_bytes(dict, b"/Filter", 0).is_some() {
+            continue;
+        } else {
+            raw.to_vec()
+        };
+        if find_bytes(&content, b"BT", 0).is_none() {
+            continue;
+        }
+        let text = pdf_content_text(&content);
+        if !text.trim().is_empty() {
+            pages.push(text);
+        }
+    }
+
+    let title = find_bytes(data, b"/Title", 0).and_then(|pos| {
+        let rest = &data[pos + 6..];
+        let offset = rest.iter().position(|b| !b.is_ascii_whitespace())?;
+        let bytes = match rest[offset] {
+            b'(' => pdf_literal(rest, offset + 1).0,
+            b'<' => pdf_hex(rest, offset + 1).0,
+            _ => return None,
+        };
+        Some(pdf_string_text(&bytes).trim().to_string()).filter(|t| !t.is_empty())
+    });
+
+    let body: Vec<String> = pages
+        .iter()
+        .map(|page| {
+            page.lines()
+                .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .collect();
+    let body = tidy_markdown(&body.join("\n\n"));
+    let printable = body
+        .chars()
+        .filter(|c| !c.is_control() || c.is_whitespace())
+        .count();
+    if body.is_empty() || printable * 10 < body.chars().count() * 9 {
+        bail!(
+            "No readable text found in the PDF. Scanned or custom-encoded PDFs need OCR or a text export first."
+        );
+    }
+    Ok(Converted {
+        title,
+        front_matter: Vec::new(),
+        body,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clawmacdo_core::archive::{ZipMethod, ZipWriter};
+    use std::io::Write;
+
+    #[test]
+    fn decode_entities_handles_multibyte_text_after_ampersand() {
+        let accented = format!("&{}", "é".repeat(8));
+        assert_eq!(decode_entities(&accented), accented);
+        assert_eq!(
+            decode_entities("&amp;日本語のテキスト"),
+            "&日本語のテキスト"
+        );
+        assert_eq!(decode_entities("R&D 研究開発"), "R&D 研究開発");
+    }
+
+    #[test]
+    fn html_to_markdown_keeps_article_and_drops_chrome() {
+        let html = r#"<html><head><title>Guide &amp; Notes</title><style>p{}</style></head>
+<body><nav><a href="/">Home</a></nav>
+<article><h1>Setup</h1><p>Run the <a href="https://example.com/x">installer</a>
+ then <strong>restart</strong>.</p>
+<div class="share-buttons">Share me</div>
+<ul><li>One</li><li>Two</li></ul><script>alert(1)</script>
+<blockquote><p>Quoted</p></blockquote><pre>a  b</pre></article>
+<footer>Footer text</footer></body></html>"#;
+        let converted = html_to_markdown(html);
+        assert_eq!(converted.title.as_deref(), Some("Guide & Notes"));
+        assert_eq!(
+            converted.body,
+            "# Setup\n\nRun the [installer](https://example.com/x) then **restart**.\n\n- One\n- Two\n\n> Quoted\n\n```\na  b\n```"
+        );
+    }
+
+    #[test]
+    fn docx_to_markdown_reads_headings_lists_and_tables() {
+        let xml = r#"<?xml version="1.0"?><w:document><w:body>
+<w:p><w:pPr><w:pStyle w:val="Heading1"/></w:pPr><w:r><w:t>Intro</w:t></w:r></w:p>
+<w:p><w:r><w:t xml:space="preserve">Hello </w:t></w:r><w:r><w:t>world &amp; co</w:t></w:r></w:p>
+<w:p><w:pPr><w:numPr><w:ilvl w:val="0"/></w:numPr></w:pPr><w:r><w:t>Item</w:t></w:r></w:p>
+<w:tbl><w:tr><w:tc><w:p><w:r><w:t>A</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>B</w:t></w:r></w:p></w:tc></w:tr></w:tbl>
+</w:body></w:document>"#;
+        let mut zip = ZipWriter::new(Vec::new());
+        zip.add_file("word/document.xml", xml.as_bytes(), ZipMethod::Deflated)
+            .unwrap();
+        zip.add_file(
+            "docProps/core.xml",
+            b"<cp:coreProperties><dc:title>Report</dc:title></cp:coreProperties>",
+            ZipMethod::Stored,
+        )
+        .unwrap();
+        let data = zip.finish().unwrap();
+
+        let converted = docx_to_markdown(&data).unwrap();
+        assert_eq!(converted.title.as_deref(), Some("Report"));
+        assert_eq!(
+            converted.body,
+            "# Intro\n\nHello world & co\n\n- Item\n| A | B |"
+        );
+    }
+
+    #[test]
+    fn pdf_to_markdown_extracts_flate_text() {
+        let content = b"BT /F1 12 Tf 72 700 Td (Hello) Tj [(Wor) -20 (ld) -300 (again)] TJ 0 -14 Td (Second \\(line\\)) Tj ET";
+        let mut encoder =
+            flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(content).unwrap();
+        let compressed = encoder.finish().unwrap();
+        let mut pdf = b"%PDF-1.4\n1 0 obj << /Title (Quarterly) >> endobj\n".to_vec();
+        pdf.extend_from_slice(
+            format!(
+                "4 0 obj << /Length {} /Filter /FlateDecode >>\nstream\n",
+                compressed.len()
+            )
+            .as_bytes(),
+        );
+        pdf.extend_from_slice(&compressed);
+        pdf.extend_from_slice(b"\nendstream\nendobj\n%%EOF\n");
+
+        let converted = pdf_to_markdown(&pdf).unwrap();
+        assert_eq!(converted.title.as_deref(), Some("Quarterly"));
+        assert_eq!(converted.body, "HelloWorld again\nSecond (line)");
+        assert!(pdf_to_markdown(b"%PDF-1.4\n%%EOF").is_err());
+    }
+
+    #[test]
+    fn prepare_ingest_chunks_with_provenance_headers() {
+        let body: String = (0..40)
+            .map(|i| format!("## Section {i}\n\n{}\n\n", "word ".repeat(60)))
+            .collect();
+        let text = format!("---\ntitle: Notes\ntags: [a]\n---\n{body}");
+        let chunks =
+            prepare_ingest(Path::new("/tmp/notes.txt"), text.as_bytes(), Some(4096)).unwrap();
+        assert!(chunks.len() > 1);
+        for (i, chunk) in chunks.iter().enumerate() {
+            let doc = String::from_utf8(chunk.data.clone()).unwrap();
+            assert!(chunk.data.len() <= 4096);
+            assert!(doc
+                .starts_with("---\ntitle: \"Notes\"\nsource: \"notes.txt\"\nsource_type: text\n"));
+            assert!(doc.contains(&format!("chunk: {} of {}\n", i + 1, chunks.len())));
+            assert!(doc.contains("tags: [a]\n"));
+            assert_eq!(
+                chunk.name,
+                format!("notes-part-{:02}-of-{:02}.md", i + 1, chunks.len())
+            );
+        }
+        let joined: String = chunks
+            .iter()
+            .map(|c| {
+                String::from_utf8_lossy(&c.data)
+                    .matches("## Section")
+                    .count()
+            })
+            .sum::<usize>()
+            .to_string();
+        assert_eq!(joined, "40");
+    }
+
+    #[test]
+    fn prepare_ingest_passes_markdown_through() {
+        let chunks = prepare_ingest(Path::new("a/doc.md"), b"# Doc\n", None).unwrap();
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].name, "doc.md");
+        assert_eq!(chunks[0].data, b"# Doc\n");
+        assert!(prepare_ingest(Path::new("a/doc.rtf"), b"x", None).is_err());
+        assert!(prepare_ingest(Path::new("a/doc.md"), b"x", Some(10)).is_err());
+    }
+}
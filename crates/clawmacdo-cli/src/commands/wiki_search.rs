@@ -0,0 +1,500 @@
+use super::wiki::{
+    clean_agent_id, clean_instance, clean_project_slug, collect_manifest, find_record, print_json,
+};
+use anyhow::{bail, Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use clawmacdo_db as db;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+pub struct WikiSearchParams {
+    pub instance: String,
+    pub agent: String,
+    pub project: String,
+    pub query: String,
+    pub limit: u32,
+    pub no_refresh: bool,
+    pub json: bool,
+}
+
+pub struct WikiGraphParams {
+    pub instance: String,
+    pub agent: String,
+    pub project: String,
+    pub format: String,
+    pub no_refresh: bool,
+}
+
+/// Outcome of bringing the local index in line with the instance.
+#[derive(Debug, Default, Serialize)]
+pub struct RefreshStats {
+    pub indexed: usize,
+    pub removed: usize,
+    pub unchanged: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct BrokenLink {
+    pub from: String,
+    pub target: String,
+}
+
+/// Link graph of a wiki project.
+#[derive(Debug, Default, Serialize)]
+pub struct WikiGraph {
+    pub nodes: Vec<String>,
+    pub edges: Vec<GraphEdge>,
+    pub broken: Vec<BrokenLink>,
+    /// Pages no other page links to, apart from the project entry points.
+    pub orphans: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GraphFormat {
+    Dot,
+    Json,
+}
+
+fn clean_graph_format(value: &str) -> Result<GraphFormat> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "dot" => Ok(GraphFormat::Dot),
+        "json" => Ok(GraphFormat::Json),
+        _ => bail!("--format must be dot or json"),
+    }
+}
+
+/// Title of a page: its first heading, or the file name without `.md`.
+fn page_title(path: &str, text: &str) -> String {
+    text.lines()
+        .find_map(|line| {
+            let rest = line.strip_prefix('#')?.trim_start_matches('#');
+            rest.starts_with(char::is_whitespace)
+                .then(|| rest.trim().trim_end_matches('#').trim().to_string())
+                .filter(|t| !t.is_empty())
+        })
+        .unwrap_or_else(|| {
+            let name = path.rsplit('/').next().unwrap_or(path);
+            name.trim_end_matches(".md").to_string()
+        })
+}
+
+/// Re-index the pages whose sha256 changed since the last refresh and drop
+/// pages that no longer exist. Page content is only fetched when something
+/// changed.
+async fn refresh_index(
+    instance: &str,
+    key: &str,
+    agent: &str,
+    project: &str,
+) -> Result<RefreshStats> {
+    let (manifest, _) = collect_manifest(instance, agent, project, false, false).await?;
+    let remote: BTreeMap<String, String> = manifest
+        .files
+        .iter()
+        .map(|f| (f.path.clone(), f.sha256.clone()))
+        .collect();
+
+    let mut conn = db::init_db()?;
+    let stored = db::wiki_page_hashes(&conn, key, agent, project)?;
+    let changed: BTreeSet<&str> = remote
+        .iter()
+        .filter(|(path, sha)| stored.get(*path) != Some(sha))
+        .map(|(path, _)| path.as_str())
+        .collect();
+    let removed: Vec<&String> = stored.keys().filter(|p| !remote.contains_key(*p)).collect();
+
+    let mut pages = Vec::new();
+    if !changed.is_empty() {
+        let (full, _) = collect_manifest(instance, agent, project, true, false).await?;
+        for file in full.files {
+            if !changed.contains(file.path.as_str()) {
+                continue;
+            }
+            let data = BASE64
+                .decode(file.data.as_deref().unwrap_or_default())
+                .with_context(|| format!("Failed to decode {}", file.path))?;
+            let body = String::from_utf8_lossy(&data).into_owned();
+            pages.push((file.path, file.sha256, body));
+        }
+    }
+
+    let tx = conn.transaction()?;
+    for path in &removed {
+        db::delete_wiki_page(&tx, key, agent, project, path)?;
+    }
+    for (path, sha, body) in &pages {
+        let title = page_title(path, body);
+        db::upsert_wiki_page(&tx, key, agent, project, path, sha, &title, body)?;
+    }
+    tx.commit()?;
+
+    Ok(RefreshStats {
+        indexed: pages.len(),
+        removed: removed.len(),
+        unchanged: remote.len() - changed.len(),
+    })
+}
+
+/// Targets of `[[wikilinks]]`, without `|alias` or `#heading` suffixes.
+fn wikilink_targets(text: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("[[") {
+        rest = &rest[start + 2..];
+        let Some(end) = rest.find("]]") else { break };
+        let inner = &rest[..end];
+        rest = &rest[end + 2..];
+        if inner.contains('\n') {
+            continue;
+        }
+        let target = inner.split('|').next().unwrap_or("");
+        let target = target.split('#').next().unwrap_or("").trim();
+        if !target.is_empty() {
+            out.push(target.to_string());
+        }
+    }
+    out
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = |b: u8| (b as char).to_digit(16);
+            if let (Some(hi), Some(lo)) = (hex(bytes[i + 1]), hex(bytes[i + 2])) {
+                out.push((hi * 16 + lo) as u8);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Relative Markdown links to other `.md` pages, resolved against the
+/// linking page's directory. External URLs and anchors are ignored.
+fn markdown_link_targets(from: &str, text: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("](") {
+        rest = &rest[start + 2..];
+        let Some(end) = rest.find(')') else { break };
+        let raw = rest[..end].trim();
+        rest = &rest[end + 1..];
+        let link = raw.split('#').next().unwrap_or("").trim();
+        if link.is_empty()
+            || link.contains("://")
+            || link.starts_with("mailto:")
+            || !link.to_ascii_lowercase().ends_with(".md")
+        {
+            continue;
+        }
+        let mut segments: Vec<&str> = if link.starts_with('/') {
+            Vec::new()
+        } else {
+            from.split('/').collect()
+        };
+        segments.pop();
+        let decoded = percent_decode(link);
+        let mut resolved: Vec<String> = segments.iter().map(|s| s.to_string()).collect();
+        for segment in decoded.split('/') {
+            match segment {
+                "" | "." => {}
+                ".." => {
+                    resolved.pop();
+                }
+                other => resolved.push(other.to_string()),
+            }
+        }
+        out.push(resolved.join("/"));
+    }
+    out
+}
+
+/// Resolve a wikilink target to a page path: an exact workspace path, a path
+/// inside the project, or a unique-enough trailing path such as a file name.
+fn resolve_wikilink(target: &str, pages: &[String], project: &str) -> Option<String> {
+    let wanted = target.trim_end_matches(".md").to_lowercase();
+    let stem = |page: &String| page.trim_end_matches(".md").to_lowercase();
+    let in_project = format!("{}/{wanted}", project.to_lowercase());
+    let suffix = format!("/{wanted}");
+    pages
+        .iter()
+        .find(|p| stem(p) == wanted)
+        .or_else(|| pages.iter().find(|p| stem(p) == in_project))
+        .or_else(|| pages.iter().find(|p| stem(p).ends_with(&suffix)))
+        .cloned()
+}
+
+/// Build the link graph from page bodies keyed by path.
+pub fn build_graph(pages: &[(String, String)], project: &str) -> WikiGraph {
+    let nodes: Vec<String> = pages.iter().map(|(path, _)| path.clone()).collect();
+    let known: BTreeSet<&str> = nodes.iter().map(String::as_str).collect();
+    let mut edges = BTreeSet::new();
+    let mut broken = BTreeSet::new();
+
+    for (from, body) in pages {
+        for target in wikilink_targets(body) {
+            match resolve_wikilink(&target, &nodes, project) {
+                Some(to) if &to != from => {
+                    edges.insert(GraphEdge {
+                        from: from.clone(),
+                        to,
+                    });
+                }
+                Some(_) => {}
+                None => {
+                    broken.insert(BrokenLink {
+                        from: from.clone(),
+                        target,
+                    });
+                }
+            }
+        }
+        for to in markdown_link_targets(from, body) {
+            if &to == from {
+                continue;
+            }
+            if known.contains(to.as_str()) {
+                edges.insert(GraphEdge {
+                    from: from.clone(),
+                    to,
+                });
+            } else {
+                broken.insert(BrokenLink {
+                    from: from.clone(),
+                    target: to,
+                });
+            }
+        }
+    }
+
+    let linked: BTreeSet<&str> = edges.iter().map(|e| e.to.as_str()).collect();
+    let entry_points = [format!("{project}.md"), format!("{project}/INDEX.md")];
+    let orphans = nodes
+        .iter()
+        .filter(|n| !linked.contains(n.as_str()) && !entry_points.contains(n))
+        .cloned()
+        .collect();
+
+    WikiGraph {
+        nodes,
+        edges: edges.into_iter().collect(),
+        broken: broken.into_iter().collect(),
+        orphans,
+    }
+}
+
+fn dot_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Render the graph as Graphviz DOT. Broken links point at red dashed
+/// placeholder nodes and orphan pages are drawn in orange.
+pub fn render_dot(graph: &WikiGraph, project: &str) -> String {
+    let orphans: BTreeSet<&str> = graph.orphans.iter().map(String::as_str).collect();
+    let mut out = format!("digraph {} {{\n", dot_quote(project));
+    out.push_str("  rankdir=LR;\n  node [shape=box, fontname=\"Helvetica\"];\n");
+    for node in &graph.nodes {
+        if orphans.contains(node.as_str()) {
+            out.push_str(&format!("  {} [color=orange];\n", dot_quote(node)));
+        } else {
+            out.push_str(&format!("  {};\n", dot_quote(node)));
+        }
+    }
+    for edge in &graph.edges {
+        out.push_str(&format!(
+            "  {} -> {};\n",
+            dot_quote(&edge.from),
+            dot_quote(&edge.to)
+        ));
+    }
+    for link in &graph.broken {
+        let placeholder = dot_quote(&format!("missing: {}", link.target));
+        out.push_str(&format!(
+            "  {placeholder} [color=red, fontcolor=red, style=dashed];\n  {} -> {placeholder} [color=red, style=dashed];\n",
+            dot_quote(&link.from)
+        ));
+    }
+    out.push_str("}\n");
+    out
+}
+
+pub async fn search(params: WikiSearchParams) -> Result<()> {
+    let instance = clean_instance(&params.instance)?;
+    let agent = clean_agent_id(&params.agent)?;
+    let project = clean_project_slug(&params.project)?;
+    if params.query.trim().is_empty() {
+        bail!("Search query must not be empty");
+    }
+    let limit = params.limit.clamp(1, 200);
+    // The index is keyed by deploy ID so it survives IP changes.
+    let key = find_record(&instance)?.id;
+
+    let refresh = if params.no_refresh {
+        None
+    } else {
+        Some(refresh_index(&instance, &key, &agent, &project).await?)
+    };
+    let conn = db::init_db()?;
+    let results = db::search_wiki_pages(&conn, &key, &agent, &project, &params.query, limit)?;
+
+    if params.json {
+        return print_json(&serde_json::json!({
+            "ok": true,
+            "project": project,
+            "query": params.query,
+            "refresh": refresh,
+            "results": results,
+        }));
+    }
+
+    if let Some(refresh) = &refresh {
+        println!(
+            "Index: {} updated, {} removed, {} unchanged",
+            refresh.indexed, refresh.removed, refresh.unchanged
+        );
+    }
+    if results.is_empty() {
+        println!("No matches for \"{}\" in {project}.", params.query);
+        return Ok(());
+    }
+    for (i, row) in results.iter().enumerate() {
+        println!("{:>2}. {}  ({})", i + 1, row.title, row.path);
+        println!("    {}", row.snippet.replace('\n', " "));
+    }
+    Ok(())
+}
+
+pub async fn graph(params: WikiGraphParams) -> Result<()> {
+    let instance = clean_instance(&params.instance)?;
+    let agent = clean_agent_id(&params.agent)?;
+    let project = clean_project_slug(&params.project)?;
+    let format = clean_graph_format(&params.format)?;
+    let key = find_record(&instance)?.id;
+
+    if !params.no_refresh {
+        refresh_index(&instance, &key, &agent, &project).await?;
+    }
+    let conn = db::init_db()?;
+    let pages = db::wiki_page_bodies(&conn, &key, &agent, &project)?;
+    if pages.is_empty() {
+        bail!("No indexed pages for project '{project}'. Run without --no-refresh first.");
+    }
+    let graph = build_graph(&pages, &project);
+
+    match format {
+        GraphFormat::Json => print_json(&serde_json::json!({
+            "ok": true,
+            "project": project,
+            "nodes": graph.nodes,
+            "edges": graph.edges,
+            "broken": graph.broken,
+            "orphans": graph.orphans,
+        })),
+        GraphFormat::Dot => {
+            print!("{}", render_dot(&graph, &project));
+            // Keep stdout pipeable into `dot`; the report goes to stderr.
+            eprintln!(
+                "{} page(s), {} link(s), {} broken, {} orphan(s)",
+                graph.nodes.len(),
+                graph.edges.len(),
+                graph.broken.len(),
+                graph.orphans.len()
+            );
+            for link in &graph.broken {
+                eprintln!("  broken: {} -> {}", link.from, link.target);
+            }
+            for orphan in &graph.orphans {
+                eprintln!("  orphan: {orphan}");
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pages(entries: &[(&str, &str)]) -> Vec<(String, String)> {
+        entries
+            .iter()
+            .map(|(p, b)| (p.to_string(), b.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn page_title_prefers_first_heading() {
+        assert_eq!(page_title("w/a.md", "intro\n## Setup ##\n# Later"), "Setup");
+        assert_eq!(page_title("w/notes.md", "#tag only"), "notes");
+    }
+
+    #[test]
+    fn wikilink_targets_strip_alias_and_heading() {
+        assert_eq!(
+            wikilink_targets("See [[Gateway|the gateway]] and [[w/setup#Install]] or [[ ]]."),
+            vec!["Gateway", "w/setup"]
+        );
+    }
+
+    #[test]
+    fn build_graph_reports_broken_links_and_orphans() {
+        let graph = build_graph(
+            &pages(&[
+                ("w.md", "[[INDEX]]"),
+                (
+                    "w/INDEX.md",
+                    "- [Gateway](topics/gateway%20token.md)\n[[missing page]]",
+                ),
+                (
+                    "w/topics/gateway token.md",
+                    "[[w]] [[gateway token]] [x](../nope.md)",
+                ),
+                ("w/lonely.md", "[site](https://example.com/a.md)"),
+            ]),
+            "w",
+        );
+        assert_eq!(
+            graph.edges,
+            vec![
+                GraphEdge {
+                    from: "w.md".into(),
+                    to: "w/INDEX.md".into()
+                },
+                GraphEdge {
+                    from: "w/INDEX.md".into(),
+                    to: "w/topics/gateway token.md".into()
+                },
+                GraphEdge {
+                    from: "w/topics/gateway token.md".into(),
+                    to: "w.md".into()
+                },
+            ]
+        );
+        let broken: Vec<&str> = graph.broken.iter().map(|b| b.target.as_str()).collect();
+        assert_eq!(broken, vec!["missing page", "w/nope.md"]);
+        assert_eq!(graph.orphans, vec!["w/lonely.md"]);
+    }
+
+    #[test]
+    fn render_dot_marks_broken_and_orphans() {
+        let graph = build_graph(&pages(&[("w/a.md", "[[b\"c]]"), ("w/z.md", "")]), "w");
+        let dot = render_dot(&graph, "w");
+        assert!(dot.starts_with("digraph \"w\" {"));
+        assert!(dot.contains("\"missing: b\\\"c\" [color=red"));
+        assert!(dot.contains("\"w/z.md\" [color=orange];"));
+        assert!(clean_graph_format("svg").is_err());
+    }
+}
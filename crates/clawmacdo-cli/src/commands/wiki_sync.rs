@@ -0,0 +1,838 @@
+use super::wiki::{
+    clean_agent_id, clean_instance, clean_project_slug, clean_relative_markdown_path,
+    collect_manifest, find_deploy_record, handle_remote_status, js_string, print_json,
+    remote_json_value, ssh_user_for_provider, MAX_MARKDOWN_BYTES,
+};
+use anyhow::{bail, Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use clawmacdo_core::config;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+/// Per-directory record of the sha256 each file had after the last sync.
+const STATE_FILE: &str = ".clawmacdo-wiki-sync.json";
+/// Suffix of the file holding the instance's copy of a conflicting page.
+const CONFLICT_SUFFIX: &str = ".conflict";
+
+pub struct WikiSyncParams {
+    pub instance: String,
+    pub agent: String,
+    pub project: String,
+    pub dir: PathBuf,
+    pub dry_run: bool,
+    pub prefer: Option<String>,
+    pub json: bool,
+}
+
+/// Which side wins when both changed since the last sync.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Prefer {
+    Local,
+    Remote,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncAction {
+    Push,
+    Pull,
+    DeleteLocal,
+    DeleteRemote,
+    /// Both sides changed; the instance copy is written beside the local file.
+    Conflict,
+    /// A `.conflict` file from an earlier run is still present.
+    Unresolved,
+}
+
+impl SyncAction {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Push => "push",
+            Self::Pull => "pull",
+            Self::DeleteLocal => "delete-local",
+            Self::DeleteRemote => "delete-remote",
+            Self::Conflict => "conflict",
+            Self::Unresolved => "unresolved",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SyncItem {
+    pub path: String,
+    pub action: SyncAction,
+    pub local_sha: Option<String>,
+    pub remote_sha: Option<String>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SyncPlan {
+    pub items: Vec<SyncItem>,
+    /// Paths identical on both sides.
+    pub unchanged: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SyncState {
+    instance: String,
+    agent: String,
+    project: String,
+    synced_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    files: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Serialize)]
+struct PushPayload {
+    writes: Vec<PayloadWrite>,
+    deletes: Vec<PayloadDelete>,
+}
+
+#[derive(Debug, Serialize)]
+struct PayloadWrite {
+    path: String,
+    base_sha: String,
+    data: String,
+}
+
+#[derive(Debug, Serialize)]
+struct PayloadDelete {
+    path: String,
+    base_sha: String,
+}
+
+#[derive(Debug, Serialize)]
+struct ItemOutcome {
+    path: String,
+    action: SyncAction,
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+}
+
+fn clean_prefer(value: Option<&str>) -> Result<Option<Prefer>> {
+    match value.map(str::trim) {
+        None | Some("") => Ok(None),
+        Some(v) if v.eq_ignore_ascii_case("local") => Ok(Some(Prefer::Local)),
+        Some(v) if v.eq_ignore_ascii_case("remote") => Ok(Some(Prefer::Remote)),
+        Some(v) => bail!("--prefer must be 'local' or 'remote', got '{v}'."),
+    }
+}
+
+fn in_project(path: &str, project: &str) -> bool {
+    path == format!("{project}.md") || path.starts_with(&format!("{project}/"))
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+fn conflict_path(dir: &Path, rel: &str) -> PathBuf {
+    dir.join(format!("{rel}{CONFLICT_SUFFIX}"))
+}
+
+/// Local files of `project` under `dir`: relative path -> sha256, plus pages
+/// that still have a `.conflict` file next to them. Hidden entries and symlinks
+/// are skipped, as on the instance.
+fn scan_local(dir: &Path, project: &str) -> Result<(BTreeMap<String, String>, BTreeSet<String>)> {
+    let mut files = BTreeMap::new();
+    let mut unresolved = BTreeSet::new();
+    if !dir.exists() {
+        return Ok((files, unresolved));
+    }
+    let mut stack = vec![dir.to_path_buf()];
+    while let Some(current) = stack.pop() {
+        for entry in std::fs::read_dir(&current)
+            .with_context(|| format!("Failed to read {}", current.display()))?
+        {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with('.') {
+                continue;
+            }
+            let file_type = entry.file_type()?;
+            if file_type.is_symlink() {
+                continue;
+            }
+            let path = entry.path();
+            if file_type.is_dir() {
+                stack.push(path);
+                continue;
+            }
+            let rel = path
+                .strip_prefix(dir)
+                .unwrap_or(&path)
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+                .join("/");
+            if let Some(page) = rel.strip_suffix(CONFLICT_SUFFIX) {
+                if in_project(page, project) {
+                    unresolved.insert(page.to_string());
+                }
+                continue;
+            }
+            if !name.to_ascii_lowercase().ends_with(".md") || !in_project(&rel, project) {
+                continue;
+            }
+            let rel = clean_relative_markdown_path(&rel)
+                .with_context(|| format!("Unsupported local wiki path {}", path.display()))?;
+            let metadata = entry.metadata()?;
+            if metadata.len() > MAX_MARKDOWN_BYTES {
+                bail!("{} is larger than 5 MiB.", path.display());
+            }
+            files.insert(rel, sha256_hex(&std::fs::read(&path)?));
+        }
+    }
+    Ok((files, unresolved))
+}
+
+/// Decide what to do with every page given local and remote hashes and the
+/// hashes recorded at the last sync (`base`).
+pub fn plan_sync(
+    local: &BTreeMap<String, String>,
+    remote: &BTreeMap<String, String>,
+    base: &BTreeMap<String, String>,
+    unresolved: &BTreeSet<String>,
+    prefer: Option<Prefer>,
+) -> SyncPlan {
+    let mut plan = SyncPlan::default();
+    let paths: BTreeSet<&String> = local
+        .keys()
+        .chain(remote.keys())
+        .chain(unresolved.iter())
+        .collect();
+
+    for path in paths {
+        let l = local.get(path);
+        let r = remote.get(path);
+        let b = base.get(path);
+
+        if unresolved.contains(path) {
+            plan.items.push(SyncItem {
+                path: path.clone(),
+                action: SyncAction::Unresolved,
+                local_sha: l.cloned(),
+                remote_sha: r.cloned(),
+            });
+            continue;
+        }
+
+        let action = match (l, r) {
+            (None, None) => continue,
+            (Some(l), Some(r)) if l == r => {
+                plan.unchanged.insert(path.clone(), l.clone());
+                continue;
+            }
+            (Some(l), Some(r)) => {
+                if b == Some(l) {
+                    SyncAction::Pull
+                } else if b == Some(r) {
+                    SyncAction::Push
+                } else {
+                    match prefer {
+                        Some(Prefer::Local) => SyncAction::Push,
+                        Some(Prefer::Remote) => SyncAction::Pull,
+                        None => SyncAction::Conflict,
+                    }
+                }
+            }
+            (Some(l), None) => match b {
+                None => SyncAction::Push,
+                Some(b) if b == l => SyncAction::DeleteLocal,
+                Some(_) => match prefer {
+                    Some(Prefer::Local) => SyncAction::Push,
+                    Some(Prefer::Remote) => SyncAction::DeleteLocal,
+                    None => SyncAction::Conflict,
+                },
+            },
+            (None, Some(r)) => match b {
+                None => SyncAction::Pull,
+                Some(b) if b == r => SyncAction::DeleteRemote,
+                Some(_) => match prefer {
+                    Some(Prefer::Local) => SyncAction::DeleteRemote,
+                    Some(Prefer::Remote) => SyncAction::Pull,
+                    None => SyncAction::Conflict,
+                },
+            },
+        };
+        plan.items.push(SyncItem {
+            path: path.clone(),
+            action,
+            local_sha: l.cloned(),
+            remote_sha: r.cloned(),
+        });
+    }
+    plan
+}
+
+/// Apply pushes and remote deletes in one pass. Every write and delete is a
+/// compare-and-swap against the sha256 seen in the manifest, like `wiki-write --base-sha`.
+fn build_apply_cmd(agent: &str, project: &str, upload_tmp: &str) -> Result<String> {
+    let home = config::OPENCLAW_HOME;
+    let mut template = r#"set -e
+export HOME="__HOME__"
+export PATH="__HOME__/.local/bin:__HOME__/.local/share/pnpm:/usr/local/bin:/usr/bin:/bin:$PATH"
+
+node <<'NODE'
+const fs = require('fs');
+const path = require('path');
+const crypto = require('crypto');
+
+const home = process.env.HOME || '__HOME__';
+const configPath = path.join(home, '.openclaw', 'openclaw.json');
+const agentId = __AGENT_JSON__;
+const project = __PROJECT_JSON__;
+const uploadTmp = __UPLOAD_TMP_JSON__;
+const maxBytes = __MAX_BYTES__;
+
+function fail(code, message, extra = {}) {
+  console.log(JSON.stringify({ ok: false, error: { code, message }, ...extra }));
+  process.exit(0);
+}
+
+function readJson(file) {
+  try {
+    return JSON.parse(fs.readFileSync(file, 'utf8'));
+  } catch (_) {
+    return {};
+  }
+}
+
+function expandWorkspace(raw) {
+  let value = typeof raw === 'string' && raw.trim()
+    ? raw.trim()
+    : path.join(home, '.openclaw', 'workspace');
+  if (value === '~') value = home;
+  if (value.startsWith('~/')) value = path.join(home, value.slice(2));
+  if (value.startsWith('$HOME/')) value = path.join(home, value.slice(6));
+  if (value.startsWith('${HOME}/')) value = path.join(home, value.slice(8));
+  if (!path.isAbsolute(value)) value = path.join(home, value);
+  return path.normalize(value);
+}
+
+function isWithin(root, target) {
+  const rel = path.relative(root, target);
+  return rel === '' || (!!rel && !rel.startsWith('..') && !path.isAbsolute(rel));
+}
+
+function sha256Bytes(data) {
+  return crypto.createHash('sha256').update(data).digest('hex');
+}
+
+const cfg = readJson(configPath);
+const agents = cfg.agents || {};
+const list = Array.isArray(agents.list) ? agents.list : [];
+const agent = list.find((item) => item && item.id === agentId);
+const workspace = expandWorkspace(
+  agent && agent.workspace
+    ? agent.workspace
+    : agents.defaults && agents.defaults.workspace
+);
+
+let payload;
+try {
+  payload = JSON.parse(fs.readFileSync(uploadTmp, 'utf8'));
+} catch (_) {
+  fail('upload_not_found', 'Uploaded sync payload was not found on the instance.');
+} finally {
+  try { fs.unlinkSync(uploadTmp); } catch (_) {}
+}
+
+if (!fs.existsSync(workspace) || !fs.statSync(workspace).isDirectory()) {
+  fail('workspace_not_found', `OpenClaw workspace not found: ${workspace}`);
+}
+const workspaceReal = fs.realpathSync(workspace);
+
+function resolveTarget(rel) {
+  if (typeof rel !== 'string' || !rel.toLowerCase().endsWith('.md')) {
+    throw new Error('Only Markdown .md files can be synced.');
+  }
+  if (rel !== `${project}.md` && !rel.startsWith(`${project}/`)) {
+    throw new Error('Path is outside the wiki project.');
+  }
+  if (rel.split('/').some((s) => !s || s === '.' || s === '..')) {
+    throw new Error('Path contains empty, . or .. segments.');
+  }
+  const target = path.resolve(workspaceReal, rel);
+  if (!isWithin(workspaceReal, target)) {
+    throw new Error('Path escapes the OpenClaw workspace allowlist.');
+  }
+  return target;
+}
+
+function currentSha(target) {
+  if (!fs.existsSync(target)) return null;
+  const stat = fs.lstatSync(target);
+  if (stat.isSymbolicLink() || !stat.isFile()) {
+    throw new Error('Path is not a regular Markdown file.');
+  }
+  if (!isWithin(workspaceReal, fs.realpathSync(target))) {
+    throw new Error('Path escapes the OpenClaw workspace allowlist.');
+  }
+  return sha256Bytes(fs.readFileSync(target));
+}
+
+const results = [];
+for (const item of payload.writes || []) {
+  try {
+    const target = resolveTarget(item.path);
+    const data = Buffer.from(item.data || '', 'base64');
+    if (data.length > maxBytes) throw new Error(`Markdown content exceeds ${maxBytes} bytes.`);
+    const current = currentSha(target);
+    const expected = item.base_sha === 'NEW' ? null : item.base_sha;
+    if (current !== expected) {
+      results.push({ path: item.path, ok: false, message: 'changed on the instance since the manifest was read', current_sha: current });
+      continue;
+    }
+    fs.mkdirSync(path.dirname(target), { recursive: true });
+    if (!isWithin(workspaceReal, fs.realpathSync(path.dirname(target)))) {
+      throw new Error('Parent directory escapes the OpenClaw workspace allowlist.');
+    }
+    const tmp = `${target}.clawmacdo-sync-${process.pid}`;
+    fs.writeFileSync(tmp, data, { mode: 0o644 });
+    fs.renameSync(tmp, target);
+    results.push({ path: item.path, ok: true, sha256: sha256Bytes(data) });
+  } catch (err) {
+    results.push({ path: item.path, ok: false, message: String(err && err.message || err) });
+  }
+}
+for (const item of payload.deletes || []) {
+  try {
+    const target = resolveTarget(item.path);
+    const current = currentSha(target);
+    if (current === null) {
+      results.push({ path: item.path, ok: true });
+      continue;
+    }
+    if (current !== item.base_sha) {
+      results.push({ path: item.path, ok: false, message: 'changed on the instance since the manifest was read', current_sha: current });
+      continue;
+    }
+    fs.unlinkSync(target);
+    results.push({ path: item.path, ok: true });
+  } catch (err) {
+    results.push({ path: item.path, ok: false, message: String(err && err.message || err) });
+  }
+}
+
+console.log(JSON.stringify({ ok: true, workspace: workspaceReal, project, results }));
+NODE
+"#
+    .to_string();
+
+    template = template.replace("__HOME__", home);
+    template = template.replace("__AGENT_JSON__", &js_string(agent)?);
+    template = template.replace("__PROJECT_JSON__", &js_string(project)?);
+    template = template.replace("__UPLOAD_TMP_JSON__", &js_string(upload_tmp)?);
+    template = template.replace("__MAX_BYTES__", &MAX_MARKDOWN_BYTES.to_string());
+    Ok(template)
+}
+
+fn load_state(dir: &Path, instance: &str, agent: &str, project: &str) -> Result<SyncState> {
+    let path = dir.join(STATE_FILE);
+    let fresh = SyncState {
+        instance: instance.to_string(),
+        agent: agent.to_string(),
+        project: project.to_string(),
+        ..SyncState::default()
+    };
+    if !path.exists() {
+        return Ok(fresh);
+    }
+    let contents = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let state: SyncState = serde_json::from_str(&contents)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+    if state.instance != instance || state.agent != agent || state.project != project {
+        eprintln!(
+            "Warning: {} tracks {}/{}/{}; treating this as a first sync.",
+            path.display(),
+            state.instance,
+            state.agent,
+            state.project
+        );
+        return Ok(fresh);
+    }
+    Ok(state)
+}
+
+/// Write `data` to `path` via a temp file in the same directory.
+fn write_atomic(path: &Path, data: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut tmp = path.as_os_str().to_os_string();
+    tmp.push(".clawmacdo-sync.tmp");
+    let tmp = PathBuf::from(tmp);
+    std::fs::write(&tmp, data)?;
+    std::fs::rename(&tmp, path).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn print_plan(plan: &SyncPlan, project: &str, dir: &Path) {
+    println!("Wiki sync: project {project} <-> {}", dir.display());
+    for item in &plan.items {
+        let note = match item.action {
+            SyncAction::Conflict => format!("  -> {}{CONFLICT_SUFFIX}", item.path),
+            SyncAction::Unresolved => {
+                format!("  (remove {}{CONFLICT_SUFFIX} once resolved)", item.path)
+            }
+            _ => String::new(),
+        };
+        println!("  {:<13} {}{note}", item.action.label(), item.path);
+    }
+    println!("  {} unchanged", plan.unchanged.len());
+}
+
+/// Two-way sync of a wiki project with a local directory.
+pub async fn sync(params: WikiSyncParams) -> Result<()> {
+    let instance = clean_instance(&params.instance)?;
+    let agent = clean_agent_id(&params.agent)?;
+    let project = clean_project_slug(&params.project)?;
+    let prefer = clean_prefer(params.prefer.as_deref())?;
+    let dir = params.dir;
+    if dir.exists() && !dir.is_dir() {
+        bail!("--dir must be a directory: {}", dir.display());
+    }
+
+    let mut state = load_state(&dir, &instance, &agent, &project)?;
+    let (local, unresolved) = scan_local(&dir, &project)?;
+    let (manifest, _) = collect_manifest(&instance, &agent, &project, false, false).await?;
+    let mut remote = BTreeMap::new();
+    for file in &manifest.files {
+        // Remote paths become local paths on pull, so hold them to the same rules.
+        let path = clean_relative_markdown_path(&file.path)
+            .with_context(|| format!("Instance returned an unsafe wiki path {}", file.path))?;
+        if !in_project(&path, &project) {
+            bail!("Instance returned a path outside project {project}: {path}");
+        }
+        remote.insert(path, file.sha256.clone());
+    }
+    let plan = plan_sync(&local, &remote, &state.files, &unresolved, prefer);
+
+    if params.dry_run {
+        if params.json {
+            return print_json(&serde_json::json!({
+                "ok": true,
+                "dry_run": true,
+                "workspace": manifest.workspace,
+                "project": project,
+                "dir": dir,
+                "unchanged": plan.unchanged.len(),
+                "actions": plan.items,
+            }));
+        }
+        print_plan(&plan, &project, &dir);
+        println!("\nDry run: nothing was changed.");
+        return Ok(());
+    }
+
+    // Fetch instance copies needed locally.
+    let needs_content: BTreeSet<&str> = plan
+        .items
+        .iter()
+        .filter(|i| {
+            matches!(i.action, SyncAction::Pull | SyncAction::Conflict) && i.remote_sha.is_some()
+        })
+        .map(|i| i.path.as_str())
+        .collect();
+    let mut remote_data: BTreeMap<String, Vec<u8>> = BTreeMap::new();
+    if !needs_content.is_empty() {
+        let (full, _) = collect_manifest(&instance, &agent, &project, true, false).await?;
+        for file in full.files {
+            if !needs_content.contains(file.path.as_str()) {
+                continue;
+            }
+            if remote.get(&file.path) != Some(&file.sha256) {
+                bail!(
+                    "{} changed on the instance during sync; re-run wiki-sync.",
+                    file.path
+                );
+            }
+            let data = BASE64
+                .decode(file.data.as_deref().unwrap_or_default())
+                .with_context(|| format!("Failed to decode {}", file.path))?;
+            remote_data.insert(file.path, data);
+        }
+    }
+
+    // Push local changes and remote deletes in one round trip.
+    let mut payload = PushPayload {
+        writes: Vec::new(),
+        deletes: Vec::new(),
+    };
+    for item in &plan.items {
+        match item.action {
+            SyncAction::Push => payload.writes.push(PayloadWrite {
+                path: item.path.clone(),
+                base_sha: item.remote_sha.clone().unwrap_or_else(|| "NEW".into()),
+                data: BASE64.encode(std::fs::read(dir.join(&item.path))?),
+            }),
+            SyncAction::DeleteRemote => payload.deletes.push(PayloadDelete {
+                path: item.path.clone(),
+                base_sha: item.remote_sha.clone().unwrap_or_default(),
+            }),
+            _ => {}
+        }
+    }
+    let mut remote_results: BTreeMap<String, (bool, Option<String>)> = BTreeMap::new();
+    if !payload.writes.is_empty() || !payload.deletes.is_empty() {
+        let (ip, key, provider) = find_deploy_record(&instance)?;
+        let ssh_user = ssh_user_for_provider(&provider).to_string();
+        let remote_tmp = format!("/tmp/clawmacdo-wiki-sync-{}.json", uuid::Uuid::new_v4());
+        let cmd = build_apply_cmd(&agent, &project, &remote_tmp)?;
+        let body = serde_json::to_vec(&payload)?;
+        let outputs = tokio::task::spawn_blocking(move || {
+            clawmacdo_ssh::scp_upload_bytes_and_exec_as(
+                &ip,
+                &key,
+                &body,
+                &remote_tmp,
+                0o644,
+                &[cmd.as_str()],
+                &ssh_user,
+            )
+        })
+        .await
+        .context("wiki sync upload task failed")??;
+        let output = outputs.first().cloned().unwrap_or_default();
+        let value = remote_json_value(&output, "wiki sync")?;
+        handle_remote_status(&value, params.json)?;
+        for result in value
+            .get("results")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            let Some(path) = result.get("path").and_then(Value::as_str) else {
+                continue;
+            };
+            let ok = result.get("ok").and_then(Value::as_bool).unwrap_or(false);
+            let message = result
+                .get("message")
+                .and_then(Value::as_str)
+                .map(str::to_string);
+            remote_results.insert(path.to_string(), (ok, message));
+        }
+    }
+
+    // Apply locally and record the new base hashes.
+    let mut outcomes = Vec::new();
+    let mut files = plan.unchanged.clone();
+    for item in &plan.items {
+        let base = state.files.get(&item.path).cloned();
+        let (ok, message) = match item.action {
+            SyncAction::Push | SyncAction::DeleteRemote => remote_results
+                .get(&item.path)
+                .cloned()
+                .unwrap_or((false, Some("no result from instance".into()))),
+            SyncAction::Pull => {
+                let data = remote_data
+                    .get(&item.path)
+                    .with_context(|| format!("Missing instance content for {}", item.path))?;
+                write_atomic(&dir.join(&item.path), data)?;
+                (true, None)
+            }
+            SyncAction::DeleteLocal => {
+                std::fs::remove_file(dir.join(&item.path))
+                    .with_context(|| format!("Failed to remove {}", item.path))?;
+                (true, None)
+            }
+            SyncAction::Conflict => {
+                let data = remote_data
+                    .get(&item.path)
+                    .map(Vec::as_slice)
+                    .unwrap_or(b"<!-- deleted on the instance -->\n");
+                write_atomic(&conflict_path(&dir, &item.path), data)?;
+                (false, Some("both sides changed".into()))
+            }
+            SyncAction::Unresolved => (false, Some("conflict not resolved yet".into())),
+        };
+
+        let next = match (item.action, ok) {
+            (SyncAction::Push, true) => item.local_sha.clone(),
+            (SyncAction::Pull, true) => item.remote_sha.clone(),
+            (SyncAction::DeleteLocal | SyncAction::DeleteRemote, true) => None,
+            // Record the instance copy so that, once the `.conflict` file is
+            // removed, the local version is pushed on the next sync.
+            (SyncAction::Conflict, _) => item.remote_sha.clone(),
+            _ => base,
+        };
+        if let Some(sha) = next {
+            files.insert(item.path.clone(), sha);
+        }
+        outcomes.push(ItemOutcome {
+            path: item.path.clone(),
+            action: item.action,
+            ok,
+            message,
+        });
+    }
+
+    state.files = files;
+    state.synced_at = Some(Utc::now());
+    write_atomic(
+        &dir.join(STATE_FILE),
+        serde_json::to_string_pretty(&state)?.as_bytes(),
+    )?;
+
+    let failed = outcomes.iter().filter(|o| !o.ok).count();
+    if params.json {
+        print_json(&serde_json::json!({
+            "ok": failed == 0,
+            "dry_run": false,
+            "workspace": manifest.workspace,
+            "project": project,
+            "dir": dir,
+            "unchanged": plan.unchanged.len(),
+            "actions": outcomes,
+        }))?;
+    } else {
+        print_plan(&plan, &project, &dir);
+        for outcome in outcomes.iter().filter(|o| !o.ok) {
+            println!(
+                "  ! {}: {}",
+                outcome.path,
+                outcome.message.as_deref().unwrap_or("failed")
+            );
+        }
+    }
+    if failed > 0 {
+        bail!("{failed} page(s) need attention; resolve them and re-run wiki-sync.");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+        entries
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn actions(plan: &SyncPlan) -> Vec<(&str, SyncAction)> {
+        plan.items
+            .iter()
+            .map(|i| (i.path.as_str(), i.action))
+            .collect()
+    }
+
+    #[test]
+    fn plan_sync_uses_base_to_pick_direction() {
+        let local = map(&[
+            ("w/same.md", "a"),
+            ("w/local-edit.md", "l2"),
+            ("w/remote-edit.md", "r1"),
+            ("w/both.md", "l2"),
+            ("w/new-local.md", "n"),
+            ("w/gone-remote.md", "g"),
+        ]);
+        let remote = map(&[
+            ("w/same.md", "a"),
+            ("w/local-edit.md", "l1"),
+            ("w/remote-edit.md", "r2"),
+            ("w/both.md", "r2"),
+            ("w/new-remote.md", "m"),
+            ("w/gone-local.md", "h"),
+        ]);
+        let base = map(&[
+            ("w/same.md", "a"),
+            ("w/local-edit.md", "l1"),
+            ("w/remote-edit.md", "r1"),
+            ("w/both.md", "b"),
+            ("w/gone-remote.md", "g"),
+            ("w/gone-local.md", "h"),
+        ]);
+        let plan = plan_sync(&local, &remote, &base, &BTreeSet::new(), None);
+        assert_eq!(
+            actions(&plan),
+            vec![
+                ("w/both.md", SyncAction::Conflict),
+                ("w/gone-local.md", SyncAction::DeleteRemote),
+                ("w/gone-remote.md", SyncAction::DeleteLocal),
+                ("w/local-edit.md", SyncAction::Push),
+                ("w/new-local.md", SyncAction::Push),
+                ("w/new-remote.md", SyncAction::Pull),
+                ("w/remote-edit.md", SyncAction::Pull),
+            ]
+        );
+        assert_eq!(plan.unchanged, map(&[("w/same.md", "a")]));
+    }
+
+    #[test]
+    fn plan_sync_prefer_resolves_conflicts() {
+        let local = map(&[("w/a.md", "l")]);
+        let remote = map(&[("w/a.md", "r")]);
+        let none = BTreeMap::new();
+        let plan = plan_sync(
+            &local,
+            &remote,
+            &none,
+            &BTreeSet::new(),
+            Some(Prefer::Local),
+        );
+        assert_eq!(actions(&plan), vec![("w/a.md", SyncAction::Push)]);
+        let plan = plan_sync(
+            &local,
+            &remote,
+            &none,
+            &BTreeSet::new(),
+            Some(Prefer::Remote),
+        );
+        assert_eq!(actions(&plan), vec![("w/a.md", SyncAction::Pull)]);
+
+        let unresolved: BTreeSet<String> = ["w/a.md".to_string()].into();
+        let plan = plan_sync(&local, &remote, &none, &unresolved, Some(Prefer::Local));
+        assert_eq!(actions(&plan), vec![("w/a.md", SyncAction::Unresolved)]);
+    }
+
+    #[test]
+    fn clean_prefer_accepts_local_or_remote() {
+        assert_eq!(clean_prefer(None).unwrap(), None);
+        assert_eq!(clean_prefer(Some("LOCAL")).unwrap(), Some(Prefer::Local));
+        assert_eq!(clean_prefer(Some("remote")).unwrap(), Some(Prefer::Remote));
+        assert!(clean_prefer(Some("newest")).is_err());
+    }
+
+    #[test]
+    fn scan_local_filters_to_project_and_finds_conflicts() {
+        let dir =
+            std::env::temp_dir().join(format!("clawmacdo-wiki-sync-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("w/sub")).unwrap();
+        std::fs::create_dir_all(dir.join(".git")).unwrap();
+        std::fs::write(dir.join("w.md"), b"root").unwrap();
+        std::fs::write(dir.join("w/sub/page.md"), b"page").unwrap();
+        std::fs::write(dir.join("w/sub/page.md.conflict"), b"theirs").unwrap();
+        std::fs::write(dir.join("w/notes.txt"), b"skip").unwrap();
+        std::fs::write(dir.join("README.md"), b"other project").unwrap();
+        std::fs::write(dir.join(".git/HEAD.md"), b"hidden").unwrap();
+
+        let (files, unresolved) = scan_local(&dir, "w").unwrap();
+        assert_eq!(
+            files.keys().map(String::as_str).collect::<Vec<_>>(),
+            vec!["w.md", "w/sub/page.md"]
+        );
+        assert_eq!(files["w.md"], sha256_hex(b"root"));
+        assert!(unresolved.contains("w/sub/page.md"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn build_apply_cmd_checks_base_sha_and_scope() {
+        let cmd = build_apply_cmd("main", "llm_wiki", "/tmp/payload.json").unwrap();
+        assert!(cmd.contains("const project = \"llm_wiki\";"));
+        assert!(cmd.contains("item.base_sha === 'NEW'"));
+        assert!(cmd.contains("Path is outside the wiki project."));
+        assert!(cmd.contains("fs.renameSync(tmp, target)"));
+    }
+}
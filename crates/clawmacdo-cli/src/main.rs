@@ -131,6 +131,12 @@ enum Commands {
         /// OpenClaw version to install (e.g. 2026.3.22). Use `openclaw-versions` to list available versions.
         #[arg(long)]
         openclaw_version: String,
+        /// Boot from a `bake` image (name or image ID) and skip the steps baked into it
+        #[arg(long)]
+        image: Option<String>,
+        /// Directory of extension step files (*.json) to run during provisioning
+        #[arg(long, env = "CLAWMACDO_EXTENSIONS_DIR")]
+        extensions_dir: Option<PathBuf>,
         /// Detach: fork deploy to background, print deploy ID, exit immediately
         #[arg(long)]
         detach: bool,
@@ -141,13 +147,94 @@ enum Commands {
         #[arg(long = "_deploy-id", hide = true)]
         _deploy_id: Option<String>,
     },
-    /// Provision Hermes Agent on AWS Lightsail
-    #[cfg(feature = "lightsail")]
+    /// Build a reusable OpenClaw image for `deploy --image`
+    Bake {
+        /// Cloud provider (digitalocean, lightsail, byteplus)
+        #[arg(long)]
+        provider: String,
+        /// OpenClaw version to bake in (e.g. 2026.3.22)
+        #[arg(long)]
+        openclaw_version: String,
+        /// Image name (default: openclaw-<version>-<id>)
+        #[arg(long)]
+        name: Option<String>,
+        /// Region for the builder and image (Lightsail uses --aws-region)
+        #[arg(long)]
+        region: Option<String>,
+        /// Builder instance size (default: the provider's deploy default)
+        #[arg(long)]
+        size: Option<String>,
+        /// DigitalOcean API token
+        #[arg(long, default_value = "", env = "DO_TOKEN")]
+        do_token: String,
+        /// AWS Access Key ID (Lightsail)
+        #[arg(long, default_value = "", env = "AWS_ACCESS_KEY_ID")]
+        aws_access_key_id: String,
+        /// AWS Secret Access Key (Lightsail)
+        #[arg(long, default_value = "", env = "AWS_SECRET_ACCESS_KEY")]
+        aws_secret_access_key: String,
+        /// AWS region (Lightsail)
+        #[arg(long, default_value = "ap-southeast-1")]
+        aws_region: String,
+        /// BytePlus Access Key
+        #[arg(long, default_value = "", env = "BYTEPLUS_ACCESS_KEY")]
+        byteplus_access_key: String,
+        /// BytePlus Secret Key
+        #[arg(long, default_value = "", env = "BYTEPLUS_SECRET_KEY")]
+        byteplus_secret_key: String,
+        /// Keep the builder instance after the image is created
+        #[arg(long)]
+        keep_builder: bool,
+        /// Output the recorded image as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// List images created by `bake`
+    #[command(name = "list-bakes")]
+    ListBakes {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Provision Hermes Agent on any supported provider
     #[command(name = "hermes-provision", visible_alias = "provision-hermes")]
     HermesProvision {
-        /// Lightsail instance name (default: hermes-<deploy-id>)
+        /// Cloud provider (lightsail, digitalocean, tencent, azure, byteplus)
+        #[arg(long, default_value = "lightsail")]
+        provider: String,
+        /// Region for non-Lightsail providers (default: the provider's deploy default)
+        #[arg(long)]
+        region: Option<String>,
+        /// Instance name (default: hermes-<deploy-id>)
         #[arg(long)]
         name: Option<String>,
+        /// DigitalOcean API token
+        #[arg(long, default_value = "", env = "DO_TOKEN")]
+        do_token: String,
+        /// Tencent SecretId
+        #[arg(long, default_value = "", env = "TENCENT_SECRET_ID")]
+        tencent_secret_id: String,
+        /// Tencent SecretKey
+        #[arg(long, default_value = "", env = "TENCENT_SECRET_KEY")]
+        tencent_secret_key: String,
+        /// Azure Tenant ID
+        #[arg(long, default_value = "", env = "AZURE_TENANT_ID")]
+        azure_tenant_id: String,
+        /// Azure Subscription ID
+        #[arg(long, default_value = "", env = "AZURE_SUBSCRIPTION_ID")]
+        azure_subscription_id: String,
+        /// Azure Client ID (Service Principal)
+        #[arg(long, default_value = "", env = "AZURE_CLIENT_ID")]
+        azure_client_id: String,
+        /// Azure Client Secret (Service Principal)
+        #[arg(long, default_value = "", env = "AZURE_CLIENT_SECRET")]
+        azure_client_secret: String,
+        /// BytePlus Access Key
+        #[arg(long, default_value = "", env = "BYTEPLUS_ACCESS_KEY")]
+        byteplus_access_key: String,
+        /// BytePlus Secret Key
+        #[arg(long, default_value = "", env = "BYTEPLUS_SECRET_KEY")]
+        byteplus_secret_key: String,
         /// AWS Access Key ID (Lightsail)
         #[arg(long, default_value = "", env = "AWS_ACCESS_KEY_ID")]
         aws_access_key_id: String,
@@ -157,7 +244,7 @@ enum Commands {
         /// AWS region (Lightsail)
         #[arg(long, default_value = "ap-southeast-1")]
         aws_region: String,
-        /// Lightsail instance size (s-1vcpu-2gb, s-2vcpu-4gb, s-4vcpu-8gb)
+        /// Instance size (default: the provider's deploy default)
         #[arg(long)]
         size: Option<String>,
         /// Hermes Agent container image
@@ -187,19 +274,82 @@ enum Commands {
         /// Telegram home channel/user ID for notifications
         #[arg(long, env = "TELEGRAM_HOME_CHANNEL")]
         telegram_home_channel: Option<String>,
-        /// Run the Hermes dashboard on 127.0.0.1:9119; access via SSH tunnel
+        /// Run the Hermes dashboard on 127.0.0.1:9119; reach it with `clawmacdo tunnel <instance> 9119`
         #[arg(long)]
         dashboard: bool,
         /// Mount /var/run/docker.sock into the Hermes container
         #[arg(long)]
         mount_docker_socket: bool,
-        /// Print the Lightsail user-data script without creating resources
+        /// Print the user-data script without creating resources
         #[arg(long)]
         dry_run: bool,
         /// Output structured JSON
         #[arg(long)]
         json: bool,
     },
+    /// Pull a Hermes image on an instance and recreate its containers when it changed
+    #[command(name = "hermes-update-image")]
+    HermesUpdateImage {
+        /// Deploy ID, hostname, or IP address of the instance
+        instance: String,
+        /// Switch to this image (default: pull the current one again)
+        #[arg(long)]
+        image: Option<String>,
+        /// Recreate the containers even when the image is unchanged
+        #[arg(long)]
+        force: bool,
+    },
+    /// Set or remove variables in an instance's Hermes env file and restart Hermes
+    #[command(name = "hermes-env-set")]
+    HermesEnvSet {
+        /// Deploy ID, hostname, or IP address of the instance
+        instance: String,
+        /// KEY=VALUE pairs to set
+        #[arg(value_name = "KEY=VALUE")]
+        assignments: Vec<String>,
+        /// Remove a variable; repeat for multiple keys
+        #[arg(long, value_name = "KEY")]
+        unset: Vec<String>,
+        /// Edit the env file without recreating the containers
+        #[arg(long)]
+        no_restart: bool,
+    },
+    /// Show Hermes gateway, dashboard or provisioning logs from an instance
+    #[command(name = "hermes-logs")]
+    HermesLogs {
+        /// Deploy ID, hostname, or IP address of the instance
+        instance: String,
+        /// Read the dashboard container instead of the gateway
+        #[arg(long, conflicts_with = "provision")]
+        dashboard: bool,
+        /// Read the bootstrap log instead of a container
+        #[arg(long)]
+        provision: bool,
+        /// Keep streaming new lines
+        #[arg(short, long)]
+        follow: bool,
+        /// Start this far back, e.g. 30m, 1h, 2d (containers only)
+        #[arg(long)]
+        since: Option<String>,
+        /// Only lines matching this extended regular expression
+        #[arg(long)]
+        grep: Option<String>,
+        /// Lines of history to print first
+        #[arg(short = 'n', long, default_value_t = 200)]
+        lines: usize,
+        /// Print secrets from the instance's env files as they are
+        #[arg(long)]
+        no_redact: bool,
+    },
+    /// Show Hermes bootstrap state and container health; exits non-zero when unhealthy
+    #[command(name = "hermes-status")]
+    HermesStatus {
+        /// Deploy ID, hostname, or IP address of the instance
+        instance: String,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
     /// Track a deployment's progress
     Track {
         /// Deploy ID, hostname, or IP address
@@ -262,6 +412,156 @@ enum Commands {
         #[arg(short, long)]
         yes: bool,
     },
+    /// Migrate an instance's full state to a new instance on any provider
+    Migrate {
+        /// Deploy ID, hostname, or IP address of the source instance
+        #[arg(long)]
+        source: String,
+        /// Target cloud provider (digitalocean, tencent, lightsail, azure, byteplus)
+        #[arg(long)]
+        provider: String,
+        /// Customer name
+        #[arg(long, default_value = "CLI User")]
+        customer_name: String,
+        /// Customer email
+        #[arg(long, default_value = "")]
+        customer_email: String,
+        /// DigitalOcean API token
+        #[arg(long, default_value = "", env = "DO_TOKEN")]
+        do_token: String,
+        /// Tencent SecretId
+        #[arg(long, default_value = "", env = "TENCENT_SECRET_ID")]
+        tencent_secret_id: String,
+        /// Tencent SecretKey
+        #[arg(long, default_value = "", env = "TENCENT_SECRET_KEY")]
+        tencent_secret_key: String,
+        /// AWS Access Key ID (Lightsail)
+        #[arg(long, default_value = "", env = "AWS_ACCESS_KEY_ID")]
+        aws_access_key_id: String,
+        /// AWS Secret Access Key (Lightsail)
+        #[arg(long, default_value = "", env = "AWS_SECRET_ACCESS_KEY")]
+        aws_secret_access_key: String,
+        /// AWS region (Lightsail)
+        #[arg(long, default_value = "ap-southeast-1")]
+        aws_region: String,
+        /// Azure Tenant ID
+        #[arg(long, default_value = "", env = "AZURE_TENANT_ID")]
+        azure_tenant_id: String,
+        /// Azure Subscription ID
+        #[arg(long, default_value = "", env = "AZURE_SUBSCRIPTION_ID")]
+        azure_subscription_id: String,
+        /// Azure Client ID
+        #[arg(long, default_value = "", env = "AZURE_CLIENT_ID")]
+        azure_client_id: String,
+        /// Azure Client Secret
+        #[arg(long, default_value = "", env = "AZURE_CLIENT_SECRET")]
+        azure_client_secret: String,
+        /// BytePlus Access Key
+        #[arg(long, default_value = "", env = "BYTEPLUS_ACCESS_KEY")]
+        byteplus_access_key: String,
+        /// BytePlus Secret Key
+        #[arg(long, default_value = "", env = "BYTEPLUS_SECRET_KEY")]
+        byteplus_secret_key: String,
+        /// BytePlus Ark API Key
+        #[arg(long, default_value = "", env = "BYTEPLUS_ARK_API_KEY")]
+        byteplus_ark_api_key: String,
+        /// Anthropic API key
+        #[arg(long, default_value = "", env = "ANTHROPIC_API_KEY")]
+        anthropic_key: String,
+        /// OpenAI API key
+        #[arg(long, default_value = "", env = "OPENAI_API_KEY")]
+        openai_key: String,
+        /// Gemini API key
+        #[arg(long, default_value = "", env = "GEMINI_API_KEY")]
+        gemini_key: String,
+        /// OpenCode API key (installs OpenCode and configures kimi-k2.5 model)
+        #[arg(long, default_value = "", env = "OPENCODE_API_KEY")]
+        opencode_api_key: String,
+        /// WhatsApp phone number
+        #[arg(long, default_value = "")]
+        whatsapp_phone_number: String,
+        /// Telegram bot token
+        #[arg(long, default_value = "")]
+        telegram_bot_token: String,
+        /// Region override
+        #[arg(long)]
+        region: Option<String>,
+        /// Instance size override
+        #[arg(long)]
+        size: Option<String>,
+        /// Hostname
+        #[arg(long)]
+        hostname: Option<String>,
+        /// Enable sandbox mode
+        #[arg(long)]
+        enable_sandbox: bool,
+        /// Enable Tailscale
+        #[arg(long)]
+        tailscale: bool,
+        /// Tailscale auth key
+        #[arg(long, default_value = "")]
+        tailscale_auth_key: String,
+        /// Primary AI model (anthropic, openai, gemini, byteplus, opencode)
+        #[arg(long, default_value = "anthropic")]
+        primary_model: String,
+        /// First failover model
+        #[arg(long, default_value = "")]
+        failover_1: String,
+        /// Second failover model
+        #[arg(long, default_value = "")]
+        failover_2: String,
+        /// Profile (messaging, coding, full)
+        #[arg(long, default_value = "full")]
+        profile: String,
+        /// Use spot instance for BytePlus (up to ~80% cheaper, may be reclaimed with 5 min warning)
+        #[arg(long)]
+        spot: bool,
+        /// OpenClaw version to install (e.g. 2026.3.22). Use `openclaw-versions` to list available versions.
+        #[arg(long)]
+        openclaw_version: String,
+        /// Destroy the source once the target is verified
+        #[arg(long)]
+        destroy_source: bool,
+        /// Seconds to wait before destroying the source (Ctrl-C to cancel)
+        #[arg(long, default_value_t = 300)]
+        destroy_after: u64,
+        /// Skip confirmation prompt
+        #[arg(long)]
+        yes: bool,
+        /// DigitalOcean API token for the source (default: --do-token)
+        #[arg(long, default_value = "", env = "SOURCE_DO_TOKEN")]
+        source_do_token: String,
+        /// Tencent SecretId for the source (default: --tencent-secret-id)
+        #[arg(long, default_value = "", env = "SOURCE_TENCENT_SECRET_ID")]
+        source_tencent_secret_id: String,
+        /// Tencent SecretKey for the source (default: --tencent-secret-key)
+        #[arg(long, default_value = "", env = "SOURCE_TENCENT_SECRET_KEY")]
+        source_tencent_secret_key: String,
+        /// AWS Access Key ID for a Lightsail source (default: --aws-access-key-id)
+        #[arg(long, default_value = "", env = "SOURCE_AWS_ACCESS_KEY_ID")]
+        source_aws_access_key_id: String,
+        /// AWS Secret Access Key for a Lightsail source (default: --aws-secret-access-key)
+        #[arg(long, default_value = "", env = "SOURCE_AWS_SECRET_ACCESS_KEY")]
+        source_aws_secret_access_key: String,
+        /// Azure Tenant ID for the source (default: --azure-tenant-id)
+        #[arg(long, default_value = "", env = "SOURCE_AZURE_TENANT_ID")]
+        source_azure_tenant_id: String,
+        /// Azure Subscription ID for the source (default: --azure-subscription-id)
+        #[arg(long, default_value = "", env = "SOURCE_AZURE_SUBSCRIPTION_ID")]
+        source_azure_subscription_id: String,
+        /// Azure Client ID for the source (default: --azure-client-id)
+        #[arg(long, default_value = "", env = "SOURCE_AZURE_CLIENT_ID")]
+        source_azure_client_id: String,
+        /// Azure Client Secret for the source (default: --azure-client-secret)
+        #[arg(long, default_value = "", env = "SOURCE_AZURE_CLIENT_SECRET")]
+        source_azure_client_secret: String,
+        /// BytePlus Access Key for the source (default: --byteplus-access-key)
+        #[arg(long, default_value = "", env = "SOURCE_BYTEPLUS_ACCESS_KEY")]
+        source_byteplus_access_key: String,
+        /// BytePlus Secret Key for the source (default: --byteplus-secret-key)
+        #[arg(long, default_value = "", env = "SOURCE_BYTEPLUS_SECRET_KEY")]
+        source_byteplus_secret_key: String,
+    },
     /// Configure Telegram bot token on a deployed instance
     TelegramSetup {
         /// Deploy ID, hostname, or IP address of the instance
@@ -363,13 +663,66 @@ enum Commands {
         /// Deploy ID, hostname, or IP address of the instance
         #[arg(long)]
         instance: String,
-        /// Tailscale auth key (tskey-auth-...)
+        /// Tailscale auth key (tskey-auth-...); minted through the API when omitted and TAILSCALE_API_KEY is set
         #[arg(long, env = "TAILSCALE_AUTH_KEY")]
-        auth_key: String,
+        auth_key: Option<String>,
         /// Port to expose via Funnel (default: 18789)
         #[arg(long, default_value = "18789")]
         port: u16,
     },
+    /// List tailnet devices matched against deployments (requires TAILSCALE_API_KEY)
+    TailscaleDevices {
+        /// Remove tagged devices that belong to no deployment
+        #[arg(long)]
+        prune: bool,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Check the tailnet policy allows the deployment tags, optionally updating it
+    TailscaleAcl {
+        /// Also require the Funnel node attribute for the tags
+        #[arg(long)]
+        funnel: bool,
+        /// Write the missing policy entries (HuJSON comments are not preserved)
+        #[arg(long)]
+        apply: bool,
+    },
+    /// Converge an instance's `tailscale serve` config: public Funnel, tailnet-only, or off
+    TailscaleServe {
+        /// Deploy ID, hostname, or IP address of the instance
+        #[arg(long)]
+        instance: String,
+        /// Serve mode
+        #[arg(long, value_parser = ["funnel", "tailnet", "off"])]
+        mode: String,
+        /// Gateway port to proxy (default: 18789)
+        #[arg(long, default_value = "18789")]
+        port: u16,
+        /// Show the commands without running them
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Choose how the OpenClaw gateway is reached: Tailscale Funnel, an HTTPS reverse proxy, or not at all
+    Expose {
+        /// Deploy ID, hostname, or IP address of the instance
+        instance: String,
+        /// funnel, proxy or none (omit to show the current mode)
+        #[arg(long, value_parser = ["funnel", "proxy", "none"])]
+        mode: Option<String>,
+        /// Public domain for proxy mode; must resolve to the instance (default: the hostname when it is a FQDN)
+        #[arg(long)]
+        domain: Option<String>,
+        /// Reverse proxy for proxy mode
+        #[arg(long, default_value = "caddy", value_parser = ["caddy", "nginx"])]
+        server: String,
+        /// ACME account email for certificate expiry notices
+        #[arg(long)]
+        email: Option<String>,
+        /// Tailscale auth key (tskey-auth-...) for funnel mode when the instance is not on the tailnet yet
+        #[arg(long)]
+        auth_key: Option<String>,
+    },
     /// Generate a temporary BytePlus ARK API key, or list available endpoints
     #[cfg(feature = "byteplus")]
     ArkApiKey {
@@ -392,7 +745,7 @@ enum Commands {
         #[arg(long, default_value = "604800")]
         duration: u64,
     },
-    /// Send a chat prompt to a BytePlus ARK model endpoint
+    /// Chat with a BytePlus ARK model endpoint (interactive when no prompt is given)
     #[cfg(feature = "byteplus")]
     ArkChat {
         /// ARK API key (Bearer token)
@@ -401,8 +754,30 @@ enum Commands {
         /// Endpoint ID (e.g., ep-20260315233753-58rpv)
         #[arg(long, env = "ARK_ENDPOINT_ID")]
         endpoint_id: String,
-        /// The prompt to send
-        prompt: String,
+        /// System prompt (replaces the one in --history)
+        #[arg(long)]
+        system: Option<String>,
+        /// JSON file the conversation is read from and saved to after each turn
+        #[arg(long)]
+        history: Option<PathBuf>,
+        /// Wait for the whole reply instead of streaming tokens
+        #[arg(long)]
+        no_stream: bool,
+        /// The prompt to send ("-" reads stdin)
+        prompt: Option<String>,
+    },
+    /// List the models served by your BytePlus ARK endpoints
+    #[cfg(feature = "byteplus")]
+    ArkModels {
+        /// BytePlus Access Key
+        #[arg(long, env = "BYTEPLUS_ACCESS_KEY")]
+        access_key: String,
+        /// BytePlus Secret Key
+        #[arg(long, env = "BYTEPLUS_SECRET_KEY")]
+        secret_key: String,
+        /// Print JSON instead of a table
+        #[arg(long)]
+        json: bool,
     },
     /// Create a named snapshot from a DigitalOcean droplet
     #[cfg(feature = "digitalocean")]
@@ -673,6 +1048,148 @@ enum Commands {
         #[arg(long)]
         version: String,
     },
+    /// Upgrade OpenClaw across selected instances in batches, reverting hosts that fail
+    Upgrade {
+        #[command(flatten)]
+        selector: commands::fleet::Selector,
+        /// Version to install (e.g. 2026.3.20) or `latest`
+        #[arg(long)]
+        to: String,
+        /// Instances in the first (canary) batch; any canary failure halts the upgrade
+        #[arg(long, default_value_t = 1)]
+        canary: usize,
+        /// Instances per batch after the canary
+        #[arg(long, default_value_t = 5)]
+        batch_size: usize,
+        /// Instances upgraded at the same time
+        #[arg(long, default_value_t = 4)]
+        concurrency: usize,
+        /// Halt after a batch once more than this many instances have failed
+        #[arg(long, default_value_t = 0)]
+        max_failures: usize,
+        /// Print the batches without changing anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Print the summary as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show recorded OpenClaw versions and version drift across deployed instances
+    Status {
+        #[command(flatten)]
+        selector: commands::fleet::Selector,
+        /// Probe each instance over SSH for its current version first
+        #[arg(long)]
+        refresh: bool,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Open a shell on an instance as its SSH user, or run one command (`ssh <instance> -- uptime`)
+    Ssh {
+        /// Deploy ID, hostname, or IP address of the instance
+        instance: String,
+        /// Allocate a terminal for the command (for interactive programs such as `top`)
+        #[arg(short = 't', long)]
+        tty: bool,
+        /// Print the equivalent OpenSSH command instead of connecting
+        #[arg(long)]
+        print: bool,
+        /// Command to run instead of a shell
+        #[arg(last = true)]
+        command: Vec<String>,
+    },
+    /// Run a command on selected instances in parallel and print each instance's output
+    Exec {
+        #[command(flatten)]
+        selector: commands::fleet::Selector,
+        /// Instances running the command at the same time
+        #[arg(long, default_value_t = 8)]
+        concurrency: usize,
+        /// Print the results as JSON
+        #[arg(long)]
+        json: bool,
+        /// Command to run
+        #[arg(last = true, required = true)]
+        command: Vec<String>,
+    },
+    /// Forward a local port to a port on an instance (`tunnel <instance> 9119` for the Hermes dashboard)
+    Tunnel {
+        /// Deploy ID, hostname, or IP address of the instance
+        instance: String,
+        /// `<port>`, `<local>:<remote>` or `<local>:<host>:<remote>`; the host is resolved on the instance
+        forward: String,
+        /// Local address to listen on
+        #[arg(long, default_value = "127.0.0.1")]
+        bind: String,
+    },
+    /// Print or follow gateway, cloud-init, Hermes provisioning or Docker logs, with secrets redacted
+    Logs {
+        /// Deploy ID, hostname, or IP address of the instance
+        instance: String,
+        /// Log to read
+        #[arg(long, default_value = "gateway", value_parser = ["gateway", "cloud-init", "hermes", "docker"])]
+        source: String,
+        /// Keep streaming new lines
+        #[arg(short, long)]
+        follow: bool,
+        /// Start this far back, e.g. 30m, 1h, 2d (gateway and docker)
+        #[arg(long)]
+        since: Option<String>,
+        /// Only lines matching this extended regular expression
+        #[arg(long)]
+        grep: Option<String>,
+        /// Lines of history to print first
+        #[arg(short = 'n', long, default_value_t = 200)]
+        lines: usize,
+        /// Container for `--source docker`
+        #[arg(long, default_value = "hermes")]
+        container: String,
+        /// Print secrets from the instance's .env files as they are
+        #[arg(long)]
+        no_redact: bool,
+    },
+    /// Chat with an instance's OpenClaw agent through the gateway's OpenAI-compatible endpoint
+    Chat {
+        /// Deploy ID, hostname, or IP address of the instance
+        instance: String,
+        /// Agent to talk to
+        #[arg(long, default_value = "main")]
+        agent: String,
+        /// Send one prompt (`-` reads stdin), print the reply and exit; fails when the agent does not respond
+        #[arg(long)]
+        once: Option<String>,
+        /// Attach a file to the first message (repeatable; text is inlined, images are sent as image parts)
+        #[arg(long)]
+        attach: Vec<PathBuf>,
+        /// System prompt for the conversation
+        #[arg(long)]
+        system: Option<String>,
+        /// How to reach the gateway: the public URL when reachable, else an SSH tunnel
+        #[arg(long, default_value = "auto", value_parser = ["auto", "public", "tunnel"])]
+        via: String,
+        /// Wait for the whole reply instead of streaming it
+        #[arg(long)]
+        no_stream: bool,
+        /// Seconds to wait without any data from the gateway
+        #[arg(long, default_value_t = 300)]
+        timeout: u64,
+    },
+    /// Audit an instance's SSH, firewall, ports, secret file modes, gateway auth and Docker exposure
+    ScanSecurity {
+        /// Deploy ID, hostname, or IP address of the instance
+        #[arg(long)]
+        instance: String,
+        /// Report format
+        #[arg(long, default_value = "text", value_parser = ["text", "json", "sarif"])]
+        format: String,
+        /// Write the report to a file instead of stdout
+        #[arg(long)]
+        out: Option<PathBuf>,
+        /// Exit non-zero when the risk level reaches this severity
+        #[arg(long, value_parser = ["low", "medium", "high", "critical"])]
+        fail_on: Option<String>,
+    },
     /// Regenerate the OpenClaw gateway auth token
     OpenclawGatewayToken {
         /// Deploy ID, hostname, or IP address of the instance
@@ -925,7 +1442,7 @@ enum Commands {
         /// Project/wiki slug under the workspace, e.g. wiki-163327
         #[arg(long)]
         project: String,
-        /// Local converted Markdown file to ingest
+        /// Local source file to ingest (.md, .txt, .html, .pdf or .docx; converted to Markdown locally)
         #[arg(long, value_name = "PATH")]
         source: std::path::PathBuf,
         /// AI instructions for deciding where the document belongs
@@ -934,6 +1451,9 @@ enum Commands {
         /// Maximum seconds to let Claude Code run
         #[arg(long, default_value = "600")]
         timeout: u64,
+        /// Split the converted document into chunks of at most this many bytes, one ingest run each
+        #[arg(long, value_name = "BYTES")]
+        chunk_bytes: Option<u64>,
         /// Output structured JSON
         #[arg(long)]
         json: bool,
@@ -956,6 +1476,26 @@ enum Commands {
         #[arg(long)]
         json: bool,
     },
+    /// Import a wiki project from a ZIP archive (the inverse of wiki-export)
+    WikiImport {
+        /// Deploy ID, hostname, or IP address of the instance
+        #[arg(long)]
+        instance: String,
+        /// Agent id whose workspace should be modified
+        #[arg(long, default_value = "main")]
+        agent: String,
+        /// Project/wiki slug to import into, e.g. llm_wiki
+        #[arg(long)]
+        project: String,
+        /// ZIP archive of Markdown pages, e.g. from wiki-export
+        archive: std::path::PathBuf,
+        /// Replace the project instead of merging pages into it
+        #[arg(long)]
+        replace: bool,
+        /// Output structured JSON
+        #[arg(long)]
+        json: bool,
+    },
     /// Safely delete a wiki-* project folder from the OpenClaw workspace
     WikiDelete {
         /// Deploy ID, hostname, or IP address of the instance
@@ -971,6 +1511,71 @@ enum Commands {
         #[arg(long)]
         json: bool,
     },
+    /// Two-way sync of a wiki project between a local directory and an instance
+    WikiSync {
+        /// Deploy ID, hostname, or IP address of the instance
+        #[arg(long)]
+        instance: String,
+        /// Agent id whose workspace should be synced
+        #[arg(long, default_value = "main")]
+        agent: String,
+        /// Project/wiki slug under the workspace, e.g. llm_wiki
+        #[arg(long)]
+        project: String,
+        /// Local directory mirroring the workspace layout (<project>.md and <project>/)
+        #[arg(long, default_value = ".")]
+        dir: std::path::PathBuf,
+        /// Only show what would be pushed, pulled or flagged as a conflict
+        #[arg(long)]
+        dry_run: bool,
+        /// Resolve conflicts automatically in favour of one side (local or remote)
+        #[arg(long)]
+        prefer: Option<String>,
+        /// Output structured JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Full-text search over a wiki project using a local index refreshed by sha256
+    WikiSearch {
+        /// Deploy ID, hostname, or IP address of the instance
+        #[arg(long)]
+        instance: String,
+        /// Agent id whose workspace should be searched
+        #[arg(long, default_value = "main")]
+        agent: String,
+        /// Project/wiki slug under the workspace, e.g. llm_wiki
+        #[arg(long)]
+        project: String,
+        /// Search words; all must match, a trailing * matches a prefix
+        query: String,
+        /// Maximum number of results
+        #[arg(long, default_value_t = 10)]
+        limit: u32,
+        /// Search the local index without contacting the instance
+        #[arg(long)]
+        no_refresh: bool,
+        /// Output structured JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Link graph of a wiki project with broken-link and orphan-page reports
+    WikiGraph {
+        /// Deploy ID, hostname, or IP address of the instance
+        #[arg(long)]
+        instance: String,
+        /// Agent id whose workspace should be graphed
+        #[arg(long, default_value = "main")]
+        agent: String,
+        /// Project/wiki slug under the workspace, e.g. llm_wiki
+        #[arg(long)]
+        project: String,
+        /// Output format: dot or json
+        #[arg(long, default_value = "dot")]
+        format: String,
+        /// Use the local index without contacting the instance
+        #[arg(long)]
+        no_refresh: bool,
+    },
     /// Deploy a ZIP of OpenClaw skills to an instance workspace and restart the gateway
     SkillDeploy {
         /// Deploy ID, hostname, or IP address of the instance
@@ -979,6 +1584,96 @@ enum Commands {
         /// Path to the .zip file containing skills
         #[arg(long)]
         file: std::path::PathBuf,
+        /// Number of previous versions to keep per skill on the instance
+        #[arg(long, default_value_t = 5)]
+        keep: usize,
+        /// Replace a stored version whose content differs from this upload
+        #[arg(long)]
+        force: bool,
+        /// Skip the skill-lint checks that run before uploading
+        #[arg(long)]
+        no_lint: bool,
+    },
+    /// Lint a skill directory or ZIP for front-matter, secrets, risky shell and size
+    SkillLint {
+        /// Path to a skill directory, a directory of skills, or a skills .zip
+        #[arg(long)]
+        path: std::path::PathBuf,
+        /// Deploy ID, hostname, or IP address of an instance to check required tools on
+        #[arg(long)]
+        instance: Option<String>,
+        /// Print findings as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Roll a skill back to a previously deployed version and restart the gateway
+    SkillRollback {
+        /// Deploy ID, hostname, or IP address of the instance
+        #[arg(long)]
+        instance: String,
+        /// Name of the skill directory to roll back (e.g. newsclaw-skills)
+        #[arg(long)]
+        skill: String,
+        /// Version to activate (default: the version before the current one)
+        #[arg(long)]
+        to: Option<String>,
+    },
+    /// Deploy a skill ZIP (or roll a skill back) across many instances in canary-first batches
+    SkillRollout {
+        #[command(flatten)]
+        selector: commands::fleet::Selector,
+        /// Path to the .zip file containing skills
+        #[arg(
+            long,
+            required_unless_present = "rollback",
+            conflicts_with = "rollback"
+        )]
+        file: Option<std::path::PathBuf>,
+        /// Roll this skill back instead of deploying a ZIP
+        #[arg(long)]
+        rollback: Option<String>,
+        /// Version to roll back to (default: the version before the current one)
+        #[arg(long, requires = "rollback")]
+        to: Option<String>,
+        /// Instances in the first (canary) batch; any canary failure halts the rollout
+        #[arg(long, default_value_t = 1)]
+        canary: usize,
+        /// Instances per batch after the canary
+        #[arg(long, default_value_t = 5)]
+        batch_size: usize,
+        /// Instances worked on at the same time
+        #[arg(long, default_value_t = 4)]
+        concurrency: usize,
+        /// Halt after a batch once more than this many instances have failed
+        #[arg(long, default_value_t = 0)]
+        max_failures: usize,
+        /// Number of previous versions to keep per skill on each instance
+        #[arg(long, default_value_t = 5)]
+        keep: usize,
+        /// Replace a stored version whose content differs from this upload
+        #[arg(long)]
+        force: bool,
+        /// Skip the skill-lint checks that run before uploading
+        #[arg(long)]
+        no_lint: bool,
+        /// Print the batches without changing anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Print the summary as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Add or remove tags on a deployed instance (used by --tag selectors)
+    Tag {
+        /// Deploy ID, hostname, or IP address of the instance
+        #[arg(long)]
+        instance: String,
+        /// Comma-separated tags to add
+        #[arg(long, value_delimiter = ',')]
+        add: Vec<String>,
+        /// Comma-separated tags to remove
+        #[arg(long, value_delimiter = ',')]
+        remove: Vec<String>,
     },
     /// Check drift between a local skill directory and the deployed skill on an instance
     SkillDiff {
@@ -1003,6 +1698,9 @@ enum Commands {
         /// Deploy ID, hostname, or IP address of the instance
         #[arg(long)]
         instance: String,
+        /// Show the stored versions of each skill
+        #[arg(long)]
+        versions: bool,
     },
     /// Check (and optionally fix) file ownership and permissions for a deployed skill
     SkillCheckPerms {
@@ -1191,6 +1889,59 @@ enum Commands {
         #[arg(long, default_value = ".")]
         output: std::path::PathBuf,
     },
+    /// Back up the local ~/.openclaw into the configured backup store
+    Backup,
+    /// List backup archives in the configured backup store
+    ListBackups {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Stream a backup of an instance's ~/.openclaw into the local backups directory
+    BackupRemote {
+        /// Deploy ID, hostname, or IP address of the instance
+        #[arg(long)]
+        instance: String,
+        /// Keep the N most recent remote backups of this instance (0 = no limit)
+        #[arg(long, default_value = "0")]
+        keep_last: usize,
+        /// Keep the newest backup of each of the last N days
+        #[arg(long, default_value = "0")]
+        keep_daily: usize,
+        /// Keep the newest backup of each of the last N weeks
+        #[arg(long, default_value = "0")]
+        keep_weekly: usize,
+        /// Stay in the foreground and repeat every interval (e.g. 30m, 6h, 1d)
+        #[arg(long)]
+        every: Option<String>,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Restore a backup archive into an existing instance's ~/.openclaw
+    RestoreBackup {
+        /// Deploy ID, hostname, or IP address of the instance
+        #[arg(long)]
+        instance: String,
+        /// Archive path, or a backup name from the configured backup store
+        #[arg(long)]
+        archive: String,
+        /// Skip confirmation prompt
+        #[arg(long)]
+        yes: bool,
+        /// Only show which files would change
+        #[arg(long)]
+        dry_run: bool,
+        /// Do not take a backup of the instance before restoring
+        #[arg(long)]
+        no_safety_backup: bool,
+    },
+    /// Show deploy outcome counts and per-step durations from the local deploy history
+    Stats {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
     /// Start the web UI server
     #[cfg(feature = "web-ui")]
     Serve {
@@ -1309,6 +2060,8 @@ async fn async_main() -> anyhow::Result<()> {
             profile,
             spot,
             openclaw_version,
+            image,
+            extensions_dir,
             detach,
             json,
             _deploy_id,
@@ -1338,27 +2091,73 @@ async fn async_main() -> anyhow::Result<()> {
                 telegram_bot_token,
                 region,
                 size,
-                hostname,
-                backup,
-                enable_backups,
-                enable_sandbox,
-                tailscale,
-                tailscale_auth_key,
-                primary_model,
-                failover_1,
-                failover_2,
-                profile,
-                spot,
-                openclaw_version,
-                detach,
+                hostname,
+                backup,
+                enable_backups,
+                enable_sandbox,
+                tailscale,
+                tailscale_auth_key,
+                primary_model,
+                failover_1,
+                failover_2,
+                profile,
+                spot,
+                openclaw_version,
+                image,
+                extensions_dir,
+                detach,
+                json,
+                deploy_id: _deploy_id,
+            })
+            .await
+        }
+        Commands::Bake {
+            provider,
+            openclaw_version,
+            name,
+            region,
+            size,
+            do_token,
+            aws_access_key_id,
+            aws_secret_access_key,
+            aws_region,
+            byteplus_access_key,
+            byteplus_secret_key,
+            keep_builder,
+            json,
+        } => {
+            commands::bake::run(commands::bake::BakeParams {
+                provider,
+                openclaw_version,
+                name,
+                region,
+                size,
+                do_token,
+                aws_access_key_id,
+                aws_secret_access_key,
+                aws_region,
+                byteplus_access_key,
+                byteplus_secret_key,
+                keep_builder,
                 json,
-                deploy_id: _deploy_id,
+                progress_tx: None,
             })
             .await
         }
-        #[cfg(feature = "lightsail")]
+        Commands::ListBakes { json } => commands::bake::list(json),
         Commands::HermesProvision {
+            provider,
+            region,
             name,
+            do_token,
+            tencent_secret_id,
+            tencent_secret_key,
+            azure_tenant_id,
+            azure_subscription_id,
+            azure_client_id,
+            azure_client_secret,
+            byteplus_access_key,
+            byteplus_secret_key,
             aws_access_key_id,
             aws_secret_access_key,
             aws_region,
@@ -1376,10 +2175,21 @@ async fn async_main() -> anyhow::Result<()> {
             mount_docker_socket,
             dry_run,
             json,
-        } => commands::hermes_lightsail::run(commands::hermes_lightsail::HermesLightsailParams {
+        } => commands::hermes::run(commands::hermes::HermesParams {
             deploy_id: None,
             customer_email: "hermes-agent".to_string(),
+            provider,
+            region,
             name,
+            do_token,
+            tencent_secret_id,
+            tencent_secret_key,
+            azure_tenant_id,
+            azure_subscription_id,
+            azure_client_id,
+            azure_client_secret,
+            byteplus_access_key,
+            byteplus_secret_key,
             aws_access_key_id,
             aws_secret_access_key,
             aws_region,
@@ -1403,6 +2213,46 @@ async fn async_main() -> anyhow::Result<()> {
         })
         .await
         .map(|_| ()),
+        Commands::HermesUpdateImage {
+            instance,
+            image,
+            force,
+        } => commands::hermes::update_image(&instance, image.as_deref(), force).await,
+        Commands::HermesEnvSet {
+            instance,
+            assignments,
+            unset,
+            no_restart,
+        } => commands::hermes::env_set(&instance, &assignments, &unset, !no_restart).await,
+        Commands::HermesLogs {
+            instance,
+            dashboard,
+            provision,
+            follow,
+            since,
+            grep,
+            lines,
+            no_redact,
+        } => {
+            commands::hermes::logs(
+                &instance,
+                dashboard,
+                provision,
+                commands::logs::LogQuery {
+                    source: String::new(),
+                    follow,
+                    since,
+                    grep,
+                    lines,
+                    container: String::new(),
+                },
+                !no_redact,
+            )
+            .await
+        }
+        Commands::HermesStatus { instance, json } => {
+            commands::hermes::status(&instance, json).await
+        }
         Commands::Track {
             query,
             follow,
@@ -1454,6 +2304,126 @@ async fn async_main() -> anyhow::Result<()> {
             })
             .await
         }
+        Commands::Migrate {
+            source,
+            provider,
+            customer_name,
+            customer_email,
+            do_token,
+            tencent_secret_id,
+            tencent_secret_key,
+            aws_access_key_id,
+            aws_secret_access_key,
+            aws_region,
+            azure_tenant_id,
+            azure_subscription_id,
+            azure_client_id,
+            azure_client_secret,
+            byteplus_access_key,
+            byteplus_secret_key,
+            byteplus_ark_api_key,
+            anthropic_key,
+            openai_key,
+            gemini_key,
+            opencode_api_key,
+            whatsapp_phone_number,
+            telegram_bot_token,
+            region,
+            size,
+            hostname,
+            enable_sandbox,
+            tailscale,
+            tailscale_auth_key,
+            primary_model,
+            failover_1,
+            failover_2,
+            profile,
+            spot,
+            openclaw_version,
+            destroy_source,
+            destroy_after,
+            yes,
+            source_do_token,
+            source_tencent_secret_id,
+            source_tencent_secret_key,
+            source_aws_access_key_id,
+            source_aws_secret_access_key,
+            source_azure_tenant_id,
+            source_azure_subscription_id,
+            source_azure_client_id,
+            source_azure_client_secret,
+            source_byteplus_access_key,
+            source_byteplus_secret_key,
+        } => {
+            commands::migrate::run(commands::migrate::MigrateParams {
+                source,
+                source_credentials: commands::migrate::SourceCredentials {
+                    do_token: source_do_token,
+                    tencent_secret_id: source_tencent_secret_id,
+                    tencent_secret_key: source_tencent_secret_key,
+                    aws_access_key_id: source_aws_access_key_id,
+                    aws_secret_access_key: source_aws_secret_access_key,
+                    azure_tenant_id: source_azure_tenant_id,
+                    azure_subscription_id: source_azure_subscription_id,
+                    azure_client_id: source_azure_client_id,
+                    azure_client_secret: source_azure_client_secret,
+                    byteplus_access_key: source_byteplus_access_key,
+                    byteplus_secret_key: source_byteplus_secret_key,
+                },
+                target: commands::deploy::DeployParams {
+                    deploy_id: None,
+                    provider,
+                    customer_name,
+                    customer_email,
+                    do_token,
+                    tencent_secret_id,
+                    tencent_secret_key,
+                    aws_access_key_id,
+                    aws_secret_access_key,
+                    aws_region,
+                    azure_tenant_id,
+                    azure_subscription_id,
+                    azure_client_id,
+                    azure_client_secret,
+                    byteplus_access_key,
+                    byteplus_secret_key,
+                    byteplus_ark_api_key,
+                    anthropic_key,
+                    openai_key,
+                    gemini_key,
+                    opencode_api_key,
+                    whatsapp_phone_number,
+                    telegram_bot_token,
+                    region,
+                    size,
+                    hostname,
+                    enable_sandbox,
+                    tailscale,
+                    primary_model,
+                    failover_1,
+                    failover_2,
+                    profile,
+                    spot,
+                    openclaw_version,
+                    image: None,
+                    extensions: Vec::new(),
+                    backup: None,
+                    enable_backups: false,
+                    tailscale_auth_key: if tailscale_auth_key.trim().is_empty() {
+                        None
+                    } else {
+                        Some(tailscale_auth_key)
+                    },
+                    non_interactive: true,
+                    progress_tx: None,
+                    db: None,
+                },
+                destroy_source,
+                destroy_after: std::time::Duration::from_secs(destroy_after),
+                yes,
+            })
+            .await
+        }
         Commands::TelegramSetup {
             instance,
             bot_token,
@@ -1464,14 +2434,74 @@ async fn async_main() -> anyhow::Result<()> {
         }
         Commands::TelegramChatId { instance } => commands::telegram::get_chat_id(&instance).await,
         Commands::TelegramReset { instance } => commands::telegram::reset(&instance).await,
-        Commands::SkillDeploy { instance, file } => {
-            commands::skill_deploy::deploy(&instance, &file).await
+        Commands::SkillDeploy {
+            instance,
+            file,
+            keep,
+            force,
+            no_lint,
+        } => commands::skill_deploy::deploy(&instance, &file, keep, force, !no_lint).await,
+        Commands::SkillLint {
+            path,
+            instance,
+            json,
+        } => commands::skill_lint::run(&path, instance.as_deref(), json).await,
+        Commands::SkillRollback {
+            instance,
+            skill,
+            to,
+        } => commands::skill_deploy::rollback(&instance, &skill, to.as_deref()).await,
+        Commands::SkillRollout {
+            selector,
+            file,
+            rollback,
+            to,
+            canary,
+            batch_size,
+            concurrency,
+            max_failures,
+            keep,
+            force,
+            no_lint,
+            dry_run,
+            json,
+        } => {
+            let action = match (file, rollback) {
+                (Some(file), _) => commands::skill_rollout::RolloutAction::Deploy {
+                    file,
+                    keep,
+                    force,
+                    lint: !no_lint,
+                },
+                (None, Some(skill)) => {
+                    commands::skill_rollout::RolloutAction::Rollback { skill, to }
+                }
+                (None, None) => unreachable!("clap requires --file or --rollback"),
+            };
+            commands::skill_rollout::run(commands::skill_rollout::SkillRolloutParams {
+                selector,
+                action,
+                canary,
+                batch_size,
+                concurrency,
+                max_failures,
+                dry_run,
+                json,
+            })
+            .await
         }
+        Commands::Tag {
+            instance,
+            add,
+            remove,
+        } => commands::fleet::tag(&instance, &add, &remove),
         Commands::SkillDiff { instance, dir } => commands::skill_diff::diff(&instance, &dir).await,
         Commands::SkillRemove { instance, skill } => {
             commands::skill_remove::remove(&instance, &skill).await
         }
-        Commands::SkillList { instance } => commands::skill_remove::list(&instance).await,
+        Commands::SkillList { instance, versions } => {
+            commands::skill_remove::list(&instance, versions).await
+        }
         Commands::SkillCheckPerms {
             instance,
             skill,
@@ -1505,7 +2535,33 @@ async fn async_main() -> anyhow::Result<()> {
             instance,
             auth_key,
             port,
-        } => commands::tailscale_funnel::setup(&instance, &auth_key, port).await,
+        } => commands::tailscale_funnel::setup(&instance, auth_key.as_deref(), port).await,
+        Commands::TailscaleDevices { prune, json } => commands::tailnet::devices(prune, json).await,
+        Commands::TailscaleAcl { funnel, apply } => commands::tailnet::acl(funnel, apply).await,
+        Commands::TailscaleServe {
+            instance,
+            mode,
+            port,
+            dry_run,
+        } => commands::tailnet::serve(&instance, &mode, port, dry_run).await,
+        Commands::Expose {
+            instance,
+            mode,
+            domain,
+            server,
+            email,
+            auth_key,
+        } => {
+            commands::expose::run(commands::expose::ExposeParams {
+                instance,
+                mode,
+                domain,
+                server,
+                email,
+                auth_key,
+            })
+            .await
+        }
         #[cfg(feature = "byteplus")]
         Commands::ArkApiKey {
             access_key,
@@ -1532,8 +2588,27 @@ async fn async_main() -> anyhow::Result<()> {
         Commands::ArkChat {
             api_key,
             endpoint_id,
+            system,
+            history,
+            no_stream,
             prompt,
-        } => commands::ark::chat(&api_key, &endpoint_id, &prompt).await,
+        } => {
+            commands::ark::chat(commands::ark::ArkChatParams {
+                api_key,
+                endpoint_id,
+                prompt,
+                system,
+                history,
+                no_stream,
+            })
+            .await
+        }
+        #[cfg(feature = "byteplus")]
+        Commands::ArkModels {
+            access_key,
+            secret_key,
+            json,
+        } => commands::ark::models(&access_key, &secret_key, json).await,
         #[cfg(feature = "digitalocean")]
         Commands::DoSnapshot {
             do_token,
@@ -1747,6 +2822,117 @@ async fn async_main() -> anyhow::Result<()> {
         Commands::OpenclawInstall { instance, version } => {
             commands::openclaw_version::run_install(&instance, &version).await
         }
+        Commands::Upgrade {
+            selector,
+            to,
+            canary,
+            batch_size,
+            concurrency,
+            max_failures,
+            dry_run,
+            json,
+        } => {
+            commands::upgrade::run(commands::upgrade::UpgradeParams {
+                selector,
+                to,
+                canary,
+                batch_size,
+                concurrency,
+                max_failures,
+                dry_run,
+                json,
+            })
+            .await
+        }
+        Commands::Status {
+            selector,
+            refresh,
+            json,
+        } => {
+            commands::version_status::run(commands::version_status::StatusParams {
+                selector,
+                refresh,
+                json,
+            })
+            .await
+        }
+        Commands::Ssh {
+            instance,
+            tty,
+            print,
+            command,
+        } => commands::ssh::shell(&instance, tty, print, command).await,
+        Commands::Exec {
+            selector,
+            concurrency,
+            json,
+            command,
+        } => commands::ssh::exec(&selector, concurrency, json, command).await,
+        Commands::Tunnel {
+            instance,
+            forward,
+            bind,
+        } => commands::ssh::tunnel(&instance, &forward, &bind).await,
+        Commands::Logs {
+            instance,
+            source,
+            follow,
+            since,
+            grep,
+            lines,
+            container,
+            no_redact,
+        } => {
+            commands::logs::run(
+                &instance,
+                commands::logs::LogQuery {
+                    source,
+                    follow,
+                    since,
+                    grep,
+                    lines,
+                    container,
+                },
+                !no_redact,
+            )
+            .await
+        }
+        Commands::Chat {
+            instance,
+            agent,
+            once,
+            attach,
+            system,
+            via,
+            no_stream,
+            timeout,
+        } => {
+            commands::chat::run(commands::chat::ChatParams {
+                instance,
+                agent,
+                once,
+                attach,
+                system,
+                via,
+                no_stream,
+                timeout,
+            })
+            .await
+        }
+        Commands::ScanSecurity {
+            instance,
+            format,
+            out,
+            fail_on,
+        } => {
+            commands::scan_security::run(commands::scan_security::ScanSecurityParams {
+                instance,
+                format,
+                out,
+                fail_on,
+            })
+            .await
+        }
         Commands::OpenclawGatewayToken { instance } => {
             commands::openclaw_gateway_token::run(&instance).await
         }
@@ -1954,6 +3140,7 @@ async fn async_main() -> anyhow::Result<()> {
             source,
             prompt,
             timeout,
+            chunk_bytes,
             json,
         } => {
             commands::wiki::ingest(commands::wiki::WikiIngestParams {
@@ -1963,6 +3150,7 @@ async fn async_main() -> anyhow::Result<()> {
                 source,
                 prompt,
                 timeout,
+                chunk_bytes,
                 json,
             })
             .await
@@ -1983,6 +3171,24 @@ async fn async_main() -> anyhow::Result<()> {
             })
             .await
         }
+        Commands::WikiImport {
+            instance,
+            agent,
+            project,
+            archive,
+            replace,
+            json,
+        } => {
+            commands::wiki::import(commands::wiki::WikiImportParams {
+                instance,
+                agent,
+                project,
+                archive,
+                replace,
+                json,
+            })
+            .await
+        }
         Commands::WikiDelete {
             instance,
             agent,
@@ -1997,6 +3203,62 @@ async fn async_main() -> anyhow::Result<()> {
             })
             .await
         }
+        Commands::WikiSync {
+            instance,
+            agent,
+            project,
+            dir,
+            dry_run,
+            prefer,
+            json,
+        } => {
+            commands::wiki_sync::sync(commands::wiki_sync::WikiSyncParams {
+                instance,
+                agent,
+                project,
+                dir,
+                dry_run,
+                prefer,
+                json,
+            })
+            .await
+        }
+        Commands::WikiSearch {
+            instance,
+            agent,
+            project,
+            query,
+            limit,
+            no_refresh,
+            json,
+        } => {
+            commands::wiki_search::search(commands::wiki_search::WikiSearchParams {
+                instance,
+                agent,
+                project,
+                query,
+                limit,
+                no_refresh,
+                json,
+            })
+            .await
+        }
+        Commands::WikiGraph {
+            instance,
+            agent,
+            project,
+            format,
+            no_refresh,
+        } => {
+            commands::wiki_search::graph(commands::wiki_search::WikiGraphParams {
+                instance,
+                agent,
+                project,
+                format,
+                no_refresh,
+            })
+            .await
+        }
         Commands::PluginInstall { instance, plugin } => {
             commands::plugin_install::run(&instance, &plugin).await
         }
@@ -2073,6 +3335,50 @@ async fn async_main() -> anyhow::Result<()> {
         Commands::MemoryDownload { instance, output } => {
             commands::memory_download::run(&instance, &output).await
         }
+        Commands::Backup => commands::backup::run().await,
+        Commands::ListBackups { json } => commands::list_backups::run(json).await,
+        Commands::BackupRemote {
+            instance,
+            keep_last,
+            keep_daily,
+            keep_weekly,
+            every,
+            json,
+        } => {
+            let params = commands::backup_remote::BackupRemoteParams {
+                query: instance,
+                retention: commands::backup_remote::RetentionPolicy {
+                    keep_last,
+                    keep_daily,
+                    keep_weekly,
+                },
+                json,
+            };
+            match every {
+                Some(interval) => {
+                    let every = commands::backup_remote::parse_interval(&interval)?;
+                    commands::backup_remote::run_scheduled(params, every).await
+                }
+                None => commands::backup_remote::run(&params).await,
+            }
+        }
+        Commands::RestoreBackup {
+            instance,
+            archive,
+            yes,
+            dry_run,
+            no_safety_backup,
+        } => {
+            commands::restore_backup::run(commands::restore_backup::RestoreBackupParams {
+                query: instance,
+                archive,
+                yes,
+                dry_run,
+                skip_safety_backup: no_safety_backup,
+            })
+            .await
+        }
+        Commands::Stats { json } => commands::stats::run(json),
         #[cfg(feature = "web-ui")]
         Commands::Serve { port } => commands::serve::run(port).await,
     }
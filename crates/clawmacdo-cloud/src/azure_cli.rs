@@ -1,5 +1,6 @@
 use async_trait::async_trait;
 use clawmacdo_core::error::AppError;
+use clawmacdo_core::metrics;
 use serde::Deserialize;
 use std::process::Command;
 use tokio::time::{sleep, Duration, Instant};
@@ -245,6 +246,13 @@ impl AzureCliProvider {
 
     /// Execute an az CLI command and return its stdout.
     fn execute_az_cli(&self, args: &[&str]) -> Result<String, AppError> {
+        let started = std::time::Instant::now();
+        let result = Self::run_az_cli(args);
+        metrics::observe_cloud_api("azure", started, result.is_ok());
+        result
+    }
+
+    fn run_az_cli(args: &[&str]) -> Result<String, AppError> {
         let output = Command::new("az")
             .args(args)
             .arg("--output")
@@ -379,7 +387,7 @@ impl CloudProvider for AzureCliProvider {
             AppError::Azure(format!("Failed to parse Azure VM create response: {e}"))
         })?;
 
-        // Open required ports
+        // Open required ports (the gateway port stays closed; see `clawmacdo expose`)
         let _ = self.execute_az_cli(&[
             "vm",
             "open-port",
@@ -388,7 +396,7 @@ impl CloudProvider for AzureCliProvider {
             "--name",
             &params.name,
             "--port",
-            "22,80,443,18789",
+            "22,80,443",
             "--priority",
             "100",
         ]);
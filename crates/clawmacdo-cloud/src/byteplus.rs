@@ -1,6 +1,8 @@
 use crate::cloud_provider::{CloudProvider, CreateInstanceParams, InstanceInfo, KeyInfo};
 use chrono::Utc;
+use clawmacdo_core::config::OPENCLAW_GATEWAY_PORT;
 use clawmacdo_core::error::AppError;
+use clawmacdo_core::metrics;
 use hmac::{Hmac, Mac};
 use reqwest::Client;
 use sha2::{Digest, Sha256};
@@ -159,6 +161,23 @@ impl BytePlusClient {
         payload: &str,
         method: &str,
         host_override: Option<&str>,
+    ) -> Result<serde_json::Value, AppError> {
+        let started = std::time::Instant::now();
+        let result = self
+            .send_signed_request(service, action, version, payload, method, host_override)
+            .await;
+        metrics::observe_cloud_api("byteplus", started, result.is_ok());
+        result
+    }
+
+    async fn send_signed_request(
+        &self,
+        service: &str,
+        action: &str,
+        version: &str,
+        payload: &str,
+        method: &str,
+        host_override: Option<&str>,
     ) -> Result<serde_json::Value, AppError> {
         let endpoint = match host_override {
             Some(h) => format!("https://{h}"),
@@ -518,7 +537,9 @@ impl BytePlusClient {
         Ok(subnet_id)
     }
 
-    /// Find or create a security group with ports 22, 80, 443, 18789.
+    /// Find or create a security group with ports 22, 80 and 443. The gateway
+    /// port stays closed (it listens on loopback behind `clawmacdo expose`), so
+    /// a rule for it left on a reused group is revoked.
     async fn ensure_security_group(&self, vpc_id: &str) -> Result<String, AppError> {
         let list_payload = serde_json::json!({
             "VpcId": vpc_id,
@@ -568,12 +589,7 @@ impl BytePlusClient {
         };
 
         // Always ensure ingress rules exist (idempotent — duplicates return an error we ignore)
-        for (port, desc) in [
-            (22, "SSH"),
-            (80, "HTTP"),
-            (443, "HTTPS"),
-            (18789, "OpenClaw Gateway"),
-        ] {
+        for (port, desc) in [(22, "SSH"), (80, "HTTP"), (443, "HTTPS")] {
             let rule_payload = serde_json::json!({
                 "SecurityGroupId": sg_id,
                 "Direction": "ingress",
@@ -603,6 +619,20 @@ impl BytePlusClient {
             }
         }
 
+        // Groups created by older releases opened the gateway port; a missing
+        // rule is not an error.
+        let revoke_payload = serde_json::json!({
+            "SecurityGroupId": sg_id,
+            "Direction": "ingress",
+            "Protocol": "tcp",
+            "PortStart": OPENCLAW_GATEWAY_PORT,
+            "PortEnd": OPENCLAW_GATEWAY_PORT,
+            "CidrIp": "0.0.0.0/0"
+        });
+        let _ = self
+            .vpc_request("RevokeSecurityGroupIngress", &revoke_payload.to_string())
+            .await;
+
         Ok(sg_id)
     }
 
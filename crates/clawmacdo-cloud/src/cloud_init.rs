@@ -35,12 +35,12 @@ packages:
 
 runcmd:
   # --- Firewall (basic - provision modules add DOCKER-USER rules later) ---
+  # The gateway port (18789) stays closed; `clawmacdo expose` publishes it.
   - ufw default deny incoming
   - ufw default allow outgoing
   - ufw allow 22/tcp
   - ufw allow 80/tcp
   - ufw allow 443/tcp
-  - ufw allow 18789/tcp
   - ufw --force enable
 
   # --- Node.js 24 LTS via NodeSource ---
@@ -91,13 +91,12 @@ apt-get upgrade -y
 
 apt-get install -y curl gnupg ufw git build-essential docker.io fail2ban unattended-upgrades
 
-# --- Firewall (basic) ---
+# --- Firewall (basic; gateway port 18789 stays closed, see `clawmacdo expose`) ---
 ufw default deny incoming
 ufw default allow outgoing
 ufw allow 22/tcp
 ufw allow 80/tcp
 ufw allow 443/tcp
-ufw allow 18789/tcp
 ufw --force enable
 
 # --- Node.js 24 LTS via NodeSource ---
@@ -1,5 +1,6 @@
 use clawmacdo_core::config;
 use clawmacdo_core::error::AppError;
+use clawmacdo_core::metrics;
 use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
 use serde::{Deserialize, Serialize};
 
@@ -162,6 +163,15 @@ impl DropletInfo {
 }
 
 impl DoClient {
+    /// Send a request, recording its latency and outcome in the metrics registry.
+    async fn send(&self, req: reqwest::RequestBuilder) -> Result<reqwest::Response, AppError> {
+        let started = std::time::Instant::now();
+        let result = req.send().await;
+        let ok = matches!(&result, Ok(resp) if resp.status().is_success());
+        metrics::observe_cloud_api("digitalocean", started, ok);
+        Ok(result?)
+    }
+
     /// NNew.
     pub fn new(token: &str) -> Result<Self, AppError> {
         let mut headers = HeaderMap::new();
@@ -189,10 +199,11 @@ impl DoClient {
     ) -> Result<SshKeyInfo, AppError> {
         let body = CreateKeyRequest { name, public_key };
         let resp = self
-            .client
-            .post(format!("{API_BASE}/account/keys"))
-            .json(&body)
-            .send()
+            .send(
+                self.client
+                    .post(format!("{API_BASE}/account/keys"))
+                    .json(&body),
+            )
             .await?;
 
         if !resp.status().is_success() {
@@ -232,10 +243,7 @@ impl DoClient {
         };
 
         let resp = self
-            .client
-            .post(format!("{API_BASE}/droplets"))
-            .json(&body)
-            .send()
+            .send(self.client.post(format!("{API_BASE}/droplets")).json(&body))
             .await?;
 
         if !resp.status().is_success() {
@@ -308,9 +316,7 @@ impl DoClient {
     /// GGet droplet.
     pub async fn get_droplet(&self, droplet_id: u64) -> Result<DropletInfo, AppError> {
         let resp = self
-            .client
-            .get(format!("{API_BASE}/droplets/{droplet_id}"))
-            .send()
+            .send(self.client.get(format!("{API_BASE}/droplets/{droplet_id}")))
             .await?;
 
         if !resp.status().is_success() {
@@ -329,12 +335,10 @@ impl DoClient {
     /// LList droplets.
     pub async fn list_droplets(&self) -> Result<Vec<DropletInfo>, AppError> {
         let resp = self
-            .client
-            .get(format!(
+            .send(self.client.get(format!(
                 "{API_BASE}/droplets?tag_name={}",
                 config::DROPLET_TAG
-            ))
-            .send()
+            )))
             .await?;
 
         if !resp.status().is_success() {
@@ -352,9 +356,7 @@ impl DoClient {
     /// List all droplets (no tag filter).
     pub async fn list_all_droplets(&self) -> Result<Vec<DropletInfo>, AppError> {
         let resp = self
-            .client
-            .get(format!("{API_BASE}/droplets?per_page=200"))
-            .send()
+            .send(self.client.get(format!("{API_BASE}/droplets?per_page=200")))
             .await?;
 
         if !resp.status().is_success() {
@@ -372,9 +374,10 @@ impl DoClient {
     /// Delete a droplet by ID.
     pub async fn delete_droplet(&self, droplet_id: u64) -> Result<(), AppError> {
         let resp = self
-            .client
-            .delete(format!("{API_BASE}/droplets/{droplet_id}"))
-            .send()
+            .send(
+                self.client
+                    .delete(format!("{API_BASE}/droplets/{droplet_id}")),
+            )
             .await?;
 
         if !resp.status().is_success() {
@@ -392,9 +395,7 @@ impl DoClient {
     /// LList ssh keys.
     pub async fn list_ssh_keys(&self) -> Result<Vec<AccountSshKey>, AppError> {
         let resp = self
-            .client
-            .get(format!("{API_BASE}/account/keys"))
-            .send()
+            .send(self.client.get(format!("{API_BASE}/account/keys")))
             .await?;
 
         if !resp.status().is_success() {
@@ -412,9 +413,10 @@ impl DoClient {
     /// List all droplet snapshots.
     pub async fn list_snapshots(&self) -> Result<Vec<SnapshotInfo>, AppError> {
         let resp = self
-            .client
-            .get(format!("{API_BASE}/snapshots?resource_type=droplet"))
-            .send()
+            .send(
+                self.client
+                    .get(format!("{API_BASE}/snapshots?resource_type=droplet")),
+            )
             .await?;
 
         if !resp.status().is_success() {
@@ -453,10 +455,7 @@ impl DoClient {
         };
 
         let resp = self
-            .client
-            .post(format!("{API_BASE}/droplets"))
-            .json(&body)
-            .send()
+            .send(self.client.post(format!("{API_BASE}/droplets")).json(&body))
             .await?;
 
         if !resp.status().is_success() {
@@ -475,9 +474,10 @@ impl DoClient {
     /// DDelete ssh key.
     pub async fn delete_ssh_key(&self, ssh_key_id: u64) -> Result<(), AppError> {
         let resp = self
-            .client
-            .delete(format!("{API_BASE}/account/keys/{ssh_key_id}"))
-            .send()
+            .send(
+                self.client
+                    .delete(format!("{API_BASE}/account/keys/{ssh_key_id}")),
+            )
             .await?;
 
         if !resp.status().is_success() {
@@ -502,10 +502,11 @@ impl DoClient {
     ) -> Result<ActionInfo, AppError> {
         let body = DropletActionRequest { action_type, name };
         let resp = self
-            .client
-            .post(format!("{API_BASE}/droplets/{droplet_id}/actions"))
-            .json(&body)
-            .send()
+            .send(
+                self.client
+                    .post(format!("{API_BASE}/droplets/{droplet_id}/actions"))
+                    .json(&body),
+            )
             .await?;
 
         if !resp.status().is_success() {
@@ -547,9 +548,7 @@ impl DoClient {
     /// Get the status of an action by ID.
     pub async fn get_action(&self, action_id: u64) -> Result<ActionInfo, AppError> {
         let resp = self
-            .client
-            .get(format!("{API_BASE}/actions/{action_id}"))
-            .send()
+            .send(self.client.get(format!("{API_BASE}/actions/{action_id}")))
             .await?;
 
         if !resp.status().is_success() {
@@ -601,9 +600,10 @@ impl DoClient {
         droplet_id: u64,
     ) -> Result<Vec<SnapshotInfo>, AppError> {
         let resp = self
-            .client
-            .get(format!("{API_BASE}/droplets/{droplet_id}/snapshots"))
-            .send()
+            .send(
+                self.client
+                    .get(format!("{API_BASE}/droplets/{droplet_id}/snapshots")),
+            )
             .await?;
 
         if !resp.status().is_success() {
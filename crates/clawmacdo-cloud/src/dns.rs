@@ -0,0 +1,716 @@
+//! DNS providers that give each deployment a stable `<hostname>.<zone>` name.
+//!
+//! `CloudflareDns` and `DoDns` (DigitalOcean Domains) manage the A/AAAA
+//! records of a single zone. The active provider is chosen with
+//! `CLAWMACDO_DNS_PROVIDER=cloudflare|digitalocean` and reads
+//! `CLAWMACDO_DNS_ZONE`, `CLAWMACDO_DNS_TOKEN` (falling back to
+//! `CLOUDFLARE_API_TOKEN` / `DO_TOKEN`), `CLAWMACDO_DNS_TTL` (default 300) and
+//! `CLAWMACDO_DNS_API_URL` (to point the client at a different endpoint).
+
+use async_trait::async_trait;
+use clawmacdo_core::error::AppError;
+use clawmacdo_core::metrics;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::net::IpAddr;
+
+pub const PROVIDER_ENV: &str = "CLAWMACDO_DNS_PROVIDER";
+pub const DEFAULT_TTL: u32 = 300;
+
+const CLOUDFLARE_API: &str = "https://api.cloudflare.com/client/v4";
+const DIGITALOCEAN_API: &str = "https://api.digitalocean.com/v2";
+
+/// One A or AAAA record.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DnsRecord {
+    pub id: String,
+    /// `A` or `AAAA`.
+    pub record_type: String,
+    /// Fully qualified name, without the trailing dot.
+    pub name: String,
+    pub content: String,
+}
+
+#[async_trait]
+pub trait DnsProvider: Send + Sync {
+    /// Provider name for messages, e.g. `cloudflare`.
+    fn name(&self) -> &'static str;
+
+    /// The zone records are created in, e.g. `example.com`.
+    fn zone(&self) -> &str;
+
+    /// The A and AAAA records for `fqdn`.
+    async fn records(&self, fqdn: &str) -> Result<Vec<DnsRecord>, AppError>;
+
+    async fn create(&self, fqdn: &str, record_type: &str, content: &str) -> Result<(), AppError>;
+
+    async fn update(&self, record: &DnsRecord, content: &str) -> Result<(), AppError>;
+
+    async fn delete(&self, record: &DnsRecord) -> Result<(), AppError>;
+}
+
+/// `<hostname>.<zone>`, lowercased and validated. A hostname that already
+/// ends in the zone is used as is.
+pub fn fqdn(hostname: &str, zone: &str) -> Result<String, AppError> {
+    let zone = zone.trim().trim_end_matches('.').to_ascii_lowercase();
+    let hostname = hostname.trim().trim_end_matches('.').to_ascii_lowercase();
+    let name = if hostname == zone || hostname.ends_with(&format!(".{zone}")) {
+        hostname
+    } else {
+        format!("{hostname}.{zone}")
+    };
+    let valid_label = |label: &str| {
+        !label.is_empty()
+            && label.len() <= 63
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    };
+    if zone.is_empty() || name.len() > 253 || !name.split('.').all(valid_label) {
+        return Err(AppError::Dns(format!("Invalid DNS name '{name}'")));
+    }
+    Ok(name)
+}
+
+fn record_type_for(ip: &str) -> Result<&'static str, AppError> {
+    match ip.parse::<IpAddr>() {
+        Ok(IpAddr::V4(_)) => Ok("A"),
+        Ok(IpAddr::V6(_)) => Ok("AAAA"),
+        Err(_) => Err(AppError::Dns(format!("'{ip}' is not an IP address"))),
+    }
+}
+
+/// Records may only be managed inside the provider's zone.
+fn check_zone(dns: &dyn DnsProvider, fqdn: &str) -> Result<(), AppError> {
+    let zone = dns.zone();
+    if fqdn != zone && !fqdn.ends_with(&format!(".{zone}")) {
+        return Err(AppError::Dns(format!("{fqdn} is not in the zone {zone}")));
+    }
+    Ok(())
+}
+
+/// Point `fqdn` at `ip` and nothing else: reuse or update one record of the
+/// matching type, then remove any other A/AAAA records on the name.
+pub async fn upsert(dns: &dyn DnsProvider, fqdn: &str, ip: &str) -> Result<(), AppError> {
+    check_zone(dns, fqdn)?;
+    let record_type = record_type_for(ip)?;
+    let existing = dns.records(fqdn).await?;
+    let keep = existing
+        .iter()
+        .position(|r| r.record_type == record_type && r.content == ip)
+        .or_else(|| existing.iter().position(|r| r.record_type == record_type));
+    match keep {
+        Some(i) if existing[i].content == ip => {}
+        Some(i) => dns.update(&existing[i], ip).await?,
+        None => dns.create(fqdn, record_type, ip).await?,
+    }
+    for (i, record) in existing.iter().enumerate() {
+        if Some(i) != keep {
+            dns.delete(record).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Remove the A/AAAA records of `fqdn`. With `only_ip`, records pointing
+/// elsewhere (e.g. at a migration target) are left alone. Returns the number
+/// of records removed.
+pub async fn remove(
+    dns: &dyn DnsProvider,
+    fqdn: &str,
+    only_ip: Option<&str>,
+) -> Result<usize, AppError> {
+    check_zone(dns, fqdn)?;
+    let mut removed = 0;
+    for record in dns.records(fqdn).await? {
+        if only_ip.is_some_and(|ip| ip != record.content) {
+            continue;
+        }
+        dns.delete(&record).await?;
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+/// Settings shared by both providers.
+#[derive(Debug, Clone)]
+pub struct DnsConfig {
+    pub zone: String,
+    pub token: String,
+    pub ttl: u32,
+    /// API base URL; the provider's public endpoint when `None`.
+    pub api_url: Option<String>,
+}
+
+impl DnsConfig {
+    fn from_env(token_fallback: &str) -> Result<Self, AppError> {
+        fn var(name: &str) -> Option<String> {
+            std::env::var(name)
+                .ok()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        }
+        let ttl = match var("CLAWMACDO_DNS_TTL") {
+            Some(v) => v
+                .parse()
+                .map_err(|_| AppError::Dns(format!("Invalid CLAWMACDO_DNS_TTL '{v}'")))?,
+            None => DEFAULT_TTL,
+        };
+        Ok(Self {
+            zone: var("CLAWMACDO_DNS_ZONE")
+                .map(|z| z.trim_end_matches('.').to_ascii_lowercase())
+                .ok_or_else(|| AppError::MissingParam("CLAWMACDO_DNS_ZONE".into()))?,
+            token: var("CLAWMACDO_DNS_TOKEN")
+                .or_else(|| var(token_fallback))
+                .ok_or_else(|| AppError::MissingParam("CLAWMACDO_DNS_TOKEN".into()))?,
+            ttl,
+            api_url: var("CLAWMACDO_DNS_API_URL"),
+        })
+    }
+}
+
+/// Build the provider selected by `CLAWMACDO_DNS_PROVIDER`; `None` when DNS
+/// management is not configured.
+pub fn configured_provider() -> Result<Option<Box<dyn DnsProvider>>, AppError> {
+    let kind = std::env::var(PROVIDER_ENV).unwrap_or_default();
+    match kind.trim().to_ascii_lowercase().as_str() {
+        "" | "none" => Ok(None),
+        "cloudflare" => Ok(Some(Box::new(CloudflareDns::new(DnsConfig::from_env(
+            "CLOUDFLARE_API_TOKEN",
+        )?)))),
+        "digitalocean" | "do" => Ok(Some(Box::new(DoDns::new(DnsConfig::from_env("DO_TOKEN")?)))),
+        other => Err(AppError::Dns(format!(
+            "Unknown {PROVIDER_ENV} value '{other}' (expected cloudflare or digitalocean)"
+        ))),
+    }
+}
+
+/// Send a request, recording its latency and outcome, and turn non-2xx
+/// responses into `AppError::Dns` using `message` to extract the API's error.
+async fn send(
+    provider: &str,
+    req: reqwest::RequestBuilder,
+    message: fn(&serde_json::Value) -> Option<String>,
+) -> Result<serde_json::Value, AppError> {
+    let started = std::time::Instant::now();
+    let result = req.send().await;
+    let ok = matches!(&result, Ok(resp) if resp.status().is_success());
+    metrics::observe_cloud_api(provider, started, ok);
+    let resp = result.map_err(|e| AppError::Dns(format!("{provider} request failed: {e}")))?;
+    let status = resp.status();
+    let text = resp.text().await.unwrap_or_default();
+    let body: serde_json::Value = serde_json::from_str(&text).unwrap_or(serde_json::Value::Null);
+    if !status.is_success() {
+        let detail = message(&body).unwrap_or(text);
+        return Err(AppError::Dns(format!(
+            "{provider} returned {status}: {detail}"
+        )));
+    }
+    Ok(body)
+}
+
+// ── Cloudflare ──────────────────────────────────────────────────────────────
+
+pub struct CloudflareDns {
+    config: DnsConfig,
+    base: String,
+    client: reqwest::Client,
+    zone_id: tokio::sync::OnceCell<String>,
+}
+
+#[derive(Deserialize)]
+struct CfZone {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct CfRecord {
+    id: String,
+    #[serde(rename = "type")]
+    record_type: String,
+    name: String,
+    content: String,
+}
+
+fn cloudflare_message(body: &serde_json::Value) -> Option<String> {
+    let errors = body.get("errors")?.as_array()?;
+    let messages: Vec<&str> = errors
+        .iter()
+        .filter_map(|e| e.get("message").and_then(|m| m.as_str()))
+        .collect();
+    (!messages.is_empty()).then(|| messages.join("; "))
+}
+
+impl CloudflareDns {
+    pub fn new(config: DnsConfig) -> Self {
+        let base = config
+            .api_url
+            .as_deref()
+            .unwrap_or(CLOUDFLARE_API)
+            .trim_end_matches('/')
+            .to_string();
+        Self {
+            config,
+            base,
+            client: reqwest::Client::new(),
+            zone_id: tokio::sync::OnceCell::new(),
+        }
+    }
+
+    async fn call(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        query: &[(&str, &str)],
+        body: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, AppError> {
+        let mut req = self
+            .client
+            .request(method, format!("{}{path}", self.base))
+            .bearer_auth(&self.config.token)
+            .query(query);
+        if let Some(body) = body {
+            req = req.json(&body);
+        }
+        send("cloudflare", req, cloudflare_message).await
+    }
+
+    async fn zone_id(&self) -> Result<&str, AppError> {
+        self.zone_id
+            .get_or_try_init(|| async {
+                let body = self
+                    .call(
+                        reqwest::Method::GET,
+                        "/zones",
+                        &[("name", self.config.zone.as_str())],
+                        None,
+                    )
+                    .await?;
+                let zones: Vec<CfZone> =
+                    serde_json::from_value(body["result"].clone()).unwrap_or_default();
+                zones.into_iter().next().map(|z| z.id).ok_or_else(|| {
+                    AppError::Dns(format!(
+                        "Cloudflare zone '{}' not found for this token",
+                        self.config.zone
+                    ))
+                })
+            })
+            .await
+            .map(String::as_str)
+    }
+}
+
+#[async_trait]
+impl DnsProvider for CloudflareDns {
+    fn name(&self) -> &'static str {
+        "cloudflare"
+    }
+
+    fn zone(&self) -> &str {
+        &self.config.zone
+    }
+
+    async fn records(&self, fqdn: &str) -> Result<Vec<DnsRecord>, AppError> {
+        let zone_id = self.zone_id().await?;
+        let body = self
+            .call(
+                reqwest::Method::GET,
+                &format!("/zones/{zone_id}/dns_records"),
+                &[("name", fqdn), ("per_page", "100")],
+                None,
+            )
+            .await?;
+        let records: Vec<CfRecord> = serde_json::from_value(body["result"].clone())?;
+        Ok(records
+            .into_iter()
+            .filter(|r| r.record_type == "A" || r.record_type == "AAAA")
+            .map(|r| DnsRecord {
+                id: r.id,
+                record_type: r.record_type,
+                name: r.name,
+                content: r.content,
+            })
+            .collect())
+    }
+
+    async fn create(&self, fqdn: &str, record_type: &str, content: &str) -> Result<(), AppError> {
+        let zone_id = self.zone_id().await?;
+        self.call(
+            reqwest::Method::POST,
+            &format!("/zones/{zone_id}/dns_records"),
+            &[],
+            Some(json!({
+                "type": record_type,
+                "name": fqdn,
+                "content": content,
+                "ttl": self.config.ttl,
+                "proxied": false,
+            })),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn update(&self, record: &DnsRecord, content: &str) -> Result<(), AppError> {
+        let zone_id = self.zone_id().await?;
+        self.call(
+            reqwest::Method::PATCH,
+            &format!("/zones/{zone_id}/dns_records/{}", record.id),
+            &[],
+            Some(json!({ "content": content, "ttl": self.config.ttl })),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn delete(&self, record: &DnsRecord) -> Result<(), AppError> {
+        let zone_id = self.zone_id().await?;
+        self.call(
+            reqwest::Method::DELETE,
+            &format!("/zones/{zone_id}/dns_records/{}", record.id),
+            &[],
+            None,
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+// ── DigitalOcean Domains ────────────────────────────────────────────────────
+
+pub struct DoDns {
+    config: DnsConfig,
+    base: String,
+    client: reqwest::Client,
+}
+
+#[derive(Deserialize)]
+struct DoRecord {
+    id: u64,
+    #[serde(rename = "type")]
+    record_type: String,
+    /// Relative to the zone; `@` for the apex.
+    name: String,
+    data: String,
+}
+
+fn digitalocean_message(body: &serde_json::Value) -> Option<String> {
+    body.get("message")?.as_str().map(str::to_string)
+}
+
+impl DoDns {
+    pub fn new(config: DnsConfig) -> Self {
+        let base = config
+            .api_url
+            .as_deref()
+            .unwrap_or(DIGITALOCEAN_API)
+            .trim_end_matches('/')
+            .to_string();
+        Self {
+            config,
+            base,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// DigitalOcean record names are relative to the domain.
+    fn relative_name(&self, fqdn: &str) -> String {
+        match fqdn.strip_suffix(&format!(".{}", self.config.zone)) {
+            Some(name) => name.to_string(),
+            None => "@".to_string(),
+        }
+    }
+
+    async fn call(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        query: &[(&str, &str)],
+        body: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, AppError> {
+        let mut req = self
+            .client
+            .request(
+                method,
+                format!("{}/domains/{}/records{path}", self.base, self.config.zone),
+            )
+            .bearer_auth(&self.config.token)
+            .query(query);
+        if let Some(body) = body {
+            req = req.json(&body);
+        }
+        send("digitalocean", req, digitalocean_message).await
+    }
+}
+
+#[async_trait]
+impl DnsProvider for DoDns {
+    fn name(&self) -> &'static str {
+        "digitalocean"
+    }
+
+    fn zone(&self) -> &str {
+        &self.config.zone
+    }
+
+    async fn records(&self, fqdn: &str) -> Result<Vec<DnsRecord>, AppError> {
+        let body = self
+            .call(
+                reqwest::Method::GET,
+                "",
+                &[("name", fqdn), ("per_page", "200")],
+                None,
+            )
+            .await?;
+        let records: Vec<DoRecord> = serde_json::from_value(body["domain_records"].clone())?;
+        let relative = self.relative_name(fqdn);
+        Ok(records
+            .into_iter()
+            .filter(|r| r.name == relative && (r.record_type == "A" || r.record_type == "AAAA"))
+            .map(|r| DnsRecord {
+                id: r.id.to_string(),
+                record_type: r.record_type,
+                name: fqdn.to_string(),
+                content: r.data,
+            })
+            .collect())
+    }
+
+    async fn create(&self, fqdn: &str, record_type: &str, content: &str) -> Result<(), AppError> {
+        self.call(
+            reqwest::Method::POST,
+            "",
+            &[],
+            Some(json!({
+                "type": record_type,
+                "name": self.relative_name(fqdn),
+                "data": content,
+                "ttl": self.config.ttl,
+            })),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn update(&self, record: &DnsRecord, content: &str) -> Result<(), AppError> {
+        self.call(
+            reqwest::Method::PATCH,
+            &format!("/{}", record.id),
+            &[],
+            Some(json!({ "data": content, "ttl": self.config.ttl })),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn delete(&self, record: &DnsRecord) -> Result<(), AppError> {
+        self.call(
+            reqwest::Method::DELETE,
+            &format!("/{}", record.id),
+            &[],
+            None,
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clawmacdo_core::test_http::{self, Response};
+    use std::collections::BTreeMap;
+    use std::sync::{Arc, Mutex};
+
+    /// `id -> (type, fqdn, content)`
+    type Zone = Arc<Mutex<BTreeMap<u64, (String, String, String)>>>;
+
+    fn config(api_url: String) -> DnsConfig {
+        DnsConfig {
+            zone: "example.com".into(),
+            token: "test-token".into(),
+            ttl: 120,
+            api_url: Some(api_url),
+        }
+    }
+
+    /// Answer one stand-in request; covers the Cloudflare and DigitalOcean
+    /// calls the providers make for the `example.com` zone.
+    fn respond(
+        zone: &Zone,
+        method: &str,
+        path: &str,
+        query: &str,
+        body: &serde_json::Value,
+    ) -> (&'static str, serde_json::Value) {
+        let mut zone = zone.lock().unwrap();
+        let param = |key: &str| {
+            query
+                .split('&')
+                .find_map(|kv| kv.strip_prefix(&format!("{key}=")))
+                .unwrap_or_default()
+                .to_string()
+        };
+        let relative = |fqdn: &str| fqdn.strip_suffix(".example.com").unwrap_or("@").to_string();
+        let next_id = zone.keys().last().map_or(1, |id| id + 1);
+        let (cf, rest) = match (
+            path.strip_prefix("/zones/zone-1/dns_records"),
+            path.strip_prefix("/domains/example.com/records"),
+        ) {
+            (Some(rest), _) => (true, rest),
+            (_, Some(rest)) => (false, rest),
+            _ if path == "/zones" && param("name") == "example.com" => {
+                return (
+                    "200 OK",
+                    json!({"success": true, "result": [{"id": "zone-1"}]}),
+                )
+            }
+            _ => return ("404 Not Found", json!({"message": "not found"})),
+        };
+        let id: Option<u64> = rest.strip_prefix('/').and_then(|id| id.parse().ok());
+        let content_key = if cf { "content" } else { "data" };
+        match (method, id) {
+            ("GET", None) => {
+                let name = param("name");
+                let list: Vec<_> = zone
+                    .iter()
+                    .filter(|(_, (_, fqdn, _))| *fqdn == name)
+                    .map(|(id, (kind, fqdn, content))| {
+                        if cf {
+                            json!({"id": id.to_string(), "type": kind, "name": fqdn, "content": content})
+                        } else {
+                            json!({"id": id, "type": kind, "name": relative(fqdn), "data": content})
+                        }
+                    })
+                    .collect();
+                let key = if cf { "result" } else { "domain_records" };
+                ("200 OK", json!({ key: list }))
+            }
+            ("POST", None) => {
+                let name = body["name"].as_str().unwrap();
+                let fqdn = if cf {
+                    name.to_string()
+                } else {
+                    format!("{name}.example.com")
+                };
+                zone.insert(
+                    next_id,
+                    (
+                        body["type"].as_str().unwrap().to_string(),
+                        fqdn,
+                        body[content_key].as_str().unwrap().to_string(),
+                    ),
+                );
+                ("201 Created", json!({}))
+            }
+            ("PATCH", Some(id)) => match zone.get_mut(&id) {
+                Some(record) => {
+                    record.2 = body[content_key].as_str().unwrap().to_string();
+                    ("200 OK", json!({}))
+                }
+                None => ("404 Not Found", json!({"message": "no such record"})),
+            },
+            ("DELETE", Some(id)) => {
+                zone.remove(&id);
+                ("204 No Content", serde_json::Value::Null)
+            }
+            _ => ("405 Method Not Allowed", json!({"message": "unsupported"})),
+        }
+    }
+
+    /// Tiny in-memory DNS API stand-in.
+    async fn fake_dns(zone: Zone) -> String {
+        let addr = test_http::serve(move |req| {
+            if req.header("authorization") != Some("Bearer test-token") {
+                return Response::json(
+                    "401 Unauthorized",
+                    &json!({"success": false, "errors": [{"message": "Invalid access token"}]}),
+                );
+            }
+            let (status, payload) = respond(&zone, &req.method, &req.path, &req.query, &req.json());
+            Response::json(status, &payload)
+        })
+        .await;
+        format!("http://{addr}")
+    }
+
+    fn records(zone: &Zone) -> Vec<(String, String, String)> {
+        zone.lock().unwrap().values().cloned().collect()
+    }
+
+    fn record(kind: &str, fqdn: &str, content: &str) -> (String, String, String) {
+        (kind.into(), fqdn.into(), content.into())
+    }
+
+    #[test]
+    fn fqdn_appends_the_zone_once() {
+        assert_eq!(
+            fqdn("OpenClaw-7f3a", "Example.com.").unwrap(),
+            "openclaw-7f3a.example.com"
+        );
+        assert_eq!(
+            fqdn("claw.example.com", "example.com").unwrap(),
+            "claw.example.com"
+        );
+        assert!(fqdn("bad_name", "example.com").is_err());
+        assert!(fqdn("openclaw", "").is_err());
+    }
+
+    #[tokio::test]
+    async fn cloudflare_upserts_and_removes_against_stand_in() {
+        let zone: Zone = Arc::default();
+        let dns = CloudflareDns::new(config(fake_dns(zone.clone()).await));
+        let name = "openclaw-1.example.com";
+
+        upsert(&dns, name, "203.0.113.10").await.unwrap();
+        upsert(&dns, name, "203.0.113.10").await.unwrap();
+        assert_eq!(records(&zone), vec![record("A", name, "203.0.113.10")]);
+
+        upsert(&dns, name, "203.0.113.20").await.unwrap();
+        assert_eq!(records(&zone), vec![record("A", name, "203.0.113.20")]);
+
+        // A destroyed source must not take the name away from its replacement.
+        assert_eq!(remove(&dns, name, Some("203.0.113.10")).await.unwrap(), 0);
+        assert_eq!(remove(&dns, name, Some("203.0.113.20")).await.unwrap(), 1);
+        assert!(records(&zone).is_empty());
+
+        let mut bad = config(dns.base.clone());
+        bad.token = "wrong".into();
+        let err = CloudflareDns::new(bad).records(name).await.unwrap_err();
+        assert!(err.to_string().contains("Invalid access token"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn digitalocean_upsert_replaces_stale_records() {
+        let zone: Zone = Arc::default();
+        let name = "openclaw-2.example.com";
+        {
+            let mut z = zone.lock().unwrap();
+            z.insert(1, record("A", name, "198.51.100.1"));
+            z.insert(2, record("A", name, "198.51.100.2"));
+            z.insert(3, record("AAAA", name, "2001:db8::1"));
+            z.insert(4, record("A", "other.example.com", "198.51.100.9"));
+        }
+        let dns = DoDns::new(config(fake_dns(zone.clone()).await));
+
+        upsert(&dns, name, "2001:db8::2").await.unwrap();
+        assert_eq!(
+            records(&zone),
+            vec![
+                record("AAAA", name, "2001:db8::2"),
+                record("A", "other.example.com", "198.51.100.9"),
+            ]
+        );
+
+        upsert(&dns, name, "198.51.100.3").await.unwrap();
+        assert_eq!(remove(&dns, name, None).await.unwrap(), 1);
+        assert_eq!(
+            records(&zone),
+            vec![record("A", "other.example.com", "198.51.100.9")]
+        );
+        assert!(upsert(&dns, name, "not-an-ip").await.is_err());
+        assert!(upsert(&dns, "claw.example.org", "198.51.100.3")
+            .await
+            .is_err());
+    }
+}
@@ -2,6 +2,8 @@
 
 pub mod cloud_init;
 pub mod cloud_provider;
+pub mod dns;
+pub mod tailscale_api;
 
 #[cfg(feature = "digitalocean")]
 pub mod digitalocean;
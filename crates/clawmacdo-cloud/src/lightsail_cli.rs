@@ -1,5 +1,6 @@
 use async_trait::async_trait;
 use clawmacdo_core::error::AppError;
+use clawmacdo_core::metrics;
 use serde::{Deserialize, Deserializer};
 use std::process::Command;
 use tokio::time::{sleep, Duration, Instant};
@@ -270,6 +271,13 @@ impl LightsailCliProvider {
 
     /// Execute AWS CLI command and return JSON output
     fn execute_aws_cli(&self, args: &[&str]) -> Result<String, AppError> {
+        let started = std::time::Instant::now();
+        let result = self.run_aws_cli(args);
+        metrics::observe_cloud_api("lightsail", started, result.is_ok());
+        result
+    }
+
+    fn run_aws_cli(&self, args: &[&str]) -> Result<String, AppError> {
         let mut cmd = Command::new("aws");
         cmd.arg("lightsail")
             .args(args)
@@ -1,6 +1,7 @@
 use crate::cloud_provider::{CloudProvider, CreateInstanceParams, InstanceInfo, KeyInfo};
 use chrono::Utc;
 use clawmacdo_core::error::AppError;
+use clawmacdo_core::metrics;
 use hmac::{Hmac, Mac};
 use reqwest::Client;
 
@@ -54,7 +55,7 @@ impl TencentClient {
         })
     }
 
-    /// Build TC3-HMAC-SHA256 authorization header and send POST request.
+    /// Send a signed request, recording its latency and outcome in the metrics registry.
     async fn signed_request(
         &self,
         service: &str,
@@ -63,6 +64,24 @@ impl TencentClient {
         action: &str,
         version: &str,
         payload: &str,
+    ) -> Result<serde_json::Value, AppError> {
+        let started = std::time::Instant::now();
+        let result = self
+            .send_signed_request(service, host, endpoint, action, version, payload)
+            .await;
+        metrics::observe_cloud_api("tencent", started, result.is_ok());
+        result
+    }
+
+    /// Build TC3-HMAC-SHA256 authorization header and send POST request.
+    async fn send_signed_request(
+        &self,
+        service: &str,
+        host: &str,
+        endpoint: &str,
+        action: &str,
+        version: &str,
+        payload: &str,
     ) -> Result<serde_json::Value, AppError> {
         let now = Utc::now();
         let timestamp = now.timestamp();
@@ -0,0 +1,1009 @@
+//! ZIP and tar.gz helpers shared by the wiki, Markdown download, skill and
+//! backup commands.
+//!
+//! The ZIP writer streams entries to any `Write` and switches to ZIP64
+//! records only when sizes, offsets or the entry count need them. The reader
+//! parses the central directory (ZIP64 included) up front and streams each
+//! entry through a CRC check, so untrusted archives can be inspected without
+//! extracting them. Entry names are checked with [`safe_relative_path`]
+//! before callers act on them.
+
+use crate::error::AppError;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use sha2::{Digest, Sha256};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Component;
+
+const LOCAL_HEADER_SIG: u32 = 0x0403_4b50;
+const CENTRAL_HEADER_SIG: u32 = 0x0201_4b50;
+const EOCD_SIG: u32 = 0x0605_4b50;
+const ZIP64_EOCD_SIG: u32 = 0x0606_4b50;
+const ZIP64_LOCATOR_SIG: u32 = 0x0706_4b50;
+const DATA_DESCRIPTOR_SIG: u32 = 0x0807_4b50;
+const ZIP64_EXTRA_ID: u16 = 0x0001;
+
+/// General purpose flag: names are UTF-8.
+const FLAG_UTF8: u16 = 0x0800;
+/// General purpose flag: sizes and CRC follow the data in a descriptor.
+const FLAG_DATA_DESCRIPTOR: u16 = 0x0008;
+
+const S_IFMT: u32 = 0o170000;
+const S_IFLNK: u32 = 0o120000;
+const S_IFDIR: u32 = 0o040000;
+
+fn archive_err(message: impl Into<String>) -> AppError {
+    AppError::Archive(message.into())
+}
+
+// ── Path safety ─────────────────────────────────────────────────────────────
+
+/// Validate an archive member name as a relative path that stays inside the
+/// extraction directory (no zip-slip).
+///
+/// Rejects empty names, absolute paths, Windows drive prefixes, backslashes,
+/// `.`/`..`/empty segments and control characters. A single trailing `/`
+/// (directory entry) is allowed and stripped.
+pub fn safe_relative_path(name: &str) -> Result<String, AppError> {
+    let trimmed = name.strip_suffix('/').unwrap_or(name);
+    if trimmed.is_empty() {
+        return Err(archive_err("archive entry has an empty name"));
+    }
+    if trimmed.starts_with('/') {
+        return Err(archive_err(format!(
+            "archive entry {name} is an absolute path"
+        )));
+    }
+    if trimmed.contains('\\') {
+        return Err(archive_err(format!(
+            "archive entry {name} contains a backslash"
+        )));
+    }
+    if trimmed.len() >= 2 && trimmed.as_bytes()[1] == b':' {
+        return Err(archive_err(format!(
+            "archive entry {name} has a drive prefix"
+        )));
+    }
+    if trimmed.chars().any(char::is_control) {
+        return Err(archive_err(format!(
+            "archive entry {name:?} contains control characters"
+        )));
+    }
+    if trimmed
+        .split('/')
+        .any(|segment| segment.is_empty() || segment == "." || segment == "..")
+    {
+        return Err(archive_err(format!(
+            "archive entry {name} escapes its directory"
+        )));
+    }
+    Ok(trimmed.to_string())
+}
+
+// ── CRC-32 ──────────────────────────────────────────────────────────────────
+
+/// Incremental CRC-32 (IEEE), as used by ZIP.
+#[derive(Debug, Clone, Copy)]
+pub struct Crc32(u32);
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Self(0xffff_ffff)
+    }
+}
+
+impl Crc32 {
+    pub fn update(&mut self, data: &[u8]) {
+        let mut crc = self.0;
+        for &byte in data {
+            crc ^= byte as u32;
+            for _ in 0..8 {
+                let mask = (crc & 1).wrapping_neg();
+                crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+            }
+        }
+        self.0 = crc;
+    }
+
+    pub fn finish(self) -> u32 {
+        !self.0
+    }
+}
+
+/// CRC-32 of a byte slice.
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = Crc32::default();
+    crc.update(data);
+    crc.finish()
+}
+
+// ── ZIP writing ─────────────────────────────────────────────────────────────
+
+/// Compression method of a ZIP entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZipMethod {
+    Stored,
+    Deflated,
+}
+
+impl ZipMethod {
+    fn code(self) -> u16 {
+        match self {
+            Self::Stored => 0,
+            Self::Deflated => 8,
+        }
+    }
+}
+
+struct CentralRecord {
+    name: String,
+    method: ZipMethod,
+    flags: u16,
+    crc32: u32,
+    compressed_size: u64,
+    size: u64,
+    offset: u64,
+    mode: u32,
+}
+
+/// Counts bytes written so entry offsets are known without `Seek`.
+struct CountingWriter<W> {
+    inner: W,
+    written: u64,
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Counts and checksums the uncompressed bytes of a streamed entry.
+struct CrcWriter<W> {
+    inner: W,
+    crc: Crc32,
+    size: u64,
+}
+
+impl<W: Write> Write for CrcWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.crc.update(&buf[..n]);
+        self.size += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Streaming ZIP writer.
+///
+/// Entries are written as they are added; only the central directory is kept
+/// in memory. Call [`ZipWriter::finish`] to write it.
+pub struct ZipWriter<W: Write> {
+    out: CountingWriter<W>,
+    records: Vec<CentralRecord>,
+}
+
+fn put_u16(out: &mut Vec<u8>, value: u16) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u64(out: &mut Vec<u8>, value: u64) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+/// Clamp a value into a 32-bit field, using the ZIP64 marker when it does not fit.
+fn field32(value: u64) -> u32 {
+    u32::try_from(value)
+        .ok()
+        .filter(|v| *v != u32::MAX)
+        .unwrap_or(u32::MAX)
+}
+
+impl<W: Write> ZipWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            out: CountingWriter { inner, written: 0 },
+            records: Vec::new(),
+        }
+    }
+
+    fn check_name(&self, name: &str) -> Result<String, AppError> {
+        let clean = safe_relative_path(name)?;
+        if clean.len() > u16::MAX as usize {
+            return Err(archive_err(format!(
+                "archive entry name is too long: {name}"
+            )));
+        }
+        Ok(clean)
+    }
+
+    /// Add a file whose contents are already in memory (mode 0644).
+    pub fn add_file(&mut self, name: &str, data: &[u8], method: ZipMethod) -> Result<(), AppError> {
+        self.add_file_with_mode(name, data, method, 0o644)
+    }
+
+    /// Add a file with explicit Unix permission bits.
+    pub fn add_file_with_mode(
+        &mut self,
+        name: &str,
+        data: &[u8],
+        method: ZipMethod,
+        mode: u32,
+    ) -> Result<(), AppError> {
+        let name = self.check_name(name)?;
+        let compressed = match method {
+            ZipMethod::Stored => None,
+            ZipMethod::Deflated => {
+                let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(data)?;
+                Some(encoder.finish()?)
+            }
+        };
+        let payload = compressed.as_deref().unwrap_or(data);
+        let crc = crc32(data);
+        let size = data.len() as u64;
+        let compressed_size = payload.len() as u64;
+        let offset = self.out.written;
+        let needs_zip64 = size >= u32::MAX as u64 || compressed_size >= u32::MAX as u64;
+
+        let mut header = Vec::with_capacity(30 + name.len() + 20);
+        put_u32(&mut header, LOCAL_HEADER_SIG);
+        put_u16(&mut header, if needs_zip64 { 45 } else { 20 });
+        put_u16(&mut header, FLAG_UTF8);
+        put_u16(&mut header, method.code());
+        put_u16(&mut header, 0);
+        put_u16(&mut header, 33);
+        put_u32(&mut header, crc);
+        put_u32(&mut header, field32(compressed_size));
+        put_u32(&mut header, field32(size));
+        put_u16(&mut header, name.len() as u16);
+        put_u16(&mut header, if needs_zip64 { 20 } else { 0 });
+        header.extend_from_slice(name.as_bytes());
+        if needs_zip64 {
+            put_u16(&mut header, ZIP64_EXTRA_ID);
+            put_u16(&mut header, 16);
+            put_u64(&mut header, size);
+            put_u64(&mut header, compressed_size);
+        }
+        self.out.write_all(&header)?;
+        self.out.write_all(payload)?;
+
+        self.records.push(CentralRecord {
+            name,
+            method,
+            flags: FLAG_UTF8,
+            crc32: crc,
+            compressed_size,
+            size,
+            offset,
+            mode,
+        });
+        Ok(())
+    }
+
+    /// Stream a file of unknown size from `reader`. Sizes and CRC follow the
+    /// data in a ZIP64 data descriptor, so nothing is buffered.
+    pub fn add_reader<R: Read>(
+        &mut self,
+        name: &str,
+        reader: &mut R,
+        method: ZipMethod,
+        mode: u32,
+    ) -> Result<(), AppError> {
+        let name = self.check_name(name)?;
+        let offset = self.out.written;
+        let flags = FLAG_UTF8 | FLAG_DATA_DESCRIPTOR;
+
+        let mut header = Vec::with_capacity(30 + name.len() + 20);
+        put_u32(&mut header, LOCAL_HEADER_SIG);
+        put_u16(&mut header, 45);
+        put_u16(&mut header, flags);
+        put_u16(&mut header, method.code());
+        put_u16(&mut header, 0);
+        put_u16(&mut header, 33);
+        put_u32(&mut header, 0);
+        put_u32(&mut header, u32::MAX);
+        put_u32(&mut header, u32::MAX);
+        put_u16(&mut header, name.len() as u16);
+        put_u16(&mut header, 20);
+        header.extend_from_slice(name.as_bytes());
+        put_u16(&mut header, ZIP64_EXTRA_ID);
+        put_u16(&mut header, 16);
+        put_u64(&mut header, 0);
+        put_u64(&mut header, 0);
+        self.out.write_all(&header)?;
+
+        let data_start = self.out.written;
+        let (crc, size) = match method {
+            ZipMethod::Stored => {
+                let mut sink = CrcWriter {
+                    inner: &mut self.out,
+                    crc: Crc32::default(),
+                    size: 0,
+                };
+                io::copy(reader, &mut sink)?;
+                (sink.crc.finish(), sink.size)
+            }
+            ZipMethod::Deflated => {
+                let encoder = DeflateEncoder::new(&mut self.out, Compression::default());
+                let mut sink = CrcWriter {
+                    inner: encoder,
+                    crc: Crc32::default(),
+                    size: 0,
+                };
+                io::copy(reader, &mut sink)?;
+                let (crc, size) = (sink.crc.finish(), sink.size);
+                sink.inner.finish()?;
+                (crc, size)
+            }
+        };
+        let compressed_size = self.out.written - data_start;
+
+        let mut descriptor = Vec::with_capacity(24);
+        put_u32(&mut descriptor, DATA_DESCRIPTOR_SIG);
+        put_u32(&mut descriptor, crc);
+        put_u64(&mut descriptor, compressed_size);
+        put_u64(&mut descriptor, size);
+        self.out.write_all(&descriptor)?;
+
+        self.records.push(CentralRecord {
+            name,
+            method,
+            flags,
+            crc32: crc,
+            compressed_size,
+            size,
+            offset,
+            mode,
+        });
+        Ok(())
+    }
+
+    /// Write the central directory and return the inner writer.
+    pub fn finish(mut self) -> Result<W, AppError> {
+        let central_offset = self.out.written;
+        for record in &self.records {
+            let mut extra = Vec::new();
+            if record.size >= u32::MAX as u64 {
+                put_u64(&mut extra, record.size);
+            }
+            if record.compressed_size >= u32::MAX as u64 {
+                put_u64(&mut extra, record.compressed_size);
+            }
+            if record.offset >= u32::MAX as u64 {
+                put_u64(&mut extra, record.offset);
+            }
+            let mut zip64 = Vec::new();
+            if !extra.is_empty() {
+                put_u16(&mut zip64, ZIP64_EXTRA_ID);
+                put_u16(&mut zip64, extra.len() as u16);
+                zip64.extend_from_slice(&extra);
+            }
+            let is_dir = record.mode & S_IFMT == S_IFDIR;
+            let file_type = if record.mode & S_IFMT == 0 {
+                0o100000
+            } else {
+                0
+            };
+            let external = ((record.mode | file_type) << 16) | if is_dir { 0x10 } else { 0 };
+
+            let mut header = Vec::with_capacity(46 + record.name.len() + zip64.len());
+            put_u32(&mut header, CENTRAL_HEADER_SIG);
+            // Made by Unix (3), spec 4.5.
+            put_u16(&mut header, (3 << 8) | 45);
+            put_u16(&mut header, if zip64.is_empty() { 20 } else { 45 });
+            put_u16(&mut header, record.flags);
+            put_u16(&mut header, record.method.code());
+            put_u16(&mut header, 0);
+            put_u16(&mut header, 33);
+            put_u32(&mut header, record.crc32);
+            put_u32(&mut header, field32(record.compressed_size));
+            put_u32(&mut header, field32(record.size));
+            put_u16(&mut header, record.name.len() as u16);
+            put_u16(&mut header, zip64.len() as u16);
+            put_u16(&mut header, 0);
+            put_u16(&mut header, 0);
+            put_u16(&mut header, 0);
+            put_u32(&mut header, external);
+            put_u32(&mut header, field32(record.offset));
+            header.extend_from_slice(record.name.as_bytes());
+            header.extend_from_slice(&zip64);
+            self.out.write_all(&header)?;
+        }
+        let central_size = self.out.written - central_offset;
+        let count = self.records.len() as u64;
+
+        let mut tail = Vec::new();
+        let needs_zip64 = count >= u16::MAX as u64
+            || central_size >= u32::MAX as u64
+            || central_offset >= u32::MAX as u64;
+        if needs_zip64 {
+            let zip64_offset = self.out.written;
+            put_u32(&mut tail, ZIP64_EOCD_SIG);
+            put_u64(&mut tail, 44);
+            put_u16(&mut tail, (3 << 8) | 45);
+            put_u16(&mut tail, 45);
+            put_u32(&mut tail, 0);
+            put_u32(&mut tail, 0);
+            put_u64(&mut tail, count);
+            put_u64(&mut tail, count);
+            put_u64(&mut tail, central_size);
+            put_u64(&mut tail, central_offset);
+            put_u32(&mut tail, ZIP64_LOCATOR_SIG);
+            put_u32(&mut tail, 0);
+            put_u64(&mut tail, zip64_offset);
+            put_u32(&mut tail, 1);
+        }
+        let count16 = u16::try_from(count)
+            .ok()
+            .filter(|c| *c != u16::MAX)
+            .unwrap_or(u16::MAX);
+        put_u32(&mut tail, EOCD_SIG);
+        put_u16(&mut tail, 0);
+        put_u16(&mut tail, 0);
+        put_u16(&mut tail, count16);
+        put_u16(&mut tail, count16);
+        put_u32(&mut tail, field32(central_size));
+        put_u32(&mut tail, field32(central_offset));
+        put_u16(&mut tail, 0);
+        self.out.write_all(&tail)?;
+        self.out.flush()?;
+        Ok(self.out.inner)
+    }
+}
+
+/// Write `entries` (name, contents) to a new ZIP file at `path`, deflated.
+pub fn write_zip_file(
+    path: &std::path::Path,
+    entries: &[(String, Vec<u8>)],
+) -> Result<(), AppError> {
+    let file = std::fs::File::create(path)?;
+    let mut zip = ZipWriter::new(io::BufWriter::new(file));
+    for (name, data) in entries {
+        zip.add_file(name, data, ZipMethod::Deflated)?;
+    }
+    zip.finish()?.flush()?;
+    Ok(())
+}
+
+// ── ZIP reading ─────────────────────────────────────────────────────────────
+
+/// Central directory record of one ZIP member.
+#[derive(Debug, Clone)]
+pub struct ZipEntryInfo {
+    /// Raw member name as stored in the archive (not yet validated).
+    pub name: String,
+    pub method: u16,
+    pub flags: u16,
+    pub crc32: u32,
+    pub compressed_size: u64,
+    pub size: u64,
+    pub offset: u64,
+    /// Unix mode from the external attributes, 0 when the archive has none.
+    pub mode: u32,
+}
+
+impl ZipEntryInfo {
+    pub fn is_dir(&self) -> bool {
+        self.name.ends_with('/') || self.mode & S_IFMT == S_IFDIR
+    }
+
+    pub fn is_symlink(&self) -> bool {
+        self.mode & S_IFMT == S_IFLNK
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.flags & 1 != 0
+    }
+}
+
+fn le_u16(b: &[u8], at: usize) -> Result<u16, AppError> {
+    b.get(at..at + 2)
+        .map(|s| u16::from_le_bytes([s[0], s[1]]))
+        .ok_or_else(|| archive_err("ZIP archive is truncated"))
+}
+
+fn le_u32(b: &[u8], at: usize) -> Result<u32, AppError> {
+    b.get(at..at + 4)
+        .map(|s| u32::from_le_bytes([s[0], s[1], s[2], s[3]]))
+        .ok_or_else(|| archive_err("ZIP archive is truncated"))
+}
+
+fn le_u64(b: &[u8], at: usize) -> Result<u64, AppError> {
+    b.get(at..at + 8)
+        .map(|s| {
+            let mut buf = [0u8; 8];
+            buf.copy_from_slice(s);
+            u64::from_le_bytes(buf)
+        })
+        .ok_or_else(|| archive_err("ZIP archive is truncated"))
+}
+
+/// Random-access ZIP reader over any `Read + Seek` source.
+pub struct ZipArchive<R> {
+    reader: R,
+    entries: Vec<ZipEntryInfo>,
+}
+
+impl<R: Read + Seek> ZipArchive<R> {
+    /// Parse the central directory. At most `max_entries` members are accepted.
+    pub fn new(mut reader: R, max_entries: usize) -> Result<Self, AppError> {
+        let len = reader.seek(SeekFrom::End(0))?;
+        let tail_len = len.min(22 + u16::MAX as u64);
+        reader.seek(SeekFrom::Start(len - tail_len))?;
+        let mut tail = vec![0u8; tail_len as usize];
+        reader.read_exact(&mut tail)?;
+        let eocd = (0..tail.len().saturating_sub(21))
+            .rev()
+            .find(|&at| le_u32(&tail, at).ok() == Some(EOCD_SIG))
+            .ok_or_else(|| archive_err("not a ZIP archive: end of central directory not found"))?;
+
+        let mut count = le_u16(&tail, eocd + 10)? as u64;
+        let mut central_size = le_u32(&tail, eocd + 12)? as u64;
+        let mut central_offset = le_u32(&tail, eocd + 16)? as u64;
+        let is_zip64 = eocd >= 20 && le_u32(&tail, eocd - 20)? == ZIP64_LOCATOR_SIG;
+        if is_zip64 {
+            let zip64_offset = le_u64(&tail, eocd - 20 + 8)?;
+            reader.seek(SeekFrom::Start(zip64_offset))?;
+            let mut record = [0u8; 56];
+            reader.read_exact(&mut record)?;
+            if le_u32(&record, 0)? != ZIP64_EOCD_SIG {
+                return Err(archive_err("ZIP64 end of central directory is corrupt"));
+            }
+            count = le_u64(&record, 32)?;
+            central_size = le_u64(&record, 40)?;
+            central_offset = le_u64(&record, 48)?;
+        }
+        if count > max_entries as u64 {
+            return Err(archive_err(format!(
+                "ZIP archive has {count} entries; the limit is {max_entries}"
+            )));
+        }
+        if central_offset.saturating_add(central_size) > len {
+            return Err(archive_err("ZIP central directory is out of bounds"));
+        }
+
+        reader.seek(SeekFrom::Start(central_offset))?;
+        let mut central = vec![0u8; central_size as usize];
+        reader.read_exact(&mut central)?;
+        let mut entries = Vec::with_capacity(count as usize);
+        let mut at = 0usize;
+        for _ in 0..count {
+            if le_u32(&central, at)? != CENTRAL_HEADER_SIG {
+                return Err(archive_err("ZIP central directory is corrupt"));
+            }
+            let made_by_unix = le_u16(&central, at + 4)? >> 8 == 3;
+            let flags = le_u16(&central, at + 8)?;
+            let method = le_u16(&central, at + 10)?;
+            let crc32 = le_u32(&central, at + 16)?;
+            let mut compressed_size = le_u32(&central, at + 20)? as u64;
+            let mut size = le_u32(&central, at + 24)? as u64;
+            let name_len = le_u16(&central, at + 28)? as usize;
+            let extra_len = le_u16(&central, at + 30)? as usize;
+            let comment_len = le_u16(&central, at + 32)? as usize;
+            let external = le_u32(&central, at + 38)?;
+            let mut offset = le_u32(&central, at + 42)? as u64;
+            let name_bytes = central
+                .get(at + 46..at + 46 + name_len)
+                .ok_or_else(|| archive_err("ZIP archive is truncated"))?;
+            let name = String::from_utf8(name_bytes.to_vec())
+                .map_err(|_| archive_err("ZIP entry name is not valid UTF-8"))?;
+            let extra = central
+                .get(at + 46 + name_len..at + 46 + name_len + extra_len)
+                .ok_or_else(|| archive_err("ZIP archive is truncated"))?;
+
+            // ZIP64 extra: only the fields saturated in the record are present, in order.
+            let mut e = 0;
+            while e + 4 <= extra.len() {
+                let id = le_u16(extra, e)?;
+                let len = le_u16(extra, e + 2)? as usize;
+                if id == ZIP64_EXTRA_ID {
+                    let mut p = e + 4;
+                    if size == u32::MAX as u64 {
+                        size = le_u64(extra, p)?;
+                        p += 8;
+                    }
+                    if compressed_size == u32::MAX as u64 {
+                        compressed_size = le_u64(extra, p)?;
+                        p += 8;
+                    }
+                    if offset == u32::MAX as u64 {
+                        offset = le_u64(extra, p)?;
+                    }
+                }
+                e += 4 + len;
+            }
+
+            entries.push(ZipEntryInfo {
+                name,
+                method,
+                flags,
+                crc32,
+                compressed_size,
+                size,
+                offset,
+                mode: if made_by_unix { external >> 16 } else { 0 },
+            });
+            at += 46 + name_len + extra_len + comment_len;
+        }
+        Ok(Self { reader, entries })
+    }
+
+    pub fn entries(&self) -> &[ZipEntryInfo] {
+        &self.entries
+    }
+
+    /// Stream the contents of entry `index`. At most `limit` uncompressed
+    /// bytes are produced; the size and CRC are checked at end of stream.
+    pub fn open(&mut self, index: usize, limit: u64) -> Result<Box<dyn Read + '_>, AppError> {
+        let entry = self
+            .entries
+            .get(index)
+            .cloned()
+            .ok_or_else(|| archive_err(format!("no ZIP entry at index {index}")))?;
+        if entry.is_encrypted() {
+            return Err(archive_err(format!(
+                "ZIP entry {} is encrypted",
+                entry.name
+            )));
+        }
+        if entry.size > limit {
+            return Err(archive_err(format!(
+                "ZIP entry {} is {} bytes; the limit is {limit}",
+                entry.name, entry.size
+            )));
+        }
+        self.reader.seek(SeekFrom::Start(entry.offset))?;
+        let mut local = [0u8; 30];
+        self.reader.read_exact(&mut local)?;
+        if le_u32(&local, 0)? != LOCAL_HEADER_SIG {
+            return Err(archive_err(format!(
+                "ZIP local header for {} is corrupt",
+                entry.name
+            )));
+        }
+        let skip = le_u16(&local, 26)? as i64 + le_u16(&local, 28)? as i64;
+        self.reader.seek(SeekFrom::Current(skip))?;
+
+        let raw = (&mut self.reader).take(entry.compressed_size);
+        let inner: Box<dyn Read + '_> = match entry.method {
+            0 => Box::new(raw),
+            8 => Box::new(DeflateDecoder::new(raw)),
+            other => {
+                return Err(archive_err(format!(
+                    "ZIP entry {} uses unsupported compression method {other}",
+                    entry.name
+                )))
+            }
+        };
+        Ok(Box::new(CheckedReader {
+            inner: inner.take(limit.min(entry.size) + 1),
+            crc: Crc32::default(),
+            read: 0,
+            expected_size: entry.size,
+            expected_crc: entry.crc32,
+            name: entry.name,
+        }))
+    }
+
+    /// Read entry `index` fully into memory (bounded by `limit`).
+    pub fn read(&mut self, index: usize, limit: u64) -> Result<Vec<u8>, AppError> {
+        let mut out = Vec::new();
+        self.open(index, limit)?.read_to_end(&mut out)?;
+        Ok(out)
+    }
+}
+
+/// Verifies size and CRC once the entry stream ends.
+struct CheckedReader<R> {
+    inner: R,
+    crc: Crc32,
+    read: u64,
+    expected_size: u64,
+    expected_crc: u32,
+    name: String,
+}
+
+impl<R: Read> Read for CheckedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.crc.update(&buf[..n]);
+        self.read += n as u64;
+        if self.read > self.expected_size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("ZIP entry {} is larger than its header says", self.name),
+            ));
+        }
+        if n == 0 && !buf.is_empty() {
+            if self.read != self.expected_size {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "ZIP entry {} size mismatch: expected {}, got {}",
+                        self.name, self.expected_size, self.read
+                    ),
+                ));
+            }
+            if self.crc.finish() != self.expected_crc {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("ZIP entry {} failed its CRC check", self.name),
+                ));
+            }
+        }
+        Ok(n)
+    }
+}
+
+/// Limits applied when reading an untrusted ZIP into memory.
+#[derive(Debug, Clone, Copy)]
+pub struct ZipLimits {
+    pub max_entries: usize,
+    pub max_entry_bytes: u64,
+    pub max_total_bytes: u64,
+}
+
+/// A ZIP member read into memory.
+#[derive(Debug, Clone)]
+pub struct ZipFile {
+    pub name: String,
+    pub data: Vec<u8>,
+    pub mode: u32,
+}
+
+/// Read the members of an in-memory ZIP that `select` picks.
+///
+/// `select` sees each raw file name and returns the name to keep it under,
+/// or `None` to skip it; directories are always skipped. Encrypted and
+/// symlink members are rejected, and every selected member is checked
+/// against `limits` and its CRC. The error type is the caller's so `select`
+/// can report its own validation failures.
+pub fn read_zip<E: From<AppError>>(
+    data: &[u8],
+    limits: ZipLimits,
+    mut select: impl FnMut(&str) -> Result<Option<String>, E>,
+) -> Result<Vec<ZipFile>, E> {
+    let mut archive = ZipArchive::new(io::Cursor::new(data), limits.max_entries)?;
+    let mut files = Vec::new();
+    let mut total = 0u64;
+    for index in 0..archive.entries().len() {
+        let entry = archive.entries()[index].clone();
+        if entry.is_dir() {
+            continue;
+        }
+        let Some(name) = select(&entry.name)? else {
+            continue;
+        };
+        if entry.is_symlink() {
+            return Err(archive_err(format!("ZIP entry {} is a symlink", entry.name)).into());
+        }
+        total = total.saturating_add(entry.size);
+        if total > limits.max_total_bytes {
+            return Err(archive_err(format!(
+                "ZIP archive expands to more than {} bytes",
+                limits.max_total_bytes
+            ))
+            .into());
+        }
+        let data = archive.read(index, limits.max_entry_bytes)?;
+        files.push(ZipFile {
+            name,
+            data,
+            mode: entry.mode,
+        });
+    }
+    Ok(files)
+}
+
+// ── tar.gz ──────────────────────────────────────────────────────────────────
+
+/// Size and sha256 of one regular file in a tar.gz.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TarFileChecksum {
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+/// Size and sha256 of every regular file in a tar.gz stream, in archive order.
+pub fn tar_gz_checksums<R: Read>(reader: R) -> Result<Vec<TarFileChecksum>, AppError> {
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(reader));
+    let mut files = Vec::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = entry.path()?.to_string_lossy().to_string();
+        let mut hasher = Sha256::new();
+        let size = io::copy(&mut entry, &mut hasher)?;
+        files.push(TarFileChecksum {
+            path,
+            size,
+            sha256: hex::encode(hasher.finalize()),
+        });
+    }
+    Ok(files)
+}
+
+/// Path and size of every member of a tar.gz stream, in archive order.
+pub fn tar_gz_list<R: Read>(reader: R) -> Result<Vec<(String, u64)>, AppError> {
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(reader));
+    let mut members = Vec::new();
+    for entry in archive.entries()? {
+        let entry = entry?;
+        let path = entry.path()?.to_string_lossy().to_string();
+        members.push((path, entry.size()));
+    }
+    Ok(members)
+}
+
+/// Check that every member path of a tar.gz stays inside the extraction
+/// directory: no absolute paths, drive prefixes or `..` components. Returns
+/// the number of members; an empty archive is an error.
+pub fn validate_tar_gz<R: Read>(reader: R) -> Result<usize, AppError> {
+    let members = tar_gz_list(reader)?;
+    for (path, _) in &members {
+        let unsafe_path = std::path::Path::new(path).components().any(|c| {
+            matches!(
+                c,
+                Component::ParentDir | Component::RootDir | Component::Prefix(_)
+            )
+        });
+        if unsafe_path {
+            return Err(archive_err(format!(
+                "archive contains an unsafe path: {path}"
+            )));
+        }
+    }
+    if members.is_empty() {
+        return Err(archive_err("archive is empty"));
+    }
+    Ok(members.len())
+}
+
+/// Build a tar.gz in memory from (path, contents, mode) members.
+pub fn tar_gz_from_files(files: &[(String, Vec<u8>, u32)]) -> Result<Vec<u8>, AppError> {
+    let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+        Vec::new(),
+        Compression::default(),
+    ));
+    for (path, data, mode) in files {
+        let path = safe_relative_path(path)?;
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(*mode);
+        header.set_mtime(0);
+        header.set_cksum();
+        builder.append_data(&mut header, &path, data.as_slice())?;
+    }
+    Ok(builder.into_inner()?.finish()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits() -> ZipLimits {
+        ZipLimits {
+            max_entries: 100,
+            max_entry_bytes: 1 << 20,
+            max_total_bytes: 1 << 22,
+        }
+    }
+
+    #[test]
+    fn safe_relative_path_rejects_zip_slip() {
+        for bad in [
+            "",
+            "/etc/passwd",
+            "../x",
+            "a/../../x",
+            "a//b",
+            "./a",
+            "a\\b",
+            "C:/x",
+            "a/\u{1}",
+        ] {
+            assert!(safe_relative_path(bad).is_err(), "{bad:?}");
+        }
+        assert_eq!(
+            safe_relative_path("skills/a/SKILL.md").unwrap(),
+            "skills/a/SKILL.md"
+        );
+        assert_eq!(safe_relative_path("skills/").unwrap(), "skills");
+    }
+
+    #[test]
+    fn zip_round_trips_stored_deflated_and_streamed_entries() {
+        let mut zip = ZipWriter::new(Vec::new());
+        zip.add_file("a.txt", b"hello", ZipMethod::Stored).unwrap();
+        let big = "repeat ".repeat(1000);
+        zip.add_file("dir/b.md", big.as_bytes(), ZipMethod::Deflated)
+            .unwrap();
+        zip.add_reader("c.bin", &mut &b"streamed"[..], ZipMethod::Deflated, 0o755)
+            .unwrap();
+        assert!(zip.add_file("../evil", b"", ZipMethod::Stored).is_err());
+        let data = zip.finish().unwrap();
+
+        let files = read_zip(&data, limits(), |name| {
+            Ok::<_, AppError>(Some(name.to_string()))
+        })
+        .unwrap();
+        let names: Vec<&str> = files.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["a.txt", "dir/b.md", "c.bin"]);
+        assert_eq!(files[1].data, big.as_bytes());
+        assert_eq!(files[2].data, b"streamed");
+        assert_eq!(files[2].mode & 0o777, 0o755);
+        assert!(data.len() < big.len());
+    }
+
+    #[test]
+    fn zip_reader_rejects_bad_crc_and_oversized_entries() {
+        let mut zip = ZipWriter::new(Vec::new());
+        zip.add_file("a.txt", b"hello world", ZipMethod::Stored)
+            .unwrap();
+        let mut data = zip.finish().unwrap();
+
+        let small = ZipLimits {
+            max_entry_bytes: 4,
+            ..limits()
+        };
+        assert!(read_zip(&data, small, |n| Ok::<_, AppError>(Some(n.into()))).is_err());
+
+        // Corrupt one byte of the stored payload.
+        let at = 30 + "a.txt".len();
+        data[at] ^= 0xff;
+        let err = read_zip(&data, limits(), |n| Ok::<_, AppError>(Some(n.into()))).unwrap_err();
+        assert!(err.to_string().contains("CRC"), "{err}");
+    }
+
+    #[test]
+    fn zip64_records_are_written_and_read_for_many_entries() {
+        let mut zip = ZipWriter::new(Vec::new());
+        for i in 0..70_000u32 {
+            zip.add_file(&format!("f{i}"), b"", ZipMethod::Stored)
+                .unwrap();
+        }
+        let data = zip.finish().unwrap();
+        let archive = ZipArchive::new(io::Cursor::new(&data), 100_000).unwrap();
+        assert_eq!(archive.entries().len(), 70_000);
+        assert_eq!(archive.entries()[69_999].name, "f69999");
+        assert!(ZipArchive::new(io::Cursor::new(&data), 1000).is_err());
+    }
+
+    #[test]
+    fn tar_gz_helpers_checksum_and_validate() {
+        let data = tar_gz_from_files(&[
+            ("openclaw/a.json".into(), b"{}".to_vec(), 0o600),
+            ("openclaw/b.txt".into(), b"b".to_vec(), 0o644),
+        ])
+        .unwrap();
+        let sums = tar_gz_checksums(data.as_slice()).unwrap();
+        assert_eq!(sums.len(), 2);
+        assert_eq!(sums[0].path, "openclaw/a.json");
+        assert_eq!(sums[0].size, 2);
+        assert_eq!(
+            sums[0].sha256,
+            "44136fa355b3678a1146ad16f7e8649e94fb4fc21fe77e8310c060f61caaff8a"
+        );
+        assert_eq!(validate_tar_gz(data.as_slice()).unwrap(), 2);
+        assert!(tar_gz_from_files(&[("../x".into(), Vec::new(), 0o644)]).is_err());
+    }
+}
@@ -220,7 +220,7 @@ pub fn normalize_hostname(input: &str) -> Result<Option<String>, AppError> {
     Ok(Some(hostname))
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeployRecord {
     pub id: String,
     #[serde(default)]
@@ -243,6 +243,10 @@ pub struct DeployRecord {
     pub resource_group: Option<String>,
     pub backup_restored: Option<String>,
     pub created_at: DateTime<Utc>,
+    /// Stable `<hostname>.<zone>` name kept pointing at `ip_address` when a DNS
+    /// provider is configured.
+    #[serde(default)]
+    pub fqdn: Option<String>,
 }
 
 impl DeployRecord {
@@ -9,6 +9,9 @@ pub enum AppError {
     #[error("Backup failed: {0}")]
     Backup(String),
 
+    #[error("Archive error: {0}")]
+    Archive(String),
+
     #[error("No backups found in {0}")]
     NoBackups(String),
 
@@ -24,6 +27,9 @@ pub enum AppError {
     #[error("BytePlus API error: {0}")]
     BytePlus(String),
 
+    #[error("DNS API error: {0}")]
+    Dns(String),
+
     #[error("Cloud provider API error: {0}")]
     CloudProviderError(String),
 
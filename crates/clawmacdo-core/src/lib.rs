@@ -1,7 +1,14 @@
 //! Core types, configuration, and error handling for ClawMacdo
 
+pub mod archive;
+pub mod backup_crypto;
+pub mod backup_store;
 pub mod config;
 pub mod error;
+pub mod metrics;
+pub mod security_scan;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_http;
 
 // Re-export commonly used items
 pub use config::*;
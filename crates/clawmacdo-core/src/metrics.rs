@@ -0,0 +1,335 @@
+//! In-process Prometheus metrics registry.
+//!
+//! Counters, gauges and histograms are kept in a single process-wide registry
+//! so the SSH, cloud and web layers can record events without threading a
+//! handle through every call. `render()` produces the Prometheus text
+//! exposition format served by `clawmacdo serve` at `/metrics`.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+pub const SSH_CONNECT_TOTAL: &str = "clawmacdo_ssh_connect_total";
+pub const SSH_CONNECT_RETRIES_TOTAL: &str = "clawmacdo_ssh_connect_retries_total";
+pub const CLOUD_API_DURATION_SECONDS: &str = "clawmacdo_cloud_api_request_duration_seconds";
+pub const CLOUD_API_ERRORS_TOTAL: &str = "clawmacdo_cloud_api_errors_total";
+pub const RATE_LIMIT_REJECTIONS_TOTAL: &str = "clawmacdo_rate_limit_rejections_total";
+pub const SSE_STREAMS_ACTIVE: &str = "clawmacdo_sse_streams_active";
+
+/// Default histogram buckets (seconds), sized for cloud API calls and deploy steps.
+pub const DEFAULT_BUCKETS: &[f64] = &[
+    0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0, 600.0, 1800.0,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricKind {
+    Counter,
+    Gauge,
+    Histogram,
+}
+
+impl MetricKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            MetricKind::Counter => "counter",
+            MetricKind::Gauge => "gauge",
+            MetricKind::Histogram => "histogram",
+        }
+    }
+}
+
+/// Help text for the metrics recorded in-process.
+const DESCRIPTIONS: &[(&str, MetricKind, &str)] = &[
+    (
+        SSH_CONNECT_TOTAL,
+        MetricKind::Counter,
+        "SSH connection attempts by outcome",
+    ),
+    (
+        SSH_CONNECT_RETRIES_TOTAL,
+        MetricKind::Counter,
+        "SSH connection retries after a retryable failure",
+    ),
+    (
+        CLOUD_API_DURATION_SECONDS,
+        MetricKind::Histogram,
+        "Cloud provider API call latency",
+    ),
+    (
+        CLOUD_API_ERRORS_TOTAL,
+        MetricKind::Counter,
+        "Cloud provider API calls that returned an error",
+    ),
+    (
+        RATE_LIMIT_REJECTIONS_TOTAL,
+        MetricKind::Counter,
+        "Web requests rejected by the rate limiter",
+    ),
+    (
+        SSE_STREAMS_ACTIVE,
+        MetricKind::Gauge,
+        "Currently open server-sent event streams",
+    ),
+];
+
+/// A fixed-bucket histogram. Also used to render histograms computed from
+/// stored history (e.g. deploy step durations) at scrape time.
+#[derive(Debug, Clone)]
+pub struct Histogram {
+    bounds: Vec<f64>,
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    pub fn new(bounds: &[f64]) -> Self {
+        Self {
+            bounds: bounds.to_vec(),
+            counts: vec![0; bounds.len()],
+            sum: 0.0,
+            count: 0,
+        }
+    }
+
+    pub fn observe(&mut self, value: f64) {
+        for (bound, count) in self.bounds.iter().zip(self.counts.iter_mut()) {
+            if value <= *bound {
+                *count += 1;
+            }
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn sum(&self) -> f64 {
+        self.sum
+    }
+
+    /// Append the `_bucket`, `_sum` and `_count` series for this histogram.
+    pub fn render_into(&self, out: &mut String, name: &str, labels: &[(&str, &str)]) {
+        for (bound, count) in self.bounds.iter().zip(self.counts.iter()) {
+            let le = format_float(*bound);
+            let mut with_le = labels.to_vec();
+            with_le.push(("le", &le));
+            let _ = writeln!(out, "{name}_bucket{} {count}", format_labels(&with_le));
+        }
+        let mut with_inf = labels.to_vec();
+        with_inf.push(("le", "+Inf"));
+        let _ = writeln!(
+            out,
+            "{name}_bucket{} {}",
+            format_labels(&with_inf),
+            self.count
+        );
+        let _ = writeln!(
+            out,
+            "{name}_sum{} {}",
+            format_labels(labels),
+            format_float(self.sum)
+        );
+        let _ = writeln!(out, "{name}_count{} {}", format_labels(labels), self.count);
+    }
+}
+
+#[derive(Default)]
+struct Registry {
+    counters: BTreeMap<(String, Vec<(String, String)>), u64>,
+    gauges: BTreeMap<(String, Vec<(String, String)>), i64>,
+    histograms: BTreeMap<(String, Vec<(String, String)>), Histogram>,
+}
+
+fn registry() -> &'static Mutex<Registry> {
+    static REGISTRY: OnceLock<Mutex<Registry>> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(Registry::default()))
+}
+
+fn owned_labels(labels: &[(&str, &str)]) -> Vec<(String, String)> {
+    labels
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+/// Increment a counter by one.
+pub fn inc_counter(name: &str, labels: &[(&str, &str)]) {
+    let mut reg = registry().lock().unwrap_or_else(|e| e.into_inner());
+    *reg.counters
+        .entry((name.to_string(), owned_labels(labels)))
+        .or_insert(0) += 1;
+}
+
+/// Add `delta` (which may be negative) to a gauge.
+pub fn add_gauge(name: &str, labels: &[(&str, &str)], delta: i64) {
+    let mut reg = registry().lock().unwrap_or_else(|e| e.into_inner());
+    *reg.gauges
+        .entry((name.to_string(), owned_labels(labels)))
+        .or_insert(0) += delta;
+}
+
+/// Record one observation (in seconds) into a histogram with default buckets.
+pub fn observe(name: &str, labels: &[(&str, &str)], value: f64) {
+    let mut reg = registry().lock().unwrap_or_else(|e| e.into_inner());
+    reg.histograms
+        .entry((name.to_string(), owned_labels(labels)))
+        .or_insert_with(|| Histogram::new(DEFAULT_BUCKETS))
+        .observe(value);
+}
+
+/// Record the latency and outcome of one cloud provider API call.
+pub fn observe_cloud_api(provider: &str, started: Instant, ok: bool) {
+    observe(
+        CLOUD_API_DURATION_SECONDS,
+        &[("provider", provider)],
+        started.elapsed().as_secs_f64(),
+    );
+    if !ok {
+        inc_counter(CLOUD_API_ERRORS_TOTAL, &[("provider", provider)]);
+    }
+}
+
+/// Increments `clawmacdo_sse_streams_active` on creation and decrements it on drop.
+pub struct SseStreamGuard;
+
+impl SseStreamGuard {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        add_gauge(SSE_STREAMS_ACTIVE, &[], 1);
+        Self
+    }
+}
+
+impl Drop for SseStreamGuard {
+    fn drop(&mut self) {
+        add_gauge(SSE_STREAMS_ACTIVE, &[], -1);
+    }
+}
+
+/// Write the `# HELP` / `# TYPE` header for a metric family.
+pub fn write_header(out: &mut String, name: &str, kind: MetricKind, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {}", kind.as_str());
+}
+
+/// Render every in-process metric in Prometheus text exposition format.
+pub fn render() -> String {
+    let reg = registry().lock().unwrap_or_else(|e| e.into_inner());
+    let mut out = String::new();
+
+    for (name, kind, help) in DESCRIPTIONS {
+        write_header(&mut out, name, *kind, help);
+        match kind {
+            MetricKind::Counter => {
+                for ((metric, labels), value) in &reg.counters {
+                    if metric == name {
+                        let _ = writeln!(out, "{name}{} {value}", format_owned_labels(labels));
+                    }
+                }
+            }
+            MetricKind::Gauge => {
+                let mut seen = false;
+                for ((metric, labels), value) in &reg.gauges {
+                    if metric == name {
+                        seen = true;
+                        let _ = writeln!(out, "{name}{} {value}", format_owned_labels(labels));
+                    }
+                }
+                if !seen {
+                    let _ = writeln!(out, "{name} 0");
+                }
+            }
+            MetricKind::Histogram => {
+                for ((metric, labels), hist) in &reg.histograms {
+                    if metric == name {
+                        let borrowed: Vec<(&str, &str)> = labels
+                            .iter()
+                            .map(|(k, v)| (k.as_str(), v.as_str()))
+                            .collect();
+                        hist.render_into(&mut out, name, &borrowed);
+                    }
+                }
+            }
+        }
+    }
+
+    out
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Format a label set as `{k="v",...}`, or an empty string when there are none.
+pub fn format_labels(labels: &[(&str, &str)]) -> String {
+    if labels.is_empty() {
+        return String::new();
+    }
+    let inner: Vec<String> = labels
+        .iter()
+        .map(|(k, v)| format!("{k}=\"{}\"", escape_label_value(v)))
+        .collect();
+    format!("{{{}}}", inner.join(","))
+}
+
+fn format_owned_labels(labels: &[(String, String)]) -> String {
+    let borrowed: Vec<(&str, &str)> = labels
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .collect();
+    format_labels(&borrowed)
+}
+
+fn format_float(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{value:.1}")
+    } else {
+        format!("{value}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histogram_buckets_are_cumulative() {
+        let mut hist = Histogram::new(&[1.0, 5.0]);
+        hist.observe(0.5);
+        hist.observe(3.0);
+        hist.observe(10.0);
+
+        let mut out = String::new();
+        hist.render_into(&mut out, "test_seconds", &[("step", "9")]);
+        assert!(out.contains("test_seconds_bucket{step=\"9\",le=\"1.0\"} 1"));
+        assert!(out.contains("test_seconds_bucket{step=\"9\",le=\"5.0\"} 2"));
+        assert!(out.contains("test_seconds_bucket{step=\"9\",le=\"+Inf\"} 3"));
+        assert!(out.contains("test_seconds_sum{step=\"9\"} 13.5"));
+        assert!(out.contains("test_seconds_count{step=\"9\"} 3"));
+    }
+
+    #[test]
+    fn label_values_are_escaped() {
+        assert_eq!(
+            format_labels(&[("label", "say \"hi\"\\")]),
+            "{label=\"say \\\"hi\\\"\\\\\"}"
+        );
+        assert_eq!(format_labels(&[]), "");
+    }
+
+    #[test]
+    fn render_includes_recorded_counters_and_gauge_default() {
+        inc_counter(RATE_LIMIT_REJECTIONS_TOTAL, &[]);
+        let out = render();
+        assert!(out.contains("# TYPE clawmacdo_rate_limit_rejections_total counter"));
+        assert!(out.contains("clawmacdo_rate_limit_rejections_total "));
+        assert!(out.contains("# TYPE clawmacdo_sse_streams_active gauge"));
+    }
+}
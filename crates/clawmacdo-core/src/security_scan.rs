@@ -0,0 +1,1215 @@
+//! Security scan of a deployed OpenClaw instance.
+//!
+//! [`COLLECT_SCRIPT`] runs as root on the instance and prints raw facts in
+//! `@@ <section>` blocks: effective sshd settings, UFW and fail2ban state,
+//! listening sockets, modes of the OpenClaw secret files, a summary of the
+//! gateway auth settings (lengths only, never the token) and Docker socket
+//! exposure. [`evaluate`] turns those sections into a [`ScanReport`] in the
+//! `{host, secrets, checks: [{id, value, ...}]}` shape the scan scripts used,
+//! with a risk score on top. [`to_sarif`] and [`to_text`] render the report
+//! for code-scanning uploads and terminals.
+
+use crate::config::{OPENCLAW_GATEWAY_PORT, OPENCLAW_HOME};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+/// Bash collector, fed to `bash -s` as root. Every probe tolerates missing
+/// tools, so the script always exits 0; the body runs with stdin closed so
+/// probes cannot swallow the rest of the script.
+pub const COLLECT_SCRIPT: &str = r#"
+collect() {
+  OC_HOME=/home/openclaw
+  section() { printf '\n@@ %s\n' "$1"; }
+
+  section host
+  hostname 2>/dev/null || cat /etc/hostname
+
+  section sshd
+  KEYS='permitrootlogin|passwordauthentication|permitemptypasswords|kbdinteractiveauthentication'
+  if command -v sshd >/dev/null 2>&1 && sshd -T 2>/dev/null | grep -Ei "^($KEYS) "; then
+    echo '# source: sshd -T'
+  else
+    cat /etc/ssh/sshd_config.d/*.conf /etc/ssh/sshd_config 2>/dev/null \
+      | grep -Ei "^[[:space:]]*($KEYS)[[:space:]]" \
+      | awk '{ k = tolower($1); if (!seen[k]++) print k, $2 }'
+    echo '# source: sshd_config'
+  fi
+
+  section ufw
+  if command -v ufw >/dev/null 2>&1; then ufw status verbose 2>&1; else echo 'ufw: not installed'; fi
+
+  section fail2ban
+  if command -v fail2ban-client >/dev/null 2>&1; then
+    state=$(systemctl is-active fail2ban 2>/dev/null)
+    echo "service: ${state:-unknown}"
+    fail2ban-client status 2>/dev/null | grep -i 'jail list' | sed 's/^[^:]*:[[:space:]]*/jails: /'
+  else
+    echo 'fail2ban: not installed'
+  fi
+
+  section ports
+  ss -H -ltnup 2>/dev/null | awk '{ print $1, $5, $7 }'
+
+  section files
+  for f in "$OC_HOME/.openclaw" "$OC_HOME/.openclaw/.env" "$OC_HOME/.openclaw/gateway.env" \
+           "$OC_HOME/.openclaw/openclaw.json" "$OC_HOME/.openclaw/credentials"; do
+    [ -e "$f" ] && stat -c '%a %U %n' "$f"
+  done
+  if [ -d "$OC_HOME/.openclaw/credentials" ]; then
+    find "$OC_HOME/.openclaw/credentials" -maxdepth 3 -type f -exec stat -c '%a %U %n' {} + 2>/dev/null
+  fi
+
+  section gateway
+  CFG="$OC_HOME/.openclaw/openclaw.json"
+  if [ ! -f "$CFG" ]; then
+    echo 'config: missing'
+  elif command -v node >/dev/null 2>&1; then
+    CFG="$CFG" node <<'NODE'
+const fs = require('fs');
+let cfg;
+try {
+  cfg = JSON.parse(fs.readFileSync(process.env.CFG, 'utf8'));
+} catch (e) {
+  console.log('config: invalid');
+  process.exit(0);
+}
+const gw = cfg.gateway || {};
+const auth = gw.auth || {};
+const describe = (name, s) => {
+  s = typeof s === 'string' ? s : '';
+  console.log(name + '_length: ' + s.length);
+  console.log(name + '_distinct: ' + new Set(s).size);
+};
+console.log('config: ok');
+console.log('mode: ' + (auth.mode || (auth.token ? 'token' : 'none')));
+describe('token', auth.token);
+describe('password', auth.password);
+console.log('bind: ' + (gw.bind || 'loopback'));
+NODE
+  else
+    echo 'config: unreadable (node not found)'
+  fi
+
+  section docker
+  if [ -S /var/run/docker.sock ]; then
+    stat -Lc 'socket: %a %U %G' /var/run/docker.sock
+  else
+    echo 'socket: none'
+  fi
+  echo "group: $(getent group docker 2>/dev/null | cut -d: -f4)"
+  if [ -f /etc/docker/daemon.json ]; then
+    echo "daemon_hosts: $(tr -d ' \n\t' </etc/docker/daemon.json | grep -o '"hosts":\[[^]]*\]')"
+  fi
+  if command -v docker >/dev/null 2>&1; then
+    docker ps -q 2>/dev/null \
+      | xargs -r docker inspect -f '{{.Name}} {{range .Mounts}}{{.Source}} {{end}}' 2>/dev/null \
+      | grep 'docker.sock' | awk '{ sub("^/", "", $1); print "mounted_by: " $1 }'
+  fi
+}
+collect </dev/null
+exit 0
+"#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+            Severity::Critical => "critical",
+        }
+    }
+
+    /// Points a failed check of this severity adds to the risk score.
+    fn weight(self) -> u32 {
+        match self {
+            Severity::Info => 0,
+            Severity::Low => 3,
+            Severity::Medium => 8,
+            Severity::High => 20,
+            Severity::Critical => 40,
+        }
+    }
+
+    /// GitHub code scanning `security-severity` (CVSS-like, 0.0–10.0).
+    fn security_severity(self) -> &'static str {
+        match self {
+            Severity::Info => "0.0",
+            Severity::Low => "3.0",
+            Severity::Medium => "5.5",
+            Severity::High => "8.0",
+            Severity::Critical => "9.5",
+        }
+    }
+
+    fn sarif_level(self) -> &'static str {
+        match self {
+            Severity::Critical | Severity::High => "error",
+            Severity::Medium => "warning",
+            Severity::Low | Severity::Info => "note",
+        }
+    }
+}
+
+impl std::str::FromStr for Severity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "info" => Ok(Severity::Info),
+            "low" => Ok(Severity::Low),
+            "medium" => Ok(Severity::Medium),
+            "high" => Ok(Severity::High),
+            "critical" => Ok(Severity::Critical),
+            other => Err(format!(
+                "unknown severity '{other}' (expected info, low, medium, high or critical)"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Fail,
+    /// The collector could not determine the setting.
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScanCheck {
+    pub id: String,
+    pub value: String,
+    pub status: CheckStatus,
+    pub severity: Severity,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remediation: Option<String>,
+    /// File (or pseudo-file) the finding is about, used for SARIF locations.
+    pub location: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RiskScore {
+    /// Sum of the failed checks' weights, capped at 100.
+    pub score: u32,
+    /// Highest severity among the failed checks (`info` when none failed).
+    pub level: Severity,
+    pub failed: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScanReport {
+    pub host: String,
+    /// Secret-bearing files readable beyond their owner, one per line.
+    pub secrets: String,
+    pub checks: Vec<ScanCheck>,
+    pub risk: RiskScore,
+    pub scanned_at: String,
+}
+
+impl ScanReport {
+    pub fn failed(&self) -> impl Iterator<Item = &ScanCheck> {
+        self.checks.iter().filter(|c| c.status == CheckStatus::Fail)
+    }
+}
+
+/// Split collector output into its `@@ <name>` sections. Comment lines and
+/// anything before the first marker are dropped.
+pub fn parse_sections(output: &str) -> BTreeMap<String, String> {
+    let mut sections: BTreeMap<String, String> = BTreeMap::new();
+    let mut current: Option<String> = None;
+    for line in output.lines() {
+        if let Some(name) = line.strip_prefix("@@ ") {
+            let name = name.trim().to_string();
+            sections.entry(name.clone()).or_default();
+            current = Some(name);
+            continue;
+        }
+        let Some(name) = &current else { continue };
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+        let body = sections.entry(name.clone()).or_default();
+        body.push_str(line.trim_end());
+        body.push('\n');
+    }
+    sections
+}
+
+/// `key: value` (or `key value` for sshd) lines of a section, keys lowercased.
+fn key_values(section: &str, separator: char) -> BTreeMap<String, String> {
+    section
+        .lines()
+        .filter_map(|line| line.split_once(separator))
+        .map(|(k, v)| (k.trim().to_ascii_lowercase(), v.trim().to_string()))
+        .collect()
+}
+
+struct Checks(Vec<ScanCheck>);
+
+impl Checks {
+    fn push(
+        &mut self,
+        id: &str,
+        value: impl Into<String>,
+        failed: Option<Severity>,
+        title: &str,
+        remediation: &str,
+        location: &str,
+    ) {
+        let (status, severity) = match failed {
+            Some(severity) => (CheckStatus::Fail, severity),
+            None => (CheckStatus::Pass, Severity::Info),
+        };
+        self.0.push(ScanCheck {
+            id: id.into(),
+            value: value.into(),
+            status,
+            severity,
+            title: title.into(),
+            remediation: failed.map(|_| remediation.to_string()),
+            location: location.into(),
+        });
+    }
+
+    fn unknown(&mut self, id: &str, value: &str, title: &str, location: &str) {
+        self.0.push(ScanCheck {
+            id: id.into(),
+            value: value.into(),
+            status: CheckStatus::Unknown,
+            severity: Severity::Info,
+            title: title.into(),
+            remediation: None,
+            location: location.into(),
+        });
+    }
+}
+
+const SSHD_CONFIG: &str = "/etc/ssh/sshd_config";
+const UFW_CONFIG: &str = "/etc/ufw/ufw.conf";
+
+fn check_sshd(checks: &mut Checks, section: Option<&String>) {
+    let settings = section.map(|s| key_values(s, ' ')).unwrap_or_default();
+    let setting = |key: &str| settings.get(key).map(|v| v.to_ascii_lowercase());
+
+    let title = "Root SSH login is restricted";
+    match setting("permitrootlogin") {
+        Some(v) => checks.push(
+            "sshd_permit_root",
+            format!("PermitRootLogin {v}"),
+            (v == "yes").then_some(Severity::High),
+            title,
+            "Set `PermitRootLogin prohibit-password` (or `no`) in sshd_config and reload sshd.",
+            SSHD_CONFIG,
+        ),
+        None => checks.unknown("sshd_permit_root", "not reported", title, SSHD_CONFIG),
+    }
+
+    let title = "SSH password authentication is disabled";
+    match setting("passwordauthentication") {
+        Some(v) => checks.push(
+            "sshd_password_auth",
+            format!("PasswordAuthentication {v}"),
+            (v == "yes").then_some(Severity::High),
+            title,
+            "Set `PasswordAuthentication no` in sshd_config and reload sshd; log in with keys only.",
+            SSHD_CONFIG,
+        ),
+        None => checks.unknown("sshd_password_auth", "not reported", title, SSHD_CONFIG),
+    }
+
+    let title = "SSH refuses empty passwords";
+    if settings.is_empty() {
+        checks.unknown("sshd_empty_passwords", "not reported", title, SSHD_CONFIG);
+        return;
+    }
+    // Absent from sshd_config means the `no` default.
+    let v = setting("permitemptypasswords").unwrap_or_else(|| "no".into());
+    checks.push(
+        "sshd_empty_passwords",
+        format!("PermitEmptyPasswords {v}"),
+        (v == "yes").then_some(Severity::Critical),
+        title,
+        "Set `PermitEmptyPasswords no` in sshd_config and reload sshd.",
+        SSHD_CONFIG,
+    );
+}
+
+/// Returns whether UFW is active and whether it allows the gateway port.
+fn check_ufw(checks: &mut Checks, section: Option<&String>) -> (bool, bool) {
+    let Some(section) = section.filter(|s| !s.trim().is_empty()) else {
+        checks.unknown(
+            "ufw_enabled",
+            "not reported",
+            "UFW firewall is active",
+            UFW_CONFIG,
+        );
+        return (false, false);
+    };
+    let enable = "Install UFW, allow SSH (`ufw allow OpenSSH`) and run `ufw enable`.";
+    if section.contains("not installed") {
+        checks.push(
+            "ufw_enabled",
+            "not installed",
+            Some(Severity::High),
+            "UFW firewall is active",
+            enable,
+            UFW_CONFIG,
+        );
+        return (false, false);
+    }
+    let active = section
+        .lines()
+        .any(|l| l.trim().eq_ignore_ascii_case("status: active"));
+    checks.push(
+        "ufw_enabled",
+        if active { "active" } else { "inactive" },
+        (!active).then_some(Severity::High),
+        "UFW firewall is active",
+        enable,
+        UFW_CONFIG,
+    );
+    if !active {
+        return (false, false);
+    }
+
+    let default = section
+        .lines()
+        .find_map(|l| l.trim().strip_prefix("Default:"))
+        .unwrap_or("not reported")
+        .trim();
+    let denies = default.contains("deny (incoming)") || default.contains("reject (incoming)");
+    checks.push(
+        "ufw_default_incoming",
+        default,
+        (!denies).then_some(Severity::Medium),
+        "UFW denies incoming traffic by default",
+        "Run `ufw default deny incoming` and allow only the ports you need.",
+        UFW_CONFIG,
+    );
+
+    let port = OPENCLAW_GATEWAY_PORT.to_string();
+    let gateway_rules: Vec<&str> = section
+        .lines()
+        .map(str::trim)
+        .filter(|l| {
+            l.split(|c: char| c == '/' || c.is_whitespace()).next() == Some(port.as_str())
+                && l.contains("ALLOW")
+        })
+        .collect();
+    let allowed = !gateway_rules.is_empty();
+    checks.push(
+        "ufw_gateway_port",
+        if allowed {
+            gateway_rules.join(", ")
+        } else {
+            format!("{port} not allowed")
+        },
+        allowed.then_some(Severity::Medium),
+        "The gateway port is not open in UFW",
+        "Remove the rule (`ufw delete allow 18789/tcp`) and reach the gateway through SSH, Tailscale or a TLS proxy.",
+        UFW_CONFIG,
+    );
+    (true, allowed)
+}
+
+fn check_fail2ban(checks: &mut Checks, section: Option<&String>) {
+    let location = "/etc/fail2ban/jail.local";
+    let title = "fail2ban is running";
+    let remediation =
+        "Install fail2ban and enable the sshd jail (`systemctl enable --now fail2ban`).";
+    let Some(section) = section.filter(|s| !s.trim().is_empty()) else {
+        checks.unknown("fail2ban", "not reported", title, location);
+        return;
+    };
+    if section.contains("not installed") {
+        checks.push(
+            "fail2ban",
+            "not installed",
+            Some(Severity::Medium),
+            title,
+            remediation,
+            location,
+        );
+        return;
+    }
+    let values = key_values(section, ':');
+    let state = values
+        .get("service")
+        .map(String::as_str)
+        .unwrap_or("unknown");
+    let jails = values
+        .get("jails")
+        .map(String::as_str)
+        .filter(|j| !j.is_empty())
+        .unwrap_or("none");
+    checks.push(
+        "fail2ban",
+        format!("{state}; jails: {jails}"),
+        (state != "active").then_some(Severity::Medium),
+        title,
+        remediation,
+        location,
+    );
+}
+
+/// A listening socket from `ss`: protocol, bind address, port and process.
+#[derive(Debug, PartialEq)]
+struct Listener {
+    proto: String,
+    addr: String,
+    port: u16,
+    process: String,
+}
+
+impl Listener {
+    fn exposed(&self) -> bool {
+        !(self.addr.starts_with("127.") || self.addr == "::1" || self.addr == "localhost")
+    }
+
+    fn describe(&self) -> String {
+        if self.process.is_empty() {
+            format!("{}/{} on {}", self.port, self.proto, self.addr)
+        } else {
+            format!(
+                "{}/{} on {} ({})",
+                self.port, self.proto, self.addr, self.process
+            )
+        }
+    }
+}
+
+fn parse_listeners(section: &str) -> Vec<Listener> {
+    section
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let proto = fields.next()?.to_string();
+            let local = fields.next()?;
+            let (addr, port) = local.rsplit_once(':')?;
+            let addr = addr.trim_start_matches('[').trim_end_matches(']');
+            let addr = addr.split('%').next().unwrap_or(addr).to_string();
+            // users:(("sshd",pid=1,fd=3)) → sshd
+            let process = fields
+                .next()
+                .and_then(|p| p.split('"').nth(1))
+                .unwrap_or("")
+                .to_string();
+            Some(Listener {
+                proto,
+                addr,
+                port: port.parse().ok()?,
+                process,
+            })
+        })
+        .collect()
+}
+
+/// Ports expected to face the internet on an OpenClaw host: SSH, HTTP(S)
+/// for a TLS proxy, DHCP and Tailscale's WireGuard port.
+const EXPECTED_PORTS: &[(&str, u16)] = &[
+    ("tcp", 22),
+    ("tcp", 80),
+    ("tcp", 443),
+    ("udp", 68),
+    ("udp", 41641),
+];
+
+fn check_ports(checks: &mut Checks, listeners: &[Listener], ufw_active: bool) {
+    let location = "/proc/net/tcp";
+    let unexpected: Vec<String> = listeners
+        .iter()
+        // The gateway and Docker API ports have their own checks.
+        .filter(|l| l.exposed() && !matches!(l.port, OPENCLAW_GATEWAY_PORT | 2375 | 2376))
+        .filter(|l| !EXPECTED_PORTS.contains(&(l.proto.as_str(), l.port)))
+        .map(Listener::describe)
+        .collect();
+    let severity = if ufw_active {
+        Severity::Low
+    } else {
+        Severity::Medium
+    };
+    checks.push(
+        "listening_ports",
+        if unexpected.is_empty() {
+            "only expected ports are exposed".to_string()
+        } else {
+            unexpected.join(", ")
+        },
+        (!unexpected.is_empty()).then_some(severity),
+        "Only expected services listen on public interfaces",
+        "Bind internal services to 127.0.0.1 or block them in UFW.",
+        location,
+    );
+
+    let gateway: Vec<String> = listeners
+        .iter()
+        .filter(|l| l.port == OPENCLAW_GATEWAY_PORT && l.exposed())
+        .map(Listener::describe)
+        .collect();
+    checks.push(
+        "gateway_listen",
+        if gateway.is_empty() {
+            "loopback only".to_string()
+        } else {
+            gateway.join(", ")
+        },
+        (!gateway.is_empty()).then_some(Severity::Medium),
+        "The gateway listens on loopback only",
+        "Set `gateway.bind` to `loopback` in openclaw.json and publish the gateway through a proxy or Tailscale.",
+        location,
+    );
+}
+
+/// A `stat -c '%a %U %n'` line.
+struct FileMode<'a> {
+    mode: u32,
+    owner: &'a str,
+    path: &'a str,
+}
+
+fn parse_file_modes(section: &str) -> Vec<FileMode<'_>> {
+    section
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(3, ' ');
+            let mode = u32::from_str_radix(parts.next()?, 8).ok()?;
+            let owner = parts.next()?;
+            let path = parts.next()?;
+            Some(FileMode { mode, owner, path })
+        })
+        .collect()
+}
+
+/// Checks secret files are private to their owner; returns the files that are not.
+fn check_files(checks: &mut Checks, section: Option<&String>) -> Vec<String> {
+    let section = section.map(String::as_str).unwrap_or("");
+    let files = parse_file_modes(section);
+    let base = format!("{OPENCLAW_HOME}/.openclaw");
+    let credentials = format!("{base}/credentials");
+    let owned = |f: &FileMode| f.owner == "openclaw" || f.owner == "root";
+    let describe = |f: &FileMode| format!("{:o} {} {}", f.mode, f.owner, f.path);
+    let mut exposed = Vec::new();
+
+    if let Some(dir) = files.iter().find(|f| f.path == base) {
+        let open = dir.mode & 0o007 != 0 || !owned(dir);
+        checks.push(
+            "openclaw_dir_mode",
+            describe(dir),
+            open.then_some(Severity::Medium),
+            "The OpenClaw state directory is private",
+            "Run `chmod 700 /home/openclaw/.openclaw` and `chown openclaw:openclaw` it.",
+            &base,
+        );
+    }
+
+    let singles = [
+        (
+            "env_file_mode",
+            ".env",
+            "The .env file is readable only by its owner",
+        ),
+        (
+            "gateway_env_mode",
+            "gateway.env",
+            "gateway.env is readable only by its owner",
+        ),
+        (
+            "openclaw_json_mode",
+            "openclaw.json",
+            "openclaw.json (holds the gateway token) is readable only by its owner",
+        ),
+    ];
+    for (id, name, title) in singles {
+        let path = format!("{base}/{name}");
+        let Some(file) = files.iter().find(|f| f.path == path) else {
+            continue;
+        };
+        let open = file.mode & 0o077 != 0 || !owned(file);
+        if open {
+            exposed.push(file.path.to_string());
+        }
+        checks.push(
+            id,
+            describe(file),
+            open.then_some(Severity::High),
+            title,
+            &format!("Run `chmod 600 {path}` and `chown openclaw:openclaw {path}`."),
+            &path,
+        );
+    }
+
+    let credential_files: Vec<&FileMode> = files
+        .iter()
+        .filter(|f| f.path.starts_with(&format!("{credentials}/")))
+        .collect();
+    if files.iter().any(|f| f.path == credentials) {
+        let open: Vec<String> = credential_files
+            .iter()
+            .filter(|f| f.mode & 0o077 != 0 || !owned(f))
+            .map(|f| describe(f))
+            .collect();
+        exposed.extend(
+            credential_files
+                .iter()
+                .filter(|f| f.mode & 0o077 != 0 || !owned(f))
+                .map(|f| f.path.to_string()),
+        );
+        checks.push(
+            "credentials_mode",
+            if open.is_empty() {
+                format!("{} file(s), all private", credential_files.len())
+            } else {
+                open.join(", ")
+            },
+            (!open.is_empty()).then_some(Severity::High),
+            "Channel credentials are readable only by their owner",
+            &format!("Run `chmod -R go-rwx {credentials}`."),
+            &credentials,
+        );
+    }
+    exposed
+}
+
+/// Rough entropy estimate: length × log2(distinct characters).
+fn entropy_bits(length: u32, distinct: u32) -> f64 {
+    if distinct < 2 {
+        return 0.0;
+    }
+    f64::from(length) * f64::from(distinct).log2()
+}
+
+fn check_gateway(checks: &mut Checks, section: Option<&String>, exposed: bool) {
+    let location = format!("{OPENCLAW_HOME}/.openclaw/openclaw.json");
+    let values = section.map(|s| key_values(s, ':')).unwrap_or_default();
+    let config = values
+        .get("config")
+        .map(String::as_str)
+        .unwrap_or("not reported");
+    if config != "ok" {
+        checks.unknown(
+            "gateway_auth_mode",
+            &format!("config: {config}"),
+            "The gateway requires authentication",
+            &location,
+        );
+        return;
+    }
+    let mode = values.get("mode").map(String::as_str).unwrap_or("none");
+    let unauthenticated = matches!(mode, "none" | "off" | "disabled");
+    checks.push(
+        "gateway_auth_mode",
+        format!("mode {mode}"),
+        unauthenticated.then_some(Severity::Critical),
+        "The gateway requires authentication",
+        "Set `gateway.auth.mode` to `token` and rotate the token with `clawmacdo openclaw-gateway-token`.",
+        &location,
+    );
+    if unauthenticated {
+        return;
+    }
+
+    let secret = if mode == "password" {
+        "password"
+    } else {
+        "token"
+    };
+    let number = |key: &str| {
+        values
+            .get(&format!("{secret}_{key}"))
+            .and_then(|v| v.parse::<u32>().ok())
+            .unwrap_or(0)
+    };
+    let (length, distinct) = (number("length"), number("distinct"));
+    let bits = entropy_bits(length, distinct);
+    let weak = if length < 16 || bits < 64.0 {
+        Some(Severity::High)
+    } else if bits < 128.0 {
+        Some(Severity::Medium)
+    } else {
+        None
+    };
+    checks.push(
+        "gateway_token_strength",
+        format!("{secret}: {length} chars, {distinct} distinct, ~{bits:.0} bits"),
+        weak,
+        "The gateway secret is long and random",
+        "Rotate the secret with `clawmacdo openclaw-gateway-token` (32 random bytes).",
+        &location,
+    );
+
+    let bind = values.get("bind").map(String::as_str).unwrap_or("loopback");
+    let severity = if exposed {
+        Severity::Medium
+    } else {
+        Severity::Low
+    };
+    checks.push(
+        "gateway_bind",
+        format!("bind {bind}"),
+        (bind != "loopback").then_some(severity),
+        "The gateway binds to loopback",
+        "Set `gateway.bind` to `loopback` and publish the gateway through a proxy or Tailscale.",
+        &location,
+    );
+}
+
+fn check_docker(checks: &mut Checks, section: Option<&String>, listeners: &[Listener]) {
+    let location = "/var/run/docker.sock";
+    let section = section.map(String::as_str).unwrap_or("");
+    let values = key_values(section, ':');
+    let socket = values.get("socket").map(String::as_str).unwrap_or("none");
+
+    if socket != "none" {
+        let mode = socket
+            .split_whitespace()
+            .next()
+            .and_then(|m| u32::from_str_radix(m, 8).ok())
+            .unwrap_or(0);
+        checks.push(
+            "docker_socket",
+            socket,
+            (mode & 0o002 != 0).then_some(Severity::Critical),
+            "The Docker socket is not world-writable",
+            "Run `chmod 660 /var/run/docker.sock`; access to the socket is root access.",
+            location,
+        );
+
+        let members: Vec<&str> = values
+            .get("group")
+            .map(|g| {
+                g.split(',')
+                    .map(str::trim)
+                    .filter(|m| !m.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+        checks.push(
+            "docker_group",
+            if members.is_empty() {
+                "no members".to_string()
+            } else {
+                members.join(", ")
+            },
+            members.contains(&"openclaw").then_some(Severity::Medium),
+            "The openclaw user is not in the docker group",
+            "Run `gpasswd -d openclaw docker`; docker group membership is equivalent to root.",
+            "/etc/group",
+        );
+
+        let mounted: Vec<&str> = section
+            .lines()
+            .filter_map(|l| l.strip_prefix("mounted_by:"))
+            .map(str::trim)
+            .collect();
+        checks.push(
+            "docker_socket_mounts",
+            if mounted.is_empty() {
+                "no container mounts docker.sock".to_string()
+            } else {
+                mounted.join(", ")
+            },
+            (!mounted.is_empty()).then_some(Severity::High),
+            "No container mounts the Docker socket",
+            "Remove the docker.sock volume from these containers or use a socket proxy with a narrow allow-list.",
+            location,
+        );
+    }
+
+    let hosts = values.get("daemon_hosts").map(String::as_str).unwrap_or("");
+    let tcp_config = hosts
+        .split('"')
+        .filter(|h| h.starts_with("tcp://"))
+        .any(|h| !h.starts_with("tcp://127.") && !h.starts_with("tcp://localhost"));
+    let tcp_listeners: Vec<String> = listeners
+        .iter()
+        .filter(|l| l.exposed() && matches!(l.port, 2375 | 2376))
+        .map(Listener::describe)
+        .collect();
+    let plain = listeners.iter().any(|l| l.exposed() && l.port == 2375);
+    let failed = if plain {
+        Some(Severity::Critical)
+    } else if tcp_config || !tcp_listeners.is_empty() {
+        Some(Severity::High)
+    } else {
+        None
+    };
+    checks.push(
+        "docker_tcp_api",
+        if failed.is_none() {
+            "not exposed".to_string()
+        } else if tcp_listeners.is_empty() {
+            hosts.to_string()
+        } else {
+            tcp_listeners.join(", ")
+        },
+        failed,
+        "The Docker API is not exposed over TCP",
+        "Remove tcp:// hosts from /etc/docker/daemon.json and the dockerd unit; use the local socket over SSH instead.",
+        "/etc/docker/daemon.json",
+    );
+}
+
+/// Score a list of checks: weights of the failures, capped at 100.
+pub fn risk_score(checks: &[ScanCheck]) -> RiskScore {
+    let failed: Vec<&ScanCheck> = checks
+        .iter()
+        .filter(|c| c.status == CheckStatus::Fail)
+        .collect();
+    RiskScore {
+        score: failed
+            .iter()
+            .map(|c| c.severity.weight())
+            .sum::<u32>()
+            .min(100),
+        level: failed
+            .iter()
+            .map(|c| c.severity)
+            .max()
+            .unwrap_or(Severity::Info),
+        failed: failed.len(),
+    }
+}
+
+/// Evaluate collector output. `host` is used when the output does not name
+/// the machine.
+pub fn evaluate(host: &str, output: &str) -> ScanReport {
+    let sections = parse_sections(output);
+    let mut checks = Checks(Vec::new());
+    let listeners = parse_listeners(sections.get("ports").map(String::as_str).unwrap_or(""));
+    let gateway_exposed = listeners
+        .iter()
+        .any(|l| l.port == OPENCLAW_GATEWAY_PORT && l.exposed());
+
+    check_sshd(&mut checks, sections.get("sshd"));
+    let (ufw_active, ufw_gateway) = check_ufw(&mut checks, sections.get("ufw"));
+    check_fail2ban(&mut checks, sections.get("fail2ban"));
+    check_ports(&mut checks, &listeners, ufw_active);
+    let exposed_secrets = check_files(&mut checks, sections.get("files"));
+    check_gateway(
+        &mut checks,
+        sections.get("gateway"),
+        gateway_exposed && (!ufw_active || ufw_gateway),
+    );
+    check_docker(&mut checks, sections.get("docker"), &listeners);
+
+    let checks = checks.0;
+    ScanReport {
+        host: sections
+            .get("host")
+            .and_then(|h| h.lines().next())
+            .map(str::trim)
+            .filter(|h| !h.is_empty())
+            .unwrap_or(host)
+            .to_string(),
+        secrets: exposed_secrets.join("\n"),
+        risk: risk_score(&checks),
+        checks,
+        scanned_at: chrono::Utc::now().to_rfc3339(),
+    }
+}
+
+/// SARIF 2.1.0 log with one rule and one result per failed check.
+pub fn to_sarif(report: &ScanReport) -> Value {
+    let failed: Vec<&ScanCheck> = report.failed().collect();
+    let rules: Vec<Value> = failed
+        .iter()
+        .map(|c| {
+            json!({
+                "id": c.id,
+                "name": c.id,
+                "shortDescription": {"text": c.title},
+                "help": {"text": c.remediation.clone().unwrap_or_default()},
+                "defaultConfiguration": {"level": c.severity.sarif_level()},
+                "properties": {
+                    "security-severity": c.severity.security_severity(),
+                    "tags": ["security"],
+                },
+            })
+        })
+        .collect();
+    let results: Vec<Value> = failed
+        .iter()
+        .enumerate()
+        .map(|(index, c)| {
+            let uri = c.location.trim_start_matches('/');
+            json!({
+                "ruleId": c.id,
+                "ruleIndex": index,
+                "level": c.severity.sarif_level(),
+                "message": {"text": format!("{} on {}: {}", c.title, report.host, c.value)},
+                "locations": [{
+                    "physicalLocation": {"artifactLocation": {"uri": uri}},
+                }],
+                "properties": {"severity": c.severity.as_str()},
+            })
+        })
+        .collect();
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "OpenClawScanner",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                },
+            },
+            "results": results,
+            "properties": {
+                "host": report.host,
+                "riskScore": report.risk.score,
+                "riskLevel": report.risk.level.as_str(),
+                "scannedAt": report.scanned_at,
+            },
+        }],
+    })
+}
+
+/// Plain-text report: `- id: value` lines like the old text format, with
+/// status, severity and remediation added.
+pub fn to_text(report: &ScanReport) -> String {
+    let mut lines = vec![
+        format!("Host: {}", report.host),
+        format!(
+            "Risk: {}/100 ({}, {} failed check(s))",
+            report.risk.score,
+            report.risk.level.as_str(),
+            report.risk.failed
+        ),
+        "Checks:".to_string(),
+    ];
+    for check in &report.checks {
+        let status = match check.status {
+            CheckStatus::Pass => "pass".to_string(),
+            CheckStatus::Fail => format!("FAIL {}", check.severity.as_str()),
+            CheckStatus::Unknown => "unknown".to_string(),
+        };
+        lines.push(format!("- [{status}] {}: {}", check.id, check.value));
+        if let Some(fix) = &check.remediation {
+            lines.push(format!("    fix: {fix}"));
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HIGH_RISK: &str = "\
+@@ host
+test-host
+@@ sshd
+permitrootlogin yes
+passwordauthentication yes
+permitemptypasswords no
+# source: sshd -T
+@@ ufw
+Status: inactive
+@@ fail2ban
+fail2ban: not installed
+@@ ports
+tcp 0.0.0.0:22 users:((\"sshd\",pid=812,fd=3))
+tcp 0.0.0.0:18789 users:((\"node\",pid=1200,fd=21))
+tcp 0.0.0.0:2375 users:((\"dockerd\",pid=900,fd=9))
+tcp 127.0.0.1:631 users:((\"cupsd\",pid=90,fd=7))
+tcp [::]:5432 users:((\"postgres\",pid=77,fd=5))
+@@ files
+755 openclaw /home/openclaw/.openclaw
+644 openclaw /home/openclaw/.openclaw/.env
+600 openclaw /home/openclaw/.openclaw/openclaw.json
+700 openclaw /home/openclaw/.openclaw/credentials
+644 openclaw /home/openclaw/.openclaw/credentials/whatsapp/creds.json
+@@ gateway
+config: ok
+mode: token
+token_length: 8
+token_distinct: 7
+password_length: 0
+password_distinct: 0
+bind: lan
+@@ docker
+socket: 666 root docker
+group: ubuntu,openclaw
+mounted_by: portainer
+";
+
+    const HARDENED: &str = "\
+@@ host
+safe-host
+@@ sshd
+permitrootlogin without-password
+passwordauthentication no
+permitemptypasswords no
+@@ ufw
+Status: active
+Logging: on (low)
+Default: deny (incoming), allow (outgoing), disabled (routed)
+New profiles: skip
+
+To                         Action      From
+--                         ------      ----
+22/tcp                     ALLOW IN    Anywhere
+@@ fail2ban
+service: active
+jails: sshd
+@@ ports
+tcp 0.0.0.0:22 users:((\"sshd\",pid=812,fd=3))
+tcp 127.0.0.1:18789 users:((\"node\",pid=1200,fd=21))
+udp 127.0.0.53%lo:53 users:((\"systemd-resolve\",pid=5,fd=13))
+@@ files
+700 openclaw /home/openclaw/.openclaw
+600 openclaw /home/openclaw/.openclaw/.env
+600 openclaw /home/openclaw/.openclaw/openclaw.json
+@@ gateway
+config: ok
+mode: token
+token_length: 64
+token_distinct: 16
+password_length: 0
+password_distinct: 0
+bind: loopback
+@@ docker
+socket: none
+group:
+";
+
+    fn status_of<'a>(report: &'a ScanReport, id: &str) -> (&'a CheckStatus, Severity) {
+        let check = report
+            .checks
+            .iter()
+            .find(|c| c.id == id)
+            .unwrap_or_else(|| panic!("missing check {id}"));
+        (&check.status, check.severity)
+    }
+
+    #[test]
+    fn high_risk_host_fails_the_expected_checks() {
+        let report = evaluate("fallback", HIGH_RISK);
+        assert_eq!(report.host, "test-host");
+        let failed: Vec<&str> = report.failed().map(|c| c.id.as_str()).collect();
+        assert_eq!(
+            failed,
+            [
+                "sshd_permit_root",
+                "sshd_password_auth",
+                "ufw_enabled",
+                "fail2ban",
+                "listening_ports",
+                "gateway_listen",
+                "openclaw_dir_mode",
+                "env_file_mode",
+                "credentials_mode",
+                "gateway_token_strength",
+                "gateway_bind",
+                "docker_socket",
+                "docker_group",
+                "docker_socket_mounts",
+                "docker_tcp_api",
+            ]
+        );
+        assert_eq!(
+            status_of(&report, "docker_tcp_api"),
+            (&CheckStatus::Fail, Severity::Critical)
+        );
+        assert_eq!(
+            status_of(&report, "gateway_bind"),
+            (&CheckStatus::Fail, Severity::Medium)
+        );
+        let ports = report
+            .checks
+            .iter()
+            .find(|c| c.id == "listening_ports")
+            .unwrap();
+        assert_eq!(ports.value, "5432/tcp on :: (postgres)");
+        assert_eq!(
+            report.secrets,
+            "/home/openclaw/.openclaw/.env\n/home/openclaw/.openclaw/credentials/whatsapp/creds.json"
+        );
+        assert_eq!(report.risk.score, 100);
+        assert_eq!(report.risk.level, Severity::Critical);
+    }
+
+    #[test]
+    fn hardened_host_passes() {
+        let report = evaluate("fallback", HARDENED);
+        let failed: Vec<&str> = report.failed().map(|c| c.id.as_str()).collect();
+        assert!(failed.is_empty(), "unexpected failures: {failed:?}");
+        assert_eq!(report.risk.score, 0);
+        assert_eq!(report.risk.level, Severity::Info);
+        assert!(report.secrets.is_empty());
+        assert!(report.checks.iter().all(|c| c.status == CheckStatus::Pass));
+    }
+
+    #[test]
+    fn missing_sections_are_unknown_rather_than_passing() {
+        let report = evaluate("10.0.0.5", "");
+        assert_eq!(report.host, "10.0.0.5");
+        assert_eq!(
+            status_of(&report, "sshd_permit_root").0,
+            &CheckStatus::Unknown
+        );
+        assert_eq!(status_of(&report, "ufw_enabled").0, &CheckStatus::Unknown);
+        assert_eq!(
+            status_of(&report, "gateway_auth_mode").0,
+            &CheckStatus::Unknown
+        );
+        assert_eq!(report.risk.failed, 0);
+    }
+
+    #[test]
+    fn report_keeps_the_fixture_shape() {
+        let fixture: Value =
+            serde_json::from_str(include_str!("../../../fixtures/sample_high_risk.json")).unwrap();
+        let report = serde_json::to_value(evaluate("fallback", HIGH_RISK)).unwrap();
+        for key in ["host", "secrets"] {
+            assert!(fixture[key].is_string() && report[key].is_string(), "{key}");
+        }
+        let expected = &fixture["checks"][0];
+        let check = report["checks"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|c| c["id"] == expected["id"])
+            .unwrap();
+        assert_eq!(check["value"], expected["value"]);
+    }
+
+    #[test]
+    fn sarif_lists_failed_checks_as_results() {
+        let report = evaluate("fallback", HIGH_RISK);
+        let sarif = to_sarif(&report);
+        assert_eq!(sarif["version"], "2.1.0");
+        let run = &sarif["runs"][0];
+        assert_eq!(run["tool"]["driver"]["name"], "OpenClawScanner");
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results.len(), report.risk.failed);
+        let root = &results[0];
+        assert_eq!(root["ruleId"], "sshd_permit_root");
+        assert_eq!(root["level"], "error");
+        assert_eq!(
+            root["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+            "etc/ssh/sshd_config"
+        );
+        assert_eq!(
+            run["tool"]["driver"]["rules"][0]["properties"]["security-severity"],
+            "8.0"
+        );
+    }
+}
@@ -0,0 +1,147 @@
+//! Minimal HTTP/1.1 server for the API stand-ins used in tests.
+//!
+//! `serve` accepts connections on a loopback port, reads each request
+//! (honouring `Content-Length`) and writes the `Response` returned by the
+//! handler, so a stand-in only has to supply its routing. Enabled for other
+//! crates with the `test-util` feature.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+
+/// One request as received by a stand-in.
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    /// Raw query string without the `?` (empty when there is none).
+    pub query: String,
+    /// Header names lower-cased, values trimmed, in request order.
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    /// The first header called `name` (case-insensitive).
+    pub fn header(&self, name: &str) -> Option<&str> {
+        let name = name.to_ascii_lowercase();
+        self.headers
+            .iter()
+            .find(|(k, _)| *k == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// The body parsed as JSON, or `Value::Null` when it is empty or invalid.
+    pub fn json(&self) -> serde_json::Value {
+        serde_json::from_slice(&self.body).unwrap_or_default()
+    }
+}
+
+/// The response a stand-in sends back; `Content-Length` is added on write.
+#[derive(Debug, Clone)]
+pub struct Response {
+    /// Status line after `HTTP/1.1`, e.g. `200 OK`.
+    pub status: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    pub fn new(status: &str, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status: status.to_string(),
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+
+    /// A JSON response; `Value::Null` sends an empty body.
+    pub fn json(status: &str, value: &serde_json::Value) -> Self {
+        let body = if value.is_null() {
+            String::new()
+        } else {
+            value.to_string()
+        };
+        Self::new(status, body).header("Content-Type", "application/json")
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+/// Read one request from `sock`: the head up to the blank line, then
+/// `Content-Length` bytes of body.
+pub async fn read_request<S: AsyncRead + Unpin>(sock: &mut S) -> Request {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let header_end = loop {
+        let n = sock.read(&mut chunk).await.unwrap();
+        assert!(n > 0, "connection closed before the request head ended");
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+    let head = String::from_utf8_lossy(&buf[..header_end]).to_string();
+    let mut lines = head.lines();
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default().to_string();
+    let target = request_line.next().unwrap_or_default();
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(k, v)| (k.trim().to_ascii_lowercase(), v.trim().to_string()))
+        .collect();
+    let content_length: usize = headers
+        .iter()
+        .find(|(k, _)| k == "content-length")
+        .map(|(_, v)| v.parse().unwrap())
+        .unwrap_or(0);
+    while buf.len() < header_end + content_length {
+        let n = sock.read(&mut chunk).await.unwrap();
+        assert!(n > 0, "connection closed before the request body ended");
+        buf.extend_from_slice(&chunk[..n]);
+    }
+    Request {
+        method,
+        path: path.to_string(),
+        query: query.to_string(),
+        headers,
+        body: buf[header_end..header_end + content_length].to_vec(),
+    }
+}
+
+/// Serve `handler` on a loopback port until the test ends and return its address.
+pub async fn serve<F>(handler: F) -> SocketAddr
+where
+    F: Fn(Request) -> Response + Send + Sync + 'static,
+{
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let handler = Arc::new(handler);
+    tokio::spawn(async move {
+        loop {
+            let Ok((mut sock, _)) = listener.accept().await else {
+                return;
+            };
+            let handler = handler.clone();
+            tokio::spawn(async move {
+                let response = handler(read_request(&mut sock).await);
+                let mut head = format!("HTTP/1.1 {}\r\n", response.status);
+                for (name, value) in &response.headers {
+                    head.push_str(&format!("{name}: {value}\r\n"));
+                }
+                head.push_str(&format!(
+                    "Content-Length: {}\r\nConnection: close\r\n\r\n",
+                    response.body.len()
+                ));
+                sock.write_all(head.as_bytes()).await.unwrap();
+                sock.write_all(&response.body).await.unwrap();
+                let _ = sock.shutdown().await;
+            });
+        }
+    });
+    addr
+}
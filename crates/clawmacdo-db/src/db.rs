@@ -2,6 +2,7 @@ use anyhow::{Context, Result};
 use clawmacdo_core::config;
 use rusqlite::Connection;
 use serde::Serialize;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 /// Return the SQLite database path under the configured clawmacdo state dir.
@@ -57,6 +58,11 @@ pub fn init_db() -> Result<Connection> {
     )
     .context("Failed to create deploy_steps table")?;
 
+    create_wiki_tables(&conn)?;
+    create_tag_tables(&conn)?;
+    create_version_tables(&conn)?;
+    create_baked_image_tables(&conn)?;
+
     Ok(conn)
 }
 
@@ -134,6 +140,14 @@ pub fn delete_deployment(conn: &Connection, id: &str) -> Result<bool> {
         "DELETE FROM deployments WHERE id = ?1",
         rusqlite::params![id],
     )?;
+    conn.execute(
+        "DELETE FROM deployment_tags WHERE deploy_id = ?1",
+        rusqlite::params![id],
+    )?;
+    conn.execute(
+        "DELETE FROM openclaw_versions WHERE deploy_id = ?1",
+        rusqlite::params![id],
+    )?;
     Ok(changed > 0)
 }
 
@@ -366,6 +380,447 @@ pub fn find_deployment_by_query(conn: &Connection, query: &str) -> Result<Option
     }
 }
 
+// ── Deploy history statistics ──────────────────────────────────────────────
+
+#[derive(Debug, Serialize)]
+pub struct OutcomeCountRow {
+    pub provider: String,
+    pub status: String,
+    pub count: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StepDurationRow {
+    pub provider: String,
+    pub step_number: i32,
+    pub label: String,
+    pub seconds: f64,
+}
+
+/// Count deployments grouped by provider and final status.
+pub fn deployment_outcome_counts(conn: &Connection) -> Result<Vec<OutcomeCountRow>> {
+    let mut stmt = conn.prepare(
+        "SELECT COALESCE(provider, 'unknown'), status, COUNT(*)
+         FROM deployments GROUP BY 1, 2 ORDER BY 1, 2",
+    )?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(OutcomeCountRow {
+                provider: row.get(0)?,
+                status: row.get(1)?,
+                count: row.get::<_, i64>(2)? as u64,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(rows)
+}
+
+/// Durations of every completed deploy step, derived from the step timestamps.
+pub fn completed_step_durations(conn: &Connection) -> Result<Vec<StepDurationRow>> {
+    let mut stmt = conn.prepare(
+        "SELECT COALESCE(d.provider, 'unknown'), s.step_number, s.label,
+                (julianday(s.completed_at) - julianday(s.started_at)) * 86400.0
+         FROM deploy_steps s LEFT JOIN deployments d ON d.id = s.deploy_id
+         WHERE s.status = 'completed' AND s.completed_at IS NOT NULL
+         ORDER BY s.step_number",
+    )?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(StepDurationRow {
+                provider: row.get(0)?,
+                step_number: row.get(1)?,
+                label: row.get(2)?,
+                seconds: row.get::<_, Option<f64>>(3)?.unwrap_or(0.0).max(0.0),
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(rows)
+}
+
+// ── Deployment tags ────────────────────────────────────────────────────────
+
+/// Create the tag table used to group deployments for fleet commands.
+fn create_tag_tables(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS deployment_tags (
+            deploy_id  TEXT NOT NULL,
+            tag        TEXT NOT NULL,
+            PRIMARY KEY (deploy_id, tag)
+        );",
+    )
+    .context("Failed to create deployment_tags table")?;
+    Ok(())
+}
+
+/// Add and remove tags on a deployment and return its resulting tags.
+pub fn update_deployment_tags(
+    conn: &Connection,
+    deploy_id: &str,
+    add: &[String],
+    remove: &[String],
+) -> Result<Vec<String>> {
+    for tag in add {
+        conn.execute(
+            "INSERT OR IGNORE INTO deployment_tags (deploy_id, tag) VALUES (?1, ?2)",
+            rusqlite::params![deploy_id, tag],
+        )?;
+    }
+    for tag in remove {
+        conn.execute(
+            "DELETE FROM deployment_tags WHERE deploy_id = ?1 AND tag = ?2",
+            rusqlite::params![deploy_id, tag],
+        )?;
+    }
+    Ok(all_deployment_tags(conn)?
+        .remove(deploy_id)
+        .unwrap_or_default())
+}
+
+/// All tags keyed by deployment ID, each list sorted.
+pub fn all_deployment_tags(conn: &Connection) -> Result<BTreeMap<String, Vec<String>>> {
+    let mut stmt =
+        conn.prepare("SELECT deploy_id, tag FROM deployment_tags ORDER BY deploy_id, tag")?;
+    let mut tags: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get(1)?)))?;
+    for row in rows {
+        let (id, tag) = row?;
+        tags.entry(id).or_default().push(tag);
+    }
+    Ok(tags)
+}
+
+// ── OpenClaw versions ──────────────────────────────────────────────────────
+
+/// Create the table holding the last known OpenClaw version per deployment.
+fn create_version_tables(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS openclaw_versions (
+            deploy_id         TEXT PRIMARY KEY,
+            version           TEXT,
+            previous_version  TEXT,
+            last_result       TEXT NOT NULL,
+            checked_at        TEXT NOT NULL
+        );",
+    )
+    .context("Failed to create openclaw_versions table")?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OpenclawVersionRow {
+    pub deploy_id: String,
+    /// Installed version, `None` when it could not be read.
+    pub version: Option<String>,
+    /// Version installed before the last upgrade.
+    pub previous_version: Option<String>,
+    /// `probed`, `upgraded`, `reverted` or `revert_failed`.
+    pub last_result: String,
+    pub checked_at: String,
+}
+
+/// Record the OpenClaw version found on (or installed to) a deployment.
+/// `previous_version` keeps its stored value when `None` is passed.
+pub fn record_openclaw_version(
+    conn: &Connection,
+    deploy_id: &str,
+    version: Option<&str>,
+    previous_version: Option<&str>,
+    last_result: &str,
+) -> Result<()> {
+    conn.execute(
+        "INSERT INTO openclaw_versions (deploy_id, version, previous_version, last_result, checked_at)
+         VALUES (?1, ?2, ?3, ?4, datetime('now'))
+         ON CONFLICT(deploy_id) DO UPDATE SET
+            version = excluded.version,
+            previous_version = COALESCE(excluded.previous_version, openclaw_versions.previous_version),
+            last_result = excluded.last_result,
+            checked_at = excluded.checked_at",
+        rusqlite::params![deploy_id, version, previous_version, last_result],
+    )?;
+    Ok(())
+}
+
+/// All recorded OpenClaw versions keyed by deployment ID.
+pub fn openclaw_versions(conn: &Connection) -> Result<BTreeMap<String, OpenclawVersionRow>> {
+    let mut stmt = conn.prepare(
+        "SELECT deploy_id, version, previous_version, last_result, checked_at FROM openclaw_versions",
+    )?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(OpenclawVersionRow {
+                deploy_id: row.get(0)?,
+                version: row.get(1)?,
+                previous_version: row.get(2)?,
+                last_result: row.get(3)?,
+                checked_at: row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(rows
+        .into_iter()
+        .map(|row| (row.deploy_id.clone(), row))
+        .collect())
+}
+
+// ── Baked images ───────────────────────────────────────────────────────────
+
+/// Create the table of images built by `bake`.
+fn create_baked_image_tables(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS baked_images (
+            name              TEXT PRIMARY KEY,
+            provider          TEXT NOT NULL,
+            region            TEXT NOT NULL,
+            image_id          TEXT NOT NULL,
+            openclaw_version  TEXT NOT NULL,
+            created_at        TEXT NOT NULL
+        );",
+    )
+    .context("Failed to create baked_images table")?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BakedImageRow {
+    pub name: String,
+    pub provider: String,
+    pub region: String,
+    /// Provider image reference: DigitalOcean snapshot ID, Lightsail
+    /// snapshot name or BytePlus image ID.
+    pub image_id: String,
+    pub openclaw_version: String,
+    pub created_at: String,
+}
+
+/// Record a finished bake, replacing any earlier image with the same name.
+pub fn insert_baked_image(
+    conn: &Connection,
+    name: &str,
+    provider: &str,
+    region: &str,
+    image_id: &str,
+    openclaw_version: &str,
+) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO baked_images (name, provider, region, image_id, openclaw_version, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, datetime('now'))",
+        rusqlite::params![name, provider, region, image_id, openclaw_version],
+    )?;
+    Ok(())
+}
+
+fn baked_image_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<BakedImageRow> {
+    Ok(BakedImageRow {
+        name: row.get(0)?,
+        provider: row.get(1)?,
+        region: row.get(2)?,
+        image_id: row.get(3)?,
+        openclaw_version: row.get(4)?,
+        created_at: row.get(5)?,
+    })
+}
+
+/// Find a baked image by name or provider image ID.
+pub fn find_baked_image(conn: &Connection, query: &str) -> Result<Option<BakedImageRow>> {
+    let mut stmt = conn.prepare(
+        "SELECT name, provider, region, image_id, openclaw_version, created_at
+         FROM baked_images WHERE name = ?1 OR image_id = ?1
+         ORDER BY created_at DESC LIMIT 1",
+    )?;
+    let mut rows = stmt.query_map([query], baked_image_from_row)?;
+    Ok(rows.next().transpose()?)
+}
+
+/// All baked images, newest first.
+pub fn list_baked_images(conn: &Connection) -> Result<Vec<BakedImageRow>> {
+    let mut stmt = conn.prepare(
+        "SELECT name, provider, region, image_id, openclaw_version, created_at
+         FROM baked_images ORDER BY created_at DESC, name",
+    )?;
+    let rows = stmt
+        .query_map([], baked_image_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(rows)
+}
+
+// ── Wiki full-text index ───────────────────────────────────────────────────
+
+/// Create the wiki page table and its FTS5 index. Pages are keyed by the
+/// deploy ID, agent and project they were indexed from.
+fn create_wiki_tables(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS wiki_pages (
+            id          INTEGER PRIMARY KEY AUTOINCREMENT,
+            instance    TEXT NOT NULL,
+            agent       TEXT NOT NULL,
+            project     TEXT NOT NULL,
+            path        TEXT NOT NULL,
+            sha256      TEXT NOT NULL,
+            title       TEXT NOT NULL,
+            indexed_at  TEXT NOT NULL,
+            UNIQUE(instance, agent, project, path)
+        );
+        CREATE VIRTUAL TABLE IF NOT EXISTS wiki_fts USING fts5(
+            title, body, tokenize = 'porter unicode61'
+        );",
+    )
+    .context("Failed to create wiki index tables")?;
+    Ok(())
+}
+
+#[derive(Debug, Serialize)]
+pub struct WikiSearchRow {
+    pub path: String,
+    pub title: String,
+    pub snippet: String,
+    pub score: f64,
+}
+
+/// Indexed sha256 per page path for one wiki project.
+pub fn wiki_page_hashes(
+    conn: &Connection,
+    instance: &str,
+    agent: &str,
+    project: &str,
+) -> Result<BTreeMap<String, String>> {
+    let mut stmt = conn.prepare(
+        "SELECT path, sha256 FROM wiki_pages WHERE instance = ?1 AND agent = ?2 AND project = ?3",
+    )?;
+    let rows = stmt
+        .query_map(rusqlite::params![instance, agent, project], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?
+        .collect::<Result<BTreeMap<_, _>, _>>()?;
+    Ok(rows)
+}
+
+/// Remove a page from the wiki index.
+pub fn delete_wiki_page(
+    conn: &Connection,
+    instance: &str,
+    agent: &str,
+    project: &str,
+    path: &str,
+) -> Result<()> {
+    conn.execute(
+        "DELETE FROM wiki_fts WHERE rowid IN (
+            SELECT id FROM wiki_pages WHERE instance = ?1 AND agent = ?2 AND project = ?3 AND path = ?4
+        )",
+        rusqlite::params![instance, agent, project, path],
+    )?;
+    conn.execute(
+        "DELETE FROM wiki_pages WHERE instance = ?1 AND agent = ?2 AND project = ?3 AND path = ?4",
+        rusqlite::params![instance, agent, project, path],
+    )
+    .context("Failed to delete wiki page from index")?;
+    Ok(())
+}
+
+/// Insert or replace a page in the wiki index.
+#[allow(clippy::too_many_arguments)]
+pub fn upsert_wiki_page(
+    conn: &Connection,
+    instance: &str,
+    agent: &str,
+    project: &str,
+    path: &str,
+    sha256: &str,
+    title: &str,
+    body: &str,
+) -> Result<()> {
+    delete_wiki_page(conn, instance, agent, project, path)?;
+    conn.execute(
+        "INSERT INTO wiki_pages (instance, agent, project, path, sha256, title, indexed_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, datetime('now'))",
+        rusqlite::params![instance, agent, project, path, sha256, title],
+    )
+    .context("Failed to insert wiki page")?;
+    conn.execute(
+        "INSERT INTO wiki_fts (rowid, title, body) VALUES (?1, ?2, ?3)",
+        rusqlite::params![conn.last_insert_rowid(), title, body],
+    )
+    .context("Failed to index wiki page")?;
+    Ok(())
+}
+
+/// Indexed page bodies for one wiki project, ordered by path.
+pub fn wiki_page_bodies(
+    conn: &Connection,
+    instance: &str,
+    agent: &str,
+    project: &str,
+) -> Result<Vec<(String, String)>> {
+    let mut stmt = conn.prepare(
+        "SELECT p.path, f.body FROM wiki_pages p JOIN wiki_fts f ON f.rowid = p.id
+         WHERE p.instance = ?1 AND p.agent = ?2 AND p.project = ?3 ORDER BY p.path",
+    )?;
+    let rows = stmt
+        .query_map(rusqlite::params![instance, agent, project], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(rows)
+}
+
+/// Turn free text into an FTS5 query: every word must match, quotes keep
+/// punctuation from being read as query syntax, and a trailing `*` is kept
+/// as a prefix search.
+pub fn wiki_fts_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .filter_map(|word| {
+            let prefix = word.len() > 1 && word.ends_with('*');
+            let word = word.trim_end_matches('*').replace('"', "\"\"");
+            if word.is_empty() {
+                return None;
+            }
+            Some(if prefix {
+                format!("\"{word}\"*")
+            } else {
+                format!("\"{word}\"")
+            })
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Rank pages of one wiki project against a free-text query with BM25,
+/// weighting title matches above body matches.
+pub fn search_wiki_pages(
+    conn: &Connection,
+    instance: &str,
+    agent: &str,
+    project: &str,
+    query: &str,
+    limit: u32,
+) -> Result<Vec<WikiSearchRow>> {
+    let fts_query = wiki_fts_query(query);
+    if fts_query.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut stmt = conn.prepare(
+        "SELECT p.path, p.title, snippet(wiki_fts, 1, '**', '**', '…', 16), bm25(wiki_fts, 5.0, 1.0)
+         FROM wiki_fts f JOIN wiki_pages p ON p.id = f.rowid
+         WHERE wiki_fts MATCH ?1 AND p.instance = ?2 AND p.agent = ?3 AND p.project = ?4
+         ORDER BY bm25(wiki_fts, 5.0, 1.0) LIMIT ?5",
+    )?;
+    let rows = stmt
+        .query_map(
+            rusqlite::params![fts_query, instance, agent, project, limit],
+            |row| {
+                Ok(WikiSearchRow {
+                    path: row.get(0)?,
+                    title: row.get(1)?,
+                    snippet: row.get(2)?,
+                    score: -row.get::<_, f64>(3)?,
+                })
+            },
+        )?
+        .collect::<Result<Vec<_>, _>>()
+        .context("Wiki search failed")?;
+    Ok(rows)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -399,6 +854,10 @@ mod tests {
             );",
         )
         .unwrap();
+        create_wiki_tables(&conn).unwrap();
+        create_tag_tables(&conn).unwrap();
+        create_version_tables(&conn).unwrap();
+        create_baked_image_tables(&conn).unwrap();
         conn
     }
 
@@ -474,4 +933,150 @@ mod tests {
         assert!(delete_deployment(&conn, "deploy-1").unwrap());
         assert!(get_deployment_by_id(&conn, "deploy-1").unwrap().is_none());
     }
+
+    #[test]
+    fn stats_queries_group_outcomes_and_measure_steps() {
+        let conn = test_conn();
+        insert_sample_deployment(&conn, "deploy-1", "openclaw-one", "203.0.113.10");
+        insert_deployment(
+            &conn,
+            "deploy-2",
+            "Jane Doe",
+            "jane@example.com",
+            "digitalocean",
+            "sgp1",
+            "s-2vcpu-4gb",
+            "openclaw-two",
+        )
+        .unwrap();
+        update_deployment_status(&conn, "deploy-2", "failed", None, None).unwrap();
+
+        conn.execute(
+            "INSERT INTO deploy_steps (deploy_id, step_number, total_steps, label, status, started_at, completed_at)
+             VALUES ('deploy-1', 9, 16, 'Creating openclaw user', 'completed', '2026-01-01 00:00:00', '2026-01-01 00:01:30')",
+            [],
+        )
+        .unwrap();
+
+        let counts = deployment_outcome_counts(&conn).unwrap();
+        assert_eq!(counts.len(), 2);
+        assert!(counts
+            .iter()
+            .any(|row| row.status == "failed" && row.count == 1));
+
+        let steps = completed_step_durations(&conn).unwrap();
+        assert_eq!(steps.len(), 1);
+        assert_eq!(steps[0].provider, "digitalocean");
+        assert!((steps[0].seconds - 90.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn deployment_tags_are_added_removed_and_grouped() {
+        let conn = test_conn();
+        let tags = |list: &[&str]| list.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            update_deployment_tags(&conn, "a", &tags(&["prod", "sg", "prod"]), &[]).unwrap(),
+            ["prod", "sg"]
+        );
+        update_deployment_tags(&conn, "b", &tags(&["canary"]), &[]).unwrap();
+        assert_eq!(
+            update_deployment_tags(&conn, "a", &[], &tags(&["sg", "missing"])).unwrap(),
+            ["prod"]
+        );
+        let all = all_deployment_tags(&conn).unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all["b"], ["canary"]);
+    }
+
+    #[test]
+    fn openclaw_versions_keep_previous_version() {
+        let conn = test_conn();
+        record_openclaw_version(&conn, "a", Some("2026.3.2"), None, "probed").unwrap();
+        record_openclaw_version(&conn, "a", Some("2026.3.20"), Some("2026.3.2"), "upgraded")
+            .unwrap();
+        record_openclaw_version(&conn, "a", Some("2026.3.20"), None, "probed").unwrap();
+        let rows = openclaw_versions(&conn).unwrap();
+        let row = &rows["a"];
+        assert_eq!(row.version.as_deref(), Some("2026.3.20"));
+        assert_eq!(row.previous_version.as_deref(), Some("2026.3.2"));
+        assert_eq!(row.last_result, "probed");
+    }
+
+    #[test]
+    fn baked_images_are_found_by_name_or_image_id() {
+        let conn = test_conn();
+        insert_baked_image(
+            &conn,
+            "oc-2026.3.22",
+            "digitalocean",
+            "sgp1",
+            "123",
+            "2026.3.22",
+        )
+        .unwrap();
+        insert_baked_image(
+            &conn,
+            "oc-bp",
+            "byteplus",
+            "ap-southeast-1",
+            "image-x",
+            "2026.3.22",
+        )
+        .unwrap();
+
+        let by_name = find_baked_image(&conn, "oc-2026.3.22").unwrap().unwrap();
+        assert_eq!(by_name.image_id, "123");
+        let by_id = find_baked_image(&conn, "image-x").unwrap().unwrap();
+        assert_eq!(by_id.provider, "byteplus");
+        assert!(find_baked_image(&conn, "missing").unwrap().is_none());
+        assert_eq!(list_baked_images(&conn).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn wiki_fts_query_quotes_words_and_keeps_prefixes() {
+        assert_eq!(wiki_fts_query("gateway token"), "\"gateway\" \"token\"");
+        assert_eq!(wiki_fts_query("deploy* a\"b"), "\"deploy\"* \"a\"\"b\"");
+        assert_eq!(wiki_fts_query("  * "), "");
+    }
+
+    #[test]
+    fn wiki_index_ranks_and_replaces_pages() {
+        let conn = test_conn();
+        let scope = ("203.0.113.10", "main", "llm_wiki");
+        let upsert = |path: &str, sha: &str, title: &str, body: &str| {
+            upsert_wiki_page(&conn, scope.0, scope.1, scope.2, path, sha, title, body).unwrap()
+        };
+        upsert(
+            "llm_wiki/a.md",
+            "a1",
+            "Gateway",
+            "How the gateway token rotates.",
+        );
+        upsert(
+            "llm_wiki/b.md",
+            "b1",
+            "Backups",
+            "Backups mention the gateway once.",
+        );
+        upsert_wiki_page(
+            &conn, "other", "main", "llm_wiki", "x.md", "x", "Gateway", "gateway",
+        )
+        .unwrap();
+
+        let rows = search_wiki_pages(&conn, scope.0, scope.1, scope.2, "gateway", 10).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].path, "llm_wiki/a.md");
+        assert!(rows[1].snippet.contains("**gateway**"));
+
+        upsert("llm_wiki/a.md", "a2", "Tokens", "Nothing about it now.");
+        delete_wiki_page(&conn, scope.0, scope.1, scope.2, "llm_wiki/b.md").unwrap();
+        assert!(
+            search_wiki_pages(&conn, scope.0, scope.1, scope.2, "gateway", 10)
+                .unwrap()
+                .is_empty()
+        );
+        let hashes = wiki_page_hashes(&conn, scope.0, scope.1, scope.2).unwrap();
+        assert_eq!(hashes.get("llm_wiki/a.md").map(String::as_str), Some("a2"));
+        assert_eq!(hashes.len(), 1);
+    }
 }
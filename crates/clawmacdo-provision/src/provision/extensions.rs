@@ -0,0 +1,589 @@
+//! User-supplied provisioning steps.
+//!
+//! An extensions directory holds one JSON file per step, for example
+//! `10-corp-ca.json`:
+//!
+//! ```json
+//! {
+//!   "name": "corp-ca",
+//!   "phase": "pre-user",
+//!   "run_as": "root",
+//!   "files": [{ "src": "corp-ca.crt", "dest": "/usr/local/share/ca-certificates/corp-ca.crt" }],
+//!   "run": "update-ca-certificates",
+//!   "timeout_secs": 120
+//! }
+//! ```
+//!
+//! Steps run in file-name order within their phase. `script` names a file
+//! next to the JSON file and `run` is an inline script; either runs under
+//! bash as `run_as` after `files` are uploaded.
+
+use super::commands::{ssh_as_openclaw_with_user_async, ssh_root_as_async};
+use super::{StepDoneFn, StepStartFn};
+use clawmacdo_core::error::AppError;
+use clawmacdo_ssh as ssh;
+use clawmacdo_ui::progress;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tokio::sync::mpsc;
+
+/// Extension steps are recorded after the 16 built-in deploy steps.
+pub const FIRST_EXTENSION_STEP: i32 = 17;
+
+const DEFAULT_TIMEOUT_SECS: u64 = 600;
+const MAX_TIMEOUT_SECS: u64 = 3600;
+
+/// Where in the deploy flow an extension step runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Phase {
+    /// Before step 9 creates the `openclaw` user.
+    PreUser,
+    /// After step 13 installs OpenClaw.
+    PostOpenclaw,
+    /// After step 15 starts the gateway.
+    PostGateway,
+}
+
+impl std::fmt::Display for Phase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Phase::PreUser => "pre-user",
+            Phase::PostOpenclaw => "post-openclaw",
+            Phase::PostGateway => "post-gateway",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RunAs {
+    #[default]
+    Root,
+    Openclaw,
+}
+
+impl RunAs {
+    fn user(self) -> &'static str {
+        match self {
+            RunAs::Root => "root",
+            RunAs::Openclaw => "openclaw",
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StepFile {
+    name: String,
+    phase: Phase,
+    #[serde(default)]
+    run_as: RunAs,
+    script: Option<String>,
+    run: Option<String>,
+    #[serde(default)]
+    files: Vec<FileEntry>,
+    timeout_secs: Option<u64>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileEntry {
+    src: String,
+    dest: String,
+    /// Octal permission string, e.g. "0644".
+    mode: Option<String>,
+}
+
+/// A file uploaded before the step's script runs, owned by the run-as user.
+#[derive(Debug, Clone)]
+pub struct ExtensionFile {
+    pub dest: String,
+    pub mode: u32,
+    pub contents: Vec<u8>,
+}
+
+/// A validated extension step with its script and files already read.
+#[derive(Debug, Clone)]
+pub struct ExtensionStep {
+    pub name: String,
+    pub phase: Phase,
+    pub run_as: RunAs,
+    pub script: Option<String>,
+    pub files: Vec<ExtensionFile>,
+    pub timeout_secs: u64,
+    /// The JSON file the step was declared in.
+    pub source: PathBuf,
+}
+
+fn invalid(path: &Path, message: impl std::fmt::Display) -> AppError {
+    AppError::Provision {
+        phase: "extensions".into(),
+        message: format!("{}: {message}", path.display()),
+    }
+}
+
+fn parse_mode(path: &Path, mode: Option<&str>) -> Result<u32, AppError> {
+    let Some(mode) = mode else {
+        return Ok(0o644);
+    };
+    match u32::from_str_radix(mode.trim_start_matches("0o"), 8) {
+        Ok(m) if m <= 0o7777 => Ok(m),
+        _ => Err(invalid(path, format!("invalid file mode '{mode}'"))),
+    }
+}
+
+fn load_step(dir: &Path, path: &Path) -> Result<ExtensionStep, AppError> {
+    let raw = std::fs::read_to_string(path)?;
+    let file: StepFile = serde_json::from_str(&raw).map_err(|e| invalid(path, e))?;
+
+    let name = file.name.trim().to_string();
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c))
+    {
+        return Err(invalid(
+            path,
+            "name must be letters, digits, '-', '_' or '.'",
+        ));
+    }
+    if file.phase == Phase::PreUser && file.run_as == RunAs::Openclaw {
+        return Err(invalid(
+            path,
+            "pre-user steps must run as root; the openclaw user does not exist yet",
+        ));
+    }
+    let script = match (file.script, file.run) {
+        (Some(_), Some(_)) => return Err(invalid(path, "set either script or run, not both")),
+        (Some(script), None) => Some(
+            std::fs::read_to_string(dir.join(&script))
+                .map_err(|e| invalid(path, format!("cannot read script '{script}': {e}")))?,
+        ),
+        (None, run) => run,
+    };
+    if script.is_none() && file.files.is_empty() {
+        return Err(invalid(path, "nothing to do: set script, run or files"));
+    }
+    let timeout_secs = file.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS);
+    if timeout_secs == 0 || timeout_secs > MAX_TIMEOUT_SECS {
+        return Err(invalid(
+            path,
+            format!("timeout_secs must be between 1 and {MAX_TIMEOUT_SECS}"),
+        ));
+    }
+
+    let mut files = Vec::with_capacity(file.files.len());
+    for entry in file.files {
+        if !entry.dest.starts_with('/') || entry.dest.contains('\'') {
+            return Err(invalid(
+                path,
+                format!("file dest '{}' must be an absolute path", entry.dest),
+            ));
+        }
+        let contents = std::fs::read(dir.join(&entry.src))
+            .map_err(|e| invalid(path, format!("cannot read file '{}': {e}", entry.src)))?;
+        files.push(ExtensionFile {
+            mode: parse_mode(path, entry.mode.as_deref())?,
+            dest: entry.dest,
+            contents,
+        });
+    }
+
+    Ok(ExtensionStep {
+        name,
+        phase: file.phase,
+        run_as: file.run_as,
+        script,
+        files,
+        timeout_secs,
+        source: path.to_path_buf(),
+    })
+}
+
+/// Load every `*.json` step in `dir`, ordered by phase and then file name.
+///
+/// Fails on the first invalid step so mistakes surface before any cloud
+/// resources are created.
+pub fn load_dir(dir: &Path) -> Result<Vec<ExtensionStep>, AppError> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map_err(|e| invalid(dir, e))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("json"))
+        .collect();
+    paths.sort();
+
+    let mut steps = Vec::with_capacity(paths.len());
+    for path in &paths {
+        let step = load_step(dir, path)?;
+        if steps.iter().any(|s: &ExtensionStep| s.name == step.name) {
+            return Err(invalid(
+                path,
+                format!("duplicate step name '{}'", step.name),
+            ));
+        }
+        steps.push(step);
+    }
+    steps.sort_by_key(|s| s.phase);
+    Ok(steps)
+}
+
+/// The script that runs on the host: the step's own script under `timeout`,
+/// with the step name and phase exported.
+fn wrap_script(step: &ExtensionStep, remote_path: &str) -> String {
+    format!(
+        r#"export CLAWMACDO_EXTENSION='{name}' CLAWMACDO_PHASE='{phase}'
+rc=0
+timeout --kill-after=10 {secs} /bin/bash '{remote_path}' || rc=$?
+rm -f '{remote_path}'
+if [ "$rc" -eq 124 ] || [ "$rc" -eq 137 ]; then
+  echo "timed out after {secs}s" >&2
+fi
+exit $rc"#,
+        name = step.name,
+        phase = step.phase,
+        secs = step.timeout_secs,
+    )
+}
+
+async fn upload(
+    ip: &str,
+    key: &Path,
+    ssh_user: &str,
+    data: Vec<u8>,
+    remote_path: String,
+) -> Result<(), AppError> {
+    let ip = ip.to_string();
+    let key = key.to_path_buf();
+    let ssh_user = ssh_user.to_string();
+    tokio::task::spawn_blocking(move || {
+        ssh::scp_upload_bytes(&ip, &key, &data, &remote_path, 0o600, &ssh_user)
+    })
+    .await
+    .map_err(|e| AppError::Ssh(format!("spawn_blocking join: {e}")))?
+}
+
+async fn run_step(
+    ip: &str,
+    key: &Path,
+    ssh_user: &str,
+    step: &ExtensionStep,
+    tx: &Option<mpsc::UnboundedSender<String>>,
+) -> Result<(), AppError> {
+    let owner = step.run_as.user();
+    for (i, file) in step.files.iter().enumerate() {
+        let tmp = format!("/tmp/.clawmacdo-ext-{}-{i}", step.name);
+        upload(ip, key, ssh_user, file.contents.clone(), tmp.clone()).await?;
+        ssh_root_as_async(
+            ip,
+            key,
+            &format!(
+                "install -D -m {mode:o} -o {owner} -g {owner} '{tmp}' '{dest}' && rm -f '{tmp}'",
+                mode = file.mode,
+                dest = file.dest,
+            ),
+            ssh_user,
+        )
+        .await?;
+        progress::emit(tx, &format!("  Uploaded {}", file.dest));
+    }
+
+    let Some(script) = &step.script else {
+        return Ok(());
+    };
+    let remote_path = format!("/tmp/.clawmacdo-ext-{}.sh", step.name);
+    upload(
+        ip,
+        key,
+        ssh_user,
+        script.as_bytes().to_vec(),
+        remote_path.clone(),
+    )
+    .await?;
+    ssh_root_as_async(
+        ip,
+        key,
+        &format!("chown {owner}:{owner} '{remote_path}' && chmod 700 '{remote_path}'"),
+        ssh_user,
+    )
+    .await?;
+    let wrapped = wrap_script(step, &remote_path);
+    let output = match step.run_as {
+        RunAs::Root => ssh_root_as_async(ip, key, &wrapped, ssh_user).await?,
+        RunAs::Openclaw => ssh_as_openclaw_with_user_async(ip, key, &wrapped, ssh_user).await?,
+    };
+    for line in output.lines().filter(|l| !l.trim().is_empty()) {
+        progress::emit(tx, &format!("  {line}"));
+    }
+    Ok(())
+}
+
+/// Run the steps of one phase, reporting each through the step callbacks.
+///
+/// `steps` is the full list from [`load_dir`]; step numbers are
+/// `FIRST_EXTENSION_STEP` plus the step's position in it.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_phase(
+    ip: &str,
+    key: &Path,
+    ssh_user: &str,
+    steps: &[ExtensionStep],
+    phase: Phase,
+    tx: &Option<mpsc::UnboundedSender<String>>,
+    on_step: &Option<StepStartFn>,
+    on_step_done: &Option<StepDoneFn>,
+) -> Result<(), AppError> {
+    for (i, step) in steps.iter().enumerate() {
+        if step.phase != phase {
+            continue;
+        }
+        let number = FIRST_EXTENSION_STEP + i as i32;
+        let label = format!("Extension: {} ({phase})", step.name);
+        if let Some(cb) = on_step {
+            cb(number, &label);
+        }
+        progress::emit(
+            tx,
+            &format!(
+                "\n[Extension] {} ({phase}, as {})...",
+                step.name,
+                step.run_as.user()
+            ),
+        );
+        run_step(ip, key, ssh_user, step, tx)
+            .await
+            .map_err(|e| AppError::Provision {
+                phase: format!("extension {}", step.name),
+                message: e.to_string(),
+            })?;
+        progress::emit(tx, &format!("  Extension '{}' done", step.name));
+        if let Some(cb) = on_step_done {
+            cb(number);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// A fresh directory under the system temp dir, removed on drop.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new() -> Self {
+            static NEXT: AtomicUsize = AtomicUsize::new(0);
+            let path = std::env::temp_dir().join(format!(
+                "clawmacdo-ext-test-{}-{}",
+                std::process::id(),
+                NEXT.fetch_add(1, Ordering::Relaxed)
+            ));
+            std::fs::create_dir_all(&path).unwrap();
+            TempDir(path)
+        }
+
+        fn write(&self, name: &str, contents: &str) -> PathBuf {
+            let path = self.0.join(name);
+            std::fs::write(&path, contents).unwrap();
+            path
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn load_err(dir: &TempDir) -> String {
+        load_dir(&dir.0).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn load_dir_orders_by_phase_then_file_name() {
+        let dir = TempDir::new();
+        dir.write(
+            "10-b.json",
+            r#"{"name":"b","phase":"post-gateway","run":"true"}"#,
+        );
+        dir.write(
+            "20-a.json",
+            r#"{"name":"a","phase":"pre-user","run":"true"}"#,
+        );
+        dir.write(
+            "05-c.json",
+            r#"{"name":"c","phase":"post-gateway","run":"true"}"#,
+        );
+        dir.write(
+            "30-d.json",
+            r#"{"name":"d","phase":"post-openclaw","run":"true"}"#,
+        );
+        dir.write("notes.txt", "not a step");
+
+        let steps = load_dir(&dir.0).unwrap();
+        let names: Vec<&str> = steps.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["a", "d", "c", "b"]);
+        assert_eq!(steps[0].run_as, RunAs::Root);
+        assert_eq!(steps[0].timeout_secs, DEFAULT_TIMEOUT_SECS);
+        assert_eq!(steps[0].source, dir.0.join("20-a.json"));
+    }
+
+    #[test]
+    fn load_dir_rejects_duplicate_names() {
+        let dir = TempDir::new();
+        dir.write(
+            "10-ca.json",
+            r#"{"name":"ca","phase":"pre-user","run":"true"}"#,
+        );
+        dir.write(
+            "20-ca.json",
+            r#"{"name":"ca","phase":"post-gateway","run":"true"}"#,
+        );
+        let err = load_err(&dir);
+        assert!(err.contains("20-ca.json"), "{err}");
+        assert!(err.contains("duplicate step name 'ca'"), "{err}");
+    }
+
+    #[test]
+    fn pre_user_steps_cannot_run_as_openclaw() {
+        let dir = TempDir::new();
+        dir.write(
+            "10-x.json",
+            r#"{"name":"x","phase":"pre-user","run_as":"openclaw","run":"true"}"#,
+        );
+        assert!(load_err(&dir).contains("pre-user steps must run as root"));
+
+        let dir = TempDir::new();
+        dir.write(
+            "10-x.json",
+            r#"{"name":"x","phase":"post-openclaw","run_as":"openclaw","run":"true"}"#,
+        );
+        assert_eq!(load_dir(&dir.0).unwrap()[0].run_as, RunAs::Openclaw);
+    }
+
+    #[test]
+    fn script_and_run_are_exclusive() {
+        let dir = TempDir::new();
+        dir.write("setup.sh", "echo from script\n");
+        dir.write(
+            "10-x.json",
+            r#"{"name":"x","phase":"post-gateway","script":"setup.sh","run":"true"}"#,
+        );
+        assert!(load_err(&dir).contains("set either script or run, not both"));
+
+        let dir = TempDir::new();
+        dir.write("setup.sh", "echo from script\n");
+        dir.write(
+            "10-x.json",
+            r#"{"name":"x","phase":"post-gateway","script":"setup.sh"}"#,
+        );
+        let steps = load_dir(&dir.0).unwrap();
+        assert_eq!(steps[0].script.as_deref(), Some("echo from script\n"));
+
+        let dir = TempDir::new();
+        dir.write("10-x.json", r#"{"name":"x","phase":"post-gateway"}"#);
+        assert!(load_err(&dir).contains("nothing to do"));
+    }
+
+    #[test]
+    fn timeout_must_be_within_bounds() {
+        for (timeout, ok) in [
+            (0, false),
+            (1, true),
+            (MAX_TIMEOUT_SECS, true),
+            (MAX_TIMEOUT_SECS + 1, false),
+        ] {
+            let dir = TempDir::new();
+            dir.write(
+                "10-x.json",
+                &format!(
+                    r#"{{"name":"x","phase":"post-gateway","run":"true","timeout_secs":{timeout}}}"#
+                ),
+            );
+            match load_dir(&dir.0) {
+                Ok(steps) => {
+                    assert!(ok, "timeout {timeout} should be rejected");
+                    assert_eq!(steps[0].timeout_secs, timeout);
+                }
+                Err(err) => {
+                    assert!(!ok, "timeout {timeout} should be accepted: {err}");
+                    assert!(err
+                        .to_string()
+                        .contains("timeout_secs must be between 1 and 3600"));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn file_dest_must_be_absolute() {
+        let dir = TempDir::new();
+        dir.write("ca.crt", "CERT");
+        dir.write(
+            "10-x.json",
+            r#"{"name":"x","phase":"pre-user","files":[{"src":"ca.crt","dest":"certs/ca.crt"}]}"#,
+        );
+        assert!(load_err(&dir).contains("must be an absolute path"));
+
+        let dir = TempDir::new();
+        dir.write("ca.crt", "CERT");
+        dir.write(
+            "10-x.json",
+            r#"{"name":"x","phase":"pre-user","files":[{"src":"ca.crt","dest":"/usr/local/share/ca-certificates/ca.crt","mode":"0600"}]}"#,
+        );
+        let step = &load_dir(&dir.0).unwrap()[0];
+        assert_eq!(
+            step.files[0].dest,
+            "/usr/local/share/ca-certificates/ca.crt"
+        );
+        assert_eq!(step.files[0].mode, 0o600);
+        assert_eq!(step.files[0].contents, b"CERT");
+    }
+
+    #[test]
+    fn parse_mode_accepts_octal_strings() {
+        let path = Path::new("x.json");
+        assert_eq!(parse_mode(path, None).unwrap(), 0o644);
+        assert_eq!(parse_mode(path, Some("0755")).unwrap(), 0o755);
+        assert_eq!(parse_mode(path, Some("0o600")).unwrap(), 0o600);
+        assert_eq!(parse_mode(path, Some("4755")).unwrap(), 0o4755);
+        assert!(parse_mode(path, Some("0999")).is_err());
+        assert!(parse_mode(path, Some("17777")).is_err());
+        assert!(parse_mode(path, Some("rwx")).is_err());
+    }
+
+    #[test]
+    fn wrap_script_applies_the_timeout_and_keeps_the_exit_code() {
+        let step = ExtensionStep {
+            name: "corp-ca".into(),
+            phase: Phase::PreUser,
+            run_as: RunAs::Root,
+            script: Some("update-ca-certificates".into()),
+            files: Vec::new(),
+            timeout_secs: 120,
+            source: PathBuf::from("10-corp-ca.json"),
+        };
+        let script = wrap_script(&step, "/tmp/.clawmacdo-ext-corp-ca.sh");
+        assert!(script.contains("CLAWMACDO_EXTENSION='corp-ca' CLAWMACDO_PHASE='pre-user'"));
+        assert!(script.contains(
+            "timeout --kill-after=10 120 /bin/bash '/tmp/.clawmacdo-ext-corp-ca.sh' || rc=$?"
+        ));
+        assert!(script.contains("timed out after 120s"));
+        assert!(script.trim_end().ends_with("exit $rc"));
+
+        // Run the wrapper locally: the step's exit status comes back unchanged
+        // and the uploaded script is removed.
+        let dir = TempDir::new();
+        let remote = dir.write("step.sh", "echo \"$CLAWMACDO_PHASE\"; exit 7\n");
+        let output = std::process::Command::new("/bin/bash")
+            .arg("-c")
+            .arg(wrap_script(&step, remote.to_str().unwrap()))
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(7));
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "pre-user\n");
+        assert!(!remote.exists());
+    }
+}
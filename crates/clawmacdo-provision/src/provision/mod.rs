@@ -1,8 +1,10 @@
 pub mod commands;
 pub mod docker;
+pub mod extensions;
 pub mod firewall;
 pub mod nodejs;
 pub mod openclaw;
+pub mod reverse_proxy;
 pub mod system_tools;
 pub mod tailscale;
 pub mod user;
@@ -32,6 +34,12 @@ pub struct ProvisionOpts<'a> {
     pub openclaw_version: &'a str,
     pub tailscale: bool,
     pub tailscale_auth_key: Option<&'a str>,
+    /// OpenClaw version of the `clawmacdo bake` image the instance booted
+    /// from. The AI CLIs are then already installed, and so is OpenClaw when
+    /// this matches `openclaw_version`.
+    pub baked_openclaw_version: Option<&'a str>,
+    /// User-supplied steps; the pre-user and post-openclaw ones run here.
+    pub extensions: &'a [extensions::ExtensionStep],
     /// SSH username to connect as (e.g. "root" for DigitalOcean, "ubuntu" for AWS Lightsail).
     /// Defaults to "root" if None.
     pub ssh_user: Option<&'a str>,
@@ -65,6 +73,18 @@ pub async fn run(ip: &str, key: &Path, opts: &ProvisionOpts<'_>) -> Result<(), A
     let tx = &opts.progress_tx;
     let ssh_user = opts.ssh_user.unwrap_or("root");
 
+    extensions::run_phase(
+        ip,
+        key,
+        ssh_user,
+        opts.extensions,
+        extensions::Phase::PreUser,
+        tx,
+        &opts.on_step,
+        &opts.on_step_done,
+    )
+    .await?;
+
     // Step 9: Create openclaw user + sudoers + .ssh
     notify_step(opts, 9, "Creating openclaw user and configuring access");
     progress::emit(
@@ -94,14 +114,29 @@ pub async fn run(ip: &str, key: &Path, opts: &ProvisionOpts<'_>) -> Result<(), A
 
     // Step 12: Install pnpm + configure for openclaw user
     notify_step(opts, 12, "Setting up Node.js/pnpm");
-    progress::emit(tx, "\n[Step 12/16] Setting up Node.js/pnpm...");
-    nodejs::provision(ip, key, ssh_user).await?;
-    progress::emit(tx, "  pnpm configured");
+    if opts.baked_openclaw_version.is_some() {
+        progress::emit(
+            tx,
+            "\n[Step 12/16] Node.js/pnpm and AI CLIs already in baked image",
+        );
+    } else {
+        progress::emit(tx, "\n[Step 12/16] Setting up Node.js/pnpm...");
+        nodejs::provision(ip, key, ssh_user).await?;
+        progress::emit(tx, "  pnpm configured");
+    }
     notify_step_done(opts, 12);
 
     // Step 13: Install OpenClaw as openclaw user
     notify_step(opts, 13, "Installing OpenClaw");
-    progress::emit(tx, "\n[Step 13/16] Installing OpenClaw...");
+    let install = opts.baked_openclaw_version != Some(opts.openclaw_version);
+    if install {
+        progress::emit(tx, "\n[Step 13/16] Installing OpenClaw...");
+    } else {
+        progress::emit(
+            tx,
+            "\n[Step 13/16] Configuring OpenClaw (installed in baked image)...",
+        );
+    }
     openclaw::provision(
         ip,
         key,
@@ -115,6 +150,7 @@ pub async fn run(ip: &str, key: &Path, opts: &ProvisionOpts<'_>) -> Result<(), A
         opts.telegram_bot_token,
         ssh_user,
         opts.openclaw_version,
+        install,
     )
     .await?;
     progress::emit(tx, "  OpenClaw installed");
@@ -123,6 +159,18 @@ pub async fn run(ip: &str, key: &Path, opts: &ProvisionOpts<'_>) -> Result<(), A
     // System tools (vim config, git config) — not a numbered step, runs as part of setup
     system_tools::provision(ip, key, ssh_user).await?;
 
+    extensions::run_phase(
+        ip,
+        key,
+        ssh_user,
+        opts.extensions,
+        extensions::Phase::PostOpenclaw,
+        tx,
+        &opts.on_step,
+        &opts.on_step_done,
+    )
+    .await?;
+
     // Step 14: Optional Tailscale
     notify_step(opts, 14, "Tailscale VPN");
     if opts.tailscale {
@@ -167,3 +215,31 @@ pub async fn run(ip: &str, key: &Path, opts: &ProvisionOpts<'_>) -> Result<(), A
 
     Ok(())
 }
+
+/// Run the provisioning steps that carry no secrets: the openclaw user,
+/// firewall, Docker daemon, Node.js CLIs, OpenClaw and system tools.
+///
+/// Used by `clawmacdo bake` to prepare a builder instance for snapshotting.
+/// Returns the installed OpenClaw version.
+pub async fn run_generic(
+    ip: &str,
+    key: &Path,
+    public_key_openssh: &str,
+    openclaw_version: &str,
+    ssh_user: &str,
+    tx: &Option<mpsc::UnboundedSender<String>>,
+) -> Result<String, AppError> {
+    progress::emit(tx, "  Creating openclaw user...");
+    user::provision(ip, key, public_key_openssh, ssh_user).await?;
+    progress::emit(tx, "  Hardening firewall...");
+    firewall::provision(ip, key, false, ssh_user).await?;
+    progress::emit(tx, "  Configuring Docker daemon...");
+    docker::provision(ip, key, ssh_user).await?;
+    progress::emit(tx, "  Installing Node.js CLIs...");
+    nodejs::provision(ip, key, ssh_user).await?;
+    progress::emit(tx, &format!("  Installing OpenClaw {openclaw_version}..."));
+    openclaw::install_openclaw(ip, key, ssh_user, openclaw_version).await?;
+    system_tools::provision(ip, key, ssh_user).await?;
+    let version = openclaw::installed_version(ip, key, ssh_user).await?;
+    Ok(version.trim().to_string())
+}
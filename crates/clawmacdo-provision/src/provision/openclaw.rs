@@ -5,6 +5,8 @@ use clawmacdo_ssh as ssh;
 use std::path::Path;
 
 /// Create directory structure, write .env, install OpenClaw via pnpm.
+/// With `install` false (a baked image that already has the wanted version)
+/// only the configuration is written and the installed version verified.
 /// Translated from openclaw-ansible/roles/openclaw/tasks/openclaw.yml + openclaw-release.yml.
 /// PProvision.
 #[allow(clippy::too_many_arguments)]
@@ -21,6 +23,7 @@ pub async fn provision(
     telegram_bot_token: &str,
     ssh_user: &str,
     openclaw_version: &str,
+    install: bool,
 ) -> Result<(), AppError> {
     let user = OPENCLAW_USER;
     let home = OPENCLAW_HOME;
@@ -181,9 +184,39 @@ chmod 700 {cd}"#,
     );
     ssh_root_as_async(ip, key, &normalize_extensions, ssh_user).await?;
 
-    // Install openclaw globally. Try pnpm first (user-scoped), fall back to npm (system-wide as root).
-    // On some Tencent Ubuntu images, npm global install fails with ENOENT due to /bin/sh quirks,
-    // so pnpm is preferred. If pnpm also fails to make it accessible, install via npm as root.
+    if install {
+        install_openclaw(ip, key, ssh_user, openclaw_version).await?;
+    }
+    let version = installed_version(ip, key, ssh_user).await?;
+    println!("  OpenClaw version: {}", version.trim());
+
+    if !anthropic_api_key.trim().is_empty() {
+        // Warm up Claude in headless mode so first manual run is not blocked by onboarding prompts.
+        // This is best-effort and should never fail the deploy.
+        let claude_bootstrap = format!(
+            "PATH={home}/.local/bin:{home}/.local/share/pnpm:/usr/local/bin:/usr/bin:/bin \
+             HOME={home} \
+             timeout 240s claude -p \"health check\" --output-format text --max-turns 1 >/dev/null 2>&1 || true",
+        );
+        if let Err(e) = ssh_as_openclaw_with_user_async(ip, key, &claude_bootstrap, ssh_user).await
+        {
+            eprintln!("  Warning: Claude bootstrap failed; continuing: {e}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Install OpenClaw globally. Try pnpm first (user-scoped), fall back to npm (system-wide as root).
+/// On some Tencent Ubuntu images, npm global install fails with ENOENT due to /bin/sh quirks,
+/// so pnpm is preferred. If pnpm also fails to make it accessible, install via npm as root.
+pub async fn install_openclaw(
+    ip: &str,
+    key: &Path,
+    ssh_user: &str,
+    openclaw_version: &str,
+) -> Result<(), AppError> {
+    let home = OPENCLAW_HOME;
     let version_spec = openclaw_version.to_string();
     let install_cmd = format!(
         "PNPM_HOME={home}/.local/share/pnpm \
@@ -222,28 +255,16 @@ chmod 700 {cd}"#,
         })?;
     }
 
-    // Verify installation (check both user and system paths)
+    Ok(())
+}
+
+/// The `openclaw --version` output, checking both user and system paths.
+pub async fn installed_version(ip: &str, key: &Path, ssh_user: &str) -> Result<String, AppError> {
+    let home = OPENCLAW_HOME;
     let verify_cmd = format!(
         "PATH={home}/.local/bin:{home}/.local/share/pnpm:/usr/local/bin:/usr/bin:/bin \
          HOME={home} \
          openclaw --version",
     );
-    let version = ssh_as_openclaw_with_user_async(ip, key, &verify_cmd, ssh_user).await?;
-    println!("  OpenClaw version: {}", version.trim());
-
-    if !anthropic_api_key.trim().is_empty() {
-        // Warm up Claude in headless mode so first manual run is not blocked by onboarding prompts.
-        // This is best-effort and should never fail the deploy.
-        let claude_bootstrap = format!(
-            "PATH={home}/.local/bin:{home}/.local/share/pnpm:/usr/local/bin:/usr/bin:/bin \
-             HOME={home} \
-             timeout 240s claude -p \"health check\" --output-format text --max-turns 1 >/dev/null 2>&1 || true",
-        );
-        if let Err(e) = ssh_as_openclaw_with_user_async(ip, key, &claude_bootstrap, ssh_user).await
-        {
-            eprintln!("  Warning: Claude bootstrap failed; continuing: {e}");
-        }
-    }
-
-    Ok(())
+    ssh_as_openclaw_with_user_async(ip, key, &verify_cmd, ssh_user).await
 }
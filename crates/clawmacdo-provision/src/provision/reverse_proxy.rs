@@ -0,0 +1,374 @@
+use crate::provision::commands::{ssh_as_openclaw_with_user_async, ssh_root_as_async};
+use clawmacdo_core::config::{OPENCLAW_GATEWAY_PORT, OPENCLAW_HOME};
+use clawmacdo_core::error::AppError;
+use std::fmt;
+use std::path::Path;
+
+/// Where the instance records how its gateway is exposed (`mode=`, `server=`, `domain=`).
+pub const EXPOSURE_FILE: &str = "/etc/clawmacdo/exposure";
+
+/// Marker written into generated proxy configs so they can be told apart
+/// from configs written by hand.
+const MANAGED_MARKER: &str = "# managed by clawmacdo";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProxyServer {
+    Caddy,
+    Nginx,
+}
+
+impl fmt::Display for ProxyServer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProxyServer::Caddy => write!(f, "caddy"),
+            ProxyServer::Nginx => write!(f, "nginx"),
+        }
+    }
+}
+
+impl std::str::FromStr for ProxyServer {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "caddy" => Ok(ProxyServer::Caddy),
+            "nginx" => Ok(ProxyServer::Nginx),
+            other => Err(AppError::Generic(format!(
+                "unknown proxy server '{other}' (expected caddy or nginx)"
+            ))),
+        }
+    }
+}
+
+/// How the gateway is currently exposed, as recorded in [`EXPOSURE_FILE`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Exposure {
+    pub mode: String,
+    pub server: Option<String>,
+    pub domain: Option<String>,
+}
+
+impl Exposure {
+    /// Public HTTPS base URL when the gateway is behind the reverse proxy.
+    pub fn proxy_url(&self) -> Option<String> {
+        match (self.mode.as_str(), &self.domain) {
+            ("proxy", Some(domain)) => Some(format!("https://{domain}")),
+            _ => None,
+        }
+    }
+}
+
+/// Parse the `key=value` lines of [`EXPOSURE_FILE`]; `None` when no mode is recorded.
+pub fn parse_exposure(contents: &str) -> Option<Exposure> {
+    let mut exposure = Exposure::default();
+    for line in contents.lines() {
+        let Some((key, value)) = line.trim().split_once('=') else {
+            continue;
+        };
+        let value = value.trim().to_string();
+        match key.trim() {
+            "mode" => exposure.mode = value,
+            "server" if !value.is_empty() => exposure.server = Some(value),
+            "domain" if !value.is_empty() => exposure.domain = Some(value),
+            _ => {}
+        }
+    }
+    (!exposure.mode.is_empty()).then_some(exposure)
+}
+
+/// Remote command printing [`EXPOSURE_FILE`] (empty when missing).
+pub fn read_exposure_cmd() -> String {
+    format!("cat {EXPOSURE_FILE} 2>/dev/null || true")
+}
+
+/// Shell snippet recording the exposure mode on the instance.
+fn record_exposure(mode: &str, server: Option<ProxyServer>, domain: Option<&str>) -> String {
+    let server = server.map(|s| s.to_string()).unwrap_or_default();
+    let domain = domain.unwrap_or("");
+    format!(
+        "mkdir -p \"$(dirname {EXPOSURE_FILE})\" && \
+         printf 'mode=%s\\nserver=%s\\ndomain=%s\\n' '{mode}' '{server}' '{domain}' > {EXPOSURE_FILE}"
+    )
+}
+
+/// Caddyfile proxying `domain` to the loopback gateway. Caddy obtains and
+/// renews the certificate itself.
+pub fn caddyfile(domain: &str, email: Option<&str>) -> String {
+    let port = OPENCLAW_GATEWAY_PORT;
+    let global = match email {
+        Some(email) => format!("{{\n\temail {email}\n}}\n\n"),
+        None => String::new(),
+    };
+    format!(
+        "{MANAGED_MARKER}\n{global}{domain} {{\n\tencode gzip\n\treverse_proxy 127.0.0.1:{port}\n}}\n"
+    )
+}
+
+/// nginx site proxying `domain` to the loopback gateway over plain HTTP;
+/// certbot adds the TLS server block and the HTTPS redirect.
+pub fn nginx_site(domain: &str) -> String {
+    let port = OPENCLAW_GATEWAY_PORT;
+    format!(
+        r#"{MANAGED_MARKER}
+map $http_upgrade $connection_upgrade {{
+    default upgrade;
+    ''      close;
+}}
+
+server {{
+    listen 80;
+    listen [::]:80;
+    server_name {domain};
+
+    location / {{
+        proxy_pass http://127.0.0.1:{port};
+        proxy_http_version 1.1;
+        proxy_set_header Upgrade $http_upgrade;
+        proxy_set_header Connection $connection_upgrade;
+        proxy_set_header Host $host;
+        proxy_set_header X-Real-IP $remote_addr;
+        proxy_set_header X-Forwarded-For $proxy_add_x_forwarded_for;
+        proxy_set_header X-Forwarded-Proto $scheme;
+        proxy_read_timeout 3600s;
+    }}
+}}
+"#
+    )
+}
+
+/// Close the raw gateway port in UFW (both address families).
+fn close_gateway_port() -> String {
+    let port = OPENCLAW_GATEWAY_PORT;
+    format!(
+        "if command -v ufw >/dev/null 2>&1; then \
+           ufw delete allow {port}/tcp >/dev/null 2>&1 || true; \
+           ufw delete allow {port} >/dev/null 2>&1 || true; \
+         fi"
+    )
+}
+
+/// Stop serving the gateway through either proxy. Hand-written configs
+/// (without the managed marker) are left alone.
+fn stop_proxies() -> String {
+    format!(
+        r#"if [ -f /etc/caddy/Caddyfile ] && grep -q '{MANAGED_MARKER}' /etc/caddy/Caddyfile; then
+  systemctl disable --now caddy >/dev/null 2>&1 || true
+fi
+if [ -f /etc/nginx/sites-available/openclaw ] && grep -q '{MANAGED_MARKER}' /etc/nginx/sites-available/openclaw; then
+  rm -f /etc/nginx/sites-enabled/openclaw
+  if systemctl is-active --quiet nginx; then nginx -t >/dev/null 2>&1 && systemctl reload nginx; fi
+fi"#
+    )
+}
+
+/// Root script installing `server`, proxying `domain` to the gateway with an
+/// ACME certificate, opening 80/443 and closing the raw gateway port.
+pub fn install_script(server: ProxyServer, domain: &str, email: Option<&str>) -> String {
+    let close = close_gateway_port();
+    let record = record_exposure("proxy", Some(server), Some(domain));
+    let setup = match server {
+        ProxyServer::Caddy => {
+            let caddyfile = caddyfile(domain, email);
+            format!(
+                r#"if [ -f /etc/nginx/sites-enabled/openclaw ]; then
+  rm -f /etc/nginx/sites-enabled/openclaw
+  systemctl is-active --quiet nginx && systemctl reload nginx || true
+fi
+systemctl disable --now nginx >/dev/null 2>&1 || true
+command -v caddy >/dev/null 2>&1 || apt-get install -y -qq caddy
+if [ -f /etc/caddy/Caddyfile ] && ! grep -q '{MANAGED_MARKER}' /etc/caddy/Caddyfile; then
+  cp /etc/caddy/Caddyfile /etc/caddy/Caddyfile.bak
+fi
+cat > /etc/caddy/Caddyfile << 'CADDYEOF'
+{caddyfile}CADDYEOF
+caddy validate --config /etc/caddy/Caddyfile --adapter caddyfile >/dev/null
+systemctl enable caddy >/dev/null 2>&1
+systemctl restart caddy"#
+            )
+        }
+        ProxyServer::Nginx => {
+            let site = nginx_site(domain);
+            let account = match email {
+                Some(email) => format!("-m '{email}'"),
+                None => "--register-unsafely-without-email".to_string(),
+            };
+            format!(
+                r#"systemctl disable --now caddy >/dev/null 2>&1 || true
+apt-get install -y -qq nginx certbot python3-certbot-nginx
+cat > /etc/nginx/sites-available/openclaw << 'NGINXEOF'
+{site}NGINXEOF
+ln -sf /etc/nginx/sites-available/openclaw /etc/nginx/sites-enabled/openclaw
+rm -f /etc/nginx/sites-enabled/default
+nginx -t
+systemctl enable nginx >/dev/null 2>&1
+systemctl restart nginx
+certbot --nginx -d '{domain}' --non-interactive --agree-tos {account} --redirect --keep-until-expiring"#
+            )
+        }
+    };
+    format!(
+        r#"set -e
+export DEBIAN_FRONTEND=noninteractive
+if command -v ufw >/dev/null 2>&1; then
+  ufw allow 80/tcp >/dev/null
+  ufw allow 443/tcp >/dev/null
+fi
+apt-get update -qq
+{setup}
+{close}
+{record}
+echo "reverse proxy: {server} for {domain}"
+"#
+    )
+}
+
+/// Root script removing the proxy and recording `mode` (`funnel` or `none`).
+pub fn disable_script(mode: &str) -> String {
+    format!(
+        "{}\n{}\n{}\n",
+        stop_proxies(),
+        close_gateway_port(),
+        record_exposure(mode, None, None)
+    )
+}
+
+/// openclaw-user command pinning the gateway to loopback, trusting the local
+/// proxy and (for proxy mode) allowing the public origin in the Control UI.
+pub fn gateway_loopback_cmd(origin: Option<&str>) -> String {
+    let home = OPENCLAW_HOME;
+    let origin = origin.unwrap_or("");
+    format!(
+        r#"export PATH="{home}/.local/bin:{home}/.local/share/pnpm:/usr/local/bin:/usr/bin:/bin" HOME="{home}" && \
+CFG="{home}/.openclaw/openclaw.json" ORIGIN='{origin}' node <<'NODE'
+const fs = require('fs');
+const path = process.env.CFG;
+const cfg = JSON.parse(fs.readFileSync(path, 'utf8'));
+cfg.gateway = cfg.gateway || {{}};
+cfg.gateway.bind = 'loopback';
+cfg.gateway.trustedProxies = cfg.gateway.trustedProxies || ['127.0.0.1/8', '::1/128'];
+const origin = process.env.ORIGIN;
+if (origin) {{
+  cfg.gateway.controlUi = cfg.gateway.controlUi || {{}};
+  const origins = cfg.gateway.controlUi.allowedOrigins || [];
+  if (!origins.includes(origin)) origins.push(origin);
+  cfg.gateway.controlUi.allowedOrigins = origins;
+}}
+fs.writeFileSync(path, JSON.stringify(cfg, null, 2) + '\n', {{ mode: 0o600 }});
+console.log('gateway.bind: loopback');
+NODE
+export XDG_RUNTIME_DIR=/run/user/$(id -u) DBUS_SESSION_BUS_ADDRESS=unix:path=/run/user/$(id -u)/bus
+systemctl --user restart openclaw-gateway.service 2>/dev/null || true"#
+    )
+}
+
+/// Root command waiting until `https://<domain>/health` answers through the
+/// proxy on this host (certificate issuance can take a minute).
+pub fn https_health_cmd(domain: &str) -> String {
+    format!(
+        "for i in $(seq 1 30); do \
+           if curl -fsS --max-time 5 --resolve '{domain}:443:127.0.0.1' 'https://{domain}/health' >/dev/null 2>&1; then \
+             echo 'https: healthy'; exit 0; \
+           fi; sleep 4; \
+         done; \
+         echo 'https: no healthy response from https://{domain}/health' >&2; exit 1"
+    )
+}
+
+/// Put the gateway behind Caddy or nginx at `https://<domain>`: install the
+/// server, obtain an ACME certificate, close the raw gateway port and wait
+/// for the HTTPS health check. Only then is the gateway rebound to loopback,
+/// so a failed install leaves its current bind untouched.
+pub async fn provision(
+    ip: &str,
+    key: &Path,
+    server: ProxyServer,
+    domain: &str,
+    email: Option<&str>,
+    ssh_user: &str,
+) -> Result<(), AppError> {
+    let health = || async {
+        ssh_root_as_async(ip, key, &https_health_cmd(domain), ssh_user)
+            .await
+            .map_err(|e| AppError::Provision {
+                phase: "reverse proxy health".into(),
+                message: format!(
+                    "{e}. Check that DNS for {domain} points at {ip} and ports 80/443 are open in the cloud firewall"
+                ),
+            })
+    };
+    ssh_root_as_async(ip, key, &install_script(server, domain, email), ssh_user)
+        .await
+        .map_err(|e| AppError::Provision {
+            phase: format!("{server} reverse proxy"),
+            message: e.to_string(),
+        })?;
+    health().await?;
+
+    let origin = format!("https://{domain}");
+    ssh_as_openclaw_with_user_async(ip, key, &gateway_loopback_cmd(Some(&origin)), ssh_user)
+        .await
+        .map_err(|e| AppError::Provision {
+            phase: "gateway loopback".into(),
+            message: e.to_string(),
+        })?;
+    // The loopback switch restarts the gateway; confirm it is back behind the proxy.
+    health().await?;
+    Ok(())
+}
+
+/// Remove the managed proxy, keep the gateway on loopback and record `mode`.
+pub async fn disable(ip: &str, key: &Path, mode: &str, ssh_user: &str) -> Result<(), AppError> {
+    ssh_root_as_async(ip, key, &disable_script(mode), ssh_user)
+        .await
+        .map_err(|e| AppError::Provision {
+            phase: "reverse proxy removal".into(),
+            message: e.to_string(),
+        })?;
+    ssh_as_openclaw_with_user_async(ip, key, &gateway_loopback_cmd(None), ssh_user)
+        .await
+        .map_err(|e| AppError::Provision {
+            phase: "gateway loopback".into(),
+            message: e.to_string(),
+        })?;
+    Ok(())
+}
+
+/// Read how the gateway is exposed on an instance.
+pub async fn exposure(ip: &str, key: &Path, ssh_user: &str) -> Result<Option<Exposure>, AppError> {
+    let output = ssh_root_as_async(ip, key, &read_exposure_cmd(), ssh_user).await?;
+    Ok(parse_exposure(&output))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn proxy_configs_point_at_the_loopback_gateway() {
+        let caddy = caddyfile("claw.example.com", Some("ops@example.com"));
+        assert!(caddy.contains("email ops@example.com"));
+        assert!(caddy.contains("claw.example.com {"));
+        assert!(caddy.contains("reverse_proxy 127.0.0.1:18789"));
+
+        let script = install_script(ProxyServer::Nginx, "claw.example.com", None);
+        assert!(script.contains("proxy_pass http://127.0.0.1:18789;"));
+        assert!(script.contains("certbot --nginx -d 'claw.example.com'"));
+        assert!(script.contains("--register-unsafely-without-email"));
+        assert!(script.contains("ufw delete allow 18789/tcp"));
+        assert!(script.contains("mode=%s"));
+    }
+
+    #[test]
+    fn exposure_file_round_trips() {
+        let exposure =
+            parse_exposure("mode=proxy\nserver=caddy\ndomain=claw.example.com\n").unwrap();
+        assert_eq!(exposure.server.as_deref(), Some("caddy"));
+        assert_eq!(
+            exposure.proxy_url().as_deref(),
+            Some("https://claw.example.com")
+        );
+        let none = parse_exposure("mode=none\nserver=\ndomain=\n").unwrap();
+        assert_eq!(none.proxy_url(), None);
+        assert_eq!(parse_exposure(""), None);
+    }
+}
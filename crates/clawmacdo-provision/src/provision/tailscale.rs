@@ -1,5 +1,6 @@
 use crate::provision::commands::ssh_root_as_async;
 use clawmacdo_core::error::AppError;
+use std::fmt;
 use std::path::Path;
 
 pub enum TailscaleProvisionStatus {
@@ -77,3 +78,166 @@ curl -fsSL "https://pkgs.tailscale.com/stable/ubuntu/noble.tailscale-keyring.lis
 fn shell_quote(s: &str) -> String {
     format!("'{}'", s.replace('\'', "'\\''"))
 }
+
+/// Desired `tailscale serve` state for the gateway on HTTPS port 443.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServeMode {
+    /// Public through Tailscale Funnel.
+    Funnel,
+    /// Reachable from the tailnet only.
+    Tailnet,
+    /// No serve config at all.
+    Off,
+}
+
+impl fmt::Display for ServeMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ServeMode::Funnel => write!(f, "funnel"),
+            ServeMode::Tailnet => write!(f, "tailnet"),
+            ServeMode::Off => write!(f, "off"),
+        }
+    }
+}
+
+impl std::str::FromStr for ServeMode {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "funnel" => Ok(ServeMode::Funnel),
+            "tailnet" => Ok(ServeMode::Tailnet),
+            "off" => Ok(ServeMode::Off),
+            other => Err(AppError::Generic(format!(
+                "unknown serve mode '{other}' (expected funnel, tailnet or off)"
+            ))),
+        }
+    }
+}
+
+/// The parts of `tailscale serve status --json` clawmacdo manages.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ServeState {
+    /// Proxy target of the `/` handler on port 443.
+    pub proxy: Option<String>,
+    /// Whether Funnel is allowed on port 443.
+    pub funnel: bool,
+    /// Anything else: other ports, paths or TCP forwards.
+    pub extra: bool,
+}
+
+impl ServeState {
+    pub fn wanted(port: u16, mode: ServeMode) -> Self {
+        let proxy = format!("http://127.0.0.1:{port}");
+        match mode {
+            ServeMode::Off => Self::default(),
+            ServeMode::Tailnet => Self {
+                proxy: Some(proxy),
+                ..Self::default()
+            },
+            ServeMode::Funnel => Self {
+                proxy: Some(proxy),
+                funnel: true,
+                extra: false,
+            },
+        }
+    }
+}
+
+/// Parse the serve config (an `ipn.ServeConfig` document). An instance
+/// without a serve config prints nothing or a "No serve config" notice.
+pub fn parse_serve_status(output: &str) -> Result<ServeState, AppError> {
+    let output = output.trim();
+    if !output.starts_with('{') {
+        if output.is_empty() || output.contains("No serve config") {
+            return Ok(ServeState::default());
+        }
+        return Err(AppError::Provision {
+            phase: "tailscale serve".into(),
+            message: format!("unexpected `tailscale serve status --json` output: {output}"),
+        });
+    }
+    let config: serde_json::Value = serde_json::from_str(output)?;
+    let mut state = ServeState::default();
+    for (port, _) in config["TCP"].as_object().into_iter().flatten() {
+        state.extra |= port != "443";
+    }
+    for (host_port, web) in config["Web"].as_object().into_iter().flatten() {
+        if !host_port.ends_with(":443") {
+            state.extra = true;
+            continue;
+        }
+        for (path, handler) in web["Handlers"].as_object().into_iter().flatten() {
+            match handler["Proxy"].as_str() {
+                Some(proxy) if path == "/" => state.proxy = Some(proxy.to_string()),
+                _ => state.extra = true,
+            }
+        }
+    }
+    for (host_port, allowed) in config["AllowFunnel"].as_object().into_iter().flatten() {
+        if allowed.as_bool() == Some(true) {
+            if host_port.ends_with(":443") {
+                state.funnel = true;
+            } else {
+                state.extra = true;
+            }
+        }
+    }
+    Ok(state)
+}
+
+/// Commands that turn `current` into the wanted state. Empty when nothing
+/// needs to change. The serve config is rebuilt from scratch so leftovers
+/// (other ports, stale targets) never survive.
+pub fn serve_plan(current: &ServeState, port: u16, mode: ServeMode) -> Vec<String> {
+    if *current == ServeState::wanted(port, mode) {
+        return Vec::new();
+    }
+    let mut commands = Vec::new();
+    if *current != ServeState::default() {
+        commands.push("tailscale serve reset".to_string());
+    }
+    let target = format!("http://127.0.0.1:{port}");
+    match mode {
+        ServeMode::Off => {}
+        ServeMode::Tailnet => commands.push(format!("tailscale serve --bg --https=443 {target}")),
+        ServeMode::Funnel => commands.push(format!("tailscale funnel --bg --https=443 {target}")),
+    }
+    commands
+}
+
+const SERVE_STATUS_CMD: &str = "tailscale serve status --json 2>&1";
+
+/// Converge the instance's serve config on `mode` for the gateway `port`.
+/// Returns the state found and the commands run (or, with `dry_run`, the
+/// commands that would run).
+pub async fn apply_serve(
+    ip: &str,
+    key: &Path,
+    port: u16,
+    mode: ServeMode,
+    dry_run: bool,
+    ssh_user: &str,
+) -> Result<(ServeState, Vec<String>), AppError> {
+    let current =
+        parse_serve_status(&ssh_root_as_async(ip, key, SERVE_STATUS_CMD, ssh_user).await?)?;
+    let commands = serve_plan(&current, port, mode);
+    if dry_run || commands.is_empty() {
+        return Ok((current, commands));
+    }
+    ssh_root_as_async(ip, key, &commands.join(" && "), ssh_user)
+        .await
+        .map_err(|e| AppError::Provision {
+            phase: "tailscale serve".into(),
+            message: e.to_string(),
+        })?;
+    let applied =
+        parse_serve_status(&ssh_root_as_async(ip, key, SERVE_STATUS_CMD, ssh_user).await?)?;
+    if applied != ServeState::wanted(port, mode) {
+        return Err(AppError::Provision {
+            phase: "tailscale serve".into(),
+            message: format!("serve config did not converge on {mode}: found {applied:?}"),
+        });
+    }
+    Ok((current, commands))
+}
@@ -1,5 +1,6 @@
 use clawmacdo_core::config;
 use clawmacdo_core::error::AppError;
+use clawmacdo_core::metrics;
 use ssh2::Session;
 use std::io::{Read, Write};
 use std::net::TcpStream;
@@ -167,13 +168,18 @@ fn connect_as(ip: &str, private_key_path: &Path, username: &str) -> Result<Sessi
 
     for attempt in 1..=MAX_RETRIES {
         match try_connect(ip, private_key_path, username) {
-            Ok(sess) => return Ok(sess),
+            Ok(sess) => {
+                metrics::inc_counter(metrics::SSH_CONNECT_TOTAL, &[("outcome", "ok")]);
+                return Ok(sess);
+            }
             Err(e) => {
+                metrics::inc_counter(metrics::SSH_CONNECT_TOTAL, &[("outcome", "error")]);
                 // Don't retry permanent errors or connection timeouts
                 if is_non_retryable_error(&e) {
                     return Err(e);
                 }
                 if attempt < MAX_RETRIES {
+                    metrics::inc_counter(metrics::SSH_CONNECT_RETRIES_TOTAL, &[]);
                     eprintln!(
                         "SSH connect to {ip} failed (attempt {attempt}/{MAX_RETRIES}): {e} — retrying in {}s",
                         RETRY_DELAY.as_secs()
@@ -339,6 +345,66 @@ pub fn exec(ip: &str, private_key_path: &Path, command: &str) -> Result<String,
     read_command_output(channel)
 }
 
+/// Execute a command as a specific user and stream its stdout into `writer`.
+///
+/// Used for large payloads (e.g. `tar -czf -` backups) that should not be
+/// buffered in memory. Returns the number of bytes written.
+pub fn exec_stream_as(
+    ip: &str,
+    private_key_path: &Path,
+    command: &str,
+    username: &str,
+    writer: &mut dyn Write,
+) -> Result<u64, AppError> {
+    let sess = connect_as(ip, private_key_path, username)?;
+    let mut channel = sess
+        .channel_session()
+        .map_err(|e| AppError::Ssh(format!("Open channel: {e}")))?;
+    channel
+        .exec(command)
+        .map_err(|e| AppError::Ssh(format!("Exec command: {e}")))?;
+    let _ = channel.send_eof();
+    // Read stderr alongside stdout: a command that fills the stderr window
+    // (a noisy `tar`) would otherwise block before stdout reaches EOF.
+    sess.set_blocking(false);
+
+    let mut counted = CountingWriter {
+        inner: writer,
+        written: 0,
+    };
+    let mut stderr = Vec::new();
+    let mut buf = [0u8; 16 * 1024];
+    loop {
+        let out = pump_nb(&mut channel, &mut counted, &mut buf)
+            .map_err(|e| AppError::Ssh(format!("Stream output: {e}")))?;
+        let err = pump_nb(&mut channel.stderr(), &mut stderr, &mut buf)
+            .map_err(|e| AppError::Ssh(format!("Read stderr: {e}")))?;
+        if !out && !err {
+            if channel.eof() {
+                break;
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+    retry_nb(|| channel.wait_close()).map_err(|e| AppError::Ssh(format!("Wait close: {e}")))?;
+    let written = counted.written;
+
+    let exit_status = channel.exit_status().unwrap_or(-1);
+    if exit_status != 0 {
+        let stderr = String::from_utf8_lossy(&stderr);
+        let details = if stderr.trim().is_empty() {
+            "no output captured"
+        } else {
+            stderr.trim()
+        };
+        return Err(AppError::Ssh(format!(
+            "Command exited with status {exit_status}: {details}"
+        )));
+    }
+
+    Ok(written)
+}
+
 /// Open one session and run multiple plain commands over it, each on its own channel.
 /// Saves TCP + handshake overhead compared to calling `exec_as` N times.
 pub fn exec_multi_as(
@@ -390,6 +456,359 @@ pub fn exec_multi_with_input_as(
     Ok(results)
 }
 
+/// Run a command as `username` and return its exit status with the merged
+/// stdout/stderr. Unlike `exec_as`, a non-zero exit is not an error.
+pub fn exec_status_as(
+    ip: &str,
+    private_key_path: &Path,
+    command: &str,
+    username: &str,
+) -> Result<(i32, String), AppError> {
+    let sess = connect_as(ip, private_key_path, username)?;
+    let mut channel = sess
+        .channel_session()
+        .map_err(|e| AppError::Ssh(format!("Open channel: {e}")))?;
+    channel
+        .exec(&format!("{{ {command}\n}} 2>&1"))
+        .map_err(|e| AppError::Ssh(format!("Exec command: {e}")))?;
+    let _ = channel.send_eof();
+    let mut output = Vec::new();
+    channel
+        .read_to_end(&mut output)
+        .map_err(|e| AppError::Ssh(format!("Read output: {e}")))?;
+    channel
+        .wait_close()
+        .map_err(|e| AppError::Ssh(format!("Wait close: {e}")))?;
+    let status = channel.exit_status().unwrap_or(-1);
+    Ok((status, String::from_utf8_lossy(&output).into_owned()))
+}
+
+/// libssh2 reports "would block" on non-blocking sessions as `EAGAIN`.
+const LIBSSH2_ERROR_EAGAIN: i32 = -37;
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(5);
+
+fn would_block(e: &std::io::Error) -> bool {
+    e.kind() == std::io::ErrorKind::WouldBlock
+}
+
+/// Retry a libssh2 call on a non-blocking session until it stops returning `EAGAIN`.
+fn retry_nb<T>(mut call: impl FnMut() -> Result<T, ssh2::Error>) -> Result<T, ssh2::Error> {
+    loop {
+        match call() {
+            Err(e) if e.code() == ssh2::ErrorCode::Session(LIBSSH2_ERROR_EAGAIN) => {
+                std::thread::sleep(POLL_INTERVAL)
+            }
+            result => return result,
+        }
+    }
+}
+
+/// `write_all` for non-blocking writers.
+fn write_all_nb(writer: &mut impl Write, mut buf: &[u8]) -> std::io::Result<()> {
+    while !buf.is_empty() {
+        match writer.write(buf) {
+            Ok(0) => return Err(std::io::ErrorKind::WriteZero.into()),
+            Ok(n) => buf = &buf[n..],
+            Err(e) if would_block(&e) => std::thread::sleep(POLL_INTERVAL),
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+/// Read what is available from `reader` into `out`. Returns `Ok(true)` when
+/// anything was copied.
+fn pump_nb(reader: &mut impl Read, out: &mut impl Write, buf: &mut [u8]) -> std::io::Result<bool> {
+    match reader.read(buf) {
+        Ok(0) => Ok(false),
+        Ok(n) => {
+            out.write_all(&buf[..n])?;
+            out.flush()?;
+            Ok(true)
+        }
+        Err(e) if would_block(&e) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// A writer that counts the bytes passed through to `inner`.
+struct CountingWriter<'a> {
+    inner: &'a mut dyn Write,
+    written: u64,
+}
+
+impl Write for CountingWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Run `command` as `username`, streaming its stdout and stderr to ours as
+/// they arrive. Returns the remote exit status.
+pub fn exec_forward_as(
+    ip: &str,
+    private_key_path: &Path,
+    command: &str,
+    username: &str,
+) -> Result<i32, AppError> {
+    let sess = connect_as(ip, private_key_path, username)?;
+    let mut channel = sess
+        .channel_session()
+        .map_err(|e| AppError::Ssh(format!("Open channel: {e}")))?;
+    channel
+        .exec(command)
+        .map_err(|e| AppError::Ssh(format!("Exec command: {e}")))?;
+    let _ = channel.send_eof();
+    sess.set_blocking(false);
+
+    let (mut stdout, mut stderr) = (std::io::stdout(), std::io::stderr());
+    let mut buf = [0u8; 16 * 1024];
+    loop {
+        let out = pump_nb(&mut channel, &mut stdout, &mut buf)
+            .map_err(|e| AppError::Ssh(format!("Read output: {e}")))?;
+        let err = pump_nb(&mut channel.stderr(), &mut stderr, &mut buf)
+            .map_err(|e| AppError::Ssh(format!("Read stderr: {e}")))?;
+        if channel.eof() {
+            break;
+        }
+        if !out && !err {
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+    retry_nb(|| channel.wait_close()).map_err(|e| AppError::Ssh(format!("Wait close: {e}")))?;
+    Ok(channel.exit_status().unwrap_or(-1))
+}
+
+/// Run a long-lived command (e.g. `journalctl -f`) as `username` and hand
+/// each line of its merged output to `on_line` as it arrives. Stops when the
+/// command exits, `on_line` returns `false`, or `cancelled` returns `true`;
+/// the last two close the channel, which ends the remote command. Idle
+/// streams are kept alive, so there is no read timeout.
+pub fn exec_lines_as(
+    ip: &str,
+    private_key_path: &Path,
+    command: &str,
+    username: &str,
+    on_line: &mut dyn FnMut(&str) -> bool,
+    cancelled: &dyn Fn() -> bool,
+) -> Result<Option<i32>, AppError> {
+    let sess = connect_as(ip, private_key_path, username)?;
+    let mut channel = sess
+        .channel_session()
+        .map_err(|e| AppError::Ssh(format!("Open channel: {e}")))?;
+    channel
+        .exec(&format!("{{ {command}\n}} 2>&1"))
+        .map_err(|e| AppError::Ssh(format!("Exec command: {e}")))?;
+    let _ = channel.send_eof();
+    sess.set_keepalive(true, 30);
+    sess.set_blocking(false);
+
+    let mut buf = [0u8; 16 * 1024];
+    let mut pending: Vec<u8> = Vec::new();
+    loop {
+        let read = match channel.read(&mut buf) {
+            Ok(n) => n,
+            Err(e) if would_block(&e) => 0,
+            Err(e) => return Err(AppError::Ssh(format!("Read output: {e}"))),
+        };
+        pending.extend_from_slice(&buf[..read]);
+        while let Some(end) = pending.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = pending.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            if !on_line(line.trim_end_matches(['\r', '\n'])) {
+                let _ = retry_nb(|| channel.close());
+                return Ok(None);
+            }
+        }
+        if channel.eof() {
+            break;
+        }
+        if cancelled() {
+            let _ = retry_nb(|| channel.close());
+            return Ok(None);
+        }
+        if read == 0 {
+            let _ = sess.keepalive_send();
+            std::thread::sleep(POLL_INTERVAL * 10);
+        }
+    }
+    if !pending.is_empty() {
+        on_line(String::from_utf8_lossy(&pending).trim_end_matches('\r'));
+    }
+    retry_nb(|| channel.wait_close()).map_err(|e| AppError::Ssh(format!("Wait close: {e}")))?;
+    Ok(channel.exit_status().ok())
+}
+
+/// Open an interactive PTY as `username` running `command`, or a login shell
+/// when `None`, and wire it to our stdin/stdout until the remote side exits.
+///
+/// The caller puts the local terminal in raw mode. `window` reports the local
+/// terminal size as `(columns, rows)` and is polled so resizes reach the PTY.
+pub fn shell_as(
+    ip: &str,
+    private_key_path: &Path,
+    username: &str,
+    command: Option<&str>,
+    term: &str,
+    window: &dyn Fn() -> (u32, u32),
+) -> Result<i32, AppError> {
+    let sess = connect_as(ip, private_key_path, username)?;
+    let mut channel = sess
+        .channel_session()
+        .map_err(|e| AppError::Ssh(format!("Open channel: {e}")))?;
+    let mut size = window();
+    channel
+        .request_pty(term, None, Some((size.0, size.1, 0, 0)))
+        .map_err(|e| AppError::Ssh(format!("Request PTY: {e}")))?;
+    match command {
+        Some(command) => channel.exec(command),
+        None => channel.shell(),
+    }
+    .map_err(|e| AppError::Ssh(format!("Start shell: {e}")))?;
+    sess.set_blocking(false);
+
+    // Stdin has no portable non-blocking read, so a thread feeds it in.
+    let (tx, rx) = std::sync::mpsc::channel::<Vec<u8>>();
+    std::thread::spawn(move || {
+        let mut stdin = std::io::stdin();
+        let mut buf = [0u8; 1024];
+        while let Ok(n @ 1..) = stdin.read(&mut buf) {
+            if tx.send(buf[..n].to_vec()).is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut stdout = std::io::stdout();
+    let mut buf = [0u8; 16 * 1024];
+    let mut stdin_open = true;
+    loop {
+        let mut busy = pump_nb(&mut channel, &mut stdout, &mut buf)
+            .map_err(|e| AppError::Ssh(format!("Read output: {e}")))?;
+        if channel.eof() {
+            break;
+        }
+        while stdin_open {
+            match rx.try_recv() {
+                Ok(data) => {
+                    write_all_nb(&mut channel, &data)
+                        .map_err(|e| AppError::Ssh(format!("Write stdin: {e}")))?;
+                    busy = true;
+                }
+                Err(std::sync::mpsc::TryRecvError::Empty) => break,
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                    stdin_open = false;
+                    let _ = retry_nb(|| channel.send_eof());
+                }
+            }
+        }
+        let now = window();
+        if now != size {
+            size = now;
+            let _ = retry_nb(|| channel.request_pty_size(size.0, size.1, None, None));
+        }
+        if !busy {
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+    retry_nb(|| channel.wait_close()).map_err(|e| AppError::Ssh(format!("Wait close: {e}")))?;
+    Ok(channel.exit_status().unwrap_or(-1))
+}
+
+/// Forward connections accepted on `listener` to `remote_host:remote_port` as
+/// seen from the instance, through `direct-tcpip` channels of a single SSH
+/// session. Runs until the session fails.
+pub fn forward_local_as(
+    ip: &str,
+    private_key_path: &Path,
+    username: &str,
+    listener: std::net::TcpListener,
+    remote_host: &str,
+    remote_port: u16,
+    on_connection: &(dyn Fn(std::net::SocketAddr) + Sync),
+) -> Result<(), AppError> {
+    let sess = connect_as(ip, private_key_path, username)?;
+    sess.set_keepalive(true, 30);
+    sess.set_blocking(false);
+
+    std::thread::scope(|scope| {
+        for stream in listener.incoming() {
+            let stream = stream.map_err(|e| AppError::Ssh(format!("Accept: {e}")))?;
+            let peer = stream
+                .peer_addr()
+                .unwrap_or_else(|_| std::net::SocketAddr::from(([127, 0, 0, 1], 0)));
+            let channel = retry_nb(|| sess.channel_direct_tcpip(remote_host, remote_port, None));
+            match channel {
+                Ok(channel) => {
+                    on_connection(peer);
+                    scope.spawn(move || {
+                        if let Err(e) = proxy_nb(stream, channel) {
+                            eprintln!("Tunnel connection from {peer} closed: {e}");
+                        }
+                    });
+                }
+                Err(e) => {
+                    eprintln!("Could not open {remote_host}:{remote_port} on {ip}: {e}");
+                    // A dead session fails every later channel too.
+                    if retry_nb(|| sess.keepalive_send()).is_err() {
+                        return Err(AppError::Ssh(format!("SSH session to {ip} lost: {e}")));
+                    }
+                }
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Copy bytes both ways between a local socket and a non-blocking channel
+/// until either side closes.
+fn proxy_nb(mut stream: TcpStream, mut channel: ssh2::Channel) -> std::io::Result<()> {
+    stream.set_nonblocking(true)?;
+    let mut buf = [0u8; 16 * 1024];
+    let mut local_open = true;
+    loop {
+        let mut busy = false;
+        if local_open {
+            match stream.read(&mut buf) {
+                Ok(0) => {
+                    local_open = false;
+                    let _ = retry_nb(|| channel.send_eof());
+                }
+                Ok(n) => {
+                    write_all_nb(&mut channel, &buf[..n])?;
+                    busy = true;
+                }
+                Err(e) if would_block(&e) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        match channel.read(&mut buf) {
+            Ok(0) => {}
+            Ok(n) => {
+                write_all_nb(&mut stream, &buf[..n])?;
+                busy = true;
+            }
+            Err(e) if would_block(&e) => {}
+            Err(e) => return Err(e),
+        }
+        if channel.eof() {
+            break;
+        }
+        if !busy {
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+    let _ = retry_nb(|| channel.close());
+    let _ = stream.shutdown(std::net::Shutdown::Both);
+    Ok(())
+}
+
 /// Upload a local file to the remote host via SCP as a specific user.
 pub fn scp_upload_as(
     ip: &str,
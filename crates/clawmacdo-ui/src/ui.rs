@@ -1,3 +1,4 @@
+use clawmacdo_core::backup_store;
 use clawmacdo_core::config::{self, DeployRecord, OPENCLAW_GATEWAY_PORT};
 use console::style;
 use dialoguer::{Input, Select};
@@ -96,24 +97,11 @@ pub fn prompt_hostname(deploy_id: &str) -> Result<String, anyhow::Error> {
     Ok(hostname)
 }
 
-/// Prompt the user to select a backup archive, or None.
-/// PPrompt backup.
-pub fn prompt_backup() -> Result<Option<PathBuf>, anyhow::Error> {
-    let backups_dir = config::backups_dir()?;
-    let mut entries: Vec<PathBuf> = Vec::new();
-
-    if backups_dir.exists() {
-        for entry in std::fs::read_dir(&backups_dir)? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.extension().and_then(|e| e.to_str()) == Some("gz") {
-                entries.push(path);
-            }
-        }
-    }
-
-    entries.sort();
-    entries.reverse(); // newest first
+/// Prompt the user to select a backup archive from the configured backup store, or None.
+/// Archives in remote stores are downloaded to the local cache before returning.
+pub async fn prompt_backup() -> Result<Option<PathBuf>, anyhow::Error> {
+    let store = backup_store::configured_store()?;
+    let entries = store.list().await?;
 
     if entries.is_empty() {
         println!(
@@ -125,22 +113,18 @@ pub fn prompt_backup() -> Result<Option<PathBuf>, anyhow::Error> {
 
     let mut labels: Vec<String> = entries
         .iter()
-        .map(|p| {
-            let name = p
-                .file_name()
-                .unwrap_or_default()
-                .to_string_lossy()
-                .to_string();
-            let size = std::fs::metadata(p)
-                .map(|m| format_bytes(m.len()))
-                .unwrap_or_else(|_| "?".into());
-            format!("{name}  ({size})")
+        .map(|b| {
+            let lock = if b.encrypted { "  [encrypted]" } else { "" };
+            format!("{}  ({}){lock}", b.name, format_bytes(b.size))
         })
         .collect();
     labels.push("(No backup)".into());
 
     let selection = Select::new()
-        .with_prompt("Select backup to restore")
+        .with_prompt(format!(
+            "Select backup to restore from {}",
+            store.describe()
+        ))
         .items(&labels)
         .default(0)
         .interact()?;
@@ -148,7 +132,7 @@ pub fn prompt_backup() -> Result<Option<PathBuf>, anyhow::Error> {
     if selection == entries.len() {
         Ok(None)
     } else {
-        Ok(Some(entries[selection].clone()))
+        Ok(Some(store.fetch(&entries[selection].name).await?))
     }
 }
 
@@ -166,6 +150,9 @@ pub fn print_summary(record: &DeployRecord) {
     println!("  Droplet ID:        {}", record.droplet_id);
     println!("  Hostname:          {}", record.hostname);
     println!("  IP Address:        {ip}");
+    if let Some(fqdn) = &record.fqdn {
+        println!("  DNS Name:          {fqdn}");
+    }
     println!("  Region:            {}", record.region);
     println!("  Size:              {}", record.size);
     println!();
@@ -199,7 +186,7 @@ pub fn print_summary(record: &DeployRecord) {
     println!("  Next steps:");
     println!("    1. ssh -i {key} root@{ip}");
     println!("    2. curl http://{ip}:{port}/health");
-    println!("    3. ssh -i {key} root@{ip} 'XDG_RUNTIME_DIR=/run/user/0 DBUS_SESSION_BUS_ADDRESS=unix:path=/run/user/0/bus journalctl --user -u openclaw-gateway.service -f'");
+    println!("    3. clawmacdo logs {} --follow", record.hostname);
     println!("{divider}\n");
 }
 